@@ -0,0 +1,29 @@
+// Native throughput of the Poseidon and Rescue-Prime hash functions over BLS12-381, in hashes per second, for inputs of
+// one to four sponge blocks. Rescue-Prime's rounds each raise the state to 1/alpha, a full-width exponentiation, so it
+// is expected to hash far slower natively than Poseidon, whose partial rounds apply a single x^5.
+//
+//   cargo bench --bench hash
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use halo2curves::bls12381::Fr;
+
+use permutation_benchmark::hash::Hasher;
+
+fn native_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("native_hash");
+    group.throughput(Throughput::Elements(1));
+    for hasher in [Hasher::<Fr>::poseidon(), Hasher::<Fr>::rescue()] {
+        let hasher = hasher.expect("the preset parameters load");
+        // 1, 3 and 7 inputs pad to one, two and four blocks of the rate
+        for len in [1, 3, 7] {
+            let inputs: Vec<Fr> = (0..len).map(Fr::from).collect();
+            group.bench_with_input(BenchmarkId::new(hasher.name(), len), &inputs, |b, inputs| {
+                b.iter(|| hasher.hash(std::hint::black_box(inputs)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, native_hash);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+// Embeds the constant tables of the presets as limbs: every table in src/constants.rs that a preset names is parsed
+// here, once per build, into the canonical value of each word as four little-endian u64 limbs, and written to
+// $OUT_DIR/embedded_constants.rs, which constants.rs includes. The field is not known here; constants.rs only reads the
+// limbs in a field whose modulus is the preset's, and checks a sample against the strings in debug builds.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use num_bigint::BigUint;
+
+// the round constant tables and MDS matrices of the presets, by the name of their const in src/constants.rs
+const ROUND_CONSTANT_TABLES: [&str; 4] = ["ROUND_CONSTANTS_PS", "ROUND_CONSTANTS_RS", "ROUND_CONSTANTS_BN254_CIRCOM", "ROUND_CONSTANTS_PS_NEPTUNE"];
+const MDS_TABLES: [&str; 4] = ["MDS_PS", "MDS_RS", "MDS_BN254_CIRCOM", "MDS_PS_NEPTUNE"];
+
+// helper function for the quoted words of the const `name`, from its `=` to the `];` that closes it
+fn table_words<'a>(source: &'a str, name: &str) -> Vec<&'a str> {
+    let start = source.find(&format!("const {}:", name)).unwrap_or_else(|| panic!("src/constants.rs has no table {}", name));
+    let body = &source[start..];
+    let body = &body[body.find('=').expect("a table has a value")..];
+    let body = &body[..body.find("];").expect("a table ends with ];")];
+    body.split('"').skip(1).step_by(2).collect()
+}
+
+// helper function for a decimal or 0x-prefixed hex word as four little-endian limbs
+fn limbs(word: &str) -> [u64; 4] {
+    let value = match word.strip_prefix("0x") {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(word.as_bytes(), 10)
+    }.unwrap_or_else(|| panic!("{:?} is not an integer", word));
+    let digits = value.to_u64_digits();
+    assert!(digits.len() <= 4, "{} does not fit in 256 bits", word);
+    std::array::from_fn(|i| digits.get(i).copied().unwrap_or(0))
+}
+
+// helper function for a list of limbs as a Rust array expression
+fn limbs_array(words: &[&str]) -> String {
+    let rows: Vec<String> = words.iter().map(|word| format!("    {:?},\n", limbs(word))).collect();
+    format!("[\n{}]", rows.concat())
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/constants.rs");
+    let source = fs::read_to_string("src/constants.rs").expect("src/constants.rs is readable");
+
+    let mut out = String::from("// generated by build.rs from the tables in src/constants.rs; canonical values as little-endian u64 limbs\n");
+    for name in ROUND_CONSTANT_TABLES {
+        let words = table_words(&source, name);
+        out += &format!("pub(crate) const {}_LIMBS: [[u64; 4]; {}] = {};\n", name, words.len(), limbs_array(&words));
+    }
+    for name in MDS_TABLES {
+        let words = table_words(&source, name);
+        assert_eq!(words.len(), 9, "{} is not a 3 x 3 matrix", name);
+        out += &format!("pub(crate) const {}_LIMBS: [[u64; 4]; 9] = {};\n", name, limbs_array(&words));
+    }
+
+    let path = Path::new(&env::var("OUT_DIR").expect("cargo sets OUT_DIR")).join("embedded_constants.rs");
+    fs::write(&path, out).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+}
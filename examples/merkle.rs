@@ -0,0 +1,135 @@
+// Verify a depth-8 Merkle path over Poseidon: the leaf, its siblings and the path bits are private, the root is public.
+//
+//   cargo run --release --example merkle -- [leaf index, 0..256]
+//
+// Each level orders (current, sibling) with the chip's conditional swap and compresses the pair with one permutation
+// whose capacity is the chip's domain tag, as PermutationInstructions::hash does for witnessed inputs.
+
+use std::time::Instant;
+use ff::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    pasta::{EqAffine, Fp},
+    plonk::{Circuit, ConstraintSystem, Error, SingleVerifier, create_proof, keygen_pk, keygen_vk, verify_proof},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use permutation_benchmark::{Digest, PermutationChip, PermutationInstructions, PoseidonChip, PoseidonChipConfig, PublicInputs, StateWord, UtilsInstructions};
+
+const DEPTH: usize = 8;
+
+// private leaf and path, public root; a set bit means the current node is the right child
+#[derive(Clone)]
+struct MerkleCircuit {
+    leaf: Value<Fp>,
+    siblings: [Value<Fp>; DEPTH],
+    bits: [Value<Fp>; DEPTH]
+}
+
+impl Circuit<Fp> for MerkleCircuit {
+    type Config = PoseidonChipConfig<Fp>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        MerkleCircuit { leaf: Value::unknown(), siblings: [Value::unknown(); DEPTH], bits: [Value::unknown(); DEPTH] }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        PoseidonChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let chip = PoseidonChip::from_config(config);
+        let tag = StateWord::Constant(chip.params().domain_tag());
+        let mut node = chip.load_private(layouter.namespace(|| "leaf"), self.leaf)?;
+        for (level, (sibling, bit)) in self.siblings.iter().zip(&self.bits).enumerate() {
+            let sibling = chip.load_private(layouter.namespace(|| format!("sibling_{}", level)), *sibling)?;
+            let [left, right] = chip.cond_swap(layouter.namespace(|| format!("swap_{}", level)), *bit, &node, &sibling)?;
+            let [parent, _, _] = chip.permute_state(
+                layouter.namespace(|| format!("compress_{}", level)),
+                [StateWord::Copy(&left), StateWord::Copy(&right), tag]
+            )?;
+            node = parent;
+        }
+
+        chip.expose_as_public(layouter.namespace(|| "root"), node, 0)
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let index: usize = std::env::args().nth(1).map_or(Ok(37), |index| index.parse())?;
+    prove_path(index)
+}
+
+// build the tree, check the path of leaf `index` against its root and a wrong root in MockProver, then prove it
+fn prove_path(index: usize) -> Result<(), Box<dyn std::error::Error>> {
+    if index >= 1 << DEPTH {
+        return Err(format!("the tree has {} leaves", 1 << DEPTH).into());
+    }
+
+    // the tree over the leaves 0, 1, .., 255, hashed natively with the chip's parameters
+    let chip = PoseidonChip::from_config(PoseidonChip::configure_default(&mut ConstraintSystem::<Fp>::default()));
+    let native = chip.params();
+    let start = Instant::now();
+    let mut levels: Vec<Vec<Fp>> = vec![(0..1u64 << DEPTH).map(Fp::from).collect()];
+    while levels.last().unwrap().len() > 1 {
+        let parents = levels.last().unwrap().chunks(2).map(|pair| native.hash_native([pair[0], pair[1]])).collect();
+        levels.push(parents);
+    }
+    let root = Digest([levels[DEPTH][0]]);
+    println!("native tree of {} leaves: {} ms, root {}", 1 << DEPTH, start.elapsed().as_millis(), root);
+
+    let siblings: [Fp; DEPTH] = std::array::from_fn(|level| levels[level][(index >> level) ^ 1]);
+    let bits: [Fp; DEPTH] = std::array::from_fn(|level| Fp::from(((index >> level) & 1) as u64));
+    let circuit = MerkleCircuit {
+        leaf: Value::known(levels[0][index]),
+        siblings: siblings.map(Value::known),
+        bits: bits.map(Value::known)
+    };
+
+    // per level a sibling row, two select regions of two rows and a compression, plus a margin for halo2's reserved
+    // (blinding) rows; MockProver accepts the root and rejects any other
+    let k = (1 + DEPTH * (1 + 4 + chip.compression_rows()) + 16).next_power_of_two().trailing_zeros();
+    let public_inputs = PublicInputs::digest_only(root);
+    MockProver::run(k, &circuit, public_inputs.to_instances())?.verify().map_err(|failures| format!("{:?}", failures))?;
+    let wrong = PublicInputs::digest_only(Digest([root.0[0] + Fp::ONE]));
+    assert!(MockProver::run(k, &circuit, wrong.into())?.verify().is_err());
+    println!("path of leaf {} verifies in MockProver", index);
+
+    let params = Params::<EqAffine>::new(k);
+    let start = Instant::now();
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+    println!("keygen at k = {}: {} ms", k, start.elapsed().as_millis());
+
+    let start = Instant::now();
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&public_inputs.as_slices()[..]], OsRng, &mut transcript)?;
+    let proof = transcript.finalize();
+    println!("proving: {} ms, {} bytes", start.elapsed().as_millis(), proof.len());
+
+    let start = Instant::now();
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+    verify_proof(&params, pk.get_vk(), SingleVerifier::new(&params), &[&public_inputs.as_slices()[..]], &mut transcript)?;
+    println!("verification: {} ms", start.elapsed().as_millis());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the paths of the first and last leaf verify and prove, with the wrong root rejected, and a leaf outside the tree
+    // is refused
+    #[test]
+    fn merkle_paths() -> Result<(), Box<dyn std::error::Error>> {
+        prove_path(0)?;
+        prove_path((1 << DEPTH) - 1)?;
+        assert!(prove_path(1 << DEPTH).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+// Mock-prove the Poseidon permutation circuit in the smallest build of the crate: the Poseidon chip and nothing else.
+// This is the build the feature matrix in scripts/check-features.sh checks first.
+//
+//   cargo run --release --no-default-features --features poseidon --example poseidon_only -- [s0 s1 s2]
+
+use ff::Field;
+use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
+
+use permutation_benchmark::prelude::*;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let words: Vec<u64> = std::env::args().skip(1).map(|word| word.parse()).collect::<Result<_, _>>()?;
+    let inputs = match words[..] {
+        [] => [0, 1, 2].map(Fp::from),
+        [s0, s1, s2] => [s0, s1, s2].map(Fp::from),
+        _ => return Err("expected three input words".into())
+    };
+    mock_prove(inputs)
+}
+
+// mock-prove the permutation of `inputs` against its digest, and check that a wrong digest is rejected
+fn mock_prove(inputs: [Fp; 3]) -> Result<(), Box<dyn std::error::Error>> {
+    // 131 rows of permutation and halo2's reserved rows fit in 2^8
+    let k = 8;
+    let circuit = PoseidonCircuit::new(inputs.map(Value::known), OutputMode::DigestOnly)?;
+    let digest: Digest<Fp> = Digest::from_state(&poseidon_params::<Fp>()?.permute_native(inputs))?;
+    MockProver::run(k, &circuit, PublicInputs::digest_only(digest).to_instances())?.verify().map_err(|failures| format!("{:?}", failures))?;
+    let wrong = PublicInputs::digest_only(Digest([digest.0[0] + Fp::ONE]));
+    assert!(MockProver::run(k, &circuit, wrong.into())?.verify().is_err(), "a wrong digest verifies");
+    println!("Poseidon verifies in MockProver at k = {}, digest {}", k, digest);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poseidon_only() -> Result<(), Box<dyn std::error::Error>> {
+        mock_prove([0, 1, 2].map(Fp::from))
+    }
+}
@@ -0,0 +1,95 @@
+// Prove knowledge of a Poseidon preimage: the three input words are private, the digest state[0] is public.
+//
+//   cargo run --release --example preimage -- [s0 s1 s2]
+//
+// The chip is embedded in a circuit of its own and proved with IPA over Pasta, the only commitment scheme halo2_proofs
+// 0.3 implements (KZG needs the PSE fork).
+
+use std::time::Instant;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::{EqAffine, Fp},
+    plonk::{Circuit, ConstraintSystem, Error, SingleVerifier, create_proof, keygen_pk, keygen_vk, verify_proof},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use permutation_benchmark::{Digest, PermutationChip, PermutationInstructions, PoseidonChip, PoseidonChipConfig, PublicInputs};
+
+// private preimage, public digest
+#[derive(Default)]
+struct PreimageCircuit {
+    inputs: [Value<Fp>; 3]
+}
+
+impl Circuit<Fp> for PreimageCircuit {
+    type Config = PoseidonChipConfig<Fp>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        PoseidonChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let chip = PoseidonChip::from_config(config);
+        let [s0, s1, s2] = self.inputs;
+        let [digest, _, _] = chip.permute(layouter.namespace(|| "permutation"), s0, s1, s2)?;
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let words: Vec<u64> = std::env::args().skip(1).map(|word| word.parse()).collect::<Result<_, _>>()?;
+    let inputs: [Fp; 3] = match words[..] {
+        [] => [0, 1, 2].map(Fp::from),
+        [s0, s1, s2] => [s0, s1, s2].map(Fp::from),
+        _ => return Err(format!("expected three input words, found {}", words.len()).into())
+    };
+    prove(inputs)
+}
+
+// prove knowledge of `inputs` for their digest, and verify the proof
+fn prove(inputs: [Fp; 3]) -> Result<(), Box<dyn std::error::Error>> {
+    // the digest comes from the native permutation with the chip's parameters
+    let chip = PoseidonChip::from_config(PoseidonChip::configure_default(&mut ConstraintSystem::<Fp>::default()));
+    let digest = Digest([chip.params().permute_native(inputs)[0]]);
+    println!("digest: {}", digest);
+    let public_inputs = PublicInputs::digest_only(digest);
+
+    let k = PoseidonChip::<Fp>::min_k(1);
+    let params = Params::<EqAffine>::new(k);
+    let circuit = PreimageCircuit { inputs: inputs.map(Value::known) };
+
+    let start = Instant::now();
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+    println!("keygen at k = {}: {} ms", k, start.elapsed().as_millis());
+
+    let start = Instant::now();
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&public_inputs.as_slices()[..]], OsRng, &mut transcript)?;
+    let proof = transcript.finalize();
+    println!("proving: {} ms, {} bytes", start.elapsed().as_millis(), proof.len());
+
+    let start = Instant::now();
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+    verify_proof(&params, pk.get_vk(), SingleVerifier::new(&params), &[&public_inputs.as_slices()[..]], &mut transcript)?;
+    println!("verification: {} ms", start.elapsed().as_millis());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preimage() -> Result<(), Box<dyn std::error::Error>> {
+        prove([0, 1, 2].map(Fp::from))
+    }
+}
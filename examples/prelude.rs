@@ -0,0 +1,64 @@
+// Mock-prove the Poseidon and Rescue-Prime permutation circuits through the prelude alone: this crate is imported only
+// as `permutation_benchmark::prelude::*`, so the example stops compiling if the prelude loses anything a downstream
+// user needs for it.
+//
+//   cargo run --release --example prelude -- [s0 s1 s2]
+
+use ff::Field;
+use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+use permutation_benchmark::prelude::*;
+
+// helper function to check a circuit against the native permutation's outputs, and a wrong output against it
+fn mock_prove(label: &str, circuit: &impl Circuit<Fp>, public_inputs: &PublicInputs<Fp>) -> Result<(), Box<dyn std::error::Error>> {
+    let k = 10;
+    MockProver::run(k, circuit, public_inputs.to_instances())?.verify().map_err(|failures| format!("{}: {:?}", label, failures))?;
+    let mut wrong = public_inputs.to_instances();
+    wrong[0][0] += Fp::ONE;
+    assert!(MockProver::run(k, circuit, wrong)?.verify().is_err(), "{} accepts a wrong output", label);
+    println!("{} verifies in MockProver at k = {}", label, k);
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let words: Vec<u64> = std::env::args().skip(1).map(|word| word.parse()).collect::<Result<_, _>>()?;
+    let inputs = match words[..] {
+        [] => [0, 1, 2].map(Fp::from),
+        [s0, s1, s2] => [s0, s1, s2].map(Fp::from),
+        _ => return Err("expected three input words".into())
+    };
+    mock_prove_both(inputs)
+}
+
+// mock-prove both permutation circuits on `inputs` in each output mode, then read the digest and try a builder
+fn mock_prove_both(inputs: [Fp; 3]) -> Result<(), Box<dyn std::error::Error>> {
+    for output_mode in [OutputMode::AllState, OutputMode::DigestOnly] {
+        let public_inputs = expected_instances(PermutationKind::Poseidon, inputs, output_mode)?;
+        assert_eq!(public_inputs.column(0)[0], poseidon_params::<Fp>()?.permute_native(inputs)[0]);
+        mock_prove("Poseidon", &PoseidonCircuit::new(inputs.map(Value::known), output_mode)?, &public_inputs)?;
+
+        let public_inputs = expected_instances(PermutationKind::Rescue, inputs, output_mode)?;
+        assert_eq!(public_inputs.column(0)[0], rescue_params::<Fp>()?.permute_native(inputs)[0]);
+        mock_prove("Rescue-Prime", &RescueCircuit::new(inputs.map(Value::known), output_mode)?, &public_inputs)?;
+    }
+
+    // the digest-only instance reads back as a single word, and prints as hex
+    let digest: Digest<Fp> = expected_instances(PermutationKind::Poseidon, inputs, OutputMode::DigestOnly)?.digest()?;
+    println!("Poseidon digest: {}", digest);
+
+    // a builder reports the parameter it is missing
+    let missing = Poseidon::<Fp>::builder().alpha(5).partial_rounds(57).build();
+    assert!(matches!(missing, Err(BenchmarkError::MissingParameter("full_rounds"))));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prelude() -> Result<(), Box<dyn std::error::Error>> {
+        mock_prove_both([0, 1, 2].map(Fp::from))
+    }
+}
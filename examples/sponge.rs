@@ -0,0 +1,109 @@
+// Hash a byte message through the sponge over Rescue-Prime: the message is packed into field elements (its length,
+// then 31 bytes per element), the packed chunks are private and the digest is public.
+//
+//   cargo run --release --example sponge -- [message]
+
+use std::marker::PhantomData;
+use std::time::Instant;
+use ff::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    pasta::{EqAffine, Fp},
+    plonk::{Circuit, ConstraintSystem, Error, SingleVerifier, create_proof, keygen_pk, keygen_vk, verify_proof},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use permutation_benchmark::{
+    Digest, PermutationChip, PermutationInstructions, RescueChip, RescueChipConfig, Sponge, SpongeConfig, StateWord, configure_sponge, hash_bytes_native,
+    pack_bytes, PublicInputs
+};
+
+// a message of `length` bytes as its packed chunks; the length is a constant of the circuit
+struct MessageCircuit {
+    length: usize,
+    chunks: Vec<Value<Fp>>,
+    _marker: PhantomData<RescueChip<Fp>>
+}
+
+impl Circuit<Fp> for MessageCircuit {
+    type Config = SpongeConfig<RescueChipConfig<Fp>>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        MessageCircuit { length: self.length, chunks: vec![Value::unknown(); self.chunks.len()], _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        configure_sponge::<Fp, RescueChip<Fp>>(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let chip = RescueChip::from_config(config.chip.clone());
+        let packed: Vec<StateWord<'_, Fp>> = std::iter::once(StateWord::Constant(Fp::from(self.length as u64)))
+            .chain(self.chunks.iter().copied().map(StateWord::Witness))
+            .collect();
+        let digest = Sponge::new().hash(&chip, &config, layouter.namespace(|| "sponge"), &packed)?;
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let message = std::env::args().nth(1).unwrap_or_else(|| "the quick brown fox jumps over the lazy dog".to_string());
+    prove_message(&message)
+}
+
+// check the digest of `message` and a wrong digest in MockProver, then prove it
+fn prove_message(message: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let chip = RescueChip::from_config(RescueChip::configure_default(&mut ConstraintSystem::<Fp>::default()));
+    let digest = Digest([hash_bytes_native(chip.params(), message.as_bytes())]);
+    let packed: Vec<Fp> = pack_bytes(message.as_bytes());
+    println!("{}-byte message, {} packed elements, digest {}", message.len(), packed.len(), digest);
+
+    let circuit = MessageCircuit {
+        length: message.len(),
+        chunks: packed[1..].iter().copied().map(Value::known).collect(),
+        _marker: PhantomData
+    };
+
+    // the sponge's rows, plus a margin for halo2's reserved (blinding) rows
+    let k = (chip.absorb_rows(packed.len()) + 16).next_power_of_two().trailing_zeros();
+    let public_inputs = PublicInputs::digest_only(digest);
+    MockProver::run(k, &circuit, public_inputs.to_instances())?.verify().map_err(|failures| format!("{:?}", failures))?;
+    let wrong = PublicInputs::digest_only(Digest([digest.0[0] + Fp::ONE]));
+    assert!(MockProver::run(k, &circuit, wrong.into())?.verify().is_err());
+    println!("digest verifies in MockProver at k = {}", k);
+
+    let params = Params::<EqAffine>::new(k);
+    let start = Instant::now();
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+    println!("keygen: {} ms", start.elapsed().as_millis());
+
+    let start = Instant::now();
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&public_inputs.as_slices()[..]], OsRng, &mut transcript)?;
+    let proof = transcript.finalize();
+    println!("proving: {} ms, {} bytes", start.elapsed().as_millis(), proof.len());
+
+    let start = Instant::now();
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+    verify_proof(&params, pk.get_vk(), SingleVerifier::new(&params), &[&public_inputs.as_slices()[..]], &mut transcript)?;
+    println!("verification: {} ms", start.elapsed().as_millis());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the empty message and one of more than a rate of packed elements
+    #[test]
+    fn sponge_messages() -> Result<(), Box<dyn std::error::Error>> {
+        prove_message("")?;
+        prove_message("the quick brown fox jumps over the lazy dog")
+    }
+}
@@ -0,0 +1,15 @@
+#!/bin/sh
+# Feature matrix: build the library under each feature set on its own, with clippy's warnings as errors, then for
+# wasm32 with both chips and no threads, tests included, and mock-prove the Poseidon circuit in the smallest build.
+# Run from the repository root; the wasm32 check needs `rustup target add wasm32-unknown-unknown`.
+set -eu
+
+for features in poseidon rescue poseidon,sponge rescue,sponge poseidon,merkle rescue,merkle poseidon,rescue,sponge,merkle; do
+    echo "== --no-default-features --features $features"
+    cargo clippy -q --no-default-features --features "$features" --lib --examples -- -D warnings
+done
+
+echo "== --target wasm32-unknown-unknown --no-default-features --features poseidon,rescue"
+cargo clippy -q --target wasm32-unknown-unknown --no-default-features --features poseidon,rescue --lib --tests -- -D warnings
+
+cargo run -q --release --no-default-features --features poseidon --example poseidon_only
@@ -0,0 +1,267 @@
+use std::path::{Path, PathBuf};
+use ff::Field;
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{Error, VerificationStrategy, VerifyingKey, keygen_vk, verify_proof},
+    poly::commitment::{Guard, MSM, Params},
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge},
+};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::circuits::{OutputMode, PoseidonCircuit, RescueCircuit};
+use crate::clock::Instant;
+use crate::error::BenchmarkError;
+use crate::params::{Endianness, fe_from_hex};
+use crate::proving::{ProvingRng, verify_ipa_proof};
+use crate::report::{BatchVerifyReport, BatchedProofReport, hex_words};
+use crate::source::active_source;
+use crate::srs::commitment_params;
+use crate::target::PermutationKind;
+use crate::transcript::TranscriptKind;
+
+// Batched verification of real IPA proofs over Pasta. Verifying an IPA proof ends in one multi-scalar multiplication
+// over the commitment parameters' generators, which dominates the verifier's time. Proofs against the same parameters
+// (same k) can leave that MSM unevaluated, and the MSMs are combined under random factors and evaluated once. halo2's
+// BatchVerifier does this for proofs under one verifying key; the combination does not depend on the key, so here the
+// Poseidon and Rescue-Prime proofs go into one batch. A batch only says whether all its proofs verify, so a rejected
+// batch is verified proof by proof to name the proofs that fail.
+
+// verification strategy that hands back a proof's final MSM instead of evaluating it, like BatchVerifier's own
+struct AccumulatingStrategy<'params> {
+    msm: MSM<'params, EqAffine>
+}
+
+impl<'params> VerificationStrategy<'params, EqAffine> for AccumulatingStrategy<'params> {
+    type Output = MSM<'params, EqAffine>;
+
+    fn process<E: EncodedChallenge<EqAffine>>(
+        self,
+        f: impl FnOnce(MSM<'params, EqAffine>) -> Result<Guard<'params, EqAffine, E>, Error>
+    ) -> Result<Self::Output, Error> {
+        Ok(f(self.msm)?.use_challenges())
+    }
+}
+
+// one proof of a batch, with its verifying key and public inputs; proofs use the Blake2b transcript
+pub(crate) struct BatchItem<'a> {
+    pub(crate) vk: &'a VerifyingKey<EqAffine>,
+    pub(crate) instance: &'a [Fp],
+    pub(crate) proof: &'a [u8]
+}
+
+// verify proofs against the same parameters with one MSM evaluation, returning the indices of the proofs that fail
+// (none if the batch verifies). Each MSM is scaled by a fresh random factor before it is added, so that one invalid
+// proof cannot cancel out against the others
+pub(crate) fn verify_batch(params: &Params<EqAffine>, items: &[BatchItem<'_>]) -> Vec<usize> {
+    let mut accumulated = params.empty_msm();
+    let mut read = true;
+    for item in items {
+        let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(item.proof);
+        let strategy = AccumulatingStrategy { msm: params.empty_msm() };
+        match verify_proof(params, item.vk, strategy, &[&[item.instance]], &mut transcript) {
+            Ok(msm) => {
+                accumulated.scale(Fp::random(OsRng));
+                accumulated.add_msm(&msm);
+            }
+            Err(_) => read = false
+        }
+    }
+    if read && accumulated.eval() {
+        return Vec::new();
+    }
+
+    items.iter().enumerate()
+        .filter(|(_, item)| verify_ipa_proof(params, item.vk, item.proof, &[item.instance], TranscriptKind::Blake2b).is_err())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// proof file read by verify-batch: a permutation circuit's proof with its public outputs, and the k and parameter
+// source the verifying key is generated for. Field elements are 0x-prefixed big-endian hex, the proof is hex
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ProofFile {
+    pub(crate) permutation: PermutationKind,
+    pub(crate) output_mode: OutputMode,
+    pub(crate) k: u32,
+    pub(crate) parameters: String,
+    pub(crate) instance: Vec<String>,
+    pub(crate) proof: String
+}
+
+impl ProofFile {
+    fn read(path: &Path) -> Result<Self, BenchmarkError> {
+        let text = std::fs::read_to_string(path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+        serde_json::from_str(&text).map_err(|e| BenchmarkError::InvalidProofFile { path: path.display().to_string(), reason: e.to_string() })
+    }
+
+    fn write(&self, path: &Path) -> Result<(), BenchmarkError> {
+        let text = serde_json::to_string_pretty(self).unwrap() + "\n";
+        std::fs::write(path, text).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })
+    }
+
+    fn proof_bytes(&self, path: &Path) -> Result<Vec<u8>, BenchmarkError> {
+        let invalid = |reason: &str| BenchmarkError::InvalidProofFile { path: path.display().to_string(), reason: reason.to_string() };
+        let digits = self.proof.strip_prefix("0x").unwrap_or(&self.proof);
+        if !digits.len().is_multiple_of(2) {
+            return Err(invalid("the proof has an odd number of hex digits"));
+        }
+        (0..digits.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid("the proof is not hex")))
+            .collect()
+    }
+}
+
+// helper function for the verifying key of a permutation circuit, which only depends on the permutation, the output
+// mode, k and the active parameters
+fn permutation_vk(params: &Params<EqAffine>, permutation: PermutationKind, output_mode: OutputMode) -> Result<VerifyingKey<EqAffine>, BenchmarkError> {
+    let vk = match permutation {
+        PermutationKind::Poseidon => keygen_vk(params, &PoseidonCircuit { output_mode, ..PoseidonCircuit::default() })?,
+        PermutationKind::Rescue => keygen_vk(params, &RescueCircuit { output_mode, ..RescueCircuit::default() })?
+    };
+    Ok(vk)
+}
+
+// helper function to prove a permutation circuit with all output words public on `inputs`, returning the proof and
+// the public outputs
+fn prove_permutation(
+    params: &Params<EqAffine>,
+    permutation: PermutationKind,
+    inputs: [Fp; 3],
+    mut rng: impl RngCore
+) -> Result<(Vec<u8>, Vec<Fp>), BenchmarkError> {
+    let (proof, instances) = permutation.target().prove(params, inputs, OutputMode::AllState, &mut rng)?;
+    Ok((proof, instances.column(0).to_vec()))
+}
+
+// write one proof file per path, alternating Poseidon and Rescue-Prime proofs on the inputs (3i, 3i + 1, 3i + 2)
+pub fn write_proofs(paths: &[PathBuf], k: u32, seed: Option<u64>) -> Result<(), BenchmarkError> {
+    let params = commitment_params(k)?;
+    let mut rng = ProvingRng::new(seed);
+    for (index, path) in paths.iter().enumerate() {
+        let permutation = if index.is_multiple_of(2) { PermutationKind::Poseidon } else { PermutationKind::Rescue };
+        let start = 3 * index as u64;
+        let (proof, instance) = prove_permutation(&params, permutation, [start, start + 1, start + 2].map(Fp::from), &mut rng)?;
+        ProofFile {
+            permutation,
+            output_mode: OutputMode::AllState,
+            k,
+            parameters: active_source().to_string(),
+            instance: hex_words(&instance),
+            proof: proof.iter().map(|byte| format!("{:02x}", byte)).collect()
+        }.write(path)?;
+        println!("{:?} proof written to {}", permutation, path.display());
+    }
+
+    Ok(())
+}
+
+// verify proof files one by one and in one batch, and print the verification times as JSON. All files must have the
+// same k and have been made with the active parameters, since the batch shares the commitment parameters; the run
+// fails if any proof is rejected, after naming it
+pub fn run_verify_batch(paths: &[PathBuf]) -> Result<(), BenchmarkError> {
+    let files = paths.iter().map(|path| ProofFile::read(path)).collect::<Result<Vec<_>, _>>()?;
+    let Some(k) = files.first().map(|file| file.k) else {
+        return Err(BenchmarkError::ParameterMismatch("verify-batch needs at least one proof file".to_string()));
+    };
+    let parameters = active_source().to_string();
+    for (path, file) in paths.iter().zip(&files) {
+        if file.k != k {
+            return Err(BenchmarkError::ParameterMismatch(format!("{} has k = {}, the first proof k = {}", path.display(), file.k, k)));
+        }
+        if file.parameters != parameters {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "{} was made with the {} parameters, the run uses {}", path.display(), file.parameters, parameters
+            )));
+        }
+    }
+
+    let params = commitment_params(k)?;
+    let mut vks = Vec::new();
+    let mut instances = Vec::new();
+    let mut proofs = Vec::new();
+    for (path, file) in paths.iter().zip(&files) {
+        vks.push(permutation_vk(&params, file.permutation, file.output_mode)?);
+        instances.push(file.instance.iter().map(|word| fe_from_hex::<Fp>(word, Endianness::Big)).collect::<Result<Vec<_>, _>>()?);
+        proofs.push(file.proof_bytes(path)?);
+    }
+
+    let mut reports = Vec::new();
+    for (((path, file), vk), (instance, proof)) in paths.iter().zip(&files).zip(&vks).zip(instances.iter().zip(&proofs)) {
+        let start = Instant::now();
+        let valid = verify_ipa_proof(&params, vk, proof, &[instance], TranscriptKind::Blake2b).is_ok();
+        reports.push(BatchedProofReport {
+            path: path.display().to_string(),
+            permutation: file.permutation,
+            proof_size: proof.len(),
+            verification_ms: start.elapsed().as_secs_f64() * 1000.0,
+            valid
+        });
+    }
+
+    let items: Vec<BatchItem<'_>> = vks.iter().zip(&instances).zip(&proofs)
+        .map(|((vk, instance), proof)| BatchItem { vk, instance, proof })
+        .collect();
+    let start = Instant::now();
+    let rejected = verify_batch(&params, &items);
+    let batched_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    let report = BatchVerifyReport {
+        k,
+        individual_ms: reports.iter().map(|report| report.verification_ms).sum(),
+        batched_ms,
+        rejected: rejected.iter().map(|&index| reports[index].path.clone()).collect(),
+        proofs: reports
+    };
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+    if !report.rejected.is_empty() {
+        return Err(BenchmarkError::VerificationFailed(format!("rejected proofs: {}", report.rejected.join(", "))));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // batched verification on a Poseidon and a Rescue-Prime proof at k: the batch of both
+    // verifies, and a batch with one proof's last scalar changed, or with a wrong public output for the other proof, is
+    // rejected with that proof named
+    #[test]
+    fn batch_verification() -> Result<(), BenchmarkError> {
+        let (k, mut rng) = (10, ProvingRng::new(Some(0)));
+        let params = commitment_params(k)?;
+        let inputs = [0, 1, 2].map(Fp::from);
+        let vk_ps = permutation_vk(&params, PermutationKind::Poseidon, OutputMode::AllState)?;
+        let vk_rs = permutation_vk(&params, PermutationKind::Rescue, OutputMode::AllState)?;
+        let (proof_ps, instance_ps) = prove_permutation(&params, PermutationKind::Poseidon, inputs, &mut rng)?;
+        let (proof_rs, instance_rs) = prove_permutation(&params, PermutationKind::Rescue, inputs, &mut rng)?;
+        let batch = |proof_rs: &[u8], instance_ps: &[Fp]| verify_batch(&params, &[
+            BatchItem { vk: &vk_ps, instance: instance_ps, proof: &proof_ps },
+            BatchItem { vk: &vk_rs, instance: &instance_rs, proof: proof_rs }
+        ]);
+
+        let start = Instant::now();
+        assert_eq!(batch(&proof_rs, &instance_ps), Vec::<usize>::new());
+        let batched_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let start = Instant::now();
+        verify_ipa_proof(&params, &vk_ps, &proof_ps, &[&instance_ps], TranscriptKind::Blake2b)?;
+        verify_ipa_proof(&params, &vk_rs, &proof_rs, &[&instance_rs], TranscriptKind::Blake2b)?;
+        let individual_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        // the low byte of the last scalar, so it still reads as a field element
+        let mut corrupted = proof_rs.clone();
+        let last_scalar = corrupted.len() - 32;
+        corrupted[last_scalar] ^= 1;
+        assert_eq!(batch(&corrupted, &instance_ps), [1]);
+        let mut wrong = instance_ps.clone();
+        wrong[0] += Fp::ONE;
+        assert_eq!(batch(&proof_rs, &wrong), [0]);
+
+        println!("batched verification of a Poseidon and a Rescue-Prime proof: {:.1} ms, individually {:.1} ms", batched_ms, individual_ms);
+        Ok(())
+    }
+}
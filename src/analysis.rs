@@ -0,0 +1,330 @@
+use std::collections::{BTreeMap, BTreeSet};
+use ff::PrimeField;
+use num_bigint::BigUint;
+use serde::Serialize;
+use halo2_proofs::{
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, Expression, keygen_vk},
+};
+use halo2curves::bls12381::Fr;
+
+use crate::error::BenchmarkError;
+use crate::gates::{record_gates, selector_index};
+use crate::params::field_modulus;
+use crate::proving::ProofScheme;
+use crate::report::query_label;
+use crate::srs::commitment_params;
+use crate::synthesis::pinned_count;
+use crate::synthesis::lookup_table_rows;
+
+// Summary of a circuit's configured constraint system, for external tools that diff circuit versions. halo2 0.3 keeps
+// the ConstraintSystem fields private, so the gates come from the inventory that create_gate records and the column
+// counts from the Debug output of the pinned constraint system.
+
+// structure for one gate with its constraints rendered as text
+#[derive(Clone, Debug, Serialize)]
+pub struct GateSummary {
+    pub name: &'static str,
+    pub degree: usize,
+    pub constraints: Vec<String>
+}
+
+// structure for the number of columns of each kind
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ColumnSummary {
+    pub advice: usize,
+    pub fixed: usize,
+    pub instance: usize,
+    pub selectors: usize,
+    // columns that take part in copy constraints
+    pub equality: usize
+}
+
+// structure for the constraint system of one circuit
+#[derive(Clone, Debug, Serialize)]
+pub struct ConstraintSystemSummary {
+    pub gates: Vec<GateSummary>,
+    pub columns: ColumnSummary,
+    // the rotations queried by the gates, by column kind ("advice", "fixed", "instance")
+    pub rotations: BTreeMap<String, BTreeSet<i32>>,
+    // lookup arguments, and the rows the circuit assigns in their table columns
+    pub lookups: usize,
+    pub lookup_table_rows: usize,
+    // shuffle arguments; halo2_proofs 0.3 has none, so this stays 0 until the crate moves to a version that does
+    pub shuffles: usize,
+    // degree of the constraint system, including the permutation argument
+    pub degree: usize,
+    // the prover's evaluation domains and FFTs at a given k, filled in by at_k
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prover: Option<ProverDomain>
+}
+
+// structure for the domains the halo2 0.3 prover works in at k and the FFTs one proof runs over them. The quotient has
+// degree (degree - 1) n - 1 for n = 2^k rows, so the gates are evaluated on an extended coset of 2^extended_k points,
+// the smallest power of two holding (degree - 1) n. Each advice and instance column, permutation product and lookup
+// polynomial (the permuted input and table and the product, per lookup) is interpolated with an inverse FFT of size n and
+// evaluated on the coset with an FFT of size 2^extended_k; the quotient is interpolated back with one inverse FFT of
+// that size. The fixed and permutation columns are transformed once by keygen, not per proof
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProverDomain {
+    pub k: u32,
+    pub degree: usize,
+    // the quotient's degree in multiples of n, i.e. the number of pieces it is committed in
+    pub quotient_degree: usize,
+    pub extended_k: u32,
+    // inverse FFTs of size 2^k
+    pub iffts: usize,
+    // FFTs of size 2^extended_k onto the coset, one per inverse FFT above
+    pub extended_ffts: usize,
+    // inverse FFTs of size 2^extended_k, the quotient's
+    pub extended_iffts: usize,
+    // butterflies over all of them, (size / 2) log2(size) per FFT, as a machine-independent measure of their work
+    pub butterflies: u64
+}
+
+impl ProverDomain {
+    // the domains and FFT counts for a constraint system of `degree` with the given column and argument counts
+    fn new(k: u32, degree: usize, advice: usize, instance: usize, equality: usize, lookups: usize) -> Self {
+        let quotient_degree = degree - 1;
+        let mut extended_k = k;
+        while 1u64 << extended_k < (quotient_degree as u64) << k {
+            extended_k += 1;
+        }
+        let polynomials = advice + instance + equality.div_ceil(degree - 2) + 3 * lookups;
+        let butterflies = |log_size: u32| (1u64 << log_size) / 2 * u64::from(log_size);
+
+        ProverDomain {
+            k,
+            degree,
+            quotient_degree,
+            extended_k,
+            iffts: polynomials,
+            extended_ffts: polynomials,
+            extended_iffts: 1,
+            butterflies: polynomials as u64 * (butterflies(k) + butterflies(extended_k)) + butterflies(extended_k)
+        }
+    }
+}
+
+impl ConstraintSystemSummary {
+    // the summary with the prover's domains and FFTs at k filled in
+    pub fn at_k(mut self, k: u32) -> Self {
+        self.prover = Some(ProverDomain::new(k, self.degree, self.columns.advice, self.columns.instance, self.columns.equality, self.lookups));
+        self
+    }
+}
+
+// the prover's domains and FFTs at k for the circuit C over any field, e.g. for the proofs of a sweep over Pasta
+pub(crate) fn prover_domain<F: PrimeField, C: Circuit<F>>(k: u32) -> ProverDomain {
+    let mut meta = ConstraintSystem::<F>::default();
+    C::configure(&mut meta);
+    let pinned = format!("{:?}", meta.pinned());
+    ProverDomain::new(
+        k,
+        meta.degree(),
+        pinned_count(&pinned, "num_advice_columns: "),
+        pinned_count(&pinned, "num_instance_columns: "),
+        pinned_list_len(&pinned, "permutation: ", "lookups: ", "Column {"),
+        pinned_list_len(&pinned, "lookups: ", "constants: ", "input_expressions")
+    )
+}
+
+// helper function to render a constant: small values and their negations in decimal, anything else in hex
+fn render_constant<F: PrimeField>(value: &F) -> String {
+    let value = BigUint::from_bytes_le(value.to_repr().as_ref());
+    let negated = field_modulus::<F>() - &value;
+    if value.bits() <= 64 {
+        value.to_string()
+    } else if negated.bits() <= 64 {
+        format!("-{}", negated)
+    } else {
+        format!("0x{:x}", value)
+    }
+}
+
+// helper function to render an expression with the column names used in the gate inventory (e.g. advice[0]@1 for
+// advice column 0 at the next row, selector[2]); the flag tells whether the rendering is a sum, which a product or a
+// negation has to parenthesize
+fn render<F: PrimeField>(expression: &Expression<F>) -> (String, bool) {
+    let factor = |expression: &Expression<F>| match render(expression) {
+        (text, true) => format!("({})", text),
+        (text, false) => text
+    };
+    match expression {
+        Expression::Constant(value) => (render_constant(value), false),
+        Expression::Selector(selector) => (selector_index(selector).map_or_else(|| "selector[?]".to_string(), |index| format!("selector[{}]", index)), false),
+        Expression::Fixed(query) => (query_label("fixed", &format!("{:?}", query)), false),
+        Expression::Advice(query) => (query_label("advice", &format!("{:?}", query)), false),
+        Expression::Instance(query) => (query_label("instance", &format!("{:?}", query)), false),
+        Expression::Negated(a) => (format!("-{}", factor(a)), false),
+        Expression::Sum(a, b) => match b.as_ref() {
+            Expression::Negated(b) => (format!("{} - {}", render(a).0, factor(b)), true),
+            b => (format!("{} + {}", render(a).0, render(b).0), true)
+        },
+        Expression::Product(a, b) => (format!("{} * {}", factor(a), factor(b)), false),
+        Expression::Scaled(a, scalar) => (format!("{} * {}", render_constant(scalar), factor(a)), false)
+    }
+}
+
+// render a constraint as text
+pub(crate) fn render_expression<F: PrimeField>(expression: &Expression<F>) -> String {
+    render(expression).0
+}
+
+// helper function to count the entries of a list in the pinned constraint system's Debug output, from `field` up to
+// the next top-level field
+fn pinned_list_len(pinned: &str, field: &str, next: &str, entry: &str) -> usize {
+    let start = pinned.find(field).expect("the pinned constraint system lists its arguments");
+    let end = pinned[start..].find(next).map_or(pinned.len(), |end| start + end);
+    pinned[start..end].matches(entry).count()
+}
+
+// configure the circuit on a fresh constraint system and summarize its gates, columns, rotations, lookups and degree;
+// the lookup table rows come from laying out the circuit's default (witness-free) instance
+pub fn analyze<C: Circuit<Fr> + Default>() -> ConstraintSystemSummary {
+    let mut meta = ConstraintSystem::default();
+    let gates = record_gates(|| {
+        C::configure(&mut meta);
+    });
+    let pinned = format!("{:?}", meta.pinned());
+
+    let mut rotations: BTreeMap<String, BTreeSet<i32>> = BTreeMap::new();
+    for query in gates.iter().flat_map(|gate| &gate.queries) {
+        if let Some((kind, rotation)) = query.split_once('[').zip(query.rsplit_once('@')).map(|((kind, _), (_, rotation))| (kind, rotation)) {
+            rotations.entry(kind.to_string()).or_default().insert(rotation.parse().expect("a rotation"));
+        }
+    }
+
+    ConstraintSystemSummary {
+        gates: gates.into_iter()
+            .map(|gate| GateSummary { name: gate.name, degree: gate.degree, constraints: gate.expressions })
+            .collect(),
+        columns: ColumnSummary {
+            advice: pinned_count(&pinned, "num_advice_columns: "),
+            fixed: pinned_count(&pinned, "num_fixed_columns: "),
+            instance: pinned_count(&pinned, "num_instance_columns: "),
+            selectors: pinned_count(&pinned, "num_selectors: "),
+            equality: pinned_list_len(&pinned, "permutation: ", "lookups: ", "Column {")
+        },
+        rotations,
+        lookups: pinned_list_len(&pinned, "lookups: ", "constants: ", "input_expressions"),
+        lookup_table_rows: lookup_table_rows(&C::default()).expect("a circuit without witnesses lays out"),
+        shuffles: 0,
+        degree: meta.degree(),
+        prover: None
+    }
+}
+
+// structure for the polynomials a verifier opens at the same points, which the multi-open argument batches together
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PointSet {
+    // the points as rotations of x; the permutation argument's last usable row is at -(blinding factors + 1)
+    pub rotations: Vec<i32>,
+    pub polynomials: usize
+}
+
+// structure for what one proof costs the verifier under a multi-open scheme, derived from the verifying key
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct VerificationCost {
+    pub scheme: ProofScheme,
+    // commitments opened, by argument; the quotient counts once, since the verifier folds its pieces into one
+    pub commitments: BTreeMap<&'static str, usize>,
+    pub opened_commitments: usize,
+    pub evaluations: usize,
+    pub quotient_pieces: usize,
+    pub permutation_chunks: usize,
+    pub points: usize,
+    pub point_sets: Vec<PointSet>,
+    // terms of the MSM the final check reduces to: both sides of the pairing for KZG, the whole generator vector for IPA
+    pub final_msm_size: usize,
+    // terms the verifier spends committing to the instance columns itself (IPA opens them, KZG evaluates them)
+    pub instance_msm_size: usize,
+    pub pairings: usize
+}
+
+// helper function to read the (column index, rotation) queries listed in the pinned verifying key's Debug output from
+// `field` up to the next field, e.g. "advice_queries: [(Column { index: 0, column_type: Advice }, Rotation(1)), ..]"
+fn pinned_queries(pinned: &str, field: &str, next: &str) -> Vec<(usize, i32)> {
+    let start = pinned.find(field).expect("the pinned verifying key lists its queries");
+    let end = pinned[start..].find(next).map_or(pinned.len(), |end| start + end);
+    let number = |text: &str| -> String { text.chars().take_while(|c| *c == '-' || c.is_ascii_digit()).collect() };
+    pinned[start..end].split("Column { index: ").skip(1).map(|entry| {
+        let rotation = &entry[entry.find("Rotation(").expect("a query has a rotation") + "Rotation(".len()..];
+        (number(entry).parse().expect("a column index"), number(rotation).parse().expect("a rotation"))
+    }).collect()
+}
+
+// compute the verification cost of the circuit C at k under `scheme`. The queries come from the verifying key generated
+// over Pasta, after keygen has compressed the selectors into fixed columns, and the arguments are opened the way the
+// halo2 verifier opens them: every queried column at its rotations, a permutation column and product per chunk of
+// degree - 2 columns (each product at x and wx, all but the last also at the last row), per lookup the product at x and
+// wx, the permuted input at x and x/w and the permuted table at x, and the quotient and the random polynomial at x.
+// Instance columns are opened under IPA only; the KZG schemes of the PSE fork evaluate them directly. The final MSM
+// holds the opened commitments with the quotient pieces apart, plus for GWC a witness commitment per point on both
+// sides of the pairing and the generator, for SHPLONK its two witness commitments (one of them on both sides) and the
+// generator, and for IPA its 2^k generators, the 2k round commitments, the multi-open and blinding commitments and the
+// two fixed generators
+pub(crate) fn verification_cost<C: Circuit<Fp> + Default>(k: u32, scheme: ProofScheme) -> Result<VerificationCost, BenchmarkError> {
+    let vk = keygen_vk(&commitment_params(k)?, &C::default())?;
+    let pinned = format!("{:?}", vk.pinned());
+    let mut meta = ConstraintSystem::<Fp>::default();
+    C::configure(&mut meta);
+    let degree = meta.degree();
+    let last = -(meta.blinding_factors() as i32 + 1);
+
+    let by_column = |queries: Vec<(usize, i32)>| -> Vec<BTreeSet<i32>> {
+        let mut columns: BTreeMap<usize, BTreeSet<i32>> = BTreeMap::new();
+        for (column, rotation) in queries {
+            columns.entry(column).or_default().insert(rotation);
+        }
+        columns.into_values().collect()
+    };
+    let mut polynomials: Vec<(&'static str, BTreeSet<i32>)> = Vec::new();
+    polynomials.extend(by_column(pinned_queries(&pinned, "advice_queries: ", "instance_queries: ")).into_iter().map(|rotations| ("advice", rotations)));
+    if scheme == ProofScheme::Ipa {
+        polynomials.extend(by_column(pinned_queries(&pinned, "instance_queries: ", "fixed_queries: ")).into_iter().map(|rotations| ("instance", rotations)));
+    }
+    polynomials.extend(by_column(pinned_queries(&pinned, "fixed_queries: ", "permutation: ")).into_iter().map(|rotations| ("fixed", rotations)));
+
+    let equality = pinned_list_len(&pinned, "permutation: ", "lookups: ", "Column {");
+    let permutation_chunks = equality.div_ceil(degree - 2);
+    polynomials.extend((0..equality).map(|_| ("permutation", BTreeSet::from([0]))));
+    polynomials.extend((0..permutation_chunks).map(|chunk| match chunk + 1 < permutation_chunks {
+        true => ("permutation", BTreeSet::from([last, 0, 1])),
+        false => ("permutation", BTreeSet::from([0, 1]))
+    }));
+    for _ in 0..pinned_list_len(&pinned, "lookups: ", "constants: ", "input_expressions") {
+        polynomials.extend([("lookups", BTreeSet::from([0, 1])), ("lookups", BTreeSet::from([-1, 0])), ("lookups", BTreeSet::from([0]))]);
+    }
+    polynomials.extend([("vanishing", BTreeSet::from([0])), ("vanishing", BTreeSet::from([0]))]);
+
+    let mut commitments: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut point_sets: BTreeMap<Vec<i32>, usize> = BTreeMap::new();
+    for (argument, rotations) in &polynomials {
+        *commitments.entry(argument).or_default() += 1;
+        *point_sets.entry(rotations.iter().copied().collect()).or_default() += 1;
+    }
+    let points = polynomials.iter().flat_map(|(_, rotations)| rotations).collect::<BTreeSet<_>>().len();
+    let quotient_pieces = degree - 1;
+    let bases = polynomials.len() - 1 + quotient_pieces;
+    let instance_columns = pinned_count(&pinned, "num_instance_columns: ");
+    let (final_msm_size, instance_msm_size, pairings) = match scheme {
+        ProofScheme::Ipa => (bases + 2 + 2 * k as usize + (1 << k) + 2, instance_columns << k, 0),
+        ProofScheme::Gwc => (bases + 2 * points + 1, 0, 2),
+        ProofScheme::Shplonk => (bases + 4, 0, 2)
+    };
+
+    Ok(VerificationCost {
+        scheme,
+        commitments,
+        opened_commitments: polynomials.len(),
+        evaluations: polynomials.iter().map(|(_, rotations)| rotations.len()).sum(),
+        quotient_pieces,
+        permutation_chunks,
+        points,
+        point_sets: point_sets.into_iter().map(|(rotations, polynomials)| PointSet { rotations, polynomials }).collect(),
+        final_msm_size,
+        instance_msm_size,
+        pairings
+    })
+}
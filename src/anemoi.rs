@@ -0,0 +1,230 @@
+use std::marker::PhantomData;
+use ff::PrimeField;
+use std::fmt::Debug;
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::chip::Number;
+use crate::gates::{allocate_selector, create_gate, pow_expression};
+use crate::native::anemoi_linear_layer;
+use crate::params::{Anemoi, anemoi_params};
+use crate::synthesis::check_region_rows;
+
+// an Anemoi state as its x and y words
+pub(crate) type AnemoiState<T, const L: usize> = ([T; L], [T; L]);
+
+// Anemoi chip configuration, for l = L column pairs: the x words in one set of advice columns, the y words in another,
+// and the round constants c and d in fixed columns next to them
+#[derive(Clone, Debug)]
+pub(crate) struct AnemoiChipConfig<F: PrimeField, const L: usize> {
+    pub(crate) permutation_params: Anemoi<F, L>,
+    pub(crate) x: [Column<Advice>; L],
+    pub(crate) y: [Column<Advice>; L],
+    pub(crate) c: [Column<Fixed>; L],
+    pub(crate) d: [Column<Fixed>; L],
+    pub(crate) instance: Column<Instance>,
+    // constants, linear layer and open Flystel of one round in one row
+    pub(crate) s_round: Selector,
+    // the linear layer applied after the last round
+    pub(crate) s_linear: Selector
+}
+
+// structure for the Anemoi permutation chip
+pub(crate) struct AnemoiChip<F: PrimeField, const L: usize> {
+    pub(crate) config: AnemoiChipConfig<F, L>,
+    pub(crate) _marker: PhantomData<F>,
+}
+
+// implement the Chip trait for AnemoiChip
+impl<F: PrimeField, const L: usize> Chip<F> for AnemoiChip<F, L> {
+    type Config = AnemoiChipConfig<F, L>;
+    type Loaded = ();
+
+    // getter for the chip config
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    // getter for the loaded field
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// helper function for the Anemoi round gate: with (x, y) the linear layer applied to the current row plus the round
+// constants and (u, v) the next row, the open Flystel is constrained through its quadratic relations
+//   (y - v)^alpha = x - beta y^2   and   u = x - beta y^2 + beta v^2 + delta
+// so x^(1/alpha) is never computed in the circuit, like the inverse S-box of Rescue-Prime
+fn create_flystel_gate_an<F: PrimeField, const L: usize>(
+    meta: &mut ConstraintSystem<F>,
+    config: &AnemoiChipConfig<F, L>
+) {
+    let params = &config.permutation_params;
+    create_gate(meta, "AN_flystel_gate", |meta| {
+        let s_round = meta.query_selector(config.s_round);
+        let x: [Expression<F>; L] = std::array::from_fn(|i| {
+            meta.query_advice(config.x[i], Rotation::cur()) + meta.query_fixed(config.c[i])
+        });
+        let y: [Expression<F>; L] = std::array::from_fn(|i| {
+            meta.query_advice(config.y[i], Rotation::cur()) + meta.query_fixed(config.d[i])
+        });
+        let u: [Expression<F>; L] = std::array::from_fn(|i| meta.query_advice(config.x[i], Rotation::next()));
+        let v: [Expression<F>; L] = std::array::from_fn(|i| meta.query_advice(config.y[i], Rotation::next()));
+        let (x, y) = anemoi_linear_layer(&params.mds, x, y);
+
+        (0..L).flat_map(|i| {
+            let t = x[i].clone() - pow_expression(y[i].clone(), 2) * params.beta;
+            let q_delta = pow_expression(v[i].clone(), 2) * params.beta + Expression::Constant(params.delta);
+            [
+                s_round.clone() * (pow_expression(y[i].clone() - v[i].clone(), params.alpha) - t.clone()),
+                s_round.clone() * (u[i].clone() - (t + q_delta))
+            ]
+        }).collect()
+    });
+}
+
+// helper function for the final linear layer: the next row is the linear layer applied to the current one
+fn create_linear_gate_an<F: PrimeField, const L: usize>(
+    meta: &mut ConstraintSystem<F>,
+    config: &AnemoiChipConfig<F, L>
+) {
+    let mds = config.permutation_params.mds;
+    create_gate(meta, "AN_linear_gate", |meta| {
+        let s_linear = meta.query_selector(config.s_linear);
+        let x: [Expression<F>; L] = std::array::from_fn(|i| meta.query_advice(config.x[i], Rotation::cur()));
+        let y: [Expression<F>; L] = std::array::from_fn(|i| meta.query_advice(config.y[i], Rotation::cur()));
+        let (x, y) = anemoi_linear_layer(&mds, x, y);
+
+        (0..L).flat_map(|i| {
+            let x_next = meta.query_advice(config.x[i], Rotation::next());
+            let y_next = meta.query_advice(config.y[i], Rotation::next());
+            [s_linear.clone() * (x_next - x[i].clone()), s_linear.clone() * (y_next - y[i].clone())]
+        }).collect()
+    });
+}
+
+// implementation of additional methods for the AnemoiChip
+impl<F: PrimeField, const L: usize> AnemoiChip<F, L> {
+    // name in the benchmark report, after the state size 2l
+    pub(crate) const NAME: &'static str = match L {
+        1 => "Anemoi-2",
+        2 => "Anemoi-4",
+        _ => "Anemoi"
+    };
+
+    // constructor
+    pub(crate) fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        AnemoiChip { config, _marker: PhantomData }
+    }
+
+    // configure the chip with its own columns, including all gates, constraints, and selectors
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, params: Anemoi<F, L>) -> <Self as Chip<F>>::Config {
+        let x = std::array::from_fn(|_| meta.advice_column());
+        let y = std::array::from_fn(|_| meta.advice_column());
+        let c = std::array::from_fn(|_| meta.fixed_column());
+        let d = std::array::from_fn(|_| meta.fixed_column());
+        let instance = meta.instance_column();
+
+        // the outputs are copied to the instance column
+        for column in x.iter().chain(y.iter()) {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(instance);
+
+        let config = AnemoiChipConfig {
+            permutation_params: params,
+            x,
+            y,
+            c,
+            d,
+            instance,
+            s_round: allocate_selector(meta),
+            s_linear: allocate_selector(meta)
+        };
+
+        // create gates and constraints
+        create_flystel_gate_an(meta, &config);
+        create_linear_gate_an(meta, &config);
+
+        config
+    }
+
+    // configure the chip with the parameters for 128-bit security
+    pub(crate) fn configure_default(meta: &mut ConstraintSystem<F>) -> <Self as Chip<F>>::Config {
+        // configure cannot return an error, so unsupported parameters are fatal here
+        let params = anemoi_params().unwrap_or_else(|e| panic!("invalid {} parameters: {}", Self::NAME, e));
+        AnemoiChip::configure(meta, params)
+    }
+
+    // the input state, one row per round and the output of the final linear layer
+    pub(crate) fn expected_rows(&self) -> usize {
+        self.config.permutation_params.rounds + 2
+    }
+
+    // copy an output word into the instance column
+    pub(crate) fn expose_as_public(&self, mut layouter: impl Layouter<F>, num: Number<F>, row: usize) -> Result<(), Error> {
+        layouter.constrain_instance(num.cell(), self.config.instance, row)
+    }
+
+    // apply the permutation to a witnessed state, returning the output x and y words
+    pub(crate) fn permute(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: [Value<F>; L],
+        y: [Value<F>; L]
+    ) -> Result<AnemoiState<Number<F>, L>, Error> {
+        let config = self.config();
+        let params = &config.permutation_params;
+        layouter.assign_region(
+            || "Anemoi_Permutation", |mut region| {
+                // the floor planner may call this closure more than once, each call gets its own span
+                let span = tracing::debug_span!("anemoi.region", rows = tracing::field::Empty, advice_cells = tracing::field::Empty,
+                    fixed_cells = tracing::field::Empty, activated_gates = tracing::field::Empty);
+                let _region = span.enter();
+                let x_words: Value<Vec<F>> = x.iter().copied().collect();
+                let y_words: Value<Vec<F>> = y.iter().copied().collect();
+                let trace = x_words.zip(y_words).map(|(x, y)| {
+                    params.trace_native(std::array::from_fn(|i| x[i]), std::array::from_fn(|i| y[i]))
+                });
+                let mut fixed_cell_ctr: usize = 0;
+                let mut activated_gates_ctr: usize = 0;
+
+                for (round, (c, d)) in params.c.iter().zip(&params.d).enumerate() {
+                    config.s_round.enable(&mut region, round)?;
+                    activated_gates_ctr += 1;
+                    for i in 0..L {
+                        region.assign_fixed(|| "c", config.c[i], round, || Value::known(c[i]))?;
+                        region.assign_fixed(|| "d", config.d[i], round, || Value::known(d[i]))?;
+                        fixed_cell_ctr += 2;
+                    }
+                }
+                config.s_linear.enable(&mut region, params.rounds)?;
+                activated_gates_ctr += 1;
+
+                // every row holds one full state: the input, the output of each round, the output
+                let rows = params.rounds + 2;
+                let mut state = Vec::new();
+                for row in 0..rows {
+                    state.clear();
+                    let words = trace.as_ref().map(|trace| [trace[row].0, trace[row].1].concat());
+                    for (i, column) in config.x.into_iter().chain(config.y).enumerate() {
+                        let cell = region.assign_advice(|| format!("state_{}", i), column, row, || words.as_ref().map(|words| words[i]))?;
+                        state.push(Number::from(cell));
+                    }
+                }
+                check_region_rows("Anemoi", rows, self.expected_rows())?;
+
+                span.record("rows", rows);
+                span.record("advice_cells", 2 * L * rows);
+                span.record("fixed_cells", fixed_cell_ctr);
+                span.record("activated_gates", activated_gates_ctr);
+
+                let mut words = state.drain(..);
+                Ok((std::array::from_fn(|_| words.next().unwrap()), std::array::from_fn(|_| words.next().unwrap())))
+            }
+        )
+    }
+}
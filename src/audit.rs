@@ -0,0 +1,237 @@
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use ff::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::{MockProver, VerifyFailure},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2curves::bls12381::Fr;
+use num_bigint::BigUint;
+use serde::Serialize;
+
+use crate::chip::{Number, PermutationChip};
+use crate::error::BenchmarkError;
+use crate::failures::summarize_failures;
+use crate::gates::{record_gates, with_stripped_gate};
+use crate::poseidon::PoseidonChip;
+use crate::rescue::RescueChip;
+use crate::synthesis::minimal_k;
+
+// Soundness audit (`audit`): every gate of both permutation chips is stripped from the constraint system in turn, and
+// a set of forged witnesses is run through MockProver on the full and on each stripped system. A forgery that the full
+// system rejects and the system without one gate accepts shows that the gate carries the constraint the forgery breaks;
+// a gate that no forgery gets through is decorative as far as the audit can tell, and fails the run. Each forgery
+// deviates from the honest witness in one place (a wrong round constant, a skipped S-box, a swapped MDS matrix, a
+// non-boolean select bit, a false equality output) and the rest of the witness follows from it, so only the rows where
+// the forgery happens break a constraint. The circuits expose nothing, so an accepted forgery is a witness that no
+// permutation of the inputs produces, accepted as one. It takes dozens of MockProver runs, so it is opt-in like the
+// stress run.
+
+// one way the witness deviates from the honest one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Forgery {
+    // the witness adds a wrong constant to word 0 in the first round
+    FirstRoundConstant,
+    // the witness adds a wrong constant to word 0, the S-box word, in the first partial round (Poseidon)
+    PartialRoundConstant,
+    // the witness adds a wrong constant to word 1, a word that bypasses the S-box, in the first partial round (Poseidon)
+    PartialRoundCarry,
+    // the witness skips the S-box x^alpha (Rescue-Prime)
+    SkippedSbox,
+    // the witness skips the inverse S-box x^(1/alpha) (Rescue-Prime)
+    SkippedInverseSbox,
+    // the witness multiplies by the MDS matrix with its first two rows swapped
+    SwappedMds,
+    // a select with the bit 2, its output from the select formula
+    NonBooleanSelect,
+    // is_equal of a word with itself, with the output 0
+    FalseInequality
+}
+
+// forgeries of the select and is_equal regions, the same for both chips
+const UTILITY_FORGERIES: [Forgery; 2] = [Forgery::NonBooleanSelect, Forgery::FalseInequality];
+
+// chips whose witness path can be made to follow a forgery after configure, through the parameters in their config
+// that only the witness reads; the gates were built from the honest parameters and keep them
+trait Forge: PermutationChip<Fr> {
+    // the forgeries of the permutation region that apply to the chip's round structure
+    const FORGERIES: &'static [Forgery];
+
+    // make the witness path of `config` follow `forgery`; the utility forgeries are laid out by the audit circuit
+    fn forge(config: &mut Self::Config, forgery: Forgery);
+}
+
+impl Forge for PoseidonChip<Fr> {
+    const FORGERIES: &'static [Forgery] = &[Forgery::FirstRoundConstant, Forgery::PartialRoundConstant, Forgery::PartialRoundCarry, Forgery::SwappedMds];
+
+    fn forge(config: &mut Self::Config, forgery: Forgery) {
+        let params = &mut config.permutation_params;
+        let first_partial = 3 * (params.full_rounds / 2);
+        match forgery {
+            Forgery::FirstRoundConstant => params.round_constants[0] += Fr::ONE,
+            Forgery::PartialRoundConstant => params.round_constants[first_partial] += Fr::ONE,
+            Forgery::PartialRoundCarry => params.round_constants[first_partial + 1] += Fr::ONE,
+            Forgery::SwappedMds => params.mds.swap(0, 1),
+            _ => {}
+        }
+    }
+}
+
+impl Forge for RescueChip<Fr> {
+    const FORGERIES: &'static [Forgery] = &[Forgery::FirstRoundConstant, Forgery::SkippedSbox, Forgery::SkippedInverseSbox, Forgery::SwappedMds];
+
+    fn forge(config: &mut Self::Config, forgery: Forgery) {
+        let params = &mut config.permutation_params;
+        match forgery {
+            Forgery::FirstRoundConstant => params.round_constants[0] += Fr::ONE,
+            Forgery::SkippedSbox => params.alpha = 1,
+            Forgery::SkippedInverseSbox => params.alpha_inv = BigUint::from(1u8),
+            Forgery::SwappedMds => params.mds.swap(0, 1),
+            _ => {}
+        }
+    }
+}
+
+// audit circuit: a permutation of (0, 1, 2), a select between its first two outputs and an is_equal of its first output
+// with itself, honest or with one forgery; nothing is exposed
+struct ForgedCircuit<P> {
+    forgery: Option<Forgery>,
+    _marker: PhantomData<P>
+}
+
+impl<P: Forge> Circuit<Fr> for ForgedCircuit<P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        ForgedCircuit { forgery: self.forgery, _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, mut config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        if let Some(forgery) = self.forgery {
+            P::forge(&mut config, forgery);
+        }
+        let chip = P::from_config(config);
+        let [s0, s1, s2] = [0, 1, 2].map(|word| Value::known(Fr::from(word)));
+        let [a, b, _] = chip.permute(layouter.namespace(|| "permutation"), s0, s1, s2)?;
+
+        let bit = if self.forgery == Some(Forgery::NonBooleanSelect) { 2 } else { 1 };
+        chip.select(layouter.namespace(|| "select"), Value::known(Fr::from(bit)), &a, &b)?;
+        let equal = if self.forgery == Some(Forgery::FalseInequality) { Fr::ZERO } else { Fr::ONE };
+        assign_is_equal(&chip, layouter.namespace(|| "is_equal"), &a, equal)
+    }
+}
+
+// helper function for an is_equal of `a` with itself whose output is `out`, laid out like UtilsInstructions::is_equal:
+// a, b and the inverse of a - b (0 here) on the first row, the output below a. Only out = 1 satisfies the gate
+fn assign_is_equal<P: PermutationChip<Fr>>(chip: &P, mut layouter: impl Layouter<Fr>, a: &Number<Fr>, out: Fr) -> Result<(), Error> {
+    let circuit_params = chip.circuit_params();
+    layouter.assign_region(
+        || "is_equal", |mut region| {
+            circuit_params.s_is_equal.enable(&mut region, 0)?;
+            a.assigned().copy_advice(|| "a", &mut region, circuit_params.advice[0], 0)?;
+            a.assigned().copy_advice(|| "b", &mut region, circuit_params.advice[1], 0)?;
+            region.assign_advice(|| "inv", circuit_params.advice[2], 0, || Value::known(Fr::ZERO))?;
+            region.assign_advice(|| "out", circuit_params.advice[0], 1, || Value::known(out))?;
+            Ok(())
+        }
+    )
+}
+
+// structure for the audit of one gate: the forgeries the constraint system accepts once the gate is stripped
+#[derive(Serialize)]
+struct GateAudit {
+    gate: &'static str,
+    constraints: usize,
+    forgeries: Vec<Forgery>
+}
+
+// structure for one forgery: the constraints the full constraint system reports as violated
+#[derive(Serialize)]
+struct ForgeryAudit {
+    forgery: Forgery,
+    violated: BTreeSet<String>
+}
+
+// structure for the audit of one chip
+#[derive(Serialize)]
+struct ChipAudit {
+    permutation: &'static str,
+    k: u32,
+    gates: Vec<GateAudit>,
+    forgeries: Vec<ForgeryAudit>
+}
+
+// helper function to run the audit circuit through MockProver, with the constraint system as configured on this thread
+fn verify<P: Forge>(k: u32, forgery: Option<Forgery>) -> Result<Result<(), Vec<VerifyFailure>>, BenchmarkError> {
+    let circuit = ForgedCircuit::<P> { forgery, _marker: PhantomData };
+    Ok(MockProver::run(k, &circuit, vec![vec![]])?.verify())
+}
+
+// helper function to audit one chip: the honest witness satisfies the full and every stripped system, every forgery
+// fails the full system, and every gate has a forgery that only it stops
+fn audit_chip<P: Forge>() -> Result<ChipAudit, BenchmarkError> {
+    let k = minimal_k(&ForgedCircuit::<P> { forgery: None, _marker: PhantomData })?;
+    let forgeries: Vec<Forgery> = P::FORGERIES.iter().copied().chain(UTILITY_FORGERIES).collect();
+    let failure = |reason: String| BenchmarkError::VerificationFailed(format!("{} audit: {}", P::NAME, reason));
+
+    if let Err(failures) = verify::<P>(k, None)? {
+        return Err(failure(format!("the honest witness fails: {}", summarize_failures(&failures))));
+    }
+    let forgeries_audit = forgeries.iter()
+        .map(|&forgery| match verify::<P>(k, Some(forgery))? {
+            Ok(()) => Err(failure(format!("{:?} passes the full constraint system", forgery))),
+            Err(failures) => Ok(ForgeryAudit {
+                forgery,
+                violated: failures.iter().filter_map(|failure| match failure {
+                    VerifyFailure::ConstraintNotSatisfied { constraint, .. } => Some(constraint.to_string()),
+                    _ => None
+                }).collect()
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let gates = record_gates(|| {
+        P::configure_default(&mut ConstraintSystem::default());
+    });
+    let mut gates_audit = Vec::new();
+    for gate in gates {
+        let (honest, accepted) = with_stripped_gate(gate.name, || -> Result<_, BenchmarkError> {
+            let accepted = forgeries.iter().map(|&forgery| Ok((forgery, verify::<P>(k, Some(forgery))?.is_ok())))
+                .collect::<Result<Vec<_>, BenchmarkError>>()?;
+            Ok((verify::<P>(k, None)?, accepted))
+        })?;
+        if let Err(failures) = honest {
+            return Err(failure(format!("the honest witness fails without {}: {}", gate.name, summarize_failures(&failures))));
+        }
+        gates_audit.push(GateAudit {
+            gate: gate.name,
+            constraints: gate.constraints,
+            forgeries: accepted.into_iter().filter(|(_, accepted)| *accepted).map(|(forgery, _)| forgery).collect()
+        });
+    }
+
+    Ok(ChipAudit { permutation: P::NAME, k, gates: gates_audit, forgeries: forgeries_audit })
+}
+
+// run the soundness audit over both permutation chips and print which forgeries each gate stops as JSON; a gate no
+// forgery gets through fails the run after the report is printed
+pub fn run_audit() -> Result<(), BenchmarkError> {
+    let audits = [audit_chip::<PoseidonChip<Fr>>()?, audit_chip::<RescueChip<Fr>>()?];
+    println!("{}", serde_json::to_string_pretty(&audits).unwrap());
+
+    let uncovered: Vec<String> = audits.iter()
+        .flat_map(|audit| audit.gates.iter().filter(|gate| gate.forgeries.is_empty()).map(|gate| format!("{} {}", audit.permutation, gate.gate)))
+        .collect();
+    if !uncovered.is_empty() {
+        return Err(BenchmarkError::VerificationFailed(format!("no forgery shows these gates are needed: {}", uncovered.join(", "))));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,363 @@
+use std::marker::PhantomData;
+use ff::PrimeField;
+use std::fmt::Debug;
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::chip::Number;
+use crate::gates::{allocate_complex_selector, allocate_selector, create_gate, matrix_mul_exprs, pow_expression};
+use crate::native::BarLimb;
+use crate::params::{Bars, BarsRound, bars_params};
+use crate::synthesis::check_region_rows;
+
+// Bar layer chip configuration, for limbs of B bits. A state row holds the three words in the first three advice
+// columns; a limb row holds the input limb, the output limb, both recomposed words, the equal-so-far flag and the
+// inverse of (digit - input limb) in all six. The digit of p - 1 and whether the limb S-box applies are fixed columns
+// of the limb rows, and the lookup table is (tag, input, output): the identity on B-bit limbs under tag 0 and the limb
+// S-box under tag 1
+#[derive(Clone, Debug)]
+pub(crate) struct BarsChipConfig<F: PrimeField, const B: usize> {
+    pub(crate) permutation_params: Bars<F>,
+    pub(crate) advice: [Column<Advice>; 6],
+    pub(crate) constants: [Column<Fixed>; 3],
+    pub(crate) digit: Column<Fixed>,
+    pub(crate) active: Column<Fixed>,
+    pub(crate) table: [TableColumn; 3],
+    pub(crate) instance: Column<Instance>,
+    // constants, x^5 and MDS matrix of an algebraic round in one row
+    pub(crate) s_algebraic: Selector,
+    // the constants of a bar round, added into the next row
+    pub(crate) s_arc: Selector,
+    // the MDS matrix after a bar round
+    pub(crate) s_linear: Selector,
+    // the row before the limbs of a word: both recomposed words start at 0 and the flag at 1
+    pub(crate) s_init: Selector,
+    // a limb row, which also turns the lookups on
+    pub(crate) s_limb: Selector
+}
+
+// structure for the bar layer chip
+pub(crate) struct BarsChip<F: PrimeField, const B: usize> {
+    pub(crate) config: BarsChipConfig<F, B>,
+    pub(crate) _marker: PhantomData<F>,
+}
+
+// implement the Chip trait for BarsChip
+impl<F: PrimeField, const B: usize> Chip<F> for BarsChip<F, B> {
+    type Config = BarsChipConfig<F, B>;
+    type Loaded = ();
+
+    // getter for the chip config
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    // getter for the loaded field
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// helper function for the round gates: an algebraic round from one row to the next, the constants of a bar round into
+// the next row, and the MDS matrix after a bar round into the next row
+fn create_round_gates_br<F: PrimeField, const B: usize>(
+    meta: &mut ConstraintSystem<F>,
+    config: &BarsChipConfig<F, B>
+) {
+    let params = &config.permutation_params;
+    create_gate(meta, "BR_algebraic_gate", |meta| {
+        let s_algebraic = meta.query_selector(config.s_algebraic);
+        let state: [Expression<F>; 3] = std::array::from_fn(|i| {
+            pow_expression(meta.query_advice(config.advice[i], Rotation::cur()) + meta.query_fixed(config.constants[i]), params.alpha)
+        });
+        let state = matrix_mul_exprs(&params.mds, &state);
+        (0..3).map(|i| s_algebraic.clone() * (meta.query_advice(config.advice[i], Rotation::next()) - state[i].clone())).collect()
+    });
+    create_gate(meta, "BR_arc_gate", |meta| {
+        let s_arc = meta.query_selector(config.s_arc);
+        (0..3).map(|i| {
+            let next = meta.query_advice(config.advice[i], Rotation::next());
+            s_arc.clone() * (next - (meta.query_advice(config.advice[i], Rotation::cur()) + meta.query_fixed(config.constants[i])))
+        }).collect()
+    });
+    create_gate(meta, "BR_linear_gate", |meta| {
+        let s_linear = meta.query_selector(config.s_linear);
+        let state = matrix_mul_exprs(&params.mds, &std::array::from_fn(|i| meta.query_advice(config.advice[i], Rotation::cur())));
+        (0..3).map(|i| s_linear.clone() * (meta.query_advice(config.advice[i], Rotation::next()) - state[i].clone())).collect()
+    });
+}
+
+// helper function for the bar of one word over its limb rows. With a and b the input and output limb, d = digit - a
+// and z = 1 - d inv, each limb row constrains
+//   input_acc = input_acc_prev 2^B + a,   output_acc = output_acc_prev 2^B + b,   d z = 0,   equal = equal_prev z
+// so z is 1 exactly when the limb equals its digit, and looks up (active, a, b) in the table, which range checks a and
+// gives b = S(a) or b = a. The second lookup puts equal_prev d in the identity part, i.e. in [0, 2^B): while the limbs
+// so far equal the digits of p - 1 the next one cannot exceed its digit, so the input limbs are the canonical ones
+fn create_bar_gates_br<F: PrimeField, const B: usize>(
+    meta: &mut ConstraintSystem<F>,
+    config: &BarsChipConfig<F, B>
+) {
+    let [input, output, input_acc, output_acc, equal, inverse] = config.advice;
+    let shift = F::from(1 << B);
+    create_gate(meta, "BR_init_gate", |meta| {
+        let s_init = meta.query_selector(config.s_init);
+        vec![
+            s_init.clone() * meta.query_advice(input_acc, Rotation::cur()),
+            s_init.clone() * meta.query_advice(output_acc, Rotation::cur()),
+            s_init * (meta.query_advice(equal, Rotation::cur()) - Expression::Constant(F::ONE))
+        ]
+    });
+    create_gate(meta, "BR_limb_gate", |meta| {
+        let s_limb = meta.query_selector(config.s_limb);
+        let a = meta.query_advice(input, Rotation::cur());
+        let d = meta.query_fixed(config.digit) - a.clone();
+        let z = Expression::Constant(F::ONE) - d.clone() * meta.query_advice(inverse, Rotation::cur());
+        vec![
+            s_limb.clone() * (meta.query_advice(input_acc, Rotation::cur()) - (meta.query_advice(input_acc, Rotation::prev()) * shift + a)),
+            s_limb.clone() * (meta.query_advice(output_acc, Rotation::cur()) - (meta.query_advice(output_acc, Rotation::prev()) * shift + meta.query_advice(output, Rotation::cur()))),
+            s_limb.clone() * d * z.clone(),
+            s_limb * (meta.query_advice(equal, Rotation::cur()) - meta.query_advice(equal, Rotation::prev()) * z)
+        ]
+    });
+
+    let [tag, table_input, table_output] = config.table;
+    meta.lookup(|meta| {
+        let s_limb = meta.query_selector(config.s_limb);
+        vec![
+            (s_limb.clone() * meta.query_fixed(config.active), tag),
+            (s_limb.clone() * meta.query_advice(input, Rotation::cur()), table_input),
+            (s_limb * meta.query_advice(output, Rotation::cur()), table_output)
+        ]
+    });
+    meta.lookup(|meta| {
+        let s_limb = meta.query_selector(config.s_limb);
+        let d = meta.query_fixed(config.digit) - meta.query_advice(input, Rotation::cur());
+        let bounded = s_limb * meta.query_advice(equal, Rotation::prev()) * d;
+        vec![(Expression::Constant(F::ZERO), tag), (bounded.clone(), table_input), (bounded, table_output)]
+    });
+}
+
+// implementation of additional methods for the BarsChip
+impl<F: PrimeField, const B: usize> BarsChip<F, B> {
+    // name in the benchmark report, after the limb width
+    pub(crate) const NAME: &'static str = match B {
+        4 => "Bars-4",
+        6 => "Bars-6",
+        8 => "Bars-8",
+        10 => "Bars-10",
+        _ => "Bars"
+    };
+
+    // constructor
+    pub(crate) fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        BarsChip { config, _marker: PhantomData }
+    }
+
+    // configure the chip with its own columns, including all gates, lookups, constraints, and selectors; the table
+    // columns are created here and filled by load_table, since halo2 assigns tables during synthesis
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, params: Bars<F>) -> <Self as Chip<F>>::Config {
+        let advice = std::array::from_fn(|_| meta.advice_column());
+        let constants = std::array::from_fn(|_| meta.fixed_column());
+        let instance = meta.instance_column();
+
+        // the state words are copied between the state rows and the limb rows, and the outputs to the instance column
+        for column in &advice[..4] {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(instance);
+
+        let config = BarsChipConfig {
+            permutation_params: params,
+            advice,
+            constants,
+            digit: meta.fixed_column(),
+            active: meta.fixed_column(),
+            table: std::array::from_fn(|_| meta.lookup_table_column()),
+            instance,
+            s_algebraic: allocate_selector(meta),
+            s_arc: allocate_selector(meta),
+            s_linear: allocate_selector(meta),
+            s_init: allocate_selector(meta),
+            s_limb: allocate_complex_selector(meta)
+        };
+
+        // create gates, lookups and constraints
+        create_round_gates_br(meta, &config);
+        create_bar_gates_br(meta, &config);
+
+        config
+    }
+
+    // configure the chip with the bar layer parameters for B-bit limbs
+    pub(crate) fn configure_default(meta: &mut ConstraintSystem<F>) -> <Self as Chip<F>>::Config {
+        // configure cannot return an error, so unsupported parameters are fatal here
+        let params = bars_params(B).unwrap_or_else(|e| panic!("invalid {} parameters: {}", Self::NAME, e));
+        BarsChip::configure(meta, params)
+    }
+
+    // the input state, one row per algebraic round, and per bar round the constants row, an initial row and the limb
+    // rows of each word, the row before the MDS matrix and the output row
+    pub(crate) fn expected_rows(&self) -> usize {
+        let limbs = self.config.permutation_params.limbs;
+        1 + self.config.permutation_params.rounds.iter().map(|round| match round {
+            BarsRound::Algebraic => 1,
+            BarsRound::Bar => 3 + 3 * (limbs + 1)
+        }).sum::<usize>()
+    }
+
+    // table rows: every B-bit limb under both tags
+    pub(crate) fn table_rows(&self) -> usize {
+        2 << B
+    }
+
+    // lookups one permutation performs: two per limb of every word in every bar round
+    pub(crate) fn lookups_per_permutation(&self) -> usize {
+        let params = &self.config.permutation_params;
+        2 * 3 * params.limbs * params.rounds.iter().filter(|round| **round == BarsRound::Bar).count()
+    }
+
+    // copy an output word into the instance column
+    pub(crate) fn expose_as_public(&self, mut layouter: impl Layouter<F>, num: Number<F>, row: usize) -> Result<(), Error> {
+        layouter.constrain_instance(num.cell(), self.config.instance, row)
+    }
+
+    // fill the lookup table: (0, a, a) for every B-bit limb a, then (1, a, S(a))
+    pub(crate) fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let config = self.config();
+        layouter.assign_table(
+            || "bar_table", |mut table| {
+                for (tag, offset) in [(0, 0), (1, 1 << B)] {
+                    for limb in 0..1u64 << B {
+                        let output = if tag == 0 { limb } else { config.permutation_params.limb_sbox(limb) };
+                        let row = offset + limb as usize;
+                        table.assign_cell(|| "tag", config.table[0], row, || Value::known(F::from(tag)))?;
+                        table.assign_cell(|| "input", config.table[1], row, || Value::known(F::from(limb)))?;
+                        table.assign_cell(|| "output", config.table[2], row, || Value::known(F::from(output)))?;
+                    }
+                }
+                Ok(())
+            }
+        )
+    }
+
+    // assign the bar of `input` from `offset`: the initial row, then one row per limb of `limbs`. The input is copied
+    // into the last input recomposition and the last output recomposition is returned
+    pub(crate) fn assign_bar_word(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        input: &Number<F>,
+        limbs: Value<Vec<BarLimb<F>>>
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+        let params = &config.permutation_params;
+        let [input_column, output_column, input_acc, output_acc, equal, inverse] = config.advice;
+        config.s_init.enable(region, offset)?;
+        region.assign_advice(|| "input_acc", input_acc, offset, || Value::known(F::ZERO))?;
+        region.assign_advice(|| "output_acc", output_acc, offset, || Value::known(F::ZERO))?;
+        region.assign_advice(|| "equal", equal, offset, || Value::known(F::ONE))?;
+
+        let mut recomposed = None;
+        for index in 0..params.limbs {
+            let row = offset + 1 + index;
+            let limb = limbs.as_ref().map(|limbs| limbs[index]);
+            config.s_limb.enable(region, row)?;
+            region.assign_fixed(|| "digit", config.digit, row, || Value::known(F::from(params.digits[index])))?;
+            region.assign_fixed(|| "active", config.active, row, || Value::known(F::from(u64::from(params.active(index)))))?;
+            region.assign_advice(|| "input", input_column, row, || limb.map(|limb| F::from(limb.input)))?;
+            region.assign_advice(|| "output", output_column, row, || limb.map(|limb| F::from(limb.output)))?;
+            let input_cell = region.assign_advice(|| "input_acc", input_acc, row, || limb.map(|limb| limb.input_acc))?;
+            let output_cell = region.assign_advice(|| "output_acc", output_acc, row, || limb.map(|limb| limb.output_acc))?;
+            region.assign_advice(|| "equal", equal, row, || limb.map(|limb| limb.equal))?;
+            region.assign_advice(|| "inverse", inverse, row, || limb.map(|limb| limb.inverse))?;
+            recomposed = Some((input_cell, output_cell));
+        }
+        let (input_cell, output_cell) = recomposed.expect("a word has limbs");
+        region.constrain_equal(input_cell.cell(), input.cell())?;
+
+        Ok(Number::from(output_cell))
+    }
+
+    // apply the permutation to a witnessed state, returning the output words
+    pub(crate) fn permute(&self, mut layouter: impl Layouter<F>, state: [Value<F>; 3]) -> Result<[Number<F>; 3], Error> {
+        let config = self.config();
+        let params = &config.permutation_params;
+        layouter.assign_region(
+            || "Bars_Permutation", |mut region| {
+                // the floor planner may call this closure more than once, each call gets its own span
+                let span = tracing::debug_span!("bars.region", rows = tracing::field::Empty, advice_cells = tracing::field::Empty,
+                    fixed_cells = tracing::field::Empty, activated_gates = tracing::field::Empty);
+                let _region = span.enter();
+                let words: Value<Vec<F>> = state.iter().copied().collect();
+                let input = words.map(|words| [words[0], words[1], words[2]]);
+                let trace = input.map(|input| params.trace_native(input));
+                let assign_state = |region: &mut Region<'_, F>, row: usize, words: Value<[F; 3]>| -> Result<[Number<F>; 3], Error> {
+                    let mut cells = Vec::new();
+                    for (i, column) in config.advice[..3].iter().enumerate() {
+                        cells.push(Number::from(region.assign_advice(|| format!("state_{}", i), *column, row, || words.map(|words| words[i]))?));
+                    }
+                    let mut cells = cells.into_iter();
+                    Ok(std::array::from_fn(|_| cells.next().unwrap()))
+                };
+                let mut advice_cell_ctr: usize = 3;
+                let mut fixed_cell_ctr: usize = 0;
+                let mut activated_gates_ctr: usize = 0;
+
+                let mut row = 0;
+                let mut cells = assign_state(&mut region, row, input)?;
+                for (round, (kind, constants)) in params.rounds.iter().zip(&params.round_constants).enumerate() {
+                    for (column, constant) in config.constants.iter().zip(constants) {
+                        region.assign_fixed(|| "round_constant", *column, row, || Value::known(*constant))?;
+                        fixed_cell_ctr += 1;
+                    }
+                    let next = trace.as_ref().map(|trace| trace[round + 1]);
+                    match kind {
+                        BarsRound::Algebraic => {
+                            config.s_algebraic.enable(&mut region, row)?;
+                            activated_gates_ctr += 1;
+                        }
+                        BarsRound::Bar => {
+                            config.s_arc.enable(&mut region, row)?;
+                            let added = trace.as_ref().map(|trace| std::array::from_fn::<F, 3, _>(|i| trace[round][i] + constants[i]));
+                            row += 1;
+                            let added_cells = assign_state(&mut region, row, added)?;
+                            advice_cell_ctr += 3;
+                            row += 1;
+
+                            let mut outputs = Vec::new();
+                            for (i, word) in added_cells.iter().enumerate() {
+                                let limbs = added.map(|added| params.bar_rows(&params.decompose(added[i])));
+                                outputs.push(self.assign_bar_word(&mut region, row, word, limbs)?);
+                                row += 1 + params.limbs;
+                                advice_cell_ctr += 3 + 6 * params.limbs;
+                                fixed_cell_ctr += 2 * params.limbs;
+                                activated_gates_ctr += 1 + params.limbs;
+                            }
+                            for (i, output) in outputs.iter().enumerate() {
+                                output.assigned().copy_advice(|| format!("bar_{}", i), &mut region, config.advice[i], row)?;
+                            }
+                            advice_cell_ctr += 3;
+                            config.s_linear.enable(&mut region, row)?;
+                            activated_gates_ctr += 2;
+                        }
+                    }
+                    row += 1;
+                    cells = assign_state(&mut region, row, next)?;
+                    advice_cell_ctr += 3;
+                }
+                let rows = row + 1;
+                check_region_rows("bar layer", rows, self.expected_rows())?;
+
+                span.record("rows", rows);
+                span.record("advice_cells", advice_cell_ctr);
+                span.record("fixed_cells", fixed_cell_ctr);
+                span.record("activated_gates", activated_gates_ctr);
+
+                Ok(cells)
+            }
+        )
+    }
+}
@@ -0,0 +1,1283 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::marker::PhantomData;
+use ff::{Field, PrimeField};
+use std::fmt::Debug;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+use clap::ValueEnum;
+use serde::Serialize;
+use halo2_proofs::{
+    circuit::{SimpleFloorPlanner, Value, floor_planner::V1},
+    dev::{FailureLocation, MockProver, VerifyFailure, metadata},
+    pasta::{EqAffine, Fp},
+    plonk::{Any, Circuit, ConstraintSystem, FloorPlanner},
+    poly::commitment::Params,
+};
+use halo2curves::bls12381::Fr;
+use halo2curves::bn256::Fr as Bn256Fr;
+use rand_core::RngCore;
+
+use crate::clock::Instant;
+use crate::chip::{PermutationChip, check_instance_ranges};
+use crate::circuits::{FloorPlannerKind, OutputMode, Planned, PoseidonCircuit, RescueCircuit, PreimageCircuit, DualCircuit, BatchCircuit, AnemoiCircuit, BarsCircuit, MerklePathCircuit, Merkle4PathCircuit, MembershipCircuit};
+use crate::analysis::{VerificationCost, analyze, verification_cost};
+use crate::anemoi::AnemoiChip;
+use crate::bars::BarsChip;
+use crate::constants::{Preset, PresetField, preset, presets};
+use crate::error::BenchmarkError;
+use crate::failures::{summarize_failures, verification_failed};
+use crate::instances::{Digest, PublicInputs};
+use crate::generate::{poseidon_mds_origin, rescue_mds_origin};
+use crate::gates::{
+    ConstantStrategy, SboxLayout, with_constant_strategy, with_sbox_layout
+};
+use crate::job::{ProverJob, preimage_request};
+use crate::memory::low_memory;
+use crate::native::{NativePermutation, merkle_levels, merkle_path};
+use crate::params::{WidePoseidon, WideRescue, anemoi_params, bars_params, poseidon_inverse_params, poseidon_params, rescue_params,
+    Endianness, fe_to_decimal, fe_to_hex, parse_word, state_from_bytes};
+use crate::poseidon::PoseidonChip;
+use crate::proving::{Backend, ProofScheme, ProvingRng, batch_proving, measure_pasta_proofs, measure_transcripts, prove_and_verify, scaling_report, bytes_proving, prove_at_minimal_k, public_message_proving, stream_proving, transcript_proving};
+use crate::report::{Repeat, ReportOutput, hex_words, BarLayerReport, ConstantStrategyReport, SboxLayoutReport, CostReport, CircuitReport, CircuitTimings, BenchmarkReport, FloorPlannerReport, RegionRowsReport, DualReport, MerkleReport, MembershipReport, ConstantsLoadReport};
+use crate::presets::{NEPTUNE_ARITY_2_TAG, bls381_neptune};
+use crate::rescue::RescueChip;
+use crate::source::{ParameterFile, ParameterSource, active_source, set_parameter_source};
+use crate::sponge::{Sponge, public_message_column};
+use crate::srs::commitment_params;
+use crate::transcript::TranscriptKind;
+use crate::sweep::sweep;
+use crate::target::PermutationKind;
+use crate::wide::{WideChip, WidePermutation};
+use crate::synthesis::{CheckMode, RegionUsage, check_k, copy_constraints, layout_rows, minimal_k, region_usage, time_synthesis, time_witness_generation};
+
+// helper function to time MockProver (run and verify separately), synthesis and witness generation over a circuit,
+// asserting that it verifies, and run the cost model on it; a quick check times MockProver at the smallest fitting k
+// and then checks the circuit once at k, except in low-memory mode, where the check at the smallest k stands
+pub(crate) fn measure_circuit<C: Circuit<Fr> + Debug>(
+    label: &str,
+    k: u32,
+    circuit: &C,
+    public_inputs: &PublicInputs<Fr>,
+    repeat: Repeat,
+    check: CheckMode
+) -> Result<(CircuitTimings, CostReport), BenchmarkError> {
+    let _span = tracing::info_span!("benchmark", label, k, rounds = repeat.rounds, warmup = repeat.warmup).entered();
+    check_k(label, k, circuit)?;
+    let mock_k = check.mock_k(k, circuit)?;
+    // a quick check in low-memory mode never holds the witness in 2^k-row columns
+    let streamed = check == CheckMode::Quick && low_memory();
+    for _ in 0..repeat.warmup {
+        MockProver::run(mock_k, circuit, public_inputs.to_instances())?.verify().map_err(|failures| verification_failed(label, &failures))?;
+        time_synthesis(circuit)?;
+        time_witness_generation(k, circuit, streamed)?;
+    }
+
+    let (mut mock_prover_ms, mut constraint_check_ms, mut synthesis_us, mut witness_gen_ms) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut mock_total = Duration::ZERO;
+    let mut witness_total = Duration::ZERO;
+    for iteration in 0..repeat.rounds {
+        let _mock = tracing::debug_span!("mock_prove", iteration).entered();
+        let start = Instant::now();
+        let prover = MockProver::run(mock_k, circuit, public_inputs.to_instances())?;
+        let duration = start.elapsed();
+
+        let start = Instant::now();
+        prover.verify().map_err(|failures| verification_failed(label, &failures))?;
+        let check = start.elapsed();
+        println!("{} MockProver time: {} ms", label, duration.as_millis());
+        mock_prover_ms.push(duration.as_millis());
+        constraint_check_ms.push(check.as_millis());
+        synthesis_us.push(time_synthesis(circuit)?);
+
+        let witness = time_witness_generation(k, circuit, streamed)?;
+        witness_gen_ms.push(witness.as_secs_f64() * 1000.0);
+        mock_total += duration + check;
+        witness_total += witness;
+    }
+    if mock_k != k && !streamed {
+        MockProver::run(k, circuit, public_inputs.to_instances())?.verify().map_err(|failures| verification_failed(label, &failures))?;
+    }
+    let mut timings = CircuitTimings::new(check, mock_prover_ms, constraint_check_ms, synthesis_us, witness_gen_ms);
+    timings.witness_gen_faster = (mock_k == k).then_some(witness_total < mock_total);
+
+    // analytic cost model
+    let cost = tracing::debug_span!("cost_model").in_scope(|| CostReport::measure(k, circuit));
+    println!("{} estimated proof size: {} bytes", label, cost.estimated_proof_size);
+    println!(
+        "{} selectors: {}, fixed columns: {} declared, {} after selector compression",
+        label, cost.selectors.selectors, cost.selectors.fixed_columns, cost.selectors.compressed_fixed_columns
+    );
+    cost.gates.print_table(label);
+
+    Ok((timings, cost))
+}
+
+// helper function to turn MockProver failures on public instance rows into readable messages. `expected` is the
+// instance that was supplied and `circuit_outputs` what the circuit computes, from the native permutation; a word
+// missing from either side is reported as "nothing". Failures elsewhere in the circuit are left to the caller
+pub(crate) fn explain_instance_mismatch<F: PrimeField>(report: &[VerifyFailure], expected: &[F], circuit_outputs: &[F]) -> Vec<String> {
+    // every circuit here has a single instance column
+    let instance = metadata::Column::from((Any::Instance, 0));
+    let rows: BTreeSet<usize> = report.iter()
+        .filter_map(|failure| match failure {
+            VerifyFailure::Permutation { column, location: FailureLocation::OutsideRegion { row } } if *column == instance => Some(*row),
+            _ => None
+        })
+        .collect();
+
+    let word = |words: &[F], row: usize| words.get(row).map_or("nothing".to_string(), |word| fe_to_hex(word, Endianness::Big));
+    rows.into_iter()
+        .map(|row| format!(
+            "output word {} mismatch: circuit produced {}, instance said {}", row, word(circuit_outputs, row), word(expected, row)
+        ))
+        .collect()
+}
+
+// helper function for the binary Merkle path circuit of a leaf in a tree built by merkle_levels
+fn binary_path_circuit<F: PrimeField, P>(levels: &[Vec<F>], index: usize) -> MerklePathCircuit<F, P> {
+    let path = merkle_path(levels, 2, index);
+    MerklePathCircuit {
+        leaf: Value::known(levels[0][index]),
+        siblings: path.iter().map(|(_, siblings)| Value::known(siblings[0])).collect(),
+        bits: path.iter().map(|&(position, _)| Value::known(F::from(position as u64))).collect(),
+        _marker: PhantomData
+    }
+}
+
+// helper function for the membership circuit of a leaf in a tree built by merkle_levels, with its secret
+fn membership_circuit<F: PrimeField, P>(levels: &[Vec<F>], index: usize, secret: F) -> MembershipCircuit<F, P> {
+    let MerklePathCircuit { leaf, siblings, bits, .. } = binary_path_circuit::<F, P>(levels, index);
+    MembershipCircuit { leaf, secret: Value::known(secret), siblings, bits, _marker: PhantomData }
+}
+
+// helper function for the 4-ary Merkle path circuit of a leaf in a tree built by merkle_levels
+fn quaternary_path_circuit<F: PrimeField, P>(levels: &[Vec<F>], index: usize) -> Merkle4PathCircuit<F, P> {
+    let path = merkle_path(levels, 4, index);
+    Merkle4PathCircuit {
+        leaf: Value::known(levels[0][index]),
+        siblings: path.iter().map(|(_, siblings)| std::array::from_fn(|i| Value::known(siblings[i]))).collect(),
+        bits: path.iter().map(|&(position, _)| [position & 1, position >> 1].map(|bit| Value::known(F::from(bit as u64)))).collect(),
+        _marker: PhantomData
+    }
+}
+
+// helper function for the verification cost of both permutation circuits at k under each scheme
+fn measure_verification_costs(k: u32) -> Result<BTreeMap<&'static str, Vec<VerificationCost>>, BenchmarkError> {
+    let schemes = [ProofScheme::Ipa, ProofScheme::Gwc, ProofScheme::Shplonk];
+    let costs = BTreeMap::from([
+        ("Poseidon", schemes.into_iter().map(|scheme| verification_cost::<PoseidonCircuit<Fp>>(k, scheme)).collect::<Result<Vec<_>, _>>()?),
+        ("Rescue-Prime", schemes.into_iter().map(|scheme| verification_cost::<RescueCircuit<Fp>>(k, scheme)).collect::<Result<Vec<_>, _>>()?)
+    ]);
+
+    for (name, cost) in costs.iter().flat_map(|(name, costs)| costs.iter().map(move |cost| (name, cost))) {
+        println!(
+            "{} verification under {:?}: {} commitments opened with {} evaluations at {} points in {} point sets, final MSM of {} terms, {} pairings",
+            name, cost.scheme, cost.opened_commitments, cost.evaluations, cost.points, cost.point_sets.len(), cost.final_msm_size, cost.pairings
+        );
+    }
+
+    Ok(costs)
+}
+
+// helper function to report a chip's row prediction and the layout of one permutation next to the previous layout;
+// `rows_per_round` is (previous, current), both after the initial state row
+fn measure_region_rows<P: PermutationChip<Fr>>(rows_per_round: (usize, usize)) -> Result<RegionRowsReport, BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let expected_rows = chip.expected_rows();
+    let rows = layout_rows(&BatchCircuit::<Fr, P> { inputs: vec![[Value::known(Fr::ONE); 3]], _marker: PhantomData })?;
+
+    let (previous, current) = rows_per_round;
+    let rounds = (expected_rows - 1) / current;
+    Ok(RegionRowsReport { permutation: P::NAME, previous_rows: 1 + previous * rounds, expected_rows, rows })
+}
+
+// helper function to list the regions of the two permutation circuits and the dual one
+fn measure_regions(circuit_ps: &PoseidonCircuit<Fr>, circuit_rs: &RescueCircuit<Fr>, dual: &DualCircuit<Fr>) -> Result<Vec<RegionUsage>, BenchmarkError> {
+    let mut regions = region_usage("Poseidon", circuit_ps)?;
+    regions.extend(region_usage("Rescue-Prime", circuit_rs)?);
+    regions.extend(region_usage("Poseidon+Rescue-Prime", dual)?);
+
+    Ok(regions)
+}
+
+// benchmark one permutation circuit on the inputs (0, 1, 2), with the public outputs from the native permutation;
+// this is the MockProver part of run_benchmark only, without the real proofs
+pub fn permutation_report(permutation: PermutationKind, k: u32, iterations: usize, check: CheckMode) -> Result<BenchmarkReport, BenchmarkError> {
+    let inputs = [Fr::from(0), Fr::from(1), Fr::from(2)];
+    let output_mode = OutputMode::AllState;
+    let target = permutation.target();
+
+    let circuit = target.measure(k, inputs, output_mode, Repeat { rounds: iterations, warmup: 0 }, check)?;
+    let regions = target.regions(output_mode)?;
+    let analysis = target.analysis(k);
+    let name = circuit.permutation;
+
+    Ok(BenchmarkReport { k, parameters: active_source().to_string(), seed: None, circuits: vec![circuit], dual: None, proofs: Vec::new(), transcripts: Vec::new(), constant_strategies: Vec::new(), sbox_layouts: Vec::new(),
+        floor_planners: Vec::new(), region_rows: Vec::new(), regions, bar_layers: Vec::new(), constants_loading: Vec::new(), verification: BTreeMap::new(),
+        analysis: BTreeMap::from([(name, analysis)]) })
+}
+
+// helper function to lay out a circuit with the floor planner FP at the smallest k that fits it, where the outputs must
+// verify. A permutation circuit also gets a real proof over Pasta, so its verifying key is generated under FP too
+fn measure_floor_planner<FP: FloorPlanner, C: Circuit<Fr> + Debug, D: Circuit<Fp>>(
+    label: &'static str,
+    circuit_name: String,
+    floor_planner: FloorPlannerKind,
+    circuit: C,
+    instance: &[Fr],
+    pasta: Option<(D, &[Fp])>,
+    rng: &mut ProvingRng
+) -> Result<FloorPlannerReport, BenchmarkError> {
+    let circuit = Planned::<_, FP>::new(circuit);
+    let rows = layout_rows(&circuit)?;
+    let k = minimal_k(&circuit)?;
+    MockProver::run(k, &circuit, vec![instance.to_vec()])?.verify()
+        .map_err(|failures| verification_failed(format_args!("{} under {:?}", circuit_name, floor_planner), &failures))?;
+
+    let proof = pasta
+        .map(|(circuit, instance)| prove_and_verify(label, &commitment_params(k)?, &Planned::<_, FP>::new(circuit), instance, &mut *rng))
+        .transpose()?;
+    println!("{} with the {:?} floor planner: {} rows, k = {}", circuit_name, floor_planner, rows, k);
+
+    Ok(FloorPlannerReport { circuit: circuit_name, floor_planner, rows, minimal_k: k, proof_size: proof.map(|proof| proof.proof_size) })
+}
+
+// helper function to lay out both permutation circuits, and a batch of 16 permutations of each kind, with the floor
+// planner FP
+fn floor_planner_reports<FP: FloorPlanner>(floor_planner: FloorPlannerKind, rng: &mut ProvingRng) -> Result<Vec<FloorPlannerReport>, BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let inputs_fp = [0, 1, 2].map(Fp::from);
+    let [p0, p1, p2] = inputs_fp.map(Value::known);
+    let output_mode = OutputMode::AllState;
+    let poseidon = poseidon_params::<Fr>()?;
+    let rescue = rescue_params::<Fr>()?;
+
+    let batch_inputs: Vec<[Fr; 3]> = (0..16u64).map(|i| [3 * i, 3 * i + 1, 3 * i + 2].map(Fr::from)).collect();
+    let batch: Vec<[Value<Fr>; 3]> = batch_inputs.iter().map(|input| input.map(Value::known)).collect();
+    let digests_ps = batch_inputs.iter().map(|input| Ok(poseidon.permute_native(*input)[0])).collect::<Result<Vec<_>, BenchmarkError>>()?;
+    let digests_rs = batch_inputs.iter().map(|input| Ok(rescue.permute_native(*input)[0])).collect::<Result<Vec<_>, BenchmarkError>>()?;
+
+    Ok(vec![
+        measure_floor_planner::<FP, _, _>(
+            "Poseidon", "Poseidon".to_string(), floor_planner,
+            PoseidonCircuit { s0, s1, s2, output_mode }, &poseidon.permute_native(inputs),
+            Some((PoseidonCircuit { s0: p0, s1: p1, s2: p2, output_mode }, &poseidon_params::<Fp>()?.permute_native(inputs_fp)[..])), rng
+        )?,
+        measure_floor_planner::<FP, _, _>(
+            "Rescue-Prime", "Rescue-Prime".to_string(), floor_planner,
+            RescueCircuit { s0, s1, s2, output_mode }, &rescue.permute_native(inputs),
+            Some((RescueCircuit { s0: p0, s1: p1, s2: p2, output_mode }, &rescue_params::<Fp>()?.permute_native(inputs_fp)[..])), rng
+        )?,
+        measure_floor_planner::<FP, _, _>(
+            "Poseidon", "Poseidon batch-16".to_string(), floor_planner,
+            BatchCircuit::<Fr, PoseidonChip<Fr>> { inputs: batch.clone(), _marker: PhantomData }, &digests_ps, None::<(PoseidonCircuit<Fp>, &[Fp])>, rng
+        )?,
+        measure_floor_planner::<FP, _, _>(
+            "Rescue-Prime", "Rescue-Prime batch-16".to_string(), floor_planner,
+            BatchCircuit::<Fr, RescueChip<Fr>> { inputs: batch, _marker: PhantomData }, &digests_rs, None::<(RescueCircuit<Fp>, &[Fp])>, rng
+        )?
+    ])
+}
+
+// helper function to compare SimpleFloorPlanner and V1 on the same circuits; every circuit must verify under both, and
+// the permutation circuits are proved under both
+fn measure_floor_planners(seed: Option<u64>) -> Result<Vec<FloorPlannerReport>, BenchmarkError> {
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = floor_planner_reports::<SimpleFloorPlanner>(FloorPlannerKind::Simple, &mut rng)?;
+    reports.extend(floor_planner_reports::<V1>(FloorPlannerKind::V1, &mut rng)?);
+
+    Ok(reports)
+}
+
+// helper function to measure a permutation circuit under each round constant strategy; each one must verify against
+// the same public outputs, and gets a real proof over Pasta
+fn compare_constant_strategies<C: Circuit<Fr> + Debug, D: Circuit<Fp>>(
+    permutation: &'static str,
+    k: u32,
+    circuit: &C,
+    instance: &[Fr],
+    pasta: (&D, &[Fp]),
+    rng: &mut ProvingRng
+) -> Result<Vec<ConstantStrategyReport>, BenchmarkError> {
+    let params = commitment_params(k)?;
+    [ConstantStrategy::Fixed, ConstantStrategy::Advice, ConstantStrategy::Gate].into_iter()
+        .map(|strategy| with_constant_strategy(strategy, || {
+            let prover = MockProver::run(k, circuit, vec![instance.to_vec()])?;
+            prover.verify().map_err(|failures| verification_failed(format_args!("{} with {:?} constants", permutation, strategy), &failures))?;
+
+            let cost = CostReport::measure(k, circuit);
+            let proof = prove_and_verify(permutation, &params, pasta.0, pasta.1, &mut *rng)?;
+            Ok(ConstantStrategyReport {
+                permutation,
+                strategy,
+                gates: cost.gates.gates.len(),
+                fixed_columns: cost.circuit["num_fixed_columns"],
+                fixed: cost.fixed,
+                advice_columns: cost.circuit["num_advice_columns"],
+                copy_constraints: copy_constraints(circuit)?,
+                estimated_proof_size: cost.estimated_proof_size,
+                proof_size: proof.proof_size
+            })
+        }))
+        .collect()
+}
+
+// helper function to compare the round constant strategies on both permutation circuits
+fn measure_constant_strategies(
+    k: u32,
+    instance_ps: &[Fr],
+    instance_rs: &[Fr],
+    output_mode: OutputMode,
+    seed: Option<u64>
+) -> Result<Vec<ConstantStrategyReport>, BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let circuit_ps = PoseidonCircuit { s0: Value::known(inputs[0]), s1: Value::known(inputs[1]), s2: Value::known(inputs[2]), output_mode };
+    let circuit_rs = RescueCircuit { s0: Value::known(inputs[0]), s1: Value::known(inputs[1]), s2: Value::known(inputs[2]), output_mode };
+    let inputs_fp = [0, 1, 2].map(Fp::from);
+    let pasta_ps = PoseidonCircuit {
+        s0: Value::known(inputs_fp[0]),
+        s1: Value::known(inputs_fp[1]),
+        s2: Value::known(inputs_fp[2]),
+        output_mode: OutputMode::AllState
+    };
+    let pasta_rs = RescueCircuit {
+        s0: Value::known(inputs_fp[0]),
+        s1: Value::known(inputs_fp[1]),
+        s2: Value::known(inputs_fp[2]),
+        output_mode: OutputMode::AllState
+    };
+    let expected_ps = poseidon_params::<Fp>()?.permute_native(inputs_fp);
+    let expected_rs = rescue_params::<Fp>()?.permute_native(inputs_fp);
+
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = compare_constant_strategies("Poseidon", k, &circuit_ps, instance_ps, (&pasta_ps, &expected_ps), &mut rng)?;
+    reports.extend(compare_constant_strategies("Rescue-Prime", k, &circuit_rs, instance_rs, (&pasta_rs, &expected_rs), &mut rng)?);
+
+    for triple in reports.chunks(3) {
+        let [fixed, advice, gate] = triple else { unreachable!() };
+        println!(
+            "{} round constants: fixed {} copies {} proof {} bytes, advice {} copies {} proof {} bytes, gate {} gates {} fixed columns proof {} bytes",
+            fixed.permutation, fixed.fixed_columns, fixed.copy_constraints, fixed.proof_size,
+            advice.fixed_columns, advice.copy_constraints, advice.proof_size,
+            gate.gates, gate.fixed_columns, gate.proof_size
+        );
+    }
+
+    Ok(reports)
+}
+
+// helper function to measure a permutation circuit under both S-box layouts; each layout must accept the outputs of the
+// native permutation, and the proofs are real IPA proofs
+fn sbox_layout_reports<C: Circuit<Fr> + Debug, D: Circuit<Fp>>(
+    label: &'static str,
+    k: u32,
+    (circuit, instance): (&C, &[Fr]),
+    pasta: (&D, &[Fp]),
+    params: &Params<EqAffine>,
+    rng: &mut ProvingRng
+) -> Result<Vec<SboxLayoutReport>, BenchmarkError> {
+    [SboxLayout::Direct, SboxLayout::Squared].into_iter()
+        .map(|layout| with_sbox_layout(layout, || {
+            let prover = MockProver::run(k, circuit, vec![instance.to_vec()])?;
+            prover.verify().map_err(|failures| verification_failed(format_args!("{} with the {:?} S-box layout", label, layout), &failures))?;
+
+            let cost = CostReport::measure(k, circuit);
+            Ok(SboxLayoutReport {
+                permutation: label,
+                layout,
+                degree: cost.circuit["max_deg"],
+                rows: cost.circuit["max_rows"],
+                advice_columns: cost.circuit["num_advice_columns"],
+                quotient_commitments: cost.proof_components["vanishing.commitments"],
+                estimated_proof_size: cost.estimated_proof_size,
+                proof: prove_and_verify(label, params, pasta.0, pasta.1, &mut *rng)?
+            })
+        }))
+        .collect()
+}
+
+// helper function to compare the S-box layouts of both permutation circuits on the inputs 0, 1 and 2: witnessing the
+// square of every S-box input caps the S-box constraints at degree 3, at the cost of three advice columns
+fn measure_sbox_layouts(k: u32, seed: Option<u64>) -> Result<Vec<SboxLayoutReport>, BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let circuit_ps = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let circuit_rs = RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let instance_ps = poseidon_params::<Fr>()?.permute_native(inputs);
+    let instance_rs = rescue_params::<Fr>()?.permute_native(inputs);
+
+    let inputs_fp = [0, 1, 2].map(Fp::from);
+    let [s0, s1, s2] = inputs_fp.map(Value::known);
+    let pasta_ps = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let pasta_rs = RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let expected_ps = poseidon_params::<Fp>()?.permute_native(inputs_fp);
+    let expected_rs = rescue_params::<Fp>()?.permute_native(inputs_fp);
+
+    let params = commitment_params(k)?;
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = sbox_layout_reports("Poseidon", k, (&circuit_ps, &instance_ps), (&pasta_ps, &expected_ps), &params, &mut rng)?;
+    reports.extend(sbox_layout_reports("Rescue-Prime", k, (&circuit_rs, &instance_rs), (&pasta_rs, &expected_rs), &params, &mut rng)?);
+
+    for pair in reports.chunks(2) {
+        let [direct, squared] = pair else { unreachable!() };
+        println!(
+            "{} S-box layouts: direct degree {} proof {} bytes {} ms, squared degree {} proof {} bytes {} ms, {} rows each",
+            direct.permutation, direct.degree, direct.proof.proof_size, direct.proof.proving_ms,
+            squared.degree, squared.proof.proof_size, squared.proof.proving_ms, direct.rows
+        );
+    }
+
+    Ok(reports)
+}
+
+// write the active Poseidon and Rescue-Prime parameters as a parameter file (JSON, or TOML for a .toml path)
+pub fn export_parameters(out: &Path) -> Result<(), BenchmarkError> {
+    ParameterFile::from_params(&poseidon_params::<Fr>()?, &rescue_params::<Fr>()?).write(out)?;
+    println!("parameters written to {}", out.display());
+    Ok(())
+}
+
+// notation of the constants printed by `params show`
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ConstantsFormat {
+    #[default]
+    Decimal,
+    // 0x-prefixed big-endian hex
+    Hex
+}
+
+// loads of each preset's tables the constants_loading times are averaged over
+const CONSTANT_LOADS: u32 = 100;
+
+// helper function to time loading every preset's tables from its embedded limbs and from its strings, in its own field
+fn measure_embedded_constants() -> Result<Vec<ConstantsLoadReport>, BenchmarkError> {
+    presets().iter()
+        .map(|preset| match preset.field {
+            PresetField::Bls12_381 => embedded_constants_report::<Fr>(preset),
+            PresetField::Bn254 => embedded_constants_report::<Bn256Fr>(preset)
+        })
+        .collect()
+}
+
+// helper function for one preset of measure_embedded_constants, in its field F
+fn embedded_constants_report<F: PrimeField>(preset: &'static Preset) -> Result<ConstantsLoadReport, BenchmarkError> {
+    let words = preset.round_constants::<F>()?.len() + 9;
+    let parsed_us = time_loads(|| Ok((preset.parsed_round_constants::<F>()?, preset.parsed_mds::<F>()?)))?;
+    let embedded_us = time_loads(|| Ok((preset.round_constants::<F>()?, preset.mds::<F>()?)))?;
+    println!("{} constants: {:.1} us parsed, {:.1} us embedded", preset.name, parsed_us, embedded_us);
+
+    Ok(ConstantsLoadReport { preset: preset.name, words, parsed_us, embedded_us })
+}
+
+// helper function for the mean time of CONSTANT_LOADS loads, in microseconds
+fn time_loads<T>(load: impl Fn() -> Result<T, BenchmarkError>) -> Result<f64, BenchmarkError> {
+    let start = Instant::now();
+    for _ in 0..CONSTANT_LOADS {
+        std::hint::black_box(load()?);
+    }
+    Ok(start.elapsed().as_secs_f64() * 1e6 / CONSTANT_LOADS as f64)
+}
+
+// structure for a preset as `params show` prints it: the metadata and hash, then the constants
+#[derive(Serialize)]
+struct PresetDump {
+    #[serde(flatten)]
+    preset: &'static Preset,
+    mds: Vec<Vec<String>>,
+    round_constants: Vec<String>
+}
+
+// helper function to write a preset's constants, parsed in the field it was generated for
+fn preset_words<F: PrimeField>(preset: &Preset, format: ConstantsFormat) -> Result<(Vec<Vec<String>>, Vec<String>), BenchmarkError> {
+    let write = |word: &F| match format {
+        ConstantsFormat::Decimal => fe_to_decimal(word),
+        ConstantsFormat::Hex => fe_to_hex(word, Endianness::Big)
+    };
+    let mds = preset.mds::<F>()?.iter().map(|row| row.iter().map(write).collect()).collect();
+    Ok((mds, preset.round_constants::<F>()?.iter().map(write).collect()))
+}
+
+// print a constants preset as JSON, with its source, field, round numbers and content hash
+pub fn show_preset(name: &str, format: ConstantsFormat) -> Result<(), BenchmarkError> {
+    let preset = preset(name)?;
+    let (mds, round_constants) = match preset.field {
+        PresetField::Bls12_381 => preset_words::<Fr>(preset, format)?,
+        PresetField::Bn254 => preset_words::<Bn256Fr>(preset, format)?
+    };
+    println!("{}", serde_json::to_string_pretty(&PresetDump { preset, mds, round_constants }).unwrap());
+    Ok(())
+}
+
+// run both permutations (and the preimage statement over each) and print the JSON report. `repeat` applies to the
+// MockProver phases and the real proofs alike; without it there are 30 MockProver rounds and one proof per circuit.
+// `transcript` is the Fiat-Shamir transcript of the real proofs, and `check` picks how the measured MockProver rounds
+// check the circuits. `verbose` also prints the regions of the circuits as a table; the JSON report always lists them
+#[allow(clippy::too_many_arguments)]
+pub fn run_benchmark(
+    output_mode: OutputMode,
+    transcript: TranscriptKind,
+    seed: Option<u64>,
+    output: Option<&ReportOutput>,
+    repeat: Option<Repeat>,
+    check: CheckMode,
+    verbose: bool
+) -> Result<(), BenchmarkError> {
+    if repeat.is_some_and(|repeat| repeat.rounds == 0) {
+        return Err(BenchmarkError::ParameterMismatch("--repeat must be at least 1".to_string()));
+    }
+
+    // input words per test case
+    let init_s0 = Fr::from(0);
+    let init_s1 = Fr::from(1);
+    let init_s2 = Fr::from(2);
+
+    let k: u32 = 10;
+    let (proof_repeat, repeat) = match repeat {
+        Some(repeat) => (repeat, repeat),
+        None => (Repeat::ONCE, Repeat { rounds: 30, warmup: 0 })
+    };
+
+    // Poseidon circuit struct 
+    let circuit_ps = PoseidonCircuit {
+        s0: Value::known(init_s0),
+        s1: Value::known(init_s1),
+        s2: Value::known(init_s2),
+        output_mode
+    };
+
+    // public outputs come from the native permutation under the active parameter source
+    let parameters = active_source();
+    let expected_ps = poseidon_params::<Fr>()?.permute_native([init_s0, init_s1, init_s2]).to_vec();
+
+    // time the MockProver runtime for Poseidon in milliseconds
+    let public_ps = expected_instances(PermutationKind::Poseidon, [init_s0, init_s1, init_s2], output_mode)?;
+    let (timings_ps, cost_ps) = measure_circuit("Poseidon", k, &circuit_ps, &public_ps, repeat, check)?;
+    let instance_ps = public_ps.column(0);
+
+    // Rescue-Prime circuit struct
+    let circuit_rs = RescueCircuit {
+        s0: Value::known(init_s0),
+        s1: Value::known(init_s1),
+        s2: Value::known(init_s2),
+        output_mode
+    };
+
+    let expected_rs = rescue_params::<Fr>()?.permute_native([init_s0, init_s1, init_s2]).to_vec();
+
+    // time the MockProver runtime for Rescue-Prime in milliseconds
+    let public_rs = expected_instances(PermutationKind::Rescue, [init_s0, init_s1, init_s2], output_mode)?;
+    let (timings_rs, cost_rs) = measure_circuit("Rescue-Prime", k, &circuit_rs, &public_rs, repeat, check)?;
+    let instance_rs = public_rs.column(0);
+
+    // preimage-knowledge statement: same inputs as private witnesses, only state[0] public
+    let preimage_ps = PreimageCircuit::<Fr, PoseidonChip<Fr>> {
+        s0: Value::known(init_s0),
+        s1: Value::known(init_s1),
+        s2: Value::known(init_s2),
+        _marker: PhantomData
+    };
+    let preimage_rs = PreimageCircuit::<Fr, RescueChip<Fr>> {
+        s0: Value::known(init_s0),
+        s1: Value::known(init_s1),
+        s2: Value::known(init_s2),
+        _marker: PhantomData
+    };
+    let (timings_pre_ps, cost_pre_ps) = measure_circuit("Poseidon preimage", k, &preimage_ps, &PublicInputs::digest_only(Digest::from_state(&expected_ps)?), repeat, check)?;
+    let (timings_pre_rs, cost_pre_rs) = measure_circuit("Rescue-Prime preimage", k, &preimage_rs, &PublicInputs::digest_only(Digest::from_state(&expected_rs)?), repeat, check)?;
+
+    let config_ps = PoseidonCircuit::<Fr>::configure(&mut ConstraintSystem::default());
+
+    // both permutations in one circuit over shared columns
+    let dual = DualCircuit { s0: Value::known(init_s0), s1: Value::known(init_s1), s2: Value::known(init_s2) };
+    let instance_dual = compose_instances(&[(0, &expected_ps), (3, &expected_rs)])?;
+    let (timings_dual, cost_dual) = measure_circuit("Poseidon+Rescue-Prime", k, &dual, &instance_dual, repeat, check)?;
+    let separate_rows = BTreeMap::from([
+        ("Poseidon", cost_ps.circuit.get("max_rows").copied().unwrap_or_default()),
+        ("Rescue-Prime", cost_rs.circuit.get("max_rows").copied().unwrap_or_default())
+    ]);
+
+    // the rows of one permutation region next to the previous layout, and the regions of the three circuits
+    let region_rows = vec![measure_region_rows::<PoseidonChip<Fr>>((3, 2))?, measure_region_rows::<RescueChip<Fr>>((6, 4))?];
+    let regions = measure_regions(&circuit_ps, &circuit_rs, &dual)?;
+
+    // keygen with unknown witnesses and real IPA proofs
+    let proofs = measure_pasta_proofs(k, ProvingRng::new(seed), proof_repeat, transcript)?;
+
+    // the Poseidon circuit with the Blake2b and the Poseidon transcript
+    let transcripts = measure_transcripts(k, ProvingRng::new(seed))?;
+
+    // round constants in fixed columns versus advice cells pinned through the constants column
+    let constant_strategies = measure_constant_strategies(k, instance_ps, instance_rs, output_mode, seed)?;
+
+    // the x^5 S-box constrained directly and through a witnessed square
+    let sbox_layouts = measure_sbox_layouts(k, seed)?;
+
+    // row usage and smallest k under SimpleFloorPlanner and V1
+    let floor_planners = measure_floor_planners(seed)?;
+
+    // loading each preset's tables from the embedded limbs and from the strings
+    let constants_loading = measure_embedded_constants()?;
+
+    // Poseidon with the inverse S-box, Anemoi with one and two column pairs, the bar layer variant with 8-bit limbs and
+    // Poseidon with neptune's parameters, after the circuits above so their CSV rows keep their positions
+    let variants = [
+        measure_poseidon_inverse(k, repeat, check)?, measure_anemoi::<1>(k, repeat, check)?, measure_anemoi::<2>(k, repeat, check)?,
+        measure_bars::<8>(k, repeat, check)?, measure_poseidon_neptune(k, repeat, check)?
+    ];
+    let bar_layers = measure_bar_layers()?;
+    let verification = measure_verification_costs(k)?;
+
+    // the parameters are read back from the configured chips so the report matches the circuits
+    let config_rs = RescueCircuit::<Fr>::configure(&mut ConstraintSystem::default());
+    println!(
+        "MDS matrices ({} parameters): Poseidon {:?}, Rescue-Prime {:?}",
+        parameters, poseidon_mds_origin(&config_ps.permutation_params), rescue_mds_origin(&config_rs.permutation_params)
+    );
+
+    let report = BenchmarkReport {
+        k,
+        parameters: parameters.to_string(),
+        seed,
+        circuits: vec![
+            CircuitReport {
+                permutation: "Poseidon",
+                statement: "permutation",
+                output_mode,
+                outputs: hex_words(instance_ps),
+                parameters: config_ps.permutation_params.report(),
+                timings: timings_ps,
+                cost: cost_ps
+            },
+            CircuitReport {
+                permutation: "Rescue-Prime",
+                statement: "permutation",
+                output_mode,
+                outputs: hex_words(instance_rs),
+                parameters: config_rs.permutation_params.report(),
+                timings: timings_rs,
+                cost: cost_rs
+            },
+            CircuitReport {
+                permutation: "Poseidon",
+                statement: "preimage",
+                output_mode: OutputMode::DigestOnly,
+                outputs: hex_words(&expected_ps[..1]),
+                parameters: config_ps.permutation_params.report(),
+                timings: timings_pre_ps,
+                cost: cost_pre_ps
+            },
+            CircuitReport {
+                permutation: "Rescue-Prime",
+                statement: "preimage",
+                output_mode: OutputMode::DigestOnly,
+                outputs: hex_words(&expected_rs[..1]),
+                parameters: config_rs.permutation_params.report(),
+                timings: timings_pre_rs,
+                cost: cost_pre_rs
+            }
+        ].into_iter().chain(variants).collect(),
+        dual: Some(DualReport {
+            timings: timings_dual,
+            cost: cost_dual,
+            separate_rows
+        }),
+        proofs,
+        transcripts,
+        constant_strategies,
+        sbox_layouts,
+        floor_planners,
+        region_rows,
+        regions,
+        bar_layers,
+        constants_loading,
+        verification,
+        analysis: BTreeMap::from([
+            ("Poseidon", analyze::<PoseidonCircuit<Fr>>().at_k(k)),
+            ("Rescue-Prime", analyze::<RescueCircuit<Fr>>().at_k(k)),
+            (AnemoiChip::<Fr, 1>::NAME, analyze::<AnemoiCircuit<Fr, 1>>().at_k(k)),
+            (AnemoiChip::<Fr, 2>::NAME, analyze::<AnemoiCircuit<Fr, 2>>().at_k(k)),
+            (BarsChip::<Fr, 8>::NAME, analyze::<BarsCircuit<Fr, 8>>().at_k(k))
+        ])
+    };
+
+    if let Some(output) = output {
+        report.write_output(output)?;
+    }
+
+    print!("{}", report.timing_table());
+    if verbose {
+        print!("{}", report.region_table());
+    }
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+    Ok(())
+}
+
+// helper function to measure the Anemoi circuit with l = L column pairs on the inputs 0, 1, .., 2l - 1, with the outputs
+// of the native permutation
+fn measure_anemoi<const L: usize>(k: u32, repeat: Repeat, check: CheckMode) -> Result<CircuitReport, BenchmarkError> {
+    let params = anemoi_params::<Fr, L>()?;
+    let x: [Fr; L] = std::array::from_fn(|i| Fr::from(i as u64));
+    let y: [Fr; L] = std::array::from_fn(|i| Fr::from((L + i) as u64));
+    let circuit = AnemoiCircuit { x: x.map(Value::known), y: y.map(Value::known) };
+    let (x_out, y_out) = params.permute_native(x, y);
+    let instance = [x_out, y_out].concat();
+
+    let label = AnemoiChip::<Fr, L>::NAME;
+    let (timings, cost) = measure_circuit(label, k, &circuit, &PublicInputs::from(instance.clone()), repeat, check)?;
+
+    Ok(CircuitReport {
+        permutation: label,
+        statement: "permutation",
+        output_mode: OutputMode::AllState,
+        outputs: hex_words(&instance),
+        parameters: params.report(),
+        timings,
+        cost
+    })
+}
+
+// helper function to measure the bar layer variant with B-bit limbs on the state 0, 1, 2
+fn measure_bars<const B: usize>(k: u32, repeat: Repeat, check: CheckMode) -> Result<CircuitReport, BenchmarkError> {
+    let params = bars_params::<Fr>(B)?;
+    let label = BarsChip::<Fr, B>::NAME;
+    let inputs = [0, 1, 2].map(Fr::from);
+    let circuit = BarsCircuit::<Fr, B> { state: inputs.map(Value::known) };
+    let instance = params.permute_native(inputs).to_vec();
+    let (timings, cost) = measure_circuit(label, k, &circuit, &PublicInputs::from(instance.clone()), repeat, check)?;
+
+    Ok(CircuitReport {
+        permutation: label,
+        statement: "permutation",
+        output_mode: OutputMode::AllState,
+        outputs: hex_words(&instance),
+        parameters: params.report(),
+        timings,
+        cost
+    })
+}
+
+// helper function to report the bar layer variant with B-bit limbs at its smallest k
+fn bar_layer_report<const B: usize>() -> Result<BarLayerReport, BenchmarkError> {
+    let params = bars_params::<Fr>(B)?;
+    let circuit = BarsCircuit::<Fr, B> { state: [0, 1, 2].map(|word| Value::known(Fr::from(word))) };
+    let k = minimal_k(&circuit)?;
+    let label = BarsChip::<Fr, B>::NAME;
+    let chip = BarsChip::<Fr, B>::construct(BarsCircuit::<Fr, B>::configure(&mut ConstraintSystem::default()));
+    let summary = analyze::<BarsCircuit<Fr, B>>();
+    let cost = CostReport::measure(k, &circuit);
+
+    Ok(BarLayerReport {
+        permutation: label,
+        limb_bits: B,
+        limbs: params.limbs,
+        limb_prime: params.limb_prime,
+        limb_alpha: params.limb_alpha,
+        active_limbs: (0..params.limbs).filter(|index| params.active(*index)).count(),
+        table_rows: chip.table_rows(),
+        lookup_arguments: summary.lookups,
+        lookups: chip.lookups_per_permutation(),
+        degree: summary.degree,
+        rows: chip.expected_rows(),
+        k,
+        estimated_proof_size: cost.estimated_proof_size,
+        lookup_proof_size: cost.lookup_proof_size
+    })
+}
+
+// helper function to compare limb widths of the bar layer variant: wider limbs mean fewer limb rows and lookups per
+// permutation but a table twice as large per bit, which sets k once it outgrows the rows
+fn measure_bar_layers() -> Result<Vec<BarLayerReport>, BenchmarkError> {
+    let reports = vec![bar_layer_report::<4>()?, bar_layer_report::<6>()?, bar_layer_report::<8>()?, bar_layer_report::<10>()?];
+    for report in &reports {
+        println!(
+            "{}: {} limbs ({} through x^{} mod {}), table {} rows, {} lookups in {} rows, degree {}, k = {}, proof {} bytes ({} from lookups)",
+            report.permutation, report.limbs, report.active_limbs, report.limb_alpha, report.limb_prime, report.table_rows,
+            report.lookups, report.rows, report.degree, report.k, report.estimated_proof_size, report.lookup_proof_size
+        );
+    }
+
+    Ok(reports)
+}
+
+// helper function to measure Poseidon with the inverse S-box x^-1, read through a parameter file like any other
+// instance
+fn measure_poseidon_inverse(k: u32, repeat: Repeat, check: CheckMode) -> Result<CircuitReport, BenchmarkError> {
+    let params = poseidon_inverse_params::<Fr>()?;
+    let path = std::env::temp_dir().join(format!("permutation_benchmark_inverse_{}.json", std::process::id()));
+    ParameterFile::from_params(&params, &rescue_params::<Fr>()?).write(&path)?;
+    let _source = set_parameter_source(ParameterSource::File(path.clone()));
+    let label = "Poseidon x^-1";
+
+    let inputs = [Fr::from(0), Fr::from(1), Fr::from(2)];
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let circuit = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let instance = params.permute_native(inputs).to_vec();
+    let (timings, cost) = measure_circuit(label, k, &circuit, &PublicInputs::from(instance.clone()), repeat, check)?;
+    let _ = std::fs::remove_file(&path);
+
+    Ok(CircuitReport {
+        permutation: label,
+        statement: "permutation",
+        output_mode: OutputMode::AllState,
+        outputs: hex_words(&instance),
+        parameters: params.report(),
+        timings,
+        cost
+    })
+}
+
+// helper function to measure Poseidon with neptune's parameters for arity 2 on neptune's layout of the inputs 0 and 1:
+// the domain tag 3 in state[0], so that the public state[1] is neptune's digest of [0, 1]
+fn measure_poseidon_neptune(k: u32, repeat: Repeat, check: CheckMode) -> Result<CircuitReport, BenchmarkError> {
+    let params = bls381_neptune()?;
+    let path = std::env::temp_dir().join(format!("permutation_benchmark_neptune_{}.json", std::process::id()));
+    ParameterFile::from_params(&params, &rescue_params::<Fr>()?).write(&path)?;
+    let _source = set_parameter_source(ParameterSource::File(path.clone()));
+    let label = "Poseidon neptune";
+
+    let inputs = [Fr::from(NEPTUNE_ARITY_2_TAG), Fr::from(0), Fr::from(1)];
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let circuit = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let instance = params.permute_native(inputs).to_vec();
+    let (timings, cost) = measure_circuit(label, k, &circuit, &PublicInputs::from(instance.clone()), repeat, check)?;
+    let _ = std::fs::remove_file(&path);
+
+    Ok(CircuitReport {
+        permutation: label,
+        statement: "permutation",
+        output_mode: OutputMode::AllState,
+        outputs: hex_words(&instance),
+        parameters: params.report(),
+        timings,
+        cost
+    })
+}
+
+// prove batches of independent permutations in one proof for each size, at the smallest fitting k, and print the
+// total and per-hash proving times as JSON
+pub fn run_batch(sizes: &[usize], seed: Option<u64>) -> Result<(), BenchmarkError> {
+    if sizes.contains(&0) {
+        return Err(BenchmarkError::ParameterMismatch("batch sizes must be at least 1".to_string()));
+    }
+
+    let mut params = BTreeMap::new();
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = Vec::new();
+    for &batch_size in sizes {
+        reports.push(batch_proving::<PoseidonChip<Fp>>(batch_size, &mut params, &mut rng)?);
+        reports.push(batch_proving::<RescueChip<Fp>>(batch_size, &mut params, &mut rng)?);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// hash transcripts of each size through the sponge and prove the challenge, at the smallest fitting k, and print
+// the rows, rows per absorbed element and proving times as JSON
+pub fn run_transcript(sizes: &[usize], keyed: bool, seed: Option<u64>) -> Result<(), BenchmarkError> {
+    if sizes.contains(&0) {
+        return Err(BenchmarkError::ParameterMismatch("transcript sizes must be at least 1".to_string()));
+    }
+
+    let mut params = BTreeMap::new();
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = Vec::new();
+    for &elements in sizes {
+        reports.push(transcript_proving::<PoseidonChip<Fp>>(elements, keyed, &mut params, &mut rng)?);
+        reports.push(transcript_proving::<RescueChip<Fp>>(elements, keyed, &mut params, &mut rng)?);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// hash messages of each size in bytes through the sponge and prove the digest, at the smallest fitting k, and print
+// the rows, rows per byte and proving times as JSON
+pub fn run_bytes(sizes: &[usize], seed: Option<u64>) -> Result<(), BenchmarkError> {
+    let mut params = BTreeMap::new();
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = Vec::new();
+    for &bytes in sizes {
+        reports.push(bytes_proving::<PoseidonChip<Fp>>(bytes, &mut params, &mut rng)?);
+        reports.push(bytes_proving::<RescueChip<Fp>>(bytes, &mut params, &mut rng)?);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// hash public messages of each number of words from a second instance column through the sponge and prove the digest,
+// at the smallest fitting k, and print the rows, rows per message word and proving times as JSON
+pub fn run_public_message(sizes: &[usize], seed: Option<u64>) -> Result<(), BenchmarkError> {
+    if sizes.contains(&0) {
+        return Err(BenchmarkError::ParameterMismatch("public message sizes must be at least 1".to_string()));
+    }
+
+    let mut params = BTreeMap::new();
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = Vec::new();
+    for &max_len in sizes {
+        reports.push(public_message_proving::<PoseidonChip<Fp>>(max_len, &mut params, &mut rng)?);
+        reports.push(public_message_proving::<RescueChip<Fp>>(max_len, &mut params, &mut rng)?);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// squeeze each number of outputs from a two-element seed in duplex mode and prove them public, at the smallest fitting
+// k, and print the rows, rows per output and proving times as JSON
+pub fn run_stream(outputs: &[usize], seed: Option<u64>) -> Result<(), BenchmarkError> {
+    if outputs.contains(&0) {
+        return Err(BenchmarkError::ParameterMismatch("stream outputs must be at least 1".to_string()));
+    }
+
+    let mut params = BTreeMap::new();
+    let mut rng = ProvingRng::new(seed);
+    let mut reports = Vec::new();
+    for &count in outputs {
+        reports.push(stream_proving::<PoseidonChip<Fp>>(count, &mut params, &mut rng)?);
+        reports.push(stream_proving::<RescueChip<Fp>>(count, &mut params, &mut rng)?);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// build a tree of 2^leaves_log2 leaves natively in both arities (binary with t = 3, 4-ary with t = 5) for both
+// permutations, prove the path of one random leaf to the public root in each, and print the depths, hashes and rows of
+// a full path as JSON
+pub fn run_merkle(leaves_log2: u32, seed: Option<u64>) -> Result<(), BenchmarkError> {
+    if leaves_log2 == 0 || !leaves_log2.is_multiple_of(2) || leaves_log2 > 24 {
+        return Err(BenchmarkError::ParameterMismatch(format!("a tree of 2^{} leaves has no 4-ary shape, use an even exponent from 2 to 24", leaves_log2)));
+    }
+
+    let mut rng = ProvingRng::new(seed);
+    let leaves: Vec<Fp> = (0..1u64 << leaves_log2).map(Fp::from).collect();
+    let index = (rng.next_u64() % leaves.len() as u64) as usize;
+    let mut params = BTreeMap::new();
+    let reports = vec![
+        binary_merkle_report::<PoseidonChip<Fp>>(&leaves, index, &mut params, &mut rng)?,
+        quaternary_merkle_report::<WidePoseidon<Fp, 5>>(&leaves, index, &mut params, &mut rng)?,
+        binary_merkle_report::<RescueChip<Fp>>(&leaves, index, &mut params, &mut rng)?,
+        quaternary_merkle_report::<WideRescue<Fp, 5>>(&leaves, index, &mut params, &mut rng)?
+    ];
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// build a random tree of 2^leaves_log2 leaves natively for both permutations, prove the membership of one random leaf
+// with a random secret (its path to the public root and its public nullifier), print the headline time and rows of the
+// full membership proof and the reports as JSON
+pub fn run_demo_membership(leaves_log2: u32, seed: Option<u64>) -> Result<(), BenchmarkError> {
+    if leaves_log2 == 0 || leaves_log2 > 24 {
+        return Err(BenchmarkError::ParameterMismatch(format!("a tree of 2^{} leaves is out of range, use an exponent from 1 to 24", leaves_log2)));
+    }
+
+    let mut rng = ProvingRng::new(seed);
+    let leaves: Vec<Fp> = (0..1u64 << leaves_log2).map(|_| Fp::random(&mut rng)).collect();
+    let index = (rng.next_u64() % leaves.len() as u64) as usize;
+    let secret = Fp::random(&mut rng);
+    let mut params = BTreeMap::new();
+    let reports = vec![
+        membership_report::<PoseidonChip<Fp>>(&leaves, index, secret, &mut params, &mut rng)?,
+        membership_report::<RescueChip<Fp>>(&leaves, index, secret, &mut params, &mut rng)?
+    ];
+
+    for report in &reports {
+        println!("{} full membership proof: {} ms, {} rows", report.permutation, report.proof.proving_ms, report.rows);
+    }
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// helper function for the tree over a t = 3 chip and the membership proof of one leaf in it
+fn membership_report<P: PermutationChip<Fp>>(
+    leaves: &[Fp],
+    index: usize,
+    secret: Fp,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<MembershipReport, BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let levels = merkle_levels(leaves.to_vec(), 2, |pair| chip.params().hash_native([pair[0], pair[1]]))?;
+    let root = levels[levels.len() - 1][0];
+    let nullifier = chip.params().hash_native([leaves[index], secret]);
+    let circuit = membership_circuit::<Fp, P>(&levels, index, secret);
+    let (k, rows, proof) = prove_at_minimal_k(P::NAME, &circuit, &[&[root, nullifier]], params, rng)?;
+
+    Ok(MembershipReport {
+        permutation: P::NAME,
+        leaves: leaves.len(),
+        depth: levels.len() - 1,
+        permutations: levels.len(),
+        k,
+        rows,
+        proof
+    })
+}
+
+// helper function for the binary tree over a t = 3 chip and the proof of one path in it
+fn binary_merkle_report<P: PermutationChip<Fp>>(
+    leaves: &[Fp],
+    index: usize,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<MerkleReport, BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let start = Instant::now();
+    let levels = merkle_levels(leaves.to_vec(), 2, |pair| chip.params().hash_native([pair[0], pair[1]]))?;
+    let native_tree_ms = start.elapsed().as_millis();
+    let circuit = binary_path_circuit::<Fp, P>(&levels, index);
+    println!("{} binary tree of {} leaves: {} ms", P::NAME, leaves.len(), native_tree_ms);
+    let (k, rows, proof) = prove_at_minimal_k(P::NAME, &circuit, &[&[levels[levels.len() - 1][0]]], params, rng)?;
+
+    Ok(MerkleReport {
+        permutation: P::NAME,
+        arity: 2,
+        width: 3,
+        leaves: leaves.len(),
+        depth: levels.len() - 1,
+        tree_hashes: levels[1..].iter().map(Vec::len).sum(),
+        native_tree_ms,
+        compression_rows: chip.compression_rows(),
+        k,
+        rows,
+        proof
+    })
+}
+
+// helper function for the 4-ary tree over the t = 5 chip and the proof of one path in it
+fn quaternary_merkle_report<W: WidePermutation<Fp, 5>>(
+    leaves: &[Fp],
+    index: usize,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<MerkleReport, BenchmarkError> {
+    let chip = WideChip::<Fp, 5, W>::construct(WideChip::configure_default(&mut ConstraintSystem::default()));
+    let wide = &chip.config.permutation_params;
+    let start = Instant::now();
+    let levels = merkle_levels(leaves.to_vec(), 4, |children| wide.compress_native(children))?;
+    let native_tree_ms = start.elapsed().as_millis();
+    let circuit = quaternary_path_circuit::<Fp, W>(&levels, index);
+    println!("{} 4-ary tree of {} leaves: {} ms", W::NAME, leaves.len(), native_tree_ms);
+    let (k, rows, proof) = prove_at_minimal_k(W::NAME, &circuit, &[&[levels[levels.len() - 1][0]]], params, rng)?;
+
+    Ok(MerkleReport {
+        permutation: W::NAME,
+        arity: 4,
+        width: 5,
+        leaves: leaves.len(),
+        depth: levels.len() - 1,
+        tree_hashes: levels[1..].iter().map(Vec::len).sum(),
+        native_tree_ms,
+        compression_rows: chip.compression_rows(),
+        k,
+        rows,
+        proof
+    })
+}
+
+// prove both permutation circuits at k on pools of each number of threads, keygen included, and print the speedup
+// curves as JSON
+pub fn run_scaling(thread_counts: &[usize], k: u32, seed: Option<u64>) -> Result<(), BenchmarkError> {
+    if thread_counts.is_empty() || thread_counts.contains(&0) {
+        return Err(BenchmarkError::ParameterMismatch("thread counts must be at least 1".to_string()));
+    }
+
+    let params = commitment_params(k)?;
+    let inputs = [0, 1, 2].map(Fp::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let poseidon = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let rescue = RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let reports = vec![
+        scaling_report("Poseidon", &params, &poseidon, expected_instances(PermutationKind::Poseidon, inputs, OutputMode::AllState)?.column(0), thread_counts, seed)?,
+        scaling_report("Rescue-Prime", &params, &rescue, expected_instances(PermutationKind::Rescue, inputs, OutputMode::AllState)?.column(0), thread_counts, seed)?
+    ];
+
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// prove the permutation circuits for every combination of backend, k, output mode and floor planner, up to
+// `max_parallel` at once with the prover on `prover_threads` threads, and print the results in sweep order as JSON
+pub fn run_sweep(
+    backends: &[Backend],
+    ks: &[u32],
+    output_modes: &[OutputMode],
+    floor_planners: &[FloorPlannerKind],
+    max_parallel: usize,
+    prover_threads: Option<usize>,
+    seed: Option<u64>
+) -> Result<(), BenchmarkError> {
+    let reports = sweep(backends, &PermutationKind::all().collect::<Vec<_>>(), ks, output_modes, floor_planners, max_parallel, prover_threads, seed)?;
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+
+    Ok(())
+}
+
+// prove knowledge of a preimage of `digest` with MockProver, reading the three private words from a file or stdin;
+// `endianness` is the byte order of 0x hex values, and of the 96-byte state when the input is `raw` bytes. With `prove`
+// the statement is proved for real instead, with IPA over Pasta on a prover job whose progress is printed, so the
+// digest is then one over Pasta's Fp
+#[allow(clippy::too_many_arguments)]
+pub fn prove_preimage(
+    digest: &str,
+    permutation: PermutationKind,
+    inputs: Option<&Path>,
+    raw: bool,
+    endianness: Endianness,
+    k: u32,
+    prove: bool,
+    seed: Option<u64>
+) -> Result<(), BenchmarkError> {
+    if prove {
+        let (digest, inputs) = read_preimage::<Fp>(digest, inputs, raw, endianness)?;
+        return match permutation {
+            PermutationKind::Poseidon => prove_preimage_job::<PoseidonChip<Fp>>(k, inputs, digest, seed),
+            PermutationKind::Rescue => prove_preimage_job::<RescueChip<Fp>>(k, inputs, digest, seed)
+        };
+    }
+    let (digest, inputs) = read_preimage::<Fr>(digest, inputs, raw, endianness)?;
+
+    // the digest the inputs actually hash to, to explain a rejection
+    let expected = expected_instances(permutation, inputs, OutputMode::DigestOnly)?.digest()?;
+    match permutation {
+        PermutationKind::Poseidon => mock_preimage::<PoseidonChip<Fr>>(k, inputs, digest, expected),
+        PermutationKind::Rescue => mock_preimage::<RescueChip<Fr>>(k, inputs, digest, expected)
+    }
+}
+
+// helper function to parse the digest of prove-preimage and read its three private words from a file or stdin
+fn read_preimage<F: PrimeField>(digest: &str, inputs: Option<&Path>, raw: bool, endianness: Endianness) -> Result<(Digest<F>, [F; 3]), BenchmarkError> {
+    let digest = Digest([parse_word::<F>(digest, endianness)?]);
+
+    let bytes = match inputs {
+        Some(path) => std::fs::read(path)
+            .map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?,
+        None => {
+            let mut bytes = Vec::new();
+            std::io::stdin().read_to_end(&mut bytes)
+                .map_err(|source| BenchmarkError::Io { path: "stdin".to_string(), source })?;
+            bytes
+        }
+    };
+    let inputs = if raw {
+        state_from_bytes::<F>(&bytes, endianness)?
+    } else {
+        let words = String::from_utf8_lossy(&bytes).split_whitespace()
+            .map(|word| parse_word::<F>(word, endianness))
+            .collect::<Result<Vec<F>, BenchmarkError>>()?;
+        let [s0, s1, s2] = words[..] else {
+            return Err(BenchmarkError::ParameterMismatch(format!("expected 3 input words, found {}", words.len())));
+        };
+        [s0, s1, s2]
+    };
+    println!("digest: {}", digest);
+
+    Ok((digest, inputs))
+}
+
+// helper function to prove the preimage statement for one chip over Pasta on a prover job, printing its progress to
+// stderr until it finishes
+fn prove_preimage_job<P: PermutationChip<Fp> + Send + 'static>(k: u32, inputs: [Fp; 3], digest: Digest<Fp>, seed: Option<u64>) -> Result<(), BenchmarkError> {
+    let request = preimage_request::<P>(inputs, k, seed)?;
+    // a proof of a digest the inputs do not hash to would only fail at verification, after the whole proof
+    let expected = request.public_inputs.digest()?;
+    if expected != digest {
+        return Err(BenchmarkError::VerificationFailed(format!("{} preimage rejected: the inputs hash to {}, not {}", P::NAME, expected, digest)));
+    }
+
+    let job = ProverJob::spawn(request);
+    while !job.is_finished() {
+        eprint!("\r{} preimage: {:<14}", P::NAME, job.progress().to_string());
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    eprintln!("\r{} preimage: {:<14}", P::NAME, job.progress().to_string());
+    let artifact = job.join()?;
+    println!("{} preimage proved: {} bytes, proving time {} ms, verification time {} ms", P::NAME, artifact.proof.len(), artifact.proving_ms, artifact.verification_ms);
+
+    Ok(())
+}
+
+// helper function to run the preimage statement for one chip through MockProver
+fn mock_preimage<P: PermutationChip<Fr>>(k: u32, inputs: [Fr; 3], digest: Digest<Fr>, expected: Digest<Fr>) -> Result<(), BenchmarkError> {
+    let circuit = PreimageCircuit::<Fr, P> {
+        s0: Value::known(inputs[0]),
+        s1: Value::known(inputs[1]),
+        s2: Value::known(inputs[2]),
+        _marker: PhantomData
+    };
+
+    check_k(P::NAME, k, &circuit)?;
+    let _span = tracing::info_span!("preimage.mock_prove", permutation = P::NAME, k).entered();
+    let start = Instant::now();
+    let prover = MockProver::run(k, &circuit, PublicInputs::digest_only(digest).to_instances())?;
+    let result = prover.verify();
+    println!("{} preimage MockProver time: {} ms", P::NAME, start.elapsed().as_millis());
+
+    match result {
+        Ok(()) => {
+            println!("{} preimage verified", P::NAME);
+            Ok(())
+        }
+        Err(failures) => {
+            let explanation = explain_instance_mismatch(&failures, &digest.0, &expected.0);
+            let reason = if explanation.is_empty() { summarize_failures(&failures).to_string() } else { explanation.join("; ") };
+            Err(BenchmarkError::VerificationFailed(format!("{} preimage rejected: {}", P::NAME, reason)))
+        }
+    }
+}
+
+// one instance column shared by several chips: each part's words at the instance offset its chip was configured with,
+// rows between the parts zero. Overlapping parts are rejected, as composed circuits reject them in synthesis
+pub fn compose_instances<F: PrimeField>(parts: &[(usize, &[F])]) -> Result<PublicInputs<F>, BenchmarkError> {
+    check_instance_ranges(&parts.iter().map(|(offset, words)| (*offset, words.len())).collect::<Vec<_>>())?;
+    let mut instance = vec![F::ZERO; parts.iter().map(|(offset, words)| offset + words.len()).max().unwrap_or(0)];
+    for (offset, words) in parts {
+        instance[*offset..*offset + words.len()].copy_from_slice(words);
+    }
+
+    Ok(PublicInputs::from(instance))
+}
+
+// expected instance columns of a permutation circuit on `inputs`: the words `output_mode` exposes of the native
+// permutation's output state, with the parameters of the active source. The MockProver, proving and sweep paths all
+// build their instances here. The circuits have a single instance column and keep their input words private, so the
+// result is one column of 3, 2 or 1 words, which `PublicInputs::digest` reads back as a digest of that many words
+pub fn expected_instances<F: PrimeField>(permutation: PermutationKind, inputs: [F; 3], output_mode: OutputMode) -> Result<PublicInputs<F>, BenchmarkError> {
+    let state = match permutation {
+        PermutationKind::Poseidon => poseidon_params::<F>()?.permute_native(inputs),
+        PermutationKind::Rescue => rescue_params::<F>()?.permute_native(inputs)
+    };
+
+    Ok(PublicInputs::from_output(output_mode, &state))
+}
+
+// expected instance columns of a public message circuit with `max_len` message slots: the sponge digest of the message
+// column in the chip's column, then the message column itself (the length, the words and the zero padding), with the
+// parameters of the active source
+pub fn public_message_instances<F: PrimeField>(permutation: PermutationKind, message: &[F], max_len: usize) -> Result<PublicInputs<F>, BenchmarkError> {
+    let column = public_message_column(message, max_len)?;
+    let digest = match permutation {
+        PermutationKind::Poseidon => Sponge::new().hash_native(&poseidon_params::<F>()?, &column),
+        PermutationKind::Rescue => Sponge::new().hash_native(&rescue_params::<F>()?, &column)
+    };
+
+    Ok(PublicInputs::from_instances(vec![vec![digest], column]))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,2285 @@
+use std::sync::Arc;
+use halo2_proofs::{
+    circuit::Layouter,
+    plonk::{Advice, Column, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+use num_bigint::BigUint;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+use super::*;
+use crate::chip::{Number, PermutationInstructions};
+use crate::circuits::{CompressionCircuit, SwapCircuit, TwinCircuit, TranscriptCircuit, MacCircuit, StreamCircuit, BytesCircuit, PublicMessageCircuit, HostCircuit, CollisionClaimCircuit};
+use crate::analysis::{ColumnSummary, PointSet, prover_domain};
+use crate::bars::BarsChipConfig;
+use crate::constants::{POSEIDON_BLS381_T3, POSEIDON_BLS381_T3_NEPTUNE, POSEIDON_BN254_T3_CIRCOM, RESCUE_BLS381_T3};
+use crate::failures::FailureSummary;
+use crate::generate::{MdsOrigin, anemoi_round_count, index_cauchy_mds, poseidon_grain, rescue_constants, rescue_mds, rescue_round_count};
+use crate::gates::{ChipOptions, allocate_columns, allocate_complex_selector, create_gate, linear_combination, matrix_mul_exprs, pow_expression, record_gates};
+use crate::hash::{Hasher, poseidon_hash, rescue_hash};
+use crate::memory::{LOW_MEMORY_THREADS, low_memory_threads, peak_heap, with_low_memory};
+use crate::native::{BarLimb, mds_inverse, mds_mul_native};
+use crate::params::{ArcPosition, BARS_ALGEBRAIC_ROUNDS, Bars, DomainTag, Poseidon, RescuePrime, SboxKind, field_modulus, parse_constants, table_constants,
+    SECURITY_LEVEL, fe_from_bytes, fe_from_hex};
+use crate::poseidon::PoseidonChipConfig;
+use crate::proving::{prove_and_verify_repeated, with_threads, prove_on_backend};
+use crate::report::{csv_columns, csv_field, parse_csv_record, query_label, SelectorReport, GateInventory};
+use crate::presets::neptune_hash;
+use crate::snapshot::synthetic_report;
+use crate::sponge::{BYTES_PER_ELEMENT, PackedChunks, hash_bytes_native, pack_bytes};
+use crate::witness::GeneratedInputs;
+use crate::synthesis::{AdviceCells, FixedCells};
+
+// k of the circuits the tests run at, the k of the benchmark
+const K: u32 = 10;
+
+// the failure summary on a synthetic list: constraints are grouped by gate and located
+// failures by region, only the first distinct locations are kept, and copy constraints to the instance column, other
+// copy constraints and lookups are counted apart, in the Display and the JSON form
+#[test]
+fn failure_summary() -> Result<(), BenchmarkError> {
+    let constraint = |gate: usize, name: &'static str, index: usize| metadata::Constraint::from((metadata::Gate::from((gate, name)), index, ""));
+    let in_region = |index: usize, name: &str, offset: usize| FailureLocation::InRegion { region: metadata::Region::from((index, name)), offset };
+    let failures = vec![
+        VerifyFailure::ConstraintNotSatisfied { constraint: constraint(0, "full round", 0), location: in_region(1, "permutation", 3), cell_values: vec![] },
+        VerifyFailure::ConstraintNotSatisfied { constraint: constraint(0, "full round", 2), location: in_region(1, "permutation", 4), cell_values: vec![] },
+        VerifyFailure::ConstraintNotSatisfied { constraint: constraint(4, "select", 0), location: in_region(3, "swap", 0), cell_values: vec![] },
+        VerifyFailure::ConstraintPoisoned { constraint: metadata::Constraint::from((metadata::Gate::from((2, "mds")), 1, "word 1")) },
+        VerifyFailure::Permutation { column: metadata::Column::from((Any::Instance, 0)), location: FailureLocation::OutsideRegion { row: 2 } },
+        VerifyFailure::Permutation { column: metadata::Column::from((Any::Advice, 1)), location: in_region(3, "swap", 1) },
+        VerifyFailure::Permutation { column: metadata::Column::from((Any::Advice, 1)), location: in_region(3, "swap", 1) },
+        VerifyFailure::Lookup { lookup_index: 0, location: FailureLocation::OutsideRegion { row: 9 } }
+    ];
+
+    let summary = summarize_failures(&failures);
+    let expected = FailureSummary {
+        total: 8,
+        gates: BTreeMap::from([("full round".to_string(), 2), ("mds".to_string(), 1), ("select".to_string(), 1)]),
+        regions: BTreeMap::from([("permutation".to_string(), 2), ("swap".to_string(), 3)]),
+        first_rows: [
+            "in Region 1 ('permutation') at offset 3", "in Region 1 ('permutation') at offset 4", "in Region 3 ('swap') at offset 0",
+            "outside any region, on row 2", "in Region 3 ('swap') at offset 1"
+        ].map(String::from).to_vec(),
+        instance: 1,
+        permutation: 2,
+        lookups: 1
+    };
+    assert_eq!(summary, expected);
+    assert_eq!(
+        summary.to_string(),
+        "8 failures; gates: 'full round' x2, 'mds' x1, 'select' x1; regions: 'permutation' x2, 'swap' x3; instance: 1; copy constraints: 2; \
+         lookups: 1; first at: in Region 1 ('permutation') at offset 3, in Region 1 ('permutation') at offset 4, in Region 3 ('swap') at offset 0, \
+         outside any region, on row 2, in Region 3 ('swap') at offset 1"
+    );
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!((&json["gates"]["full round"], &json["regions"]["swap"], &json["instance"]), (&2.into(), &3.into(), &1.into()));
+
+    assert_eq!(summarize_failures(&[]), FailureSummary::default());
+    assert_eq!(summarize_failures(&failures[4..5]).to_string(), "1 failure; instance: 1; first at: outside any region, on row 2");
+
+    Ok(())
+}
+
+// helper function to check the compression circuit against the native hash, and that the domain tag separates digests
+fn check_compression<P: PermutationChip<Fr>>(k: u32, inputs: [Fr; 2]) -> Result<(), BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let digest = chip.params().hash_native(inputs);
+
+    let circuit = CompressionCircuit::<Fr, P> {
+        a0: Value::known(inputs[0]),
+        a1: Value::known(inputs[1]),
+        _marker: PhantomData
+    };
+    assert_eq!(MockProver::run(k, &circuit, vec![vec![digest]])?.verify(), Ok(()));
+
+    // the same message under a different tag must give a different digest
+    let other_tag = DomainTag::Custom(chip.params().domain_tag() + Fr::from(1)).value();
+    assert_ne!(chip.params().permute_native([inputs[0], inputs[1], other_tag])[0], digest);
+    println!("{} compression digest: {}", P::NAME, fe_to_hex(&digest, Endianness::Big));
+
+    Ok(())
+}
+
+// domain-separated compression of the two rate words
+#[test]
+fn compression() -> Result<(), BenchmarkError> {
+    check_compression::<PoseidonChip<Fr>>(K, [Fr::ZERO, Fr::ONE])?;
+    check_compression::<RescueChip<Fr>>(K, [Fr::ZERO, Fr::ONE])
+}
+
+// is-equal gate witnessed cell by cell, so that a wrong inverse can be placed next to a and b
+#[derive(Debug)]
+struct RawIsEqualCircuit {
+    a: Fr,
+    b: Fr,
+    inv: Fr,
+    out: Fr
+}
+
+impl Circuit<Fr> for RawIsEqualCircuit {
+    type Config = PoseidonChipConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        RawIsEqualCircuit { a: Fr::ZERO, b: Fr::ZERO, inv: Fr::ZERO, out: Fr::ZERO }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoseidonChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let circuit_params = &config.circuit_params;
+        layouter.assign_region(
+            || "raw_is_equal", |mut region| {
+                circuit_params.s_is_equal.enable(&mut region, 0)?;
+                for (column, value) in [(0, self.a), (1, self.b), (2, self.inv)] {
+                    region.assign_advice(|| "input", circuit_params.advice[column], 0, || Value::known(value))?;
+                }
+                region.assign_advice(|| "out", circuit_params.advice[0], 1, || Value::known(self.out))?;
+                Ok(())
+            }
+        )
+    }
+}
+
+// the is-equal gate itself: for a != b only the true inverse with out = 0 passes, a zero
+// inverse or out = 1 fails; for a = b, out = 1 passes with any inverse and out = 0 fails
+#[test]
+fn is_equal_gate() -> Result<(), BenchmarkError> {
+    let k = K;
+    let [a, b, _] = poseidon_params::<Fr>()?.permute_native([0, 1, 2].map(Fr::from));
+    let inv = (a - b).invert().expect("the inputs differ");
+    let raw = |a: Fr, b: Fr, inv: Fr, out: u64| RawIsEqualCircuit { a, b, inv, out: Fr::from(out) };
+    assert_eq!(MockProver::run(k, &raw(a, b, inv, 0), vec![vec![]])?.verify(), Ok(()));
+    assert!(MockProver::run(k, &raw(a, b, Fr::ZERO, 0), vec![vec![]])?.verify().is_err());
+    assert!(MockProver::run(k, &raw(a, b, inv, 1), vec![vec![]])?.verify().is_err());
+    assert_eq!(MockProver::run(k, &raw(a, a, inv, 1), vec![vec![]])?.verify(), Ok(()));
+    assert!(MockProver::run(k, &raw(a, a, inv, 0), vec![vec![]])?.verify().is_err());
+    Ok(())
+}
+
+// permutation whose outputs are pinned to expected constants with assert_output, so it has no instance values
+struct AssertedCircuit<P> {
+    inputs: [Fr; 3],
+    expected: [Fr; 3],
+    _marker: PhantomData<P>
+}
+
+impl<P: PermutationChip<Fr>> Circuit<Fr> for AssertedCircuit<P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        AssertedCircuit { inputs: [Fr::ZERO; 3], expected: self.expected, _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let chip = P::from_config(config);
+        let [s0, s1, s2] = self.inputs.map(Value::known);
+        let outputs = chip.permute(layouter.namespace(|| "permutation"), s0, s1, s2)?;
+        for (row, (num, expected)) in outputs.into_iter().zip(self.expected).enumerate() {
+            chip.assert_output(layouter.namespace(|| format!("assert_s{}", row)), num, expected)?;
+        }
+
+        Ok(())
+    }
+}
+
+// helper function to check assert_output without an instance vector: the native outputs verify, and a wrong expected
+// word fails only on the copy constraint to the constants column
+fn check_assert_output<P: PermutationChip<Fr>>(k: u32, inputs: [Fr; 3]) -> Result<(), BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let expected = chip.params().permute_native(inputs);
+    let circuit = AssertedCircuit::<P> { inputs, expected, _marker: PhantomData };
+    assert_eq!(MockProver::run(k, &circuit, vec![vec![]])?.verify(), Ok(()));
+
+    for word in 0..3 {
+        let mut wrong = expected;
+        wrong[word] += Fr::ONE;
+        let circuit = AssertedCircuit::<P> { inputs, expected: wrong, _marker: PhantomData };
+        let failures = MockProver::run(k, &circuit, vec![vec![]])?.verify().expect_err("a wrong expected output is rejected");
+        let constants = metadata::Column::from((Any::Fixed, 0));
+        assert!(failures.iter().all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+        assert!(failures.iter().any(|failure| matches!(failure, VerifyFailure::Permutation { column, .. } if *column == constants)));
+        let summary = summarize_failures(&failures);
+        assert!(summary.gates.is_empty() && summary.has_permutation_failures() && !summary.has_instance_failures(), "{}", summary);
+    }
+
+    Ok(())
+}
+
+// outputs pinned to constants instead of instance rows
+#[test]
+fn asserted_outputs() -> Result<(), BenchmarkError> {
+    check_assert_output::<PoseidonChip<Fr>>(K, [0, 1, 2].map(Fr::from))?;
+    check_assert_output::<RescueChip<Fr>>(K, [0, 1, 2].map(Fr::from))
+}
+
+// Poseidon permutation configured with the first CONSTANTS round constants, padded with zeros, and without the
+// builder's check that the table fits the round counts
+#[derive(Default)]
+struct ConstantTableCircuit<const CONSTANTS: usize>;
+
+impl<const CONSTANTS: usize> Circuit<Fr> for ConstantTableCircuit<CONSTANTS> {
+    type Config = PoseidonChipConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        ConstantTableCircuit
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        let params = poseidon_params().map(|mut params| {
+            params.round_constants.resize(CONSTANTS, Fr::ZERO);
+            params
+        });
+        PoseidonChip::configure(meta, advice, constants, instance, params, ChipOptions::default())
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let chip = PoseidonChip::from_config(config);
+        let [s0, s1, s2] = [0, 1, 2].map(|word| Value::known(Fr::from(word)));
+        chip.permute(layouter.namespace(|| "permutation"), s0, s1, s2).map(|_| ())
+    }
+}
+
+// the Poseidon witness path against a constants table that does not fit the round counts:
+// one constant short fails synthesis in the last round, three extra fail it after the last round, and the messages
+// logged with the failures name the round and the count
+#[test]
+fn constant_table() -> Result<(), BenchmarkError> {
+    let k = K;
+    assert_eq!(MockProver::run(k, &ConstantTableCircuit::<195>, vec![vec![]])?.verify(), Ok(()));
+    assert!(matches!(MockProver::run(k, &ConstantTableCircuit::<194>, vec![vec![]]), Err(Error::Synthesis)));
+    assert!(matches!(MockProver::run(k, &ConstantTableCircuit::<198>, vec![vec![]]), Err(Error::Synthesis)));
+
+    let mut params = poseidon_params::<Fr>()?;
+    params.round_constants.truncate(194);
+    assert!(matches!(
+        params.round_constants_at(64, 192),
+        Err(BenchmarkError::ParameterMismatch(message)) if message == "Poseidon round 64 needs round constants 192..195, the table has 194"
+    ));
+    params.round_constants.resize(198, Fr::ZERO);
+    assert!(matches!(
+        params.check_constants_consumed(195),
+        Err(BenchmarkError::ParameterMismatch(message)) if message == "Poseidon rounds consumed 195 of the 198 round constants"
+    ));
+
+    Ok(())
+}
+
+// chips whose MDS matrix is set per chip when they are configured, with a backdoor to the matrix their witness path
+// reads, which only the MDS consistency check below writes to
+trait MdsOverride: PermutationChip<Fr> {
+    // the gate that embeds the MDS matrix
+    const MDS_GATE: &'static str;
+
+    // configure the chip with the benchmark parameters but the given MDS matrix
+    fn configure_with_mds(meta: &mut ConstraintSystem<Fr>, mds: [[Fr; 3]; 3]) -> Self::Config;
+
+    fn witness_mds(config: &mut Self::Config) -> &mut [[Fr; 3]; 3];
+}
+
+impl MdsOverride for PoseidonChip<Fr> {
+    const MDS_GATE: &'static str = "ML_gate";
+
+    fn configure_with_mds(meta: &mut ConstraintSystem<Fr>, mds: [[Fr; 3]; 3]) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        let params = poseidon_params().map(|params| Poseidon { mds, ..params }).and_then(|params| params.validate().map(|()| params));
+        PoseidonChip::configure(meta, advice, constants, instance, params, ChipOptions::default())
+    }
+
+    fn witness_mds(config: &mut Self::Config) -> &mut [[Fr; 3]; 3] {
+        &mut config.permutation_params.mds
+    }
+}
+
+impl MdsOverride for RescueChip<Fr> {
+    const MDS_GATE: &'static str = "ML_ARC_gate";
+
+    fn configure_with_mds(meta: &mut ConstraintSystem<Fr>, mds: [[Fr; 3]; 3]) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        let params = rescue_params().and_then(|params| {
+            let mds_inv = mds_inverse(&mds).ok_or_else(|| BenchmarkError::ParameterMismatch("the Rescue-Prime MDS matrix is singular".to_string()))?;
+            let params = RescuePrime { mds, mds_inv, ..params };
+            params.validate().map(|()| params)
+        });
+        RescueChip::configure(meta, advice, constants, instance, params, ChipOptions::default())
+    }
+
+    fn witness_mds(config: &mut Self::Config) -> &mut [[Fr; 3]; 3] {
+        &mut config.permutation_params.mds
+    }
+}
+
+// permutation of (0, 1, 2) with all three outputs public, on a chip configured with the index Cauchy MDS matrix; with
+// MISMATCHED the first two rows of the matrix the witness path reads are swapped after configure, so the witness no
+// longer follows the matrix embedded in the gate
+struct MdsCircuit<P, const MISMATCHED: bool>(PhantomData<P>);
+
+impl<P: MdsOverride, const MISMATCHED: bool> Circuit<Fr> for MdsCircuit<P, MISMATCHED> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        MdsCircuit(PhantomData)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let mut config = P::configure_with_mds(meta, index_cauchy_mds());
+        if MISMATCHED {
+            P::witness_mds(&mut config).swap(0, 1);
+        }
+        config
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let chip = P::from_config(config);
+        let [s0, s1, s2] = [0, 1, 2].map(|word| Value::known(Fr::from(word)));
+        let outputs = chip.permute(layouter.namespace(|| "permutation"), s0, s1, s2)?;
+        chip.expose_outputs(layouter.namespace(|| "outputs"), outputs, 3)
+    }
+}
+
+// helper function to check that a chip's MDS matrix is set once for its gate and its witness: a chip configured with
+// another matrix matches the native permutation under that matrix, and a witness path reading a different matrix than
+// the gate embeds fails MockProver in the MDS gate and in no other gate
+fn check_mds_override<P: MdsOverride>(k: u32) -> Result<(), BenchmarkError> {
+    let chip = P::from_config(P::configure_with_mds(&mut ConstraintSystem::default(), index_cauchy_mds()));
+    let expected = chip.params().permute_native([0, 1, 2].map(Fr::from)).to_vec();
+    assert_eq!(MockProver::run(k, &MdsCircuit::<P, false>(PhantomData), vec![expected.clone()])?.verify(), Ok(()), "{} with the index Cauchy MDS matrix", P::NAME);
+
+    let failures = MockProver::run(k, &MdsCircuit::<P, true>(PhantomData), vec![expected])?.verify()
+        .expect_err("a witness MDS matrix other than the gate's is rejected");
+    let gates: BTreeSet<String> = failures.iter().filter_map(|failure| match failure {
+        VerifyFailure::ConstraintNotSatisfied { constraint, .. } => Some(constraint.to_string()),
+        _ => None
+    }).collect();
+    assert!(!gates.is_empty() && gates.iter().all(|constraint| constraint.contains(&format!("'{}'", P::MDS_GATE))), "{} MDS mismatch: {:?}", P::NAME, gates);
+    let summary = summarize_failures(&failures);
+    assert_eq!(summary.gates.keys().collect::<Vec<_>>(), [P::MDS_GATE], "{}", summary);
+    assert_eq!(summary.total, summary.gates[P::MDS_GATE] + summary.instance + summary.permutation, "{}", summary);
+
+    Ok(())
+}
+
+// the MDS matrix is set per chip, and a witness path reading another matrix than the gate's is caught
+#[test]
+fn mds_override() -> Result<(), BenchmarkError> {
+    check_mds_override::<PoseidonChip<Fr>>(K)?;
+    check_mds_override::<RescueChip<Fr>>(K)
+}
+
+// helper function to check the collision claim: one state twice gives bit 1, two different states give bit 0, and
+// the opposite bit is rejected in both cases
+fn check_collision_claim<P: PermutationChip<Fr>>(k: u32) -> Result<(), BenchmarkError> {
+    let claim = |left: [u64; 3], right: [u64; 3]| CollisionClaimCircuit::<Fr, P> {
+        left: left.map(|word| Value::known(Fr::from(word))),
+        right: right.map(|word| Value::known(Fr::from(word))),
+        _marker: PhantomData
+    };
+    for (circuit, equal) in [(claim([0, 1, 2], [0, 1, 2]), Fr::ONE), (claim([0, 1, 2], [0, 1, 3]), Fr::ZERO)] {
+        assert_eq!(MockProver::run(k, &circuit, vec![vec![equal]])?.verify(), Ok(()));
+        assert!(MockProver::run(k, &circuit, vec![vec![Fr::ONE - equal]])?.verify().is_err());
+    }
+
+    Ok(())
+}
+
+// in-circuit digest equality
+#[test]
+fn collision_claim() -> Result<(), BenchmarkError> {
+    check_collision_claim::<PoseidonChip<Fr>>(K)?;
+    check_collision_claim::<RescueChip<Fr>>(K)
+}
+
+// helper function to check the conditional swap: both bit values swap correctly, a non-boolean bit is rejected
+fn check_swap<P: PermutationChip<Fr>>(k: u32, a: Fr, b: Fr) -> Result<(), BenchmarkError> {
+    let swap = |bit: u64| SwapCircuit::<Fr, P> {
+        a: Value::known(a),
+        b: Value::known(b),
+        bit: Value::known(Fr::from(bit)),
+        _marker: PhantomData
+    };
+    assert_eq!(MockProver::run(k, &swap(0), vec![vec![a, b]])?.verify(), Ok(()));
+    assert_eq!(MockProver::run(k, &swap(1), vec![vec![b, a]])?.verify(), Ok(()));
+
+    // with bit = 2 the outputs follow the select formula, so only the boolean constraint can fail
+    let two = Fr::from(2);
+    let outputs = vec![a + two * (b - a), b + two * (a - b)];
+    assert!(MockProver::run(k, &swap(2), vec![outputs])?.verify().is_err());
+
+    Ok(())
+}
+
+// conditional select / swap on the shared advice columns, on the first two output words of each permutation
+#[test]
+fn swap() -> Result<(), BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let [a, b, _] = poseidon_params::<Fr>()?.permute_native(inputs);
+    check_swap::<PoseidonChip<Fr>>(K, a, b)?;
+    let [a, b, _] = rescue_params::<Fr>()?.permute_native(inputs);
+    check_swap::<RescueChip<Fr>>(K, a, b)
+}
+
+// helper function to check the batch circuit: every digest matches the native permutation of its own input, and a
+// batch whose digests are out of order is rejected
+fn check_batch<P: PermutationChip<Fr>>(batch_size: u64) -> Result<(), BenchmarkError> {
+    let inputs: Vec<[Fr; 3]> = (0..batch_size).map(|i| [3 * i, 3 * i + 1, 3 * i + 2].map(Fr::from)).collect();
+    let circuit = BatchCircuit::<Fr, P> {
+        inputs: inputs.iter().map(|input| input.map(Value::known)).collect(),
+        _marker: PhantomData
+    };
+
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let mut digests = inputs.iter().map(|input| Ok(chip.params().permute_native(*input)[0])).collect::<Result<Vec<_>, BenchmarkError>>()?;
+    let k = minimal_k(&circuit)?;
+    assert_eq!(MockProver::run(k, &circuit, vec![digests.clone()])?.verify(), Ok(()));
+
+    digests.swap(0, 1);
+    assert!(MockProver::run(k, &circuit, vec![digests])?.verify().is_err());
+
+    Ok(())
+}
+
+// four independent permutations in one circuit, each digest checked against the native permutation
+#[test]
+fn batch() -> Result<(), BenchmarkError> {
+    check_batch::<PoseidonChip<Fr>>(4)?;
+    check_batch::<RescueChip<Fr>>(4)
+}
+
+// helper function to check the transcript sponge: the circuit's challenge matches the native sponge, a wrong challenge
+// is rejected, and the padding keeps inputs that differ by trailing zeros apart
+fn check_transcript<P: PermutationChip<Fr>>(elements: u64) -> Result<(), BenchmarkError> {
+    let values: Vec<Fr> = (1..=elements).map(Fr::from).collect();
+    let circuit = TranscriptCircuit::<Fr, P> { elements: values.iter().copied().map(Value::known).collect(), _marker: PhantomData };
+
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let challenge = Sponge::new().hash_native(chip.params(), &values);
+    let k = minimal_k(&circuit)?;
+    assert_eq!(MockProver::run(k, &circuit, vec![vec![challenge]])?.verify(), Ok(()));
+    assert!(MockProver::run(k, &circuit, vec![vec![challenge + Fr::ONE]])?.verify().is_err());
+
+    let extended = [values.as_slice(), &[Fr::ZERO]].concat();
+    assert_ne!(Sponge::new().hash_native(chip.params(), &extended), challenge);
+
+    Ok(())
+}
+
+// transcripts through the sponge: an odd count (padding fills the last block) and an even one (padding adds a block)
+#[test]
+fn transcripts() -> Result<(), BenchmarkError> {
+    for elements in [5, 6] {
+        check_transcript::<PoseidonChip<Fr>>(elements)?;
+        check_transcript::<RescueChip<Fr>>(elements)?;
+    }
+
+    Ok(())
+}
+
+// helper function to check the keyed sponge: different keys give different tags, and neither is the unkeyed output;
+// the circuit's tag matches the native one, and a wrong key does not verify against the public tag
+fn check_mac<P: PermutationChip<Fr>>(words: u64) -> Result<(), BenchmarkError> {
+    let message: Vec<Fr> = (1..=words).map(Fr::from).collect();
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let [key, other_key] = [Fr::from(7), Fr::from(8)];
+    let tag = Sponge::new_keyed(key).hash_native(chip.params(), &message);
+    assert_ne!(Sponge::new_keyed(other_key).hash_native(chip.params(), &message), tag);
+    assert_ne!(Sponge::new().hash_native(chip.params(), &message), tag);
+
+    let circuit = |key: Fr| MacCircuit::<Fr, P> { key: Value::known(key), message: message.iter().copied().map(Value::known).collect(), _marker: PhantomData };
+    let instance = [&[tag], message.as_slice()].concat();
+    let k = minimal_k(&circuit(key))?;
+    assert_eq!(MockProver::run(k, &circuit(key), vec![instance.clone()])?.verify(), Ok(()));
+    assert!(MockProver::run(k, &circuit(other_key), vec![instance])?.verify().is_err());
+
+    Ok(())
+}
+
+// keyed MACs over an odd and an even number of words
+#[test]
+fn macs() -> Result<(), BenchmarkError> {
+    for words in [5, 6] {
+        check_mac::<PoseidonChip<Fr>>(words)?;
+        check_mac::<RescueChip<Fr>>(words)?;
+    }
+
+    Ok(())
+}
+
+// helper function to check the public message circuit: one circuit with `max_len` slots verifies the empty, a half
+// and a full message against the instances public_message_instances builds, a changed word anywhere in either column
+// (the digest, the length, a message word or a padding slot) is rejected, and a message longer than the slots is refused
+fn check_public_message<P: PermutationChip<Fr>>(permutation: PermutationKind, max_len: usize) -> Result<(), BenchmarkError> {
+    let circuit = PublicMessageCircuit::<Fr, P> { max_len, _marker: PhantomData };
+    let k = minimal_k(&circuit)?;
+    let message: Vec<Fr> = (1..=max_len as u64 + 1).map(Fr::from).collect();
+    for len in [0, max_len / 2, max_len] {
+        let instances = public_message_instances(permutation, &message[..len], max_len)?;
+        assert_eq!(MockProver::run(k, &circuit, instances.to_instances())?.verify(), Ok(()), "{} public message of {} words", P::NAME, len);
+    }
+
+    let instances = public_message_instances(permutation, &message[..max_len / 2], max_len)?.to_instances();
+    for (column, words) in instances.iter().enumerate() {
+        for row in 0..words.len() {
+            let mut changed = instances.clone();
+            changed[column][row] += Fr::ONE;
+            assert!(MockProver::run(k, &circuit, changed)?.verify().is_err(), "{} public message with row {} of column {} changed", P::NAME, row, column);
+        }
+    }
+    assert!(public_message_instances(permutation, &message, max_len).is_err());
+
+    Ok(())
+}
+
+// public messages absorbed from a second instance column, in 8 slots
+#[test]
+fn public_messages() -> Result<(), BenchmarkError> {
+    check_public_message::<PoseidonChip<Fr>>(PermutationKind::Poseidon, 8)?;
+    check_public_message::<RescueChip<Fr>>(PermutationKind::Rescue, 8)
+}
+
+// helper function to check byte message hashing: the packing is canonical for bytes that would exceed the modulus as
+// 32-byte limbs, the digests of a few fixed messages (empty, packed lengths of both parities) are pinned, messages
+// differing by a trailing zero byte hash apart, and the circuit's digest matches and rejects a wrong one
+fn check_bytes<P: PermutationChip<Fr>>() -> Result<(), BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let ones = [0xffu8; 32];
+    assert!(fe_from_bytes::<Fr>(&ones, Endianness::Little).is_err());
+    let packed: Vec<Fr> = pack_bytes(&ones);
+    assert_eq!(packed, [Fr::from(32), fe_from_bytes(&ones[..31], Endianness::Little)?, Fr::from(0xff)]);
+    assert_eq!(pack_bytes::<Fr>(&[]), [Fr::ZERO]);
+
+    // digests of the empty message, "abc", 31 and 32 bytes of 0xff
+    let pinned = match P::NAME {
+        "Poseidon" => [
+            "0x1b323db4217b4f9c4c07148c896a1ba836b13366cf0344d74e24f4154e5c1588",
+            "0x545fb8e1d07e08bd542be854a7c9d4954505b2d43bd4214433e88166346061f2",
+            "0x673b015a84e85636d3e6fc06e76bad463c038d45a648b9dd3a5419b0baf9ad87",
+            "0x29ff161b4e0513b9dbc334664d43838e6ea15aa33deee3e0448af3087bc6831b"
+        ],
+        _ => [
+            "0x33ba13be5e6c3d1158508392be5cc78fe4f41300b976f2de79bcb9b71ae124fa",
+            "0x5546374a02ac8539c2b40b40f2d016e301149be5057417b3946d9533957a8fae",
+            "0x11b0daad9b47aff14a58ae398f7bd96c9d7f6268f6ea91905703598ddf096dde",
+            "0x54d7bdbb57d3163139971134bdbf60f6ce47979ad4a57d5f1e600c0e6936780c"
+        ]
+    };
+    let messages: [&[u8]; 4] = [b"", b"abc", &ones[..31], &ones];
+    for (message, pinned) in messages.into_iter().zip(pinned) {
+        let digest = hash_bytes_native(chip.params(), message);
+        assert_eq!(digest, fe_from_hex::<Fr>(pinned, Endianness::Big)?, "{} digest of a {}-byte message", P::NAME, message.len());
+        assert_ne!(hash_bytes_native(chip.params(), &[message, &[0]].concat()), digest);
+
+        let packed: Vec<Fr> = pack_bytes(message);
+        assert_eq!(packed.len(), 1 + message.len().div_ceil(BYTES_PER_ELEMENT));
+        let circuit = BytesCircuit::<Fr, P> { length: message.len(), chunks: packed[1..].iter().copied().map(Value::known).collect(), _marker: PhantomData };
+        let k = minimal_k(&circuit)?;
+        assert_eq!(MockProver::run(k, &circuit, vec![vec![digest]])?.verify(), Ok(()));
+        assert!(MockProver::run(k, &circuit, vec![vec![digest + Fr::ONE]])?.verify().is_err());
+    }
+
+    Ok(())
+}
+
+// byte messages packed into field elements, against pinned digests
+#[test]
+fn byte_messages() -> Result<(), BenchmarkError> {
+    check_bytes::<PoseidonChip<Fr>>()?;
+    check_bytes::<RescueChip<Fr>>()
+}
+
+// message length of the streaming memory measurement
+const STREAMING_BYTES: usize = 256 * 1024;
+
+// helper function to check streaming absorption: a message absorbed as it is packed hashes to the digest of its packed
+// elements absorbed from a buffer, natively over a 256 KiB message and in MockProver over a short one, where the
+// streamed and buffered circuits also lay out the same rows; a batch whose states are generated as they are assigned
+// verifies the digests of the same states. Laying out the 256 KiB message circuit is measured for its peak heap with
+// every chunk packed up front and with each chunk packed when its block is absorbed
+fn check_streaming<P: PermutationChip<Fr>>() -> Result<(), BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let message: Arc<[u8]> = (0..STREAMING_BYTES).map(|i| (i * 7) as u8).collect();
+    let digest = hash_bytes_native(chip.params(), &message);
+    assert_eq!(Sponge::new().hash_native(chip.params(), &pack_bytes::<Fr>(&message)), digest, "{} streamed and buffered digests", P::NAME);
+
+    let short: Arc<[u8]> = message[..200].into();
+    let short_digest = hash_bytes_native(chip.params(), &short);
+    let buffered = BytesCircuit::<Fr, P> { length: short.len(), chunks: pack_bytes::<Fr>(&short)[1..].iter().copied().map(Value::known).collect(), _marker: PhantomData };
+    let streamed = BytesCircuit::<Fr, P, _> { length: short.len(), chunks: PackedChunks::new(short.clone()), _marker: PhantomData };
+    assert_eq!(layout_rows(&streamed)?, layout_rows(&buffered)?);
+    let k = minimal_k(&streamed)?;
+    assert_eq!(MockProver::run(k, &streamed, vec![vec![short_digest]])?.verify(), Ok(()));
+    assert_eq!(MockProver::run(k, &buffered, vec![vec![short_digest]])?.verify(), Ok(()));
+    assert!(MockProver::run(k, &streamed, vec![vec![short_digest + Fr::ONE]])?.verify().is_err());
+
+    let generate: fn(usize) -> [Fr; 3] = |i| [3 * i, 3 * i + 1, 3 * i + 2].map(|word| Fr::from(word as u64));
+    let batch = BatchCircuit::<Fr, P, _> { inputs: GeneratedInputs::new(4, generate), _marker: PhantomData };
+    let digests = (0..4).map(|i| Ok(chip.params().permute_native(generate(i))[0])).collect::<Result<Vec<Fr>, BenchmarkError>>()?;
+    let k = minimal_k(&batch)?;
+    assert_eq!(MockProver::run(k, &batch, vec![digests.clone()])?.verify(), Ok(()));
+    assert!(MockProver::run(k, &batch, vec![digests.into_iter().rev().collect()])?.verify().is_err());
+
+    let (rows_buffered, peak_buffered) = peak_heap(|| {
+        let chunks = pack_bytes::<Fr>(&message)[1..].iter().copied().map(Value::known).collect();
+        layout_rows(&BytesCircuit::<Fr, P> { length: message.len(), chunks, _marker: PhantomData })
+    });
+    let (rows_streamed, peak_streamed) = peak_heap(|| {
+        layout_rows(&BytesCircuit::<Fr, P, _> { length: message.len(), chunks: PackedChunks::new(message.clone()), _marker: PhantomData })
+    });
+    let rows = rows_buffered?;
+    assert_eq!(rows_streamed?, rows);
+    match (peak_buffered, peak_streamed) {
+        (Some(buffered), Some(streamed)) => {
+            assert!(streamed < buffered, "{} streaming peaks at {} bytes, buffered at {}", P::NAME, streamed, buffered);
+            println!(
+                "streaming: {} hash of a {} KiB message, {} rows, synthesis peaks at {} KiB of heap with the chunks packed up front and {} KiB streamed",
+                P::NAME, STREAMING_BYTES / 1024, rows, buffered / 1024, streamed / 1024
+            );
+        }
+        _ => println!("streaming: {} hash of a {} KiB message, {} rows, heap not measured", P::NAME, STREAMING_BYTES / 1024, rows)
+    }
+
+    Ok(())
+}
+
+// the same messages absorbed as they are packed, and a batch generated as it is assigned
+#[test]
+fn streaming() -> Result<(), BenchmarkError> {
+    check_streaming::<PoseidonChip<Fr>>()?;
+    check_streaming::<RescueChip<Fr>>()
+}
+
+// k of the commitment parameters cached before the low-memory check's batch, which that batch never proves at
+const LOW_MEMORY_SPARE_K: u32 = 4;
+
+// low-memory mode: a batch of Poseidon permutations proves and verifies out of it and in it,
+// where it runs on LOW_MEMORY_THREADS threads and the parameters cached for another k are released, and a quick check of
+// the Poseidon circuit generates its witness without 2^k-row columns. The two heap peaks are printed rather than
+// compared, since the counts are process-wide
+#[test]
+fn low_memory_proofs() -> Result<(), BenchmarkError> {
+    let (k, seed) = (K, Some(0));
+    let mut peaks = Vec::new();
+    for low in [false, true] {
+        let threads = if low { low_memory_threads(None) } else { None };
+        let mut params = BTreeMap::from([(LOW_MEMORY_SPARE_K, commitment_params(LOW_MEMORY_SPARE_K)?)]);
+        let report = with_low_memory(low, || with_threads(threads, || batch_proving::<PoseidonChip<Fp>>(8, &mut params, ProvingRng::new(seed))))??;
+        assert_eq!(report.low_memory, low);
+        assert_eq!(params.contains_key(&LOW_MEMORY_SPARE_K), !low, "parameters for another k cached in low-memory mode: {}", low);
+        if low && cfg!(feature = "multicore") {
+            assert_eq!(report.proof.threads, LOW_MEMORY_THREADS);
+        }
+        peaks.push(report.peak_heap_bytes.map_or_else(|| "not counted".to_string(), |bytes| format!("{} KiB", bytes >> 10)));
+    }
+
+    let input = [0, 1, 2].map(Fr::from);
+    let circuit = PoseidonCircuit::new(input.map(Value::known), OutputMode::DigestOnly)?;
+    let digest = poseidon_params::<Fr>()?.permute_native(input)[0];
+    with_low_memory(true, || measure_circuit("Poseidon", k, &circuit, &PublicInputs::digest_only(Digest([digest])), Repeat::ONCE, CheckMode::Quick))?;
+    println!("low-memory mode: a batch of 8 proves and verifies, peak heap {} in the default mode and {} in low-memory mode", peaks[0], peaks[1]);
+
+    Ok(())
+}
+
+// helper function to check the duplex stream: the circuit's outputs match the native stream, whose first word is the
+// sponge hash of the seed, a different seed gives different outputs, and a wrong output is rejected
+fn check_stream<P: PermutationChip<Fr>>(outputs: usize) -> Result<(), BenchmarkError> {
+    let seed = [Fr::from(3), Fr::from(4)];
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let stream = Sponge::new().squeeze_many_native(chip.params(), &seed, outputs);
+    assert_eq!(stream.len(), outputs);
+    assert_eq!(stream[0], Sponge::new().hash_native(chip.params(), &seed));
+
+    let other = Sponge::new().squeeze_many_native(chip.params(), &[Fr::from(3), Fr::from(5)], outputs);
+    assert!(stream.iter().zip(&other).all(|(a, b)| a != b));
+
+    let circuit = StreamCircuit::<Fr, P> { seed: seed.map(Value::known).to_vec(), outputs, _marker: PhantomData };
+    let k = minimal_k(&circuit)?;
+    assert_eq!(MockProver::run(k, &circuit, vec![stream.clone()])?.verify(), Ok(()));
+    let mut wrong = stream;
+    wrong[outputs - 1] += Fr::ONE;
+    assert!(MockProver::run(k, &circuit, vec![wrong])?.verify().is_err());
+
+    Ok(())
+}
+
+// streams squeezed from a seed in duplex mode: an odd count (half of the last state is dropped) and an even one
+#[test]
+fn squeezed_streams() -> Result<(), BenchmarkError> {
+    for outputs in [5, 6] {
+        check_stream::<PoseidonChip<Fr>>(outputs)?;
+        check_stream::<RescueChip<Fr>>(outputs)?;
+    }
+
+    Ok(())
+}
+
+// helper function to check the Merkle paths of a 16-leaf tree in both arities: for leaves at every position of their
+// group, the binary path over the t = 3 chip and the 4-ary path over the t = 5 chip reach the natively computed root,
+// and a wrong root or a wrong position bit is rejected
+fn check_merkle<P: PermutationChip<Fr>, W: WidePermutation<Fr, 5>>() -> Result<(), BenchmarkError> {
+    let leaves: Vec<Fr> = (0..16u64).map(Fr::from).collect();
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let binary = merkle_levels(leaves.clone(), 2, |pair| chip.params().hash_native([pair[0], pair[1]]))?;
+    let wide = W::load()?;
+    let quaternary = merkle_levels(leaves, 4, |children| wide.compress_native(children))?;
+    assert_eq!((binary.len() - 1, quaternary.len() - 1), (4, 2));
+
+    for index in [0, 7, 9, 14] {
+        let circuit = binary_path_circuit::<Fr, P>(&binary, index);
+        let k = minimal_k(&circuit)?;
+        let root = binary[4][0];
+        assert_eq!(MockProver::run(k, &circuit, vec![vec![root]])?.verify(), Ok(()), "{} binary path of leaf {}", P::NAME, index);
+        assert!(MockProver::run(k, &circuit, vec![vec![root + Fr::ONE]])?.verify().is_err());
+        let mut flipped = circuit;
+        flipped.bits[0] = flipped.bits[0].map(|bit| Fr::ONE - bit);
+        assert!(MockProver::run(k, &flipped, vec![vec![root]])?.verify().is_err());
+
+        let circuit = quaternary_path_circuit::<Fr, W>(&quaternary, index);
+        let k = minimal_k(&circuit)?;
+        let root = quaternary[2][0];
+        assert_eq!(MockProver::run(k, &circuit, vec![vec![root]])?.verify(), Ok(()), "{} 4-ary path of leaf {}", W::NAME, index);
+        assert!(MockProver::run(k, &circuit, vec![vec![root + Fr::ONE]])?.verify().is_err());
+        let mut flipped = circuit;
+        flipped.bits[0][1] = flipped.bits[0][1].map(|bit| Fr::ONE - bit);
+        assert!(MockProver::run(k, &flipped, vec![vec![root]])?.verify().is_err());
+    }
+
+    Ok(())
+}
+
+// Merkle paths in a binary tree over t = 3 and a 4-ary tree over t = 5, against the native trees
+#[test]
+fn merkle_paths() -> Result<(), BenchmarkError> {
+    check_merkle::<PoseidonChip<Fr>, WidePoseidon<Fr, 5>>()?;
+    check_merkle::<RescueChip<Fr>, WideRescue<Fr, 5>>()
+}
+
+// helper function to check the membership circuit on a random 16-leaf tree: a valid proof with the native root and
+// nullifier verifies, a wrong sibling, a flipped path bit, another secret or a wrong public nullifier is rejected, and
+// the nullifier is H(leaf, secret) natively, the same for the same leaf and secret and different for another secret
+fn check_membership<P: PermutationChip<Fr>>() -> Result<(), BenchmarkError> {
+    let mut rng = ChaCha20Rng::seed_from_u64(1144);
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let leaves: Vec<Fr> = (0..16).map(|_| Fr::random(&mut rng)).collect();
+    let levels = merkle_levels(leaves.clone(), 2, |pair| chip.params().hash_native([pair[0], pair[1]]))?;
+    let root = levels[4][0];
+    let secret = Fr::random(&mut rng);
+
+    let nullifier = chip.params().hash_native([leaves[5], secret]);
+    assert_eq!(nullifier, chip.params().hash_native([leaves[5], secret]));
+    assert_ne!(nullifier, chip.params().hash_native([leaves[5], secret + Fr::ONE]));
+    assert_ne!(nullifier, chip.params().hash_native([leaves[6], secret]));
+
+    let circuit = membership_circuit::<Fr, P>(&levels, 5, secret);
+    let k = minimal_k(&circuit)?;
+    assert_eq!(MockProver::run(k, &circuit, vec![vec![root, nullifier]])?.verify(), Ok(()), "{} membership of leaf 5", P::NAME);
+    assert!(MockProver::run(k, &circuit, vec![vec![root, nullifier + Fr::ONE]])?.verify().is_err());
+
+    let mut wrong = membership_circuit::<Fr, P>(&levels, 5, secret);
+    wrong.siblings[2] = wrong.siblings[2].map(|sibling| sibling + Fr::ONE);
+    assert!(MockProver::run(k, &wrong, vec![vec![root, nullifier]])?.verify().is_err());
+    let mut flipped = membership_circuit::<Fr, P>(&levels, 5, secret);
+    flipped.bits[1] = flipped.bits[1].map(|bit| Fr::ONE - bit);
+    assert!(MockProver::run(k, &flipped, vec![vec![root, nullifier]])?.verify().is_err());
+    let other = membership_circuit::<Fr, P>(&levels, 5, secret + Fr::ONE);
+    assert!(MockProver::run(k, &other, vec![vec![root, nullifier]])?.verify().is_err());
+
+    Ok(())
+}
+
+// membership proofs: a binary path to the public root and a public nullifier bound to the same leaf
+#[test]
+fn membership() -> Result<(), BenchmarkError> {
+    check_membership::<PoseidonChip<Fr>>()?;
+    check_membership::<RescueChip<Fr>>()
+}
+
+// the wide instances at t = 3 against the generated instances of the t = 3 chips, which come
+// from the same generators, and the t = 5 ones against their own round schedule
+#[test]
+fn wide_params() -> Result<(), BenchmarkError> {
+    let input = [0, 1, 2].map(Fr::from);
+    let _source = set_parameter_source(ParameterSource::Generated);
+    assert_eq!(WidePoseidon::<Fr, 3>::load()?.permute_native(input), poseidon_params::<Fr>()?.permute_native(input));
+    assert_eq!(WideRescue::<Fr, 3>::load()?.permute_native(input), rescue_params::<Fr>()?.permute_native(input));
+
+    let poseidon = WidePoseidon::<Fr, 5>::load()?;
+    assert_eq!(poseidon.trace_native([Fr::ZERO; 5]).len(), 1 + 2 * (poseidon.full_rounds + poseidon.partial_rounds));
+    let rescue = WideRescue::<Fr, 5>::load()?;
+    assert_eq!(rescue.trace_native([Fr::ZERO; 5]).len(), 1 + 4 * rescue.rounds);
+
+    Ok(())
+}
+
+// helper function to check the validation of k: below the chip's min_k the preimage statement and the measurement are
+// rejected before synthesis with an error naming the minimum, the minimum itself works, and a MockProver run that
+// bypasses the validation fails with the typed out-of-rows error instead of a prover error
+fn check_k_validation<P: PermutationChip<Fr>>() -> Result<(), BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let circuit = PreimageCircuit::<Fr, P> { s0: Value::known(inputs[0]), s1: Value::known(inputs[1]), s2: Value::known(inputs[2]), _marker: PhantomData };
+    let minimum = P::min_k(1);
+    assert_eq!(minimal_k(&circuit)?, minimum, "{} min_k", P::NAME);
+    let digest = circuit_digest::<P>(inputs)?;
+
+    let error = mock_preimage::<P>(minimum - 1, inputs, Digest([digest]), Digest([digest])).expect_err("a k below the minimum");
+    assert!(matches!(error, BenchmarkError::KTooSmall { k, minimum: needed, .. } if k == minimum - 1 && needed == minimum), "{:?}", error);
+    let message = error.to_string();
+    assert!(message.starts_with(P::NAME) && message.contains(&format!("needs k >= {}", minimum)), "{}", message);
+    mock_preimage::<P>(minimum, inputs, Digest([digest]), Digest([digest]))?;
+
+    let bypassed = MockProver::run(minimum - 1, &circuit, vec![vec![digest]]).map(|_| ()).map_err(BenchmarkError::from);
+    assert!(matches!(bypassed, Err(BenchmarkError::NotEnoughRows { k }) if k == minimum - 1), "{:?}", bypassed);
+
+    let measured = measure_circuit(P::NAME, minimum - 1, &circuit, &PublicInputs::digest_only(Digest([digest])), Repeat::ONCE, CheckMode::Full);
+    assert!(matches!(measured, Err(BenchmarkError::KTooSmall { .. })));
+
+    Ok(())
+}
+
+// a k below the smallest that fits is rejected up front, naming the minimum
+#[test]
+fn k_validation() -> Result<(), BenchmarkError> {
+    check_k_validation::<PoseidonChip<Fr>>()?;
+    check_k_validation::<RescueChip<Fr>>()
+}
+
+// helper function for the digest of the preimage statement over a chip's default parameters
+fn circuit_digest<P: PermutationChip<Fr>>(inputs: [Fr; 3]) -> Result<Fr, BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    Ok(chip.params().permute_native(inputs)[0])
+}
+
+// circuit assigning one select row directly, so that an output inconsistent with the bit can be witnessed
+#[derive(Debug)]
+struct RawSelectCircuit {
+    bit: Fr,
+    a: Fr,
+    b: Fr,
+    out: Fr
+}
+
+impl Circuit<Fr> for RawSelectCircuit {
+    type Config = PoseidonChipConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        RawSelectCircuit { bit: Fr::ZERO, a: Fr::ZERO, b: Fr::ZERO, out: Fr::ZERO }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoseidonChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let circuit_params = &config.circuit_params;
+        layouter.assign_region(
+            || "raw_select", |mut region| {
+                circuit_params.s_select.enable(&mut region, 0)?;
+                for (column, value) in [(0, self.bit), (1, self.a), (2, self.b)] {
+                    region.assign_advice(|| "input", circuit_params.advice[column], 0, || Value::known(value))?;
+                }
+                region.assign_advice(|| "out", circuit_params.advice[0], 1, || Value::known(self.out))?;
+                Ok(())
+            }
+        )
+    }
+}
+
+// the select gate itself: a consistent row passes, an output that disagrees with the bit fails
+#[test]
+fn select_gate() -> Result<(), BenchmarkError> {
+    let k = K;
+    let [a, b, _] = poseidon_params::<Fr>()?.permute_native([0, 1, 2].map(Fr::from));
+    let consistent = RawSelectCircuit { bit: Fr::ONE, a, b, out: a };
+    assert_eq!(MockProver::run(k, &consistent, vec![vec![]])?.verify(), Ok(()));
+    let inconsistent = RawSelectCircuit { bit: Fr::ONE, a, b, out: b };
+    assert!(MockProver::run(k, &inconsistent, vec![vec![]])?.verify().is_err());
+
+    Ok(())
+}
+
+// circuit looking up one private value in a 4-bit range table, so that the lookup counts of the constraint system
+// summary and the cost model have a nonzero case small enough to price by hand
+#[derive(Debug, Default)]
+struct RangeLookupCircuit {
+    value: Value<Fr>
+}
+
+impl Circuit<Fr> for RangeLookupCircuit {
+    type Config = (Column<Advice>, TableColumn, Selector);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let (value, table, s_lookup) = (meta.advice_column(), meta.lookup_table_column(), allocate_complex_selector(meta));
+        meta.lookup(|meta| vec![(meta.query_selector(s_lookup) * meta.query_advice(value, Rotation::cur()), table)]);
+        (value, table, s_lookup)
+    }
+
+    fn synthesize(&self, (value, table, s_lookup): Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range_table", |mut table_layouter| {
+                for row in 0..RANGE_TABLE_ROWS {
+                    table_layouter.assign_cell(|| "range", table, row, || Value::known(Fr::from(row as u64)))?;
+                }
+                Ok(())
+            }
+        )?;
+        layouter.assign_region(
+            || "range_lookup", |mut region| {
+                s_lookup.enable(&mut region, 0)?;
+                region.assign_advice(|| "value", value, 0, || self.value).map(|_| ())
+            }
+        )
+    }
+}
+
+const RANGE_TABLE_ROWS: usize = 16;
+
+// the lookup counts: none in the permutation circuits under either S-box layout, and one
+// argument over a 16-row table in the range circuit, whose commitments and evaluations the cost model prices
+#[test]
+fn lookup_counts() -> Result<(), BenchmarkError> {
+    for layout in [SboxLayout::Direct, SboxLayout::Squared] {
+        for summary in with_sbox_layout(layout, || [analyze::<PoseidonCircuit<Fr>>(), analyze::<RescueCircuit<Fr>>()]) {
+            assert_eq!((summary.lookups, summary.lookup_table_rows, summary.shuffles), (0, 0, 0), "lookups under {:?}", layout);
+        }
+    }
+
+    let summary = analyze::<RangeLookupCircuit>();
+    assert_eq!((summary.lookups, summary.lookup_table_rows, summary.shuffles), (1, RANGE_TABLE_ROWS, 0));
+    let k = 6;
+    let circuit = RangeLookupCircuit { value: Value::known(Fr::from(11)) };
+    assert_eq!(MockProver::run(k, &circuit, vec![])?.verify(), Ok(()));
+    let outside = RangeLookupCircuit { value: Value::known(Fr::from(RANGE_TABLE_ROWS as u64)) };
+    assert!(MockProver::run(k, &outside, vec![])?.verify().is_err());
+
+    // three commitments (permuted input, permuted table, product) and five evaluations per lookup argument
+    let cost = CostReport::measure(k, &circuit);
+    assert_eq!(cost.lookup_proof_size, 3 * 48 + 5 * 32);
+    let cost = CostReport::measure(10, &PoseidonCircuit::<Fr>::default());
+    assert_eq!(cost.lookup_proof_size, 0);
+
+    Ok(())
+}
+
+// pin the Poseidon gate inventory (name, constraints, degree, queried cells), so that layout
+// changes to the shared gates show up here before they show up in the benchmark numbers
+#[test]
+fn gate_inventory() {
+    let state = "advice[0]@0 advice[0]@1 advice[1]@0 advice[1]@1 advice[2]@0 advice[2]@1";
+    let constants = "fixed[0]@0 fixed[1]@0 fixed[2]@0";
+    let expected = [
+        ("ML_gate", 3, 2, state.to_string()),
+        ("Select_gate", 2, 3, "advice[0]@0 advice[0]@1 advice[1]@0 advice[2]@0".to_string()),
+        ("Is_equal_gate", 2, 3, "advice[0]@0 advice[0]@1 advice[1]@0 advice[2]@0".to_string()),
+        ("PS_full_round_gate", 3, 6, format!("{} {}", state, constants)),
+        ("PS_partial_round_gate", 3, 6, format!("{} {}", state, constants))
+    ];
+
+    let inventory = GateInventory::collect::<Fr, PoseidonCircuit<Fr>>();
+    let actual: Vec<_> = inventory.gates.iter()
+        .map(|gate| (gate.name, gate.constraints, gate.degree, gate.queries.iter().cloned().collect::<Vec<_>>().join(" ")))
+        .collect();
+    assert_eq!(actual, expected);
+    assert_eq!((inventory.total_constraints, inventory.max_degree, inventory.distinct_queries), (13, 6, 9));
+}
+
+// the gate expression helpers without MockProver: pow_expression, linear_combination and
+// matrix_mul_exprs over three queried advice cells, evaluated with Expression::evaluate at seeded random points, give
+// what the same arithmetic gives on the field elements, at the degree the gates count on
+#[test]
+fn expression_helpers() {
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let advice: [Column<Advice>; 3] = [(); 3].map(|()| meta.advice_column());
+    let mut cells = None;
+    meta.create_gate("expressions", |meta| {
+        let queried = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+        cells = Some(queried.clone());
+        vec![queried[0].clone()]
+    });
+    let cells = cells.expect("the gate queries the cells");
+
+    let mut rng = ChaCha20Rng::seed_from_u64(1140);
+    for _ in 0..4 {
+        let point: [Fr; 3] = [(); 3].map(|()| Fr::random(&mut rng));
+        let values: BTreeMap<String, Fr> = (0..3).map(|i| (format!("advice[{}]@0", i), point[i])).collect();
+        let evaluate = |expression: &Expression<Fr>| expression.evaluate(
+            &|constant| constant,
+            &|_| unreachable!("the helpers query no selectors"),
+            &|_| unreachable!("the helpers query no fixed cells"),
+            &|query| values[&query_label("advice", &format!("{:?}", query))],
+            &|_| unreachable!("the helpers query no instance cells"),
+            &|a| -a,
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|a, scalar| a * scalar
+        );
+
+        for alpha in [1, 2, 5, 7] {
+            let power = pow_expression(cells[0].clone(), alpha);
+            assert_eq!(evaluate(&power), point[0].pow_vartime([alpha]));
+            assert_eq!(power.degree(), alpha as usize);
+        }
+
+        let coeffs: [Fr; 3] = [(); 3].map(|()| Fr::random(&mut rng));
+        let combination = linear_combination(&coeffs, &cells);
+        assert_eq!(evaluate(&combination), coeffs[0] * point[0] + coeffs[1] * point[1] + coeffs[2] * point[2]);
+        assert_eq!(combination.degree(), 1);
+        assert_eq!(evaluate(&linear_combination(&[], &[])), Fr::ZERO);
+
+        let mds: [[Fr; 3]; 3] = [(); 3].map(|()| [(); 3].map(|()| Fr::random(&mut rng)));
+        let product = matrix_mul_exprs(&mds, &cells);
+        assert_eq!(product.each_ref().map(evaluate), mds_mul_native(&mds, &point));
+        assert!(product.iter().all(|word| word.degree() == 1));
+    }
+}
+
+// the constraint system summary of the Poseidon circuit: its gates by name, the column
+// counts, the rotations and the degree, and one constraint per recorded constraint, rendered with column names
+#[test]
+fn circuit_analysis() {
+    let summary = analyze::<PoseidonCircuit<Fr>>();
+    let names: Vec<&str> = summary.gates.iter().map(|gate| gate.name).collect();
+    for name in ["ML_gate", "PS_full_round_gate", "PS_partial_round_gate"] {
+        assert!(names.contains(&name), "the Poseidon summary has no gate {}", name);
+    }
+    assert_eq!(summary.columns, ColumnSummary { advice: 3, fixed: 3, instance: 1, selectors: 5, equality: 7 });
+    assert_eq!(summary.rotations["advice"], BTreeSet::from([0, 1]));
+    assert_eq!(summary.rotations["fixed"], BTreeSet::from([0]));
+    assert_eq!((summary.lookups, summary.lookup_table_rows, summary.shuffles, summary.degree), (0, 0, 0, 6));
+
+    let inventory = GateInventory::collect::<Fr, PoseidonCircuit<Fr>>();
+    for (gate, report) in summary.gates.iter().zip(&inventory.gates) {
+        assert_eq!(gate.constraints.len(), report.constraints, "{} constraints", gate.name);
+    }
+    let ml_gate = &summary.gates[0].constraints[0];
+    assert!(ml_gate.starts_with("selector[") && ml_gate.contains("advice[0]@1"), "ML_gate renders as {}", ml_gate);
+}
+
+// the prover's domains: the Poseidon circuit at k = 10 has degree 6, so its quotient has 5
+// pieces and the gates are evaluated on a 2^13 coset; one proof transforms its 3 advice columns, its instance column and
+// its 2 permutation products (7 columns in chunks of 4). The count is the same over Pasta. The squared S-box layout has
+// degree 4, which halves the extended domain of both permutations; both layouts are printed
+#[test]
+fn prover_domains() {
+    let domain = analyze::<PoseidonCircuit<Fr>>().at_k(10).prover.expect("at_k fills in the prover domain");
+    assert_eq!(
+        (domain.degree, domain.quotient_degree, domain.extended_k, domain.iffts, domain.extended_ffts, domain.extended_iffts),
+        (6, 5, 13, 6, 6, 1)
+    );
+    assert_eq!(domain.butterflies, 6 * (512 * 10 + 4096 * 13) + 4096 * 13);
+    assert_eq!(prover_domain::<Fp, PoseidonCircuit<Fp>>(10), domain);
+
+    for layout in [SboxLayout::Direct, SboxLayout::Squared] {
+        let summaries = with_sbox_layout(layout, || [("Poseidon", analyze::<PoseidonCircuit<Fr>>()), ("Rescue-Prime", analyze::<RescueCircuit<Fr>>())]);
+        for (name, summary) in summaries {
+            let domain = summary.at_k(10).prover.expect("at_k fills in the prover domain");
+            assert_eq!(domain.extended_k, match layout { SboxLayout::Direct => 13, SboxLayout::Squared => 12 }, "{} under {:?}", name, layout);
+            println!(
+                "{} prover at k = 10 ({:?} S-box): degree {}, extended k {}, {} FFTs of 2^10 and {} of 2^{} points, {} butterflies",
+                name, layout, domain.degree, domain.extended_k, domain.iffts, domain.extended_ffts + domain.extended_iffts, domain.extended_k, domain.butterflies
+            );
+        }
+    }
+}
+
+// the chip embedded in a host circuit: the host's gate and the chip share three advice
+// columns, the chip enables nothing, and both verify in one MockProver run. The permutation argument has the three
+// advice columns, the instance column and the host's constants column only, against 7 for the chip on its own
+#[test]
+fn host_circuit() -> Result<(), BenchmarkError> {
+    let params = poseidon_params()?;
+    let (a, b) = (Fr::from(3), Fr::from(5));
+    let digest = params.permute_native([a, a * b, params.domain_tag()])[0];
+    let circuit = HostCircuit { a: Value::known(a), b: Value::known(b) };
+    let k = minimal_k(&circuit)?;
+    assert_eq!(MockProver::run(k, &circuit, vec![vec![digest, a * b]])?.verify(), Ok(()));
+    assert!(MockProver::run(k, &circuit, vec![vec![digest, a * b + Fr::ONE]])?.verify().is_err());
+
+    let cost = CostReport::measure(k, &circuit);
+    assert_eq!((cost.circuit["advice_columns"], cost.circuit["permutation_cols"]), (3, 5));
+    println!("host circuit digest: {}", fe_to_hex(&digest, Endianness::Big));
+    Ok(())
+}
+
+// chips sharing an instance column: two Poseidon permutations with the second chip's outputs
+// at offset 3 verify against both output states composed at rows 0..3 and 3..6, and not against the states swapped.
+// With the second chip at offset 2 its rows overlap the first chip's, which synthesis rejects before MockProver
+// checks anything, and so does compose_instances
+#[test]
+fn instance_offsets() -> Result<(), BenchmarkError> {
+    let inputs = [[0, 1, 2], [3, 4, 5]].map(|words| words.map(Fr::from));
+    let outputs: [Digest<Fr, 3>; 2] = [expected_instances(PermutationKind::Poseidon, inputs[0], OutputMode::AllState)?.digest()?,
+        expected_instances(PermutationKind::Poseidon, inputs[1], OutputMode::AllState)?.digest()?];
+    let circuit = TwinCircuit::<Fr, 3> { first: inputs[0].map(Value::known), second: inputs[1].map(Value::known) };
+    let k = minimal_k(&circuit)?;
+    let instances = compose_instances(&[(0, &outputs[0].0), (3, &outputs[1].0)])?;
+    assert_eq!(instances.column(0).len(), 6);
+    assert_eq!(MockProver::run(k, &circuit, instances.to_instances())?.verify(), Ok(()));
+    let swapped = compose_instances(&[(0, &outputs[1].0), (3, &outputs[0].0)])?;
+    assert!(MockProver::run(k, &circuit, swapped.into())?.verify().is_err());
+
+    let overlapping = TwinCircuit::<Fr, 2> { first: circuit.first, second: circuit.second };
+    assert!(matches!(MockProver::run(k, &overlapping, vec![vec![Fr::ZERO; 5]]), Err(Error::Synthesis)));
+    assert!(matches!(compose_instances(&[(0, &outputs[0].0), (2, &outputs[1].0)]), Err(BenchmarkError::ParameterMismatch(_))));
+
+    Ok(())
+}
+
+// helper function to check the chip's capacity predictions against actual layouts: compression rows, sponge absorb
+// rows for 1 to 6 elements, and min_k for batches, which must run under MockProver at min_k and not fit below it
+fn check_capacity<P: PermutationChip<Fr>>() -> Result<(), BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let compression = CompressionCircuit::<Fr, P> { a0: Value::known(Fr::ONE), a1: Value::known(Fr::ONE), _marker: PhantomData };
+    assert_eq!(layout_rows(&compression)?, chip.compression_rows(), "{} compression rows", P::NAME);
+    for elements in 1..=6 {
+        let transcript = TranscriptCircuit::<Fr, P> { elements: vec![Value::known(Fr::ONE); elements], _marker: PhantomData };
+        assert_eq!(layout_rows(&transcript)?, chip.absorb_rows(elements), "{} absorb rows for {} elements", P::NAME, elements);
+    }
+
+    for size in [1, 4, 16] {
+        let inputs = vec![[Fr::ZERO, Fr::ONE, Fr::from(2)]; size];
+        let digest = chip.params().permute_native(inputs[0])[0];
+        let circuit = BatchCircuit::<Fr, P> { inputs: inputs.iter().map(|input| input.map(Value::known)).collect(), _marker: PhantomData };
+        let k = P::min_k(size);
+        assert_eq!(minimal_k(&circuit)?, k, "{} min_k for {} permutations", P::NAME, size);
+        assert_eq!(MockProver::run(k, &circuit, vec![vec![digest; size]])?.verify(), Ok(()));
+        assert!(MockProver::run(k - 1, &circuit, vec![vec![digest; size]]).is_err(), "{} batch of {} fits below min_k", P::NAME, size);
+        println!("{} min_k for {} permutations: {}", P::NAME, size, k);
+    }
+
+    Ok(())
+}
+
+// compression, sponge and batch row predictions, for sizing k before synthesis
+#[test]
+fn capacity() -> Result<(), BenchmarkError> {
+    check_capacity::<PoseidonChip<Fr>>()?;
+    check_capacity::<RescueChip<Fr>>()
+}
+
+// parameter construction: the builders reproduce the benchmark instances and reject
+// missing or inconsistent fields, and malformed constants tables are rejected with the offending index
+#[test]
+fn parameter_validation() -> Result<(), BenchmarkError> {
+    let poseidon = Poseidon::<Fr>::builder().full_rounds(8).partial_rounds(57).alpha(5).build()?;
+    assert_eq!((poseidon.n, poseidon.round_constants.len()), (195, 195));
+    let rescue = RescuePrime::<Fr>::builder().rounds(14).alpha(5).build()?;
+    assert_eq!(
+        rescue.alpha_inv.to_string(),
+        "20974350070050476191779096203274386335076221000211055129041463479975432473805"
+    );
+
+    assert!(matches!(
+        Poseidon::<Fr>::builder().partial_rounds(57).alpha(5).build(),
+        Err(BenchmarkError::MissingParameter("full_rounds"))
+    ));
+    assert!(matches!(
+        RescuePrime::<Fr>::builder().rounds(14).build(),
+        Err(BenchmarkError::MissingParameter("alpha"))
+    ));
+    // the preset tables hold 195 Poseidon and 84 Rescue-Prime constants
+    assert!(matches!(
+        Poseidon::<Fr>::builder().full_rounds(8).partial_rounds(56).alpha(5).build(),
+        Err(BenchmarkError::ParameterMismatch(_))
+    ));
+    // R_F = 7 with R_P = 58 has the right number of constants, but the full rounds cannot split evenly around the
+    // partial ones: the builder rejects it, and so does validating an instance changed after building
+    assert!(matches!(
+        Poseidon::<Fr>::builder().full_rounds(7).partial_rounds(58).alpha(5).build(),
+        Err(BenchmarkError::ParameterMismatch(message)) if message.contains("7 is odd")
+    ));
+    let odd = Poseidon { full_rounds: 7, partial_rounds: 58, ..poseidon.clone() };
+    assert!(matches!(odd.validate(), Err(BenchmarkError::ParameterMismatch(_))));
+    let full_rounds: Vec<usize> = (0..65).filter(|round| poseidon.is_full_round(*round)).collect();
+    assert_eq!(full_rounds, [0, 1, 2, 3, 61, 62, 63, 64]);
+    assert!(matches!(
+        RescuePrime::<Fr>::builder().rounds(14).alpha(3).build(),
+        Err(BenchmarkError::ParameterMismatch(_))
+    ));
+
+    // the preset round count is the security formula's for m = 3, capacity 1, 128 bits and x^5; a formula change
+    // has to update this pin and the preset constants table
+    assert_eq!(rescue_round_count(3, 1, 128, 5), 14);
+    assert_eq!((rescue.rounds, rescue.secure_rounds), (14, 14));
+
+    // the Anemoi round counts of the reference implementation's table for x^5 and 128 bits, for l = 1 and 2
+    assert_eq!(anemoi_round_count(128, 1, 5)?, 21);
+    assert_eq!(anemoi_round_count(128, 2, 5)?, 14);
+    assert!(matches!(anemoi_round_count(128, 1, 13), Err(BenchmarkError::Unsupported(_))));
+    let constants = |rounds: usize| rescue_constants::<Fr, 3>(rounds, 1, 128);
+    assert!(matches!(
+        RescuePrime::<Fr>::builder().rounds(13).alpha(5).round_constants(constants(13)).build(),
+        Err(BenchmarkError::ParameterMismatch(_))
+    ));
+    let reduced = RescuePrime::<Fr>::builder().unchecked_rounds(4).alpha(5).round_constants(constants(4)).build()?;
+    assert_eq!((reduced.rounds, reduced.secure_rounds), (4, 14));
+
+    // the preset MDS matrices are the reference derivations over BLS12-381 (circomlib's over BN254 are checked with its
+    // vectors); the index Cauchy matrix is another instance, and a matrix from neither derivation is custom
+    assert_eq!(poseidon_mds_origin(&poseidon), MdsOrigin::Grain);
+    assert_eq!(rescue_mds_origin(&rescue), MdsOrigin::Vandermonde);
+    let index_cauchy = Poseidon::<Fr>::builder().full_rounds(8).partial_rounds(57).alpha(5).mds(index_cauchy_mds()).build()?;
+    assert_ne!(index_cauchy.mds, poseidon.mds);
+    assert_eq!(poseidon_mds_origin(&index_cauchy), MdsOrigin::IndexCauchy);
+    let [row0, row1, row2] = poseidon.mds;
+    let swapped = Poseidon::<Fr>::builder().full_rounds(8).partial_rounds(57).alpha(5).mds([row1, row0, row2]).build()?;
+    assert_eq!(poseidon_mds_origin(&swapped), MdsOrigin::Custom);
+
+    // a singular matrix is rejected by validation, before a chip could embed it in its MixLayer gate: by both builders,
+    // and by validating an instance changed after building
+    let singular = [row0, row1, std::array::from_fn(|j| row0[j] + row1[j])];
+    assert!(matches!(
+        Poseidon::<Fr>::builder().full_rounds(8).partial_rounds(57).alpha(5).mds(singular).build(),
+        Err(BenchmarkError::ParameterMismatch(message)) if message == "the Poseidon MDS matrix is singular"
+    ));
+    assert!(matches!(
+        RescuePrime::<Fr>::builder().rounds(14).alpha(5).mds(singular).build(),
+        Err(BenchmarkError::ParameterMismatch(message)) if message == "the Rescue-Prime MDS matrix is singular"
+    ));
+    let edited = Poseidon { mds: singular, ..poseidon.clone() };
+    assert!(matches!(edited.validate(), Err(BenchmarkError::ParameterMismatch(_))));
+
+    let modulus = field_modulus::<Fr>().to_string();
+    assert!(matches!(
+        parse_constants::<Fr, _>("malformed", &["1", "2", "three"]),
+        Err(BenchmarkError::InvalidConstant { index: 2, .. })
+    ));
+    assert!(matches!(
+        parse_constants::<Fr, _>("out of range", &["1", &modulus]),
+        Err(BenchmarkError::InvalidConstant { index: 1, .. })
+    ));
+
+    // constants are written canonically: decimal without leading zeros, hex at full width in either case
+    let [lower, upper] = ["0a", "0A"].map(|byte| format!("0x{}{}", "00".repeat(31), byte));
+    assert_eq!(parse_constants::<Fr, _>("canonical", &["0", "10", &lower, &upper])?, [0, 10, 10, 10].map(Fr::from));
+    for written in ["010", "00", "0x0a", "+10", " 10"] {
+        assert!(matches!(parse_constants::<Fr, _>("leading zeros", &[written]), Err(BenchmarkError::InvalidConstant { index: 0, .. })));
+    }
+    // over Pasta the tables are reduced mod p, but only after they are checked in the field they were written for
+    assert!(matches!(table_constants::<Fp, _>("leading zeros", &["1", "007"]), Err(BenchmarkError::InvalidConstant { index: 1, .. })));
+    assert!(matches!(table_constants::<Fp, _>("out of range", &[&modulus]), Err(BenchmarkError::InvalidConstant { index: 0, .. })));
+
+    let mut edited = rescue.clone();
+    edited.alpha_inv += 1u32;
+    assert!(matches!(edited.validate(), Err(BenchmarkError::ParameterMismatch(_))));
+
+    Ok(())
+}
+
+// the native Rescue-Prime inverse: it undoes the permutation on edge and seeded random states
+// in both orders, its cached inverse MDS matrix is the one computed from the matrix and goes stale when the matrix is
+// edited, and a preimage it finds for a target output state is a witness the permutation circuit accepts
+#[test]
+fn rescue_inverse() -> Result<(), BenchmarkError> {
+    let k = K;
+    let rescue = rescue_params::<Fr>()?;
+    assert_eq!(Some(rescue.mds_inverse()), mds_inverse(&rescue.mds));
+    let poseidon = poseidon_params::<Fr>()?;
+    assert_eq!(Some(poseidon.mds_inverse()?), mds_inverse(&poseidon.mds));
+
+    let mut rng = ChaCha20Rng::seed_from_u64(1142);
+    let mut states = vec![[Fr::ZERO; 3], [Fr::ONE; 3], [-Fr::ONE; 3], [0, 1, 2].map(Fr::from)];
+    states.extend((0..8).map(|_| [(); 3].map(|()| Fr::random(&mut rng))));
+    for state in states {
+        assert_eq!(rescue.permute_inverse_native(rescue.permute_native(state)), state, "inverse(permute({:?}))", state);
+        assert_eq!(rescue.permute_native(rescue.permute_inverse_native(state)), state, "permute(inverse({:?}))", state);
+    }
+
+    let target = [1, 2, 3].map(Fr::from);
+    let preimage = rescue.permute_inverse_native(target);
+    let circuit = RescueCircuit::new(preimage.map(Value::known), OutputMode::AllState)?;
+    assert_eq!(MockProver::run(k, &circuit, vec![target.to_vec()])?.verify(), Ok(()));
+
+    let mut edited = rescue.clone();
+    edited.mds.swap(0, 1);
+    assert!(matches!(edited.validate(), Err(BenchmarkError::ParameterMismatch(message)) if message.contains("cached inverse")));
+
+    Ok(())
+}
+
+// expected_instances in every output mode: one instance column of 3, 2 or 1 words, the first
+// words of the native output state, which the circuit in that mode accepts and a wrong last word makes it reject
+#[test]
+fn expected_instances_per_mode() -> Result<(), BenchmarkError> {
+    let k = K;
+    let inputs = [3, 4, 5].map(Fr::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let states = [poseidon_params::<Fr>()?.permute_native(inputs), rescue_params::<Fr>()?.permute_native(inputs)];
+    for (output_mode, len) in [(OutputMode::AllState, 3), (OutputMode::RateOnly, 2), (OutputMode::DigestOnly, 1)] {
+        for (permutation, state) in [PermutationKind::Poseidon, PermutationKind::Rescue].into_iter().zip(states) {
+            let instances = expected_instances(permutation, inputs, output_mode)?.to_instances();
+            assert_eq!(instances, vec![state[..len].to_vec()]);
+
+            let mut wrong = instances.clone();
+            wrong[0][len - 1] += Fr::ONE;
+            let verify = |instances: Vec<Vec<Fr>>| match permutation {
+                PermutationKind::Poseidon => MockProver::run(k, &PoseidonCircuit { s0, s1, s2, output_mode }, instances).map(|prover| prover.verify().is_ok()),
+                PermutationKind::Rescue => MockProver::run(k, &RescueCircuit { s0, s1, s2, output_mode }, instances).map(|prover| prover.verify().is_ok())
+            };
+            assert!(verify(instances)?);
+            assert!(!verify(wrong)?);
+        }
+    }
+
+    Ok(())
+}
+
+// the typed public inputs: the constructor for each output mode gives the instance of
+// expected_instances, one column of 3, 2 or 1 words that reads back only as a digest of that many words. Digests and
+// instances print and serialize as big-endian hex and round-trip through JSON; a digest of the wrong length or a word
+// >= p does not deserialize
+#[test]
+fn public_input_layouts() -> Result<(), BenchmarkError> {
+    let inputs = [3, 4, 5].map(Fr::from);
+    let state = poseidon_params::<Fr>()?.permute_native(inputs);
+    let all_state = expected_instances(PermutationKind::Poseidon, inputs, OutputMode::AllState)?;
+    let rate_only = expected_instances(PermutationKind::Poseidon, inputs, OutputMode::RateOnly)?;
+    let digest_only = expected_instances(PermutationKind::Poseidon, inputs, OutputMode::DigestOnly)?;
+    assert_eq!(all_state, PublicInputs::all_state(Digest(state)));
+    assert_eq!(rate_only, PublicInputs::rate_only(Digest::from_state(&state)?));
+    assert_eq!(digest_only, PublicInputs::digest_only(Digest([state[0]])));
+    for (public_inputs, len) in [(&all_state, 3), (&rate_only, 2), (&digest_only, 1)] {
+        let instances: Vec<Vec<Fr>> = public_inputs.clone().into();
+        assert_eq!(instances, vec![state[..len].to_vec()]);
+        assert_eq!(&PublicInputs::from_instances(instances), public_inputs);
+    }
+    let digest = all_state.digest::<3>()?;
+    assert_eq!(digest.0, state);
+    assert_eq!(rate_only.digest::<2>()?.0, state[..2]);
+    assert!(all_state.digest::<1>().is_err() && digest_only.digest::<3>().is_err());
+    assert!(PublicInputs::from_instances(vec![vec![state[0]], vec![state[0]]]).digest::<1>().is_err());
+
+    let words = Digest([Fr::ONE, Fr::from(2)]);
+    assert_eq!(Digest([Fr::ONE]).to_string(), format!("0x{:064x}", 1));
+    assert_eq!(words.to_string(), format!("0x{:064x}, 0x{:064x}", 1, 2));
+    assert_eq!(PublicInputs::rate_only(words).to_string(), format!("[0x{:064x}, 0x{:064x}]", 1, 2));
+    assert_eq!(digest.to_string(), hex_words(&state).join(", "));
+
+    assert_eq!(serde_json::to_string(&Digest([Fr::ONE])).unwrap(), format!("[\"0x{:064x}\"]", 1));
+    let json = serde_json::to_string(&all_state).unwrap();
+    assert_eq!(json, serde_json::to_string(&[hex_words(&state)]).unwrap());
+    assert_eq!(serde_json::from_str::<PublicInputs<Fr>>(&json).unwrap(), all_state);
+    let json = serde_json::to_string(&digest).unwrap();
+    assert_eq!(serde_json::from_str::<Digest<Fr, 3>>(&json).unwrap(), digest);
+    assert!(serde_json::from_str::<Digest<Fr, 2>>(&json).is_err());
+    assert!(serde_json::from_str::<Digest<Fr>>(&format!("[\"{:#x}\"]", field_modulus::<Fr>())).is_err());
+
+    Ok(())
+}
+
+// the hash module: the one-shot functions give the sponge's digests and the digests pinned
+// here for the preset parameters, so a change to the parameters, the padding or the sponge shows up as a different
+// digest; a reused hasher gives the same digests
+#[test]
+fn hash_digests() -> Result<(), BenchmarkError> {
+    let pinned = [
+        (Vec::new(), "0x5bef7d2fee96f0de76e343681d097707082f298d54bd1a8ae473d07b5b93e93b", "0x2dce5903c9be7ef545cb508d0e717b576447ca24e5a324c64f9d54a95ef1b725"),
+        // (1, 2) and (1, 2, 0) differ only by a trailing zero, which the padding keeps apart
+        ([1, 2].map(Fr::from).to_vec(), "0x69f399ee1d0a101490aeb4850c978517861b4fd74c7ea195cfcc1b73dafef3a2", "0x5e966408d33b9dc26da7f5e8a883ab46a8b585cb46d53393a62d84c67a61f331"),
+        ([1, 2, 0].map(Fr::from).to_vec(), "0x0c4f03cd2851224e7f01c92d209140484c2717f661d5283e12e6439f7559c796", "0x43fd0720c96c33da66e05cea35f94e99fcf54d9edf82fb72f6a79202db2ac8f3"),
+        ([0, 1, 2].map(Fr::from).to_vec(), "0x519cc43561c103a883a86cbd5449d17b7d57954bc7d0dcbb3632863044241ee8", "0x105c653cf9358af273a4f087b44ae005e2fd2dd1547f203ad90d71eadcf19bfe")
+    ];
+    let (poseidon, rescue) = (Hasher::<Fr>::poseidon()?, Hasher::<Fr>::rescue()?);
+    for (inputs, digest_ps, digest_rs) in pinned {
+        assert_eq!(fe_to_hex(&poseidon_hash(&inputs)?, Endianness::Big), digest_ps);
+        assert_eq!(fe_to_hex(&rescue_hash(&inputs)?, Endianness::Big), digest_rs);
+        assert_eq!(poseidon_hash(&inputs)?, Sponge::new().hash_native(&poseidon_params::<Fr>()?, &inputs));
+        assert_eq!(rescue_hash(&inputs)?, Sponge::new().hash_native(&rescue_params::<Fr>()?, &inputs));
+        assert_eq!((poseidon.hash(&inputs), rescue.hash(&inputs)), (poseidon_hash(&inputs)?, rescue_hash(&inputs)?));
+    }
+
+    Ok(())
+}
+
+// that alpha drives the S-box gates and the witness: x^3 and x^5 gates have degree 3 and 5,
+// x^3 is rejected over BLS12-381 (3 divides p - 1), and both circuits built from a parameter file with alpha = 7 match
+// their native permutation, whose outputs differ from the x^5 instance
+#[test]
+fn sbox_alpha() -> Result<(), BenchmarkError> {
+    let k = K;
+    for alpha in [3, 5] {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let advice = meta.advice_column();
+        let gates = record_gates(|| {
+            create_gate(&mut meta, "sbox", |meta| vec![pow_expression(meta.query_advice(advice, Rotation::cur()), alpha)]);
+        });
+        assert_eq!(gates[0].degree, alpha as usize);
+    }
+    assert!(matches!(
+        Poseidon::<Fr>::builder().full_rounds(8).partial_rounds(57).alpha(3).build(),
+        Err(BenchmarkError::ParameterMismatch(_))
+    ));
+
+    let inputs = [Fr::from(0), Fr::from(1), Fr::from(2)];
+    let mut file = ParameterFile::from_params(&poseidon_params::<Fr>()?, &rescue_params::<Fr>()?);
+    let preset_ps = poseidon_params::<Fr>()?.permute_native(inputs);
+    let preset_rs = rescue_params::<Fr>()?.permute_native(inputs);
+    if let (Some(poseidon), Some(rescue)) = (file.poseidon.as_mut(), file.rescue.as_mut()) {
+        poseidon.alpha = 7;
+        rescue.alpha = 7;
+    }
+    let path = std::env::temp_dir().join("permutation_benchmark_alpha7.json");
+    file.write(&path)?;
+
+    let _source = set_parameter_source(ParameterSource::File(path.clone()));
+    let expected_ps = poseidon_params::<Fr>()?.permute_native(inputs);
+    let expected_rs = rescue_params::<Fr>()?.permute_native(inputs);
+    assert!(expected_ps != preset_ps && expected_rs != preset_rs);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let circuit_ps = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let circuit_rs = RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    assert_eq!(MockProver::run(k, &circuit_ps, vec![expected_ps.to_vec()])?.verify(), Ok(()));
+    assert_eq!(MockProver::run(k, &circuit_rs, vec![expected_rs.to_vec()])?.verify(), Ok(()));
+    assert_eq!(GateInventory::collect::<Fr, PoseidonCircuit<Fr>>().max_degree, 8);
+    let _ = std::fs::remove_file(&path);
+
+    Ok(())
+}
+
+// where the Poseidon rounds add their constants: every Poseidon preset declares its convention
+// (constants before the S-box, like the circomlib and neptune vectors checked against them) and the builder takes it
+// from the preset; the same constants after the S-box give another permutation; the constants-after-S-box form of the
+// preset, with its first constants folded into the input, computes the preset permutation; and circuits built from
+// parameter files with the constants after the S-box match their native permutation under every constant strategy
+// and both S-box layouts, rejecting the outputs of the other convention
+#[test]
+fn arc_positions() -> Result<(), BenchmarkError> {
+    let k = K;
+    for preset in presets() {
+        let declared = preset.arc.is_some();
+        assert_eq!(declared, preset.permutation == "Poseidon", "{} declares where its round constants are added: {}", preset.name, declared);
+        if let (Some(arc), PresetField::Bls12_381) = (preset.arc, preset.field) {
+            assert_eq!(Poseidon::<Fr>::builder().preset(preset.name).build()?.arc, arc, "{} builds with its own convention", preset.name);
+        }
+    }
+
+    let before = Poseidon::<Fr>::builder().preset(POSEIDON_BLS381_T3).build()?;
+    let after = Poseidon::<Fr>::builder().preset(POSEIDON_BLS381_T3).arc(ArcPosition::AfterSbox).build()?;
+    assert_eq!(before.arc, ArcPosition::BeforeSbox);
+    let (first, folded) = before.after_sbox_form()?;
+    assert!(matches!(folded.after_sbox_form(), Err(BenchmarkError::ParameterMismatch(_))));
+    let mut rng = ChaCha20Rng::seed_from_u64(1150);
+    let inputs: Vec<[Fr; 3]> = [[0, 1, 2].map(Fr::from), [Fr::ZERO; 3], [-Fr::ONE; 3]].into_iter()
+        .chain((0..4).map(|_| [(); 3].map(|()| Fr::random(&mut rng))))
+        .collect();
+    for input in &inputs {
+        let expected = before.permute_native(*input);
+        assert_ne!(after.permute_native(*input), expected, "the same constants after the S-box permute {:?} alike", input);
+        assert_eq!(folded.permute_native(std::array::from_fn(|i| input[i] + first[i])), expected, "folded form of {:?}", input);
+    }
+    let steps: Vec<&str> = after.trace_native(inputs[0]).iter().take(3).map(|step| step.step).collect();
+    assert_eq!(steps, ["sbox", "arc", "mds"]);
+    assert_eq!(after.trace_native(inputs[0]).last().map(|step| step.state), Some(after.permute_native(inputs[0])));
+
+    // the folded form as a parameter file: a circuit adding the constants after the S-box reproduces the preset digests
+    let [s0, s1, s2] = inputs[0];
+    let folded_input = [s0 + first[0], s1 + first[1], s2 + first[2]];
+    for (name, params, input) in [("after", &after, inputs[0]), ("folded", &folded, folded_input)] {
+        let file = ParameterFile::from_params(params, &rescue_params::<Fr>()?);
+        let scratch = tempfile::NamedTempFile::new().unwrap();
+        let path = scratch.path().to_path_buf();
+        file.write(&path)?;
+        let written = std::fs::read_to_string(&path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+        assert!(written.contains("\"arc\": \"after-sbox\""), "the {} parameter file names its convention", name);
+
+        let _source = set_parameter_source(ParameterSource::File(path.clone()));
+        assert_eq!(poseidon_params::<Fr>()?.arc, ArcPosition::AfterSbox);
+        let expected = params.permute_native(input);
+        let other = Poseidon { arc: ArcPosition::BeforeSbox, ..params.clone() }.permute_native(input);
+        let [s0, s1, s2] = input.map(Value::known);
+        let circuit = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+        for strategy in [ConstantStrategy::Fixed, ConstantStrategy::Advice, ConstantStrategy::Gate] {
+            for layout in [SboxLayout::Direct, SboxLayout::Squared] {
+                with_constant_strategy(strategy, || with_sbox_layout(layout, || -> Result<(), BenchmarkError> {
+                    MockProver::run(k, &circuit, vec![expected.to_vec()])?.verify()
+                        .map_err(|failures| verification_failed(format_args!("{} constants after the S-box, {:?}, {:?}", name, strategy, layout), &failures))?;
+                    assert!(MockProver::run(k, &circuit, vec![other.to_vec()])?.verify().is_err());
+                    Ok(())
+                }))?;
+            }
+        }
+        if name == "folded" {
+            assert_eq!(expected, before.permute_native(inputs[0]));
+        }
+    }
+    println!(
+        "round constant positions: {} Poseidon presets add them before the S-box; after it the same constants give other digests, and the folded form reproduces the preset in and out of circuit",
+        presets().iter().filter(|preset| preset.arc.is_some()).count()
+    );
+
+    Ok(())
+}
+
+// the parameter sources: generation reproduces the preset tables, a parameter file written
+// from the preset instance reads back to it (JSON and TOML), and a file with a wrong constant count is rejected
+#[test]
+fn parameter_sources() -> Result<(), BenchmarkError> {
+    let poseidon = ParameterSource::Preset.poseidon::<Fr>()?;
+    let rescue = ParameterSource::Preset.rescue::<Fr>()?;
+    let same_poseidon = |other: &Poseidon<Fr>| other.round_constants == poseidon.round_constants && other.mds == poseidon.mds;
+    let same_rescue = |other: &RescuePrime<Fr>| other.round_constants == rescue.round_constants && other.mds == rescue.mds;
+    assert!(same_poseidon(&ParameterSource::Generated.poseidon()?));
+    assert!(same_rescue(&ParameterSource::Generated.rescue()?));
+
+    let file = ParameterFile::from_params(&poseidon, &rescue);
+    for name in ["permutation_benchmark_params.json", "permutation_benchmark_params.toml"] {
+        let path = std::env::temp_dir().join(name);
+        file.write(&path)?;
+        let source = ParameterSource::File(path.clone());
+        assert!(same_poseidon(&source.poseidon()?));
+        assert!(same_rescue(&source.rescue()?));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    let mut short = file.clone();
+    if let Some(section) = short.poseidon.as_mut() {
+        section.round_constants.pop();
+    }
+    let path = std::env::temp_dir().join("permutation_benchmark_short_params.json");
+    short.write(&path)?;
+    let result = ParameterSource::File(path.clone()).poseidon::<Fr>();
+    let _ = std::fs::remove_file(&path);
+    assert!(matches!(result, Err(BenchmarkError::ParameterMismatch(_))));
+
+    // a user's file goes through the same canonical check as the preset tables
+    let mut padded = file.clone();
+    if let Some(section) = padded.rescue.as_mut() {
+        section.round_constants[5].insert(0, '0');
+    }
+    let path = std::env::temp_dir().join("permutation_benchmark_padded_params.json");
+    padded.write(&path)?;
+    let result = ParameterSource::File(path.clone()).rescue::<Fr>();
+    let _ = std::fs::remove_file(&path);
+    assert!(matches!(result, Err(BenchmarkError::InvalidConstant { index: 5, .. })));
+
+    Ok(())
+}
+
+// every constants preset against its committed hash and its provenance: the hash recomputed
+// from the parsed constants, the generated presets rerun through their generator, and the builders and reports naming
+// the presets they were built from
+#[test]
+fn preset_tables() -> Result<(), BenchmarkError> {
+    for preset in presets() {
+        assert_eq!(preset.content_hash()?, preset.hash, "the constants of {} do not match its committed hash", preset.name);
+    }
+
+    let poseidon = preset(POSEIDON_BLS381_T3)?;
+    let grain = poseidon_grain::<Fr, 3>(poseidon.full_rounds, poseidon.partial_rounds, SboxKind::Power(poseidon.alpha));
+    assert_eq!((poseidon.round_constants::<Fr>()?, poseidon.mds::<Fr>()?), grain);
+    let rescue = preset(RESCUE_BLS381_T3)?;
+    assert_eq!(rescue.round_constants::<Fr>()?, rescue_constants::<Fr, 3>(rescue.full_rounds, 1, SECURITY_LEVEL as usize));
+    assert_eq!(rescue.mds::<Fr>()?, rescue_mds::<Fr, 3>()?);
+    let circom = preset(POSEIDON_BN254_T3_CIRCOM)?;
+    let grain = poseidon_grain::<Bn256Fr, 3>(circom.full_rounds, circom.partial_rounds, SboxKind::Power(circom.alpha));
+    assert_eq!((circom.round_constants::<Bn256Fr>()?, circom.mds::<Bn256Fr>()?), grain);
+    let neptune = preset(POSEIDON_BLS381_T3_NEPTUNE)?;
+    // neptune seeds Grain with the inverse S-box bits although its S-box is x^5
+    let (grain_constants, _) = poseidon_grain::<Fr, 3>(neptune.full_rounds, neptune.partial_rounds, SboxKind::Inverse);
+    assert_eq!((neptune.round_constants::<Fr>()?, neptune.mds::<Fr>()?), (grain_constants, index_cauchy_mds()));
+
+    // the builders default to the presets, and the reports name them; a changed constant is no preset's
+    let mut params = Poseidon::<Fr>::builder().preset(POSEIDON_BLS381_T3).build()?;
+    let reported = |params: &Poseidon<Fr>| params.report().constants.and_then(|constants| constants.preset);
+    assert_eq!(reported(&params), Some(POSEIDON_BLS381_T3));
+    assert_eq!(reported(&ParameterSource::Preset.poseidon()?), Some(POSEIDON_BLS381_T3));
+    params.round_constants[0] += Fr::ONE;
+    assert_eq!(reported(&params), None);
+    let rescue_report = ParameterSource::Preset.rescue::<Fr>()?.report();
+    assert_eq!(rescue_report.constants.and_then(|constants| constants.preset), Some(RESCUE_BLS381_T3));
+    assert!(matches!(preset("poseidon-bls381-t5"), Err(BenchmarkError::ParameterMismatch(_))));
+
+    Ok(())
+}
+
+// circuit with the bar of one word whose limb rows are given as they are, for B = 8: the input word is a private cell,
+// the recomposed output public
+#[derive(Debug)]
+struct BarWordCircuit {
+    word: Value<Fr>,
+    limbs: Value<Vec<BarLimb<Fr>>>
+}
+
+impl Circuit<Fr> for BarWordCircuit {
+    type Config = BarsChipConfig<Fr, 8>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        BarWordCircuit { word: Value::unknown(), limbs: Value::unknown() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        BarsChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let chip = BarsChip::construct(config);
+        chip.load_table(layouter.namespace(|| "bar_table"))?;
+        let output = layouter.assign_region(
+            || "bar_word", |mut region| {
+                let word = Number::from(region.assign_advice(|| "word", chip.config.advice[0], 0, || self.word)?);
+                chip.assign_bar_word(&mut region, 1, &word, self.limbs.clone())
+            }
+        )?;
+        chip.expose_as_public(layouter.namespace(|| "output"), output, 0)
+    }
+}
+
+// that the bar layer gates and lookups pin the limbs: the canonical limbs of a word pass, and
+// two witnesses that recompose to the same word mod p fail, the limbs of word + p (which fit in 32 limbs of 8 bits and
+// only the canonicity lookup stops) and the canonical limbs with the limb S-box left out
+#[test]
+fn bar_word() -> Result<(), BenchmarkError> {
+    let k = 10;
+    let params = bars_params::<Fr>(8)?;
+    let word = Fr::random(ChaCha20Rng::seed_from_u64(1130));
+    let run = |limbs: Vec<u64>, params: &Bars<Fr>| {
+        let rows = params.bar_rows(&limbs);
+        let output = rows.last().expect("a word has limbs").output_acc;
+        let circuit = BarWordCircuit { word: Value::known(word), limbs: Value::known(rows) };
+        MockProver::run(k, &circuit, vec![vec![output]]).map(|prover| (output, prover.verify().is_ok()))
+    };
+
+    let canonical = params.decompose(word);
+    assert_eq!(run(canonical.clone(), &params)?, (params.bar(word), true));
+
+    let shifted = BigUint::from_bytes_le(word.to_repr().as_ref()) + field_modulus::<Fr>();
+    let mask = BigUint::from(0xffu32);
+    let non_canonical: Vec<u64> = (0..params.limbs).rev()
+        .map(|i| ((&shifted >> (8 * i)) & &mask).iter_u64_digits().next().unwrap_or(0))
+        .collect();
+    assert_ne!(non_canonical, canonical);
+    assert!(!run(non_canonical, &params)?.1, "the bar accepts the limbs of word + p");
+
+    let identity = Bars { limb_alpha: 1, ..params.clone() };
+    let (output, accepted) = run(canonical, &identity)?;
+    assert_ne!(output, params.bar(word));
+    assert!(!accepted, "the bar accepts limbs that skip the limb S-box");
+
+    Ok(())
+}
+
+// the quick MockProver check against the full one at k: both accept the circuit and reject a
+// wrong output, both reject an is-equal row with a zero inverse, and the time of each is printed
+#[test]
+fn quick_mode() -> Result<(), BenchmarkError> {
+    let k = K;
+    let inputs = [0, 1, 2].map(Fr::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let circuit = &PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let instance = poseidon_params::<Fr>()?.permute_native(inputs).to_vec();
+    let mut wrong = instance.clone();
+    wrong[0] += Fr::ONE;
+    let corrupted = RawIsEqualCircuit { a: Fr::from(3), b: Fr::from(5), inv: Fr::ZERO, out: Fr::ZERO };
+
+    for check in [CheckMode::Full, CheckMode::Quick] {
+        let mock_k = check.mock_k(k, circuit)?;
+        let start = Instant::now();
+        assert_eq!(MockProver::run(mock_k, circuit, vec![instance.clone()])?.verify(), Ok(()), "{:?} check", check);
+        println!("Poseidon {:?} MockProver check at k = {}: {} ms (k = {})", check, k, start.elapsed().as_millis(), mock_k);
+
+        assert!(MockProver::run(mock_k, circuit, vec![wrong.clone()])?.verify().is_err(), "{:?} check accepts a wrong output", check);
+        let corrupted_k = check.mock_k(k, &corrupted)?;
+        assert!(MockProver::run(corrupted_k, &corrupted, vec![vec![]])?.verify().is_err(), "{:?} check accepts a corrupted witness", check);
+    }
+    assert!(CheckMode::Quick.mock_k(k, circuit)? < k);
+
+    Ok(())
+}
+
+// the prover thread pools: a Poseidon proof on one thread and on two (with the multicore
+// feature) verifies, and its report records the pool it ran on
+#[test]
+fn prover_threads() -> Result<(), BenchmarkError> {
+    let seed = Some(0);
+    let [s0, s1, s2] = [0, 1, 2].map(|word| Value::known(Fr::from(word)));
+    let circuit = &PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let inputs = [0, 1, 2].map(Fp::from);
+    let pasta = PoseidonCircuit { s0: Value::known(inputs[0]), s1: Value::known(inputs[1]), s2: Value::known(inputs[2]), output_mode: OutputMode::AllState };
+    let expected = poseidon_params::<Fp>()?.permute_native(inputs);
+    let params = commitment_params(minimal_k(circuit)?)?;
+    let thread_counts: &[usize] = if cfg!(feature = "multicore") { &[1, 2] } else { &[1] };
+    for &threads in thread_counts {
+        let proof = with_threads(Some(threads), || prove_and_verify("Poseidon threads", &params, &pasta, &expected, ProvingRng::new(seed)))??;
+        assert_eq!(proof.threads, threads);
+    }
+
+    Ok(())
+}
+
+// repeated measurements with three timed rounds after one warmup: every phase of the
+// MockProver measurement and of a real proof has statistics over exactly three rounds, in order
+#[test]
+fn repeated_rounds() -> Result<(), BenchmarkError> {
+    let (k, seed) = (K, Some(0));
+    let inputs = [0, 1, 2].map(Fr::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let circuit = &PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let public_inputs = &expected_instances(PermutationKind::Poseidon, inputs, OutputMode::AllState)?;
+    let repeat = Repeat { rounds: 3, warmup: 1 };
+    let (timings, _) = measure_circuit("Poseidon repeat", k, circuit, public_inputs, repeat, CheckMode::Full)?;
+    assert_eq!(timings.stats.keys().copied().collect::<Vec<_>>(), ["constraint_check_ms", "mock_prover_ms", "synthesis_us", "witness_gen_ms"]);
+    assert_eq!(timings.mock_prover_ms.len(), 3);
+
+    let inputs = [0, 1, 2].map(Fp::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let pasta = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let expected = poseidon_params::<Fp>()?.permute_native(inputs);
+    let proof = prove_and_verify_repeated("Poseidon repeat", &commitment_params(k)?, &pasta, &[&expected], ProvingRng::new(seed), repeat, TranscriptKind::Blake2b)?;
+
+    for stats in timings.stats.values().chain([&proof.proving_stats, &proof.verification_stats]) {
+        assert_eq!(stats.rounds, 3);
+        assert!(stats.is_consistent(), "inconsistent statistics {:?}", stats);
+    }
+    assert_eq!(proof.proving_ms, proof.proving_stats.median.round() as u128);
+
+    Ok(())
+}
+
+// a small sweep with two points proving at once: every point is present, in sweep order; in low-memory mode the
+// points are proved on a pool of LOW_MEMORY_THREADS threads
+#[test]
+fn sweep_order() -> Result<(), BenchmarkError> {
+    let modes = [OutputMode::AllState, OutputMode::DigestOnly];
+    let simple = [FloorPlannerKind::Simple];
+    let reports = sweep(&[Backend::Ipa], &[PermutationKind::Poseidon, PermutationKind::Rescue], &[8], &modes, &simple, 2, Some(2), Some(0))?;
+    let points: Vec<_> = reports.iter().map(|report| (report.proof.backend, report.permutation, report.k, report.output_mode)).collect();
+    assert_eq!(points, [
+        (Backend::Ipa, "Poseidon", 8, OutputMode::AllState),
+        (Backend::Ipa, "Poseidon", 8, OutputMode::DigestOnly),
+        (Backend::Ipa, "Rescue-Prime", 8, OutputMode::AllState),
+        (Backend::Ipa, "Rescue-Prime", 8, OutputMode::DigestOnly)
+    ]);
+    assert!(reports.iter().all(|report| report.minimal_k <= 8 && report.proof.proof_size > 0));
+    assert!(reports.iter().all(|report| (report.prover.k, report.prover.extended_k) == (8, 11)));
+    let low = with_low_memory(true, || sweep(&[Backend::Ipa], &[PermutationKind::Poseidon], &[8], &modes[..1], &simple, 4, None, Some(0)))?;
+    assert_eq!(low.iter().map(|report| report.proof.threads).collect::<Vec<_>>(), [LOW_MEMORY_THREADS]);
+    assert!(matches!(sweep(&[Backend::Ipa], &[PermutationKind::Poseidon], &[4], &modes, &simple, 2, None, None), Err(BenchmarkError::KTooSmall { k: 4, minimum: 8, .. })));
+
+    Ok(())
+}
+
+// the proving backends: one Poseidon proof on every backend, tagged with its backend
+#[test]
+fn backends() -> Result<(), BenchmarkError> {
+    let (k, seed) = (K, Some(0));
+    for &backend in Backend::value_variants() {
+        let proof = prove_on_backend(backend, k, ProvingRng::new(seed))?;
+        assert_eq!(proof.backend, backend);
+        println!("Poseidon proof on the {:?} backend: {} bytes", backend, proof.proof_size);
+    }
+
+    Ok(())
+}
+
+// the two permutation circuits on the inputs 0, 1 and 2 with all state words public, as run_benchmark measures them: the
+// reference outputs, the columns, selectors and cells of the layout, and the instances and witnesses they reject
+#[test]
+fn permutation_circuits() -> Result<(), BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let expected_ps = poseidon_params::<Fr>()?.permute_native(inputs).to_vec();
+    let expected_rs = rescue_params::<Fr>()?.permute_native(inputs).to_vec();
+
+    // the preset parameters must reproduce the reference outputs
+    let reference_ps = vec![
+        Fr::from_str_vartime("18456658763349757341014058622209659766100673761449600566550821987295786346378").unwrap(),
+        Fr::from_str_vartime("37068251774887509885063625701815026138353041152735229476479055620962268601796").unwrap(),
+        Fr::from_str_vartime("26763157702141528937904191329664859174584798817251788852101947537759678822298").unwrap()
+    ];
+    let reference_rs = vec![
+        Fr::from_str_vartime("20837336434853470849910909576721791703386530727763098803394615300550680488910").unwrap(),
+        Fr::from_str_vartime("25771045850287316209319297577315389859184751579565922583267218707663223737221").unwrap(),
+        Fr::from_str_vartime("47778332175771177523183464148522719206884558815624567948365727904575578981390").unwrap()
+    ];
+    assert_eq!((&expected_ps, &expected_rs), (&reference_ps, &reference_rs));
+
+    let output_mode = OutputMode::AllState;
+    let circuit_ps = PoseidonCircuit { s0, s1, s2, output_mode };
+    let circuit_rs = RescueCircuit { s0, s1, s2, output_mode };
+    let public_ps = expected_instances(PermutationKind::Poseidon, inputs, output_mode)?;
+    let public_rs = expected_instances(PermutationKind::Rescue, inputs, output_mode)?;
+    let (_, cost_ps) = measure_circuit("Poseidon", K, &circuit_ps, &public_ps, Repeat::ONCE, CheckMode::Full)?;
+    let (_, cost_rs) = measure_circuit("Rescue-Prime", K, &circuit_rs, &public_rs, Repeat::ONCE, CheckMode::Full)?;
+
+    // both circuits are expected to use exactly the three shared advice columns
+    assert_eq!((cost_ps.circuit.get("advice_columns"), cost_rs.circuit.get("advice_columns")), (Some(&3), Some(&3)));
+
+    // five selectors per chip, compressed into three fixed columns next to the three round constant columns; a
+    // layout that adds or drops a selector has to update this pin
+    let selectors = SelectorReport { selectors: 5, fixed_columns: 3, compressed_fixed_columns: 6 };
+    assert_eq!((&cost_ps.selectors, &cost_rs.selectors), (&selectors, &selectors));
+
+    // every round constant in its own fixed cell, none repeated and no constants column in use: 195 for Poseidon
+    // (8 + 57 rounds of 3) and 84 for Rescue-Prime (14 rounds of 6); the selector cells are one per row except the
+    // output row
+    let fixed_ps = FixedCells { fixed_cells_assigned: 195, distinct_constants: 195, selector_cells: 130 };
+    let fixed_rs = FixedCells { fixed_cells_assigned: 84, distinct_constants: 84, selector_cells: 56 };
+    assert_eq!((cost_ps.fixed, cost_rs.fixed), (fixed_ps, fixed_rs));
+
+    // the inputs are private witnesses and the outputs are exposed through the instance column, so no advice cell
+    // is a copy; every row of the layout fills all three advice columns: 131 rows for Poseidon, 57 for Rescue-Prime
+    let advice_ps = AdviceCells { computed: 393, copied: 0, padding: 0 };
+    let advice_rs = AdviceCells { computed: 171, copied: 0, padding: 0 };
+    assert_eq!((cost_ps.advice, cost_rs.advice), (advice_ps, advice_rs));
+
+    // an instance vector missing an exposed word must be rejected
+    let instance_ps = public_ps.column(0);
+    let short_instance = instance_ps[..instance_ps.len() - 1].to_vec();
+    assert!(MockProver::run(K, &circuit_ps, vec![short_instance])?.verify().is_err());
+
+    // a wrong public word is explained by its index, with the circuit's and the instance's values
+    let mut wrong_instance = expected_ps.clone();
+    wrong_instance[1] += Fr::ONE;
+    let failures = MockProver::run(K, &circuit_ps, vec![wrong_instance.clone()])?.verify().unwrap_err();
+    let summary = summarize_failures(&failures);
+    assert!(summary.gates.is_empty() && (summary.instance, summary.permutation) == (1, 1), "{}", summary);
+    assert!(summary.first_rows.contains(&"outside any region, on row 1".to_string()), "{}", summary);
+    assert_eq!(explain_instance_mismatch(&failures, &wrong_instance, &expected_ps), vec![format!(
+        "output word 1 mismatch: circuit produced {}, instance said {}",
+        fe_to_hex(&expected_ps[1], Endianness::Big), fe_to_hex(&wrong_instance[1], Endianness::Big)
+    )]);
+
+    // a wrong preimage must be rejected by the instance constraint
+    let wrong_preimage = PreimageCircuit::<Fr, PoseidonChip<Fr>> { s0: Value::known(Fr::ONE), s1, s2, _marker: PhantomData };
+    assert!(MockProver::run(K, &wrong_preimage, vec![vec![expected_ps[0]]])?.verify().is_err());
+
+    // both permutations in one circuit over shared columns fill all three advice columns of every row, and reject their
+    // outputs swapped
+    let dual = DualCircuit { s0, s1, s2 };
+    let instance_dual = compose_instances(&[(0, &expected_ps), (3, &expected_rs)])?;
+    let (_, cost_dual) = measure_circuit("Poseidon+Rescue-Prime", K, &dual, &instance_dual, Repeat::ONCE, CheckMode::Full)?;
+    let advice_dual = cost_dual.advice;
+    assert_eq!(advice_dual.computed + advice_dual.copied + advice_dual.padding, layout_rows(&dual)? * 3);
+    let swapped = [expected_rs.as_slice(), expected_ps.as_slice()].concat();
+    assert!(MockProver::run(K, &dual, vec![swapped])?.verify().is_err());
+
+    Ok(())
+}
+
+// one permutation region uses exactly the rows its chip predicts, also when regions are stacked in a batch of four
+#[test]
+fn region_rows() -> Result<(), BenchmarkError> {
+    fn check<P: PermutationChip<Fr>>(rows_per_round: (usize, usize)) -> Result<(), BenchmarkError> {
+        let report = measure_region_rows::<P>(rows_per_round)?;
+        assert_eq!(report.rows, report.expected_rows, "{} region rows", P::NAME);
+        let batch = BatchCircuit::<Fr, P> { inputs: vec![[Value::known(Fr::ONE); 3]; 4], _marker: PhantomData };
+        assert_eq!(layout_rows(&batch)?, 4 * report.expected_rows, "{} batch rows", P::NAME);
+        Ok(())
+    }
+    check::<PoseidonChip<Fr>>((3, 2))?;
+    check::<RescueChip<Fr>>((6, 4))
+}
+
+// each permutation circuit has one region, which starts at row 0 and has the chip's predicted rows (131 for Poseidon,
+// 57 for Rescue-Prime), and the dual circuit has both of them one after the other
+#[test]
+fn regions() -> Result<(), BenchmarkError> {
+    let [s0, s1, s2] = [0, 1, 2].map(|word| Value::known(Fr::from(word)));
+    let regions = measure_regions(
+        &PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState },
+        &RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState },
+        &DualCircuit { s0, s1, s2 }
+    )?;
+
+    let rows = |circuit: &str| -> Vec<(&str, usize, usize)> {
+        regions.iter().filter(|region| region.circuit == circuit).map(|region| (region.name.as_str(), region.start, region.rows)).collect()
+    };
+    assert_eq!(rows("Poseidon"), [("Poseidon_Permutation", 0, 131)]);
+    assert_eq!(rows("Rescue-Prime"), [("Rescue-Prime_Permutation", 0, 57)]);
+    assert_eq!(rows("Poseidon+Rescue-Prime"), [("Poseidon_Permutation", 0, 131), ("Rescue-Prime_Permutation", 131, 57)]);
+
+    Ok(())
+}
+
+// both floor planners on the permutation circuits and batches of 16: every circuit verifies at its smallest k and
+// rejects a wrong output there, and the permutation circuits are proved under both
+#[test]
+fn floor_planners() -> Result<(), BenchmarkError> {
+    fn rejects_wrong_output<FP: FloorPlanner, C: Circuit<Fr>>(circuit: C, instance: &[Fr]) -> Result<bool, BenchmarkError> {
+        let circuit = Planned::<_, FP>::new(circuit);
+        let mut wrong = instance.to_vec();
+        wrong[0] += Fr::ONE;
+        Ok(MockProver::run(minimal_k(&circuit)?, &circuit, vec![wrong])?.verify().is_err())
+    }
+
+    let reports = measure_floor_planners(Some(0))?;
+    assert_eq!(reports.len(), 8);
+    for report in &reports {
+        assert_eq!(report.proof_size.is_some(), !report.circuit.contains("batch"), "{} under {:?}", report.circuit, report.floor_planner);
+        assert!(report.proof_size.is_none_or(|size| size > 0));
+    }
+
+    let inputs = [0, 1, 2].map(Fr::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let output_mode = OutputMode::AllState;
+    let expected_ps = poseidon_params::<Fr>()?.permute_native(inputs);
+    let expected_rs = rescue_params::<Fr>()?.permute_native(inputs);
+    assert!(rejects_wrong_output::<SimpleFloorPlanner, _>(PoseidonCircuit { s0, s1, s2, output_mode }, &expected_ps)?);
+    assert!(rejects_wrong_output::<V1, _>(PoseidonCircuit { s0, s1, s2, output_mode }, &expected_ps)?);
+    assert!(rejects_wrong_output::<SimpleFloorPlanner, _>(RescueCircuit { s0, s1, s2, output_mode }, &expected_rs)?);
+    assert!(rejects_wrong_output::<V1, _>(RescueCircuit { s0, s1, s2, output_mode }, &expected_rs)?);
+
+    Ok(())
+}
+
+// the round constant strategies: the chip config records the strategy. Witnessing the constants in advice cells
+// replaces the three fixed columns with three advice columns plus one constants column, at one extra copy constraint
+// per round constant; inlining them trades the three fixed columns and every fixed cell of the schedule for a gate and
+// a selector per ARC step
+#[test]
+fn constant_strategies() -> Result<(), BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let digests = [poseidon_params::<Fr>()?.hash_native([inputs[0], inputs[1]]), rescue_params::<Fr>()?.hash_native([inputs[0], inputs[1]])];
+    let expected_ps = poseidon_params::<Fr>()?.permute_native(inputs);
+    let expected_rs = rescue_params::<Fr>()?.permute_native(inputs);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    for strategy in [ConstantStrategy::Fixed, ConstantStrategy::Advice, ConstantStrategy::Gate] {
+        with_constant_strategy(strategy, || -> Result<(), BenchmarkError> {
+            let config = PoseidonCircuit::<Fr>::configure(&mut ConstraintSystem::default());
+            assert_eq!(config.circuit_params.constants.strategy(), strategy);
+
+            // the domain tag goes through the strategy's constants column, the digests must not depend on it
+            let compression_ps = CompressionCircuit::<Fr, PoseidonChip<Fr>> { a0: Value::known(inputs[0]), a1: Value::known(inputs[1]), _marker: PhantomData };
+            let compression_rs = CompressionCircuit::<Fr, RescueChip<Fr>> { a0: Value::known(inputs[0]), a1: Value::known(inputs[1]), _marker: PhantomData };
+            assert_eq!(MockProver::run(K, &compression_ps, vec![vec![digests[0]]])?.verify(), Ok(()));
+            assert_eq!(MockProver::run(K, &compression_rs, vec![vec![digests[1]]])?.verify(), Ok(()));
+
+            // a wrong output word is rejected whichever way the constants are read
+            let (mut wrong_ps, mut wrong_rs) = (expected_ps.to_vec(), expected_rs.to_vec());
+            wrong_ps[0] += Fr::ONE;
+            wrong_rs[0] += Fr::ONE;
+            assert!(MockProver::run(K, &PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState }, vec![wrong_ps])?.verify().is_err(), "{:?}", strategy);
+            assert!(MockProver::run(K, &RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState }, vec![wrong_rs])?.verify().is_err(), "{:?}", strategy);
+            Ok(())
+        })?;
+    }
+
+    let reports = measure_constant_strategies(K, &expected_ps, &expected_rs, OutputMode::AllState, Some(0))?;
+
+    // ARC steps of three constants each, and the gates that add them when they are read from columns (Poseidon has a
+    // full and a partial round gate)
+    let round_constants = [poseidon_params::<Fr>()?.round_constants.len(), rescue_params::<Fr>()?.round_constants.len()];
+    let shared_gates = [2, 1];
+    for ((triple, constants), shared) in reports.chunks(3).zip(round_constants).zip(shared_gates) {
+        let [fixed, advice, gate] = triple else { unreachable!() };
+        assert_eq!(advice.fixed_columns + 2, fixed.fixed_columns);
+        assert_eq!(advice.advice_columns, fixed.advice_columns + 3);
+        assert_eq!(advice.copy_constraints, fixed.copy_constraints + constants);
+        assert_eq!(gate.gates, fixed.gates - shared + constants / 3);
+        assert_eq!(gate.fixed.fixed_cells_assigned, fixed.fixed.fixed_cells_assigned - constants);
+        assert_eq!((gate.advice_columns, gate.copy_constraints), (fixed.advice_columns, fixed.copy_constraints));
+    }
+
+    Ok(())
+}
+
+// the S-box layouts. Witnessing the square of every S-box input (for Rescue-Prime's inverse S-box, of every output,
+// since the gate checks output^5 = input) caps the S-box constraints at degree 3, so with the selector the circuit's
+// degree drops from 6 to 4 and the quotient is committed in 3 pieces instead of 5, at the cost of three advice columns
+// and no rows. Both layouts must give the outputs of the native permutations on fixed and random inputs
+#[test]
+fn sbox_layouts() -> Result<(), BenchmarkError> {
+    fn check_outputs<C: Circuit<Fr> + Debug>(circuit: &C, expected: &[Fr], layout: SboxLayout) -> Result<(), BenchmarkError> {
+        assert_eq!(MockProver::run(K, circuit, vec![expected.to_vec()])?.verify(), Ok(()), "{:?} with the {:?} S-box layout", circuit, layout);
+        let mut wrong = expected.to_vec();
+        wrong[0] += Fr::ONE;
+        assert!(MockProver::run(K, circuit, vec![wrong])?.verify().is_err(), "{:?} S-box layout accepts a wrong output", layout);
+        Ok(())
+    }
+
+    let mut rng = ChaCha20Rng::seed_from_u64(1116);
+    let inputs: Vec<[Fr; 3]> = [[0, 1, 2].map(Fr::from), [-Fr::ONE, Fr::ZERO, Fr::ONE]].into_iter()
+        .chain((0..2).map(|_| [(); 3].map(|()| Fr::random(&mut rng))))
+        .collect();
+    let (poseidon, rescue) = (poseidon_params::<Fr>()?, rescue_params::<Fr>()?);
+    for layout in [SboxLayout::Direct, SboxLayout::Squared] {
+        with_sbox_layout(layout, || -> Result<(), BenchmarkError> {
+            assert_eq!(PoseidonCircuit::<Fr>::configure(&mut ConstraintSystem::default()).squares.is_some(), layout == SboxLayout::Squared);
+            assert_eq!(RescueCircuit::<Fr>::configure(&mut ConstraintSystem::default()).squares.is_some(), layout == SboxLayout::Squared);
+
+            for &state in &inputs {
+                let [s0, s1, s2] = state.map(Value::known);
+                check_outputs(&PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState }, &poseidon.permute_native(state), layout)?;
+                check_outputs(&RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState }, &rescue.permute_native(state), layout)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    for pair in measure_sbox_layouts(K, Some(0))?.chunks(2) {
+        let [direct, squared] = pair else { unreachable!() };
+        assert_eq!((direct.degree, squared.degree), (6, 4), "{} degrees", direct.permutation);
+        assert_eq!(squared.rows, direct.rows);
+        assert_eq!(squared.advice_columns, direct.advice_columns + 3);
+        assert_eq!(squared.quotient_commitments + 2, direct.quotient_commitments);
+    }
+
+    Ok(())
+}
+
+// every preset reads the same constants from its embedded limbs as from its strings, in its own field
+#[test]
+fn embedded_constants() -> Result<(), BenchmarkError> {
+    fn check<F: PrimeField>(preset: &Preset) -> Result<(), BenchmarkError> {
+        let embedded = (preset.round_constants::<F>()?, preset.mds::<F>()?);
+        assert_eq!(embedded, (preset.parsed_round_constants::<F>()?, preset.parsed_mds::<F>()?), "embedded constants of {}", preset.name);
+        Ok(())
+    }
+    for preset in presets() {
+        match preset.field {
+            PresetField::Bls12_381 => check::<Fr>(preset)?,
+            PresetField::Bn254 => check::<Bn256Fr>(preset)?
+        }
+    }
+
+    Ok(())
+}
+
+// the bar layer variant at its smallest k for each limb width: it matches the native permutation there, with two lookup
+// arguments into a table of the chip's rows, and wider limbs mean fewer limb rows and lookups per permutation but a
+// table twice as large per bit
+#[test]
+fn bar_layers() -> Result<(), BenchmarkError> {
+    fn check<const B: usize>() -> Result<(), BenchmarkError> {
+        let inputs = [0, 1, 2].map(Fr::from);
+        let circuit = BarsCircuit::<Fr, B> { state: inputs.map(Value::known) };
+        let k = minimal_k(&circuit)?;
+        let label = BarsChip::<Fr, B>::NAME;
+        assert_eq!(MockProver::run(k, &circuit, vec![bars_params::<Fr>(B)?.permute_native(inputs).to_vec()])?.verify(), Ok(()), "{} at k = {}", label, k);
+
+        let chip = BarsChip::<Fr, B>::construct(BarsCircuit::<Fr, B>::configure(&mut ConstraintSystem::default()));
+        let summary = analyze::<BarsCircuit<Fr, B>>();
+        assert_eq!((summary.lookups, summary.lookup_table_rows), (2, chip.table_rows()), "{} lookups", label);
+        Ok(())
+    }
+    check::<4>()?;
+    check::<6>()?;
+    check::<8>()?;
+    check::<10>()?;
+
+    let reports = measure_bar_layers()?;
+    for pair in reports.windows(2) {
+        assert!(pair[1].lookups < pair[0].lookups && pair[1].rows < pair[0].rows, "{} against {}", pair[1].permutation, pair[0].permutation);
+        assert_eq!(pair[1].table_rows, pair[0].table_rows << (pair[1].limb_bits - pair[0].limb_bits));
+    }
+
+    Ok(())
+}
+
+// helper function to check the Anemoi circuit with l = L column pairs on the inputs 0, 1, .., 2l - 1: the region has
+// the predicted rows and a wrong output word is rejected
+fn check_anemoi<const L: usize>() -> Result<(), BenchmarkError> {
+    let params = anemoi_params::<Fr, L>()?;
+    let label = AnemoiChip::<Fr, L>::NAME;
+    let report = measure_anemoi::<L>(K, Repeat::ONCE, CheckMode::Full)?;
+    assert_eq!(report.cost.circuit.get("max_rows"), Some(&(params.rounds + 2)), "{} rows", label);
+    assert_eq!(report.cost.circuit.get("advice_columns"), Some(&(2 * L)), "{} advice columns", label);
+
+    let x: [Fr; L] = std::array::from_fn(|i| Fr::from(i as u64));
+    let y: [Fr; L] = std::array::from_fn(|i| Fr::from((L + i) as u64));
+    let circuit = AnemoiCircuit { x: x.map(Value::known), y: y.map(Value::known) };
+    let (x_out, y_out) = params.permute_native(x, y);
+    let mut wrong_instance = [x_out, y_out].concat();
+    wrong_instance[2 * L - 1] += Fr::ONE;
+    assert!(MockProver::run(K, &circuit, vec![wrong_instance])?.verify().is_err(), "{} accepts a wrong output", label);
+
+    Ok(())
+}
+
+// Anemoi with one and two column pairs
+#[test]
+fn anemoi() -> Result<(), BenchmarkError> {
+    check_anemoi::<1>()?;
+    check_anemoi::<2>()
+}
+
+// helper function for the state that reaches the first bar round with `target` as its input after the round constants:
+// the algebraic rounds before it run backwards, through the inverse MDS matrix and x^(1/alpha)
+fn bars_preimage(params: &Bars<Fr>, target: [Fr; 3]) -> Result<[Fr; 3], BenchmarkError> {
+    let mds_inv = mds_inverse(&params.mds)
+        .ok_or_else(|| BenchmarkError::ParameterMismatch("the bar layer MDS matrix is singular".to_string()))?;
+    let alpha_inv = BigUint::from(params.alpha).modinv(&(field_modulus::<Fr>() - 1u32))
+        .ok_or_else(|| BenchmarkError::ParameterMismatch(format!("x^{} does not permute the field", params.alpha)))?;
+
+    let bar_constants = params.round_constants[BARS_ALGEBRAIC_ROUNDS];
+    let mut state: [Fr; 3] = std::array::from_fn(|i| target[i] - bar_constants[i]);
+    for constants in params.round_constants[..BARS_ALGEBRAIC_ROUNDS].iter().rev() {
+        let unmixed = mds_mul_native(&mds_inv, &state);
+        state = std::array::from_fn(|i| unmixed[i].pow_vartime(alpha_inv.to_u64_digits()) - constants[i]);
+    }
+
+    Ok(state)
+}
+
+// the bar layer variant with 8-bit limbs: the limb S-box permutes the limbs, the bar fixes 0 and p - 1, and the
+// circuit matches the native permutation on fixed and random states and on states whose bar inputs are p - 1 in every
+// word (every limb equal to its digit of p - 1, the boundary of the canonicity check) or equal p - 1 down to a middle
+// limb, and rejects a wrong output
+#[test]
+fn bars() -> Result<(), BenchmarkError> {
+    const B: usize = 8;
+    let params = bars_params::<Fr>(B)?;
+    let label = BarsChip::<Fr, B>::NAME;
+    let mut outputs: Vec<u64> = (0..1u64 << B).map(|limb| params.limb_sbox(limb)).collect();
+    outputs.sort_unstable();
+    assert!(outputs.iter().copied().eq(0..1u64 << B), "the {} limb S-box is not a permutation", label);
+    assert_eq!((params.bar(Fr::ZERO), params.bar(-Fr::ONE)), (Fr::ZERO, -Fr::ONE));
+
+    let middle = Fr::from(2).pow_vartime([(B * (params.limbs / 2)) as u64]);
+    let targets = [[-Fr::ONE; 3], [-Fr::ONE - middle, Fr::ZERO, middle]];
+    let mut rng = ChaCha20Rng::seed_from_u64(1130);
+    let mut states = vec![[0, 1, 2].map(Fr::from)];
+    for target in targets {
+        let state = bars_preimage(&params, target)?;
+        let bar_input = params.trace_native(state)[BARS_ALGEBRAIC_ROUNDS];
+        let constants = params.round_constants[BARS_ALGEBRAIC_ROUNDS];
+        assert_eq!(std::array::from_fn(|i| bar_input[i] + constants[i]), target, "{} bar input", label);
+        states.push(state);
+    }
+    states.extend((0..2).map(|_| [(); 3].map(|()| Fr::random(&mut rng))));
+    for state in &states {
+        let circuit = BarsCircuit::<Fr, B> { state: state.map(Value::known) };
+        let expected = params.permute_native(*state).to_vec();
+        assert_eq!(MockProver::run(K, &circuit, vec![expected.clone()])?.verify(), Ok(()), "{} on {:?}", label, state);
+
+        let mut wrong = expected;
+        wrong[2] += Fr::ONE;
+        assert!(MockProver::run(K, &circuit, vec![wrong])?.verify().is_err(), "{} accepts a wrong output", label);
+    }
+
+    let report = measure_bars::<B>(K, Repeat::ONCE, CheckMode::Full)?;
+    let circuit = BarsCircuit::<Fr, B> { state: states[0].map(Value::known) };
+    let chip = BarsChip::<Fr, B>::construct(BarsCircuit::<Fr, B>::configure(&mut ConstraintSystem::default()));
+    assert_eq!(report.cost.circuit.get("advice_columns"), Some(&6), "{} advice columns", label);
+    assert_eq!(layout_rows(&circuit)?, chip.expected_rows().max(chip.table_rows()), "{} rows", label);
+
+    Ok(())
+}
+
+// Poseidon with the inverse S-box x^-1, read through a parameter file like any other instance: the circuit matches the
+// native permutation on seeded random states, on a state with a zero word and on a state whose first S-box input is
+// zero (0 -> 0), rejects a wrong output, and has gates of degree 4 instead of 6
+#[test]
+fn poseidon_inverse() -> Result<(), BenchmarkError> {
+    let params = poseidon_inverse_params::<Fr>()?;
+    assert_eq!((params.full_rounds, params.partial_rounds), (8, 62));
+    assert_eq!(SboxKind::Inverse.apply(Fr::ZERO), Fr::ZERO);
+
+    let report = measure_poseidon_inverse(K, Repeat::ONCE, CheckMode::Full)?;
+    assert_eq!(report.cost.gates.max_degree, 4);
+    assert_eq!(report.cost.circuit.get("max_rows"), Some(&(1 + 2 * (params.full_rounds + params.partial_rounds))));
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    ParameterFile::from_params(&params, &rescue_params::<Fr>()?).write(file.path())?;
+    let _source = set_parameter_source(ParameterSource::File(file.path().to_path_buf()));
+    let mut rng = ChaCha20Rng::seed_from_u64(1088);
+    let mut states: Vec<[Fr; 3]> = (0..4).map(|_| std::array::from_fn(|_| Fr::random(&mut rng))).collect();
+    states.push([Fr::ZERO, Fr::random(&mut rng), Fr::random(&mut rng)]);
+    states.push([-params.round_constants[0], Fr::random(&mut rng), Fr::random(&mut rng)]);
+    for state in &states {
+        let [s0, s1, s2] = state.map(Value::known);
+        let circuit = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+        let expected = params.permute_native(*state).to_vec();
+        assert_eq!(MockProver::run(K, &circuit, vec![expected.clone()])?.verify(), Ok(()), "Poseidon x^-1 on {:?}", state);
+
+        let mut wrong = expected;
+        wrong[0] += Fr::ONE;
+        assert!(MockProver::run(K, &circuit, vec![wrong])?.verify().is_err(), "Poseidon x^-1 accepts a wrong output");
+    }
+
+    Ok(())
+}
+
+// Poseidon with neptune's parameters for arity 2: with the domain tag 3 in state[0], the public state[1] is neptune's
+// digest of [0, 1], in the rows of its round counts
+#[test]
+fn poseidon_neptune() -> Result<(), BenchmarkError> {
+    let params = bls381_neptune()?;
+    let report = measure_poseidon_neptune(K, Repeat::ONCE, CheckMode::Full)?;
+    let instance = params.permute_native([Fr::from(NEPTUNE_ARITY_2_TAG), Fr::from(0), Fr::from(1)]);
+    assert_eq!(report.outputs, hex_words(&instance));
+    assert_eq!(instance[1], neptune_hash(&params, [Fr::from(0), Fr::from(1)])?);
+    assert_eq!(report.cost.circuit.get("max_rows"), Some(&(1 + 2 * (params.full_rounds + params.partial_rounds))));
+
+    Ok(())
+}
+
+// the verification cost of both permutation circuits. Under GWC at k = 10 the counts are pinned: Poseidon opens 3
+// advice columns at x and wx, 6 fixed columns (3 of them compressed selectors), 7 permutation columns, 2 permutation
+// products of 4 columns each, the quotient (5 pieces) and the random polynomial, at the 3 points x, wx and the last
+// row. Rescue-Prime has the same columns and queries, so its counts are the same
+#[test]
+fn verification_costs() -> Result<(), BenchmarkError> {
+    for (name, circuit_cost) in [("Poseidon", verification_cost::<PoseidonCircuit<Fp>>(K, ProofScheme::Gwc)?), ("Rescue-Prime", verification_cost::<RescueCircuit<Fp>>(K, ProofScheme::Gwc)?)] {
+        let commitments = BTreeMap::from([("advice", 3), ("fixed", 6), ("permutation", 9), ("vanishing", 2)]);
+        assert_eq!(circuit_cost.commitments, commitments, "{} commitments", name);
+        assert_eq!(
+            (circuit_cost.opened_commitments, circuit_cost.evaluations, circuit_cost.quotient_pieces, circuit_cost.permutation_chunks, circuit_cost.points),
+            (20, 26, 5, 2, 3),
+            "{} openings", name
+        );
+        assert_eq!(circuit_cost.point_sets, vec![
+            PointSet { rotations: vec![-6, 0, 1], polynomials: 1 },
+            PointSet { rotations: vec![0], polynomials: 15 },
+            PointSet { rotations: vec![0, 1], polynomials: 4 }
+        ], "{} point sets", name);
+        assert_eq!((circuit_cost.final_msm_size, circuit_cost.instance_msm_size, circuit_cost.pairings), (31, 0, 2), "{} final check", name);
+    }
+
+    Ok(())
+}
+
+// the CSV export, written twice to a scratch file and parsed back: one header, then one row per circuit and run with
+// every column, and a file with other columns is not appended to
+#[test]
+fn csv() -> Result<(), BenchmarkError> {
+    let report = synthetic_report();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.csv");
+    report.append_csv(&path)?;
+    report.append_csv(&path)?;
+    let text = std::fs::read_to_string(&path).unwrap();
+
+    let records: Vec<Vec<String>> = text.lines().map(parse_csv_record).collect();
+    let rows = report.csv_rows();
+    assert_eq!(records.len(), 1 + 2 * rows.len());
+    assert_eq!(records[0], csv_columns());
+    assert!(records.iter().all(|record| record.len() == csv_columns().len()));
+    assert_eq!(records[1..=rows.len()], records[rows.len() + 1..]);
+    assert_eq!(records[1][..5], ["Poseidon", "10", "permutation/all-state", "ipa", "ipa"]);
+    let columns = csv_columns();
+    let column = |name: &str| columns.iter().position(|column| column == name).unwrap();
+    assert_eq!(records[1][column("rows")], report.circuits[0].cost.circuit["max_rows"].to_string());
+    assert_eq!(records[1][column("proof_size")], report.proofs[0].proof_size.to_string());
+    assert_eq!((records[2][column("backend")].as_str(), records[2][column("scheme")].as_str()), ("mock", "mock"));
+    // the scheme column spells each scheme as the JSON report does
+    assert!([ProofScheme::Ipa, ProofScheme::Shplonk, ProofScheme::Gwc].iter().all(|scheme| serde_json::to_value(scheme).unwrap() == scheme.name()));
+    // every row has the MockProver statistics, only the proved rows have the proof statistics
+    assert!(records[1..].iter().all(|record| !record[column("mock_prover_ms_median")].is_empty()));
+    assert!(!records[1][column("proving_ms_median")].is_empty() && records[2][column("proving_ms_median")].is_empty());
+
+    // quoted fields, e.g. a parameter file path with a comma, survive the round trip
+    let fields = ["file:a,\"b\".json", "", "line\nbreak"];
+    assert_eq!(parse_csv_record(&fields.map(csv_field).join(",")), fields);
+
+    std::fs::write(&path, "permutation,k\n").unwrap();
+    assert!(matches!(report.append_csv(&path), Err(BenchmarkError::ParameterMismatch(_))));
+
+    Ok(())
+}
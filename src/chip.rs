@@ -0,0 +1,295 @@
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Cell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::error::BenchmarkError;
+use crate::gates::CircuitParameters;
+use crate::native::NativePermutation;
+use crate::synthesis::fitting_k;
+
+// structure to store numbers in cells, returned by the permutation chips
+#[derive(Clone, Debug)]
+pub struct Number<F: PrimeField>(AssignedCell<F, F>);
+
+impl<F: PrimeField> Number<F> {
+    pub fn new(cell: AssignedCell<F, F>) -> Self {
+        Number(cell)
+    }
+
+    // cell location, for copy and instance constraints
+    pub fn cell(&self) -> Cell {
+        self.0.cell()
+    }
+
+    // assigned value (unknown during keygen)
+    pub fn value(&self) -> Value<&F> {
+        self.0.value()
+    }
+
+    // the underlying assigned cell, e.g. to copy it into another region with copy_advice
+    pub fn assigned(&self) -> &AssignedCell<F, F> {
+        &self.0
+    }
+}
+
+impl<F: PrimeField> From<AssignedCell<F, F>> for Number<F> {
+    fn from(cell: AssignedCell<F, F>) -> Self {
+        Number(cell)
+    }
+}
+
+// how one word of a permutation's initial state is assigned
+#[derive(Clone, Copy, Debug)]
+pub enum StateWord<'a, F: PrimeField> {
+    // private witness
+    Witness(Value<F>),
+    // constant (e.g. a domain tag or sponge padding), constrained via the constants column
+    Constant(F),
+    // copy of an earlier cell, e.g. an output of the previous permutation, which chains permutations
+    Copy(&'a Number<F>),
+    // copy of an instance cell (column, absolute row), e.g. a word of a public message; the column needs equality
+    Instance(Column<Instance>, usize)
+}
+
+// helper function to assign a word in the first row of a region (the initial state of a permutation, a sponge input)
+pub(crate) fn assign_word<F: PrimeField>(
+    region: &mut Region<'_, F>,
+    name: &'static str,
+    column: Column<Advice>,
+    word: StateWord<'_, F>
+) -> Result<AssignedCell<F, F>, Error> {
+    let name = || name;
+    match word {
+        StateWord::Witness(value) => region.assign_advice(name, column, 0, || value),
+        StateWord::Constant(constant) => region.assign_advice_from_constant(name, column, 0, constant),
+        StateWord::Copy(number) => number.assigned().copy_advice(name, region, column, 0),
+        StateWord::Instance(instance, row) => region.assign_advice_from_instance(name, instance, row, column, 0)
+    }
+}
+
+// trait for the sub-functions of the circuit
+pub trait PermutationInstructions<F: PrimeField>: Chip<F> {
+    type Num;
+
+    // expose a value as public at instance row `row`, counted from the chip's instance offset (0 unless a host
+    // configured the chip to share the instance column)
+    fn expose_as_public(&self, layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error>;
+
+    // pin a value to a known constant through the constants column instead of an instance row, so a quick check needs
+    // no instance vector (the real circuits expose their outputs)
+    fn assert_output(&self, layouter: impl Layouter<F>, num: Self::Num, expected: F) -> Result<(), Error>;
+
+    // permutation of an initial state whose words are witnessed, constant or copied from earlier cells
+    fn permute_state(&self, layouter: impl Layouter<F>, initial: [StateWord<'_, F>; 3]) -> Result<[Self::Num; 3], Error>;
+
+    // permutation of a fully witnessed initial state
+    fn permute(
+        &self, 
+        layouter: impl Layouter<F>,
+        a0: Value<F>,
+        a1: Value<F>,
+        a2: Value<F>
+    ) -> Result<[Self::Num; 3], Error> {
+        self.permute_state(layouter, [StateWord::Witness(a0), StateWord::Witness(a1), StateWord::Witness(a2)])
+    }
+
+    // permutation of already assigned cells, copy-constrained into the permutation region
+    fn permute_assigned(&self, layouter: impl Layouter<F>, state: [&Number<F>; 3]) -> Result<[Self::Num; 3], Error> {
+        self.permute_state(layouter, state.map(StateWord::Copy))
+    }
+
+    // 2-to-1 compression: the rate words are witnessed, the domain tag fills the capacity, the digest is state[0]
+    fn hash(&self, layouter: impl Layouter<F>, a0: Value<F>, a1: Value<F>) -> Result<Self::Num, Error>;
+
+    // expose the first `count` permutation outputs at instance rows 0..count past the chip's instance offset
+    fn expose_outputs(&self, mut layouter: impl Layouter<F>, outputs: [Self::Num; 3], count: usize) -> Result<(), Error> {
+        for (row, num) in outputs.into_iter().take(count).enumerate() {
+            self.expose_as_public(layouter.namespace(|| format!("result_s{}", row)), num, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+// helper function to check that the instance rows of chips sharing one instance column do not overlap; `ranges` are
+// (offset, count) per chip. Two chips exposing different cells on one row make the circuit unsatisfiable, and MockProver
+// would only report it as a failed row, so composed circuits check their offsets before laying anything out
+pub(crate) fn check_instance_ranges(ranges: &[(usize, usize)]) -> Result<(), BenchmarkError> {
+    for (i, &(offset, count)) in ranges.iter().enumerate() {
+        for &(other, other_count) in &ranges[..i] {
+            if offset < other + other_count && other < offset + count {
+                return Err(BenchmarkError::ParameterMismatch(format!(
+                    "instance rows {}..{} overlap rows {}..{} of another chip on the same instance column",
+                    offset, offset + count, other, other + other_count
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// the select bit: witnessed in the select region, or copied from an earlier select so several selects share it
+enum SelectBit<'a, F: PrimeField> {
+    Witness(Value<F>),
+    Copy(&'a Number<F>)
+}
+
+// helper function to assign one select: bit, a, b on the first row (a and b copied in) and the output below bit
+fn assign_select<F: PrimeField>(
+    mut layouter: impl Layouter<F>,
+    circuit_params: &CircuitParameters,
+    bit: SelectBit<'_, F>,
+    a: &Number<F>,
+    b: &Number<F>
+) -> Result<(Number<F>, Number<F>), Error> {
+    layouter.assign_region(
+        || "select", |mut region| {
+            circuit_params.s_select.enable(&mut region, 0)?;
+            let bit = match &bit {
+                SelectBit::Witness(value) => region.assign_advice(|| "bit", circuit_params.advice[0], 0, || *value)?,
+                SelectBit::Copy(number) => number.assigned().copy_advice(|| "bit", &mut region, circuit_params.advice[0], 0)?
+            };
+            let a = a.assigned().copy_advice(|| "a", &mut region, circuit_params.advice[1], 0)?;
+            let b = b.assigned().copy_advice(|| "b", &mut region, circuit_params.advice[2], 0)?;
+
+            let out = bit.value().copied()
+                .zip(a.value().copied())
+                .zip(b.value().copied())
+                .map(|((bit, a), b)| b + bit * (a - b));
+            let out = region.assign_advice(|| "out", circuit_params.advice[0], 1, || out)?;
+
+            Ok((Number::from(bit), Number::from(out)))
+        }
+    )
+}
+
+// trait for the selection helpers (e.g. Merkle path ordering), built on the select gate both chips configure
+pub trait UtilsInstructions<F: PrimeField>: Chip<F> {
+    // the shared columns and selectors of the chip
+    fn circuit_params(&self) -> &CircuitParameters;
+
+    // witness a private value, e.g. a Merkle sibling
+    fn load_private(&self, mut layouter: impl Layouter<F>, value: Value<F>) -> Result<Number<F>, Error> {
+        let circuit_params = self.circuit_params();
+        layouter.assign_region(
+            || "load_private", |mut region| {
+                region.assign_advice(|| "private", circuit_params.advice[0], 0, || value).map(Number::from)
+            }
+        )
+    }
+
+    // out = a if bit else b, with the bit witnessed and constrained boolean; the bit cell is returned with the
+    // output so that further selects can reuse it
+    fn select(
+        &self,
+        layouter: impl Layouter<F>,
+        bit: Value<F>,
+        a: &Number<F>,
+        b: &Number<F>
+    ) -> Result<(Number<F>, Number<F>), Error> {
+        assign_select(layouter, self.circuit_params(), SelectBit::Witness(bit), a, b)
+    }
+
+    // select with the bit copied from an earlier select
+    fn select_with_bit(&self, layouter: impl Layouter<F>, bit: &Number<F>, a: &Number<F>, b: &Number<F>) -> Result<Number<F>, Error> {
+        let (_, out) = assign_select(layouter, self.circuit_params(), SelectBit::Copy(bit), a, b)?;
+        Ok(out)
+    }
+
+    // out = 1 if a = b else 0, from the inverse of a - b witnessed next to a and b (zero when a = b, where any
+    // inverse satisfies the gate)
+    fn is_equal(&self, mut layouter: impl Layouter<F>, a: &Number<F>, b: &Number<F>) -> Result<Number<F>, Error> {
+        let circuit_params = self.circuit_params();
+        layouter.assign_region(
+            || "is_equal", |mut region| {
+                circuit_params.s_is_equal.enable(&mut region, 0)?;
+                let a = a.assigned().copy_advice(|| "a", &mut region, circuit_params.advice[0], 0)?;
+                let b = b.assigned().copy_advice(|| "b", &mut region, circuit_params.advice[1], 0)?;
+
+                let diff = a.value().copied().zip(b.value().copied()).map(|(a, b)| a - b);
+                let inv = diff.map(|diff| diff.invert().unwrap_or(F::ZERO));
+                region.assign_advice(|| "inv", circuit_params.advice[2], 0, || inv)?;
+                let out = diff.map(|diff| if diff.is_zero_vartime() { F::ONE } else { F::ZERO });
+                region.assign_advice(|| "out", circuit_params.advice[0], 1, || out).map(Number::from)
+            }
+        )
+    }
+
+    // (b, a) if bit else (a, b), from two selects sharing one bit cell
+    fn cond_swap(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bit: Value<F>,
+        a: &Number<F>,
+        b: &Number<F>
+    ) -> Result<[Number<F>; 2], Error> {
+        let (bit, left) = self.select(layouter.namespace(|| "swap_left"), bit, b, a)?;
+        let right = self.select_with_bit(layouter.namespace(|| "swap_right"), &bit, a, b)?;
+        Ok([left, right])
+    }
+}
+
+// trait for chips that a generic circuit can configure and construct on its own (e.g. PreimageCircuit<F, P>)
+pub trait PermutationChip<F: PrimeField>: PermutationInstructions<F, Num = Number<F>> + UtilsInstructions<F> + Sized {
+    // name used in logs and the benchmark report
+    const NAME: &'static str;
+
+    // the gates (by the name create_gate gave them) whose selectors the chip enables on one row on purpose, one set per
+    // combination; the selector overlap lint reports any other row with more than one selector enabled. No chip fuses
+    // gates on a row yet, so the set is empty unless a chip declares one
+    const SELECTOR_OVERLAPS: &'static [&'static [&'static str]] = &[];
+
+    // allocate the shared columns and configure the chip with the benchmark parameters
+    fn configure_default(meta: &mut ConstraintSystem<F>) -> Self::Config;
+
+    // check that configure_default can use the parameters of the active source under the active S-box layout;
+    // Circuit::configure cannot return an error, so the constructors of the circuits check this first
+    fn check_parameters() -> Result<(), BenchmarkError>;
+
+    // construct the chip from its configuration
+    fn from_config(config: Self::Config) -> Self;
+
+    // the configured parameters, which also provide the native permutation
+    fn params(&self) -> &dyn NativePermutation<F>;
+
+    // rows of one permutation region, predicted from the round counts and the layout; synthesis fails on a mismatch
+    fn expected_rows(&self) -> usize;
+
+    // The layouter places constant words (the domain tag, sponge padding) at the next free row of the constants
+    // column, which with fixed round constants is the first round constant column. The permutation regions use that
+    // column too, so each constant takes a row below the region it was assigned in.
+
+    // rows of one 2-to-1 compression (hash): a permutation region, then the tag in the constants column
+    fn compression_rows(&self) -> usize {
+        self.expected_rows() + 1
+    }
+
+    // rows of an unkeyed sponge absorbing `elements` words: one permutation per padded block of two, and for every
+    // block after the first two add rows on the advice columns, with the block's padding words in the constants column
+    fn absorb_rows(&self, elements: usize) -> usize {
+        let rows = self.expected_rows();
+        let blocks = (elements + 1).div_ceil(2);
+        let padding = |block: usize| (2 * block..2 * block + 2).filter(|word| *word >= elements).count();
+
+        // the first region starts at row 0; the tag and the first block's padding follow it in the constants column
+        let (mut advice_end, mut constants_end) = (rows, rows + 1 + padding(0));
+        for block in 1..blocks {
+            let start = (advice_end + 2).max(constants_end + padding(block));
+            advice_end = start + rows;
+            constants_end = advice_end;
+        }
+
+        advice_end.max(constants_end)
+    }
+
+    // smallest k whose 2^k rows fit `num_permutations` permutation regions plus halo2's reserved (blinding) rows,
+    // for the chip configured with its default parameters on its own columns
+    fn min_k(num_permutations: usize) -> u32 {
+        let mut meta = ConstraintSystem::default();
+        let chip = Self::from_config(Self::configure_default(&mut meta));
+        fitting_k(&meta, num_permutations * chip.expected_rows())
+    }
+}
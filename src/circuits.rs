@@ -0,0 +1,1217 @@
+use std::marker::PhantomData;
+use ff::PrimeField;
+use std::fmt::Debug;
+use serde::{Deserialize, Serialize};
+use clap::ValueEnum;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error, FloorPlanner},
+};
+#[cfg(any(feature = "poseidon", feature = "rescue"))]
+use halo2_proofs::circuit::Chip;
+// the circuits around the Poseidon chip alone: the twin, host and circomlib circuits
+#[cfg(feature = "poseidon")]
+use {
+    ff::Field,
+    halo2curves::bn256::Fr as Bn256Fr,
+    crate::gates::{ChipOptions, allocate_columns},
+    crate::presets::bn254_circom,
+};
+// the circuits that pair the Poseidon chip with another chip or gate: the dual and chain circuits, and the twin and
+// host circuits of the tests
+#[cfg(all(feature = "poseidon", any(test, feature = "rescue")))]
+use crate::{chip::Number, params::poseidon_params};
+// the twin and host circuits only the tests build
+#[cfg(all(test, feature = "poseidon"))]
+use {
+    halo2_proofs::plonk::{Advice, Column, Selector},
+    crate::chip::check_instance_ranges,
+    crate::gates::{ConstantColumns, allocate_selector, create_mul_gate, enable_constant},
+};
+
+#[cfg(feature = "bench-cli")]
+use crate::anemoi::{AnemoiChip, AnemoiChipConfig};
+#[cfg(feature = "bench-cli")]
+use crate::bars::{BarsChip, BarsChipConfig};
+use crate::chip::PermutationChip;
+#[cfg(any(feature = "poseidon", feature = "rescue"))]
+use crate::chip::PermutationInstructions;
+#[cfg(any(feature = "poseidon", feature = "sponge", feature = "merkle"))]
+use crate::chip::StateWord;
+#[cfg(any(feature = "poseidon", feature = "rescue"))]
+use crate::error::BenchmarkError;
+#[cfg(feature = "merkle")]
+use crate::chip::UtilsInstructions;
+use crate::params::PermutationParameters;
+use crate::witness::InputStream;
+#[cfg(all(feature = "poseidon", feature = "rescue"))]
+use crate::params::rescue_params;
+#[cfg(feature = "poseidon")]
+use crate::poseidon::{PoseidonChipConfig, PoseidonChip};
+#[cfg(feature = "rescue")]
+use crate::rescue::{RescueChipConfig, RescueChip};
+#[cfg(feature = "sponge")]
+use crate::sponge::{BYTES_PER_ELEMENT, PublicMessageConfig, Sponge, SpongeConfig, configure_public_message, configure_sponge};
+#[cfg(feature = "merkle")]
+use crate::wide::{WideChip, WideChipConfig, WidePermutation};
+
+// which post-permutation state words the circuits expose as public instances (at instance rows 0, 1, ...)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputMode {
+    // all state words, including the capacity element (original behavior)
+    #[default]
+    AllState,
+    // only the rate portion state[0..rate], as a real sponge would output
+    RateOnly,
+    // only the digest state[0]
+    DigestOnly
+}
+
+impl OutputMode {
+    // number of exposed words, which is also the length of the expected instance vector
+    pub(crate) fn num_outputs(&self, params: &PermutationParameters) -> usize {
+        match self {
+            OutputMode::AllState => params.state_size,
+            OutputMode::RateOnly => params.rate,
+            OutputMode::DigestOnly => 1
+        }
+    }
+
+    // size the expected instance vector from the full expected output state
+    pub(crate) fn expected_instance<F: PrimeField>(&self, params: &PermutationParameters, state: &[F]) -> Vec<F> {
+        state[..self.num_outputs(params)].to_vec()
+    }
+}
+
+// floor planner a circuit is laid out with; the circuits are written for SimpleFloorPlanner and run under V1 through
+// `Planned`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FloorPlannerKind {
+    // one region after the other, each starting below the previous one
+    #[default]
+    Simple,
+    // halo2's V1 planner: measures every region first, then packs regions into free rows column by column
+    V1
+}
+
+// a circuit laid out with the floor planner FP instead of its own; configuration and synthesis are the wrapped circuit's
+pub(crate) struct Planned<C, FP> {
+    pub(crate) circuit: C,
+    _planner: PhantomData<FP>
+}
+
+impl<C, FP> Planned<C, FP> {
+    pub(crate) fn new(circuit: C) -> Self {
+        Planned { circuit, _planner: PhantomData }
+    }
+}
+
+impl<C: Debug, FP> Debug for Planned<C, FP> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Planned").field("circuit", &self.circuit).field("planner", &std::any::type_name::<FP>()).finish()
+    }
+}
+
+impl<F: PrimeField, C: Circuit<F>, FP: FloorPlanner> Circuit<F> for Planned<C, FP> {
+    type Config = C::Config;
+    type FloorPlanner = FP;
+
+    fn without_witnesses(&self) -> Self {
+        Planned::new(self.circuit.without_witnesses())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(config, layouter)
+    }
+}
+
+// Poseidon circuit structure TODO: is this worth abstraction if I need two synthesizing calls anyways?
+#[cfg(feature = "poseidon")]
+#[derive(Default, Debug)]
+pub struct PoseidonCircuit<F: PrimeField> {
+    pub(crate) s0: Value<F>, 
+    pub(crate) s1: Value<F>, 
+    pub(crate) s2: Value<F>,
+    pub(crate) output_mode: OutputMode
+}
+
+// Rescue-Prime circuit structure
+#[cfg(feature = "rescue")]
+#[derive(Default, Debug)]
+pub struct RescueCircuit<F: PrimeField> {
+    pub(crate) s0: Value<F>, 
+    pub(crate) s1: Value<F>, 
+    pub(crate) s2: Value<F>,
+    pub(crate) output_mode: OutputMode
+}
+
+// both permutations in one circuit over the same columns, on the same private inputs (six public outputs)
+#[cfg(all(feature = "poseidon", feature = "rescue"))]
+#[derive(Default, Debug)]
+pub(crate) struct DualCircuit<F: PrimeField> {
+    pub(crate) s0: Value<F>,
+    pub(crate) s1: Value<F>,
+    pub(crate) s2: Value<F>
+}
+
+// both chip configurations over one set of advice, fixed and instance columns
+#[cfg(all(feature = "poseidon", feature = "rescue"))]
+#[derive(Clone, Debug)]
+pub(crate) struct DualConfig<F: PrimeField> {
+    pub(crate) poseidon: PoseidonChipConfig<F>,
+    pub(crate) rescue: RescueChipConfig<F>
+}
+
+// two Poseidon permutations in one circuit on private inputs, both exposing their full output state on one shared
+// instance column: the first chip at rows 0..3, the second at its instance offset OFFSET
+#[cfg(all(test, feature = "poseidon"))]
+#[derive(Clone, Debug)]
+pub(crate) struct TwinCircuit<F: PrimeField, const OFFSET: usize> {
+    pub(crate) first: [Value<F>; 3],
+    pub(crate) second: [Value<F>; 3]
+}
+
+#[cfg(all(test, feature = "poseidon"))]
+impl<F: PrimeField, const OFFSET: usize> Default for TwinCircuit<F, OFFSET> {
+    fn default() -> Self {
+        TwinCircuit { first: [Value::unknown(); 3], second: [Value::unknown(); 3] }
+    }
+}
+
+#[cfg(all(test, feature = "poseidon"))]
+#[derive(Clone, Debug)]
+pub(crate) struct TwinConfig<F: PrimeField> {
+    first: PoseidonChipConfig<F>,
+    second: PoseidonChipConfig<F>
+}
+
+// chain circuit structure: `poseidon` Poseidon permutations followed by `rescue` Rescue-Prime permutations over the
+// dual configuration's shared columns, each permuting the previous output through copy constraints; the initial state
+// is private and the final state[0] is public at instance row 0. The regions fill whole rows, so the chain's height is
+// the sum of its permutations' rows, which lets the stress run fill the usable rows of a column exactly
+#[cfg(all(feature = "poseidon", feature = "rescue"))]
+#[derive(Default, Debug)]
+pub(crate) struct ChainCircuit<F: PrimeField> {
+    pub(crate) inputs: [Value<F>; 3],
+    pub(crate) poseidon: usize,
+    pub(crate) rescue: usize
+}
+
+// Preimage-knowledge circuit structure: the input words are private and only the digest (state[0]) is public
+pub(crate) struct PreimageCircuit<F: PrimeField, P> {
+    pub(crate) s0: Value<F>,
+    pub(crate) s1: Value<F>,
+    pub(crate) s2: Value<F>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// 2-to-1 compression circuit structure: two private rate words, the domain tag in the capacity, public digest
+#[cfg(test)]
+pub(crate) struct CompressionCircuit<F: PrimeField, P> {
+    pub(crate) a0: Value<F>,
+    pub(crate) a1: Value<F>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// conditional swap circuit structure: two private words and a private bit, the swapped pair is public
+#[cfg(test)]
+pub(crate) struct SwapCircuit<F: PrimeField, P> {
+    pub(crate) a: Value<F>,
+    pub(crate) b: Value<F>,
+    pub(crate) bit: Value<F>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// collision claim circuit structure: two private input states permuted with the same chip, and a public bit that is 1
+// exactly when their digests (state[0]) are equal
+#[cfg(test)]
+pub(crate) struct CollisionClaimCircuit<F: PrimeField, P> {
+    pub(crate) left: [Value<F>; 3],
+    pub(crate) right: [Value<F>; 3],
+    pub(crate) _marker: PhantomData<P>
+}
+
+// batch circuit structure: independent permutations of private input states, one public digest per input. halo2 0.3
+// has no Circuit::Params, so the batch size is the length of `inputs`, which without_witnesses() keeps; the inputs are
+// a Vec of states, or a stream that produces each state when its permutation is assigned
+pub(crate) struct BatchCircuit<F: PrimeField, P, S = Vec<[Value<F>; 3]>> {
+    pub(crate) inputs: S,
+    pub(crate) _marker: PhantomData<(F, P)>
+}
+
+// transcript circuit structure: private transcript elements absorbed through the sponge, the squeezed challenge is
+// public; the number of elements is the length of `elements`, which without_witnesses() keeps
+#[cfg(feature = "sponge")]
+pub(crate) struct TranscriptCircuit<F: PrimeField, P> {
+    pub(crate) elements: Vec<Value<F>>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// byte message circuit structure: the packed chunks of a message are private witnesses after its length, which is a
+// constant of the circuit, and the sponge digest is public; the chunks are not range checked, so the circuit proves
+// knowledge of packed elements rather than of bytes. The chunks are a Vec of packed elements, or PackedChunks, which
+// packs each chunk from the message bytes when the sponge absorbs it
+#[cfg(feature = "sponge")]
+pub(crate) struct BytesCircuit<F: PrimeField, P, S = Vec<Value<F>>> {
+    pub(crate) length: usize,
+    pub(crate) chunks: S,
+    pub(crate) _marker: PhantomData<(F, P)>
+}
+
+// stream circuit structure: a private seed absorbed through the sponge, `outputs` words squeezed in duplex mode and
+// exposed at instance rows 0..outputs; the seed length and the number of outputs shape the circuit
+#[cfg(feature = "sponge")]
+pub(crate) struct StreamCircuit<F: PrimeField, P> {
+    pub(crate) seed: Vec<Value<F>>,
+    pub(crate) outputs: usize,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// binary Merkle path circuit structure: the leaf, one sibling and one path bit per level are private, the root is
+// public at instance row 0; a set bit means the current node is the right child, and the depth is the length of
+// `siblings`, which without_witnesses() keeps
+#[cfg(feature = "merkle")]
+pub(crate) struct MerklePathCircuit<F: PrimeField, P> {
+    pub(crate) leaf: Value<F>,
+    pub(crate) siblings: Vec<Value<F>>,
+    pub(crate) bits: Vec<Value<F>>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// membership circuit structure: a private leaf, its binary Merkle path as in MerklePathCircuit and a private secret;
+// the root is public at instance row 0 and the nullifier H(leaf, secret), the compression of the same leaf cell with the
+// secret, at instance row 1
+#[cfg(feature = "merkle")]
+pub(crate) struct MembershipCircuit<F: PrimeField, P> {
+    pub(crate) leaf: Value<F>,
+    pub(crate) secret: Value<F>,
+    pub(crate) siblings: Vec<Value<F>>,
+    pub(crate) bits: Vec<Value<F>>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// 4-ary Merkle path circuit structure over the width-5 chip: per level three private siblings and the node's position
+// as two private bits (low, high); the siblings are the node's pair mate followed by the other pair in order, the
+// root is public at instance row 0
+#[cfg(feature = "merkle")]
+pub(crate) struct Merkle4PathCircuit<F: PrimeField, P> {
+    pub(crate) leaf: Value<F>,
+    pub(crate) siblings: Vec<[Value<F>; 3]>,
+    pub(crate) bits: Vec<[Value<F>; 2]>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+// implementation of the Circuit trait for the Poseidon Circuit
+#[cfg(feature = "poseidon")]
+impl<F: PrimeField> Circuit<F> for PoseidonCircuit<F> {
+    type Config = PoseidonChipConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the output mode shapes the copy constraints, so it is kept for keygen
+    fn without_witnesses(&self) -> Self {
+        Self { output_mode: self.output_mode, ..Self::default() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        PoseidonChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("poseidon.synthesize", output_mode = ?self.output_mode).entered();
+        let chip = PoseidonChip::construct(config);
+        let result = chip.permute(
+            layouter.namespace(|| "poseidon_permutation"),
+            self.s0,
+            self.s1,
+            self.s2
+        )?;
+
+        // the output mode decides how many words become public
+        let count = self.output_mode.num_outputs(&chip.config().permutation_params.common_params);
+        chip.expose_outputs(layouter.namespace(|| "outputs"), result, count)
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl<F: PrimeField> PoseidonCircuit<F> {
+    // the permutation of the private `inputs`, with the words `output_mode` selects as public instances; fails when
+    // the active parameters cannot be configured (see PermutationChip::check_parameters)
+    pub fn new(inputs: [Value<F>; 3], output_mode: OutputMode) -> Result<Self, BenchmarkError> {
+        PoseidonChip::<F>::check_parameters()?;
+        let [s0, s1, s2] = inputs;
+        Ok(PoseidonCircuit { s0, s1, s2, output_mode })
+    }
+}
+
+#[cfg(feature = "rescue")]
+impl<F: PrimeField> RescueCircuit<F> {
+    // the permutation of the private `inputs`, with the words `output_mode` selects as public instances; fails when
+    // the active parameters cannot be configured
+    pub fn new(inputs: [Value<F>; 3], output_mode: OutputMode) -> Result<Self, BenchmarkError> {
+        RescueChip::<F>::check_parameters()?;
+        let [s0, s1, s2] = inputs;
+        Ok(RescueCircuit { s0, s1, s2, output_mode })
+    }
+}
+
+// implementation of the Circuit trait for the Rescue-Prime Circuit
+#[cfg(feature = "rescue")]
+impl<F: PrimeField> Circuit<F> for RescueCircuit<F> {
+    type Config = RescueChipConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the output mode shapes the copy constraints, so it is kept for keygen
+    fn without_witnesses(&self) -> Self {
+        Self { output_mode: self.output_mode, ..Self::default() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        RescueChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("rescue.synthesize", output_mode = ?self.output_mode).entered();
+        let chip = RescueChip::construct(config);
+        let result = chip.permute(
+            layouter.namespace(|| "rescue_permutation"),
+            self.s0,
+            self.s1,
+            self.s2
+        )?;
+
+        // the output mode decides how many words become public
+        let count = self.output_mode.num_outputs(&chip.config().permutation_params.common_params);
+        chip.expose_outputs(layouter.namespace(|| "outputs"), result, count)
+    }
+}
+
+// implementation of the Circuit trait for both permutations sharing columns
+#[cfg(all(feature = "poseidon", feature = "rescue"))]
+impl<F: PrimeField> Circuit<F> for DualCircuit<F> {
+    type Config = DualConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    // the Poseidon chip is always configured first: selectors are allocated in configure order, and that order is
+    // part of the verifying key
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        DualConfig {
+            poseidon: PoseidonChip::configure(meta, advice, constants, instance, poseidon_params(), ChipOptions::default()),
+            rescue: RescueChip::configure(meta, advice, constants, instance, rescue_params(), ChipOptions { instance_offset: 3, ..ChipOptions::default() })
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("dual.synthesize").entered();
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let rescue = RescueChip::construct(config.rescue);
+        let outputs_ps = poseidon.permute(layouter.namespace(|| "poseidon_permutation"), self.s0, self.s1, self.s2)?;
+        let outputs_rs = rescue.permute(layouter.namespace(|| "rescue_permutation"), self.s0, self.s1, self.s2)?;
+
+        // Poseidon outputs at instance rows 0..3, Rescue-Prime outputs at rows 3..6 through its instance offset
+        poseidon.expose_outputs(layouter.namespace(|| "poseidon_outputs"), outputs_ps, 3)?;
+        rescue.expose_outputs(layouter.namespace(|| "rescue_outputs"), outputs_rs, 3)
+    }
+}
+
+// implementation of the Circuit trait for two Poseidon permutations sharing columns, the second chip's outputs at
+// instance rows OFFSET..OFFSET + 3; an OFFSET below 3 overlaps the first chip's rows and fails synthesis
+#[cfg(all(test, feature = "poseidon"))]
+impl<F: PrimeField, const OFFSET: usize> Circuit<F> for TwinCircuit<F, OFFSET> {
+    type Config = TwinConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        TwinConfig {
+            first: PoseidonChip::configure(meta, advice, constants, instance, poseidon_params(), ChipOptions::default()),
+            second: PoseidonChip::configure(meta, advice, constants, instance, poseidon_params(), ChipOptions { instance_offset: OFFSET, ..ChipOptions::default() })
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("twin.synthesize").entered();
+        let ranges = [&config.first, &config.second].map(|config| (config.circuit_params.instance_offset, 3));
+        check_instance_ranges(&ranges).map_err(|error| {
+            tracing::error!("{}", error);
+            Error::Synthesis
+        })?;
+
+        let first = PoseidonChip::construct(config.first);
+        let second = PoseidonChip::construct(config.second);
+        let [a0, a1, a2] = self.first;
+        let [b0, b1, b2] = self.second;
+        let outputs_first = first.permute(layouter.namespace(|| "first_permutation"), a0, a1, a2)?;
+        let outputs_second = second.permute(layouter.namespace(|| "second_permutation"), b0, b1, b2)?;
+        first.expose_outputs(layouter.namespace(|| "first_outputs"), outputs_first, 3)?;
+        second.expose_outputs(layouter.namespace(|| "second_outputs"), outputs_second, 3)
+    }
+}
+
+// implementation of the Circuit trait for a chain of permutations, with the dual circuit's configuration
+#[cfg(all(feature = "poseidon", feature = "rescue"))]
+impl<F: PrimeField> Circuit<F> for ChainCircuit<F> {
+    type Config = DualConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the chain's length shapes the circuit, so it is kept for keygen
+    fn without_witnesses(&self) -> Self {
+        ChainCircuit { inputs: [Value::unknown(); 3], ..*self }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        DualCircuit::<F>::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("chain.synthesize", poseidon = self.poseidon, rescue = self.rescue).entered();
+        if self.poseidon + self.rescue == 0 {
+            tracing::error!("a chain needs at least one permutation");
+            return Err(Error::Synthesis);
+        }
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let rescue = RescueChip::construct(config.rescue);
+
+        let [a0, a1, a2] = self.inputs;
+        let mut state: Option<[Number<F>; 3]> = None;
+        for step in 0..self.poseidon + self.rescue {
+            let mut layouter = layouter.namespace(|| format!("chain_{}", step));
+            let initial = match &state {
+                Some([s0, s1, s2]) => [StateWord::Copy(s0), StateWord::Copy(s1), StateWord::Copy(s2)],
+                None => [StateWord::Witness(a0), StateWord::Witness(a1), StateWord::Witness(a2)]
+            };
+            state = Some(match step < self.poseidon {
+                true => poseidon.permute_state(layouter.namespace(|| "poseidon_permutation"), initial)?,
+                false => rescue.permute_state(layouter.namespace(|| "rescue_permutation"), initial)?
+            });
+        }
+
+        // the Poseidon chip exposes at instance offset 0, whichever chip ran last
+        let [digest, _, _] = state.expect("at least one permutation");
+        poseidon.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+// manual Default/Debug so the chip type parameter needs neither
+impl<F: PrimeField, P> Default for PreimageCircuit<F, P> {
+    fn default() -> Self {
+        PreimageCircuit { s0: Value::unknown(), s1: Value::unknown(), s2: Value::unknown(), _marker: PhantomData }
+    }
+}
+
+impl<F: PrimeField, P> Debug for PreimageCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreimageCircuit").field("s0", &self.s0).field("s1", &self.s1).field("s2", &self.s2).finish()
+    }
+}
+
+// implementation of the Circuit trait for the preimage-knowledge statement over either chip
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for PreimageCircuit<F, P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("preimage.synthesize", permutation = P::NAME).entered();
+        let chip = P::from_config(config);
+        let [digest, _, _] = chip.permute(
+            layouter.namespace(|| "preimage_permutation"),
+            self.s0,
+            self.s1,
+            self.s2
+        )?;
+
+        // the digest is the only public value
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+#[cfg(test)]
+impl<F: PrimeField, P> Default for CompressionCircuit<F, P> {
+    fn default() -> Self {
+        CompressionCircuit { a0: Value::unknown(), a1: Value::unknown(), _marker: PhantomData }
+    }
+}
+
+#[cfg(test)]
+impl<F: PrimeField, P> Debug for CompressionCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressionCircuit").field("a0", &self.a0).field("a1", &self.a1).finish()
+    }
+}
+
+// implementation of the Circuit trait for the domain-separated compression over either chip
+#[cfg(test)]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for CompressionCircuit<F, P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("compression.synthesize", permutation = P::NAME).entered();
+        let chip = P::from_config(config);
+        let digest = chip.hash(layouter.namespace(|| "compression"), self.a0, self.a1)?;
+
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+#[cfg(test)]
+impl<F: PrimeField, P> Default for SwapCircuit<F, P> {
+    fn default() -> Self {
+        SwapCircuit { a: Value::unknown(), b: Value::unknown(), bit: Value::unknown(), _marker: PhantomData }
+    }
+}
+
+#[cfg(test)]
+impl<F: PrimeField, P> Debug for SwapCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SwapCircuit").field("a", &self.a).field("b", &self.b).field("bit", &self.bit).finish()
+    }
+}
+
+// implementation of the Circuit trait for the conditional swap over either chip's select gate
+#[cfg(test)]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for SwapCircuit<F, P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("swap.synthesize", permutation = P::NAME).entered();
+        let chip = P::from_config(config);
+        let a = chip.load_private(layouter.namespace(|| "a"), self.a)?;
+        let b = chip.load_private(layouter.namespace(|| "b"), self.b)?;
+        let [left, right] = chip.cond_swap(layouter.namespace(|| "cond_swap"), self.bit, &a, &b)?;
+
+        chip.expose_as_public(layouter.namespace(|| "left"), left, 0)?;
+        chip.expose_as_public(layouter.namespace(|| "right"), right, 1)
+    }
+}
+
+#[cfg(test)]
+impl<F: PrimeField, P> Default for CollisionClaimCircuit<F, P> {
+    fn default() -> Self {
+        CollisionClaimCircuit { left: [Value::unknown(); 3], right: [Value::unknown(); 3], _marker: PhantomData }
+    }
+}
+
+#[cfg(test)]
+impl<F: PrimeField, P> Debug for CollisionClaimCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CollisionClaimCircuit").field("left", &self.left).field("right", &self.right).finish()
+    }
+}
+
+// implementation of the Circuit trait for the digest comparison over either chip's is-equal gate
+#[cfg(test)]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for CollisionClaimCircuit<F, P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("collision_claim.synthesize", permutation = P::NAME).entered();
+        let chip = P::from_config(config);
+        let [l0, l1, l2] = self.left;
+        let [r0, r1, r2] = self.right;
+        let [left, _, _] = chip.permute(layouter.namespace(|| "left"), l0, l1, l2)?;
+        let [right, _, _] = chip.permute(layouter.namespace(|| "right"), r0, r1, r2)?;
+        let equal = chip.is_equal(layouter.namespace(|| "is_equal"), &left, &right)?;
+
+        chip.expose_as_public(layouter.namespace(|| "equal"), equal, 0)
+    }
+}
+
+impl<F: PrimeField, P, S: Debug> Debug for BatchCircuit<F, P, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchCircuit").field("inputs", &self.inputs).finish()
+    }
+}
+
+// implementation of the Circuit trait for a batch of permutations over either chip
+impl<F: PrimeField, P: PermutationChip<F>, S: InputStream<[Value<F>; 3]>> Circuit<F> for BatchCircuit<F, P, S> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the batch size shapes the circuit, so only the values are dropped
+    fn without_witnesses(&self) -> Self {
+        BatchCircuit { inputs: self.inputs.without_witnesses(), _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("batch.synthesize", permutation = P::NAME, batch_size = self.inputs.len()).entered();
+        let chip = P::from_config(config);
+
+        // each permutation gets its own region, laid out one after the other; digest i is public at instance row i
+        for (row, [s0, s1, s2]) in self.inputs.stream().enumerate() {
+            let [digest, _, _] = chip.permute(layouter.namespace(|| format!("permutation_{}", row)), s0, s1, s2)?;
+            chip.expose_as_public(layouter.namespace(|| format!("digest_{}", row)), digest, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+// keyed sponge MAC circuit structure: "I know a key such that MAC_key(message) = tag", with the key private and the
+// tag (instance row 0) and message words (rows 1, 2, ...) public; without_witnesses() keeps the message length
+#[cfg(feature = "sponge")]
+pub(crate) struct MacCircuit<F: PrimeField, P> {
+    pub(crate) key: Value<F>,
+    pub(crate) message: Vec<Value<F>>,
+    pub(crate) _marker: PhantomData<P>
+}
+
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P> Debug for MacCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MacCircuit").field("key", &self.key).field("message", &self.message).finish()
+    }
+}
+
+// implementation of the Circuit trait for the keyed sponge over either chip
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for MacCircuit<F, P> {
+    type Config = SpongeConfig<P::Config>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the message length shapes the circuit, so only the values are dropped
+    fn without_witnesses(&self) -> Self {
+        MacCircuit { key: Value::unknown(), message: vec![Value::unknown(); self.message.len()], _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure_sponge::<F, P>(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("mac.synthesize", permutation = P::NAME, words = self.message.len()).entered();
+        let chip = P::from_config(config.chip.clone());
+
+        // the message words are witnessed once, exposed, and copied into the sponge
+        let mut message = Vec::new();
+        for (index, word) in self.message.iter().enumerate() {
+            let word = chip.load_private(layouter.namespace(|| format!("message_{}", index)), *word)?;
+            chip.expose_as_public(layouter.namespace(|| format!("public_message_{}", index)), word.clone(), index + 1)?;
+            message.push(word);
+        }
+
+        let words: Vec<StateWord<'_, F>> = message.iter().map(StateWord::Copy).collect();
+        let tag = Sponge::new_keyed(self.key).hash(&chip, &config, layouter.namespace(|| "keyed_sponge"), &words)?;
+        chip.expose_as_public(layouter.namespace(|| "tag"), tag, 0)
+    }
+}
+
+// public message circuit structure: the message is public in a second instance column instead of private, and the
+// sponge absorbs it from there through assign_advice_from_instance. The column has room for `max_len` words after the
+// length (public_message_column), so the circuit has no witness and its shape depends on `max_len` alone; the digest is
+// public at row 0 of the chip's instance column
+#[cfg(feature = "sponge")]
+pub(crate) struct PublicMessageCircuit<F: PrimeField, P> {
+    pub(crate) max_len: usize,
+    pub(crate) _marker: PhantomData<(F, P)>
+}
+
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P> Debug for PublicMessageCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PublicMessageCircuit").field("max_len", &self.max_len).finish()
+    }
+}
+
+// implementation of the Circuit trait for a public message hashed through the sponge over either chip
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for PublicMessageCircuit<F, P> {
+    type Config = PublicMessageConfig<P::Config>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // every absorbed word comes from the instance, so there is nothing to drop
+    fn without_witnesses(&self) -> Self {
+        PublicMessageCircuit { max_len: self.max_len, _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure_public_message::<F, P>(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("public_message.synthesize", permutation = P::NAME, max_len = self.max_len).entered();
+        let chip = P::from_config(config.sponge.chip.clone());
+        // the length and every slot, used or padding, are copied from the message column as their blocks are absorbed
+        let words = (0..=self.max_len).map(|row| StateWord::Instance(config.message, row));
+        let digest = Sponge::new().hash_stream(&chip, &config.sponge, layouter.namespace(|| "sponge"), words)?;
+
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P> Debug for TranscriptCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TranscriptCircuit").field("elements", &self.elements).finish()
+    }
+}
+
+// implementation of the Circuit trait for a transcript hashed through the sponge over either chip
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for TranscriptCircuit<F, P> {
+    type Config = SpongeConfig<P::Config>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the number of elements shapes the circuit, so only the values are dropped
+    fn without_witnesses(&self) -> Self {
+        TranscriptCircuit { elements: vec![Value::unknown(); self.elements.len()], _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure_sponge::<F, P>(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("transcript.synthesize", permutation = P::NAME, elements = self.elements.len()).entered();
+        let chip = P::from_config(config.chip.clone());
+        let elements: Vec<StateWord<'_, F>> = self.elements.iter().copied().map(StateWord::Witness).collect();
+        let challenge = Sponge::new().hash(&chip, &config, layouter.namespace(|| "sponge"), &elements)?;
+
+        chip.expose_as_public(layouter.namespace(|| "challenge"), challenge, 0)
+    }
+}
+
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P: PermutationChip<F>, S: InputStream<Value<F>>> Circuit<F> for BytesCircuit<F, P, S> {
+    type Config = SpongeConfig<P::Config>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the message length shapes the circuit, so only the chunk values are dropped
+    fn without_witnesses(&self) -> Self {
+        BytesCircuit { length: self.length, chunks: self.chunks.without_witnesses(), _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure_sponge::<F, P>(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("bytes.synthesize", permutation = P::NAME, length = self.length).entered();
+        assert_eq!(self.chunks.len(), self.length.div_ceil(BYTES_PER_ELEMENT), "the chunks do not pack a {}-byte message", self.length);
+        let chip = P::from_config(config.chip.clone());
+        // the chunks are pulled as their blocks are absorbed
+        let packed = std::iter::once(StateWord::Constant(F::from(self.length as u64))).chain(self.chunks.stream().map(StateWord::Witness));
+        let digest = Sponge::new().hash_stream(&chip, &config, layouter.namespace(|| "sponge"), packed)?;
+
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+#[cfg(feature = "sponge")]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for StreamCircuit<F, P> {
+    type Config = SpongeConfig<P::Config>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the seed length and the number of outputs shape the circuit, so only the values are dropped
+    fn without_witnesses(&self) -> Self {
+        StreamCircuit { seed: vec![Value::unknown(); self.seed.len()], outputs: self.outputs, _marker: PhantomData }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure_sponge::<F, P>(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("stream.synthesize", permutation = P::NAME, outputs = self.outputs).entered();
+        let chip = P::from_config(config.chip.clone());
+        let seed: Vec<StateWord<'_, F>> = self.seed.iter().copied().map(StateWord::Witness).collect();
+        let stream = Sponge::new().squeeze_many(&chip, &config, layouter.namespace(|| "sponge"), &seed, self.outputs)?;
+
+        for (row, output) in stream.into_iter().enumerate() {
+            chip.expose_as_public(layouter.namespace(|| format!("output_{}", row)), output, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+// example host circuit embedding the Poseidon chip: the host's multiplication gate and the chip share the three advice
+// columns, and the host enables equality and its own constants column itself, so the chip is configured with both
+// options off. It proves knowledge of (a, b) with Poseidon(a, a * b, tag)[0] = digest, the digest public at instance
+// row 0 and the product at row 1
+#[cfg(all(test, feature = "poseidon"))]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HostCircuit<F: PrimeField> {
+    pub(crate) a: Value<F>,
+    pub(crate) b: Value<F>
+}
+
+#[cfg(all(test, feature = "poseidon"))]
+#[derive(Clone, Debug)]
+pub(crate) struct HostConfig<F: PrimeField> {
+    poseidon: PoseidonChipConfig<F>,
+    advice: [Column<Advice>; 3],
+    s_mul: Selector
+}
+
+#[cfg(all(test, feature = "poseidon"))]
+impl<F: PrimeField> Circuit<F> for HostCircuit<F> {
+    type Config = HostConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let constants = meta.fixed_column();
+        enable_constant(meta, constants);
+        meta.enable_equality(instance);
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let s_mul = allocate_selector(meta);
+        create_mul_gate(meta, advice, s_mul);
+
+        // the chip only gets its own round constant columns; everything else is the host's
+        let round_constants = ConstantColumns::Fixed([meta.fixed_column(), meta.fixed_column(), meta.fixed_column()]);
+        let options = ChipOptions { enable_equality: false, enable_constants: false, instance_offset: 0 };
+        HostConfig { poseidon: PoseidonChip::configure(meta, advice, round_constants, instance, poseidon_params(), options), advice, s_mul }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("host.synthesize").entered();
+        let chip = PoseidonChip::construct(config.poseidon);
+
+        // the host's own row: [a, b, a * b] under its multiplication gate
+        let [a, _, product] = layouter.assign_region(
+            || "mul", |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                let a = region.assign_advice(|| "a", config.advice[0], 0, || self.a)?;
+                let b = region.assign_advice(|| "b", config.advice[1], 0, || self.b)?;
+                let product = region.assign_advice(|| "a * b", config.advice[2], 0, || self.a * self.b)?;
+                Ok([a, b, product].map(Number::from))
+            }
+        )?;
+
+        // the tag goes through the host's constants column
+        let tag = StateWord::Constant(chip.params().domain_tag());
+        let [digest, _, _] = chip.permute_state(layouter.namespace(|| "poseidon"), [StateWord::Copy(&a), StateWord::Copy(&product), tag])?;
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)?;
+        chip.expose_as_public(layouter.namespace(|| "product"), product, 1)
+    }
+}
+
+// Anemoi permutation circuit structure for l = L column pairs: the x and y input words are private, all 2l output
+// words are public (x words at instance rows 0..l, then the y words)
+#[cfg(feature = "bench-cli")]
+#[derive(Clone, Debug)]
+pub(crate) struct AnemoiCircuit<F: PrimeField, const L: usize> {
+    pub(crate) x: [Value<F>; L],
+    pub(crate) y: [Value<F>; L]
+}
+
+#[cfg(feature = "bench-cli")]
+impl<F: PrimeField, const L: usize> Default for AnemoiCircuit<F, L> {
+    fn default() -> Self {
+        AnemoiCircuit { x: [Value::unknown(); L], y: [Value::unknown(); L] }
+    }
+}
+
+// bar layer circuit structure for B-bit limbs: the three input words are private, the three output words public
+#[cfg(feature = "bench-cli")]
+#[derive(Clone, Debug)]
+pub(crate) struct BarsCircuit<F: PrimeField, const B: usize> {
+    pub(crate) state: [Value<F>; 3]
+}
+
+#[cfg(feature = "bench-cli")]
+impl<F: PrimeField, const B: usize> Default for BarsCircuit<F, B> {
+    fn default() -> Self {
+        BarsCircuit { state: [Value::unknown(); 3] }
+    }
+}
+
+#[cfg(feature = "merkle")]
+impl<F: PrimeField, P> Debug for MerklePathCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MerklePathCircuit").field("leaf", &self.leaf).field("siblings", &self.siblings).field("bits", &self.bits).finish()
+    }
+}
+
+// implementation of the Circuit trait for a binary Merkle path over either chip: per level the chip's conditional
+// swap orders (node, sibling) and one permutation with the domain tag in the capacity compresses the pair
+#[cfg(feature = "merkle")]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for MerklePathCircuit<F, P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        MerklePathCircuit {
+            leaf: Value::unknown(),
+            siblings: vec![Value::unknown(); self.siblings.len()],
+            bits: vec![Value::unknown(); self.bits.len()],
+            _marker: PhantomData
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("merkle.synthesize", permutation = P::NAME, arity = 2, depth = self.siblings.len()).entered();
+        let chip = P::from_config(config);
+        let tag = StateWord::Constant(chip.params().domain_tag());
+        let mut node = chip.load_private(layouter.namespace(|| "leaf"), self.leaf)?;
+        for (level, (sibling, bit)) in self.siblings.iter().zip(&self.bits).enumerate() {
+            let sibling = chip.load_private(layouter.namespace(|| format!("sibling_{}", level)), *sibling)?;
+            let [left, right] = chip.cond_swap(layouter.namespace(|| format!("swap_{}", level)), *bit, &node, &sibling)?;
+            let [parent, _, _] = chip.permute_state(
+                layouter.namespace(|| format!("compress_{}", level)),
+                [StateWord::Copy(&left), StateWord::Copy(&right), tag]
+            )?;
+            node = parent;
+        }
+
+        chip.expose_as_public(layouter.namespace(|| "root"), node, 0)
+    }
+}
+
+#[cfg(feature = "merkle")]
+impl<F: PrimeField, P> Debug for MembershipCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MembershipCircuit").field("leaf", &self.leaf).field("secret", &self.secret).field("siblings", &self.siblings).field("bits", &self.bits).finish()
+    }
+}
+
+// implementation of the Circuit trait for a membership proof over either chip: the leaf cell feeds both the nullifier
+// compression and the first level of the path, so the nullifier is bound to the leaf the path opens
+#[cfg(feature = "merkle")]
+impl<F: PrimeField, P: PermutationChip<F>> Circuit<F> for MembershipCircuit<F, P> {
+    type Config = P::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        MembershipCircuit {
+            leaf: Value::unknown(),
+            secret: Value::unknown(),
+            siblings: vec![Value::unknown(); self.siblings.len()],
+            bits: vec![Value::unknown(); self.bits.len()],
+            _marker: PhantomData
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        P::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("membership.synthesize", permutation = P::NAME, depth = self.siblings.len()).entered();
+        let chip = P::from_config(config);
+        let tag = StateWord::Constant(chip.params().domain_tag());
+        let leaf = chip.load_private(layouter.namespace(|| "leaf"), self.leaf)?;
+        let secret = chip.load_private(layouter.namespace(|| "secret"), self.secret)?;
+        let [nullifier, _, _] = chip.permute_state(
+            layouter.namespace(|| "nullifier"),
+            [StateWord::Copy(&leaf), StateWord::Copy(&secret), tag]
+        )?;
+
+        let mut node = leaf;
+        for (level, (sibling, bit)) in self.siblings.iter().zip(&self.bits).enumerate() {
+            let sibling = chip.load_private(layouter.namespace(|| format!("sibling_{}", level)), *sibling)?;
+            let [left, right] = chip.cond_swap(layouter.namespace(|| format!("swap_{}", level)), *bit, &node, &sibling)?;
+            let [parent, _, _] = chip.permute_state(
+                layouter.namespace(|| format!("compress_{}", level)),
+                [StateWord::Copy(&left), StateWord::Copy(&right), tag]
+            )?;
+            node = parent;
+        }
+
+        chip.expose_as_public(layouter.namespace(|| "root"), node, 0)?;
+        chip.expose_as_public(layouter.namespace(|| "nullifier"), nullifier, 1)
+    }
+}
+
+#[cfg(feature = "merkle")]
+impl<F: PrimeField, P> Debug for Merkle4PathCircuit<F, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Merkle4PathCircuit").field("leaf", &self.leaf).field("siblings", &self.siblings).field("bits", &self.bits).finish()
+    }
+}
+
+// implementation of the Circuit trait for a 4-ary Merkle path over the width-5 chip: per level the low bit orders the
+// node and its pair mate with a conditional swap, the high bit puts that pair before or after the other one with four
+// selects sharing one bit cell, and compress4 hashes the four children
+#[cfg(feature = "merkle")]
+impl<F: PrimeField, P: WidePermutation<F, 5>> Circuit<F> for Merkle4PathCircuit<F, P> {
+    type Config = WideChipConfig<F, 5, P>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Merkle4PathCircuit {
+            leaf: Value::unknown(),
+            siblings: vec![[Value::unknown(); 3]; self.siblings.len()],
+            bits: vec![[Value::unknown(); 2]; self.bits.len()],
+            _marker: PhantomData
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        WideChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("merkle.synthesize", permutation = P::NAME, arity = 4, depth = self.siblings.len()).entered();
+        let chip = WideChip::<F, 5, P>::construct(config);
+        let mut node = chip.load_private(layouter.namespace(|| "leaf"), self.leaf)?;
+        for (level, (siblings, [low, high])) in self.siblings.iter().zip(&self.bits).enumerate() {
+            let [mate, other0, other1] = siblings.map(|sibling| chip.load_private(layouter.namespace(|| format!("sibling_{}", level)), sibling));
+            let (mate, other0, other1) = (mate?, other0?, other1?);
+            let [pair0, pair1] = chip.cond_swap(layouter.namespace(|| format!("swap_{}", level)), *low, &node, &mate)?;
+
+            // children (pair0, pair1, other0, other1) if the high bit is 0, else (other0, other1, pair0, pair1)
+            let (high, child0) = chip.select(layouter.namespace(|| format!("child0_{}", level)), *high, &other0, &pair0)?;
+            let child1 = chip.select_with_bit(layouter.namespace(|| format!("child1_{}", level)), &high, &other1, &pair1)?;
+            let child2 = chip.select_with_bit(layouter.namespace(|| format!("child2_{}", level)), &high, &pair0, &other0)?;
+            let child3 = chip.select_with_bit(layouter.namespace(|| format!("child3_{}", level)), &high, &pair1, &other1)?;
+            node = chip.compress4(layouter.namespace(|| format!("compress4_{}", level)), [child0, child1, child2, child3])?;
+        }
+
+        chip.expose_as_public(layouter.namespace(|| "root"), node, 0)
+    }
+}
+
+// implementation of the Circuit trait for the Anemoi Circuit
+#[cfg(feature = "bench-cli")]
+impl<F: PrimeField, const L: usize> Circuit<F> for AnemoiCircuit<F, L> {
+    type Config = AnemoiChipConfig<F, L>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        AnemoiChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("anemoi.synthesize", l = L).entered();
+        let chip = AnemoiChip::construct(config);
+        let (x, y) = chip.permute(layouter.namespace(|| "anemoi_permutation"), self.x, self.y)?;
+
+        for (row, word) in x.into_iter().chain(y).enumerate() {
+            chip.expose_as_public(layouter.namespace(|| format!("output_{}", row)), word, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+// implementation of the Circuit trait for the bar layer Circuit
+#[cfg(feature = "bench-cli")]
+impl<F: PrimeField, const B: usize> Circuit<F> for BarsCircuit<F, B> {
+    type Config = BarsChipConfig<F, B>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        BarsChip::configure_default(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = tracing::debug_span!("bars.synthesize", bits = B).entered();
+        let chip = BarsChip::construct(config);
+        chip.load_table(layouter.namespace(|| "bar_table"))?;
+        let words = chip.permute(layouter.namespace(|| "bars_permutation"), self.state)?;
+
+        for (row, word) in words.into_iter().enumerate() {
+            chip.expose_as_public(layouter.namespace(|| format!("output_{}", row)), word, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+// circomlib's poseidon([a, b]) over the BN254 scalar field: the capacity word 0 is a constant in state[0], the two
+// inputs are private in state[1] and state[2], and the digest state[0] is public
+#[cfg(feature = "poseidon")]
+#[derive(Default, Debug)]
+pub(crate) struct CircomPoseidonCircuit {
+    pub(crate) inputs: [Value<Bn256Fr>; 2]
+}
+
+// implementation of the Circuit trait for the circomlib Poseidon Circuit
+#[cfg(feature = "poseidon")]
+impl Circuit<Bn256Fr> for CircomPoseidonCircuit {
+    type Config = PoseidonChipConfig<Bn256Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Bn256Fr>) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        PoseidonChip::configure(meta, advice, constants, instance, bn254_circom(), ChipOptions::default())
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Bn256Fr>) -> Result<(), Error> {
+        let chip = PoseidonChip::construct(config);
+        let [a, b] = self.inputs;
+        let [digest, _, _] = chip.permute_state(
+            layouter.namespace(|| "circom_poseidon"),
+            [StateWord::Constant(Bn256Fr::ZERO), StateWord::Witness(a), StateWord::Witness(b)]
+        )?;
+        chip.expose_as_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
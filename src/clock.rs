@@ -0,0 +1,6 @@
+// monotonic clock for the timings: std::time::Instant panics on wasm32-unknown-unknown, where web-time reads
+// performance.now() instead
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
@@ -0,0 +1,342 @@
+use ff::PrimeField;
+use halo2curves::bls12381::Fr;
+use halo2curves::bn256::Fr as Bn256Fr;
+use num_bigint::BigUint;
+use serde::Serialize;
+use sha3::{Digest, Sha3_256};
+
+use crate::error::BenchmarkError;
+use crate::params::{ArcPosition, field_modulus, parse_constants, parse_fe, table_constants};
+
+// The constant tables of the permutations, written as they were published, and the presets that name them. The
+// builders take their default constants from a preset by name rather than from a table, so a report can name the preset
+// and its content hash.
+
+// Poseidon round constants
+pub(crate) const ROUND_CONSTANTS_PS: [&str; 195] = ["48991097081732275468845314168021420565497297775988823234113406403095118809216", "38385660029618165285848698857635215143135976511856402182142757680787979296154", "45664917788634056160947231182803089169570746657219074370482409200042991921246", "46611823467219910333349433978991031443945697128435279755908258896090196676828", "21239555800391983336673016232252577145979304597102502292785557024177155115319", "5444549814002252718699361548642546874417220826495496552290417094191494299797", "6120941817780228594851185625662354154126315032538247033968198498911791651970", "23268934541565483112488314239282439244757346303484537549209002605218913236536", "34778900561716047730386110499058136122597669775051061603711724688203374984731", "11866412958831620887953860204795878894545618212709331023611019011793447488176", "1292810553955081089139103033821163176614817808018762694232693357405135340213", "29829440149074940820671559824872937980763748927491238614065138142835318453671", "43007325278312980663982452106946226844964622384017700838855297379677047113384", "6207852559847946300667836829798951848361581084433525098597857899536657157132", "51263844854419207560514475863120683772532929850629546992690510884221364990253", "47537207485065031976374469967696134772574834313568026823983918780308518394040", "2221931791899303960239149702171682649773262449196140787838362753706579104592", "39456839086017037141295863080128693714705835125922448198802062180577619415688", "7307684192235537965831376311417883513796535701244096178785218530839409056523", "40363790847223872255995860144037894400158879326818322790255787884037990480527", "46370977865329511267956842930057959446221524060145738210680245530954549945015", "31963375456062604704511762940421329756212766442452555529101241339674782334039", "14931035994999669353073307088521670981122374648927581516990615825314462827897", "9146050314741225622437907700594105481623623087635695897868792721147700541623", "43028866523328004770172322384235815492694573248368601737155468843525625413279", "10642771813466087799681476709295362996886361934733270333728358675267521442184", "26204626472182247586446753357603232226235570940686295317661191583409532523578", "51764778305842182544341507127328333397682018984536762517144144495830254727692", "46323013798997081811959707047808149003166619133464450127989691277775183404349", "5482714761779403197336605367697000529513289823583027739458069397684408687717", "12801259943830582826718901632357112368256632783422449824889858551937326401170", "24705221370028061177410670936487461711735994635988936070623351799675117594850", "34818354068777339891091714877681898548352650337240481539567373888981659308099", "35437981511765462742605234803376772682840664204821301764084738573774616215109", "1433523918194521021731556457516832465819757187635645935518277720319249889445", "1786444825311968572352002116054188762971225383128313206702203805257523693888", "22232073076796622550494050910209988454596433174206874696362037700514082492276", "24042430109235922611027968831657325520072553641473321784508698720854180658031", "45406805567398680921065452923276055166961588153660261520529196040913487916279", "35053262861048825411061280559553895536192334830763062477277235807515959383150", "25108964803188800737437394246442073858261740146181095550988111856238954490309", "35192650141137106058577418514209092904214762437910434967540336800650620041958", "34220944794619662782589792809938215078980533657269200933482014763836254210880", "39884393792242132075258602070541114557272278571033974158755307717930033808078", "6528627567246138898338135471584665860403024864125846353758054588554049365178", "26135348890537017135058266369936506677345001674530050056494732502158573534651", "45940975099728729872716617510434185869788979733816569378448209603957649084497", "15421094974171181812057105309783852016087843260648209913425190920580878315912", "17821536801502538623431403481143359660601434134694528982404802873816360858943", "8010729838943058740614807905113741378835761166137481371357965047712306801123", "18699215163509883263304393673283276029620709331747651039747044003384506899917", "37045787943638220002917633921716309877792707850558591835874081145770158399128", "21575637935417645110089037900895429146838845113516284564671508366546944971174", "1788789771738709712587591109966362080868778924904243569200231458308784197447", "31893695366599021197812621371715665903315747385247436549810717167321695484766", "51153400179598348220410722401172031495931771158209082356586940118519763307990", "27065341612806387486757726552834268222391812301897865130062594135449450311205", "21631377794423816098233500204394685009343254816615902551641496756763638503963", "48126155452550090941025807356211843589751116110477652511672279566428926247148", "41945332685105951593851845839403181725987901258063429769257339995392450728766", "24296067579767080403247766323431204628341605710487447431323947636125286730412", "15881178462681378844988252603563609691162651204658664856493588769950563205407", "33027381395215663927148306470841421013404116814305740800948949823021554274098", "39278310473084767209787340524936392884387815060990743323143945308386189000820", "36914830105593239127583246606078015086694578878061417360363710472659792271157", "2471481831227881021689006198592503194795082772689986463565415296171852015386", "10133170919569185596470854926690039229735632740212998846069400800395437949818", "13713875128407368240685505357662717227751490836079655538057610707920043576169", "8342666644640774986634432327796294683569398370446186977217700283927741456745", "46601389125814748868096111624907238097032545985765609175268428943258314495300", "20955390743109511563797223108807741951396100480021156649651505770632943438749", "30784566406743698397200754777301033281231860349200935908047757137616877875074", "48343196439030272896030042717039190414055291776286919553358305329065060244544", "5454630884154432785537568532823077194524789618913833351503828005963129645447", "5929264687259766357446095238429932392315604113095822327000589827415320983004", "22075444908821639097706881947036304396835729534515628434816919715415538390017", "25941058816975140552446994550948593572939163972016393579803457030200129476973", "39776348414428957147819346902864822521632016599308432283712625663034427240337", "7416720880414633042939600412231360970614004283597614937824398530497243499212", "27759512177446113435859126093069895419463054324674208616122176370583357562941", "2693390255841122228782459820336527344026453452088174693463152401174043438469", "50367239350666539482528955684311280608817276753868085587890812549436189586564", "16174733649048109460569124327899128868049112853807486992529031028618670502840", "25032516686620026063532769674876936116496163673410980298313095252836905833243", "29144403930621998939944109351403497411548441156029659945515675350299265094466", "2003270776024057925128728348175382837282431082428047352264694823915738934597", "33363216671247018657387321397537436143187354110057266627888117938607035196831", "20203086474546098412356910533884833744816739556295954278635367853784856438617", "42960220771318412318176969631346524408076008158165832346168142557674200614679", "6311431299350400649257553117850994107778654765725553469026713480041524237057", "20356164198757608998824195662812920762417225019317083164408248459556033087792", "50934696509775059306730966013034554090787668615778167832259926621090584698298", "12540543785093585171832085015032615168496292565469198040103631290639480719638", "7087832377964131545651220267742883342179930832350845193376391176592931716961", "34984411233898940973869087861225504483500912780307024595154545196097892807889", "35766364158306764887416108757297765472332147961010533956614913565935878448984", "1765971701998656161486995693692800538505518481763639488010072221442068236951", "52296260704967533238281867983484652098827616020272035805695017707768629021210", "4935673489774322197628160742241883723281125866438378640636969542959380659457", "49493374663267588751846054378343301708694531580092984346087290317742537210902", "11234520985865325412206403291118519753189986845681526796638090446788348697652", "24240566602759984788029880030276085623682320979885122363103446030346976862554", "45173673056688650486124798353267048676515652881324846851443098010775612892322", "273339079894952168974065527137723282564095652951909656957160946114792896627", "4470325051640351957976738782642661997153601739638632363210829100051811744274", "35146154431885107533179241729875580217482204780231937987130147605583867466092", "5623976303155942456710618286519758761204923686926813378548021075733755166889", "24016465951530015578209275233668961482322584131459513288081598210134015257997", "17969920097176891022415687639709999939084490545645205326481661860931808113029", "45152206508674411747856285000257938228137174933577379726580072509850619926251", "38945634795250927360607537392732805897873100986379288027606175928019977509609", "32851666289693613044889283133849490343674968726730793059165429991055922454070", "31944620853700630151347751910587969550223781655480776781612692884058563662268", "25256966274452535017610572446887439115046074651331211781708168773655007778872", "9486939021502590608732001628331695421223550406038486802197261945175668785507", "39459143086960362426927505137137876218390935544236059938922871880000296175208", "31894450224048346260322339655447950546670422421242715439734122749915296243605", "26892539091318428420931225040417651442139701587930804697886023619431558542747", "2542844944718735302766446637202404427628413878092734865912744553984157161261", "31883859221346313107414474846252752604992097590133961842848913019073014153010", "51303361359653464050006771537341226976539604964205923399469614564706008834052", "51171387502764330562774849667033034283056080450385872897204773223645085369254", "7237091576916241695047293084522141336268656276386088021954481852199921973216", "25026554458962841467968682601680143746537618788336396538569095145280445662154", "16003513886762983460717836271035484656754723355114772159990269505739759600774", "20742179979178809796122395691368538694837598010689782796398715701486525085958", "44785832974715571208383539748048195425158621451201620091409304675643540484444", "40997683756979855969631370242290487603852436449608298499325558394715696204831", "24039577999618876159836452559464600377553684696598310542830185648570694947325", "214991500380221402745874275507138825943309188151683861156767017258335759518", "37648944229324812379904445632193391903358473357814505256571234492472677352375", "33262001091080721927187326829375441597312853742311915461357184164050334176171", "12889759088432190033171086881844675377815686311282488955569491035800531227592", "38889970121432469903433846063190552781925277874128916432889442865031400486457", "9686759546395317438502700818478291413888291261781927399197594299119600593872", "25228839869827315437841994432860023863461613471517457235105091951188556007171", "29251067411858749210993269168637503659802522399342640488863629751155422442084", "40912660681512278236165911366927220401330409827994264103091984300131586078341", "12796501909444494709088656380507035418412240267936921974592450125220369752821", "41489997591227135571666436387925119767986380278590920811343183082128452793080", "21497862265009693334292006570547451455021214638930393134366176167326805799325", "42759488993366187559528022270353477068325476435317366129099617149236057994173", "51812786435352958751631482409057671996557140765865434087196139886155873550638", "49668984917578993057336571483567900930503120626539459296975328351727319861276", "16647828498038646540925328826301561929374469486623027976723819473821480409681", "48148303340548214354795067112758174231010308760482898449349672592745234924387", "40514099213939369482769058963482609316155051560990264349668700968914554718236", "36567947302783543506732234132138195442155777559454242003814702099955749246290", "22396816925035795192842094319757131771178499933587237012855640944068186589937", "47761479716265566311036142819261705369735044145214592608213591050556455450430", "13277094590686127307617107451297268367321013828763858520220510028318248040673", "6273610774394348396010704017556554992266752629801490457323912355626787108751", "47394279615623798760617602748864924711531390489909756029248999925570450315302", "27952252793623580780344613559829677253211432925530630621608481053048520434744", "1683222943011658234228486862639342402730538635204883039431226239924268835592", "6849709550515639669397513895396396226183305237153796793058311861850242817732", "51524350017816629912679960748295545024593637560633508281874724597080573807830", "26590614177194547630006347843068513496427790322854759433492355517360208924714", "31548830001396651725711310298465958490865636855427227043617585502978053092924", "14291568473806392803367440164088272381690062239638560607879858528716058147676", "21146452903160991922099734199583866923318964586815062550024895407430164358523", "22961005724583382013438450487662047962072123198815308647967555251332825175693", "4752908842318626074338926279870993084957055641402767877988223199262408017438", "41544523600430331260332604149473035199994864893327747257504064038791086157408", "17323878296591859990733132832893641096022161936583121997952997880406237212813", "18014582744613086697405046476881081314871698927785490238333612330034405321202", "45325447140824171211209633262297712878556500592023247082629492785769121758434", "6192753434333002929210820794040779560623421075700800400752599138519650269040", "12937001546279985738495952624875312380127801527837660882855310431015537184413", "45991618799696924909840068913271150748052998998510820293768267349781597832497", "37441188106719457933929221474454571110916912448355945524409576665808556247872", "49875923679586708113406579244909793162425404239213510953269412337363307325571", "15051465698071304017966667797323113094420513709580063806706433232853573089040", "10338905189138871748742400929101717755982978259187828256039071250817040249017", "40261933448177008341539991920645739011692467645144896682394869561245899318641", "38346498339252184147870281431364733631809877281747451440216067081256241485418", "6209216396715641040468803949857167055175110420218294975303260728579180870134", "25923422290512595808420551575642237631007497169886590851128840338102194873726", "11953618934086915505672657493115697182858104796786340137294500949047339928290", "48506710952023206646326838201389789459004051035511888474426942257560405427104", "49584811575438811511092715559885015474424100729555178730940640525393341823572", "25222528947373923151054372702664425173210441980263130389325557963853429239320", "36212452941316997504575803214309342413443151488267891949906815090453746563323", "19548334171603533109137618032918088438321356008712800140019849908969476369140", "13369714008256347363334888026585995433724817786797528430136744458743428376798", "23153174875441426069922538845839074574095797738892298576581895020444392853731", "19950632315767750645780485212179021291844439659606854957365124208057044477001", "4990085320684307481424051057758258811192003289472239932032551966513564492664", "29810043862384409261569733347989054089853302964778668946432779952952625186706", "10937492441648375945337911315608624372433158520395209903090712138844575570844", "24981706249730491732129119057314109520549309496394969130105355950186024721860", "10498082524469215029826843019306692952360905490979497919767209022386939911216", "15682375221169428458922809183562392617423770660027773228464622792081026981791", "41914385147673242564111169184735297479310144571630342213035237856939024640011", "39667818743665708661866396692813914317148400284941420155363896112617842800421"];
+
+// Rescue-Prime round constants
+pub(crate) const ROUND_CONSTANTS_RS: [&str; 84] = ["35495817390819093545263349384941809089491580678942832859579453034368810736263", "4734865798690304458175502708216292605326887152358688691882538799996069070938", "31271008447681288492961289082649653266089021637020407236527451612237705002107", "3752272659749554246987316978069954116630957098620898965749354210894049705204", "22641555720019163306763445608116202165619173600682976754848212896631953422071", "28122533469631806190969995639553619503758826280316271478360761787725211583550", "25847917841495375497002109968427099088777388041775300281757084913772616807196", "32694606500120353152300866547101238346520817919199364752958292990138213972843", "27286327057691837800467727052167328890802672763096896941933952396730026264130", "11421505857991327619183254231367489753132565965114463729904675480639756627135", "521411871436069789624101480374109564923458769959324381065745329697883697117", "23880784307761253829209017376202022699450440759526482483183942457652656506129", "32944735989607121897647886317992117157418889561697480633116336030286723761501", "23809168654834556097350366212084670162247725165957937623679460641681583816451", "3163860194972429483721954648842733164010713297776971497284575674748141326227", "4994154821407041837874226315683255286085207059107827489820229821534877668868", "50472710115457611398312524300398743989276776324315737822995925423912734574272", "251866835357267652745308982111788504159393069098120092619439598668220537943", "29306447221479286209562070090539769526225070913770783266162336064629228514551", "29283041777181961494713136804131952798141345310627850728919908467956333015832", "28656363295645570828788643827370268834132346888229153863515891780361414296486", "25038928963239238795570624926346448459425394096652630785926109997438209703232", "8137054880809446884023200631931681550641379823710586899296036975467179806266", "40023642373942331790709007028495088784452433159634511649021697266107433596568", "35762237949937672281308268151392628513069349315494090383109234785560672634670", "30999566811631951689259246295471339743428563889981096112711184113782054324157", "20279178450660587763205226449293238908131708902882258115414408411285674682667", "7251226788353540177691937542431845975737106489341120571030231114808456476646", "52125099959305698802726608420202937507908602874086034970293871469588059526157", "36947771116325024965590213964896639663487838999452121836698608133540047510904", "6731449362796983987468313257604646517406447849071950000589095424962988643919", "46799204329731723451752711923834870677752669570495984560450489328024837708708", "35089631385082017128756246668734504606091189119241613702809534617529750689438", "48404791586561114467519265925614105026432456534013682923179665391057050944501", "25910045457085525717925797997640841840596905619632452927132962053945891631463", "1002644049329627578859603332717752156946995816186878866098534634466684910592", "27000834541453700882360080348023947440783037764820885196153273422481631245741", "12589074081116083610034305532223638886927072126291986576471860562392225733147", "42427223203410224646468929039478899902048566366856240877703602702087931641888", "13041605696900798404650686538893086909353822453068056131548498883864307018762", "7724559080250826493557773439911765324995115520951876421071063545832580076523", "2608760983816514764568197126437451665283344192910536302819820213681815485600", "4325576551800410431474186754039992813847609089390921236861130833620395142916", "28244869281227089786402354774575238327642814071062911402571918173773147690382", "52254320812514580546932455788288716956214894268551482805284261007871578815161", "44449246366481365934850731985584672122835299278127134772360027315881296465188", "38769336262079049280674269301353892930067342680672673045972987208159445324024", "42379436704506954159182654240696088598260763773619436089417882065405547940000", "22632779538473440042293241998410977359589070603696185849507766111228222504955", "15890983544445833013318912933113160561188216234423366865567699135890287302776", "42613622386509970285531317746282776785466762852259244468998263414951438072346", "3349279328650947992104289938299770603841093392045299702204191673899696292828", "24960454956681584943062398789550512391287992748093736571644480050215988836698", "31771416517485450527236959099354889665790926800645171236881417310335951093156", "6723262288337923380317441046361546981088139618189440979848042063784738198448", "34890105450847731125549485970715770779411614440863872580331681887247489622411", "11578979089604924419672152758230524155578424562011333797269885929442680042317", "50413588594256908654341963895371964591208017449187726872226940257366479794931", "25342645262500526730472670090219790271213097305995599586909134601382438580057", "8823851208157208211075893000112820438603010547555640271936182343488623715695", "48715166069588125017688857080421400882110756555551531562607166243928305121118", "457928742693316582022794368629361528074764749146022984852066320975235063636", "40784591676918140113004512439228960581998583153376036451231191678625605644121", "18433242804842005502998987143284711404363511412515282751547329224013759991670", "13389179080347763657382998600872902733061029331254291204270991952891409570918", "50192764209384080101272306620889875080455867628520281400927714930481563250325", "21188812847528225766555643216406799500549004969671500977130541863203997121380", "15467310814359095588985846207322319122950649805677111326687390171860927014900", "43568129081901200261103456211527409151200730655566657378341556085992472943958", "34271132631203889901701300408318058004416254071247236806623005223769350150039", "22173004425756666568314241635854763913339665884248599814793658197582222664954", "32975563242070450354147568749607182665869459510325615902750312087436132984686", "22696762757124796424578806530049133427552572655901519744413916679979764071390", "17763704296411643970998432037239004006015355463277677435659459899409343551392", "47107020014905029302099526236973268575042805085389783842994685212684421454488", "13304672766482627838923613214260444961210749299235217922669168410578113120633", "14336471400558675842362782084319960764287611922882892949544609123042059062824", "19303757685423427260649409150012846414071844305131989213305575732858057757894", "41105909312432760443399922527873622836019389621682258300053074843930035806751", "4449965847617470660026263611722341184463318026296894969809166330782012760219", "31939993490530073679397065723723444395703645080257573290017499883874398700446", "44612014630702294701797504988969181620837907283197659821551486351788471559337", "42992712381319065313644044212157260265940162092852802442073735607198967462282", "966835047744911231490794763166379188555949592683359886287393788918898119684"];
+
+// Poseidon MDS matrix, row by row
+pub(crate) const MDS_PS: [[&str; 3]; 3] = [
+    [
+        "27854988750630959170337239780597144027224715023811960992659706878268355039181", 
+        "25146695260744508059100624982461970690166157722474767565243652164077487269055", 
+        "20045359041216123667749848881863965260443684681509271093016182932435520519586"
+    ],
+    [
+        "14489116502293865465195620705098702569149962166993518933952339786917836503875", 
+        "13125423966940654332711887575940116829944663267413330181877013057693186361539", 
+        "37781904496949962127477230973432217892379931214289750852498713884075794707207"
+    ],
+    [
+        "13626913895298938265545264952401615832299228269982032679076937571883280705196",
+        "1961062001717124873779753860369853658060849384038305407377314938662537282272",
+        "39178371364179396693874733819376491076633720395229958100530484864695867731796"
+    ]
+];
+
+// Rescue-Prime MDS matrix, row by row
+pub(crate) const MDS_RS: [[&str; 3]; 3] = [
+    [
+        "343", 
+        "52435875175126190479447740508185965837690552500527637822603658699938581184114", 
+        "57" 
+    ],
+    [
+        "19551",
+        "52435875175126190479447740508185965837690552500527637822603658699938581162113", 
+        "2850"
+    ],
+    [
+        "977550", 
+        "52435875175126190479447740508185965837690552500527637822603658699938580066914",
+        "140050"
+    ]
+];
+
+// circomlib's Poseidon round constants for t = 3 over the BN254 scalar field (8 full, 57 partial rounds), as published
+// in their poseidon_constants.json; they are what the Grain LFSR gives for these round numbers
+pub(crate) const ROUND_CONSTANTS_BN254_CIRCOM: [&str; 195] = ["0x0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e", "0x00f1445235f2148c5986587169fc1bcd887b08d4d00868df5696fff40956e864", "0x08dff3487e8ac99e1f29a058d0fa80b930c728730b7ab36ce879f3890ecf73f5", "0x2f27be690fdaee46c3ce28f7532b13c856c35342c84bda6e20966310fadc01d0", "0x2b2ae1acf68b7b8d2416bebf3d4f6234b763fe04b8043ee48b8327bebca16cf2", "0x0319d062072bef7ecca5eac06f97d4d55952c175ab6b03eae64b44c7dbf11cfa", "0x28813dcaebaeaa828a376df87af4a63bc8b7bf27ad49c6298ef7b387bf28526d", "0x2727673b2ccbc903f181bf38e1c1d40d2033865200c352bc150928adddf9cb78", "0x234ec45ca27727c2e74abd2b2a1494cd6efbd43e340587d6b8fb9e31e65cc632", "0x15b52534031ae18f7f862cb2cf7cf760ab10a8150a337b1ccd99ff6e8797d428", "0x0dc8fad6d9e4b35f5ed9a3d186b79ce38e0e8a8d1b58b132d701d4eecf68d1f6", "0x1bcd95ffc211fbca600f705fad3fb567ea4eb378f62e1fec97805518a47e4d9c", "0x10520b0ab721cadfe9eff81b016fc34dc76da36c2578937817cb978d069de559", "0x1f6d48149b8e7f7d9b257d8ed5fbbaf42932498075fed0ace88a9eb81f5627f6", "0x1d9655f652309014d29e00ef35a2089bfff8dc1c816f0dc9ca34bdb5460c8705", "0x04df5a56ff95bcafb051f7b1cd43a99ba731ff67e47032058fe3d4185697cc7d", "0x0672d995f8fff640151b3d290cedaf148690a10a8c8424a7f6ec282b6e4be828", "0x099952b414884454b21200d7ffafdd5f0c9a9dcc06f2708e9fc1d8209b5c75b9", "0x052cba2255dfd00c7c483143ba8d469448e43586a9b4cd9183fd0e843a6b9fa6", "0x0b8badee690adb8eb0bd74712b7999af82de55707251ad7716077cb93c464ddc", "0x119b1590f13307af5a1ee651020c07c749c15d60683a8050b963d0a8e4b2bdd1", "0x03150b7cd6d5d17b2529d36be0f67b832c4acfc884ef4ee5ce15be0bfb4a8d09", "0x2cc6182c5e14546e3cf1951f173912355374efb83d80898abe69cb317c9ea565", "0x005032551e6378c450cfe129a404b3764218cadedac14e2b92d2cd73111bf0f9", "0x233237e3289baa34bb147e972ebcb9516469c399fcc069fb88f9da2cc28276b5", "0x05c8f4f4ebd4a6e3c980d31674bfbe6323037f21b34ae5a4e80c2d4c24d60280", "0x0a7b1db13042d396ba05d818a319f25252bcf35ef3aeed91ee1f09b2590fc65b", "0x2a73b71f9b210cf5b14296572c9d32dbf156e2b086ff47dc5df542365a404ec0", "0x1ac9b0417abcc9a1935107e9ffc91dc3ec18f2c4dbe7f22976a760bb5c50c460", "0x12c0339ae08374823fabb076707ef479269f3e4d6cb104349015ee046dc93fc0", "0x0b7475b102a165ad7f5b18db4e1e704f52900aa3253baac68246682e56e9a28e", "0x037c2849e191ca3edb1c5e49f6e8b8917c843e379366f2ea32ab3aa88d7f8448", "0x05a6811f8556f014e92674661e217e9bd5206c5c93a07dc145fdb176a716346f", "0x29a795e7d98028946e947b75d54e9f044076e87a7b2883b47b675ef5f38bd66e", "0x20439a0c84b322eb45a3857afc18f5826e8c7382c8a1585c507be199981fd22f", "0x2e0ba8d94d9ecf4a94ec2050c7371ff1bb50f27799a84b6d4a2a6f2a0982c887", "0x143fd115ce08fb27ca38eb7cce822b4517822cd2109048d2e6d0ddcca17d71c8", "0x0c64cbecb1c734b857968dbbdcf813cdf8611659323dbcbfc84323623be9caf1", "0x028a305847c683f646fca925c163ff5ae74f348d62c2b670f1426cef9403da53", "0x2e4ef510ff0b6fda5fa940ab4c4380f26a6bcb64d89427b824d6755b5db9e30c", "0x0081c95bc43384e663d79270c956ce3b8925b4f6d033b078b96384f50579400e", "0x2ed5f0c91cbd9749187e2fade687e05ee2491b349c039a0bba8a9f4023a0bb38", "0x30509991f88da3504bbf374ed5aae2f03448a22c76234c8c990f01f33a735206", "0x1c3f20fd55409a53221b7c4d49a356b9f0a1119fb2067b41a7529094424ec6ad", "0x10b4e7f3ab5df003049514459b6e18eec46bb2213e8e131e170887b47ddcb96c", "0x2a1982979c3ff7f43ddd543d891c2abddd80f804c077d775039aa3502e43adef", "0x1c74ee64f15e1db6feddbead56d6d55dba431ebc396c9af95cad0f1315bd5c91", "0x07533ec850ba7f98eab9303cace01b4b9e4f2e8b82708cfa9c2fe45a0ae146a0", "0x21576b438e500449a151e4eeaf17b154285c68f42d42c1808a11abf3764c0750", "0x2f17c0559b8fe79608ad5ca193d62f10bce8384c815f0906743d6930836d4a9e", "0x2d477e3862d07708a79e8aae946170bc9775a4201318474ae665b0b1b7e2730e", "0x162f5243967064c390e095577984f291afba2266c38f5abcd89be0f5b2747eab", "0x2b4cb233ede9ba48264ecd2c8ae50d1ad7a8596a87f29f8a7777a70092393311", "0x2c8fbcb2dd8573dc1dbaf8f4622854776db2eece6d85c4cf4254e7c35e03b07a", "0x1d6f347725e4816af2ff453f0cd56b199e1b61e9f601e9ade5e88db870949da9", "0x204b0c397f4ebe71ebc2d8b3df5b913df9e6ac02b68d31324cd49af5c4565529", "0x0c4cb9dc3c4fd8174f1149b3c63c3c2f9ecb827cd7dc25534ff8fb75bc79c502", "0x174ad61a1448c899a25416474f4930301e5c49475279e0639a616ddc45bc7b54", "0x1a96177bcf4d8d89f759df4ec2f3cde2eaaa28c177cc0fa13a9816d49a38d2ef", "0x066d04b24331d71cd0ef8054bc60c4ff05202c126a233c1a8242ace360b8a30a", "0x2a4c4fc6ec0b0cf52195782871c6dd3b381cc65f72e02ad527037a62aa1bd804", "0x13ab2d136ccf37d447e9f2e14a7cedc95e727f8446f6d9d7e55afc01219fd649", "0x1121552fca26061619d24d843dc82769c1b04fcec26f55194c2e3e869acc6a9a", "0x00ef653322b13d6c889bc81715c37d77a6cd267d595c4a8909a5546c7c97cff1", "0x0e25483e45a665208b261d8ba74051e6400c776d652595d9845aca35d8a397d3", "0x29f536dcb9dd7682245264659e15d88e395ac3d4dde92d8c46448db979eeba89", "0x2a56ef9f2c53febadfda33575dbdbd885a124e2780bbea170e456baace0fa5be", "0x1c8361c78eb5cf5decfb7a2d17b5c409f2ae2999a46762e8ee416240a8cb9af1", "0x151aff5f38b20a0fc0473089aaf0206b83e8e68a764507bfd3d0ab4be74319c5", "0x04c6187e41ed881dc1b239c88f7f9d43a9f52fc8c8b6cdd1e76e47615b51f100", "0x13b37bd80f4d27fb10d84331f6fb6d534b81c61ed15776449e801b7ddc9c2967", "0x01a5c536273c2d9df578bfbd32c17b7a2ce3664c2a52032c9321ceb1c4e8a8e4", "0x2ab3561834ca73835ad05f5d7acb950b4a9a2c666b9726da832239065b7c3b02", "0x1d4d8ec291e720db200fe6d686c0d613acaf6af4e95d3bf69f7ed516a597b646", "0x041294d2cc484d228f5784fe7919fd2bb925351240a04b711514c9c80b65af1d", "0x154ac98e01708c611c4fa715991f004898f57939d126e392042971dd90e81fc6", "0x0b339d8acca7d4f83eedd84093aef51050b3684c88f8b0b04524563bc6ea4da4", "0x0955e49e6610c94254a4f84cfbab344598f0e71eaff4a7dd81ed95b50839c82e", "0x06746a6156eba54426b9e22206f15abca9a6f41e6f535c6f3525401ea0654626", "0x0f18f5a0ecd1423c496f3820c549c27838e5790e2bd0a196ac917c7ff32077fb", "0x04f6eeca1751f7308ac59eff5beb261e4bb563583ede7bc92a738223d6f76e13", "0x2b56973364c4c4f5c1a3ec4da3cdce038811eb116fb3e45bc1768d26fc0b3758", "0x123769dd49d5b054dcd76b89804b1bcb8e1392b385716a5d83feb65d437f29ef", "0x2147b424fc48c80a88ee52b91169aacea989f6446471150994257b2fb01c63e9", "0x0fdc1f58548b85701a6c5505ea332a29647e6f34ad4243c2ea54ad897cebe54d", "0x12373a8251fea004df68abcf0f7786d4bceff28c5dbbe0c3944f685cc0a0b1f2", "0x21e4f4ea5f35f85bad7ea52ff742c9e8a642756b6af44203dd8a1f35c1a90035", "0x16243916d69d2ca3dfb4722224d4c462b57366492f45e90d8a81934f1bc3b147", "0x1efbe46dd7a578b4f66f9adbc88b4378abc21566e1a0453ca13a4159cac04ac2", "0x07ea5e8537cf5dd08886020e23a7f387d468d5525be66f853b672cc96a88969a", "0x05a8c4f9968b8aa3b7b478a30f9a5b63650f19a75e7ce11ca9fe16c0b76c00bc", "0x20f057712cc21654fbfe59bd345e8dac3f7818c701b9c7882d9d57b72a32e83f", "0x04a12ededa9dfd689672f8c67fee31636dcd8e88d01d49019bd90b33eb33db69", "0x27e88d8c15f37dcee44f1e5425a51decbd136ce5091a6767e49ec9544ccd101a", "0x2feed17b84285ed9b8a5c8c5e95a41f66e096619a7703223176c41ee433de4d1", "0x1ed7cc76edf45c7c404241420f729cf394e5942911312a0d6972b8bd53aff2b8", "0x15742e99b9bfa323157ff8c586f5660eac6783476144cdcadf2874be45466b1a", "0x1aac285387f65e82c895fc6887ddf40577107454c6ec0317284f033f27d0c785", "0x25851c3c845d4790f9ddadbdb6057357832e2e7a49775f71ec75a96554d67c77", "0x15a5821565cc2ec2ce78457db197edf353b7ebba2c5523370ddccc3d9f146a67", "0x2411d57a4813b9980efa7e31a1db5966dcf64f36044277502f15485f28c71727", "0x002e6f8d6520cd4713e335b8c0b6d2e647e9a98e12f4cd2558828b5ef6cb4c9b", "0x2ff7bc8f4380cde997da00b616b0fcd1af8f0e91e2fe1ed7398834609e0315d2", "0x00b9831b948525595ee02724471bcd182e9521f6b7bb68f1e93be4febb0d3cbe", "0x0a2f53768b8ebf6a86913b0e57c04e011ca408648a4743a87d77adbf0c9c3512", "0x00248156142fd0373a479f91ff239e960f599ff7e94be69b7f2a290305e1198d", "0x171d5620b87bfb1328cf8c02ab3f0c9a397196aa6a542c2350eb512a2b2bcda9", "0x170a4f55536f7dc970087c7c10d6fad760c952172dd54dd99d1045e4ec34a808", "0x29aba33f799fe66c2ef3134aea04336ecc37e38c1cd211ba482eca17e2dbfae1", "0x1e9bc179a4fdd758fdd1bb1945088d47e70d114a03f6a0e8b5ba650369e64973", "0x1dd269799b660fad58f7f4892dfb0b5afeaad869a9c4b44f9c9e1c43bdaf8f09", "0x22cdbc8b70117ad1401181d02e15459e7ccd426fe869c7c95d1dd2cb0f24af38", "0x0ef042e454771c533a9f57a55c503fcefd3150f52ed94a7cd5ba93b9c7dacefd", "0x11609e06ad6c8fe2f287f3036037e8851318e8b08a0359a03b304ffca62e8284", "0x1166d9e554616dba9e753eea427c17b7fecd58c076dfe42708b08f5b783aa9af", "0x2de52989431a859593413026354413db177fbf4cd2ac0b56f855a888357ee466", "0x3006eb4ffc7a85819a6da492f3a8ac1df51aee5b17b8e89d74bf01cf5f71e9ad", "0x2af41fbb61ba8a80fdcf6fff9e3f6f422993fe8f0a4639f962344c8225145086", "0x119e684de476155fe5a6b41a8ebc85db8718ab27889e85e781b214bace4827c3", "0x1835b786e2e8925e188bea59ae363537b51248c23828f047cff784b97b3fd800", "0x28201a34c594dfa34d794996c6433a20d152bac2a7905c926c40e285ab32eeb6", "0x083efd7a27d1751094e80fefaf78b000864c82eb571187724a761f88c22cc4e7", "0x0b6f88a3577199526158e61ceea27be811c16df7774dd8519e079564f61fd13b", "0x0ec868e6d15e51d9644f66e1d6471a94589511ca00d29e1014390e6ee4254f5b", "0x2af33e3f866771271ac0c9b3ed2e1142ecd3e74b939cd40d00d937ab84c98591", "0x0b520211f904b5e7d09b5d961c6ace7734568c547dd6858b364ce5e47951f178", "0x0b2d722d0919a1aad8db58f10062a92ea0c56ac4270e822cca228620188a1d40", "0x1f790d4d7f8cf094d980ceb37c2453e957b54a9991ca38bbe0061d1ed6e562d4", "0x0171eb95dfbf7d1eaea97cd385f780150885c16235a2a6a8da92ceb01e504233", "0x0c2d0e3b5fd57549329bf6885da66b9b790b40defd2c8650762305381b168873", "0x1162fb28689c27154e5a8228b4e72b377cbcafa589e283c35d3803054407a18d", "0x2f1459b65dee441b64ad386a91e8310f282c5a92a89e19921623ef8249711bc0", "0x1e6ff3216b688c3d996d74367d5cd4c1bc489d46754eb712c243f70d1b53cfbb", "0x01ca8be73832b8d0681487d27d157802d741a6f36cdc2a0576881f9326478875", "0x1f7735706ffe9fc586f976d5bdf223dc680286080b10cea00b9b5de315f9650e", "0x2522b60f4ea3307640a0c2dce041fba921ac10a3d5f096ef4745ca838285f019", "0x23f0bee001b1029d5255075ddc957f833418cad4f52b6c3f8ce16c235572575b", "0x2bc1ae8b8ddbb81fcaac2d44555ed5685d142633e9df905f66d9401093082d59", "0x0f9406b8296564a37304507b8dba3ed162371273a07b1fc98011fcd6ad72205f", "0x2360a8eb0cc7defa67b72998de90714e17e75b174a52ee4acb126c8cd995f0a8", "0x15871a5cddead976804c803cbaef255eb4815a5e96df8b006dcbbc2767f88948", "0x193a56766998ee9e0a8652dd2f3b1da0362f4f54f72379544f957ccdeefb420f", "0x2a394a43934f86982f9be56ff4fab1703b2e63c8ad334834e4309805e777ae0f", "0x1859954cfeb8695f3e8b635dcb345192892cd11223443ba7b4166e8876c0d142", "0x04e1181763050e58013444dbcb99f1902b11bc25d90bbdca408d3819f4fed32b", "0x0fdb253dee83869d40c335ea64de8c5bb10eb82db08b5e8b1f5e5552bfd05f23", "0x058cbe8a9a5027bdaa4efb623adead6275f08686f1c08984a9d7c5bae9b4f1c0", "0x1382edce9971e186497eadb1aeb1f52b23b4b83bef023ab0d15228b4cceca59a", "0x03464990f045c6ee0819ca51fd11b0be7f61b8eb99f14b77e1e6634601d9e8b5", "0x23f7bfc8720dc296fff33b41f98ff83c6fcab4605db2eb5aaa5bc137aeb70a58", "0x0a59a158e3eec2117e6e94e7f0e9decf18c3ffd5e1531a9219636158bbaf62f2", "0x06ec54c80381c052b58bf23b312ffd3ce2c4eba065420af8f4c23ed0075fd07b", "0x118872dc832e0eb5476b56648e867ec8b09340f7a7bcb1b4962f0ff9ed1f9d01", "0x13d69fa127d834165ad5c7cba7ad59ed52e0b0f0e42d7fea95e1906b520921b1", "0x169a177f63ea681270b1c6877a73d21bde143942fb71dc55fd8a49f19f10c77b", "0x04ef51591c6ead97ef42f287adce40d93abeb032b922f66ffb7e9a5a7450544d", "0x256e175a1dc079390ecd7ca703fb2e3b19ec61805d4f03ced5f45ee6dd0f69ec", "0x30102d28636abd5fe5f2af412ff6004f75cc360d3205dd2da002813d3e2ceeb2", "0x10998e42dfcd3bbf1c0714bc73eb1bf40443a3fa99bef4a31fd31be182fcc792", "0x193edd8e9fcf3d7625fa7d24b598a1d89f3362eaf4d582efecad76f879e36860", "0x18168afd34f2d915d0368ce80b7b3347d1c7a561ce611425f2664d7aa51f0b5d", "0x29383c01ebd3b6ab0c017656ebe658b6a328ec77bc33626e29e2e95b33ea6111", "0x10646d2f2603de39a1f4ae5e7771a64a702db6e86fb76ab600bf573f9010c711", "0x0beb5e07d1b27145f575f1395a55bf132f90c25b40da7b3864d0242dcb1117fb", "0x16d685252078c133dc0d3ecad62b5c8830f95bb2e54b59abdffbf018d96fa336", "0x0a6abd1d833938f33c74154e0404b4b40a555bbbec21ddfafd672dd62047f01a", "0x1a679f5d36eb7b5c8ea12a4c2dedc8feb12dffeec450317270a6f19b34cf1860", "0x0980fb233bd456c23974d50e0ebfde4726a423eada4e8f6ffbc7592e3f1b93d6", "0x161b42232e61b84cbf1810af93a38fc0cece3d5628c9282003ebacb5c312c72b", "0x0ada10a90c7f0520950f7d47a60d5e6a493f09787f1564e5d09203db47de1a0b", "0x1a730d372310ba82320345a29ac4238ed3f07a8a2b4e121bb50ddb9af407f451", "0x2c8120f268ef054f817064c369dda7ea908377feaba5c4dffbda10ef58e8c556", "0x1c7c8824f758753fa57c00789c684217b930e95313bcb73e6e7b8649a4968f70", "0x2cd9ed31f5f8691c8e39e4077a74faa0f400ad8b491eb3f7b47b27fa3fd1cf77", "0x23ff4f9d46813457cf60d92f57618399a5e022ac321ca550854ae23918a22eea", "0x09945a5d147a4f66ceece6405dddd9d0af5a2c5103529407dff1ea58f180426d", "0x188d9c528025d4c2b67660c6b771b90f7c7da6eaa29d3f268a6dd223ec6fc630", "0x3050e37996596b7f81f68311431d8734dba7d926d3633595e0c0d8ddf4f0f47f", "0x15af1169396830a91600ca8102c35c426ceae5461e3f95d89d829518d30afd78", "0x1da6d09885432ea9a06d9f37f873d985dae933e351466b2904284da3320d8acc", "0x2796ea90d269af29f5f8acf33921124e4e4fad3dbe658945e546ee411ddaa9cb", "0x202d7dd1da0f6b4b0325c8b3307742f01e15612ec8e9304a7cb0319e01d32d60", "0x096d6790d05bb759156a952ba263d672a2d7f9c788f4c831a29dace4c0f8be5f", "0x054efa1f65b0fce283808965275d877b438da23ce5b13e1963798cb1447d25a4", "0x1b162f83d917e93edb3308c29802deb9d8aa690113b2e14864ccf6e18e4165f1", "0x21e5241e12564dd6fd9f1cdd2a0de39eedfefc1466cc568ec5ceb745a0506edc", "0x1cfb5662e8cf5ac9226a80ee17b36abecb73ab5f87e161927b4349e10e4bdf08", "0x0f21177e302a771bbae6d8d1ecb373b62c99af346220ac0129c53f666eb24100", "0x1671522374606992affb0dd7f71b12bec4236aede6290546bcef7e1f515c2320", "0x0fa3ec5b9488259c2eb4cf24501bfad9be2ec9e42c5cc8ccd419d2a692cad870", "0x193c0e04e0bd298357cb266c1506080ed36edce85c648cc085e8c57b1ab54bba", "0x102adf8ef74735a27e9128306dcbc3c99f6f7291cd406578ce14ea2adaba68f8", "0x0fe0af7858e49859e2a54d6f1ad945b1316aa24bfbdd23ae40a6d0cb70c3eab1", "0x216f6717bbc7dedb08536a2220843f4e2da5f1daa9ebdefde8a5ea7344798d22", "0x1da55cc900f0d21f4a3e694391918a1b3c23b2ac773c6b3ef88e2e4228325161"];
+
+// circomlib's Poseidon MDS matrix for t = 3 over the BN254 scalar field, row by row
+pub(crate) const MDS_BN254_CIRCOM: [[&str; 3]; 3] = [
+    [
+        "0x109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b",
+        "0x16ed41e13bb9c0c66ae119424fddbcbc9314dc9fdbdeea55d6c64543dc4903e0",
+        "0x2b90bba00fca0589f617e7dcbfe82e0df706ab640ceb247b791a93b74e36736d"
+    ],
+    [
+        "0x2969f27eed31a480b9c36c764379dbca2cc8fdd1415c3dded62940bcde0bd771",
+        "0x2e2419f9ec02ec394c9871c832963dc1b89d743c8c7b964029b2311687b1fe23",
+        "0x101071f0032379b697315876690f053d148d4e109f5fb065c8aacc55a0f89bfa"
+    ],
+    [
+        "0x143021ec686a3f330d5f9e654638065ce6cd79e28c5b3753326244ee65a1b1a7",
+        "0x176cc029695ad02582a70eff08a6fd99d057e12e58e7d7b6b16cdfabc8ee2911",
+        "0x19a3fc0a56702bf417ba7fee3802593fa644470307043f7773279cd71d25d5e0"
+    ]
+];
+
+// neptune's (Filecoin) Poseidon round constants for arity 2 (t = 3) over BLS12-381 (8 full, 55 partial rounds), as its
+// PoseidonConstants generate them; neptune seeds the Grain LFSR with S-box bits 1 (the reference's x^-1) for x^5, so
+// they differ from the reference Grain constants for these round numbers
+pub(crate) const ROUND_CONSTANTS_PS_NEPTUNE: [&str; 189] = ["0x669f064bfa3ae17a23bd51861dbb4a24501eac92a2758b36a7320a009d6ed3d8", "0x0a61a8defbacca36e4537ff2c84fa66ceee67c9645ac27346e72ab842b9d3f15", "0x21e9cefa24b89d09f91b1e8a45df275b17292b4e1aaa49301e234771128165d5", "0x4e2377d2f5cb43b2b56e1026a391be2d5e4ebc5102b7e51dc161d2b03886ee3d", "0x02255d26879b6d0d49762a88d8d5d0879f01a3b06fa57c240a8119099cb02ea3", "0x27aa46e0263ddc662845f98eb5db8645f2e8baeec680ddfc16f4dd3d288d8c5b", "0x62e1fa5861a7807d02be301fd89b30346f4875c83d73dff890cb8624fcef0186", "0x19f00d6d8a121f271c14bc471fdd3bc901907fb5e36e40f96a772c143cfb0ca0", "0x3499f695552f2c56f23e96df7f8f3c4dd3be0cf50259f3ef7e65d565609eafe6", "0x236d5cf282247aaf3941406ecdb907076f45f12f89b007168f7c7a2b0157e0f5", "0x445daf9d58581e5b0ed41b01f9228f811993992832094a9782d1d844649b4fd1", "0x21a58e9955e3645b224d031a69ef9b23678bd7e144dd9214cf29a71c0a8426bf", "0x48d3f5c7ccc9228420cb0c4d6eda2d5a3cd7bb8f18a0817cbd522c81d998a723", "0x5135f0a3315e5cc27b414ead7ca52af60e714b39873cf991278d9c83899ab5cb", "0x1d5284a657f69b5c6ae0b7e714cb38bcd175ff96a11f8bd9c77d3a2f1613a743", "0x49c2165a0680f7a121d168565177702344690ac5f14fe4038f9c9bbc28024722", "0x5e8eabc6a3adae0ef1a79060bad37f893ddd5335f4fc71c262e8546aaf307170", "0x3264d05b6c40c40c0d487222b6f385f69317a90cd6793532036125902138f079", "0x727f2a5b23ecc1410331890376f382fbad02b541140a28008dce723994810339", "0x1f00c3b0f6ebf427238bcaf2d615ca05e8cb7b9a6eb7c667d70587f1e7aee758", "0x3ef895995e82a276f18232d1f5ad341f46e4119098b175038e540a80d926d400", "0x5606f5aaa845b400ed53b49dfc2119884e00f0729e0f9cf360c5c9d8762968cc", "0x106150f58dccb090de9cfee7219a9448af8127f93ba645903a93a4dc666f5808", "0x3117bbb789d8a521034590fe35014bcaa3be1f4cd443985574249e62d15cf96f", "0x5404ca4c386646a17fba4557ead8a6404f76c1d8ad154c7bfb8c48f46662a7b6", "0x561682d9ccc4d7033e1957e0aee030249e1e3ebd68a7c72b619d4e628f977920", "0x609ebf7a728acdf6d42473dfb8dc2a9b2e9435e4b74ffe14721b3a2e044ec332", "0x206c2444abbaf221cc01b4d06b0915c3ab384102429c4c946846f58c08e45d2e", "0x41e35e0864e292c378fefc1dc0b0d94d54561dd1498a500681206f3f416edf91", "0x65963f69684a9459e05fdebc7e85f5e42ec41a7370438ac37c681e1743af3517", "0x384c77b79a3f1cc004b6d28122541c92bf3f7ade7172c92b06f24e236980012f", "0x1d641158354425746db2c72c52f05d7935191258c6fc7c419a6c29b6da32c1a3", "0x4f804c762f12827c32d5b018fb1aae24e5b284a0efb6c1666605e726e94baddd", "0x02c302e7c79800112c508bffb262bea2e38a1eccabbcfa18ea7c313d50266e6f", "0x2118be89bcf376e2cbd7741faa335b572f125299604678eca035e35114759ab4", "0x33652630502c8a44b9d65f5e9815fe36c31023b4d8b7c3639f59447024b9f2cf", "0x4b15c0ed31137be5b371052083fb04a273796e0936b2cbf1efcc1f686f2dcb90", "0x3f86a1801f46d7f30a14daa6fce4a6589986fad1b8ec46d42799bf8985852053", "0x05a46f165678e990fbde85c5e5fb483ef720160b08b9eb343d2ce7db33d2a857", "0x3b8f9db5bf15e2dc98d96bb31bc7c0d011d23fa38b1079ce1e0aa0a2177246c4", "0x161e9f7d4ef018f47889bd6497d7f93c6b24d9d232dfc84b50315e6f520f17bc", "0x264a98f297e5b58bf21a27d1c865dc75c914d49ede4b27168b365da04eb4152b", "0x38802665b39df51c986bed3c9a386d77643c7a233c75eb2aa0d5dbef34c7a11e", "0x67fcca14ef5d1f6fb5e2a9be09cc870b49c6cbebc2e860f54e9282ac50284c0c", "0x2ca0307ccf7f7b3f7ad993a80b2973e375fd28c689ba05d90a63a7c908629be8", "0x1f3072135ccf376c85d03d9b56361c721422706aba7c8612c9b647ff9c4035ea", "0x4f6851c88570a7773020d2bec31d919acec86caa1c1b266bb32b1a1c8f3a516a", "0x54b838cf161d883e4cf655f50389e65815d63f99383b45cefdb4d3e313d14ac6", "0x3ce7859d585daada6ef747bc68544191c9eb9a59f79703d473424ee64276f9db", "0x57c14154a0017a88b65492f03154f11af675d4645f69077743b14ea4f49b4030", "0x5f68e7f4c3235215f6d62be4bfaf7b3c46ffd16ae1c18dce078c72e4eacfecb0", "0x2d993835b6108c60aa78d1f8b241188a77256399101a131b848212b2db53c67b", "0x5e4a9799bbb8f9b35079c1a03038ad2e56648c8e4cc192b2bd2ad14948e8f255", "0x62f6aead0e19b6e33c5a062eea003ea9e4345898eacee8bb2a8b436fa8420a33", "0x1fdadd9c8da406323c5849b3989781fdd0438a961b2eeb7e079a37c72cd24cdb", "0x359359b7346f649bb44b8b636523abad631b76a0de563282393a6c147cf4acab", "0x15675df989854f06371913c37bdc1c006814e9cf4ac6124b11aeb774d42ac2b6", "0x3670c3a64b4ba2694ca7e8f75ab7f7758e511a30e570d5a9f4e83afcf50f4ee0", "0x66b4703bf0415b2af3ea660b391d16da7b254fa0b52e318125c78bfbedb87171", "0x72f66342d2b390fdf253d0b44363d5b53a8fff249be3466ea33727cbe3c4c1f3", "0x37cf10985d6ba77eef963771323962a435cc9a0f3b3e3a8c11a5ac9e39db1395", "0x27e4538f90ec0b009d0fba99491c34bf33d48fc90e3f1e331e31446a160192b7", "0x1b711906c22ac9937281cd1ae0f2e82828f10f229eb5b027ab11f05968d9eb6f", "0x54a3606a3e8c5ab2ddec5898a33119b5d5ab4a8e15ab22914c52f982f38008e5", "0x0e0aeb0f0e759a2bbde9c7ca41ebfa2f3fdd3de93f4a1595195ed82b9b2f055e", "0x29839536e73ae40362f720bda04e4e56f167d6d1d054109a6f4f11466086efdc", "0x54d8e3ec7800ffb1b46ef89751c6b7b6c4c96ba1168e4ec46854d29d5ed64b2f", "0x0dd33054f93fa501add5c7bd132146cce38b708774c505c93b604f3a5aa931d0", "0x58ed1f752578c70c8831abc2f58a332efed3780b9a4eda0ccd37a4f4b7fcf66e", "0x59c0bd12ac0f1e8df7e4362b9823267de599b9c364c98607d85ccc676e321ea1", "0x0734e8779b78f832b20642577c774662bde5b65af599e48382a6a2fbd555a72f", "0x4296c2cd2d63615ad57278a61087f0171f5ca5c88a670cdce45d8353c4f27d6d", "0x70ca4f659ac365454fd71c2b5bd64640f5b8ab6a706c5b8b9b829eeaa0f7a28d", "0x2b3ed2067e88294cacc8bca7b14dd4d15750b5ea5f076ca7c41854f5cd16a79d", "0x1f6005f1c1e35f721df557808f34d2cb1ef353729be365a5ba3e679b2e928fba", "0x5fd220812f0b9285b3ce0d7d0b87b726d1682c5a328722182550f30bab0e7487", "0x29fcc8a49f9ace62888ad3b41c7cb12999d3a583dc70d473c2ce7d8871f179a0", "0x62a4e582d88ceac82e31b1a4e134fd4df8e71f47ca60cc69109c6b543a8a344a", "0x31c4d94ce026bad4959787e2181dcc89a61462a70c1e175750528998cf81e018", "0x3f26b82b525e9e40b511580861cc406bb79a074b8a98b32f718ba2c11362f807", "0x65013234a39c87f836f7f9cc8179904c765ff0fd99b11ed4243a3bf3b1177f2c", "0x0c22db7080672b164157ab4063e0fcc66c1e221b6e85212292cc6f22772459f8", "0x1757f9fac9967eedbbb9a3256e3535d67c1e269a14a8fb091ace65e23661ac39", "0x504cef8899122412be40825c6eeb0f7c86999662ec4c7620b14d6e3bf1caca54", "0x376de57ed93de38cd2f30a79fb29b92e4e144fcb881ef39426b8c98dd993f8ed", "0x0dba6d221e3690786243d96ea8c49e84533b982a05d2fa88175bbf19c7c4c1d4", "0x444130bfb285396e90923711f781d49da1556ab8d94994b3f2bd7b9f7d6e1bde", "0x423b9d3d069fbe61b5e54d09bc30397da5907d668ce5266f92bdc36fc935200d", "0x1da3918f5cd423cba96924cfad5663bea9752c74f2931f3b468f9851606155e9", "0x53022ce831f40d5ced2e74387f326b92d86f0dd0715432dcb4cff11862cfaeb4", "0x69e2fe14a6875a92c1568823dddf060746525455dbd687157125626ec14ecdc1", "0x2ac84677966e174c8c71dfe13fbfae2aee0e4d88b3c54135286526cfd9cb57bd", "0x06fbb5301e4cffa534c8135147704fd7e72a364858597eb3483d4eafcaa9c226", "0x5ce6e13a1ad45cd2f54893808dbe78c4250984a3f71277b9e006a20319240f7c", "0x582fd75a0cf50bb031c8d6ab9862c5019598d2529a481fbdf3617e4e91046232", "0x04f4473b814bef668e7e4a22010dba7874e44c34da109c5acec3750c3ffb9365", "0x207c2cba5430a95a8f3bd861cb5b0ee38b643eadf8bf659f7c765b067a1cf3cd", "0x6d0e096809b5e23ffb0cc9b1ca9fccfabe911e532783c3ff5454ce254e63d716", "0x05969d41cc3bbaaa2e9d4d05b337149453c2028c1d1a4a71bebdd8b9ca1aac95", "0x19548ff7a77670d86a925c8c3ed6d343f60337ee3bf60b37c3eee2bfad77aa9c", "0x5796bc6126b98754dc44e77ca7b3c89f02d60a48149081a3f763dcb37b25ca02", "0x4aa80af316c7a2b662d2e74e63852475457a950aafc7330f3af65a51fcf3dc0f", "0x19cc9df4e33ed7741e3b72596a0489968be9253c3981e851727c1342eea142ff", "0x1cee1165c26e9a5698fcb59ee514b2dd2921f6dfbb7d0b51cd841e09beaa7e97", "0x28ad294f58c5c3e4b9c1aac0f1a014d7a7e0a03cfe4d42585e7b7036a6219619", "0x63591b7786b99b3ea7d1f0be28c3c389602e31685ef3fd7312a4f952ec79d318", "0x3592ab44a12ea16648299f14738ea0817e1b563d3b8000a39cb16eb18b76414a", "0x375513f5f15360c7f90824119b64d79f4e95a792e79bab0ae960235b33d34d13", "0x6aaff1f098eae8d9c9d29c71a556f5411201785b2e32a321c53494281d4a52f6", "0x4a60db5b4fda6bd3b43fed4a90b702f61de166efde36e51bb48c98691379f7ae", "0x16a315ece8d14bf6e327e1c2aa7f9764367fb501470a428cb43cbda39895ef7a", "0x4732994724da47623604e949d226c07f0ad227e88cf0c92058c7921dc3d1c02f", "0x66cfb45f6647a095523adb5cdd45376af456a4235f900f037b3ce4c99c9bad4b", "0x5534431c0d6f15f862d16496529992af11219bc1b936b0003d884e00a5de1abc", "0x2af548fbe4c0e02176091ffbb6f3a1b9d1f313dd66643eebdeb9126c7e897ee8", "0x46c38ece952e86615931ca15c5e11f7a35bb2c6312a4d74fe153ff959b58fa15", "0x0d58dbde546445276d5029bae57752e71ab406d91046f32fdb312592a8d7c86a", "0x4a61e2f96f08bcfd428bb21a79d56951f7568a2d7619dae91b8432ebc923e6ab", "0x59c58d944cb5fa9b70159faaa5a21be4762a62f0f5a78e94efe68237b208ac59", "0x1de64bab1cae8dc35d54bec400e5f25128ad37a1069f8fce553b03ad0ed33fcc", "0x532d70f037959493d5f3a90120495a4c97a3a12440dc35ebdd771abdb276ed2f", "0x2fa34fcd7ac076e02e9679ffd214ce9d68e180184449717a5926b68d82ef116a", "0x739afa5f847a858b43940441ce2a87739644a930eec30e9e929569552cf616b6", "0x0f2ec24a54a31240f34724ea1476973ff062af17d068b27512e241ce51351030", "0x5a927a7ff5ff45e058e4d806b6f0b7d6ff171ede3b3d01d4f8385cab0a1b0e5d", "0x29d8d55cec33000e31b019a0dd8cb14bf9d915a75570bf5f9320b6c5d9d0da0d", "0x37a368626670937de8b35c912a6b82f4aaf60ab63803649802434ca27a071eed", "0x32f8ef20df6c486572088b70537db5ceaeb79ccd643e9e1c0290bb19350f572c", "0x009df831fa9cedacaa065f7bdd5581482e6aad615eb472fd5850a4d0c7477e0c", "0x557f47b72f848625ca347a6d8653d42625e745feea3bb289cd75acfb523d917f", "0x6b0844f5eb2fc982dc88c156d33bd39b5ba4c8fd4cd0247faa379722820b7466", "0x227dee5b1c7f8c409f38d4980b01355d7b21d12e0610ac1317ac724296f8dc2b", "0x46074918624b162c87153dd07b1fb944c38272d01437f9994ef532bb4b4d5f7b", "0x2527405b6d64e04172b68288cda1cb70f848ce6e831d84558b8be56b19d9db33", "0x43a67538a944e568afc6cc5c571fe59e89e4cc960f4957743b6433b3d177d9b6", "0x3dfa278c4f8d8744448747e6340e908bad61b520d15377262173b78d6d3547b8", "0x28470d01333eeb1857e7595d0778318d986c435b8c5c094e225b7d11ca1174ac", "0x15fd96cd7931143ec393ba38b3b15e689b611dd3b99196677260cb3f7f8db9c0", "0x44068fd9a1c6015b4158d15d9a8f2fb298dee91162c63cca3ab814ec29364daf", "0x4c59f63d303f727c672c388d3dbf6a353f9b84c22e511c0aa5bfdbee41171729", "0x3101540e1d7f0a923e53e1ef86def6b0a86e9f036a004be5ddde44df43fd7706", "0x22e2471398685d19af0f408fd1b18cd9442ed279fffdfee27bcfe39d8b8f236e", "0x5c586d10ae52a9e1f274af47ea80443accb97ac0ea73d68b42d54c51b323f6d9", "0x35b59f926a693af114bddce789fca3b67c458e224e82be2aa914c79ed039ad85", "0x0b2d458ee73f4e6054d60365fe40b6120861d7bef7c63c25204577a73448ba0c", "0x02f95cf5081a78c48e317874ab8670a7075da766fc20154f99368269de3b94b5", "0x71c801487fcbbc03b61aedfa14de3d4d8184f51a6319dab24bf5e49115618534", "0x307b41c27156ac0be05eca41a102dc8823b3ddbc1399dc8ce841c1f933caf041", "0x02fc9786ab1b448457ac98c9314454dfc6c638ae8481c3200ecd17dd31d253a5", "0x4409ab4f6c0f069d724f86b5b0d55904e213d4a5fc9755c981ffa016af40ac74", "0x39176c107168eb6620ebfefff311bfe280abe3f48f8751485a1db30deaad785d", "0x204f0ac20ece2cc11169e30799579971c5f02234708b45ea5f066214cccb06a6", "0x739fc006d3b54d5a707a40c835fa6a27713717267d5192f77cb65c5cc8c4eb9b", "0x15a6df21167207003961190a8236d7e7dcd6268085760c8563962e6f87d09acf", "0x40376b10da48099faba417215372cbb92466b79cbacaad347b88931a9757b5d8", "0x5d32aa7e6fa4e2e52b9d47032eacd6509d7e5bcd4660d06ae0204e472bf111eb", "0x2c0e9f70303690b7e14cb877cd268d9781e0050668e9665f04ea09f94ab0817c", "0x231dda54ee054a0afc0530a3064ff80b787e0b25d99596e37ba53aeb3cdffd79", "0x274b78559e51e5426dc8ff296a065973bee858b2434639f66f58d466f9d99af4", "0x5992bb8ba0ca256821c627895438811ae3c066dac732b888fd0e837c669ab727", "0x597a123502768a985def858caf2fc0b2de9e4c4226799a5dc592de58d6a35f56", "0x2a0c027d819603f0735178ad5f7bf87ca5b9387ed0a3b7a8c1b7228c8e335e54", "0x62bd0e7766514106ce033ca67f075830ffc2c54b92903d4228c75762e728ecc4", "0x00fb6ba60efe32561f1d9d57f69c56fca34c01cf6a3b1ab9ccbcf2a2e647d1dc", "0x450c9b21e12734bb4ffcd66850045257f923280145073db0caef03014d4b2bd0", "0x5a496fff496aebe0f694c6d6ed8797ccb76f0001cdbefecdb20f8a1cc3e0cd73", "0x601352aa918622a208db33a151eb2e3e8d027f108e760055a0fa47d2d91d4933", "0x59eda8ec0f8cf0cfe509cfc2bb12eb2251d9e30528c92087cc810b45c6289ec4", "0x47f1310d0e239c2c1e8df723862024239660bc94c20b02c5d8d56e8de98a95c4", "0x6f53b661d4b6220596d1b6436b68e0479a5f410227e6ec8cbd68d5a917ce5bc5", "0x6a0728efd48227aab46a8c1e78a6f4fd55061d6a07d1a8c969ba10eadf521f06", "0x2de99c53670cdb1ed5436b55ce0c1fe82628e2897b39f3c8f47fbc7ce454fc0d", "0x35a1bad35f8a78a9e422036bc48dbf999bccbc5baf65a9867df972615989d00e", "0x529d9e8d93678bfe1bcf24d7add25feeb6d6dcbc1420a0c6bcf9ebc8e7c723f6", "0x69cf3756ce67cd72216b0a58196037587a54e6f9a278a05db40532b86aa85c9a", "0x4d850bab7657c1ac71fd4572fd69b6d56216613fb89b262e002abd6203ff2686", "0x2784e7ef9ac462b56e24d1117b25343e45e37a625e3491614513030aa7f18968", "0x11bcab21e844da9a2a9da9721a1140213ce8d1f5adbadf22d3bdb0393ec5a7e9", "0x1aa8afc39349a7c2dd1dc466421a8891e04c456cc781e0f8bc7d64462213f45c", "0x1cdba5a6ff825aff032ee88da1cc50cc4f4e71d012107432c172ac9894c225d8", "0x15090a6658f804a428093e2f98736de433abe87a4b1dc8b2466a3733a62c6787", "0x3a1864e0ca051a15ce8c00eae2043a7faccbbc2bc0c94629667ca55db404f474", "0x2e2b4cba4fdd3698a9047f1db8a13338220e2777f5a1fd5bb83df6a7c4a649c3", "0x3d316650b675ecb72159173b507f73db67e700372a8943ae3965da155ed9d012", "0x08d5b91751a690a7545406c32eb2cc458a4611741c12a4c3e4b2ef5865202d92", "0x1662a1e3946393e6cbd798b894f344ace1d2071489ad43e6d414e393e5baecc1", "0x6b98576fe63ba1e850a4acbbe5d904dccc6a96c1d3dff54d573fbcd6cb536bcc", "0x54a91d320b6372d425e262ae021becdf6eb6917be79ad083e2aeade6f7a5a190", "0x60dfbfa5d5dd06351a917a05466e5884ed12e38ec24d5bb80be0abe065395e5c"];
+
+// neptune's MDS matrix for t = 3, row by row: the Cauchy matrix 1 / (x_i + y_j) with x_i = i and y_j = 3 + j
+pub(crate) const MDS_PS_NEPTUNE: [[&str; 3]; 3] = [
+    [
+        "0x4d491a377113a8daccd13ab0066be558e27e6d5755543d54aaaaaaaa00000001",
+        "0x56f23d7e5f361df6266b620607396203fece3b023ffec4ff3fffffff40000001",
+        "0x458e97984c2b4b2b51ef819e6c2de803323e959b66656a65cccccccc33333334"
+    ],
+    [
+        "0x56f23d7e5f361df6266b620607396203fece3b023ffec4ff3fffffff40000001",
+        "0x458e97984c2b4b2b51ef819e6c2de803323e959b66656a65cccccccc33333334",
+        "0x609b60c54d5893118005895c0806deaf1b1e08ad2aa94ca9d555555480000001"
+    ],
+    [
+        "0x458e97984c2b4b2b51ef819e6c2de803323e959b66656a65cccccccc33333334",
+        "0x609b60c54d5893118005895c0806deaf1b1e08ad2aa94ca9d555555480000001",
+        "0x211f5460e751918257c7624b7077624aaa362edc49241a48db6db6db24924925"
+    ]
+];
+
+// the tables above as canonical little-endian u64 limbs, parsed by build.rs: `<TABLE>_LIMBS`, the MDS matrices
+// flattened row by row
+include!(concat!(env!("OUT_DIR"), "/embedded_constants.rs"));
+
+// the named parameter sets above, with where their constants come from and a content hash to pin them; published
+// results name the preset (and its hash) they were measured on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Preset {
+    pub name: &'static str,
+    pub permutation: &'static str,
+    pub source: PresetSource,
+    pub field: PresetField,
+    pub width: usize,
+    // Rescue-Prime rounds are all full rounds
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    pub alpha: u64,
+    // where the Poseidon rounds add the constants, the convention of the implementation they are checked against
+    // (None for Rescue-Prime, whose constants follow the MDS matrix)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arc: Option<ArcPosition>,
+    // SHA3-256 of the constants, see constants_hash
+    pub hash: &'static str,
+    #[serde(skip)]
+    round_constants: &'static [&'static str],
+    #[serde(skip)]
+    mds: &'static [[&'static str; 3]; 3],
+    // the same tables embedded as limbs, read instead of the strings in the preset's own field
+    #[serde(skip)]
+    round_constant_limbs: &'static [[u64; 4]],
+    #[serde(skip)]
+    mds_limbs: &'static [[u64; 4]; 9]
+}
+
+// where the constants of a preset come from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PresetSource {
+    // output of a generator algorithm from a seed, which the presets check reruns
+    Generated { algorithm: &'static str, seed: &'static str },
+    // copied from an implementation without a generator to rerun
+    Legacy
+}
+
+// the scalar field a preset was generated for, where its constants are parsed strictly
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PresetField {
+    Bls12_381,
+    Bn254
+}
+
+impl PresetField {
+    pub(crate) fn modulus(&self) -> BigUint {
+        match self {
+            PresetField::Bls12_381 => field_modulus::<Fr>(),
+            PresetField::Bn254 => field_modulus::<Bn256Fr>()
+        }
+    }
+}
+
+// the preset the Poseidon builder defaults to: POSEIDON^π, x^5, t = 3, 8 full and 57 partial rounds
+pub const POSEIDON_BLS381_T3: &str = "poseidon-bls381-t3";
+// the preset the Rescue-Prime builder defaults to: Rescue-XLIX, x^5, m = 3, capacity 1, 128-bit security
+pub const RESCUE_BLS381_T3: &str = "rescue-bls381-t3";
+// circomlib's Poseidon for t = 3 over BN254, see presets.rs
+pub const POSEIDON_BN254_T3_CIRCOM: &str = "poseidon-bn254-t3-circom";
+// neptune's Poseidon for arity 2 over BLS12-381, see presets.rs
+pub const POSEIDON_BLS381_T3_NEPTUNE: &str = "poseidon-bls381-t3-neptune";
+
+static PRESETS: [Preset; 4] = [
+    Preset {
+        name: POSEIDON_BLS381_T3,
+        permutation: "Poseidon",
+        source: PresetSource::Generated {
+            algorithm: "Grain LFSR (round constants, then the Cauchy MDS matrix)",
+            seed: "field 1, S-box 0 (x^alpha), n = 255, t = 3, R_F = 8, R_P = 57"
+        },
+        field: PresetField::Bls12_381,
+        width: 3,
+        full_rounds: 8,
+        partial_rounds: 57,
+        alpha: 5,
+        arc: Some(ArcPosition::BeforeSbox),
+        hash: "8c86b3978d1884016da7ca8b156565bb48007e837c42ff5288b662149bd5a8f8",
+        round_constants: &ROUND_CONSTANTS_PS,
+        mds: &MDS_PS,
+        round_constant_limbs: &ROUND_CONSTANTS_PS_LIMBS,
+        mds_limbs: &MDS_PS_LIMBS
+    },
+    Preset {
+        name: RESCUE_BLS381_T3,
+        permutation: "Rescue-Prime",
+        source: PresetSource::Generated {
+            algorithm: "SHAKE256 (round constants) and the systematic Vandermonde matrix over g = 7 (MDS)",
+            seed: "Rescue-XLIX(p,3,1,128)"
+        },
+        field: PresetField::Bls12_381,
+        width: 3,
+        full_rounds: 14,
+        partial_rounds: 0,
+        alpha: 5,
+        arc: None,
+        hash: "41ed0db5d44022b8785772a232990de763b8cf847a019e6dcea597c9e92175f0",
+        round_constants: &ROUND_CONSTANTS_RS,
+        mds: &MDS_RS,
+        round_constant_limbs: &ROUND_CONSTANTS_RS_LIMBS,
+        mds_limbs: &MDS_RS_LIMBS
+    },
+    Preset {
+        name: POSEIDON_BN254_T3_CIRCOM,
+        permutation: "Poseidon",
+        source: PresetSource::Generated {
+            algorithm: "Grain LFSR (round constants, then the Cauchy MDS matrix)",
+            seed: "field 1, S-box 0 (x^alpha), n = 254, t = 3, R_F = 8, R_P = 57"
+        },
+        field: PresetField::Bn254,
+        width: 3,
+        full_rounds: 8,
+        partial_rounds: 57,
+        alpha: 5,
+        arc: Some(ArcPosition::BeforeSbox),
+        hash: "0b0cf89e1a3de9b7ca6d0118c5f88c94f2b4fe5f53f08398865ad7694ba6b93a",
+        round_constants: &ROUND_CONSTANTS_BN254_CIRCOM,
+        mds: &MDS_BN254_CIRCOM,
+        round_constant_limbs: &ROUND_CONSTANTS_BN254_CIRCOM_LIMBS,
+        mds_limbs: &MDS_BN254_CIRCOM_LIMBS
+    },
+    Preset {
+        name: POSEIDON_BLS381_T3_NEPTUNE,
+        permutation: "Poseidon",
+        source: PresetSource::Generated {
+            algorithm: "Grain LFSR (round constants) and the Cauchy matrix 1 / (x_i + y_j), x_i = i, y_j = t + j (MDS)",
+            seed: "field 1, S-box 1 (neptune's seed for x^5), n = 255, t = 3, R_F = 8, R_P = 55"
+        },
+        field: PresetField::Bls12_381,
+        width: 3,
+        full_rounds: 8,
+        partial_rounds: 55,
+        alpha: 5,
+        arc: Some(ArcPosition::BeforeSbox),
+        hash: "9edcac3b249c4226f35f0459c1cf6fd3b1c11b638d09da173f23a49f5746e26c",
+        round_constants: &ROUND_CONSTANTS_PS_NEPTUNE,
+        mds: &MDS_PS_NEPTUNE,
+        round_constant_limbs: &ROUND_CONSTANTS_PS_NEPTUNE_LIMBS,
+        mds_limbs: &MDS_PS_NEPTUNE_LIMBS
+    }
+];
+
+// every preset, in the order above
+pub fn presets() -> &'static [Preset] {
+    &PRESETS
+}
+
+// the preset called `name`
+pub fn preset(name: &str) -> Result<&'static Preset, BenchmarkError> {
+    PRESETS.iter().find(|preset| preset.name == name).ok_or_else(|| BenchmarkError::ParameterMismatch(format!(
+        "no constants preset {:?}, the presets are {}", name, PRESETS.map(|preset| preset.name).join(", ")
+    )))
+}
+
+impl Preset {
+    // the round constants in F: the embedded limbs in the preset's own field, or the strings checked there and reduced
+    // mod p in any other (see table_constants)
+    pub fn round_constants<F: PrimeField>(&self) -> Result<Vec<F>, BenchmarkError> {
+        match field_modulus::<F>() == self.field.modulus() {
+            true => embedded_constants(self.round_constant_limbs, self.round_constants).map_or_else(|| self.parsed_round_constants(), Ok),
+            false => table_constants(self.name, self.round_constants)
+        }
+    }
+
+    // the MDS matrix in F, read like the round constants
+    pub fn mds<F: PrimeField>(&self) -> Result<[[F; 3]; 3], BenchmarkError> {
+        let entries = match field_modulus::<F>() == self.field.modulus() {
+            true => match embedded_constants(self.mds_limbs, self.mds.as_flattened()) {
+                Some(entries) => entries,
+                None => return self.parsed_mds()
+            },
+            false => table_constants::<F, _>(self.name, self.mds.as_flattened())?
+        };
+        Ok(std::array::from_fn(|i| std::array::from_fn(|j| entries[3 * i + j])))
+    }
+
+    // the round constants parsed strictly from the strings, in the preset's own field
+    pub(crate) fn parsed_round_constants<F: PrimeField>(&self) -> Result<Vec<F>, BenchmarkError> {
+        parse_constants(self.name, self.round_constants)
+    }
+
+    // the MDS matrix parsed strictly from the strings, in the preset's own field
+    pub(crate) fn parsed_mds<F: PrimeField>(&self) -> Result<[[F; 3]; 3], BenchmarkError> {
+        let entries = parse_constants::<F, _>(self.name, self.mds.as_flattened())?;
+        Ok(std::array::from_fn(|i| std::array::from_fn(|j| entries[3 * i + j])))
+    }
+
+    // the hash of the constants as parsed in the preset's field, to compare with the committed `hash`
+    pub fn content_hash(&self) -> Result<String, BenchmarkError> {
+        match self.field {
+            PresetField::Bls12_381 => Ok(constants_hash::<Fr>(&self.round_constants()?, &self.mds()?)),
+            PresetField::Bn254 => Ok(constants_hash::<Bn256Fr>(&self.round_constants()?, &self.mds()?))
+        }
+    }
+}
+
+// helper function for embedded limbs in F, whose modulus is the table's: the limbs are the little-endian repr (PrimeField
+// reprs are little-endian here), or None if F reads them as a value that is not canonical. Debug builds check the first
+// and last word against the strings, so that a limb order mistake in build.rs shows up as a failed assertion
+fn embedded_constants<F: PrimeField>(limbs: &[[u64; 4]], source: &[&str]) -> Option<Vec<F>> {
+    let words = limbs.iter()
+        .map(|limbs| {
+            let mut repr = F::Repr::default();
+            for (bytes, limb) in repr.as_mut().chunks_mut(8).zip(limbs) {
+                bytes.copy_from_slice(&limb.to_le_bytes());
+            }
+            Option::from(F::from_repr(repr))
+        })
+        .collect::<Option<Vec<F>>>()?;
+    debug_assert_eq!(words.first(), parse_fe::<F>(source[0]).ok().as_ref(), "embedded limbs of {:?}", source[0]);
+    debug_assert_eq!(words.last(), parse_fe::<F>(source[source.len() - 1]).ok().as_ref(), "embedded limbs of {:?}", source[source.len() - 1]);
+    Some(words)
+}
+
+// SHA3-256 over the round constants in order and then the MDS matrix row by row, each word as its fe_byte_len()
+// big-endian bytes, in lowercase hex. It depends only on the field elements, not on how a table writes them
+pub(crate) fn constants_hash<F: PrimeField>(round_constants: &[F], mds: &[[F; 3]; 3]) -> String {
+    let mut hasher = Sha3_256::new();
+    for word in round_constants.iter().chain(mds.as_flattened()) {
+        let mut bytes = word.to_repr().as_ref().to_vec();
+        bytes.reverse();
+        hasher.update(&bytes);
+    }
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// the preset whose constants hash to `hash`, to name the preset a set of parameters was built from
+pub(crate) fn preset_with_hash(hash: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.hash == hash)
+}
@@ -0,0 +1,215 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    circuit::Value,
+    dev::MockProver,
+    pasta::Fp,
+    plonk::Circuit,
+};
+use halo2curves::bls12381::Fr;
+
+use crate::circuits::{OutputMode, PoseidonCircuit, RescueCircuit};
+use crate::error::BenchmarkError;
+use crate::failures::verification_failed;
+use crate::native::NativePermutation;
+use crate::params::{poseidon_params, rescue_params, wide_poseidon_params, wide_rescue_params};
+use crate::presets::bn254_circom;
+use crate::proving::{PRESET_VK_DIGESTS, current_threads, vk_digest};
+use crate::source::{ParameterFile, ParameterSource, active_source};
+use crate::srs::commitment_params;
+use crate::synthesis::minimal_k;
+use crate::target::PermutationKind;
+
+// Setup check (`doctor`): every check runs even when an earlier one fails, a panic included, and the report lists the
+// environment and one line per check, so that it can be pasted into a bug report as it is.
+//  - parameters: the invariants of validate() for the preset and generated parameters over both fields, the circomlib
+//    preset and the t = 5 instances, and for a parameter file given with --parameters
+//  - constants: the preset and generated tables written as JSON and TOML parameter files and read back unchanged
+//  - equivalence: the native permutations and the circuits agree on three inputs, in MockProver at the smallest k
+//  - selectors: no row of a permutation circuit enables two selectors, unless its chip declares the combination
+//  - vk: two keygens of each permutation circuit give the same vk, and with the preset parameters the pinned one
+
+// k of the vk check, the k the preset vk digests are pinned at
+const VK_K: u32 = 10;
+
+// helper function for the inputs of the equivalence check: small words, p - 1 in every word, and unrelated large words
+fn doctor_inputs() -> [[Fr; 3]; 3] {
+    let word = Fr::from(0x0123_4567_89ab_cdef);
+    [[0, 1, 2].map(Fr::from), [-Fr::ONE; 3], [word, word.square(), word.cube()]]
+}
+
+// helper function for the CPU model, as /proc/cpuinfo names it (Linux only)
+fn cpu_model() -> String {
+    std::fs::read_to_string("/proc/cpuinfo").ok()
+        .and_then(|cpuinfo| cpuinfo.lines().find_map(|line| line.strip_prefix("model name").map(|model| model.trim_start_matches([' ', '\t', ':']).to_string())))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// helper function for the cargo features this binary was built with
+fn enabled_features() -> String {
+    let features = [
+        ("multicore", cfg!(feature = "multicore")),
+        ("poseidon", cfg!(feature = "poseidon")),
+        ("rescue", cfg!(feature = "rescue")),
+        ("sponge", cfg!(feature = "sponge")),
+        ("merkle", cfg!(feature = "merkle")),
+        ("bench-cli", cfg!(feature = "bench-cli")),
+        ("ark", cfg!(feature = "ark")),
+        ("chrome-trace", cfg!(feature = "chrome-trace"))
+    ];
+    features.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+}
+
+// helper function for the parameter invariants of one source over F
+fn check_parameters<F: PrimeField>(source: &ParameterSource) -> Result<String, BenchmarkError> {
+    let poseidon = source.poseidon::<F>()?;
+    poseidon.validate()?;
+    let rescue = source.rescue::<F>()?;
+    rescue.validate()?;
+    Ok(format!("Poseidon R_F = {}, R_P = {}, Rescue-Prime {} rounds", poseidon.full_rounds, poseidon.partial_rounds, rescue.rounds))
+}
+
+// helper function to write the parameters of a source as a parameter file with `extension` and read them back
+fn check_round_trip(source: &ParameterSource, extension: &str) -> Result<String, BenchmarkError> {
+    let (poseidon, rescue) = (source.poseidon::<Fr>()?, source.rescue::<Fr>()?);
+    let path = std::env::temp_dir().join(format!("permutation_benchmark_doctor_{}.{}", std::process::id(), extension));
+    ParameterFile::from_params(&poseidon, &rescue).write(&path)?;
+    let read = ParameterSource::File(path.clone());
+    let (poseidon_read, rescue_read) = (read.poseidon::<Fr>(), read.rescue::<Fr>());
+    let _ = std::fs::remove_file(&path);
+    let (poseidon_read, rescue_read) = (poseidon_read?, rescue_read?);
+
+    if poseidon_read.round_constants != poseidon.round_constants || poseidon_read.mds != poseidon.mds
+        || (poseidon_read.full_rounds, poseidon_read.partial_rounds, poseidon_read.sbox, poseidon_read.arc) != (poseidon.full_rounds, poseidon.partial_rounds, poseidon.sbox, poseidon.arc) {
+        return Err(BenchmarkError::VerificationFailed(format!("Poseidon parameters changed through a {} file", extension)));
+    }
+    if rescue_read.round_constants != rescue.round_constants || rescue_read.mds != rescue.mds
+        || (rescue_read.rounds, rescue_read.alpha) != (rescue.rounds, rescue.alpha) {
+        return Err(BenchmarkError::VerificationFailed(format!("Rescue-Prime parameters changed through a {} file", extension)));
+    }
+    Ok(format!("{} Poseidon and {} Rescue-Prime round constants unchanged", poseidon.round_constants.len(), rescue.round_constants.len()))
+}
+
+// helper function for the equivalence of a permutation circuit with all outputs public and the native permutation
+fn check_equivalence<C: Circuit<Fr>>(circuit: impl Fn([Value<Fr>; 3]) -> C, native: impl Fn([Fr; 3]) -> [Fr; 3]) -> Result<String, BenchmarkError> {
+    let mut ks = Vec::new();
+    for inputs in doctor_inputs() {
+        let circuit = circuit(inputs.map(Value::known));
+        let k = minimal_k(&circuit)?;
+        if let Err(failures) = MockProver::run(k, &circuit, vec![native(inputs).to_vec()])?.verify() {
+            return Err(verification_failed(format_args!("input {:?} at k = {}", inputs, k), &failures));
+        }
+        ks.push(k);
+    }
+    Ok(format!("{} inputs agree at k = {}", ks.len(), ks[0]))
+}
+
+// helper function for the vk of a permutation circuit over Pasta: two keygens agree, and the preset one is the pinned
+fn check_vk<C: Circuit<Fp>>(label: &str, circuit: &C) -> Result<String, BenchmarkError> {
+    let params = commitment_params(VK_K)?;
+    let digest = vk_digest(&params, circuit)?;
+    if vk_digest(&params, circuit)? != digest {
+        return Err(BenchmarkError::VerificationFailed(format!("two {} keygens at k = {} differ", label, VK_K)));
+    }
+    let pinned = PRESET_VK_DIGESTS.iter().find(|(name, _)| *name == label).map(|(_, digest)| *digest);
+    match active_source() {
+        ParameterSource::Preset if pinned != Some(digest.as_str()) => {
+            Err(BenchmarkError::VerificationFailed(format!("{} vk digest {} differs from the pinned preset digest", label, &digest[..16])))
+        }
+        ParameterSource::Preset => Ok(format!("deterministic at k = {} and equal to the pinned digest {}", VK_K, &digest[..16])),
+        _ => Ok(format!("deterministic at k = {}, digest {}", VK_K, &digest[..16]))
+    }
+}
+
+// helper function for the selector overlap lint on a permutation circuit: no row enables two selectors unless its chip
+// declares the combination
+fn check_selectors(kind: PermutationKind) -> Result<String, BenchmarkError> {
+    let overlaps = kind.target().selector_overlaps(OutputMode::AllState)?;
+    if let Some(overlap) = overlaps.iter().find(|overlap| !overlap.allowed) {
+        let undeclared = overlaps.iter().filter(|overlap| !overlap.allowed).count();
+        return Err(BenchmarkError::VerificationFailed(format!("{} rows enable undeclared selector combinations, first {}", undeclared, overlap)));
+    }
+    match overlaps.len() {
+        0 => Ok("no row enables more than one selector".to_string()),
+        allowed => Ok(format!("{} rows enable declared selector combinations only", allowed))
+    }
+}
+
+// helper function to run one check, turning a panic into a failure with its message
+fn run_check(name: &str, check: impl FnOnce() -> Result<String, BenchmarkError>) -> bool {
+    let outcome = catch_unwind(AssertUnwindSafe(check)).unwrap_or_else(|payload| {
+        let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(BenchmarkError::VerificationFailed(format!("panicked: {}", message)))
+    });
+    match outcome {
+        Ok(detail) => {
+            println!("[ok]   {}: {}", name, detail);
+            true
+        }
+        Err(error) => {
+            println!("[FAIL] {}: {}", name, error);
+            false
+        }
+    }
+}
+
+// run every doctor check and report them together; the error counts the failed checks
+pub fn run_doctor() -> Result<(), BenchmarkError> {
+    println!("permutation_benchmark {}", env!("CARGO_PKG_VERSION"));
+    println!("os:         {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    println!("cpu:        {}", cpu_model());
+    println!("threads:    {}", current_threads());
+    println!("features:   {}", enabled_features());
+    println!("fields:     BLS12-381 scalar field (MockProver, native), Pasta Fp (proofs, IPA)");
+    println!("parameters: {}", active_source());
+    println!();
+
+    let mut sources = vec![ParameterSource::Preset, ParameterSource::Generated];
+    if let ParameterSource::File(path) = active_source() {
+        sources.push(ParameterSource::File(path));
+    }
+    let mut results = Vec::new();
+    for source in &sources {
+        results.push(run_check(&format!("parameters {} over BLS12-381", source), || check_parameters::<Fr>(source)));
+        results.push(run_check(&format!("parameters {} over Pasta", source), || check_parameters::<Fp>(source)));
+    }
+    results.push(run_check("parameters circomlib BN254", || {
+        let poseidon = bn254_circom()?;
+        poseidon.validate()?;
+        Ok(format!("{} round constants", poseidon.round_constants.len()))
+    }));
+    results.push(run_check("parameters t = 5", || {
+        let (poseidon, rescue) = (wide_poseidon_params::<Fr, 5>()?, wide_rescue_params::<Fr, 5>()?);
+        Ok(format!("Poseidon R_P = {}, Rescue-Prime {} rounds", poseidon.partial_rounds, rescue.rounds))
+    }));
+    for source in &sources[..2] {
+        for extension in ["json", "toml"] {
+            results.push(run_check(&format!("constants {} through {}", source, extension), || check_round_trip(source, extension)));
+        }
+    }
+    results.push(run_check("equivalence Poseidon", || {
+        let params = poseidon_params::<Fr>()?;
+        check_equivalence(|[s0, s1, s2]| PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState }, |inputs| params.permute_native(inputs))
+    }));
+    results.push(run_check("equivalence Rescue-Prime", || {
+        let params = rescue_params::<Fr>()?;
+        check_equivalence(|[s0, s1, s2]| RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState }, |inputs| params.permute_native(inputs))
+    }));
+    for kind in PermutationKind::all() {
+        results.push(run_check(&format!("selectors {}", kind), || check_selectors(kind)));
+    }
+    let unknown = [Value::<Fp>::unknown(); 3];
+    results.push(run_check("vk Poseidon", || check_vk("Poseidon", &PoseidonCircuit::new(unknown, OutputMode::AllState)?)));
+    results.push(run_check("vk Rescue-Prime", || check_vk("Rescue-Prime", &RescueCircuit::new(unknown, OutputMode::AllState)?)));
+
+    let failed = results.iter().filter(|passed| !**passed).count();
+    println!();
+    println!("{} of {} checks passed", results.len() - failed, results.len());
+    match failed {
+        0 => Ok(()),
+        failed => Err(BenchmarkError::VerificationFailed(format!("{} of {} doctor checks failed", failed, results.len())))
+    }
+}
+
@@ -0,0 +1,49 @@
+use halo2_proofs::plonk::Error;
+
+// crate error type: parameter construction, input parsing, and proving report through it
+#[derive(Debug, thiserror::Error)]
+pub enum BenchmarkError {
+    #[error("invalid constant in {table} at index {index}: {value:?}")]
+    InvalidConstant { table: &'static str, index: usize, value: String },
+    #[error("missing parameter: {0}")]
+    MissingParameter(&'static str),
+    #[error("parameter mismatch: {0}")]
+    ParameterMismatch(String),
+    #[error("invalid field element: {0:?}")]
+    InvalidFieldElement(String),
+    #[error("prover error: {0}")]
+    ProverError(#[source] Error),
+    #[error("{circuit} does not fit at k = {k}: it needs k >= {minimum} ({rows} rows plus halo2's reserved rows)")]
+    KTooSmall { circuit: String, k: u32, minimum: u32, rows: usize },
+    #[error("the circuit does not fit in the 2^{k} rows of k = {k}; rerun with a larger k")]
+    NotEnoughRows { k: u32 },
+    #[error("verification failed: {0}")]
+    VerificationFailed(String),
+    #[error("{path}: {source}")]
+    Io { path: String, source: std::io::Error },
+    #[error("invalid parameter file {path}: {reason}")]
+    InvalidParameterFile { path: String, reason: String },
+    #[error("invalid test vector file {path}: {reason}")]
+    InvalidVectorFile { path: String, reason: String },
+    #[error("invalid input file {path}, line {line}: {reason}")]
+    InvalidInputsFile { path: String, line: usize, reason: String },
+    #[error("invalid proof file {path}: {reason}")]
+    InvalidProofFile { path: String, reason: String },
+    #[error("invalid SRS file {path}: {reason}")]
+    InvalidSrsFile { path: String, reason: String },
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+    #[error("{0} was cancelled")]
+    Cancelled(String)
+}
+
+// halo2 reports a circuit that outgrows 2^k rows from inside synthesis; that case gets its own variant, since it is the
+// one a user fixes by passing a larger k
+impl From<Error> for BenchmarkError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::NotEnoughRowsAvailable { current_k } => BenchmarkError::NotEnoughRows { k: current_k },
+            error => BenchmarkError::ProverError(error)
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::Serialize;
+use halo2_proofs::dev::{FailureLocation, VerifyFailure, metadata::Region};
+
+use crate::error::BenchmarkError;
+
+// Summary of the failures MockProver reports for a circuit that does not verify. A broken composed circuit can fail
+// the same constraint on hundreds of rows, so the failures are grouped instead of printed one by one. halo2 0.3 keeps
+// the fields of gates, constraints, regions and columns private, so they are grouped by the names in their Display
+// output ("Constraint 0 in gate 2 ('name')", "Region 4 ('name')", "Column('Instance', 0)").
+
+// offending locations kept in the summary, in the order MockProver reports them
+const FIRST_ROWS: usize = 5;
+
+// structure for the failures of one MockProver run
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FailureSummary {
+    pub total: usize,
+    // unsatisfied or poisoned constraints and unassigned cells, by the name of their gate
+    pub gates: BTreeMap<String, usize>,
+    // failures located in a region, by the region's name
+    pub regions: BTreeMap<String, usize>,
+    // the first distinct locations, "in Region 4 ('name') at offset 3" or "outside any region, on row 17"
+    pub first_rows: Vec<String>,
+    // copy constraints to an instance column and instance cells a gate reads unassigned, i.e. public inputs that do
+    // not match the circuit
+    pub instance: usize,
+    // copy constraints failing on a cell of another column; MockProver reports a broken copy on both of its cells, so a
+    // public input that does not match also counts here once, for the advice cell copied to it
+    pub permutation: usize,
+    pub lookups: usize
+}
+
+impl FailureSummary {
+    // whether any public input disagrees with the circuit
+    pub fn has_instance_failures(&self) -> bool {
+        self.instance > 0
+    }
+
+    // whether any copy constraint outside the instance column is broken
+    pub fn has_permutation_failures(&self) -> bool {
+        self.permutation > 0
+    }
+}
+
+// group MockProver's failures by gate and region, with the first offending locations and the copy constraint and
+// lookup failures counted apart
+pub fn summarize_failures(failures: &[VerifyFailure]) -> FailureSummary {
+    let mut summary = FailureSummary { total: failures.len(), ..FailureSummary::default() };
+
+    for failure in failures {
+        let (gate, location) = match failure {
+            VerifyFailure::CellNotAssigned { gate, region, gate_offset, .. } => (Some(gate.to_string()), Some(in_region(region, *gate_offset))),
+            VerifyFailure::InstanceCellNotAssigned { gate, region, gate_offset, .. } => {
+                summary.instance += 1;
+                (Some(gate.to_string()), Some(in_region(region, *gate_offset)))
+            }
+            VerifyFailure::ConstraintNotSatisfied { constraint, location, .. } => (Some(constraint_gate(&constraint.to_string())), Some(located(location))),
+            VerifyFailure::ConstraintPoisoned { constraint } => (Some(constraint_gate(&constraint.to_string())), None),
+            VerifyFailure::Lookup { location, .. } => {
+                summary.lookups += 1;
+                (None, Some(located(location)))
+            }
+            VerifyFailure::Permutation { column, location } => {
+                match column.to_string().starts_with("Column('Instance'") {
+                    true => summary.instance += 1,
+                    false => summary.permutation += 1
+                }
+                (None, Some(located(location)))
+            }
+        };
+
+        if let Some(gate) = gate {
+            *summary.gates.entry(quoted_name(&gate)).or_default() += 1;
+        }
+        if let Some((region, location)) = location {
+            if let Some(region) = region {
+                *summary.regions.entry(region).or_default() += 1;
+            }
+            if summary.first_rows.len() < FIRST_ROWS && !summary.first_rows.contains(&location) {
+                summary.first_rows.push(location);
+            }
+        }
+    }
+
+    summary
+}
+
+// helper function for the error of a circuit that does not verify, with its failures summarized after the context
+pub(crate) fn verification_failed(context: impl fmt::Display, failures: &[VerifyFailure]) -> BenchmarkError {
+    BenchmarkError::VerificationFailed(format!("{}: {}", context, summarize_failures(failures)))
+}
+
+// helper function for a location as the name of its region, if it is in one, and its Display output
+fn located(location: &FailureLocation) -> (Option<String>, String) {
+    match location {
+        FailureLocation::InRegion { region, .. } => (Some(quoted_name(&region.to_string())), location.to_string()),
+        FailureLocation::OutsideRegion { .. } => (None, location.to_string())
+    }
+}
+
+// helper function for an offset in a region, the way FailureLocation displays it
+fn in_region(region: &Region, offset: usize) -> (Option<String>, String) {
+    (Some(quoted_name(&region.to_string())), format!("in {} at offset {}", region, offset))
+}
+
+// helper function for the name in the last "('name')" of a gate's or region's Display output, or all of it if none
+fn quoted_name(display: &str) -> String {
+    match (display.rfind("('"), display.rfind("')")) {
+        (Some(start), Some(end)) if start + 2 <= end => display[start + 2..end].to_string(),
+        _ => display.to_string()
+    }
+}
+
+// helper function for the name of the gate a constraint's Display output ends with
+fn constraint_gate(display: &str) -> String {
+    quoted_name(display.rsplit_once(" in gate ").map_or(display, |(_, gate)| gate))
+}
+
+// one line, since the summary ends up in error messages: the count, then each non-empty group
+impl fmt::Display for FailureSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |groups: &BTreeMap<String, usize>| groups.iter().map(|(name, count)| format!("'{}' x{}", name, count)).collect::<Vec<_>>().join(", ");
+        write!(f, "{} failure{}", self.total, if self.total == 1 { "" } else { "s" })?;
+        if !self.gates.is_empty() {
+            write!(f, "; gates: {}", counts(&self.gates))?;
+        }
+        if !self.regions.is_empty() {
+            write!(f, "; regions: {}", counts(&self.regions))?;
+        }
+        if self.has_instance_failures() {
+            write!(f, "; instance: {}", self.instance)?;
+        }
+        if self.has_permutation_failures() {
+            write!(f, "; copy constraints: {}", self.permutation)?;
+        }
+        if self.lookups > 0 {
+            write!(f, "; lookups: {}", self.lookups)?;
+        }
+        if !self.first_rows.is_empty() {
+            write!(f, "; first at: {}", self.first_rows.join(", "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,553 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use ff::{Field, PrimeField};
+use std::fmt::Debug;
+use halo2_proofs::{
+    circuit::{Region, Value},
+    plonk::{
+        Advice, Fixed, Column, ConstraintSystem, Error, Instance, Selector, Expression, VirtualCells
+    },
+    poly::Rotation,
+};
+
+use serde::Serialize;
+
+use crate::error::BenchmarkError;
+use crate::params::SboxKind;
+#[cfg(feature = "bench-cli")]
+use crate::report::GateReport;
+
+thread_local! {
+    // gates created while an inventory is being recorded (see record_gates)
+    #[cfg(feature = "bench-cli")]
+    static GATE_LOG: RefCell<Option<Vec<GateReport>>> = const { RefCell::new(None) };
+    // selectors allocated while an inventory is being recorded, in allocation order (see allocate_selector)
+    #[cfg(feature = "bench-cli")]
+    static SELECTOR_LOG: RefCell<Option<Vec<Selector>>> = const { RefCell::new(None) };
+    // columns enabled for global constants while a configure call is tracked (see track_constants)
+    static CONSTANT_LOG: RefCell<Option<Vec<Column<Fixed>>>> = const { RefCell::new(None) };
+    // Circuit::configure takes no arguments in halo2 0.3, so allocate_columns reads the strategy set here
+    static CONSTANT_STRATEGY: Cell<ConstantStrategy> = const { Cell::new(ConstantStrategy::Fixed) };
+    // likewise for the S-box layout, read by sbox_square_columns
+    static SBOX_LAYOUT: Cell<SboxLayout> = const { Cell::new(SboxLayout::Direct) };
+    // the gate the soundness audit leaves out of the constraint system (see with_stripped_gate)
+    #[cfg(feature = "bench-cli")]
+    static STRIPPED_GATE: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+// helper function to create a gate and note its constraints, degree and queried cells in the active inventory; the gate
+// the audit strips is not created at all, its selector is then enabled without constraining anything
+pub(crate) fn create_gate<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>,
+    name: &'static str,
+    constraints: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>
+) {
+    #[cfg(feature = "bench-cli")]
+    if STRIPPED_GATE.with(Cell::get) == Some(name) {
+        return;
+    }
+    meta.create_gate(name, |meta| {
+        let constraints = constraints(meta);
+        #[cfg(feature = "bench-cli")]
+        GATE_LOG.with(|log| {
+            if let Some(gates) = log.borrow_mut().as_mut() {
+                gates.push(GateReport::new(name, &constraints));
+            }
+        });
+        constraints
+    });
+}
+
+// helper function to run a configure call and return the gates it created, in creation order
+#[cfg(feature = "bench-cli")]
+pub(crate) fn record_gates(configure: impl FnOnce()) -> Vec<GateReport> {
+    record_configure(configure).0
+}
+
+// helper function to run a configure call and return the gates it created and the selectors it allocated, both in
+// creation order
+#[cfg(feature = "bench-cli")]
+pub(crate) fn record_configure(configure: impl FnOnce()) -> (Vec<GateReport>, Vec<Selector>) {
+    GATE_LOG.with(|log| log.replace(Some(Vec::new())));
+    SELECTOR_LOG.with(|log| log.replace(Some(Vec::new())));
+    configure();
+    (GATE_LOG.with(|log| log.take()).unwrap_or_default(), SELECTOR_LOG.with(|log| log.take()).unwrap_or_default())
+}
+
+// helper function for the chips to allocate a selector, noted in the active inventory so that gates and the selector
+// overlap lint can name it by its allocation order
+pub(crate) fn allocate_selector<F: Field>(meta: &mut ConstraintSystem<F>) -> Selector {
+    log_selector(meta.selector())
+}
+
+// helper function to allocate a complex selector (one a lookup can query), noted like allocate_selector
+pub(crate) fn allocate_complex_selector<F: Field>(meta: &mut ConstraintSystem<F>) -> Selector {
+    log_selector(meta.complex_selector())
+}
+
+// helper function to note a selector in the active inventory
+fn log_selector(selector: Selector) -> Selector {
+    #[cfg(feature = "bench-cli")]
+    SELECTOR_LOG.with(|log| {
+        if let Some(selectors) = log.borrow_mut().as_mut() {
+            selectors.push(selector);
+        }
+    });
+    selector
+}
+
+// helper function for the position of a selector among those allocated in the active inventory, None outside of one
+#[cfg(feature = "bench-cli")]
+pub(crate) fn selector_index(selector: &Selector) -> Option<usize> {
+    SELECTOR_LOG.with(|log| log.borrow().as_ref().and_then(|selectors| selectors.iter().position(|allocated| allocated == selector)))
+}
+
+// helper function to enable a fixed column for global constants, noted for track_constants; the constraint system's
+// list is private in halo2 0.3, and the layout helpers hand it to the floor planner themselves
+pub(crate) fn enable_constant<F: Field>(meta: &mut ConstraintSystem<F>, column: Column<Fixed>) {
+    meta.enable_constant(column);
+    CONSTANT_LOG.with(|log| {
+        if let Some(columns) = log.borrow_mut().as_mut().filter(|columns| !columns.contains(&column)) {
+            columns.push(column);
+        }
+    });
+}
+
+// helper function to run a configure call and return its config with the columns it enabled for global constants, in
+// the order the constraint system keeps them
+pub(crate) fn track_constants<T>(configure: impl FnOnce() -> T) -> (T, Vec<Column<Fixed>>) {
+    let previous = CONSTANT_LOG.with(|log| log.replace(Some(Vec::new())));
+    let config = configure();
+    let columns = CONSTANT_LOG.with(|log| log.replace(previous)).unwrap_or_default();
+    (config, columns)
+}
+
+// helper function to configure circuits on this thread without the gate called `name` while `run` executes, for the
+// soundness audit
+#[cfg(feature = "bench-cli")]
+pub(crate) fn with_stripped_gate<T>(name: &'static str, run: impl FnOnce() -> T) -> T {
+    let previous = STRIPPED_GATE.with(|active| active.replace(Some(name)));
+    let result = run();
+    STRIPPED_GATE.with(|active| active.set(previous));
+    result
+}
+
+// how the round constants reach the gates that add them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ConstantStrategy {
+    // three fixed columns filled from the constant schedule (the benchmark design)
+    #[default]
+    Fixed,
+    // three extra advice columns, each constant pinned by assign_advice_from_constant through one constants column
+    Advice,
+    // no round constant columns: a gate and selector per ARC step with its three constants inlined as
+    // Expression::Constant, like the MDS entries
+    Gate
+}
+
+// helper function to configure circuits on this thread with `strategy` while `run` executes (configure, keygen,
+// MockProver and the cost model all call Circuit::configure)
+pub(crate) fn with_constant_strategy<T>(strategy: ConstantStrategy, run: impl FnOnce() -> T) -> T {
+    let previous = CONSTANT_STRATEGY.with(|active| active.replace(strategy));
+    let result = run();
+    CONSTANT_STRATEGY.with(|active| active.set(previous));
+    result
+}
+
+// how the Poseidon round gates and the Rescue-Prime SubBytes gates constrain the x^5 S-box and its inverse
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SboxLayout {
+    // output = input^5 in one constraint of degree 5, and input = output^5 for the Rescue-Prime inverse S-box (the
+    // benchmark design)
+    #[default]
+    Direct,
+    // the square of each S-box input witnessed in an extra advice column next to it: square = input^2 and
+    // output = square^2 * input, two constraints of degree at most 3. The inverse S-box witnesses the square of its
+    // output instead, the x^5 side of the relation
+    Squared
+}
+
+// helper function to configure circuits on this thread with the S-box `layout` while `run` executes
+pub(crate) fn with_sbox_layout<T>(layout: SboxLayout, run: impl FnOnce() -> T) -> T {
+    let previous = SBOX_LAYOUT.with(|active| active.replace(layout));
+    let result = run();
+    SBOX_LAYOUT.with(|active| active.set(previous));
+    result
+}
+
+// helper function to allocate the square columns of the active S-box layout, one per state word, or none
+pub(crate) fn sbox_square_columns<F: PrimeField>(meta: &mut ConstraintSystem<F>) -> Option<[Column<Advice>; 3]> {
+    match SBOX_LAYOUT.with(Cell::get) {
+        SboxLayout::Direct => None,
+        SboxLayout::Squared => Some([meta.advice_column(), meta.advice_column(), meta.advice_column()])
+    }
+}
+
+// helper function to check that the active S-box layout can constrain `sbox`: the squared layout witnesses x^2 and
+// constrains x^5 only
+pub(crate) fn check_sbox_layout(sbox: SboxKind) -> Result<(), BenchmarkError> {
+    if SBOX_LAYOUT.with(Cell::get) == SboxLayout::Squared && sbox != SboxKind::Power(5) {
+        return Err(BenchmarkError::ParameterMismatch(format!("the squared S-box layout constrains x^5, the parameters have {:?}", sbox)));
+    }
+    Ok(())
+}
+
+// the columns the round constant gates read the round constants from, at their own row
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ConstantColumns {
+    Fixed([Column<Fixed>; 3]),
+    // `constants` is the fixed column the layouter places the pinned values in and copies them from
+    Advice { columns: [Column<Advice>; 3], constants: Column<Fixed> },
+    // `constants` only holds global constants (e.g. a domain tag), the round constants are part of the gates
+    Gate { constants: Column<Fixed> }
+}
+
+impl ConstantColumns {
+    #[cfg(test)]
+    pub(crate) fn strategy(&self) -> ConstantStrategy {
+        match self {
+            ConstantColumns::Fixed(_) => ConstantStrategy::Fixed,
+            ConstantColumns::Advice { .. } => ConstantStrategy::Advice,
+            ConstantColumns::Gate { .. } => ConstantStrategy::Gate
+        }
+    }
+
+    // enable the columns for global constants, and equality on advice cells that are pinned to them
+    pub(crate) fn enable<F: PrimeField>(&self, meta: &mut ConstraintSystem<F>) {
+        match self {
+            ConstantColumns::Fixed(fixed) => {
+                for column in fixed {
+                    enable_constant(meta, *column);
+                }
+            }
+            ConstantColumns::Advice { columns, constants } => {
+                enable_constant(meta, *constants);
+                for column in columns {
+                    meta.enable_equality(*column);
+                }
+            }
+            ConstantColumns::Gate { constants } => enable_constant(meta, *constants)
+        }
+    }
+}
+
+// where a gate that adds round constants reads them from
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum GateConstants<F: PrimeField> {
+    // the strategy's columns at the gate's row, one gate for every ARC step
+    Columns(ConstantColumns),
+    // the three constants of a single ARC step, under the gate strategy
+    Inline([F; 3])
+}
+
+impl<F: PrimeField> GateConstants<F> {
+    // round constant `index` (0..3) at the current row
+    pub(crate) fn query(&self, meta: &mut VirtualCells<'_, F>, index: usize) -> Expression<F> {
+        match self {
+            GateConstants::Columns(ConstantColumns::Fixed(fixed)) => meta.query_fixed(fixed[index]),
+            GateConstants::Columns(ConstantColumns::Advice { columns, .. }) => meta.query_advice(columns[index], Rotation::cur()),
+            GateConstants::Columns(ConstantColumns::Gate { .. }) => unreachable!("the gate strategy inlines its round constants"),
+            GateConstants::Inline(values) => Expression::Constant(values[index])
+        }
+    }
+}
+
+// helper function to create the gates that add round constants, three per ARC step, and return the selector of each
+// step; `create` makes the gate of a kind (e.g. full or partial round) from its constants and selector. With round
+// constant columns there is one gate per kind, shared by its steps, and under the gate strategy one per step. A
+// trailing incomplete step (from a table that does not fit the round counts) gets no selector, the witness path reports it
+pub(crate) fn create_arc_gates<F: PrimeField, K: Ord + Copy>(
+    meta: &mut ConstraintSystem<F>,
+    constants: ConstantColumns,
+    round_constants: &[F],
+    kind: impl Fn(usize) -> K,
+    mut create: impl FnMut(&mut ConstraintSystem<F>, K, GateConstants<F>, Selector)
+) -> Vec<Selector> {
+    let mut shared: BTreeMap<K, Selector> = BTreeMap::new();
+    round_constants.chunks_exact(3)
+        .enumerate()
+        .map(|(step, values)| match constants {
+            ConstantColumns::Gate { .. } => {
+                let selector = allocate_selector(meta);
+                create(meta, kind(step), GateConstants::Inline([values[0], values[1], values[2]]), selector);
+                selector
+            }
+            columns => *shared.entry(kind(step)).or_insert_with(|| {
+                let selector = allocate_selector(meta);
+                create(meta, kind(step), GateConstants::Columns(columns), selector);
+                selector
+            })
+        })
+        .collect()
+}
+
+// what a chip's configure enables on the columns it is given; a host circuit that shares its columns with the chip
+// and sets them up itself turns these off, e.g. to keep its own constants column the one the floor planner places
+// constants in (the first enabled one) and the chip's round constant columns out of the permutation argument
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ChipOptions {
+    // equality on the advice and instance columns, which copies and public outputs need
+    pub(crate) enable_equality: bool,
+    // the round constant columns (or the advice strategy's constants column) as global constants columns
+    pub(crate) enable_constants: bool,
+    // instance row of the chip's first public value, so chips and a host sharing one instance column each expose
+    // their values on rows of their own
+    pub(crate) instance_offset: usize
+}
+
+impl Default for ChipOptions {
+    fn default() -> Self {
+        ChipOptions { enable_equality: true, enable_constants: true, instance_offset: 0 }
+    }
+}
+
+impl ChipOptions {
+    // enable what the options ask for on the chip's columns
+    pub(crate) fn enable<F: PrimeField>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constants: ConstantColumns,
+        instance: Column<Instance>
+    ) {
+        if self.enable_equality {
+            meta.enable_equality(instance);
+            for column in advice {
+                meta.enable_equality(column);
+            }
+        }
+        if self.enable_constants {
+            constants.enable(meta);
+        }
+    }
+}
+
+// struture for common circuit parameters
+#[derive(Clone, Debug)]
+pub struct CircuitParameters {
+    pub(crate) advice: [Column<Advice>; 3],
+    pub(crate) constants: ConstantColumns,
+    pub(crate) instance: Column<Instance>,
+    // expose_as_public rows are counted from here
+    pub(crate) instance_offset: usize,
+    pub(crate) s_select: Selector,
+    pub(crate) s_is_equal: Selector
+}
+
+// one fixed cell of a chip's round constant schedule, at a row offset within the permutation region
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FixedAssignment<F: PrimeField> {
+    pub(crate) offset: usize,
+    pub(crate) column: usize,
+    pub(crate) value: F
+}
+
+// helper function to lay the round constants out three per row (fixed[0..3]) on the rows where the chip adds them;
+// computed once in configure, since the fixed columns are the same for every proof. The gate strategy assigns nothing
+pub(crate) fn constant_schedule<F: PrimeField>(
+    constants: ConstantColumns,
+    round_constants: &[F],
+    arc_rows: impl Iterator<Item = usize>
+) -> Vec<FixedAssignment<F>> {
+    if let ConstantColumns::Gate { .. } = constants {
+        return Vec::new();
+    }
+    round_constants.chunks(3)
+        .zip(arc_rows)
+        .flat_map(|(row, offset)| {
+            row.iter().enumerate().map(move |(column, value)| FixedAssignment { offset, column, value: *value })
+        })
+        .collect()
+}
+
+// helper function to replay a constant schedule into the permutation region, returning the number of constant cells;
+// with advice constants the layouter adds one constants-column cell and one copy constraint per cell
+pub(crate) fn assign_constant_schedule<F: PrimeField>(
+    region: &mut Region<F>,
+    constants: ConstantColumns,
+    schedule: &[FixedAssignment<F>]
+) -> Result<usize, Error> {
+    for cell in schedule {
+        match constants {
+            ConstantColumns::Fixed(fixed) => {
+                region.assign_fixed(|| "round_constant", fixed[cell.column], cell.offset, || Value::known(cell.value))?;
+            }
+            ConstantColumns::Advice { columns, .. } => {
+                region.assign_advice_from_constant(|| "round_constant", columns[cell.column], cell.offset, cell.value)?;
+            }
+            ConstantColumns::Gate { .. } => unreachable!("the gate strategy has an empty constant schedule")
+        }
+    }
+
+    Ok(schedule.len())
+}
+
+// helper function for the S-box power x^alpha in a gate, multiplied out left to right so the expression has degree alpha
+pub(crate) fn pow_expression<F: PrimeField>(x: Expression<F>, alpha: u64) -> Expression<F> {
+    (1..alpha).fold(x.clone(), |power, _| power * x.clone())
+}
+
+// helper function for the linear combination coeffs[0] * terms[0] + coeffs[1] * terms[1] + ... in a gate, summed left
+// to right with every coefficient embedded as a constant; an empty combination is the constant 0
+pub(crate) fn linear_combination<F: PrimeField>(coeffs: &[F], terms: &[Expression<F>]) -> Expression<F> {
+    assert_eq!(coeffs.len(), terms.len(), "a linear combination takes one coefficient per term");
+    coeffs.iter().zip(terms)
+        .map(|(coeff, term)| term.clone() * Expression::Constant(*coeff))
+        .reduce(|sum, term| sum + term)
+        .unwrap_or(Expression::Constant(F::ZERO))
+}
+
+// helper function for the matrix product M * state on gate expressions, a linear combination per row of M, oriented
+// like mds_mul_native
+pub(crate) fn matrix_mul_exprs<F: PrimeField, const T: usize>(mds: &[[F; T]; T], state: &[Expression<F>; T]) -> [Expression<F>; T] {
+    std::array::from_fn(|i| linear_combination(&mds[i], state))
+}
+
+// helper function for the constraints of an S-box from input to output: output = input^alpha for the power map, and
+// for x^-1 the pair input * (input * output - 1) = 0 and output * (input * output - 1) = 0, which force
+// output = input^-1 for a non-zero input and output = 0 for a zero input without an extra is-zero cell (degree 3)
+pub(crate) fn sbox_constraints<F: PrimeField>(input: Expression<F>, output: Expression<F>, sbox: SboxKind) -> Vec<Expression<F>> {
+    match sbox {
+        SboxKind::Power(alpha) => vec![output - pow_expression(input, alpha)],
+        SboxKind::Inverse => {
+            let product = input.clone() * output.clone() - Expression::Constant(F::ONE);
+            vec![input * product.clone(), output * product]
+        }
+    }
+}
+
+// helper function for the constraints of the x^5 S-box with the input's square witnessed: square = input^2 and
+// output = square^2 * input
+pub(crate) fn squared_sbox_constraints<F: PrimeField>(input: Expression<F>, square: Expression<F>, output: Expression<F>) -> Vec<Expression<F>> {
+    vec![square.clone() - pow_expression(input.clone(), 2), output - square.clone() * square * input]
+}
+
+// helper function for the sponge's add gate: advice[2] = advice[0] + advice[1] on one row
+pub(crate) fn create_add_gate<F: PrimeField>(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3], s_add: Selector) {
+    create_gate(meta, "Add_gate", |meta| {
+        let s_add = meta.query_selector(s_add);
+        let a = meta.query_advice(advice[0], Rotation::cur());
+        let b = meta.query_advice(advice[1], Rotation::cur());
+        let sum = meta.query_advice(advice[2], Rotation::cur());
+
+        vec![s_add * (sum - (a + b))]
+    });
+}
+
+// helper function for the example host circuit's multiplication gate: advice[2] = advice[0] * advice[1] on one row
+pub(crate) fn create_mul_gate<F: PrimeField>(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3], s_mul: Selector) {
+    create_gate(meta, "Mul_gate", |meta| {
+        let s_mul = meta.query_selector(s_mul);
+        let a = meta.query_advice(advice[0], Rotation::cur());
+        let b = meta.query_advice(advice[1], Rotation::cur());
+        let product = meta.query_advice(advice[2], Rotation::cur());
+
+        vec![s_mul * (product - a * b)]
+    });
+}
+
+// helper function for the MDS product of the current row, M * cur; both chips embed the matrix as constants
+fn mds_product<F: PrimeField>(meta: &mut VirtualCells<'_, F>, advice: [Column<Advice>; 3], mds: &[[F; 3]; 3]) -> [Expression<F>; 3] {
+    matrix_mul_exprs(mds, &advice.map(|column| meta.query_advice(column, Rotation::cur())))
+}
+
+// MixLayer: next = M * cur
+pub(crate) fn create_mds_mul_gate<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>, 
+    advice: [Column<Advice>; 3], 
+    s_mds_mul: Selector,
+    mds: &[[F; 3]; 3]
+) {
+    create_gate(meta, "ML_gate", |meta| {
+        let s_mds_mul = meta.query_selector(s_mds_mul);
+        let product = mds_product(meta, advice, mds);
+
+        product.into_iter().enumerate()
+            .map(|(i, word)| s_mds_mul.clone() * (meta.query_advice(advice[i], Rotation::next()) - word))
+            .collect()
+    });
+}
+
+// MixLayer followed by the round constant injection, next = M * cur + rc with the constants read from the current
+// row (or inlined), so the MDS output row needs no ARC row of its own
+pub(crate) fn create_mds_arc_gate<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>,
+    advice: [Column<Advice>; 3],
+    constants: GateConstants<F>,
+    s_mds_arc: Selector,
+    mds: &[[F; 3]; 3]
+) {
+    create_gate(meta, "ML_ARC_gate", |meta| {
+        let s_mds_arc = meta.query_selector(s_mds_arc);
+        let product = mds_product(meta, advice, mds);
+
+        product.into_iter().enumerate()
+            .map(|(i, word)| s_mds_arc.clone() * (meta.query_advice(advice[i], Rotation::next()) - (word + constants.query(meta, i))))
+            .collect()
+    });
+}
+
+// conditional select over two rows: bit, a, b on the current row and the output in advice[0] on the next;
+// the bit is constrained boolean and out = bit * a + (1 - bit) * b, written as out - b - bit * (a - b)
+pub(crate) fn create_select_gate<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>,
+    advice: [Column<Advice>; 3],
+    s_select: Selector
+) {
+    create_gate(meta, "Select_gate", |meta| {
+        let s_select = meta.query_selector(s_select);
+        let bit = meta.query_advice(advice[0], Rotation::cur());
+        let a = meta.query_advice(advice[1], Rotation::cur());
+        let b = meta.query_advice(advice[2], Rotation::cur());
+        let out = meta.query_advice(advice[0], Rotation::next());
+        let one = Expression::Constant(F::ONE);
+
+        vec![
+            s_select.clone() * bit.clone() * (one - bit.clone()),
+            s_select * (out - b.clone() - bit * (a - b))
+        ]
+    });
+}
+
+// equality check over two rows: a, b, inv on the current row and the output in advice[0] on the next. For a != b,
+// (a - b) * inv = 1 - out can only hold with out = 0 (and inv = (a - b)^-1); for a = b it forces out = 1, which
+// (a - b) * out = 0 allows, so out is 1 exactly when a = b
+pub(crate) fn create_is_equal_gate<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>,
+    advice: [Column<Advice>; 3],
+    s_is_equal: Selector
+) {
+    create_gate(meta, "Is_equal_gate", |meta| {
+        let s_is_equal = meta.query_selector(s_is_equal);
+        let a = meta.query_advice(advice[0], Rotation::cur());
+        let b = meta.query_advice(advice[1], Rotation::cur());
+        let inv = meta.query_advice(advice[2], Rotation::cur());
+        let out = meta.query_advice(advice[0], Rotation::next());
+        let one = Expression::Constant(F::ONE);
+
+        vec![
+            s_is_equal.clone() * ((a.clone() - b.clone()) * inv - (one - out.clone())),
+            s_is_equal * (a - b) * out
+        ]
+    });
+}
+
+// helper function to allocate the columns shared by both chips, with the round constant columns of the active strategy
+pub(crate) fn allocate_columns<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>
+) -> ([Column<Advice>; 3], ConstantColumns, Column<Instance>) {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let constants = match CONSTANT_STRATEGY.with(Cell::get) {
+        ConstantStrategy::Fixed => ConstantColumns::Fixed([meta.fixed_column(), meta.fixed_column(), meta.fixed_column()]),
+        ConstantStrategy::Advice => ConstantColumns::Advice {
+            columns: [meta.advice_column(), meta.advice_column(), meta.advice_column()],
+            constants: meta.fixed_column()
+        },
+        ConstantStrategy::Gate => ConstantColumns::Gate { constants: meta.fixed_column() }
+    };
+    let instance = meta.instance_column();
+
+    (advice, constants, instance)
+}
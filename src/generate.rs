@@ -0,0 +1,291 @@
+use ff::PrimeField;
+use num_bigint::BigUint;
+use serde::Serialize;
+use sha3::Shake256;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+
+use crate::error::BenchmarkError;
+use crate::params::{Poseidon, RescuePrime, SboxKind, fe_from_biguint, field_modulus};
+
+// Grain LFSR of the Poseidon reference implementation (generate_parameters_grain.sage), in self-shrinking mode
+struct GrainLfsr {
+    state: [bool; 80]
+}
+
+impl GrainLfsr {
+    // the 80-bit seed is field = 1 (prime field), sbox (0 for x^alpha, 1 for x^-1), n, t, R_F, R_P and 30 set bits
+    fn new(field_size: usize, t: usize, full_rounds: usize, partial_rounds: usize, sbox: SboxKind) -> Self {
+        let sbox = match sbox {
+            SboxKind::Power(_) => 0,
+            SboxKind::Inverse => 1
+        };
+        let fields = [(1, 2), (sbox, 4), (field_size, 12), (t, 12), (full_rounds, 10), (partial_rounds, 10), ((1 << 30) - 1, 30)];
+        let mut state = [false; 80];
+        let mut position = 0;
+        for (value, len) in fields {
+            for bit in (0..len).rev() {
+                state[position] = (value >> bit) & 1 == 1;
+                position += 1;
+            }
+        }
+
+        let mut lfsr = GrainLfsr { state };
+        for _ in 0..160 {
+            lfsr.step();
+        }
+        lfsr
+    }
+
+    fn step(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.rotate_left(1);
+        self.state[79] = bit;
+        bit
+    }
+
+    // bits are drawn in pairs, the second one is kept when the first one is set
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.step();
+            let bit = self.step();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    // next `bits` output bits as a big-endian integer
+    fn next_integer(&mut self, bits: usize) -> BigUint {
+        let mut value = BigUint::default();
+        for _ in 0..bits {
+            value <<= 1;
+            if self.next_bit() {
+                value += 1u32;
+            }
+        }
+        value
+    }
+}
+
+// helper function to generate the Poseidon round constants and MDS matrix (t = T) with the Grain LFSR. Constants are
+// rejection-sampled below p; the MDS matrix is the Cauchy matrix 1 / (x_i + y_j) from the following 2t samples. The
+// reference's invariant-subspace checks on the matrix are not run, the BLS12-381 matrix they accept is the first one drawn
+pub(crate) fn poseidon_grain<F: PrimeField, const T: usize>(full_rounds: usize, partial_rounds: usize, sbox: SboxKind) -> (Vec<F>, [[F; T]; T]) {
+    let t = T;
+    let modulus = field_modulus::<F>();
+    let field_size = modulus.bits() as usize;
+    let mut lfsr = GrainLfsr::new(field_size, t, full_rounds, partial_rounds, sbox);
+
+    let round_constants = (0..t * (full_rounds + partial_rounds))
+        .map(|_| loop {
+            let value = lfsr.next_integer(field_size);
+            if value < modulus {
+                break fe_from_biguint(&value);
+            }
+        })
+        .collect();
+
+    let mds = loop {
+        let samples: Vec<F> = (0..2 * t).map(|_| fe_from_biguint(&(lfsr.next_integer(field_size) % &modulus))).collect();
+        let distinct = samples.iter().enumerate().all(|(i, a)| samples[..i].iter().all(|b| a != b));
+        let inverses: Option<Vec<F>> = (0..t * t)
+            .map(|index| Option::from((samples[index / t] + samples[t + index % t]).invert()))
+            .collect();
+        if let (true, Some(inverses)) = (distinct, inverses) {
+            break std::array::from_fn(|i| std::array::from_fn(|j| inverses[t * i + j]));
+        }
+    };
+
+    (round_constants, mds)
+}
+
+// helper function for the Cauchy matrix 1 / (x_i + y_j) with x_i = i and y_j = t + j (t = 3), the deterministic choice
+// of implementations that derive the Poseidon MDS matrix without sampling it
+pub(crate) fn index_cauchy_mds<F: PrimeField>() -> [[F; 3]; 3] {
+    std::array::from_fn(|i| std::array::from_fn(|j| {
+        F::from((i + 3 + j) as u64).invert().expect("i + t + j is not zero in a large field")
+    }))
+}
+
+// where an MDS matrix comes from, so that a report names the instance it was measured on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum MdsOrigin {
+    // the Poseidon reference: the Cauchy matrix from the Grain samples after the round constants (poseidon_grain)
+    Grain,
+    // the Cauchy matrix with x_i = i and y_j = t + j (index_cauchy_mds)
+    IndexCauchy,
+    // the Rescue-Prime reference: the systematic form of the Vandermonde matrix (rescue_mds)
+    Vandermonde,
+    // the Anemoi reference matrix for the number of column pairs (anemoi_mds)
+    Anemoi,
+    // none of the derivations above, e.g. a matrix from a parameter file
+    Custom
+}
+
+// helper function to find which derivation gives a Poseidon instance's MDS matrix, for its field and round numbers
+pub(crate) fn poseidon_mds_origin<F: PrimeField>(params: &Poseidon<F>) -> MdsOrigin {
+    if params.mds == poseidon_grain::<F, 3>(params.full_rounds, params.partial_rounds, params.sbox).1 {
+        MdsOrigin::Grain
+    } else if params.mds == index_cauchy_mds() {
+        MdsOrigin::IndexCauchy
+    } else {
+        MdsOrigin::Custom
+    }
+}
+
+// helper function to find whether a Rescue-Prime instance uses the reference MDS matrix for its field
+pub(crate) fn rescue_mds_origin<F: PrimeField>(params: &RescuePrime<F>) -> MdsOrigin {
+    match rescue_mds::<F, 3>() {
+        Ok(mds) if mds == params.mds => MdsOrigin::Vandermonde,
+        _ => MdsOrigin::Custom
+    }
+}
+
+// helper function for the Poseidon round numbers with the inverse S-box, from the paper's bounds for x^-1 as in the
+// reference script (calc_round_numbers.py): R_F >= 6 against statistical attacks if M <= (floor(log2 p) - 2)(t + 1),
+// else 10, and R_P >= ceil(min(M, n) / 2) + ceil(log2 t) - floor(R_F log2 t) against interpolation attacks. With the
+// security margin (R_F + 2, ceil(1.075 R_P)) added, the pair with the fewest S-boxes t R_F + R_P is returned
+pub(crate) fn poseidon_inverse_round_numbers(t: usize, field_size: usize, security: u32) -> (usize, usize) {
+    let m = security as f64;
+    let log_t = (t as f64).log2();
+    let statistical = if m <= ((field_size - 1) as f64 - 2.0) * (t + 1) as f64 { 6 } else { 10 };
+    let interpolation = |full_rounds: usize| {
+        (m.min(field_size as f64) / 2.0).ceil() + log_t.ceil() - (full_rounds as f64 * log_t).floor()
+    };
+
+    let mut best: Option<(usize, usize)> = None;
+    for partial_rounds in 1..500 {
+        for full_rounds in (4..100).step_by(2) {
+            if full_rounds < statistical || (partial_rounds as f64) < interpolation(full_rounds) {
+                continue;
+            }
+            let candidate = (full_rounds + 2, (partial_rounds as f64 * 1.075).ceil() as usize);
+            let cost = |(full_rounds, partial_rounds): (usize, usize)| t * full_rounds + partial_rounds;
+            if best.is_none_or(|best| cost(candidate) < cost(best) || (cost(candidate) == cost(best) && candidate.0 < best.0)) {
+                best = Some(candidate);
+            }
+        }
+    }
+
+    best.expect("large enough round numbers meet both bounds")
+}
+
+// helper function for the Rescue-Prime round count from the security formula of the reference implementation
+// (get_number_of_rounds): the smallest l1 for which a Groebner basis attack on l1 rounds costs more than
+// 2^security, i.e. binomial(v + d, v)^2 > 2^security with v = m * (l1 - 1) + rate variables and degree of regularity
+// d = floor((alpha - 1) * m * (l1 - 1) / 2) + 2, then at least 5 and 50% on top. The formula does not involve p (the
+// field only enters through alpha, which has to be a permutation exponent for it)
+pub(crate) fn rescue_round_count(m: usize, capacity: usize, security: u32, alpha: u64) -> usize {
+    let rate = m - capacity;
+    let target = BigUint::from(1u8) << security;
+    let binomial = |n: usize, k: usize| (0..k).fold(BigUint::from(1u8), |acc, i| acc * (n - i) / (i + 1));
+    let l1 = (1..)
+        .find(|l1: &usize| {
+            let v = m * (l1 - 1) + rate;
+            let d = ((alpha as usize - 1) * m * (l1 - 1)) / 2 + 2;
+            binomial(v + d, v).pow(2) > target
+        })
+        .expect("the attack cost grows without bound");
+
+    (3 * l1.max(5)).div_ceil(2)
+}
+
+// helper function for the Anemoi round count from the reference implementation (get_n_rounds): the smallest r for which
+// binomial(4lr + kappa, 2lr)^2 reaches 2^security, where kappa depends on alpha, plus 2 rounds for the second attack
+// model and min(5, l + 1) rounds of margin, and at least 8
+pub(crate) fn anemoi_round_count(security: u32, l: usize, alpha: u64) -> Result<usize, BenchmarkError> {
+    let kappa = match alpha {
+        3 => 1,
+        5 => 2,
+        7 => 4,
+        9 => 7,
+        11 => 9,
+        _ => return Err(BenchmarkError::Unsupported(format!("Anemoi round count for x^{}", alpha)))
+    };
+    let target = BigUint::from(1u8) << security;
+    let binomial = |n: usize, k: usize| (0..k).fold(BigUint::from(1u8), |acc, i| acc * (n - i) / (i + 1));
+    let r = (1..)
+        .find(|r: &usize| binomial(4 * l * r + kappa, 2 * l * r).pow(2) >= target)
+        .expect("the attack cost grows without bound");
+
+    Ok((r + 2 + (l + 1).min(5)).max(8))
+}
+
+// helper function to generate the Anemoi round constants from the digits of pi as in the reference implementation,
+// with pi_0 = 1415926535, pi_1 = 8979323846 and g the multiplicative generator:
+// c[r][i] = g * (pi_0^r)^2 + (pi_0^r + pi_1^i)^alpha and d[r][i] = g * (pi_1^i)^2 + (pi_0^r + pi_1^i)^alpha + g^-1
+pub(crate) fn anemoi_constants<F: PrimeField, const L: usize>(rounds: usize, alpha: u64) -> (Vec<[F; L]>, Vec<[F; L]>) {
+    let g = F::MULTIPLICATIVE_GENERATOR;
+    let g_inv = g.invert().expect("the generator is not zero");
+    let (pi_0, pi_1) = (F::from(1415926535), F::from(8979323846));
+    (0..rounds)
+        .map(|r| {
+            let pi_0_r = pi_0.pow_vartime([r as u64]);
+            let pi_1_i: [F; L] = std::array::from_fn(|i| pi_1.pow_vartime([i as u64]));
+            let sum = pi_1_i.map(|pi_1_i| (pi_0_r + pi_1_i).pow_vartime([alpha]));
+            let c = std::array::from_fn(|i| g * pi_0_r.square() + sum[i]);
+            let d = std::array::from_fn(|i| g * pi_1_i[i].square() + sum[i] + g_inv);
+            (c, d)
+        })
+        .unzip()
+}
+
+// helper function for the Anemoi linear layer matrix M_x for l = L; the reference defines it per l, and only l = 1
+// (the identity) and l = 2 are implemented here
+pub(crate) fn anemoi_mds<F: PrimeField, const L: usize>() -> Result<[[F; L]; L], BenchmarkError> {
+    let g = F::MULTIPLICATIVE_GENERATOR;
+    let rows = match L {
+        1 => vec![vec![F::ONE]],
+        2 => vec![vec![F::ONE, g], vec![g, g.square() + F::ONE]],
+        _ => return Err(BenchmarkError::Unsupported(format!("Anemoi with l = {}, only l = 1 and 2 are implemented", L)))
+    };
+
+    Ok(std::array::from_fn(|i| std::array::from_fn(|j| rows[i][j])))
+}
+
+// helper function to generate the Rescue-Prime round constants (m = M) from SHAKE256 as in the reference
+// implementation: the seed is "Rescue-XLIX(p,m,capacity,security_level)" and each constant is read from
+// ceil(log2(p) / 8) + 1 little-endian bytes, reduced mod p
+pub(crate) fn rescue_constants<F: PrimeField, const M: usize>(rounds: usize, capacity: usize, security_level: usize) -> Vec<F> {
+    let m = M;
+    let modulus = field_modulus::<F>();
+    let bytes_per_int = (modulus.bits() as usize).div_ceil(8) + 1;
+    let seed = format!("Rescue-XLIX({},{},{},{})", modulus, m, capacity, security_level);
+
+    let mut shake = Shake256::default();
+    shake.update(seed.as_bytes());
+    let mut reader = shake.finalize_xof();
+    (0..2 * m * rounds)
+        .map(|_| {
+            let mut chunk = vec![0u8; bytes_per_int];
+            reader.read(&mut chunk);
+            fe_from_biguint(&(BigUint::from_bytes_le(&chunk) % &modulus))
+        })
+        .collect()
+}
+
+// helper function to generate the Rescue-Prime MDS matrix (m = M): bring the m x 2m Vandermonde matrix
+// V[i][j] = g^(i * j) into systematic form [I | R] and take the transpose of R. g is F::MULTIPLICATIVE_GENERATOR, which
+// is the smallest primitive root (7) for BLS12-381 as in the reference
+pub(crate) fn rescue_mds<F: PrimeField, const M: usize>() -> Result<[[F; M]; M], BenchmarkError> {
+    let g = F::MULTIPLICATIVE_GENERATOR;
+    let mut rows: Vec<Vec<F>> = (0..M).map(|i| (0..2 * M).map(|j| g.pow([(i * j) as u64])).collect()).collect();
+
+    // Gauss-Jordan elimination, the left block is an invertible Vandermonde matrix so no row swaps are needed
+    for pivot in 0..M {
+        let inverse = Option::<F>::from(rows[pivot][pivot].invert())
+            .ok_or_else(|| BenchmarkError::ParameterMismatch("the Rescue-Prime Vandermonde matrix is singular".to_string()))?;
+        rows[pivot] = rows[pivot].iter().map(|entry| *entry * inverse).collect();
+        for row in 0..M {
+            if row != pivot {
+                let factor = rows[row][pivot];
+                rows[row] = (0..2 * M).map(|j| rows[row][j] - factor * rows[pivot][j]).collect();
+            }
+        }
+    }
+
+    Ok(std::array::from_fn(|i| std::array::from_fn(|j| rows[j][M + i])))
+}
+
@@ -0,0 +1,66 @@
+use ff::PrimeField;
+use halo2curves::bls12381::Fr;
+
+use crate::error::BenchmarkError;
+use crate::native::NativePermutation;
+use crate::params::{poseidon_params, rescue_params};
+use crate::presets::{bls381_neptune, neptune_hash};
+use crate::sponge::Sponge;
+
+// Native Poseidon and Rescue-Prime hash functions over field elements, with no halo2 types involved, e.g. for an
+// algebraic Fiat-Shamir transcript or for expected values in tests. Both absorb the inputs through the unkeyed sponge
+// (rate 2, capacity starting at the domain tag plus the tag of the 10* padding) and return state[0], so a digest here is the digest of
+// Sponge::hash in a circuit. The parameters are the ones of the parameter source active on this thread.
+
+// hasher over one permutation, holding its parameters so repeated hashes do not load them again
+pub struct Hasher<F: PrimeField> {
+    name: &'static str,
+    permutation: Box<dyn NativePermutation<F> + Send + Sync>
+}
+
+impl<F: PrimeField> Hasher<F> {
+    // hasher over the benchmark's Poseidon instance
+    pub fn poseidon() -> Result<Self, BenchmarkError> {
+        Ok(Hasher { name: "Poseidon", permutation: Box::new(poseidon_params::<F>()?) })
+    }
+
+    // hasher over the benchmark's Rescue-Prime instance
+    pub fn rescue() -> Result<Self, BenchmarkError> {
+        Ok(Hasher { name: "Rescue-Prime", permutation: Box::new(rescue_params::<F>()?) })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    // one application of the permutation, for the duplex sponge of the Poseidon transcript
+    pub(crate) fn permute(&self, state: [F; 3]) -> [F; 3] {
+        self.permutation.permute_native(state)
+    }
+
+    pub(crate) fn domain_tag(&self) -> F {
+        self.permutation.domain_tag()
+    }
+
+    // hash any number of field elements, the empty input included
+    pub fn hash(&self, inputs: &[F]) -> F {
+        Sponge::new().hash_native(self.permutation.as_ref(), inputs)
+    }
+}
+
+// hash field elements with Poseidon
+pub fn poseidon_hash<F: PrimeField>(inputs: &[F]) -> Result<F, BenchmarkError> {
+    Ok(Hasher::poseidon()?.hash(inputs))
+}
+
+// hash field elements with Rescue-Prime
+pub fn rescue_hash<F: PrimeField>(inputs: &[F]) -> Result<F, BenchmarkError> {
+    Ok(Hasher::rescue()?.hash(inputs))
+}
+
+// hash two BLS12-381 words as neptune's Poseidon<Fr, U2>::hash does, with its parameters (the
+// poseidon-bls381-t3-neptune preset) and its layout rather than the sponge's, whatever the active parameter source;
+// for code moving from neptune that has to keep its digests
+pub fn neptune_poseidon_hash(inputs: [Fr; 2]) -> Result<Fr, BenchmarkError> {
+    neptune_hash(&bls381_neptune()?, inputs)
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use ff::PrimeField;
+use halo2_proofs::pasta::Fp;
+use halo2curves::bls12381::Fr;
+
+use crate::error::BenchmarkError;
+use crate::native::NativePermutation;
+use crate::params::{Endianness, fe_to_hex, parse_word, poseidon_params, rescue_params};
+use crate::poseidon::PoseidonChip;
+use crate::proving::{ProvingRng, prove_batch};
+use crate::report::{csv_field, parse_csv_record};
+use crate::rescue::RescueChip;
+use crate::target::PermutationKind;
+
+// Input files of many states for `hash --inputs-file`: every row is three field elements, decimal or 0x-prefixed hex,
+// and the output repeats the rows in the same format with the digest (state[0] after the permutation) appended.
+// Blank lines and lines starting with '#' are skipped in both formats.
+//  - CSV: `s0,s1,s2` per line, with an optional `s0,s1,s2` header that gains a `digest` column
+//  - JSON Lines (.json or .jsonl): one array of three words per line, strings or integers below 2^64
+
+// input file formats, told apart by the extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InputsFormat {
+    Csv,
+    JsonLines
+}
+
+impl InputsFormat {
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json" | "jsonl") => InputsFormat::JsonLines,
+            _ => InputsFormat::Csv
+        }
+    }
+}
+
+// one well-formed row: its line number (1-based), its words as written and the parsed state
+#[derive(Debug)]
+struct InputRow<F> {
+    #[cfg_attr(not(test), allow(dead_code))]
+    line: usize,
+    words: Vec<String>,
+    state: [F; 3]
+}
+
+// a parsed input file: the CSV header if there was one, and the well-formed rows in file order
+#[derive(Debug)]
+struct InputsFile<F> {
+    format: InputsFormat,
+    header: bool,
+    rows: Vec<InputRow<F>>
+}
+
+// helper function to split one JSON Lines row into its words, keeping integers as their decimal text
+fn json_words(record: &str) -> Result<Vec<String>, String> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(record).map_err(|e| format!("not a JSON array: {}", e))?;
+    values.into_iter()
+        .map(|value| match value {
+            serde_json::Value::String(word) => Ok(word),
+            serde_json::Value::Number(number) if number.is_u64() => Ok(number.to_string()),
+            other => Err(format!("{} is not a field element, write large values as strings", other))
+        })
+        .collect()
+}
+
+// helper function to parse the rows of an input file. A malformed row aborts with its line number, or with `lenient`
+// is reported on stderr and skipped
+fn parse_inputs<F: PrimeField>(path: &Path, text: &str, endianness: Endianness, lenient: bool) -> Result<InputsFile<F>, BenchmarkError> {
+    let format = InputsFormat::of(path);
+    let mut file = InputsFile { format, header: false, rows: Vec::new() };
+    for (index, record) in text.lines().enumerate() {
+        let line = index + 1;
+        let record = record.trim();
+        if record.is_empty() || record.starts_with('#') {
+            continue;
+        }
+
+        let words = match format {
+            InputsFormat::Csv => Ok(parse_csv_record(record).into_iter().map(|word| word.trim().to_string()).collect::<Vec<_>>()),
+            InputsFormat::JsonLines => json_words(record)
+        };
+        if format == InputsFormat::Csv && file.rows.is_empty() && !file.header && words.as_deref() == Ok(&["s0", "s1", "s2"].map(String::from)[..]) {
+            file.header = true;
+            continue;
+        }
+
+        let state = words.clone().and_then(|words| {
+            let [s0, s1, s2] = &words[..] else {
+                return Err(format!("expected 3 words, found {}", words.len()));
+            };
+            [s0, s1, s2].into_iter()
+                .map(|word| parse_word::<F>(word, endianness).map_err(|e| e.to_string()))
+                .collect::<Result<Vec<F>, String>>()
+                .map(|state| [state[0], state[1], state[2]])
+        });
+        match (words, state) {
+            (Ok(words), Ok(state)) => file.rows.push(InputRow { line, words, state }),
+            (Err(reason), _) | (_, Err(reason)) => {
+                let error = BenchmarkError::InvalidInputsFile { path: path.display().to_string(), line, reason };
+                if !lenient {
+                    return Err(error);
+                }
+                eprintln!("warning: {}, skipped", error);
+            }
+        }
+    }
+
+    Ok(file)
+}
+
+// helper function to render the rows with their digests appended, in the format they were read in
+fn render_digests<F: PrimeField>(file: &InputsFile<F>, digests: &[F]) -> String {
+    let mut out = String::new();
+    if file.header {
+        out.push_str("s0,s1,s2,digest\n");
+    }
+    for (row, digest) in file.rows.iter().zip(digests) {
+        let mut words = row.words.clone();
+        words.push(fe_to_hex(digest, Endianness::Big));
+        match file.format {
+            InputsFormat::Csv => out.push_str(&words.iter().map(|word| csv_field(word)).collect::<Vec<_>>().join(",")),
+            InputsFormat::JsonLines => out.push_str(&serde_json::to_string(&words).unwrap())
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// helper function for the digests of the rows under the active parameters
+fn native_digests<F: PrimeField>(permutation: PermutationKind, file: &InputsFile<F>) -> Result<Vec<F>, BenchmarkError> {
+    let digest = |params: &dyn NativePermutation<F>| file.rows.iter().map(|row| params.permute_native(row.state)[0]).collect();
+    Ok(match permutation {
+        PermutationKind::Poseidon => digest(&poseidon_params::<F>()?),
+        PermutationKind::Rescue => digest(&rescue_params::<F>()?)
+    })
+}
+
+// helper function to write the output to `out`, or to stdout when no path is given
+fn write_output(out: Option<&Path>, content: &str) -> Result<(), BenchmarkError> {
+    match out {
+        Some(path) => std::fs::write(path, content).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source }),
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+// hash every state of an input file and write the rows with their digests. Natively the digests are over the
+// BLS12-381 scalar field like the test vectors; with `prove` the states are read over Pasta's Fp, which the real
+// proofs run on, all of them are proved in one batch proof, and its report is printed as JSON on stderr, so that it
+// never mixes with digests written to stdout
+pub fn hash_inputs_file(
+    path: &Path,
+    permutation: PermutationKind,
+    endianness: Endianness,
+    lenient: bool,
+    prove: bool,
+    out: Option<&Path>,
+    seed: Option<u64>
+) -> Result<(), BenchmarkError> {
+    let text = std::fs::read_to_string(path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+    if !prove {
+        let file = parse_inputs::<Fr>(path, &text, endianness, lenient)?;
+        return write_output(out, &render_digests(&file, &native_digests(permutation, &file)?));
+    }
+
+    let file = parse_inputs::<Fp>(path, &text, endianness, lenient)?;
+    if file.rows.is_empty() {
+        return Err(BenchmarkError::ParameterMismatch(format!("{} has no states to prove", path.display())));
+    }
+    let states: Vec<[Fp; 3]> = file.rows.iter().map(|row| row.state).collect();
+    let mut params = BTreeMap::new();
+    let report = match permutation {
+        PermutationKind::Poseidon => prove_batch::<PoseidonChip<Fp>>(&states, &mut params, ProvingRng::new(seed))?,
+        PermutationKind::Rescue => prove_batch::<RescueChip<Fp>>(&states, &mut params, ProvingRng::new(seed))?
+    };
+    write_output(out, &render_digests(&file, &native_digests(permutation, &file)?))?;
+    eprintln!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // check the input file parsing on a small CSV with a malformed line: strict mode names the line, lenient mode skips
+    // it and appends digests that match the native permutation, and the same rows as JSON Lines give the same digests
+    #[test]
+    fn inputs_files() -> Result<(), BenchmarkError> {
+        let csv = Path::new("inputs.csv");
+        let text = "s0,s1,s2\n0,1,2\n# comment\n\n3,4\n0x05,6,7\n";
+        match parse_inputs::<Fr>(csv, text, Endianness::Big, false) {
+            Err(BenchmarkError::InvalidInputsFile { line: 5, .. }) => {}
+            other => return Err(BenchmarkError::VerificationFailed(format!("strict mode should reject line 5, got {:?}", other)))
+        }
+
+        let file = parse_inputs::<Fr>(csv, text, Endianness::Big, true)?;
+        assert!(file.header);
+        assert_eq!(file.rows.iter().map(|row| row.line).collect::<Vec<_>>(), [2, 6]);
+        let digests = native_digests(PermutationKind::Poseidon, &file)?;
+        let params = poseidon_params::<Fr>()?;
+        assert_eq!(digests, [[0, 1, 2], [5, 6, 7]].map(|state| params.permute_native(state.map(Fr::from))[0]));
+        let rendered = render_digests(&file, &digests);
+        assert_eq!(rendered.lines().next(), Some("s0,s1,s2,digest"));
+        assert_eq!(rendered.lines().nth(2), Some(format!("0x05,6,7,{}", fe_to_hex(&digests[1], Endianness::Big)).as_str()));
+
+        let json = Path::new("inputs.jsonl");
+        let text = "[\"0\", 1, \"2\"]\n[\"0x05\", \"6\", 7]\n[1, 2, -3]\n";
+        match parse_inputs::<Fr>(json, text, Endianness::Big, false) {
+            Err(BenchmarkError::InvalidInputsFile { line: 3, .. }) => {}
+            other => return Err(BenchmarkError::VerificationFailed(format!("strict mode should reject JSON line 3, got {:?}", other)))
+        }
+        let file = parse_inputs::<Fr>(json, text, Endianness::Big, true)?;
+        assert_eq!(native_digests(PermutationKind::Poseidon, &file)?, digests);
+        assert!(render_digests(&file, &digests).starts_with("[\"0\",\"1\",\"2\",\"0x"));
+        println!("input files: malformed rows rejected in strict mode and skipped in lenient mode, CSV and JSON Lines digests agree");
+
+        Ok(())
+    }
+}
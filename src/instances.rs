@@ -0,0 +1,163 @@
+use std::fmt;
+use ff::PrimeField;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::circuits::OutputMode;
+use crate::error::BenchmarkError;
+use crate::params::{Endianness, fe_from_hex, fe_to_hex, get_common_params};
+
+// Typed public inputs: a permutation's exposed output words as a `Digest` of as many words as the output mode exposes,
+// and the instance columns of a circuit as `PublicInputs`, which converts to the nested vectors MockProver::run and
+// verify_proof take. Both print and serialize their words as 0x-prefixed big-endian hex, the format of the reports.
+
+// the words a permutation circuit exposes: N = 3 for all of the state, 2 for the rate, 1 (the default) for the digest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Digest<F, const N: usize = 1>(pub [F; N]);
+
+impl<F: PrimeField, const N: usize> Digest<F, N> {
+    // the first N words of an output state, rejecting a state shorter than that
+    pub fn from_state(state: &[F]) -> Result<Self, BenchmarkError> {
+        let words = state.get(..N).ok_or_else(|| BenchmarkError::ParameterMismatch(format!(
+            "a digest of {} words needs a state of at least {} words, {} given", N, N, state.len()
+        )))?;
+        Ok(Digest(words.try_into().unwrap()))
+    }
+}
+
+impl<F, const N: usize> From<[F; N]> for Digest<F, N> {
+    fn from(words: [F; N]) -> Self {
+        Digest(words)
+    }
+}
+
+// a single word prints as its hex, several words comma-separated in state order
+impl<F: PrimeField, const N: usize> fmt::Display for Digest<F, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex_list(&self.0))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Serialize for Digest<F, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|word| fe_to_hex(word, Endianness::Big)))
+    }
+}
+
+impl<'de, F: PrimeField, const N: usize> Deserialize<'de> for Digest<F, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let words = parse_hex_words::<F, D::Error>(Vec::<String>::deserialize(deserializer)?)?;
+        let len = words.len();
+        words.try_into().map(Digest)
+            .map_err(|_| serde::de::Error::invalid_length(len, &format!("a digest of {} words", N).as_str()))
+    }
+}
+
+// the instance columns of a circuit in column order, each the words at instance rows 0, 1, ...
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PublicInputs<F> {
+    columns: Vec<Vec<F>>
+}
+
+impl<F: PrimeField> PublicInputs<F> {
+    // the instance of a permutation circuit in OutputMode::AllState
+    pub fn all_state(digest: Digest<F, 3>) -> Self {
+        Self::from(digest.0.to_vec())
+    }
+
+    // the instance of a permutation circuit in OutputMode::RateOnly
+    pub fn rate_only(digest: Digest<F, 2>) -> Self {
+        Self::from(digest.0.to_vec())
+    }
+
+    // the instance of a permutation circuit in OutputMode::DigestOnly
+    pub fn digest_only(digest: Digest<F>) -> Self {
+        Self::from(digest.0.to_vec())
+    }
+
+    // the instance of a permutation circuit in `output_mode` whose output state is `state`
+    pub fn from_output(output_mode: OutputMode, state: &[F; 3]) -> Self {
+        Self::from(output_mode.expected_instance(&get_common_params(), state))
+    }
+
+    // instance columns as MockProver::run takes them
+    pub fn from_instances(columns: Vec<Vec<F>>) -> Self {
+        PublicInputs { columns }
+    }
+
+    // the single instance column of a permutation circuit as a digest of N words; the wrong N for the circuit's output
+    // mode, or a circuit with other columns, is an error instead of a misaligned instance
+    pub fn digest<const N: usize>(&self) -> Result<Digest<F, N>, BenchmarkError> {
+        match &self.columns[..] {
+            [column] if column.len() == N => Digest::from_state(column),
+            _ => Err(BenchmarkError::ParameterMismatch(format!(
+                "expected one instance column of {} words, found columns of {:?} words", N, self.columns.iter().map(Vec::len).collect::<Vec<_>>()
+            )))
+        }
+    }
+
+    // all instance columns in order
+    pub fn columns(&self) -> &[Vec<F>] {
+        &self.columns
+    }
+
+    // the words of instance column `index`
+    pub fn column(&self, index: usize) -> &[F] {
+        &self.columns[index]
+    }
+
+    // instance columns as MockProver::run takes them
+    pub fn to_instances(&self) -> Vec<Vec<F>> {
+        self.columns.clone()
+    }
+
+    // instance columns as create_proof and verify_proof take them for one circuit
+    pub fn as_slices(&self) -> Vec<&[F]> {
+        self.columns.iter().map(Vec::as_slice).collect()
+    }
+}
+
+// a circuit with one instance column
+impl<F> From<Vec<F>> for PublicInputs<F> {
+    fn from(column: Vec<F>) -> Self {
+        PublicInputs { columns: vec![column] }
+    }
+}
+
+impl<F> From<PublicInputs<F>> for Vec<Vec<F>> {
+    fn from(public_inputs: PublicInputs<F>) -> Self {
+        public_inputs.columns
+    }
+}
+
+// every column in brackets, e.g. `[0x01.., 0x02..]`
+impl<F: PrimeField> fmt::Display for PublicInputs<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns: Vec<String> = self.columns.iter().map(|column| format!("[{}]", hex_list(column))).collect();
+        f.write_str(&columns.join(", "))
+    }
+}
+
+impl<F: PrimeField> Serialize for PublicInputs<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.columns.iter().map(|column| column.iter().map(|word| fe_to_hex(word, Endianness::Big)).collect::<Vec<_>>()))
+    }
+}
+
+impl<'de, F: PrimeField> Deserialize<'de> for PublicInputs<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let columns = Vec::<Vec<String>>::deserialize(deserializer)?.into_iter()
+            .map(parse_hex_words::<F, D::Error>)
+            .collect::<Result<_, _>>()?;
+        Ok(PublicInputs { columns })
+    }
+}
+
+// helper function to join words as big-endian hex
+fn hex_list<F: PrimeField>(words: &[F]) -> String {
+    words.iter().map(|word| fe_to_hex(word, Endianness::Big)).collect::<Vec<_>>().join(", ")
+}
+
+// helper function to parse big-endian hex words for the deserializers, rejecting values >= p
+fn parse_hex_words<F: PrimeField, E: serde::de::Error>(words: Vec<String>) -> Result<Vec<F>, E> {
+    words.iter().map(|word| fe_from_hex(word, Endianness::Big).map_err(E::custom)).collect()
+}
@@ -0,0 +1,5 @@
+// conversions between this crate's field elements and other proving stacks' field types
+#[cfg(feature = "ark")]
+pub mod ark;
+#[cfg(feature = "neptune")]
+pub mod neptune;
@@ -0,0 +1,148 @@
+use ark_ff::{BigInt, BigInteger, PrimeField as ArkPrimeField};
+use ff::PrimeField;
+use halo2curves::bls12381::Fr;
+use num_bigint::BigUint;
+
+use crate::error::BenchmarkError;
+use crate::params::{Endianness, fe_from_bytes};
+
+// an arkworks BLS12-381 scalar, converted to and from halo2curves' through canonical little-endian bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArkFr(pub ark_bls12_381::Fr);
+
+// both fields have the same modulus, so a canonical halo2curves element is a canonical arkworks one
+impl From<Fr> for ArkFr {
+    fn from(value: Fr) -> Self {
+        let bigint = BigInt::try_from(BigUint::from_bytes_le(value.to_repr().as_ref())).expect("a 255-bit value fits in 4 limbs");
+        ArkFr(ark_bls12_381::Fr::from_bigint(bigint).expect("the moduli are equal"))
+    }
+}
+
+// the bytes are checked against the modulus like any other input
+impl TryFrom<ArkFr> for Fr {
+    type Error = BenchmarkError;
+
+    fn try_from(value: ArkFr) -> Result<Self, Self::Error> {
+        fe_from_bytes(&value.0.into_bigint().to_bytes_le(), Endianness::Little)
+    }
+}
+
+// convert a permutation state to arkworks
+pub fn state_to_ark(state: [Fr; 3]) -> [ark_bls12_381::Fr; 3] {
+    state.map(|word| ArkFr::from(word).0)
+}
+
+// convert a permutation state from arkworks
+pub fn state_from_ark(state: [ark_bls12_381::Fr; 3]) -> Result<[Fr; 3], BenchmarkError> {
+    let [s0, s1, s2] = state.map(|word| Fr::try_from(ArkFr(word)));
+    Ok([s0?, s1?, s2?])
+}
+
+// convert an instance vector (e.g. public outputs) to arkworks
+pub fn instance_to_ark(instance: &[Fr]) -> Vec<ark_bls12_381::Fr> {
+    instance.iter().map(|word| ArkFr::from(*word).0).collect()
+}
+
+// convert an instance vector from arkworks
+pub fn instance_from_ark(instance: &[ark_bls12_381::Fr]) -> Result<Vec<Fr>, BenchmarkError> {
+    instance.iter().map(|word| Fr::try_from(ArkFr(*word))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::Field;
+    use ff::Field as _;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::{RngCore, SeedableRng};
+
+    use crate::native::NativePermutation;
+    use crate::params::{ArcPosition, Poseidon, RescuePrime, SboxKind, field_modulus, poseidon_params, rescue_params};
+    use super::*;
+
+    // helper function for the MDS multiplication over the arkworks field, oriented like mds_mul_native
+    fn mds_mul_ark(mds: &[[ark_bls12_381::Fr; 3]; 3], state: &[ark_bls12_381::Fr; 3]) -> [ark_bls12_381::Fr; 3] {
+        std::array::from_fn(|i| state[0] * mds[i][0] + state[1] * mds[i][1] + state[2] * mds[i][2])
+    }
+
+    // helper function for the Poseidon permutation re-implemented over the arkworks field from the converted constants
+    fn poseidon_ark(params: &Poseidon<Fr>, mut state: [ark_bls12_381::Fr; 3]) -> [ark_bls12_381::Fr; 3] {
+        let mds = params.mds.map(state_to_ark);
+        let round_constants = instance_to_ark(&params.round_constants);
+        let sbox = |a: ark_bls12_381::Fr| match params.sbox {
+            SboxKind::Power(alpha) => a.pow([alpha]),
+            SboxKind::Inverse => a.inverse().unwrap_or_default()
+        };
+
+        let add_constants = |state: &mut [ark_bls12_381::Fr; 3], round: usize| {
+            for (i, word) in state.iter_mut().enumerate() {
+                *word += round_constants[3 * round + i];
+            }
+        };
+
+        for round in 0..params.full_rounds + params.partial_rounds {
+            if params.arc == ArcPosition::BeforeSbox {
+                add_constants(&mut state, round);
+            }
+            if params.is_full_round(round) {
+                state = state.map(sbox);
+            } else {
+                state[0] = sbox(state[0]);
+            }
+            if params.arc == ArcPosition::AfterSbox {
+                add_constants(&mut state, round);
+            }
+            state = mds_mul_ark(&mds, &state);
+        }
+
+        state
+    }
+
+    // helper function for the Rescue-Prime permutation re-implemented over the arkworks field from the converted constants
+    fn rescue_ark(params: &RescuePrime<Fr>, mut state: [ark_bls12_381::Fr; 3]) -> [ark_bls12_381::Fr; 3] {
+        let mds = params.mds.map(state_to_ark);
+        let round_constants = instance_to_ark(&params.round_constants);
+        let alpha_inv = params.alpha_inv.to_u64_digits();
+
+        for round in 0..params.rounds {
+            state = state.map(|a| a.pow([params.alpha]));
+            state = mds_mul_ark(&mds, &state);
+            state = std::array::from_fn(|i| state[i] + round_constants[6 * round + i]);
+            state = state.map(|a| a.pow(&alpha_inv));
+            state = mds_mul_ark(&mds, &state);
+            state = std::array::from_fn(|i| state[i] + round_constants[6 * round + 3 + i]);
+        }
+
+        state
+    }
+
+    // the arkworks conversions: both fields have the same modulus, seeded random elements and the
+    // edge values round-trip both ways, and both permutations re-implemented over arkworks from the converted constants
+    // give the digests of the native permutations here
+    #[test]
+    fn ark_interop() -> Result<(), BenchmarkError> {
+        assert_eq!(BigUint::from(ark_bls12_381::Fr::MODULUS), field_modulus::<Fr>());
+
+        let mut rng = ChaCha20Rng::seed_from_u64(1090);
+        let mut values: Vec<Fr> = vec![Fr::ZERO, Fr::ONE, -Fr::ONE];
+        values.extend((0..64).map(|_| Fr::random(&mut rng)));
+        for value in &values {
+            assert_eq!(Fr::try_from(ArkFr::from(*value))?, *value);
+        }
+        for _ in 0..64 {
+            let mut bytes = [0u8; 64];
+            rng.fill_bytes(&mut bytes);
+            let value = ark_bls12_381::Fr::from_le_bytes_mod_order(&bytes);
+            assert_eq!(ArkFr::from(Fr::try_from(ArkFr(value))?).0, value);
+        }
+        assert_eq!(instance_from_ark(&instance_to_ark(&values))?, values);
+
+        let poseidon = poseidon_params::<Fr>()?;
+        let rescue = rescue_params::<Fr>()?;
+        for state in [[Fr::from(0), Fr::from(1), Fr::from(2)], [values[3], values[4], values[5]]] {
+            assert_eq!(state_from_ark(poseidon_ark(&poseidon, state_to_ark(state)))?, poseidon.permute_native(state));
+            assert_eq!(state_from_ark(rescue_ark(&rescue, state_to_ark(state)))?, rescue.permute_native(state));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use blstrs::Scalar;
+use ff::PrimeField;
+use generic_array::typenum::U2;
+use halo2curves::bls12381::Fr;
+use neptune::poseidon::{HashMode, PoseidonConstants};
+use neptune::Poseidon as NeptunePoseidon;
+
+use crate::error::BenchmarkError;
+use crate::params::{Endianness, fe_from_bytes};
+
+// a blstrs BLS12-381 scalar, neptune's field type, converted to and from halo2curves' through canonical little-endian
+// bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NeptuneFr(pub Scalar);
+
+// both fields have the same modulus and little-endian representations, so a canonical halo2curves element is a
+// canonical blstrs one
+impl From<Fr> for NeptuneFr {
+    fn from(value: Fr) -> Self {
+        NeptuneFr(Scalar::from_repr(value.to_repr().into()).expect("the moduli are equal"))
+    }
+}
+
+// the bytes are checked against the modulus like any other input
+impl TryFrom<NeptuneFr> for Fr {
+    type Error = BenchmarkError;
+
+    fn try_from(value: NeptuneFr) -> Result<Self, Self::Error> {
+        fe_from_bytes(&value.0.to_repr(), Endianness::Little)
+    }
+}
+
+// digest of [a, b] under neptune itself, in its reference (unoptimized) hash mode and in the optimized default, which
+// must agree
+pub fn neptune_digest(inputs: [Fr; 2]) -> Result<Fr, BenchmarkError> {
+    let constants = PoseidonConstants::<Scalar, U2>::new();
+    let preimage = inputs.map(|word| NeptuneFr::from(word).0);
+    let correct = NeptunePoseidon::<Scalar, U2>::new_with_preimage(&preimage, &constants).hash_in_mode(HashMode::Correct);
+    let optimized = NeptunePoseidon::<Scalar, U2>::new_with_preimage(&preimage, &constants).hash();
+    assert_eq!(correct, optimized, "neptune's hash modes disagree");
+    Fr::try_from(NeptuneFr(correct))
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use num_bigint::BigUint;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use crate::constants::{POSEIDON_BLS381_T3_NEPTUNE, preset};
+    use crate::native::NativePermutation;
+    use crate::params::field_modulus;
+    use crate::presets::{NEPTUNE_ARITY_2_TAG, bls381_neptune, neptune_hash};
+    use super::*;
+
+    // the neptune preset against neptune itself: the conversions round-trip, neptune's generated
+    // constants for arity 2 are the preset's (round numbers, round constants, MDS matrix and domain tag), and the native
+    // digests here match neptune's for fixed and seeded random inputs, under the preset and under its
+    // constants-after-S-box form
+    #[test]
+    fn neptune_interop() -> Result<(), BenchmarkError> {
+        assert_eq!(BigUint::from_bytes_le((-Scalar::ONE).to_repr().as_ref()) + 1u8, field_modulus::<Fr>());
+        let mut rng = ChaCha20Rng::seed_from_u64(1141);
+        let mut values: Vec<Fr> = vec![Fr::ZERO, Fr::ONE, -Fr::ONE];
+        values.extend((0..16).map(|_| Fr::random(&mut rng)));
+        for value in &values {
+            assert_eq!(Fr::try_from(NeptuneFr::from(*value))?, *value);
+        }
+
+        let constants = PoseidonConstants::<Scalar, U2>::new();
+        let neptune_preset = preset(POSEIDON_BLS381_T3_NEPTUNE)?;
+        assert_eq!((constants.full_rounds, constants.partial_rounds), (neptune_preset.full_rounds, neptune_preset.partial_rounds));
+        assert_eq!(constants.domain_tag, Scalar::from(NEPTUNE_ARITY_2_TAG));
+        let round_constants = constants.round_constants.as_ref().expect("neptune keeps the unoptimized constants")
+            .iter().map(|word| Fr::try_from(NeptuneFr(*word)))
+            .collect::<Result<Vec<Fr>, BenchmarkError>>()?;
+        assert_eq!(round_constants, neptune_preset.round_constants::<Fr>()?);
+        let mds = constants.mds_matrices.m.iter()
+            .map(|row| row.iter().map(|word| Fr::try_from(NeptuneFr(*word))).collect::<Result<Vec<Fr>, BenchmarkError>>())
+            .collect::<Result<Vec<_>, BenchmarkError>>()?;
+        assert_eq!(mds, neptune_preset.mds::<Fr>()?.map(|row| row.to_vec()).to_vec());
+
+        let params = bls381_neptune()?;
+        let mut inputs: Vec<[Fr; 2]> = vec![[Fr::ZERO, Fr::ZERO], [Fr::ZERO, Fr::ONE], [Fr::from(12345), Fr::from(67890)], [-Fr::ONE, -Fr::ONE]];
+        inputs.extend(values.chunks_exact(2).map(|pair| [pair[0], pair[1]]));
+        // neptune's optimized mode adds the first round constants to the preimage and the rest after the S-box, as the
+        // constants-after-S-box form does
+        let (first, folded) = params.after_sbox_form()?;
+        for pair in inputs {
+            let digest = neptune_digest(pair)?;
+            assert_eq!(neptune_hash(&params, pair)?, digest, "neptune digest of {:?}", pair);
+            let state = [Fr::from(NEPTUNE_ARITY_2_TAG), pair[0], pair[1]];
+            assert_eq!(folded.permute_native(std::array::from_fn(|i| state[i] + first[i]))[1], digest, "folded digest of {:?}", pair);
+        }
+
+        Ok(())
+    }
+}
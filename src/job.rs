@@ -0,0 +1,243 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use halo2_proofs::{
+    circuit::Value,
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, keygen_pk, keygen_vk},
+};
+use serde::Serialize;
+
+use crate::chip::PermutationChip;
+use crate::circuits::PreimageCircuit;
+use crate::clock::Instant;
+use crate::error::BenchmarkError;
+use crate::instances::{Digest, PublicInputs};
+use crate::proving::{ProvingRng, create_ipa_proof, verify_ipa_proof};
+use crate::source::{active_source, set_parameter_source};
+use crate::srs::commitment_params;
+use crate::synthesis::{check_k, with_round_hook};
+use crate::transcript::TranscriptKind;
+
+// Prover jobs: keygen, proving and verification of a circuit over Pasta on a worker thread, for a caller that cannot
+// block on a proof (a service handling requests). The job reports its phase and, while the phase synthesizes the
+// circuit, how many of its chip rounds are done: the Poseidon and Rescue-Prime chips pass a round checkpoint
+// (synthesis::round_checkpoint) before every round. A layout pass counts the rounds of one synthesis first; keygen
+// synthesizes twice (verifying and proving key) and the prover once, and the prover's FFTs and commitments after its
+// synthesis, like the verification, have no estimate. Cancellation is cooperative: the flag is read at the next round
+// checkpoint, where it fails synthesis, or at the next phase, and the job returns BenchmarkError::Cancelled with
+// everything it allocated dropped along with its thread.
+
+// what a job is doing
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobPhase {
+    // spawned, the worker thread has not started
+    Queued,
+    // the layout pass that checks k and counts the rounds of one synthesis
+    Layout,
+    // commitment parameters, verifying key and proving key
+    Keygen,
+    // witness synthesis and the prover
+    Proving,
+    // the proof checked against its public inputs
+    Verifying,
+    // finished, with a proof or an error
+    Done
+}
+
+impl fmt::Display for JobPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JobPhase::Queued => "queued",
+            JobPhase::Layout => "layout",
+            JobPhase::Keygen => "keygen",
+            JobPhase::Proving => "proving",
+            JobPhase::Verifying => "verifying",
+            JobPhase::Done => "done"
+        })
+    }
+}
+
+// a job's phase, with the share of the phase's rounds synthesized so far in percent when the phase synthesizes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct JobProgress {
+    pub phase: JobPhase,
+    pub percent: Option<u8>
+}
+
+// e.g. `keygen 40%`, or `verifying`
+impl fmt::Display for JobProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.percent {
+            Some(percent) => write!(f, "{} {}%", self.phase, percent),
+            None => write!(f, "{}", self.phase)
+        }
+    }
+}
+
+// what a job proves: `circuit` at k against its instance columns, with the prover's randomness seeded as --seed does
+pub struct ProofRequest<C> {
+    pub label: String,
+    pub circuit: C,
+    pub public_inputs: PublicInputs<Fp>,
+    pub k: u32,
+    pub seed: Option<u64>
+}
+
+// a proof a job created and verified, with the statement it proves and its timings
+#[derive(Clone, Debug)]
+pub struct ProofArtifact {
+    pub label: String,
+    pub k: u32,
+    pub public_inputs: PublicInputs<Fp>,
+    pub proof: Vec<u8>,
+    pub proving_ms: u128,
+    pub verification_ms: u128
+}
+
+// state shared by a job's handle and its worker thread
+struct JobState {
+    label: String,
+    cancelled: AtomicBool,
+    phase: Mutex<JobPhase>,
+    // rounds synthesized in the current phase, and the rounds it synthesizes in all (0 without an estimate)
+    rounds: AtomicUsize,
+    total: AtomicUsize,
+    on_progress: Box<dyn Fn(JobProgress) + Send + Sync>
+}
+
+impl JobState {
+    fn progress(&self) -> JobProgress {
+        let total = self.total.load(Ordering::Relaxed);
+        let percent = (total > 0).then(|| (100 * self.rounds.load(Ordering::Relaxed) / total).min(100) as u8);
+        JobProgress { phase: *self.phase.lock().unwrap(), percent }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // start `phase`, which synthesizes `total` rounds, unless the job has been cancelled
+    fn enter(&self, phase: JobPhase, total: usize) -> Result<(), BenchmarkError> {
+        if self.is_cancelled() {
+            return Err(BenchmarkError::Cancelled(self.label.clone()));
+        }
+        *self.phase.lock().unwrap() = phase;
+        self.rounds.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+        (self.on_progress)(self.progress());
+        Ok(())
+    }
+
+    // the round checkpoint: count the round, report it, and ask synthesis to stop if the job has been cancelled
+    fn round(&self) -> bool {
+        self.rounds.fetch_add(1, Ordering::Relaxed);
+        (self.on_progress)(self.progress());
+        !self.is_cancelled()
+    }
+}
+
+// handle to a proof being created on a worker thread
+pub struct ProverJob {
+    state: Arc<JobState>,
+    handle: JoinHandle<Result<ProofArtifact, BenchmarkError>>
+}
+
+impl ProverJob {
+    // start the job on a new thread; the parameter source active here is the job's
+    pub fn spawn<C: Circuit<Fp> + Send + 'static>(request: ProofRequest<C>) -> Self {
+        Self::spawn_with(request, |_| {})
+    }
+
+    // ProverJob::spawn with `on_progress` called on the worker thread at every phase and round, e.g. to push progress
+    // to a client; the job waits for it to return
+    pub fn spawn_with<C: Circuit<Fp> + Send + 'static>(request: ProofRequest<C>, on_progress: impl Fn(JobProgress) + Send + Sync + 'static) -> Self {
+        let state = Arc::new(JobState {
+            label: request.label.clone(),
+            cancelled: AtomicBool::new(false),
+            phase: Mutex::new(JobPhase::Queued),
+            rounds: AtomicUsize::new(0),
+            total: AtomicUsize::new(0),
+            on_progress: Box::new(on_progress)
+        });
+        let worker = state.clone();
+        let source = active_source();
+        let handle = std::thread::spawn(move || {
+            let _parameters = set_parameter_source(source);
+            let hook = worker.clone();
+            let result = with_round_hook(move || hook.round(), || run_job(&worker, request));
+            // whatever failed after the cancellation failed because of it
+            let result = match result {
+                Err(_) if worker.is_cancelled() => Err(BenchmarkError::Cancelled(worker.label.clone())),
+                result => result
+            };
+            *worker.phase.lock().unwrap() = JobPhase::Done;
+            worker.total.store(0, Ordering::Relaxed);
+            (worker.on_progress)(worker.progress());
+            result
+        });
+
+        ProverJob { state, handle }
+    }
+
+    pub fn progress(&self) -> JobProgress {
+        self.state.progress()
+    }
+
+    // ask the job to stop at its next round checkpoint or phase
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    // wait for the job and take its proof, or its error; a panic on the worker thread is resumed here
+    pub fn join(self) -> Result<ProofArtifact, BenchmarkError> {
+        self.handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+// helper function for the worker thread: layout, keygen, proving and verification of the request, one phase each
+fn run_job<C: Circuit<Fp>>(state: &JobState, request: ProofRequest<C>) -> Result<ProofArtifact, BenchmarkError> {
+    let ProofRequest { label, circuit, public_inputs, k, seed } = request;
+    let instance = public_inputs.as_slices();
+
+    state.enter(JobPhase::Layout, 0)?;
+    check_k(&label, k, &circuit)?;
+    let rounds = state.rounds.load(Ordering::Relaxed);
+
+    state.enter(JobPhase::Keygen, 2 * rounds)?;
+    let params = commitment_params(k)?;
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+
+    state.enter(JobPhase::Proving, rounds)?;
+    let start = Instant::now();
+    let proof = create_ipa_proof(&params, &pk, &circuit, &instance, ProvingRng::new(seed), TranscriptKind::Blake2b)?;
+    let proving_ms = start.elapsed().as_millis();
+
+    state.enter(JobPhase::Verifying, 0)?;
+    let start = Instant::now();
+    verify_ipa_proof(&params, pk.get_vk(), &proof, &instance, TranscriptKind::Blake2b)?;
+    let verification_ms = start.elapsed().as_millis();
+
+    Ok(ProofArtifact { label, k, public_inputs, proof, proving_ms, verification_ms })
+}
+
+// the preimage statement of chip P on `inputs` as a job request, its digest over Pasta public
+pub(crate) fn preimage_request<P: PermutationChip<Fp> + Send + 'static>(inputs: [Fp; 3], k: u32, seed: Option<u64>) -> Result<ProofRequest<PreimageCircuit<Fp, P>>, BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let digest = Digest([chip.params().permute_native(inputs)[0]]);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    Ok(ProofRequest {
+        label: format!("{} preimage", P::NAME),
+        circuit: PreimageCircuit { s0, s1, s2, _marker: std::marker::PhantomData },
+        public_inputs: PublicInputs::digest_only(digest),
+        k,
+        seed
+    })
+}
@@ -0,0 +1,175 @@
+/*
+* Benchmarks
+*  - Number of rows
+*  - Number of gates enabled and number of constraints per gate
+*  - MockProver runtime
+*  - Number of round constants
+*  - Number of rounds
+*  - Runtime for one round
+*  - Advice cell count
+*  - Total cell count
+*  - Maximum degree
+*/
+
+
+// Cargo features: `poseidon` and `rescue` compile one chip each, `sponge` and `merkle` the constructions over them, and
+// `bench-cli` the command line tool with everything it measures, which needs all four. The benchmark circuits are
+// crate-private and only the tool reaches most of them, so without it they are allowed to go unused.
+#![cfg_attr(not(feature = "bench-cli"), allow(dead_code))]
+
+#[cfg(feature = "bench-cli")]
+mod aggregation;
+#[cfg(feature = "bench-cli")]
+mod analysis;
+#[cfg(feature = "bench-cli")]
+mod anemoi;
+#[cfg(feature = "bench-cli")]
+mod audit;
+#[cfg(feature = "bench-cli")]
+mod bars;
+#[cfg(feature = "bench-cli")]
+mod bench;
+mod chip;
+mod circuits;
+mod clock;
+pub mod constants;
+#[cfg(feature = "bench-cli")]
+mod doctor;
+mod error;
+#[cfg(feature = "bench-cli")]
+mod failures;
+mod gates;
+mod generate;
+#[cfg(feature = "sponge")]
+pub mod hash;
+#[cfg(feature = "bench-cli")]
+mod inputs;
+mod instances;
+#[cfg(feature = "bench-cli")]
+mod job;
+#[cfg(any(feature = "ark", feature = "neptune"))]
+pub mod interop;
+#[cfg(feature = "bench-cli")]
+mod lint;
+#[cfg(feature = "bench-cli")]
+mod memory;
+mod native;
+mod params;
+#[cfg(feature = "poseidon")]
+mod poseidon;
+pub mod prelude;
+mod presets;
+#[cfg(feature = "bench-cli")]
+mod proving;
+#[cfg(feature = "bench-cli")]
+mod report;
+#[cfg(feature = "rescue")]
+mod rescue;
+#[cfg(feature = "bench-cli")]
+mod single_gate;
+#[cfg(feature = "bench-cli")]
+mod snapshot;
+mod source;
+#[cfg(feature = "sponge")]
+mod sponge;
+#[cfg(feature = "bench-cli")]
+mod srs;
+#[cfg(feature = "bench-cli")]
+mod stress;
+#[cfg(feature = "bench-cli")]
+mod sweep;
+mod synthesis;
+#[cfg(feature = "bench-cli")]
+mod target;
+#[cfg(feature = "bench-cli")]
+mod transcript;
+#[cfg(feature = "bench-cli")]
+mod vectors;
+#[cfg(all(target_arch = "wasm32", any(feature = "poseidon", feature = "rescue")))]
+mod wasm;
+#[cfg(feature = "merkle")]
+mod wide;
+mod witness;
+
+#[cfg(feature = "bench-cli")]
+pub use analysis::{ColumnSummary, ConstraintSystemSummary, GateSummary, ProverDomain, analyze};
+#[cfg(feature = "bench-cli")]
+pub use bench::{compose_instances, expected_instances, permutation_report, public_message_instances, run_benchmark, run_sweep};
+pub use chip::{Number, PermutationChip, PermutationInstructions, StateWord, UtilsInstructions};
+pub use circuits::{FloorPlannerKind, OutputMode};
+#[cfg(feature = "poseidon")]
+pub use circuits::PoseidonCircuit;
+#[cfg(feature = "rescue")]
+pub use circuits::RescueCircuit;
+pub use error::BenchmarkError;
+#[cfg(feature = "bench-cli")]
+pub use failures::{FailureSummary, summarize_failures};
+pub use gates::CircuitParameters;
+pub use instances::{Digest, PublicInputs};
+#[cfg(feature = "bench-cli")]
+pub use job::{JobPhase, JobProgress, ProofArtifact, ProofRequest, ProverJob};
+pub use native::NativePermutation;
+pub use params::{ArcPosition, Endianness, Poseidon, PoseidonBuilder, RescuePrime, RescuePrimeBuilder, SboxKind, fe_from_hex, fe_to_hex, poseidon_params,
+    rescue_params, state_from_bytes};
+#[cfg(feature = "poseidon")]
+pub use poseidon::{PoseidonChip, PoseidonChipConfig};
+#[cfg(feature = "bench-cli")]
+pub use proving::{Backend, ProofScheme};
+#[cfg(feature = "bench-cli")]
+pub use report::{BenchmarkReport, Repeat, ReportOutput};
+#[cfg(feature = "rescue")]
+pub use rescue::{RescueChip, RescueChipConfig};
+pub use source::{ParameterSource, ParameterSourceGuard, set_parameter_source};
+#[cfg(feature = "sponge")]
+pub use sponge::{BYTES_PER_ELEMENT, PublicMessageConfig, Sponge, SpongeConfig, SpongePadding, configure_public_message, configure_sponge, hash_bytes_native, pack_bytes, public_message_column};
+pub use synthesis::CheckMode;
+#[cfg(feature = "bench-cli")]
+pub use target::{PermutationArg, PermutationKind};
+#[cfg(feature = "bench-cli")]
+pub use transcript::{PoseidonChallenge, PoseidonRead, PoseidonWrite, TranscriptKind};
+
+// the subcommands of the command line tool, public for main.rs only and outside the semver promise of the prelude
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use aggregation::{run_verify_batch, write_proofs};
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use audit::run_audit;
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use bench::{ConstantsFormat, export_parameters, prove_preimage, run_batch, run_bytes, run_demo_membership, run_merkle, run_public_message, run_scaling, run_stream, run_transcript, show_preset};
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use doctor::run_doctor;
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use inputs::hash_inputs_file;
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use memory::{PeakAllocator, low_memory_threads, with_low_memory};
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use proving::with_threads;
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use srs::{generate_srs, use_srs_file};
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use stress::run_stress;
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use vectors::{write_fixtures, write_vectors};
+
+// the cases the integration tests run, public for tests/ only and outside the semver promise as well
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use memory::live_heap;
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use single_gate::{GateWitness, SingleGate};
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use snapshot::render_snapshots;
+#[cfg(feature = "bench-cli")]
+#[doc(hidden)]
+pub use vectors::{check_circom_vectors, check_neptune_vectors, check_trace_fixture, check_vectors};
@@ -0,0 +1,152 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use ff::PrimeField;
+use halo2_proofs::{
+    plonk::{Circuit, ConstraintSystem},
+};
+use serde::Serialize;
+
+use crate::error::BenchmarkError;
+use crate::gates::{record_configure, track_constants};
+use crate::synthesis::selector_rows;
+
+// Selector overlap lint: after synthesis, every row with more than one selector enabled is listed with the gates its
+// selectors switch on. A layout bug often shows up as two gates on one row whose next-row expectations conflict (e.g.
+// the MDS gate and a round gate both constraining Rotation::next from the same row), which MockProver only reports
+// when the witness happens to break one of them. The combinations a chip intends are declared in
+// PermutationChip::SELECTOR_OVERLAPS; none of the chips here fuse gates on a row, so every overlap is a failure.
+
+// structure for one row with more than one selector enabled
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct SelectorOverlap {
+    pub(crate) row: usize,
+    // the gates of each enabled selector, sorted; a selector no gate queries is named selector[i]
+    pub(crate) gates: Vec<String>,
+    // whether the chip declares this combination
+    pub(crate) allowed: bool
+}
+
+impl fmt::Display for SelectorOverlap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: {} ({})", self.row, self.gates.join(" + "), if self.allowed { "allowed" } else { "not allowed" })
+    }
+}
+
+// helper function to configure a circuit, synthesize it and list the rows with more than one selector enabled, each
+// checked against the declared combinations `allowed`. The selectors are the ones the circuit's chips allocate through
+// allocate_selector, numbered in allocation order; a selector is named by the gates that query it, joined by '/' when
+// it switches on several, and a combination is allowed when its gates are exactly one of the declared sets
+pub(crate) fn selector_overlaps<F: PrimeField, C: Circuit<F>>(circuit: &C, allowed: &[&[&str]]) -> Result<Vec<SelectorOverlap>, BenchmarkError> {
+    let mut meta = ConstraintSystem::default();
+    let mut configured = None;
+    let (gates, allocated) = record_configure(|| {
+        configured = Some(track_constants(|| C::configure(&mut meta)));
+    });
+    let (config, constants) = configured.expect("configure ran");
+    let rows = selector_rows(config, constants, circuit)?;
+
+    let mut names: BTreeMap<usize, Vec<&'static str>> = BTreeMap::new();
+    for gate in &gates {
+        for selector in &gate.selectors {
+            names.entry(*selector).or_default().push(gate.name);
+        }
+    }
+    let allowed: Vec<BTreeSet<&str>> = allowed.iter().map(|gates| gates.iter().copied().collect()).collect();
+
+    let mut overlaps = Vec::new();
+    for (row, enabled) in rows.into_iter().filter(|(_, enabled)| enabled.len() > 1) {
+        let selectors = enabled.iter()
+            .map(|selector| allocated.iter().position(|allocated| allocated == selector).ok_or_else(|| BenchmarkError::VerificationFailed(
+                format!("row {} enables a selector the circuit did not allocate through allocate_selector", row)
+            )))
+            .collect::<Result<BTreeSet<usize>, _>>()?;
+        let combination: BTreeSet<&str> = selectors.iter().flat_map(|selector| names.get(selector).into_iter().flatten().copied()).collect();
+        let mut labels: Vec<String> = selectors.iter()
+            .map(|selector| names.get(selector).map_or_else(|| format!("selector[{}]", selector), |gates| gates.join("/")))
+            .collect();
+        labels.sort();
+        overlaps.push(SelectorOverlap { row, gates: labels, allowed: allowed.contains(&combination) });
+    }
+    Ok(overlaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Column, Error, Selector},
+        poly::Rotation,
+    };
+    use halo2curves::bls12381::Fr;
+
+    use super::*;
+    use crate::chip::PermutationChip;
+    use crate::circuits::{ChainCircuit, DualCircuit, OutputMode};
+    use crate::gates::{allocate_selector, create_gate};
+    use crate::poseidon::PoseidonChip;
+    use crate::rescue::RescueChip;
+    use crate::target::PermutationKind;
+
+    // a circuit with two gates whose selectors are both enabled on row 0, and only the first on row 1; the gates are never
+    // checked, only where their selectors are enabled
+    #[derive(Default)]
+    struct OverlapCircuit<F: PrimeField>(PhantomData<F>);
+
+    impl<F: PrimeField> Circuit<F> for OverlapCircuit<F> {
+        type Config = (Column<Advice>, Selector, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (advice, s_first, s_second) = (meta.advice_column(), allocate_selector(meta), allocate_selector(meta));
+            create_gate(meta, "Overlap_first_gate", |meta| {
+                vec![meta.query_selector(s_first) * meta.query_advice(advice, Rotation::next())]
+            });
+            create_gate(meta, "Overlap_second_gate", |meta| {
+                vec![meta.query_selector(s_second) * (meta.query_advice(advice, Rotation::next()) - meta.query_advice(advice, Rotation::cur()))]
+            });
+            (advice, s_first, s_second)
+        }
+
+        fn synthesize(&self, (advice, s_first, s_second): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            layouter.assign_region(|| "overlap", |mut region| {
+                s_first.enable(&mut region, 0)?;
+                s_second.enable(&mut region, 0)?;
+                s_first.enable(&mut region, 1)?;
+                for row in 0..3 {
+                    region.assign_advice(|| "word", advice, row, || Value::known(F::ZERO))?;
+                }
+                Ok(())
+            })
+        }
+    }
+
+    // the selector overlap lint: the permutation circuits of every registered kind, both
+    // permutations over shared columns and a chain of them enable at most one selector per row, and a circuit enabling
+    // two on one row is reported, allowed only once the combination is declared
+    #[test]
+    fn overlapping_selectors() -> Result<(), BenchmarkError> {
+        for kind in PermutationKind::all() {
+            let overlaps = kind.target().selector_overlaps(OutputMode::AllState)?;
+            if let Some(overlap) = overlaps.first() {
+                return Err(BenchmarkError::VerificationFailed(format!("{} circuit enables overlapping selectors, {}", kind, overlap)));
+            }
+        }
+        let both: Vec<&[&str]> = PoseidonChip::<Fr>::SELECTOR_OVERLAPS.iter().chain(RescueChip::<Fr>::SELECTOR_OVERLAPS).copied().collect();
+        assert_eq!(selector_overlaps(&DualCircuit::<Fr>::default(), &both)?, []);
+        assert_eq!(selector_overlaps(&ChainCircuit::<Fr> { inputs: [Value::unknown(); 3], poseidon: 2, rescue: 2 }, &both)?, []);
+
+        let overlaps = selector_overlaps(&OverlapCircuit::<Fr>::default(), &[])?;
+        assert_eq!(overlaps, [SelectorOverlap { row: 0, gates: vec!["Overlap_first_gate".to_string(), "Overlap_second_gate".to_string()], allowed: false }]);
+        assert_eq!(overlaps[0].to_string(), "row 0: Overlap_first_gate + Overlap_second_gate (not allowed)");
+        let declared = selector_overlaps(&OverlapCircuit::<Fr>::default(), &[&["Overlap_second_gate", "Overlap_first_gate"]])?;
+        assert!(declared.len() == 1 && declared[0].allowed);
+        println!("selector overlaps: none in the permutation, dual and chain circuits; an undeclared overlap is reported with its row and gates");
+
+        Ok(())
+    }
+}
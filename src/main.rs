@@ -1,1092 +1,364 @@
-use std::marker::PhantomData;
-use ff::PrimeField;
-use num_bigint::BigUint;
-use std::fmt::Debug;
-use std::str::FromStr;
-use std::time::{Instant, Duration};
-use halo2_proofs::{
-    circuit::{AssignedCell, Region, Chip, Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Fixed, Circuit, Column, ConstraintSystem, Error, Instance, Selector, Expression},
-    poly::Rotation,
-};
-
-/*
-* Benchmarks
-*  - Number of rows
-*  - Number of gates enabled and number of constraints per gate
-*  - MockProver runtime
-*  - Number of round constants
-*  - Number of rounds
-*  - Runtime for one round
-*  - Advice cell count
-*  - Total cell count
-*  - Maximum degree
-*/
-
-// Poseidon round constants
-const ROUND_CONSTANTS_PS: [&str; 195] = ["48991097081732275468845314168021420565497297775988823234113406403095118809216", "38385660029618165285848698857635215143135976511856402182142757680787979296154", "45664917788634056160947231182803089169570746657219074370482409200042991921246", "46611823467219910333349433978991031443945697128435279755908258896090196676828", "21239555800391983336673016232252577145979304597102502292785557024177155115319", "5444549814002252718699361548642546874417220826495496552290417094191494299797", "6120941817780228594851185625662354154126315032538247033968198498911791651970", "23268934541565483112488314239282439244757346303484537549209002605218913236536", "34778900561716047730386110499058136122597669775051061603711724688203374984731", "11866412958831620887953860204795878894545618212709331023611019011793447488176", "1292810553955081089139103033821163176614817808018762694232693357405135340213", "29829440149074940820671559824872937980763748927491238614065138142835318453671", "43007325278312980663982452106946226844964622384017700838855297379677047113384", "6207852559847946300667836829798951848361581084433525098597857899536657157132", "51263844854419207560514475863120683772532929850629546992690510884221364990253", "47537207485065031976374469967696134772574834313568026823983918780308518394040", "2221931791899303960239149702171682649773262449196140787838362753706579104592", "39456839086017037141295863080128693714705835125922448198802062180577619415688", "7307684192235537965831376311417883513796535701244096178785218530839409056523", "40363790847223872255995860144037894400158879326818322790255787884037990480527", "46370977865329511267956842930057959446221524060145738210680245530954549945015", "31963375456062604704511762940421329756212766442452555529101241339674782334039", "14931035994999669353073307088521670981122374648927581516990615825314462827897", "9146050314741225622437907700594105481623623087635695897868792721147700541623", "43028866523328004770172322384235815492694573248368601737155468843525625413279", "10642771813466087799681476709295362996886361934733270333728358675267521442184", "26204626472182247586446753357603232226235570940686295317661191583409532523578", "51764778305842182544341507127328333397682018984536762517144144495830254727692", "46323013798997081811959707047808149003166619133464450127989691277775183404349", "5482714761779403197336605367697000529513289823583027739458069397684408687717", "12801259943830582826718901632357112368256632783422449824889858551937326401170", "24705221370028061177410670936487461711735994635988936070623351799675117594850", "34818354068777339891091714877681898548352650337240481539567373888981659308099", "35437981511765462742605234803376772682840664204821301764084738573774616215109", "1433523918194521021731556457516832465819757187635645935518277720319249889445", "1786444825311968572352002116054188762971225383128313206702203805257523693888", "22232073076796622550494050910209988454596433174206874696362037700514082492276", "24042430109235922611027968831657325520072553641473321784508698720854180658031", "45406805567398680921065452923276055166961588153660261520529196040913487916279", "35053262861048825411061280559553895536192334830763062477277235807515959383150", "25108964803188800737437394246442073858261740146181095550988111856238954490309", "35192650141137106058577418514209092904214762437910434967540336800650620041958", "34220944794619662782589792809938215078980533657269200933482014763836254210880", "39884393792242132075258602070541114557272278571033974158755307717930033808078", "6528627567246138898338135471584665860403024864125846353758054588554049365178", "26135348890537017135058266369936506677345001674530050056494732502158573534651", "45940975099728729872716617510434185869788979733816569378448209603957649084497", "15421094974171181812057105309783852016087843260648209913425190920580878315912", "17821536801502538623431403481143359660601434134694528982404802873816360858943", "8010729838943058740614807905113741378835761166137481371357965047712306801123", "18699215163509883263304393673283276029620709331747651039747044003384506899917", "37045787943638220002917633921716309877792707850558591835874081145770158399128", "21575637935417645110089037900895429146838845113516284564671508366546944971174", "1788789771738709712587591109966362080868778924904243569200231458308784197447", "31893695366599021197812621371715665903315747385247436549810717167321695484766", "51153400179598348220410722401172031495931771158209082356586940118519763307990", "27065341612806387486757726552834268222391812301897865130062594135449450311205", "21631377794423816098233500204394685009343254816615902551641496756763638503963", "48126155452550090941025807356211843589751116110477652511672279566428926247148", "41945332685105951593851845839403181725987901258063429769257339995392450728766", "24296067579767080403247766323431204628341605710487447431323947636125286730412", "15881178462681378844988252603563609691162651204658664856493588769950563205407", "33027381395215663927148306470841421013404116814305740800948949823021554274098", "39278310473084767209787340524936392884387815060990743323143945308386189000820", "36914830105593239127583246606078015086694578878061417360363710472659792271157", "2471481831227881021689006198592503194795082772689986463565415296171852015386", "10133170919569185596470854926690039229735632740212998846069400800395437949818", "13713875128407368240685505357662717227751490836079655538057610707920043576169", "8342666644640774986634432327796294683569398370446186977217700283927741456745", "46601389125814748868096111624907238097032545985765609175268428943258314495300", "20955390743109511563797223108807741951396100480021156649651505770632943438749", "30784566406743698397200754777301033281231860349200935908047757137616877875074", "48343196439030272896030042717039190414055291776286919553358305329065060244544", "5454630884154432785537568532823077194524789618913833351503828005963129645447", "5929264687259766357446095238429932392315604113095822327000589827415320983004", "22075444908821639097706881947036304396835729534515628434816919715415538390017", "25941058816975140552446994550948593572939163972016393579803457030200129476973", "39776348414428957147819346902864822521632016599308432283712625663034427240337", "7416720880414633042939600412231360970614004283597614937824398530497243499212", "27759512177446113435859126093069895419463054324674208616122176370583357562941", "2693390255841122228782459820336527344026453452088174693463152401174043438469", "50367239350666539482528955684311280608817276753868085587890812549436189586564", "16174733649048109460569124327899128868049112853807486992529031028618670502840", "25032516686620026063532769674876936116496163673410980298313095252836905833243", "29144403930621998939944109351403497411548441156029659945515675350299265094466", "2003270776024057925128728348175382837282431082428047352264694823915738934597", "33363216671247018657387321397537436143187354110057266627888117938607035196831", "20203086474546098412356910533884833744816739556295954278635367853784856438617", "42960220771318412318176969631346524408076008158165832346168142557674200614679", "6311431299350400649257553117850994107778654765725553469026713480041524237057", "20356164198757608998824195662812920762417225019317083164408248459556033087792", "50934696509775059306730966013034554090787668615778167832259926621090584698298", "12540543785093585171832085015032615168496292565469198040103631290639480719638", "7087832377964131545651220267742883342179930832350845193376391176592931716961", "34984411233898940973869087861225504483500912780307024595154545196097892807889", "35766364158306764887416108757297765472332147961010533956614913565935878448984", "1765971701998656161486995693692800538505518481763639488010072221442068236951", "52296260704967533238281867983484652098827616020272035805695017707768629021210", "4935673489774322197628160742241883723281125866438378640636969542959380659457", "49493374663267588751846054378343301708694531580092984346087290317742537210902", "11234520985865325412206403291118519753189986845681526796638090446788348697652", "24240566602759984788029880030276085623682320979885122363103446030346976862554", "45173673056688650486124798353267048676515652881324846851443098010775612892322", "273339079894952168974065527137723282564095652951909656957160946114792896627", "4470325051640351957976738782642661997153601739638632363210829100051811744274", "35146154431885107533179241729875580217482204780231937987130147605583867466092", "5623976303155942456710618286519758761204923686926813378548021075733755166889", "24016465951530015578209275233668961482322584131459513288081598210134015257997", "17969920097176891022415687639709999939084490545645205326481661860931808113029", "45152206508674411747856285000257938228137174933577379726580072509850619926251", "38945634795250927360607537392732805897873100986379288027606175928019977509609", "32851666289693613044889283133849490343674968726730793059165429991055922454070", "31944620853700630151347751910587969550223781655480776781612692884058563662268", "25256966274452535017610572446887439115046074651331211781708168773655007778872", "9486939021502590608732001628331695421223550406038486802197261945175668785507", "39459143086960362426927505137137876218390935544236059938922871880000296175208", "31894450224048346260322339655447950546670422421242715439734122749915296243605", "26892539091318428420931225040417651442139701587930804697886023619431558542747", "2542844944718735302766446637202404427628413878092734865912744553984157161261", "31883859221346313107414474846252752604992097590133961842848913019073014153010", "51303361359653464050006771537341226976539604964205923399469614564706008834052", "51171387502764330562774849667033034283056080450385872897204773223645085369254", "7237091576916241695047293084522141336268656276386088021954481852199921973216", "25026554458962841467968682601680143746537618788336396538569095145280445662154", "16003513886762983460717836271035484656754723355114772159990269505739759600774", "20742179979178809796122395691368538694837598010689782796398715701486525085958", "44785832974715571208383539748048195425158621451201620091409304675643540484444", "40997683756979855969631370242290487603852436449608298499325558394715696204831", "24039577999618876159836452559464600377553684696598310542830185648570694947325", "214991500380221402745874275507138825943309188151683861156767017258335759518", "37648944229324812379904445632193391903358473357814505256571234492472677352375", "33262001091080721927187326829375441597312853742311915461357184164050334176171", "12889759088432190033171086881844675377815686311282488955569491035800531227592", "38889970121432469903433846063190552781925277874128916432889442865031400486457", "9686759546395317438502700818478291413888291261781927399197594299119600593872", "25228839869827315437841994432860023863461613471517457235105091951188556007171", "29251067411858749210993269168637503659802522399342640488863629751155422442084", "40912660681512278236165911366927220401330409827994264103091984300131586078341", "12796501909444494709088656380507035418412240267936921974592450125220369752821", "41489997591227135571666436387925119767986380278590920811343183082128452793080", "21497862265009693334292006570547451455021214638930393134366176167326805799325", "42759488993366187559528022270353477068325476435317366129099617149236057994173", "51812786435352958751631482409057671996557140765865434087196139886155873550638", "49668984917578993057336571483567900930503120626539459296975328351727319861276", "16647828498038646540925328826301561929374469486623027976723819473821480409681", "48148303340548214354795067112758174231010308760482898449349672592745234924387", "40514099213939369482769058963482609316155051560990264349668700968914554718236", "36567947302783543506732234132138195442155777559454242003814702099955749246290", "22396816925035795192842094319757131771178499933587237012855640944068186589937", "47761479716265566311036142819261705369735044145214592608213591050556455450430", "13277094590686127307617107451297268367321013828763858520220510028318248040673", "6273610774394348396010704017556554992266752629801490457323912355626787108751", "47394279615623798760617602748864924711531390489909756029248999925570450315302", "27952252793623580780344613559829677253211432925530630621608481053048520434744", "1683222943011658234228486862639342402730538635204883039431226239924268835592", "6849709550515639669397513895396396226183305237153796793058311861850242817732", "51524350017816629912679960748295545024593637560633508281874724597080573807830", "26590614177194547630006347843068513496427790322854759433492355517360208924714", "31548830001396651725711310298465958490865636855427227043617585502978053092924", "14291568473806392803367440164088272381690062239638560607879858528716058147676", "21146452903160991922099734199583866923318964586815062550024895407430164358523", "22961005724583382013438450487662047962072123198815308647967555251332825175693", "4752908842318626074338926279870993084957055641402767877988223199262408017438", "41544523600430331260332604149473035199994864893327747257504064038791086157408", "17323878296591859990733132832893641096022161936583121997952997880406237212813", "18014582744613086697405046476881081314871698927785490238333612330034405321202", "45325447140824171211209633262297712878556500592023247082629492785769121758434", "6192753434333002929210820794040779560623421075700800400752599138519650269040", "12937001546279985738495952624875312380127801527837660882855310431015537184413", "45991618799696924909840068913271150748052998998510820293768267349781597832497", "37441188106719457933929221474454571110916912448355945524409576665808556247872", "49875923679586708113406579244909793162425404239213510953269412337363307325571", "15051465698071304017966667797323113094420513709580063806706433232853573089040", "10338905189138871748742400929101717755982978259187828256039071250817040249017", "40261933448177008341539991920645739011692467645144896682394869561245899318641", "38346498339252184147870281431364733631809877281747451440216067081256241485418", "6209216396715641040468803949857167055175110420218294975303260728579180870134", "25923422290512595808420551575642237631007497169886590851128840338102194873726", "11953618934086915505672657493115697182858104796786340137294500949047339928290", "48506710952023206646326838201389789459004051035511888474426942257560405427104", "49584811575438811511092715559885015474424100729555178730940640525393341823572", "25222528947373923151054372702664425173210441980263130389325557963853429239320", "36212452941316997504575803214309342413443151488267891949906815090453746563323", "19548334171603533109137618032918088438321356008712800140019849908969476369140", "13369714008256347363334888026585995433724817786797528430136744458743428376798", "23153174875441426069922538845839074574095797738892298576581895020444392853731", "19950632315767750645780485212179021291844439659606854957365124208057044477001", "4990085320684307481424051057758258811192003289472239932032551966513564492664", "29810043862384409261569733347989054089853302964778668946432779952952625186706", "10937492441648375945337911315608624372433158520395209903090712138844575570844", "24981706249730491732129119057314109520549309496394969130105355950186024721860", "10498082524469215029826843019306692952360905490979497919767209022386939911216", "15682375221169428458922809183562392617423770660027773228464622792081026981791", "41914385147673242564111169184735297479310144571630342213035237856939024640011", "39667818743665708661866396692813914317148400284941420155363896112617842800421"];
-
-// Rescue-Prime round constants
-const ROUND_CONSTANTS_RS: [&str; 84] = ["35495817390819093545263349384941809089491580678942832859579453034368810736263", "4734865798690304458175502708216292605326887152358688691882538799996069070938", "31271008447681288492961289082649653266089021637020407236527451612237705002107", "3752272659749554246987316978069954116630957098620898965749354210894049705204", "22641555720019163306763445608116202165619173600682976754848212896631953422071", "28122533469631806190969995639553619503758826280316271478360761787725211583550", "25847917841495375497002109968427099088777388041775300281757084913772616807196", "32694606500120353152300866547101238346520817919199364752958292990138213972843", "27286327057691837800467727052167328890802672763096896941933952396730026264130", "11421505857991327619183254231367489753132565965114463729904675480639756627135", "521411871436069789624101480374109564923458769959324381065745329697883697117", "23880784307761253829209017376202022699450440759526482483183942457652656506129", "32944735989607121897647886317992117157418889561697480633116336030286723761501", "23809168654834556097350366212084670162247725165957937623679460641681583816451", "3163860194972429483721954648842733164010713297776971497284575674748141326227", "4994154821407041837874226315683255286085207059107827489820229821534877668868", "50472710115457611398312524300398743989276776324315737822995925423912734574272", "251866835357267652745308982111788504159393069098120092619439598668220537943", "29306447221479286209562070090539769526225070913770783266162336064629228514551", "29283041777181961494713136804131952798141345310627850728919908467956333015832", "28656363295645570828788643827370268834132346888229153863515891780361414296486", "25038928963239238795570624926346448459425394096652630785926109997438209703232", "8137054880809446884023200631931681550641379823710586899296036975467179806266", "40023642373942331790709007028495088784452433159634511649021697266107433596568", "35762237949937672281308268151392628513069349315494090383109234785560672634670", "30999566811631951689259246295471339743428563889981096112711184113782054324157", "20279178450660587763205226449293238908131708902882258115414408411285674682667", "7251226788353540177691937542431845975737106489341120571030231114808456476646", "52125099959305698802726608420202937507908602874086034970293871469588059526157", "36947771116325024965590213964896639663487838999452121836698608133540047510904", "6731449362796983987468313257604646517406447849071950000589095424962988643919", "46799204329731723451752711923834870677752669570495984560450489328024837708708", "35089631385082017128756246668734504606091189119241613702809534617529750689438", "48404791586561114467519265925614105026432456534013682923179665391057050944501", "25910045457085525717925797997640841840596905619632452927132962053945891631463", "1002644049329627578859603332717752156946995816186878866098534634466684910592", "27000834541453700882360080348023947440783037764820885196153273422481631245741", "12589074081116083610034305532223638886927072126291986576471860562392225733147", "42427223203410224646468929039478899902048566366856240877703602702087931641888", "13041605696900798404650686538893086909353822453068056131548498883864307018762", "7724559080250826493557773439911765324995115520951876421071063545832580076523", "2608760983816514764568197126437451665283344192910536302819820213681815485600", "4325576551800410431474186754039992813847609089390921236861130833620395142916", "28244869281227089786402354774575238327642814071062911402571918173773147690382", "52254320812514580546932455788288716956214894268551482805284261007871578815161", "44449246366481365934850731985584672122835299278127134772360027315881296465188", "38769336262079049280674269301353892930067342680672673045972987208159445324024", "42379436704506954159182654240696088598260763773619436089417882065405547940000", "22632779538473440042293241998410977359589070603696185849507766111228222504955", "15890983544445833013318912933113160561188216234423366865567699135890287302776", "42613622386509970285531317746282776785466762852259244468998263414951438072346", "3349279328650947992104289938299770603841093392045299702204191673899696292828", "24960454956681584943062398789550512391287992748093736571644480050215988836698", "31771416517485450527236959099354889665790926800645171236881417310335951093156", "6723262288337923380317441046361546981088139618189440979848042063784738198448", "34890105450847731125549485970715770779411614440863872580331681887247489622411", "11578979089604924419672152758230524155578424562011333797269885929442680042317", "50413588594256908654341963895371964591208017449187726872226940257366479794931", "25342645262500526730472670090219790271213097305995599586909134601382438580057", "8823851208157208211075893000112820438603010547555640271936182343488623715695", "48715166069588125017688857080421400882110756555551531562607166243928305121118", "457928742693316582022794368629361528074764749146022984852066320975235063636", "40784591676918140113004512439228960581998583153376036451231191678625605644121", "18433242804842005502998987143284711404363511412515282751547329224013759991670", "13389179080347763657382998600872902733061029331254291204270991952891409570918", "50192764209384080101272306620889875080455867628520281400927714930481563250325", "21188812847528225766555643216406799500549004969671500977130541863203997121380", "15467310814359095588985846207322319122950649805677111326687390171860927014900", "43568129081901200261103456211527409151200730655566657378341556085992472943958", "34271132631203889901701300408318058004416254071247236806623005223769350150039", "22173004425756666568314241635854763913339665884248599814793658197582222664954", "32975563242070450354147568749607182665869459510325615902750312087436132984686", "22696762757124796424578806530049133427552572655901519744413916679979764071390", "17763704296411643970998432037239004006015355463277677435659459899409343551392", "47107020014905029302099526236973268575042805085389783842994685212684421454488", "13304672766482627838923613214260444961210749299235217922669168410578113120633", "14336471400558675842362782084319960764287611922882892949544609123042059062824", "19303757685423427260649409150012846414071844305131989213305575732858057757894", "41105909312432760443399922527873622836019389621682258300053074843930035806751", "4449965847617470660026263611722341184463318026296894969809166330782012760219", "31939993490530073679397065723723444395703645080257573290017499883874398700446", "44612014630702294701797504988969181620837907283197659821551486351788471559337", "42992712381319065313644044212157260265940162092852802442073735607198967462282", "966835047744911231490794763166379188555949592683359886287393788918898119684"];
-// structure to store numbers in cells
-struct Number<F: PrimeField>(AssignedCell<F, F>);
-
-// structure for shared parameters for permutation functions
-#[derive(Clone, Debug)]
-struct PermutationParameters {
-    state_size: usize,
-    rate: usize,
-    capacity: usize 
-}
-
-// structure for Poseidon specific permutation parameters
-#[derive(Clone, Debug)]
-struct Poseidon<F: PrimeField> {
-    common_params: PermutationParameters,
-    partial_rounds: usize,
-    full_rounds: usize,
-    n: usize,
-    alpha: F,
-    mds: [[F; 3]; 3]
-}
-
-// structure for Rescue-Prime specific permutation parameters
-#[derive(Clone, Debug)]
-struct RescuePrime<F: PrimeField> {
-    common_params: PermutationParameters,
-    rounds: usize,
-    alpha: F,
-    alpha_inv: BigUint,
-    mds: [[F; 3]; 3]
-}
-
-// struture for common circuit parameters
-#[derive(Clone, Debug)]
-struct CircuitParameters {
-    advice: [Column<Advice>; 3],
-    fixed: [Column<Fixed>; 3],
-    instance: Column<Instance>,
-    s_mds_mul: Selector,
-    s_add_rcs: Selector
-}
-
-// Poseidon chip configuration
-#[derive(Clone, Debug)]
-struct PoseidonChipConfig<F: PrimeField> {
-    permutation_params: Poseidon<F>,
-    circuit_params: CircuitParameters,
-    _marker: PhantomData<F>,
-    // the below selectors are specific to Poseidon (Hades construction)
-    s_sub_bytes_full: Selector,
-    s_sub_bytes_partial: Selector
-}
-
-// Rescue-Prime chip configuration
-#[derive(Clone, Debug)]
-struct RescueChipConfig<F: PrimeField> {
-    permutation_params: RescuePrime<F>,
-    circuit_params: CircuitParameters,
-    _marker: PhantomData<F>,
-    // the selector below is specific to Rescue-Prime
-    s_sub_bytes: Selector,
-    s_sub_bytes_inv: Selector
-}
-
-// structure for the poseidon permutation chip
-struct PoseidonChip<F: PrimeField> {
-    config: PoseidonChipConfig<F>,
-    _marker: PhantomData<F>,
-}
-
-// structure for the poseidon permutation chip
-struct RescueChip<F: PrimeField> {
-    config: RescueChipConfig<F>,
-    _marker: PhantomData<F>,
-}
-
-// Poseidon circuit structure TODO: is this worth abstraction if I need two synthesizing calls anyways?
-#[derive(Default)]
-struct PoseidonCircuit<F: PrimeField> {
-    s0: Value<F>, 
-    s1: Value<F>, 
-    s2: Value<F>
-}
-
-// Rescue-Prime circuit structure
-#[derive(Default)]
-struct RescueCircuit<F: PrimeField> {
-    s0: Value<F>, 
-    s1: Value<F>, 
-    s2: Value<F>
-}
-
-// implement the Chip trait for PoseidonChip
-impl<F: PrimeField> Chip<F> for PoseidonChip<F> {
-    type Config = PoseidonChipConfig<F>;
-    type Loaded = ();
-
-    // getter for the chip config
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
+use std::path::PathBuf;
+use clap::{Parser, Subcommand};
 
-    // getter for the loaded field
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
-}
-
-// implement the Chip trait for RescueChip
-impl<F: PrimeField> Chip<F> for RescueChip<F> {
-    type Config = RescueChipConfig<F>;
-    type Loaded = ();
-
-    // getter for the chip config
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
-
-    // getter for the loaded field
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
-}
-
-// helper methods that both chips call when configuring (gate construction, column configurations, etc.)
-// gates created are stored in the ConstraintSystem instance
-fn create_arc_gate<F: PrimeField>(
-    meta: &mut ConstraintSystem<F>, 
-    advice: [Column<Advice>; 3], 
-    fixed: [Column<Fixed>; 3], 
-    s_add_rcs: Selector
-) {
-    meta.create_gate("ARC_Gate", |meta| {
-        let s_add_rcs = meta.query_selector(s_add_rcs);
-        let a0 = meta.query_advice(advice[0], Rotation::cur());
-        let a1 = meta.query_advice(advice[1], Rotation::cur());
-        let a2 = meta.query_advice(advice[2], Rotation::cur());
-        let a0_next = meta.query_advice(advice[0], Rotation::next());
-        let a1_next = meta.query_advice(advice[1], Rotation::next());
-        let a2_next = meta.query_advice(advice[2], Rotation::next());
-        let rc0 = meta.query_fixed(fixed[0]); // query_fixed reads from current row when gate is active
-        let rc1 = meta.query_fixed(fixed[1]);
-        let rc2 = meta.query_fixed(fixed[2]);
-
-        // constraint should be vec![0, 0, 0]
-        vec![
-            s_add_rcs.clone() * (a0_next - (a0 + rc0)), 
-            s_add_rcs.clone() * (a1_next - (a1 + rc1)), 
-            s_add_rcs * (a2_next - (a2 + rc2))
-        ]
-    });
-}
-
-fn create_mds_mul_gate<F: PrimeField>(
-    meta: &mut ConstraintSystem<F>, 
-    advice: [Column<Advice>; 3], 
-    s_mds_mul: Selector,
-    mds: &[[F; 3]; 3]
-) {
-    meta.create_gate("ML_gate", |meta| {
-        let s_mds_mul = meta.query_selector(s_mds_mul);
-        let a0 = meta.query_advice(advice[0], Rotation::cur());
-        let a1 = meta.query_advice(advice[1], Rotation::cur());
-        let a2 = meta.query_advice(advice[2], Rotation::cur());
-        let a0_next = meta.query_advice(advice[0], Rotation::next());
-        let a1_next = meta.query_advice(advice[1], Rotation::next());
-        let a2_next = meta.query_advice(advice[2], Rotation::next());
-
-        // MDS matrix elements from row in column 0 -> column 2 order, use Expression:Constant to embed into polynomial
-        let mds_0_0 = Expression::Constant(mds[0][0]);
-        let mds_0_1 = Expression::Constant(mds[0][1]);
-        let mds_0_2 = Expression::Constant(mds[0][2]);
-        let mds_1_0 = Expression::Constant(mds[1][0]);
-        let mds_1_1 = Expression::Constant(mds[1][1]);
-        let mds_1_2 = Expression::Constant(mds[1][2]);
-        let mds_2_0 = Expression::Constant(mds[2][0]);
-        let mds_2_1 = Expression::Constant(mds[2][1]);
-        let mds_2_2 = Expression::Constant(mds[2][2]);
-        
-        // constraint - computes vector matrix product
-        vec![
-            s_mds_mul.clone() * (a0_next - (a0.clone()*mds_0_0 + a1.clone()*mds_0_1 + a2.clone()*mds_0_2)),
-            s_mds_mul.clone() * (a1_next - (a0.clone()*mds_1_0 + a1.clone()*mds_1_1 + a2.clone()*mds_1_2)),
-            s_mds_mul * (a2_next - (a0*mds_2_0 + a1*mds_2_1 + a2*mds_2_2))
-        ]
-    });
-}
-
-// helper functions for creating Poseidon specific gates
-fn create_partial_sbox_gate_ps<F: PrimeField>(
-    meta: &mut ConstraintSystem<F>,
-    advice: Column<Advice>,
-    s_sub_bytes_partial: Selector, 
-) {
-    meta.create_gate("PS_partial_sbox_gate", |meta| {
-        let s_sub_bytes_partial = meta.query_selector(s_sub_bytes_partial);
-        let a0 = meta.query_advice(advice, Rotation::cur()); // state[0] = state[0]**5, alpha = 5
-        let a0_next = meta.query_advice(advice, Rotation::next());
-
-        vec![s_sub_bytes_partial* (a0_next - (a0.clone()*a0.clone()*a0.clone()*a0.clone()*a0))]
-    });
-}
-
-fn create_full_sbox_gate_ps<F: PrimeField>(
-    meta: &mut ConstraintSystem<F>,
-    advice: [Column<Advice>; 3],
-    s_sub_bytes_full: Selector, 
-) {
-    meta.create_gate("PS_full_sbox_gate", |meta| {
-        let s_sub_bytes_full = meta.query_selector(s_sub_bytes_full);
-        let a0 = meta.query_advice(advice[0], Rotation::cur());
-        let a1 = meta.query_advice(advice[1], Rotation::cur());
-        let a2 = meta.query_advice(advice[2], Rotation::cur()); 
-        let a0_next = meta.query_advice(advice[0], Rotation::next());
-        let a1_next = meta.query_advice(advice[1], Rotation::next());
-        let a2_next = meta.query_advice(advice[2], Rotation::next()); 
+use permutation_benchmark::{
+    Backend, BenchmarkError, CheckMode, ConstantsFormat, Endianness, FloorPlannerKind, OutputMode, ParameterSource, PermutationKind, Repeat, ReportOutput, TranscriptKind, export_parameters, hash_inputs_file, prove_preimage, run_audit, run_doctor, PeakAllocator, run_batch, run_benchmark, run_bytes, run_demo_membership, run_merkle, run_public_message, run_scaling, run_stream, run_stress, run_sweep, run_transcript, run_verify_batch, write_proofs,
+    set_parameter_source, generate_srs, low_memory_threads, show_preset, use_srs_file, with_low_memory, with_threads, write_fixtures, write_vectors
+};
 
-        vec![
-            s_sub_bytes_full.clone() * (a0_next - (a0.clone()*a0.clone()*a0.clone()*a0.clone()*a0)),
-            s_sub_bytes_full.clone() * (a1_next - (a1.clone()*a1.clone()*a1.clone()*a1.clone()*a1)),
-            s_sub_bytes_full * (a2_next - (a2.clone()*a2.clone()*a2.clone()*a2.clone()*a2))
-        ]
-    });
+// counts heap bytes for the memory measurements of the benchmark
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+#[derive(Parser)]
+#[command(about = "Poseidon and Rescue-Prime permutation benchmarks in Halo2")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Print spans at this level and above to stderr, with their timings when they close
+    #[arg(long, global = true)]
+    log_level: Option<tracing::Level>,
+    /// Round constants and MDS matrices: "preset", "generated" (Grain LFSR / SHAKE256), or a JSON/TOML parameter file
+    #[arg(long, global = true, default_value = "preset")]
+    parameters: ParameterSource,
+    /// Seed a ChaCha20 RNG for the real proofs, which makes them byte-identical across runs (OS randomness if omitted)
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+    /// Run everything, keygen, proving and verification included, on a thread pool of this size (rayon's default if
+    /// omitted)
+    #[arg(long, global = true)]
+    threads: Option<usize>,
+    /// Trade proving time for a smaller peak heap: keep commitment parameters for one k at a time, drop the proving key
+    /// before verifying, generate quick-check witnesses without 2^k-row columns, and prove on 2 threads unless
+    /// --threads is given
+    #[arg(long, global = true)]
+    low_memory: bool,
+    /// Read the IPA commitment parameters of the real proofs from this SRS file (written by `params generate`) instead
+    /// of generating them; a file for k serves every smaller k
+    #[arg(long, global = true)]
+    srs: Option<PathBuf>,
+    /// Write a Chrome trace (chrome://tracing, Perfetto) of every span to this file
+    #[cfg(feature = "chrome-trace")]
+    #[arg(long, global = true)]
+    trace_out: Option<PathBuf>
 }
 
-// helper functions for creating Rescue-Prime specific gates
-// alpha = 5
-// alpha_inv = 20974350070050476191779096203274386335076221000211055129041463479975432473805 = inverse(5, p-1)
-fn create_sbox_gate_rs<F: PrimeField>(
-    meta: &mut ConstraintSystem<F>, 
-    advice: [Column<Advice>; 3],
-    s_sub_bytes: Selector
-) {
-    meta.create_gate("RS_sbox_gate", |meta| {
-        let s_sub_bytes = meta.query_selector(s_sub_bytes);
-        let a0 = meta.query_advice(advice[0], Rotation::cur());
-        let a1 = meta.query_advice(advice[1], Rotation::cur());
-        let a2 = meta.query_advice(advice[2], Rotation::cur()); 
-        let a0_next = meta.query_advice(advice[0], Rotation::next());
-        let a1_next = meta.query_advice(advice[1], Rotation::next());
-        let a2_next = meta.query_advice(advice[2], Rotation::next());
-
-        vec![
-            s_sub_bytes.clone() * (a0_next - (a0.clone()*a0.clone()*a0.clone()*a0.clone()*a0)),
-            s_sub_bytes.clone() * (a1_next - (a1.clone()*a1.clone()*a1.clone()*a1.clone()*a1)),
-            s_sub_bytes * (a2_next - (a2.clone()*a2.clone()*a2.clone()*a2.clone()*a2))
-        ]
-    });
+// keeps the Chrome trace writer alive, the file is flushed when this is dropped
+struct TracingGuard {
+    #[cfg(feature = "chrome-trace")]
+    _chrome: Option<tracing_chrome::FlushGuard>
 }
 
-fn create_sbox_inv_gate_rs<F: PrimeField>(
-    meta: &mut ConstraintSystem<F>,
-    advice: [Column<Advice>; 3],
-    s_sub_bytes_inv: Selector
-) {
-    meta.create_gate("RS_sbox_inv_gate", |meta| {
-        let s_sub_bytes_inv = meta.query_selector(s_sub_bytes_inv);
-        let a0 = meta.query_advice(advice[0], Rotation::cur());
-        let a1 = meta.query_advice(advice[1], Rotation::cur());
-        let a2 = meta.query_advice(advice[2], Rotation::cur()); 
-        let a0_next = meta.query_advice(advice[0], Rotation::next());
-        let a1_next = meta.query_advice(advice[1], Rotation::next());
-        let a2_next = meta.query_advice(advice[2], Rotation::next());
+// install the subscriber requested on the command line; without either flag no subscriber is installed and spans are no-ops
+fn init_tracing(cli: &Cli) -> TracingGuard {
+    use tracing_subscriber::{fmt::format::FmtSpan, layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
-        // constrain a_next^alpha = a_current instead of a_next = a_current^alpha_inv
-        vec![
-            s_sub_bytes_inv.clone() * (a0 - (a0_next.clone()*a0_next.clone()*a0_next.clone()*a0_next.clone()*a0_next)),
-            s_sub_bytes_inv.clone() * (a1 - (a1_next.clone()*a1_next.clone()*a1_next.clone()*a1_next.clone()*a1_next)),
-            s_sub_bytes_inv * (a2 - (a2_next.clone()*a2_next.clone()*a2_next.clone()*a2_next.clone()*a2_next))
-        ]
+    let fmt_layer = cli.log_level.map(|level| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_filter(tracing_subscriber::filter::LevelFilter::from_level(level))
     });
-}
 
-// implementation of additional methods for the PoseidonChip
-impl<F: PrimeField> PoseidonChip<F> {
-    // constructor
-    fn construct(config: <Self as Chip<F>>::Config) -> Self {
-        PoseidonChip { config, _marker: PhantomData}
-    }
-
-    // configure the chip including all gates, constraints, and selectors
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 3],
-        fixed: [Column<Fixed>; 3],
-        instance: Column<Instance>,
-        params: Poseidon<F>
-    ) -> <Self as Chip<F>>::Config {
-        // enable equality constraints on the instance column
-        meta.enable_equality(instance);
-
-        // enable equality constraits on all advice columns
-        for column in &advice {
-            meta.enable_equality(*column);
-        }
-
-        // enable constant on all the fixed columns
-        for column in &fixed {
-            meta.enable_constant(*column);
+    #[cfg(feature = "chrome-trace")]
+    let (chrome_layer, chrome_guard) = match &cli.trace_out {
+        Some(path) => {
+            let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new().file(path).build();
+            (Some(layer), Some(guard))
         }
+        None => (None, None)
+    };
+    #[cfg(not(feature = "chrome-trace"))]
+    let chrome_layer: Option<tracing_subscriber::layer::Identity> = None;
 
-        let s_add_rcs = meta.selector();
-        let s_mds_mul = meta.selector();
-        let s_sub_bytes_full = meta.selector();
-        let s_sub_bytes_partial = meta.selector();  
-
-        // create gates and constraints
-        create_arc_gate(meta, advice, fixed, s_add_rcs);
-        create_mds_mul_gate(meta, advice, s_mds_mul, &params.mds);
-        create_full_sbox_gate_ps(meta, advice, s_sub_bytes_full);
-        create_partial_sbox_gate_ps(meta, advice[0], s_sub_bytes_partial);
-
-        let circuit_params = CircuitParameters {
-            advice,
-            fixed,
-            instance,
-            s_mds_mul,
-            s_add_rcs
-        };
-        
-        // return the config
-        PoseidonChipConfig {
-            permutation_params: params,
-            circuit_params,
-            _marker: PhantomData,
-            s_sub_bytes_full,
-            s_sub_bytes_partial
-        }
-    }
-}
-
-// implementation of additional methods for the RescueChip
-impl<F: PrimeField> RescueChip<F> {
-    // constructor
-    fn construct(config: <Self as Chip<F>>::Config) -> Self {
-        RescueChip { config, _marker: PhantomData}
-    }
-
-    // configure the chip including all gates, constraints, and selectors
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 3],
-        fixed: [Column<Fixed>; 3],
-        instance: Column<Instance>,
-        params: RescuePrime<F>
-    ) -> <Self as Chip<F>>::Config {
-        // enable equality constraints on the instance column
-        meta.enable_equality(instance);
-
-        // enable equality constraits on all advice columns
-        for column in &advice {
-            meta.enable_equality(*column);
-        }
-
-        // enable constant on all the fixed columns
-        for column in &fixed {
-            meta.enable_constant(*column);
-        }
-
-        let s_add_rcs = meta.selector();
-        let s_mds_mul = meta.selector();
-        let s_sub_bytes = meta.selector();
-        let s_sub_bytes_inv = meta.selector();  
-
-        // create gates and constraints
-        create_arc_gate(meta, advice, fixed, s_add_rcs);
-        create_mds_mul_gate(meta, advice, s_mds_mul, &params.mds);
-        create_sbox_gate_rs(meta, advice, s_sub_bytes);
-        create_sbox_inv_gate_rs(meta, advice, s_sub_bytes_inv);
-
-        let circuit_params = CircuitParameters {
-            advice,
-            fixed,
-            instance,
-            s_mds_mul,
-            s_add_rcs
-        };
-        
-        // return the config
-        RescueChipConfig {
-            permutation_params: params,
-            circuit_params,
-            _marker: PhantomData,
-            s_sub_bytes,
-            s_sub_bytes_inv
-        }
-    }
-}
-
-// trait for the sub-functions of the circuit
-trait PermutationInstructions<F: PrimeField>: Chip<F> {
-    type Num;
-
-    // expose a value as public for
-    fn expose_as_public(&self, layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error>;
-
-    // permutation
-    fn permute(
-        &self, 
-        layouter: impl Layouter<F>,
-        a0: Value<F>,
-        a1: Value<F>,
-        a2: Value<F>
-    ) -> Result<[Self::Num; 3], Error>;
-}
-
-// implementation of the PermutationInstructions trait for the PoseidonChip
-impl<F: PrimeField> PermutationInstructions<F> for PoseidonChip<F> {
-    type Num = Number<F>;
-
-    fn expose_as_public(&self, mut layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error> {
-        let config = self.config();
-        layouter.constrain_instance(num.0.cell(), config.circuit_params.instance, row)
-    }
-
-    fn permute(
-        &self, mut layouter: impl Layouter<F>, 
-        a0: Value<F>,
-        a1: Value<F>,
-        a2: Value<F>
-    ) -> Result<[Self::Num; 3], Error> {
-        let config = self.config();
-        layouter.assign_region(
-            || "Poseidon_Permutation", |mut region| {
-                let mut constant_idx: usize = 0; // index into round constants
-                let mut offset: usize = 0; // row index for computations on state
-                let mut advice_cell_ctr: usize = 0; 
-                let mut fixed_cell_ctr: usize = 0;
-                let mut activated_gates_ctr: usize = 0;
-
-                // initial state
-                let mut state = [
-                    region.assign_advice(|| "state_0", config.circuit_params.advice[0], offset, || a0)?,
-                    region.assign_advice(|| "state_1", config.circuit_params.advice[1], offset, || a1)?, 
-                    region.assign_advice(|| "state_2", config.circuit_params.advice[2], offset, || a2)?
-                ];
-
-                advice_cell_ctr += 3; // 3 used by loading the initial state
-
-                // helper function for power of 5 for SubBytes (in-place modification)
-                let pow5 = |a: F| -> F {
-                    let temp = a * a; // a^2
-                    let temp_1 = temp * temp; // a^4
-                    a * temp_1 // a^5
-                };
-
-                // helper function for computing one poseidon round full or partial based on boolean
-                let poseidon_round = |
-                    region: &mut Region<F>,
-                    state: &mut [AssignedCell<F, F>; 3],
-                    constant_idx: &mut usize,
-                    offset: &mut usize,
-                    full_round: bool,
-                    advice_cell_ctr: &mut usize,
-                    fixed_cell_ctr: &mut usize,
-                    activated_gates_ctr: &mut usize
-                | -> Result<(), Error> {
-                    // assign the needed round constants to the fixed column for gate to read from, use local vars for state
-                    let rc0 = F::from_str_vartime(ROUND_CONSTANTS_PS[*constant_idx]).unwrap();
-                    let rc1 = F::from_str_vartime(ROUND_CONSTANTS_PS[*constant_idx + 1]).unwrap();
-                    let rc2 = F::from_str_vartime(ROUND_CONSTANTS_PS[*constant_idx + 2]).unwrap();
-                    region.assign_fixed(|| "c0", config.circuit_params.fixed[0], *offset, || Value::known(rc0))?;
-                    region.assign_fixed(|| "c1", config.circuit_params.fixed[1], *offset, || Value::known(rc1))?;
-                    region.assign_fixed(|| "c2", config.circuit_params.fixed[2], *offset, || Value::known(rc2))?;
-                    *fixed_cell_ctr += 3;
-
-                    config.circuit_params.s_add_rcs.enable(region, *offset)?; // enable the ARC selector 
-                    *activated_gates_ctr += 1;
-                    *constant_idx += 3; // 3 round constants used from the flat list
-                    *offset += 1; // first row used for fixed columns and initial state
-
-                    let after_arc = [
-                        state[0].value().map(|v| *v + rc0),
-                        state[1].value().map(|v| *v + rc1),
-                        state[2].value().map(|v| *v + rc2)
-                    ];
-
-                    // assign state values after ARC to advice columns
-                    state[0] = region.assign_advice(|| "s0_arc", config.circuit_params.advice[0], *offset, || after_arc[0])?;
-                    state[1] = region.assign_advice(|| "s1_arc", config.circuit_params.advice[1], *offset, || after_arc[1])?;
-                    state[2] = region.assign_advice(|| "s2_arc", config.circuit_params.advice[2], *offset, || after_arc[2])?;
-                    *advice_cell_ctr += 3; // increment number of advice cells used
-
-                    // SubBytes based on parameter for full or partial round (partial round only applies to state[0])
-                    if full_round == true {
-                        config.s_sub_bytes_full.enable(region, *offset)?;
-                        *activated_gates_ctr += 1;
-                        *offset += 1;
-
-                        let after_sb = [
-                            state[0].value().map(|v| pow5(*v)),
-                            state[1].value().map(|v| pow5(*v)),
-                            state[2].value().map(|v| pow5(*v))
-                        ];
-
-                        state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || after_sb[0])?;
-                        state[1] = region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || after_sb[1])?;
-                        state[2] = region.assign_advice(|| "s2_sb", config.circuit_params.advice[2], *offset, || after_sb[2])?;
-                        *advice_cell_ctr += 3; // increment number of advice cells used
-                    }
-
-                    else {
-                        config.s_sub_bytes_partial.enable(region, *offset)?;
-                        *activated_gates_ctr += 1;
-                        *offset += 1;
-                        state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || state[0].value().map(|v| pow5(*v)))?;
-                        // copy other values to new offset, without modification
-                        region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || state[1].value().copied())?;
-                        region.assign_advice(|| "s1_sb", config.circuit_params.advice[2], *offset, || state[2].value().copied())?;
-                        *advice_cell_ctr += 3; // increment number of advice cells used
-                    }
-
-                    // MixLayer
-                    config.circuit_params.s_mds_mul.enable(region, *offset)?;
-                    *activated_gates_ctr += 1;
-                    *offset += 1;
-                    
-                    let mds = [
-                        [
-                            config.permutation_params.mds[0][0], 
-                            config.permutation_params.mds[0][1], 
-                            config.permutation_params.mds[0][2]],
-                        [
-                            config.permutation_params.mds[1][0], 
-                            config.permutation_params.mds[1][1], 
-                            config.permutation_params.mds[1][2]
-                        ],
-                        [
-                            config.permutation_params.mds[2][0], 
-                            config.permutation_params.mds[2][1], 
-                            config.permutation_params.mds[2][2]
-                        ]
-                    ];
-
-                    // extract copies of state values using .value().copied() then nest map() calls to get inner values
-                    let after_ml = [
-                        state[0].value().copied()
-                            .zip(state[1].value().copied())
-                            .zip(state[2].value().copied()) // gives ((Value<F>, Value<F>), Value<F>)
-                            .map(|((s0, s1), s2)| {
-                                s0 * mds[0][0] + s1 * mds[0][1] + s2 * mds[0][2]
-                            }),
-                        state[0].value().copied()
-                            .zip(state[1].value().copied())
-                            .zip(state[2].value().copied())
-                            .map(|((s0, s1), s2)| {
-                                s0 * mds[1][0] + s1 * mds[1][1] + s2 * mds[1][2]
-                            }),
-                        state[0].value().copied()
-                            .zip(state[1].value().copied())
-                            .zip(state[2].value().copied()) 
-                            .map(|((s0, s1), s2)| {
-                                s0 * mds[2][0] + s1 * mds[2][1] + s2 * mds[2][2]
-                            }),
-                    ];
-
-                    state[0] = region.assign_advice(|| "s0_ml", config.circuit_params.advice[0], *offset, || after_ml[0])?;
-                    state[1] = region.assign_advice(|| "s1_ml", config.circuit_params.advice[1], *offset, || after_ml[1])?;
-                    state[2] = region.assign_advice(|| "s2_ml", config.circuit_params.advice[2], *offset, || after_ml[2])?;
-                    *advice_cell_ctr += 3; // increment number of advice cells used
-
-                    Ok(())
-                };
-
-                // half of the full rounds
-                for _ in 0..(config.permutation_params.full_rounds / 2) { 
-                    poseidon_round(
-                        &mut region, 
-                        &mut state, 
-                        &mut constant_idx, 
-                        &mut offset, 
-                        true, 
-                        &mut advice_cell_ctr,
-                        &mut fixed_cell_ctr,
-                        &mut activated_gates_ctr
-                    )?;
-                }
-
-                // all of the partial rounds
-                for _ in 0..config.permutation_params.partial_rounds {
-                    poseidon_round(
-                        &mut region, 
-                        &mut state, 
-                        &mut constant_idx, 
-                        &mut offset, 
-                        false, 
-                        &mut advice_cell_ctr,
-                        &mut fixed_cell_ctr,
-                        &mut activated_gates_ctr
-                    )?;
-                }
-
-                // second half of the full rounds
-                for _ in 0..(config.permutation_params.full_rounds / 2) {
-                    poseidon_round(
-                        &mut region, 
-                        &mut state, 
-                        &mut constant_idx, 
-                        &mut offset, 
-                        true, 
-                        &mut advice_cell_ctr,
-                        &mut fixed_cell_ctr,
-                        &mut activated_gates_ctr
-                    )?;
-                }
-
-                // log the number of rows used for Poseidon
-                println!("Poseidon rows used: {}", offset);
-                // log the number of advice cells used for Poseidon
-                println!("Poseidon advice cells used: {}", advice_cell_ctr);
-                // log the number of fixed cells used for Poseidon
-                println!("Poseidon fixed cells used: {}", fixed_cell_ctr);
-                // log the number of activated gates used for Poseidon
-                println!("Poseidon activated gates: {}", activated_gates_ctr);
-
-                Ok([Number(state[0].clone()), Number(state[1].clone()), Number(state[2].clone())])
-            }
-        )
+    if fmt_layer.is_some() || chrome_layer.is_some() {
+        tracing_subscriber::registry().with(fmt_layer).with(chrome_layer).init();
     }
-}
 
-// implementation of the PermutationInstructions trait for the RescueChip
-impl<F: PrimeField> PermutationInstructions<F> for RescueChip<F> {
-    type Num = Number<F>;
-
-    fn expose_as_public(&self, mut layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error> {
-        let config = self.config();
-        layouter.constrain_instance(num.0.cell(), config.circuit_params.instance, row)
-    }
-
-    fn permute(
-        &self, mut layouter: impl Layouter<F>, 
-        a0: Value<F>,
-        a1: Value<F>,
-        a2: Value<F>
-    ) -> Result<[Self::Num; 3], Error> {
-        let config = self.config();
-        layouter.assign_region(
-            || "Rescue-Prime_Permutation", |mut region| {
-                let mut offset: usize = 0; // row index for computations on state
-                let mut advice_cell_ctr: usize = 0; 
-                let mut fixed_cell_ctr: usize = 0;
-                let mut activated_gates_ctr: usize = 0;
-
-                // initial state
-                let mut state = [
-                    region.assign_advice(|| "state_0", config.circuit_params.advice[0], offset, || a0)?,
-                    region.assign_advice(|| "state_1", config.circuit_params.advice[1], offset, || a1)?, 
-                    region.assign_advice(|| "state_2", config.circuit_params.advice[2], offset, || a2)?
-                ];
-
-                advice_cell_ctr += 3;
-
-                // helper function for power of 5 for SubBytes (in-place modification)
-                let pow5 = |a: F| -> F {
-                    let temp = a * a; // a^2
-                    let temp_1 = temp * temp; // a^4
-                    a * temp_1 // a^5
-                };
-
-                // helper function for MDS multiplication
-                let mds_mul = |
-                    state: &mut [AssignedCell<F, F>; 3], 
-                    region: &mut Region<F>, 
-                    offset: &mut usize, 
-                    advice_cell_ctr: &mut usize,
-                    activated_gates_ctr: &mut usize
-                | -> Result<(), Error> {
-                    let mds = [
-                        [
-                            config.permutation_params.mds[0][0], 
-                            config.permutation_params.mds[0][1], 
-                            config.permutation_params.mds[0][2]
-                        ],
-                        [
-                            config.permutation_params.mds[1][0], 
-                            config.permutation_params.mds[1][1], 
-                            config.permutation_params.mds[1][2]
-                        ],
-                        [
-                            config.permutation_params.mds[2][0], 
-                            config.permutation_params.mds[2][1], 
-                            config.permutation_params.mds[2][2]
-                        ]
-                    ];
-
-                    config.circuit_params.s_mds_mul.enable(region, *offset)?;
-                    *activated_gates_ctr += 1;
-                    *offset += 1;
-
-                    let after_ml = [
-                        state[0].value().copied()
-                            .zip(state[1].value().copied())
-                            .zip(state[2].value().copied()) // gives ((Value<F>, Value<F>), Value<F>)
-                            .map(|((s0, s1), s2)| {
-                                s0 * mds[0][0] + s1 * mds[0][1] + s2 * mds[0][2]
-                            }),
-                        state[0].value().copied()
-                            .zip(state[1].value().copied())
-                            .zip(state[2].value().copied())
-                            .map(|((s0, s1), s2)| {
-                                s0 * mds[1][0] + s1 * mds[1][1] + s2 * mds[1][2]
-                            }),
-                        state[0].value().copied()
-                            .zip(state[1].value().copied())
-                            .zip(state[2].value().copied()) 
-                            .map(|((s0, s1), s2)| {
-                                s0 * mds[2][0] + s1 * mds[2][1] + s2 * mds[2][2]
-                            }),
-                    ];
-
-                    state[0] = region.assign_advice(|| "s0_ml", config.circuit_params.advice[0], *offset, || after_ml[0])?;
-                    state[1] = region.assign_advice(|| "s1_ml", config.circuit_params.advice[1], *offset, || after_ml[1])?;
-                    state[2] = region.assign_advice(|| "s2_ml", config.circuit_params.advice[2], *offset, || after_ml[2])?;
-                    *advice_cell_ctr += 3; // increment number of advice cells used
-
-                    Ok(())
-                };
-
-                // helper function for injecting the round constants
-                let inject_rcs = |
-                    state: &mut [AssignedCell<F, F>; 3], 
-                    region: &mut Region<F>, 
-                    offset: &mut usize, 
-                    idx_0: usize,
-                    idx_1: usize,
-                    idx_2: usize, 
-                    advice_cell_ctr: &mut usize,
-                    fixed_cell_ctr: &mut usize,
-                    activated_gates_ctr: &mut usize
-                | -> Result<(), Error> {
-                    // assign the needed round constants to the fixed column for gate to read from, use local vars for state
-                    let rc0 = F::from_str_vartime(ROUND_CONSTANTS_RS[idx_0]).unwrap();
-                    let rc1 = F::from_str_vartime(ROUND_CONSTANTS_RS[idx_1]).unwrap();
-                    let rc2 = F::from_str_vartime(ROUND_CONSTANTS_RS[idx_2]).unwrap();
-                    region.assign_fixed(|| "c0", config.circuit_params.fixed[0], *offset, || Value::known(rc0))?;
-                    region.assign_fixed(|| "c1", config.circuit_params.fixed[1], *offset, || Value::known(rc1))?;
-                    region.assign_fixed(|| "c2", config.circuit_params.fixed[2], *offset, || Value::known(rc2))?;
-                    *fixed_cell_ctr += 3;
-
-                    config.circuit_params.s_add_rcs.enable(region, *offset)?; // enable the ARC selector 
-                    *activated_gates_ctr += 1;
-                    *offset += 1; 
-
-                    let after_arc = [
-                        state[0].value().map(|v| *v + rc0),
-                        state[1].value().map(|v| *v + rc1),
-                        state[2].value().map(|v| *v + rc2)
-                    ];
-
-                    state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || after_arc[0])?;
-                    state[1] = region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || after_arc[1])?;
-                    state[2] = region.assign_advice(|| "s2_sb", config.circuit_params.advice[2], *offset, || after_arc[2])?;
-                    *advice_cell_ctr += 3; // increment number of advice cells used
-
-                    Ok(())
-                };
-
-                // helper function for computing one rescue round
-                let rescue_round = |
-                    region: &mut Region<F>,
-                    state: &mut [AssignedCell<F, F>; 3],
-                    round: usize,
-                    offset: &mut usize,
-                    advice_cell_ctr: &mut usize,
-                    fixed_cell_ctr: &mut usize,
-                    activated_gates_ctr: &mut usize
-                | -> Result<(), Error> {
-                    config.s_sub_bytes.enable(region, *offset)?;
-                    *activated_gates_ctr += 1;
-                    *offset += 1;
-
-                    let after_sb = [
-                        state[0].value().map(|v| pow5(*v)),
-                        state[1].value().map(|v| pow5(*v)),
-                        state[2].value().map(|v| pow5(*v))
-                    ];
-
-                    state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || after_sb[0])?;
-                    state[1] = region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || after_sb[1])?;
-                    state[2] = region.assign_advice(|| "s2_sb", config.circuit_params.advice[2], *offset, || after_sb[2])?;
-                    *advice_cell_ctr += 3; // increment number of advice cells used
-
-                    // MDS Multiplication helper function
-                    mds_mul(state, region, offset, advice_cell_ctr, activated_gates_ctr)?;
-
-                    // Add/Inject Round Constants helper function
-                    let state_size: usize = config.permutation_params.common_params.state_size;
-                    let mut base_idx: usize = 2*round*state_size;
-                    inject_rcs(
-                        state, 
-                        region, 
-                        offset, 
-                        base_idx, 
-                        base_idx+1, 
-                        base_idx+2, 
-                        advice_cell_ctr, 
-                        fixed_cell_ctr, 
-                        activated_gates_ctr
-                    )?;
-                    
-                    // inverse SubBytes
-                    config.s_sub_bytes_inv.enable(region, *offset)?;
-                    *activated_gates_ctr += 1;
-                    *offset += 1;
-                    
-                    let alpha_inv_vec: Vec<u64> = config.permutation_params.alpha_inv.to_u64_digits();
-
-                    let after_sb_inv = [
-                        state[0].value().map(|v| v.pow_vartime(&alpha_inv_vec)),
-                        state[1].value().map(|v| v.pow_vartime(&alpha_inv_vec)),
-                        state[2].value().map(|v| v.pow_vartime(&alpha_inv_vec))
-                    ];
-
-                    state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || after_sb_inv[0])?;
-                    state[1] = region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || after_sb_inv[1])?;
-                    state[2] = region.assign_advice(|| "s2_sb", config.circuit_params.advice[2], *offset, || after_sb_inv[2])?;
-                    *advice_cell_ctr += 3; // increment number of advice cells used
-
-                    // second mds multiplication
-                    mds_mul(state, region, offset, advice_cell_ctr, activated_gates_ctr)?;
-
-                    // second inject/add round constants
-                    base_idx = 2*round*state_size+state_size;
-                    inject_rcs(
-                        state, 
-                        region, 
-                        offset, 
-                        base_idx, 
-                        base_idx+1, 
-                        base_idx+2, 
-                        advice_cell_ctr, 
-                        fixed_cell_ctr, 
-                        activated_gates_ctr
-                    )?;
-
-                    Ok(())
-                };
-
-                // perform the Rescue-Prime rounds
-                for i in 0..config.permutation_params.rounds {
-                    rescue_round(
-                        &mut region, 
-                        &mut state, 
-                        i, 
-                        &mut offset, 
-                        &mut advice_cell_ctr, 
-                        &mut fixed_cell_ctr, 
-                        &mut activated_gates_ctr
-                    )?;
-                }
-
-                // log the number of rows used for Rescue-Prime
-                println!("Rescue-Prime rows used: {}", offset);
-                // log the number of advice cells used for Rescue-Prime
-                println!("Rescue-Prime advice cells used: {}", advice_cell_ctr);
-                // log the number of fixed cells used for Rescue-Prime
-                println!("Rescue-Prime fixed cells used: {}", fixed_cell_ctr);
-                // log the number of activated gates used for Rescue-Prime
-                println!("Rescue-Prime activated gates: {}", activated_gates_ctr);
-
-                Ok([Number(state[0].clone()), Number(state[1].clone()), Number(state[2].clone())])
-            }
-        )
+    TracingGuard {
+        #[cfg(feature = "chrome-trace")]
+        _chrome: chrome_guard
     }
 }
 
-// helper function to return common parameters struct
-fn get_common_params() -> PermutationParameters
- {
-    let state_size: usize = 3;
-    let rate: usize = 2;
-    let capacity: usize = 1;
-
-    PermutationParameters {
-        state_size,
-        rate,
-        capacity
+#[derive(Subcommand)]
+enum Command {
+    /// Run the benchmark for both permutations (default)
+    Bench {
+        /// Which output state words are exposed as public instances
+        #[arg(long, value_enum, default_value_t)]
+        output_mode: OutputMode,
+        /// Fiat-Shamir transcript for the real proofs
+        #[arg(long, value_enum, default_value_t)]
+        transcript: TranscriptKind,
+        /// Also write the metrics in another format: `--output csv results.csv` appends rows, `--output markdown
+        /// results.md` writes a Markdown table
+        #[arg(long, num_args = 2, value_names = ["FORMAT", "PATH"])]
+        output: Option<Vec<String>>,
+        /// Timed rounds of every measured phase, MockProver and real proofs alike (30 MockProver rounds and one proof
+        /// if omitted)
+        #[arg(long)]
+        repeat: Option<usize>,
+        /// Untimed rounds before the timed ones
+        #[arg(long, default_value_t = 0, requires = "repeat")]
+        warmup: usize,
+        /// How MockProver checks the measured circuits: at k, or at the smallest k that fits followed by one full check
+        #[arg(long, value_enum, default_value_t)]
+        check_mode: CheckMode,
+        /// Also print the starting row and row count of every region as a table (the JSON report always lists them)
+        #[arg(long)]
+        verbose: bool,
+        /// Instead of the benchmark, run the stress cases: circuits far below and right at the usable rows of their k,
+        /// checked with MockProver
+        #[arg(long)]
+        stress: bool
+    },
+    /// Prove knowledge of a preimage of a public digest (state[0] after the permutation)
+    ProvePreimage {
+        /// Public digest, decimal or 0x-prefixed hex
+        #[arg(long)]
+        digest: String,
+        #[arg(long, value_enum, default_value = "poseidon")]
+        permutation: PermutationKind,
+        /// File with the three private input words, whitespace separated (stdin if omitted)
+        #[arg(long)]
+        inputs: Option<PathBuf>,
+        /// Read the inputs as a raw 96-byte state (three 32-byte words) instead of text
+        #[arg(long)]
+        raw: bool,
+        /// Byte order of 0x hex values and of raw input bytes
+        #[arg(long, value_enum, default_value = "big")]
+        endianness: Endianness,
+        #[arg(long, default_value_t = 10)]
+        k: u32,
+        /// Create and verify a real IPA proof over Pasta on a prover job, printing its progress; the digest is then
+        /// over Pasta's Fp
+        #[arg(long)]
+        prove: bool
+    },
+    /// Prove batches of independent permutations in one proof and report the proving time per hash
+    Batch {
+        /// Batch sizes, comma separated
+        #[arg(long, value_delimiter = ',', default_value = "1,16,256")]
+        sizes: Vec<usize>
+    },
+    /// Hash transcripts through the sponge and report rows and proving time as the transcript grows
+    Transcript {
+        /// Numbers of absorbed elements, comma separated
+        #[arg(long, value_delimiter = ',', default_value = "8,32,128")]
+        sizes: Vec<usize>,
+        /// Prove a MAC under a private key (keyed sponge) instead of an unkeyed challenge
+        #[arg(long)]
+        keyed: bool
+    },
+    /// Hash byte messages packed into field elements and prove the digest, per permutation
+    Bytes {
+        /// Message sizes in bytes, comma separated
+        #[arg(long, value_delimiter = ',', default_value = "64,1024,4096")]
+        sizes: Vec<usize>
+    },
+    /// Hash messages exposed in a second instance column through the sponge and prove the digest, per permutation
+    PublicMessage {
+        /// Message slots, all filled, comma separated
+        #[arg(long, value_delimiter = ',', default_value = "32")]
+        sizes: Vec<usize>
+    },
+    /// Squeeze many outputs from a two-element seed through the sponge in duplex mode and prove them, per permutation
+    Stream {
+        /// Numbers of squeezed outputs, comma separated
+        #[arg(long, value_delimiter = ',', default_value = "16,64")]
+        outputs: Vec<usize>
+    },
+    /// Build a Merkle tree natively as binary with t = 3 and as 4-ary with t = 5, and prove one full path in each
+    Merkle {
+        /// Base-2 logarithm of the number of leaves, even so that the tree also has a 4-ary shape
+        #[arg(long, default_value_t = 16)]
+        leaves_log2: u32
+    },
+    /// Prove the membership of a random leaf in a random tree, its path to the public root and its public nullifier
+    /// H(leaf, secret), with both permutations
+    DemoMembership {
+        /// Base-2 logarithm of the number of leaves
+        #[arg(long, default_value_t = 10)]
+        leaves_log2: u32
+    },
+    /// Prove both permutation circuits on thread pools of several sizes and report the speedup over the first size
+    Scaling {
+        /// Thread counts, comma separated
+        #[arg(long, value_delimiter = ',', default_value = "1,2,4,8")]
+        thread_counts: Vec<usize>,
+        #[arg(long, default_value_t = 10)]
+        k: u32
+    },
+    /// Prove both permutation circuits for every combination of backend, k, output mode and floor planner, in parallel
+    Sweep {
+        /// Proving backends, comma separated
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "ipa")]
+        backends: Vec<Backend>,
+        /// Values of k, comma separated
+        #[arg(long, value_delimiter = ',', default_value = "8,10,12")]
+        ks: Vec<u32>,
+        /// Output modes, comma separated
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "all-state")]
+        output_modes: Vec<OutputMode>,
+        /// Floor planners, comma separated
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "simple")]
+        floor_planners: Vec<FloorPlannerKind>,
+        /// Most proofs in progress at once (each holds its proving key in memory)
+        #[arg(long, default_value_t = 2)]
+        max_parallel: usize,
+        /// Threads of halo2's parallel prover, shared by all proofs (rayon's default if omitted)
+        #[arg(long)]
+        prover_threads: Option<usize>
+    },
+    /// Verify proof files one by one and in one batch, and compare the verification times
+    VerifyBatch {
+        /// Proof files; all must have the same k and the parameters of this run
+        #[arg(required = true)]
+        proofs: Vec<PathBuf>,
+        /// Prove the permutation circuits first, Poseidon and Rescue-Prime alternately, and write one proof per file
+        #[arg(long)]
+        write: bool,
+        /// k of the proofs written with --write
+        #[arg(long, default_value_t = 10, requires = "write")]
+        k: u32
+    },
+    /// Write SRS files of IPA commitment parameters, or show a constants preset
+    Params {
+        #[command(subcommand)]
+        action: ParamsCommand
+    },
+    /// Write the selected parameters as a parameter file (TOML for a .toml path, JSON otherwise)
+    ExportParameters {
+        #[arg(long)]
+        out: PathBuf
+    },
+    /// Hash every state of a CSV or JSON Lines file and write the rows with their digests appended, in the same format
+    Hash {
+        /// Input file: `s0,s1,s2` per line (optional header), or one JSON array of three words per line for .json and
+        /// .jsonl; words are decimal or 0x-prefixed hex
+        #[arg(long)]
+        inputs_file: PathBuf,
+        #[arg(long, value_enum, default_value = "poseidon")]
+        permutation: PermutationKind,
+        /// Byte order of 0x hex words
+        #[arg(long, value_enum, default_value = "big")]
+        endianness: Endianness,
+        /// Report malformed rows on stderr and skip them instead of aborting at the first one
+        #[arg(long)]
+        lenient: bool,
+        /// Prove all states in one batch proof over Pasta (digests over Pasta's Fp instead of BLS12-381) and print its
+        /// report on stderr
+        #[arg(long, requires = "out")]
+        prove: bool,
+        /// Output file (stdout if omitted)
+        #[arg(long)]
+        out: Option<PathBuf>
+    },
+    /// Write the round-by-round trace fixture: every intermediate Poseidon and Rescue-Prime state for the input (0, 1,
+    /// 2), preset parameters
+    GenFixtures {
+        /// Output file (tests/data/trace.json if omitted)
+        #[arg(long)]
+        out: Option<PathBuf>
+    },
+    /// Check the setup and print a report for bug reports: parameter invariants, constants round-trips, native vs
+    /// circuit equivalence, vk determinism and the environment; every check runs even after a failure
+    Doctor,
+    /// Soundness audit: strip each gate of both permutation chips in turn, run forged witnesses against the full and the
+    /// stripped constraint systems, and print which forgeries each gate alone stops as JSON; fails if a gate stops none
+    Audit,
+    /// Write the Poseidon and Rescue-Prime test vectors (preset parameters) as JSON
+    Vectors {
+        /// Output file (tests/data/vectors.json if omitted)
+        #[arg(long)]
+        out: Option<PathBuf>
     }
 }
 
-// implementation of the Circuit trait for the Poseidon Circuit
-impl<F: PrimeField> Circuit<F> for PoseidonCircuit<F> {
-    type Config = PoseidonChipConfig<F>;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
-
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
-        let fixed = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
-        let instance = meta.instance_column();
-        
-        let common_params = get_common_params();
-        let permutation_params = Poseidon {
-            common_params,
-            partial_rounds: 57 as usize,
-            full_rounds: 8 as usize,
-            n: 195 as usize,
-            alpha: F::from(5),
-            mds: 
-            [
-                [
-                    F::from_str_vartime("27854988750630959170337239780597144027224715023811960992659706878268355039181").unwrap(), 
-                    F::from_str_vartime("25146695260744508059100624982461970690166157722474767565243652164077487269055").unwrap(), 
-                    F::from_str_vartime("20045359041216123667749848881863965260443684681509271093016182932435520519586").unwrap()
-                ],
-                [
-                    F::from_str_vartime("14489116502293865465195620705098702569149962166993518933952339786917836503875").unwrap(), 
-                    F::from_str_vartime("13125423966940654332711887575940116829944663267413330181877013057693186361539").unwrap(), 
-                    F::from_str_vartime("37781904496949962127477230973432217892379931214289750852498713884075794707207").unwrap()
-                ],
-                [
-                    F::from_str_vartime("13626913895298938265545264952401615832299228269982032679076937571883280705196").unwrap(),
-                    F::from_str_vartime("1961062001717124873779753860369853658060849384038305407377314938662537282272").unwrap(),
-                    F::from_str_vartime("39178371364179396693874733819376491076633720395229958100530484864695867731796").unwrap()
-                ]
-            ]
-        };
-        
-        PoseidonChip::configure(meta, advice, fixed, instance, permutation_params)
-    }
-
-    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
-        let chip = PoseidonChip::construct(config);
-        let result = chip.permute(
-            layouter.namespace(|| "poseidon_permutation"),
-            self.s0,
-            self.s1,
-            self.s2
-        )?;
-
-        chip.expose_as_public(layouter.namespace(|| "result_s0_ps"), Number(result[0].0.clone()), 0)?;
-        chip.expose_as_public(layouter.namespace(|| "result_s1_ps"), Number(result[1].0.clone()), 1)?;
-        chip.expose_as_public(layouter.namespace(|| "result_s2_ps"), Number(result[2].0.clone()), 2)?;
-        
-        Ok(())
+#[derive(Subcommand)]
+enum ParamsCommand {
+    /// Generate the commitment parameters for k and write them with a SHA-256 checksum
+    Generate {
+        #[arg(long, default_value_t = 16)]
+        k: u32,
+        #[arg(long)]
+        out: PathBuf
+    },
+    /// Print a constants preset as JSON: its source, field, round numbers, content hash, MDS matrix and round constants
+    Show {
+        /// Preset name, e.g. poseidon-bls381-t3, rescue-bls381-t3 or poseidon-bn254-t3-circom
+        #[arg(long)]
+        preset: String,
+        #[arg(long, value_enum, default_value = "decimal")]
+        format: ConstantsFormat
     }
 }
 
-// implementation of the Circuit trait for the Rescue-Prime Circuit
-impl<F: PrimeField> Circuit<F> for RescueCircuit<F> {
-    type Config = RescueChipConfig<F>;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
-
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
-        let fixed = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
-        let instance = meta.instance_column();
-        
-        let common_params = get_common_params();
-        let permutation_params = RescuePrime {
-            common_params,
-            rounds: 14,
-            alpha: F::from(5),
-            alpha_inv: BigUint::from_str("20974350070050476191779096203274386335076221000211055129041463479975432473805").unwrap(),
-            mds: 
-            [
-                [
-                    F::from_str_vartime("343").unwrap(), 
-                    F::from_str_vartime("52435875175126190479447740508185965837690552500527637822603658699938581184114").unwrap(), 
-                    F::from_str_vartime("57").unwrap() 
-                ],
-                [
-                    F::from_str_vartime("19551").unwrap(),
-                    F::from_str_vartime("52435875175126190479447740508185965837690552500527637822603658699938581162113").unwrap(), 
-                    F::from_str_vartime("2850").unwrap()
-                ],
-                [
-                    F::from_str_vartime("977550").unwrap(), 
-                    F::from_str_vartime("52435875175126190479447740508185965837690552500527637822603658699938580066914").unwrap(),
-                    F::from_str_vartime("140050").unwrap()
-                ]
-            ]
-        };
-        
-        RescueChip::configure(meta, advice, fixed, instance, permutation_params)
-    }
-
-    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
-        let chip = RescueChip::construct(config);
-        let result = chip.permute(
-            layouter.namespace(|| "rescue_permutation"),
-            self.s0,
-            self.s1,
-            self.s2
-        )?;
-
-        chip.expose_as_public(layouter.namespace(|| "result_s0_rs"), Number(result[0].0.clone()), 0)?;
-        chip.expose_as_public(layouter.namespace(|| "result_s1_rs"), Number(result[1].0.clone()), 1)?;
-        chip.expose_as_public(layouter.namespace(|| "result_s2_rs"), Number(result[2].0.clone()), 2)?;
-        
-        Ok(())
+// helper function to read `--output <format> <path>`, for the formats besides the JSON printed to stdout
+fn report_output(output: Option<Vec<String>>) -> Result<Option<ReportOutput>, BenchmarkError> {
+    match output.as_deref() {
+        None => Ok(None),
+        Some([format, path]) if format == "csv" => Ok(Some(ReportOutput::Csv(PathBuf::from(path)))),
+        Some([format, path]) if format == "markdown" => Ok(Some(ReportOutput::Markdown(PathBuf::from(path)))),
+        Some(other) => Err(BenchmarkError::Unsupported(format!("output format {:?}, only csv and markdown are available", other[0])))
     }
 }
 
-
 // main function
 fn main() {
-    use halo2_proofs::dev::MockProver;
-    use halo2curves::bls12381::Fr;
-
-    // input words per test case
-    let init_s0 = Fr::from(0);
-    let init_s1 = Fr::from(1);
-    let init_s2 = Fr::from(2);
-
-    // Poseidon circuit struct 
-    let circuit_ps = PoseidonCircuit {
-        s0: Value::known(init_s0),
-        s1: Value::known(init_s1),
-        s2: Value::known(init_s2),
-    };
-
-    let k: u32 = 10;
-    let expected_ps = vec![
-        Fr::from_str_vartime("18456658763349757341014058622209659766100673761449600566550821987295786346378").unwrap(),
-        Fr::from_str_vartime("37068251774887509885063625701815026138353041152735229476479055620962268601796").unwrap(),
-        Fr::from_str_vartime("26763157702141528937904191329664859174584798817251788852101947537759678822298").unwrap()
-    ];
-
-    // time the MockProver runtime for Poseidon in milliseconds - 30 iterations
-    for _ in 0..30 {
-        let start_ps = Instant::now();
-        let prover = MockProver::run(k, &circuit_ps, vec![expected_ps.clone()]).unwrap();
-        let duration_ps = start_ps.elapsed();
-
-        assert_eq!(prover.verify(), Ok(()));
-        println!("Poseidon MockProver time: {} ms", duration_ps.as_millis());
-    }
-    
-
-    // Rescue-Prime circuit struct
-    let circuit_rs = RescueCircuit {
-        s0: Value::known(init_s0),
-        s1: Value::known(init_s1),
-        s2: Value::known(init_s2)
+    let cli = Cli::parse();
+
+    // the guard is scoped so the trace is flushed before a possible exit, which skips destructors
+    let result = {
+        let _tracing = init_tracing(&cli);
+        let _parameters = set_parameter_source(cli.parameters.clone());
+        if let Some(srs) = cli.srs.clone() {
+            use_srs_file(srs);
+        }
+        let default_command = Command::Bench { output_mode: OutputMode::default(), transcript: TranscriptKind::default(), output: None, repeat: None, warmup: 0, check_mode: CheckMode::default(), verbose: false, stress: false };
+        let command = cli.command.unwrap_or(default_command);
+        let seed = cli.seed;
+        let threads = if cli.low_memory { low_memory_threads(cli.threads) } else { cli.threads };
+        with_low_memory(cli.low_memory, || with_threads(threads, || match command {
+            Command::Bench { stress: true, .. } => run_stress(),
+            Command::Bench { output_mode, transcript, output, repeat, warmup, check_mode, verbose, stress: false } => match report_output(output) {
+                Ok(output) => run_benchmark(output_mode, transcript, seed, output.as_ref(), repeat.map(|rounds| Repeat { rounds, warmup }), check_mode, verbose),
+                Err(error) => Err(error)
+            },
+            Command::ProvePreimage { digest, permutation, inputs, raw, endianness, k, prove } => {
+                prove_preimage(&digest, permutation, inputs.as_deref(), raw, endianness, k, prove, seed)
+            }
+            Command::Batch { sizes } => run_batch(&sizes, seed),
+            Command::Transcript { sizes, keyed } => run_transcript(&sizes, keyed, seed),
+            Command::Bytes { sizes } => run_bytes(&sizes, seed),
+            Command::PublicMessage { sizes } => run_public_message(&sizes, seed),
+            Command::Stream { outputs } => run_stream(&outputs, seed),
+            Command::Merkle { leaves_log2 } => run_merkle(leaves_log2, seed),
+            Command::DemoMembership { leaves_log2 } => run_demo_membership(leaves_log2, seed),
+            Command::VerifyBatch { proofs, write, k } => match write {
+                true => write_proofs(&proofs, k, seed).and_then(|()| run_verify_batch(&proofs)),
+                false => run_verify_batch(&proofs)
+            },
+            Command::Scaling { thread_counts, k } => run_scaling(&thread_counts, k, seed),
+            Command::Sweep { backends, ks, output_modes, floor_planners, max_parallel, prover_threads } => {
+                run_sweep(&backends, &ks, &output_modes, &floor_planners, max_parallel, prover_threads, seed)
+            }
+            Command::Params { action: ParamsCommand::Generate { k, out } } => generate_srs(k, &out),
+            Command::Params { action: ParamsCommand::Show { preset, format } } => show_preset(&preset, format),
+            Command::ExportParameters { out } => export_parameters(&out),
+            Command::Hash { inputs_file, permutation, endianness, lenient, prove, out } => {
+                hash_inputs_file(&inputs_file, permutation, endianness, lenient, prove, out.as_deref(), seed)
+            }
+            Command::GenFixtures { out } => write_fixtures(out.as_deref()),
+            Command::Doctor => run_doctor(),
+            Command::Audit => run_audit(),
+            Command::Vectors { out } => write_vectors(out.as_deref())
+        })).and_then(|result| result)
     };
 
-    let expected_rs = vec![
-        Fr::from_str_vartime("20837336434853470849910909576721791703386530727763098803394615300550680488910").unwrap(),
-        Fr::from_str_vartime("25771045850287316209319297577315389859184751579565922583267218707663223737221").unwrap(),
-        Fr::from_str_vartime("47778332175771177523183464148522719206884558815624567948365727904575578981390").unwrap()
-    ];
-
-    // time the MockProver runtime for Rescue-Prime in milliseconds - 30 iterations
-    for _ in 0..30 {
-        let start_rs = Instant::now();
-        let prover_1 = MockProver::run(k, &circuit_rs, vec![expected_rs.clone()]).unwrap();
-        let duration_rs = start_rs.elapsed();
-
-        assert_eq!(prover_1.verify(), Ok(()));
-        println!("Rescue-Prime MockProver time: {} ms", duration_rs.as_millis());
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
     }
-    
-}
\ No newline at end of file
+}
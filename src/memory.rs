@@ -0,0 +1,107 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Heap accounting for the memory measurements: the command line tool installs PeakAllocator as its global allocator,
+// which forwards to the system allocator and counts the live bytes and their high-water mark. The counts are
+// process-wide, so a measurement includes whatever other threads allocate meanwhile. Without the allocator installed
+// (the library on its own, the examples) nothing is counted and measurements are None.
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+// Low-memory mode (`--low-memory`) trades proving time for a smaller peak: commitment parameters are kept for one k at
+// a time, the proving key is dropped once every proof is made and the proofs are verified against the verifying key
+// alone, a quick check evaluates the witness cell by cell instead of into 2^k-row columns and skips MockProver's
+// second run at the measured k, and the prover's pool runs on LOW_MEMORY_THREADS threads unless --threads says
+// otherwise. halo2 0.3 keeps the proving key whole through proving, so its extended-domain polynomials still peak
+// there.
+
+// prover threads in low-memory mode without --threads; each thread of the FFTs and MSMs holds a working set of its own
+pub const LOW_MEMORY_THREADS: usize = 2;
+
+thread_local! {
+    // whether the proving paths on this thread run in low-memory mode (see with_low_memory)
+    static LOW_MEMORY: Cell<bool> = const { Cell::new(false) };
+}
+
+// system allocator that counts live heap bytes and their peak
+pub struct PeakAllocator;
+
+// helper function to count `size` more live bytes
+fn grow(size: usize) {
+    let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+// SAFETY: every call is forwarded to the system allocator unchanged; the counters do not touch the memory
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            match new_size >= layout.size() {
+                true => grow(new_size - layout.size()),
+                false => {
+                    LIVE.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+                }
+            }
+        }
+        new_ptr
+    }
+}
+
+// helper function to run `f` and measure the most heap bytes live during it beyond those live before it, or None if
+// PeakAllocator is not the global allocator
+pub(crate) fn peak_heap<T>(f: impl FnOnce() -> T) -> (T, Option<usize>) {
+    let baseline = LIVE.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let result = f();
+    let peak = PEAK.load(Ordering::Relaxed).saturating_sub(baseline);
+    (result, (baseline > 0).then_some(peak))
+}
+
+// the heap bytes live right now, or None if PeakAllocator is not the global allocator
+pub fn live_heap() -> Option<usize> {
+    let live = LIVE.load(Ordering::Relaxed);
+    (live > 0).then_some(live)
+}
+
+// whether the proving paths on this thread run in low-memory mode
+pub(crate) fn low_memory() -> bool {
+    LOW_MEMORY.with(Cell::get)
+}
+
+// run `f` in low-memory mode, or out of it, on this thread; with_threads carries the mode onto its pool
+pub fn with_low_memory<T>(low_memory: bool, f: impl FnOnce() -> T) -> T {
+    let previous = LOW_MEMORY.with(|active| active.replace(low_memory));
+    let result = f();
+    LOW_MEMORY.with(|active| active.set(previous));
+    result
+}
+
+// the prover threads for `--threads` in low-memory mode: as given, or LOW_MEMORY_THREADS; a build without multicore
+// proves on one thread regardless
+pub fn low_memory_threads(threads: Option<usize>) -> Option<usize> {
+    threads.or(cfg!(feature = "multicore").then_some(LOW_MEMORY_THREADS))
+}
@@ -0,0 +1,429 @@
+use ff::PrimeField;
+use num_bigint::BigUint;
+use std::ops::{Add, Mul};
+
+use crate::error::BenchmarkError;
+use crate::params::{Anemoi, ArcPosition, Bars, BarsRound, Poseidon, RescuePrime, WidePoseidon, WideRescue};
+
+// helper function for the MDS multiplication out of circuit, with the same orientation as the ML gate; the chips'
+// witness paths use it too, on the matrix their config holds
+pub(crate) fn mds_mul_native<F: PrimeField>(mds: &[[F; 3]; 3], state: &[F; 3]) -> [F; 3] {
+    std::array::from_fn(|i| state[0] * mds[i][0] + state[1] * mds[i][1] + state[2] * mds[i][2])
+}
+
+// helper function for the inverse of a 3x3 MDS matrix through its cofactors, None if the matrix is singular
+pub(crate) fn mds_inverse<F: PrimeField>(mds: &[[F; 3]; 3]) -> Option<[[F; 3]; 3]> {
+    let cofactor = |i: usize, j: usize| {
+        mds[(i + 1) % 3][(j + 1) % 3] * mds[(i + 2) % 3][(j + 2) % 3] - mds[(i + 1) % 3][(j + 2) % 3] * mds[(i + 2) % 3][(j + 1) % 3]
+    };
+    let determinant: F = (0..3).map(|j| mds[0][j] * cofactor(0, j)).sum();
+    let determinant_inv = determinant.invert().into_option()?;
+
+    Some(std::array::from_fn(|i| std::array::from_fn(|j| cofactor(j, i) * determinant_inv)))
+}
+
+// trait for the native (out-of-circuit) permutations, used to compute expected public values
+pub trait NativePermutation<F: PrimeField> {
+    fn permute_native(&self, state: [F; 3]) -> [F; 3];
+
+    // capacity value used by the compression function
+    fn domain_tag(&self) -> F;
+
+    // native counterpart of PermutationInstructions::hash
+    fn hash_native(&self, inputs: [F; 2]) -> F {
+        self.permute_native([inputs[0], inputs[1], self.domain_tag()])[0]
+    }
+}
+
+impl<F: PrimeField> NativePermutation<F> for Poseidon<F> {
+    // same round schedule as the chip: RF/2 full rounds, RP partial rounds, RF/2 full rounds, each adding its
+    // constants before or after the S-box as `arc` says
+    fn permute_native(&self, mut state: [F; 3]) -> [F; 3] {
+        let sbox = |a: F| self.sbox.apply(a);
+        let add_constants = |state: &mut [F; 3], round: usize| {
+            for (i, word) in state.iter_mut().enumerate() {
+                *word += self.round_constants[3 * round + i];
+            }
+        };
+
+        for round in 0..(self.full_rounds + self.partial_rounds) {
+            if self.arc == ArcPosition::BeforeSbox {
+                add_constants(&mut state, round);
+            }
+
+            if self.is_full_round(round) {
+                state = state.map(sbox);
+            } else {
+                state[0] = sbox(state[0]);
+            }
+
+            if self.arc == ArcPosition::AfterSbox {
+                add_constants(&mut state, round);
+            }
+            state = mds_mul_native(&self.mds, &state);
+        }
+
+        state
+    }
+
+    fn domain_tag(&self) -> F {
+        self.domain_tag.value()
+    }
+}
+
+impl<F: PrimeField> NativePermutation<F> for RescuePrime<F> {
+    // each round: x^alpha, MDS, constants, x^(1/alpha), MDS, constants
+    fn permute_native(&self, mut state: [F; 3]) -> [F; 3] {
+        let m = self.common_params.state_size;
+        let alpha_inv = self.alpha_inv.to_u64_digits();
+        let add_constants = |state: &mut [F; 3], base: usize| {
+            for (i, word) in state.iter_mut().enumerate() {
+                *word += self.round_constants[base + i];
+            }
+        };
+
+        for round in 0..self.rounds {
+            state = state.map(|a| a.pow_vartime([self.alpha]));
+            state = mds_mul_native(&self.mds, &state);
+            add_constants(&mut state, 2 * round * m);
+            state = state.map(|a| a.pow_vartime(&alpha_inv));
+            state = mds_mul_native(&self.mds, &state);
+            add_constants(&mut state, 2 * round * m + m);
+        }
+
+        state
+    }
+
+    fn domain_tag(&self) -> F {
+        self.domain_tag.value()
+    }
+}
+
+impl<F: PrimeField> RescuePrime<F> {
+    // the inverse of permute_native, for building witnesses backwards from a target state: the rounds in reverse, each
+    // subtracting its constants, applying the inverse MDS matrix and inverting the S-box of its half
+    pub fn permute_inverse_native(&self, mut state: [F; 3]) -> [F; 3] {
+        let m = self.common_params.state_size;
+        let alpha_inv = self.alpha_inv.to_u64_digits();
+        let sub_constants = |state: &mut [F; 3], base: usize| {
+            for (i, word) in state.iter_mut().enumerate() {
+                *word -= self.round_constants[base + i];
+            }
+        };
+
+        for round in (0..self.rounds).rev() {
+            sub_constants(&mut state, 2 * round * m + m);
+            state = mds_mul_native(&self.mds_inv, &state);
+            state = state.map(|a| a.pow_vartime([self.alpha]));
+            sub_constants(&mut state, 2 * round * m);
+            state = mds_mul_native(&self.mds_inv, &state);
+            state = state.map(|a| a.pow_vartime(&alpha_inv));
+        }
+
+        state
+    }
+}
+
+// one step of a round-by-round trace of a t = 3 permutation: the round, the step applied in it, and the state after it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TraceStep<F> {
+    pub(crate) round: usize,
+    pub(crate) step: &'static str,
+    pub(crate) state: [F; 3]
+}
+
+impl<F: PrimeField> Poseidon<F> {
+    // the states after the ARC, S-box and MDS steps of every round (the S-box first after the S-box convention), in the
+    // order of permute_native, whose output is the last state; 3 (R_F + R_P) steps in all
+    pub(crate) fn trace_native(&self, mut state: [F; 3]) -> Vec<TraceStep<F>> {
+        let mut steps = Vec::with_capacity(3 * (self.full_rounds + self.partial_rounds));
+        for round in 0..(self.full_rounds + self.partial_rounds) {
+            let arc = |state: &mut [F; 3], steps: &mut Vec<TraceStep<F>>| {
+                for (i, word) in state.iter_mut().enumerate() {
+                    *word += self.round_constants[3 * round + i];
+                }
+                steps.push(TraceStep { round, step: "arc", state: *state });
+            };
+            if self.arc == ArcPosition::BeforeSbox {
+                arc(&mut state, &mut steps);
+            }
+
+            if self.is_full_round(round) {
+                state = state.map(|a| self.sbox.apply(a));
+            } else {
+                state[0] = self.sbox.apply(state[0]);
+            }
+            steps.push(TraceStep { round, step: "sbox", state });
+
+            if self.arc == ArcPosition::AfterSbox {
+                arc(&mut state, &mut steps);
+            }
+            state = mds_mul_native(&self.mds, &state);
+            steps.push(TraceStep { round, step: "mds", state });
+        }
+
+        steps
+    }
+
+    // the same permutation with its constants after the S-box, as implementations that fold the round constants
+    // schedule it (circomlib's optimized template, neptune's default hash mode): the first round's constants, which
+    // are added to the input, and parameters whose round r adds M^-1 times round r + 1's constants after its S-box,
+    // and nothing after the last one. permute_native(x) = folded.permute_native(x + first)
+    pub(crate) fn after_sbox_form(&self) -> Result<([F; 3], Poseidon<F>), BenchmarkError> {
+        if self.arc != ArcPosition::BeforeSbox {
+            return Err(BenchmarkError::ParameterMismatch("the Poseidon round constants are already added after the S-box".to_string()));
+        }
+        let mds_inv = mds_inverse(&self.mds)
+            .ok_or_else(|| BenchmarkError::ParameterMismatch("the Poseidon MDS matrix is singular".to_string()))?;
+        let rounds: Vec<[F; 3]> = self.round_constants.chunks_exact(3).map(|words| [words[0], words[1], words[2]]).collect();
+        let round_constants = rounds[1..].iter()
+            .flat_map(|constants| mds_mul_native(&mds_inv, constants))
+            .chain([F::ZERO; 3])
+            .collect();
+
+        Ok((rounds[0], Poseidon { arc: ArcPosition::AfterSbox, round_constants, ..self.clone() }))
+    }
+}
+
+impl<F: PrimeField> RescuePrime<F> {
+    // the states after the six steps of every round (x^alpha, MDS, constants, x^(1/alpha), MDS, constants), in the order
+    // of permute_native, whose output is the last state
+    pub(crate) fn trace_native(&self, mut state: [F; 3]) -> Vec<TraceStep<F>> {
+        let m = self.common_params.state_size;
+        let alpha_inv = self.alpha_inv.to_u64_digits();
+        let mut steps = Vec::with_capacity(6 * self.rounds);
+        for round in 0..self.rounds {
+            for (half, step) in [(0, ["sbox", "mds", "arc"]), (1, ["sbox_inv", "mds_inv", "arc_inv"])] {
+                state = match half {
+                    0 => state.map(|a| a.pow_vartime([self.alpha])),
+                    _ => state.map(|a| a.pow_vartime(&alpha_inv))
+                };
+                steps.push(TraceStep { round, step: step[0], state });
+                state = mds_mul_native(&self.mds, &state);
+                steps.push(TraceStep { round, step: step[1], state });
+                for (i, word) in state.iter_mut().enumerate() {
+                    *word += self.round_constants[2 * round * m + half * m + i];
+                }
+                steps.push(TraceStep { round, step: step[2], state });
+            }
+        }
+
+        steps
+    }
+}
+
+// helper function for the Anemoi linear layer on field elements and on gate expressions alike: x <- M x and
+// y <- M (y rotated left by one word), then the Pseudo-Hadamard transform y <- y + x, x <- x + y
+pub(crate) fn anemoi_linear_layer<F, T, const L: usize>(mds: &[[F; L]; L], x: [T; L], y: [T; L]) -> ([T; L], [T; L])
+where
+    F: PrimeField,
+    T: Clone + Add<Output = T> + Mul<F, Output = T>
+{
+    let mul = |v: &[T; L]| -> [T; L] {
+        std::array::from_fn(|i| {
+            (1..L).fold(v[0].clone() * mds[i][0], |acc, j| acc + v[j].clone() * mds[i][j])
+        })
+    };
+    let x = mul(&x);
+    let y = mul(&std::array::from_fn(|i| y[(i + 1) % L].clone()));
+    let y: [T; L] = std::array::from_fn(|i| y[i].clone() + x[i].clone());
+    let x = std::array::from_fn(|i| x[i].clone() + y[i].clone());
+
+    (x, y)
+}
+
+impl<F: PrimeField, const L: usize> Anemoi<F, L> {
+    // open Flystel on one (x, y) pair: x <- x - Q_gamma(y), y <- y - x^(1/alpha), x <- x + Q_delta(y)
+    fn flystel(&self, x: F, y: F) -> (F, F) {
+        let x = x - self.beta * y.square();
+        let y = y - x.pow_vartime(self.alpha_inv.to_u64_digits());
+        (x + self.beta * y.square() + self.delta, y)
+    }
+
+    // states after each round and after the final linear layer, starting with the input: rounds + 2 states, one per
+    // row of the chip's region
+    pub(crate) fn trace_native(&self, x: [F; L], y: [F; L]) -> Vec<([F; L], [F; L])> {
+        let mut states = vec![(x, y)];
+        for round in 0..self.rounds {
+            let (x, y) = states[round];
+            let x = std::array::from_fn(|i| x[i] + self.c[round][i]);
+            let y = std::array::from_fn(|i| y[i] + self.d[round][i]);
+            let (x, y) = anemoi_linear_layer(&self.mds, x, y);
+            let pairs: [(F, F); L] = std::array::from_fn(|i| self.flystel(x[i], y[i]));
+            states.push((pairs.map(|pair| pair.0), pairs.map(|pair| pair.1)));
+        }
+        let (x, y) = states[self.rounds];
+        states.push(anemoi_linear_layer(&self.mds, x, y));
+
+        states
+    }
+
+    // each round: constants, linear layer, open Flystel on every pair; the linear layer is applied once more at the end
+    pub(crate) fn permute_native(&self, x: [F; L], y: [F; L]) -> ([F; L], [F; L]) {
+        *self.trace_native(x, y).last().expect("the trace starts with the input")
+    }
+}
+
+// one limb row of a bar: the input and output limbs, both words recomposed up to this limb, whether the input limbs
+// so far equal the digits of p - 1, and the inverse of (digit - input limb) the equality is decided with (0 if equal)
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BarLimb<F> {
+    pub(crate) input: u64,
+    pub(crate) output: u64,
+    pub(crate) input_acc: F,
+    pub(crate) output_acc: F,
+    pub(crate) equal: F,
+    pub(crate) inverse: F
+}
+
+impl<F: PrimeField> Bars<F> {
+    // the limb S-box: a^limb_alpha mod limb_prime below limb_prime, identity above
+    pub(crate) fn limb_sbox(&self, limb: u64) -> u64 {
+        match limb < self.limb_prime {
+            true => (0..self.limb_alpha).fold(1, |power, _| power * limb % self.limb_prime),
+            false => limb
+        }
+    }
+
+    // whether the limb S-box is applied at limb `index`, where the digit of p - 1 is at least the limb prime
+    pub(crate) fn active(&self, index: usize) -> bool {
+        self.digits[index] >= self.limb_prime
+    }
+
+    // the limbs of a word, most significant first
+    pub(crate) fn decompose(&self, word: F) -> Vec<u64> {
+        let value = BigUint::from_bytes_le(word.to_repr().as_ref());
+        let mask = BigUint::from((1u64 << self.bits) - 1);
+        (0..self.limbs).rev().map(|i| ((&value >> (self.bits * i)) & &mask).iter_u64_digits().next().unwrap_or(0)).collect()
+    }
+
+    // the limb rows of a bar over the given input limbs, which need not be the canonical ones of any word
+    pub(crate) fn bar_rows(&self, limbs: &[u64]) -> Vec<BarLimb<F>> {
+        let shift = F::from(1 << self.bits);
+        let (mut input_acc, mut output_acc, mut equal) = (F::ZERO, F::ZERO, F::ONE);
+        limbs.iter().enumerate().map(|(index, input)| {
+            let output = if self.active(index) { self.limb_sbox(*input) } else { *input };
+            let difference = F::from(self.digits[index]) - F::from(*input);
+            let inverse = difference.invert().unwrap_or(F::ZERO);
+            input_acc = input_acc * shift + F::from(*input);
+            output_acc = output_acc * shift + F::from(output);
+            equal *= F::ONE - difference * inverse;
+            BarLimb { input: *input, output, input_acc, output_acc, equal, inverse }
+        }).collect()
+    }
+
+    // the bar of one word
+    pub(crate) fn bar(&self, word: F) -> F {
+        self.bar_rows(&self.decompose(word)).last().expect("a word has limbs").output_acc
+    }
+
+    // states before each round and after the last, starting with the input
+    pub(crate) fn trace_native(&self, state: [F; 3]) -> Vec<[F; 3]> {
+        let mut states = vec![state];
+        for (round, constants) in self.rounds.iter().zip(&self.round_constants) {
+            let state: [F; 3] = std::array::from_fn(|i| states[states.len() - 1][i] + constants[i]);
+            let state = match round {
+                BarsRound::Algebraic => state.map(|word| word.pow_vartime([self.alpha])),
+                BarsRound::Bar => state.map(|word| self.bar(word))
+            };
+            states.push(mds_mul_native(&self.mds, &state));
+        }
+
+        states
+    }
+
+    // each round: constants, the S-boxes of the round, MDS matrix
+    pub(crate) fn permute_native(&self, state: [F; 3]) -> [F; 3] {
+        *self.trace_native(state).last().expect("the trace starts with the input")
+    }
+}
+
+// helper function for the width-T MixLayer, oriented like mds_mul_native; the gates use matrix_mul_exprs
+pub(crate) fn wide_mds_mul<F: PrimeField, const T: usize>(mds: &[[F; T]; T], state: &[F; T]) -> [F; T] {
+    std::array::from_fn(|i| (1..T).fold(state[0] * mds[i][0], |acc, j| acc + state[j] * mds[i][j]))
+}
+
+impl<F: PrimeField, const T: usize> WidePoseidon<F, T> {
+    pub(crate) fn is_full_round(&self, round: usize) -> bool {
+        let first_partial = self.full_rounds / 2;
+        !(first_partial..first_partial + self.partial_rounds).contains(&round)
+    }
+
+    // states after each ARC and S-box step and each MixLayer, starting with the input: one per row of the wide chip's
+    // region, 1 + 2 (R_F + R_P) in all
+    pub(crate) fn trace_native(&self, input: [F; T]) -> Vec<[F; T]> {
+        let mut states = vec![input];
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            let mut state = *states.last().expect("the trace starts with the input");
+            for (word, constant) in state.iter_mut().zip(constants) {
+                *word += constant;
+            }
+            let words = if self.is_full_round(round) { T } else { 1 };
+            for word in state.iter_mut().take(words) {
+                *word = word.pow_vartime([5]);
+            }
+            states.push(state);
+            states.push(wide_mds_mul(&self.mds, &state));
+        }
+
+        states
+    }
+}
+
+impl<F: PrimeField, const T: usize> WideRescue<F, T> {
+    // states after each S-box, MixLayer and constants step of every round, starting with the input: one per row of
+    // the wide chip's region, 1 + 4 per round in all
+    pub(crate) fn trace_native(&self, input: [F; T]) -> Vec<[F; T]> {
+        let alpha_inv = self.alpha_inv.to_u64_digits();
+        let mix = |state: [F; T], constants: &[F; T]| -> [F; T] {
+            let state = wide_mds_mul(&self.mds, &state);
+            std::array::from_fn(|i| state[i] + constants[i])
+        };
+
+        let mut states = vec![input];
+        for constants in self.round_constants.chunks(2) {
+            let state = *states.last().expect("the trace starts with the input");
+            let state = state.map(|word| word.pow_vartime([self.alpha]));
+            states.push(state);
+            let state = mix(state, &constants[0]);
+            states.push(state);
+            let state = state.map(|word| word.pow_vartime(&alpha_inv));
+            states.push(state);
+            states.push(mix(state, &constants[1]));
+        }
+
+        states
+    }
+}
+
+// helper function for the levels of a Merkle tree of the given arity, leaves first and the root last, with every node
+// the compression of its `arity` children in order
+pub(crate) fn merkle_levels<F: PrimeField>(
+    leaves: Vec<F>,
+    arity: usize,
+    compress: impl Fn(&[F]) -> F
+) -> Result<Vec<Vec<F>>, BenchmarkError> {
+    let mut levels = vec![leaves];
+    while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+        if !level.len().is_multiple_of(arity) {
+            return Err(BenchmarkError::ParameterMismatch(format!("{} nodes do not split into groups of {}", level.len(), arity)));
+        }
+        let parents = level.chunks(arity).map(&compress).collect();
+        levels.push(parents);
+    }
+
+    Ok(levels)
+}
+
+// helper function for the path of a leaf: per level, the node's position among its siblings and the siblings in the
+// order the path circuits take them, the pair mate first and then the other pair (for arity 2, the mate alone)
+pub(crate) fn merkle_path<F: PrimeField>(levels: &[Vec<F>], arity: usize, mut index: usize) -> Vec<(usize, Vec<F>)> {
+    levels[..levels.len() - 1].iter().map(|level| {
+        let (first, position) = (index - index % arity, index % arity);
+        let children = &level[first..first + arity];
+        let other = (position & !1) ^ 2;
+        let siblings = [position ^ 1, other, other + 1].into_iter().filter(|&child| child < arity).map(|child| children[child]).collect();
+        index /= arity;
+        (position, siblings)
+    }).collect()
+}
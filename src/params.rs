@@ -0,0 +1,940 @@
+use ff::PrimeField;
+use num_bigint::BigUint;
+use std::fmt::Debug;
+use std::str::FromStr;
+use clap::ValueEnum;
+use halo2curves::bls12381::Fr;
+use serde::{Deserialize, Serialize};
+
+use crate::error::BenchmarkError;
+use crate::constants::{POSEIDON_BLS381_T3, RESCUE_BLS381_T3, preset};
+use crate::generate::{anemoi_constants, anemoi_mds, anemoi_round_count, poseidon_grain, poseidon_inverse_round_numbers, rescue_constants, rescue_mds, rescue_round_count};
+use crate::native::mds_inverse;
+use crate::source::active_source;
+
+// structure for shared parameters for permutation functions
+#[derive(Clone, Debug)]
+pub(crate) struct PermutationParameters {
+    pub(crate) state_size: usize,
+    pub(crate) rate: usize,
+    pub(crate) capacity: usize 
+}
+
+// domain separation tag placed in the capacity element by the compression/sponge APIs
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum DomainTag<F: PrimeField> {
+    // fixed-length hashing with `output_len` output words, encoded as 2^64 + (output_len - 1)
+    FixedLength { output_len: usize },
+    // user-supplied constant
+    #[cfg_attr(not(test), allow(dead_code))]
+    Custom(F)
+}
+
+impl<F: PrimeField> DomainTag<F> {
+    // the field element written into the capacity slot
+    pub(crate) fn value(&self) -> F {
+        match self {
+            DomainTag::FixedLength { output_len } => F::from(1 << 32).square() + F::from(*output_len as u64 - 1),
+            DomainTag::Custom(tag) => *tag
+        }
+    }
+}
+
+// S-box of a Poseidon instance: the power map x^alpha, or the inverse map x^-1 with 0 mapped to 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SboxKind {
+    Power(u64),
+    Inverse
+}
+
+impl SboxKind {
+    // the exponent as written in parameter files and the report, -1 for the inverse map
+    pub(crate) fn from_exponent(exponent: i64) -> Result<Self, BenchmarkError> {
+        match exponent {
+            -1 => Ok(SboxKind::Inverse),
+            alpha if alpha >= 0 => Ok(SboxKind::Power(alpha as u64)),
+            other => Err(BenchmarkError::ParameterMismatch(format!("the S-box x^{} is not supported", other)))
+        }
+    }
+
+    pub(crate) fn exponent(&self) -> i64 {
+        match self {
+            SboxKind::Power(alpha) => *alpha as i64,
+            SboxKind::Inverse => -1
+        }
+    }
+
+    // the S-box out of circuit
+    pub(crate) fn apply<F: PrimeField>(&self, x: F) -> F {
+        match self {
+            SboxKind::Power(alpha) => x.pow_vartime([*alpha]),
+            SboxKind::Inverse => x.invert().unwrap_or(F::ZERO)
+        }
+    }
+}
+
+// where a Poseidon round adds its round constants: before the S-box, as in the Poseidon paper and every preset here,
+// or after the S-box and before the MDS matrix. The two agree only with different tables (see
+// Poseidon::after_sbox_form), so the same constants under the other convention give another permutation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArcPosition {
+    #[default]
+    BeforeSbox,
+    AfterSbox
+}
+
+impl ArcPosition {
+    pub(crate) fn is_before_sbox(&self) -> bool {
+        *self == ArcPosition::BeforeSbox
+    }
+}
+
+// structure for Poseidon specific permutation parameters
+#[derive(Clone, Debug)]
+pub struct Poseidon<F: PrimeField> {
+    pub(crate) common_params: PermutationParameters,
+    pub(crate) partial_rounds: usize,
+    pub(crate) full_rounds: usize,
+    pub(crate) n: usize,
+    pub(crate) sbox: SboxKind,
+    pub(crate) arc: ArcPosition,
+    pub(crate) mds: [[F; 3]; 3],
+    pub(crate) round_constants: Vec<F>,
+    pub(crate) domain_tag: DomainTag<F>
+}
+
+// structure for Rescue-Prime specific permutation parameters
+#[derive(Clone, Debug)]
+pub struct RescuePrime<F: PrimeField> {
+    pub(crate) common_params: PermutationParameters,
+    pub(crate) rounds: usize,
+    // the round count the security formula derives for this state shape and alpha (see rescue_round_count)
+    pub(crate) secure_rounds: usize,
+    pub(crate) alpha: u64,
+    pub(crate) alpha_inv: BigUint,
+    pub(crate) mds: [[F; 3]; 3],
+    // the inverse of mds, derived once by the builder for the inverse permutation
+    pub(crate) mds_inv: [[F; 3]; 3],
+    pub(crate) round_constants: Vec<F>,
+    pub(crate) domain_tag: DomainTag<F>
+}
+
+// structure for Anemoi parameters with l = L column pairs (state x_0..x_{l-1}, y_0..y_{l-1}); the open Flystel uses
+// Q_gamma(y) = beta y^2 + gamma and Q_delta(y) = beta y^2 + delta with beta = g, gamma = 0 and delta = g^-1 for the
+// field's multiplicative generator g
+#[derive(Clone, Debug)]
+pub(crate) struct Anemoi<F: PrimeField, const L: usize> {
+    pub(crate) rounds: usize,
+    pub(crate) alpha: u64,
+    pub(crate) alpha_inv: BigUint,
+    pub(crate) beta: F,
+    pub(crate) delta: F,
+    pub(crate) mds: [[F; L]; L],
+    // per round, added to the x and y words before the linear layer
+    pub(crate) c: Vec<[F; L]>,
+    pub(crate) d: Vec<[F; L]>
+}
+
+// round of the bar layer variant: x^5 on every word, or the bar layer, which splits every word into limbs and looks
+// each limb up in a small S-box table (Reinforced Concrete's Bars)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BarsRound {
+    Algebraic,
+    Bar
+}
+
+// structure for the bar layer variant over t = 3 words: algebraic rounds on both sides of the bar rounds, each round
+// adding its constants, applying its S-boxes and then the MDS matrix. A bar splits a word into `limbs` limbs of `bits`
+// bits, most significant first, and maps limb i through a -> a^limb_alpha mod limb_prime when it is below limb_prime
+// and digit i of p - 1 is at least limb_prime; every other limb is kept. Such a limb map keeps the order of a limb
+// against the digit, so a word below p stays below p and the bar is a permutation of F
+#[derive(Clone, Debug)]
+pub(crate) struct Bars<F: PrimeField> {
+    pub(crate) bits: usize,
+    pub(crate) limbs: usize,
+    // the digits of p - 1 in base 2^bits, most significant first
+    pub(crate) digits: Vec<u64>,
+    pub(crate) limb_prime: u64,
+    pub(crate) limb_alpha: u64,
+    // the S-box of the algebraic rounds
+    pub(crate) alpha: u64,
+    pub(crate) rounds: Vec<BarsRound>,
+    pub(crate) mds: [[F; 3]; 3],
+    // one row of three per round, added before its S-boxes
+    pub(crate) round_constants: Vec<[F; 3]>
+}
+
+// structure for a Poseidon instance of width T (x^5, capacity 1, rate T - 1) for the wide chip, e.g. t = 5 for 4-ary
+// Merkle trees; the round constants are one row of T per round
+#[derive(Clone, Debug)]
+pub(crate) struct WidePoseidon<F: PrimeField, const T: usize> {
+    pub(crate) full_rounds: usize,
+    pub(crate) partial_rounds: usize,
+    pub(crate) mds: [[F; T]; T],
+    pub(crate) round_constants: Vec<[F; T]>,
+    pub(crate) domain_tag: DomainTag<F>
+}
+
+// structure for a Rescue-Prime instance of width T (capacity 1) for the wide chip; the round constants are two rows of
+// T per round, one after each MixLayer
+#[derive(Clone, Debug)]
+pub(crate) struct WideRescue<F: PrimeField, const T: usize> {
+    pub(crate) rounds: usize,
+    pub(crate) alpha: u64,
+    pub(crate) alpha_inv: BigUint,
+    pub(crate) mds: [[F; T]; T],
+    pub(crate) round_constants: Vec<[F; T]>,
+    pub(crate) domain_tag: DomainTag<F>
+}
+
+// target security level in bits of both permutation instances
+pub(crate) const SECURITY_LEVEL: u32 = 128;
+
+// helper function to return common parameters struct
+pub(crate) fn get_common_params() -> PermutationParameters
+ {
+    let state_size: usize = 3;
+    let rate: usize = 2;
+    let capacity: usize = 1;
+
+    PermutationParameters {
+        state_size,
+        rate,
+        capacity
+    }
+}
+
+// helper function to read the modulus of F (PrimeField::MODULUS is big-endian hex)
+pub(crate) fn field_modulus<F: PrimeField>() -> BigUint {
+    BigUint::parse_bytes(F::MODULUS.trim_start_matches("0x").as_bytes(), 16).expect("PrimeField::MODULUS is hex")
+}
+
+// helper function to render a field element as a decimal string (PrimeField reprs are little-endian here)
+pub(crate) fn fe_to_decimal<F: PrimeField>(value: &F) -> String {
+    BigUint::from_bytes_le(value.to_repr().as_ref()).to_string()
+}
+
+// helper function to map an integer below p into F
+pub(crate) fn fe_from_biguint<F: PrimeField>(value: &BigUint) -> F {
+    value.to_bytes_be().iter().fold(F::ZERO, |acc, byte| acc * F::from(256) + F::from(u64::from(*byte)))
+}
+
+// helper function to check that a parsed constant is the one its source string writes: the element written back in
+// the source's notation must be the source string. Decimal constants are written without leading zeros ("0" for zero),
+// and hex constants as 0x and the fe_byte_len() big-endian bytes at full width, as circomlib publishes them, in either
+// case. Anything else (leading zeros, short hex, a value parse_fe would not read back as itself) is rejected rather
+// than silently accepted as some other element
+fn is_canonical<F: PrimeField>(value: &F, source: &str) -> bool {
+    let integer = BigUint::from_bytes_le(value.to_repr().as_ref());
+    match source.strip_prefix("0x") {
+        Some(hex) => hex.eq_ignore_ascii_case(&format!("{:0width$x}", integer, width = 2 * fe_byte_len::<F>())),
+        None => source == integer.to_string()
+    }
+}
+
+// helper function to parse a table of constants, decimal or full-width hex, rejecting anything that is not a
+// canonically written field element (see is_canonical)
+pub(crate) fn parse_constants<F: PrimeField, S: AsRef<str>>(table: &'static str, values: &[S]) -> Result<Vec<F>, BenchmarkError> {
+    values.iter().map(AsRef::as_ref).enumerate()
+        .map(|(index, value)| {
+            parse_fe(value).ok()
+                .filter(|parsed| is_canonical(parsed, value))
+                .ok_or_else(|| BenchmarkError::InvalidConstant { table, index, value: value.to_string() })
+        })
+        .collect()
+}
+
+// helper function to read one of the constant tables (or a parameter file section) in F. The tables are generated for
+// the BLS12-381 scalar field and are parsed strictly there; over any other field (Pasta, used for keygen and real proofs)
+// they are checked there all the same and then reduced mod p, which keeps the circuit shape but is not a vetted
+// parameter set for that field
+pub(crate) fn table_constants<F: PrimeField, S: AsRef<str>>(table: &'static str, values: &[S]) -> Result<Vec<F>, BenchmarkError> {
+    let modulus = field_modulus::<F>();
+    if modulus == field_modulus::<Fr>() {
+        return parse_constants(table, values);
+    }
+    parse_constants::<Fr, S>(table, values)?;
+
+    values.iter().map(AsRef::as_ref).enumerate()
+        .map(|(index, value)| {
+            parse_integer(value)
+                .map(|v| fe_from_biguint(&(v % &modulus)))
+                .ok_or_else(|| BenchmarkError::InvalidConstant { table, index, value: value.to_string() })
+        })
+        .collect()
+}
+
+// helper function for the inverse S-box exponent, alpha^-1 mod (p - 1)
+fn alpha_inverse<F: PrimeField>(alpha: u64) -> Result<BigUint, BenchmarkError> {
+    let order = field_modulus::<F>() - 1u32;
+    BigUint::from(alpha).modinv(&order).ok_or_else(|| {
+        BenchmarkError::ParameterMismatch(format!("x^{} is not a permutation, {} and p - 1 are not coprime", alpha, alpha))
+    })
+}
+
+// helper function to parse an MDS matrix given row by row (constant indices are row * 3 + column)
+pub(crate) fn parse_mds<F: PrimeField, S: AsRef<str>>(table: &'static str, rows: &[[S; 3]; 3]) -> Result<[[F; 3]; 3], BenchmarkError> {
+    let entries = table_constants(table, rows.as_flattened())?;
+    Ok(std::array::from_fn(|i| std::array::from_fn(|j| entries[3 * i + j])))
+}
+
+// helper function to return the Poseidon parameters used by the benchmark (POSEIDON^π, x^5, t = 3), taken from the
+// parameter source active on this thread
+pub fn poseidon_params<F: PrimeField>() -> Result<Poseidon<F>, BenchmarkError> {
+    active_source().poseidon()
+}
+
+// helper function to return the Poseidon instance with the inverse S-box x^-1: the round numbers from the paper's
+// bounds for x^-1 (see poseidon_inverse_round_numbers) and the Grain LFSR constants and MDS matrix for them
+pub(crate) fn poseidon_inverse_params<F: PrimeField>() -> Result<Poseidon<F>, BenchmarkError> {
+    let common_params = get_common_params();
+    let field_size = field_modulus::<F>().bits() as usize;
+    let (full_rounds, partial_rounds) = poseidon_inverse_round_numbers(common_params.state_size, field_size, SECURITY_LEVEL);
+    let (round_constants, mds) = poseidon_grain(full_rounds, partial_rounds, SboxKind::Inverse);
+
+    Poseidon::builder()
+        .full_rounds(full_rounds)
+        .partial_rounds(partial_rounds)
+        .sbox(SboxKind::Inverse)
+        .mds(mds)
+        .round_constants(round_constants)
+        .build()
+}
+
+// helper function to return the Rescue-Prime parameters used by the benchmark (Rescue-XLIX, m = 3), taken from the
+// parameter source active on this thread
+pub fn rescue_params<F: PrimeField>() -> Result<RescuePrime<F>, BenchmarkError> {
+    active_source().rescue()
+}
+
+// helper function to return the Anemoi parameters with l = L: the smallest alpha for which x^alpha permutes F, and
+// the round count and constants derived as in the reference implementation; there is no preset table to read
+pub(crate) fn anemoi_params<F: PrimeField, const L: usize>() -> Result<Anemoi<F, L>, BenchmarkError> {
+    let alpha = (3..=11).step_by(2).find(|alpha| check_alpha::<F>(*alpha).is_ok()).ok_or_else(|| {
+        BenchmarkError::Unsupported("Anemoi needs x^alpha to be a permutation for an odd alpha up to 11".to_string())
+    })?;
+    let rounds = anemoi_round_count(SECURITY_LEVEL, L, alpha)?;
+    let (c, d) = anemoi_constants::<F, L>(rounds, alpha);
+    let beta = F::MULTIPLICATIVE_GENERATOR;
+
+    Ok(Anemoi {
+        rounds,
+        alpha,
+        alpha_inv: check_alpha::<F>(alpha)?,
+        beta,
+        delta: beta.invert().expect("the generator is not zero"),
+        mds: anemoi_mds::<F, L>()?,
+        c,
+        d
+    })
+}
+
+// algebraic rounds before and after the bar rounds, and bar rounds, as in Reinforced Concrete's 3 Bricks, Bars, 3 Bricks
+pub(crate) const BARS_ALGEBRAIC_ROUNDS: usize = 3;
+pub(crate) const BARS_BAR_ROUNDS: usize = 1;
+
+// helper function to return the bar layer parameters with `bits`-bit limbs. The limb prime is the prime below 2^bits
+// that maximizes (active limbs) * log2(limb prime), the bits of p - 1 that pass through a nonlinear limb map, and
+// limb_alpha the smallest exponent from 3 up that permutes it. Round constants and MDS matrix come from the Grain LFSR
+// for the 7 rounds as full rounds. This is an experiment for the lookup cost, with no cryptanalysis behind the choices
+pub(crate) fn bars_params<F: PrimeField>(bits: usize) -> Result<Bars<F>, BenchmarkError> {
+    if !(2..=16).contains(&bits) {
+        return Err(BenchmarkError::Unsupported(format!("bar layer limbs of {} bits, the tables support 2 to 16", bits)));
+    }
+    let modulus = field_modulus::<F>();
+    let limbs = (modulus.bits() as usize).div_ceil(bits);
+    let mask = BigUint::from((1u64 << bits) - 1);
+    let p_minus_one = modulus - 1u32;
+    let digits: Vec<u64> = (0..limbs).rev()
+        .map(|i| ((&p_minus_one >> (bits * i)) & &mask).iter_u64_digits().next().unwrap_or(0))
+        .collect();
+
+    let is_prime = |n: u64| n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d));
+    let active = |prime: u64| digits.iter().filter(|digit| **digit >= prime).count();
+    let limb_prime = (3..1u64 << bits).filter(|n| is_prime(*n))
+        .max_by(|a, b| (active(*a) as f64 * (*a as f64).log2()).total_cmp(&(active(*b) as f64 * (*b as f64).log2())))
+        .ok_or_else(|| BenchmarkError::Unsupported(format!("no limb prime below 2^{}", bits)))?;
+    let gcd = |mut a: u64, mut b: u64| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+    let limb_alpha = (3..limb_prime).find(|alpha| gcd(*alpha, limb_prime - 1) == 1)
+        .ok_or_else(|| BenchmarkError::Unsupported(format!("no limb S-box exponent for the prime {}", limb_prime)))?;
+
+    let alpha = 5;
+    check_alpha::<F>(alpha)?;
+    let rounds: Vec<BarsRound> = [BarsRound::Algebraic; BARS_ALGEBRAIC_ROUNDS].into_iter()
+        .chain([BarsRound::Bar; BARS_BAR_ROUNDS])
+        .chain([BarsRound::Algebraic; BARS_ALGEBRAIC_ROUNDS])
+        .collect();
+    let (constants, mds) = poseidon_grain::<F, 3>(rounds.len(), 0, SboxKind::Power(alpha));
+
+    Ok(Bars {
+        bits,
+        limbs,
+        digits,
+        limb_prime,
+        limb_alpha,
+        alpha,
+        round_constants: constants.chunks(3).map(|row| [row[0], row[1], row[2]]).collect(),
+        rounds,
+        mds
+    })
+}
+
+// helper function to return the Poseidon instance of width T: x^5 with the round numbers of the Poseidon paper for a
+// 255-bit field and 128-bit security (R_F = 8, and R_P = 57 for t = 3, 60 for t = 5), and the Grain LFSR constants and
+// MDS matrix for them. There is no preset table for t = 5, so every parameter source gives the generated instance; for
+// t = 3 it is the instance of the generated source
+pub(crate) fn wide_poseidon_params<F: PrimeField, const T: usize>() -> Result<WidePoseidon<F, T>, BenchmarkError> {
+    let (full_rounds, partial_rounds) = match T {
+        3 => (8, 57),
+        5 => (8, 60),
+        _ => return Err(BenchmarkError::Unsupported(format!("Poseidon round numbers for t = {}, only t = 3 and 5 are implemented", T)))
+    };
+    let (round_constants, mds) = poseidon_grain::<F, T>(full_rounds, partial_rounds, SboxKind::Power(5));
+
+    Ok(WidePoseidon {
+        full_rounds,
+        partial_rounds,
+        mds,
+        round_constants: round_constants.chunks(T).map(|row| std::array::from_fn(|i| row[i])).collect(),
+        domain_tag: DomainTag::FixedLength { output_len: 1 }
+    })
+}
+
+// helper function to return the Rescue-Prime instance of width T: x^5, capacity 1, the round count of the security
+// formula for m = T, and the SHAKE256 constants and Vandermonde MDS matrix of the reference implementation
+pub(crate) fn wide_rescue_params<F: PrimeField, const T: usize>() -> Result<WideRescue<F, T>, BenchmarkError> {
+    let alpha = 5;
+    let rounds = rescue_round_count(T, 1, SECURITY_LEVEL, alpha);
+    let round_constants = rescue_constants::<F, T>(rounds, 1, SECURITY_LEVEL as usize);
+
+    Ok(WideRescue {
+        rounds,
+        alpha,
+        alpha_inv: check_alpha::<F>(alpha)?,
+        mds: rescue_mds::<F, T>()?,
+        round_constants: round_constants.chunks(T).map(|row| std::array::from_fn(|i| row[i])).collect(),
+        domain_tag: DomainTag::FixedLength { output_len: 1 }
+    })
+}
+
+// helper function to check the S-box exponent: the gates compute x^alpha for any alpha, but it has to be non-linear
+// and x^alpha has to be a permutation of F
+fn check_alpha<F: PrimeField>(alpha: u64) -> Result<BigUint, BenchmarkError> {
+    if alpha < 3 {
+        return Err(BenchmarkError::ParameterMismatch(format!("the S-box x^{} is not a non-linear permutation", alpha)));
+    }
+
+    alpha_inverse::<F>(alpha)
+}
+
+// builder for the Poseidon parameters: the round counts and the S-box are required, the state shape comes from the
+// shared parameters and the MDS matrix, round constants and their position in the round default to the
+// poseidon-bls381-t3 preset. With preset(), everything not set explicitly comes from the named preset instead
+#[derive(Clone, Debug)]
+pub struct PoseidonBuilder<F: PrimeField> {
+    preset: Option<&'static str>,
+    full_rounds: Option<usize>,
+    partial_rounds: Option<usize>,
+    sbox: Option<SboxKind>,
+    arc: Option<ArcPosition>,
+    mds: Option<[[F; 3]; 3]>,
+    round_constants: Option<Vec<F>>
+}
+
+impl<F: PrimeField> Poseidon<F> {
+    pub fn builder() -> PoseidonBuilder<F> {
+        PoseidonBuilder { preset: None, full_rounds: None, partial_rounds: None, sbox: None, arc: None, mds: None, round_constants: None }
+    }
+}
+
+impl<F: PrimeField> PoseidonBuilder<F> {
+    // take the round numbers, S-box and constants of a preset in constants.rs (by name) unless set explicitly
+    pub fn preset(mut self, name: &'static str) -> Self {
+        self.preset = Some(name);
+        self
+    }
+
+    pub fn full_rounds(mut self, full_rounds: usize) -> Self {
+        self.full_rounds = Some(full_rounds);
+        self
+    }
+
+    pub fn partial_rounds(mut self, partial_rounds: usize) -> Self {
+        self.partial_rounds = Some(partial_rounds);
+        self
+    }
+
+    // the power S-box x^alpha
+    pub fn alpha(self, alpha: u64) -> Self {
+        self.sbox(SboxKind::Power(alpha))
+    }
+
+    pub fn sbox(mut self, sbox: SboxKind) -> Self {
+        self.sbox = Some(sbox);
+        self
+    }
+
+    pub fn arc(mut self, arc: ArcPosition) -> Self {
+        self.arc = Some(arc);
+        self
+    }
+
+    pub fn mds(mut self, mds: [[F; 3]; 3]) -> Self {
+        self.mds = Some(mds);
+        self
+    }
+
+    pub fn round_constants(mut self, round_constants: Vec<F>) -> Self {
+        self.round_constants = Some(round_constants);
+        self
+    }
+
+    // derive n, then check the parameters against each other and against the chip
+    pub fn build(self) -> Result<Poseidon<F>, BenchmarkError> {
+        let named = self.preset.map(preset).transpose()?;
+        let full_rounds = self.full_rounds.or(named.map(|preset| preset.full_rounds))
+            .ok_or(BenchmarkError::MissingParameter("full_rounds"))?;
+        let partial_rounds = self.partial_rounds.or(named.map(|preset| preset.partial_rounds))
+            .ok_or(BenchmarkError::MissingParameter("partial_rounds"))?;
+        let sbox = self.sbox.or(named.map(|preset| SboxKind::Power(preset.alpha)))
+            .ok_or(BenchmarkError::MissingParameter("alpha"))?;
+        let constants = match named {
+            Some(named) => named,
+            None => preset(POSEIDON_BLS381_T3)?
+        };
+        let arc = self.arc.or(constants.arc).unwrap_or_default();
+        // x^-1 (with 0 -> 0) permutes every field
+        if let SboxKind::Power(alpha) = sbox {
+            check_alpha::<F>(alpha)?;
+        }
+
+        let common_params = get_common_params();
+        let params = Poseidon {
+            n: common_params.state_size * (full_rounds + partial_rounds),
+            common_params,
+            partial_rounds,
+            full_rounds,
+            sbox,
+            arc,
+            mds: match self.mds {
+                Some(mds) => mds,
+                None => constants.mds()?
+            },
+            round_constants: match self.round_constants {
+                Some(round_constants) => round_constants,
+                None => constants.round_constants()?
+            },
+            domain_tag: DomainTag::FixedLength { output_len: 1 }
+        };
+        params.validate()?;
+
+        Ok(params)
+    }
+}
+
+// builder for the Rescue-Prime parameters: the round count and alpha are required, alpha_inv is derived from the field
+// and the MDS matrix and round constants default to the rescue-bls381-t3 preset, or everything not set explicitly to
+// the preset named with preset(). A round count set with rounds() must reach the count from the security formula;
+// unchecked_rounds() is for benchmark-only settings below it
+#[derive(Clone, Debug)]
+pub struct RescuePrimeBuilder<F: PrimeField> {
+    preset: Option<&'static str>,
+    rounds: Option<usize>,
+    checked_rounds: bool,
+    alpha: Option<u64>,
+    mds: Option<[[F; 3]; 3]>,
+    round_constants: Option<Vec<F>>
+}
+
+impl<F: PrimeField> RescuePrime<F> {
+    pub fn builder() -> RescuePrimeBuilder<F> {
+        RescuePrimeBuilder { preset: None, rounds: None, checked_rounds: true, alpha: None, mds: None, round_constants: None }
+    }
+}
+
+impl<F: PrimeField> RescuePrimeBuilder<F> {
+    // take the round count, alpha and constants of a preset in constants.rs (by name) unless set explicitly
+    pub fn preset(mut self, name: &'static str) -> Self {
+        self.preset = Some(name);
+        self
+    }
+
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = Some(rounds);
+        self.checked_rounds = true;
+        self
+    }
+
+    pub fn unchecked_rounds(mut self, rounds: usize) -> Self {
+        self.rounds = Some(rounds);
+        self.checked_rounds = false;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: u64) -> Self {
+        self.alpha = Some(alpha);
+        self
+    }
+
+    pub fn mds(mut self, mds: [[F; 3]; 3]) -> Self {
+        self.mds = Some(mds);
+        self
+    }
+
+    pub fn round_constants(mut self, round_constants: Vec<F>) -> Self {
+        self.round_constants = Some(round_constants);
+        self
+    }
+
+    // derive alpha_inv, then check the parameters against each other and against the chip
+    pub fn build(self) -> Result<RescuePrime<F>, BenchmarkError> {
+        let named = self.preset.map(preset).transpose()?;
+        let rounds = self.rounds.or(named.map(|preset| preset.full_rounds)).ok_or(BenchmarkError::MissingParameter("rounds"))?;
+        let alpha = self.alpha.or(named.map(|preset| preset.alpha)).ok_or(BenchmarkError::MissingParameter("alpha"))?;
+        let constants = match named {
+            Some(named) => named,
+            None => preset(RESCUE_BLS381_T3)?
+        };
+        let alpha_inv = check_alpha::<F>(alpha)?;
+        let common_params = get_common_params();
+        let secure_rounds = rescue_round_count(common_params.state_size, common_params.capacity, SECURITY_LEVEL, alpha);
+        if self.checked_rounds && rounds < secure_rounds {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "Rescue-Prime with {} rounds is below the {} rounds of the security formula for {}-bit security with x^{}; \
+                 use unchecked rounds for a benchmark-only setting",
+                rounds, secure_rounds, SECURITY_LEVEL, alpha
+            )));
+        }
+
+        let mds = match self.mds {
+            Some(mds) => mds,
+            None => constants.mds()?
+        };
+        let mds_inv = validate_mds("Rescue-Prime", &mds)?;
+
+        let params = RescuePrime {
+            common_params,
+            rounds,
+            secure_rounds,
+            alpha,
+            alpha_inv,
+            mds,
+            mds_inv,
+            round_constants: match self.round_constants {
+                Some(round_constants) => round_constants,
+                None => constants.round_constants()?
+            },
+            domain_tag: DomainTag::FixedLength { output_len: 1 }
+        };
+        params.validate()?;
+
+        Ok(params)
+    }
+}
+
+// helper function to check the shared parameters against the fixed width (3) of the chips
+fn validate_common_params(params: &PermutationParameters) -> Result<(), BenchmarkError> {
+    if params.state_size != 3 || params.rate + params.capacity != params.state_size {
+        return Err(BenchmarkError::ParameterMismatch(format!(
+            "state size {} with rate {} and capacity {}, the chips require 3 = rate + capacity",
+            params.state_size, params.rate, params.capacity
+        )));
+    }
+
+    Ok(())
+}
+
+// helper function to reject a singular MDS matrix before a chip embeds it in its MixLayer gate: the linear layer would
+// not be a permutation, and the gate would pin the next row to fewer than three independent words. Returns the inverse
+fn validate_mds<F: PrimeField>(name: &str, mds: &[[F; 3]; 3]) -> Result<[[F; 3]; 3], BenchmarkError> {
+    mds_inverse(mds).ok_or_else(|| BenchmarkError::ParameterMismatch(format!("the {} MDS matrix is singular", name)))
+}
+
+impl<F: PrimeField> Poseidon<F> {
+    // parameters without rounds, for a chip whose parameters could not be loaded: configure cannot return an error in
+    // halo2 0.3, so the chip builds no round gates from these and its synthesis fails with the error it keeps
+    pub(crate) fn unconfigured() -> Self {
+        Poseidon {
+            common_params: get_common_params(),
+            partial_rounds: 0,
+            full_rounds: 0,
+            n: 0,
+            sbox: SboxKind::Power(5),
+            arc: ArcPosition::default(),
+            mds: [[F::ZERO; 3]; 3],
+            round_constants: Vec::new(),
+            domain_tag: DomainTag::FixedLength { output_len: 1 }
+        }
+    }
+
+    // check that the round counts and the constants table agree, that the full rounds split evenly and that the MDS
+    // matrix is invertible
+    pub(crate) fn validate(&self) -> Result<(), BenchmarkError> {
+        validate_common_params(&self.common_params)?;
+        validate_mds("Poseidon", &self.mds)?;
+        if !self.full_rounds.is_multiple_of(2) {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "the full rounds are split evenly around the partial rounds, {} is odd", self.full_rounds
+            )));
+        }
+        let needed = self.common_params.state_size * (self.full_rounds + self.partial_rounds);
+        if self.n != needed || self.round_constants.len() != needed {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "Poseidon with {} full and {} partial rounds needs {} round constants, n = {} and {} given",
+                self.full_rounds, self.partial_rounds, needed, self.n, self.round_constants.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    // the three round constants of `round`, at `index` in the flat table
+    pub(crate) fn round_constants_at(&self, round: usize, index: usize) -> Result<[F; 3], BenchmarkError> {
+        match self.round_constants.get(index..index + 3) {
+            Some(&[rc0, rc1, rc2]) => Ok([rc0, rc1, rc2]),
+            _ => Err(BenchmarkError::ParameterMismatch(format!(
+                "Poseidon round {} needs round constants {}..{}, the table has {}", round, index, index + 3, self.round_constants.len()
+            )))
+        }
+    }
+
+    // check that the rounds used up the whole constants table
+    pub(crate) fn check_constants_consumed(&self, consumed: usize) -> Result<(), BenchmarkError> {
+        if consumed != self.round_constants.len() {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "Poseidon rounds consumed {} of the {} round constants", consumed, self.round_constants.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    // the round schedule shared by the chip and the native permutations: R_F / 2 full rounds, the R_P partial rounds,
+    // then R_F / 2 full rounds again (an odd R_F is rejected by validate rather than split unevenly)
+    pub(crate) fn is_full_round(&self, round: usize) -> bool {
+        let first_partial = self.full_rounds / 2;
+        !(first_partial..first_partial + self.partial_rounds).contains(&round)
+    }
+
+    // the inverse of the MDS matrix, e.g. for moving round constants through the linear layer as the sparse partial
+    // round optimization does; computed on each call, since the Poseidon permutation does not need it
+    pub fn mds_inverse(&self) -> Result<[[F; 3]; 3], BenchmarkError> {
+        validate_mds("Poseidon", &self.mds)
+    }
+}
+
+impl<F: PrimeField> RescuePrime<F> {
+    // parameters without rounds, for a chip whose parameters could not be loaded (see Poseidon::unconfigured)
+    pub(crate) fn unconfigured() -> Self {
+        RescuePrime {
+            common_params: get_common_params(),
+            rounds: 0,
+            secure_rounds: 0,
+            alpha: 5,
+            alpha_inv: BigUint::default(),
+            mds: [[F::ZERO; 3]; 3],
+            mds_inv: [[F::ZERO; 3]; 3],
+            round_constants: Vec::new(),
+            domain_tag: DomainTag::FixedLength { output_len: 1 }
+        }
+    }
+
+    // check that the round count and the constants table agree, that alpha_inv inverts alpha and that the MDS matrix is
+    // invertible with mds_inv as its inverse
+    pub(crate) fn validate(&self) -> Result<(), BenchmarkError> {
+        validate_common_params(&self.common_params)?;
+        validate_mds("Rescue-Prime", &self.mds)?;
+        // like alpha_inv, the cached inverse goes stale if the matrix is edited by hand
+        let product: [[F; 3]; 3] = std::array::from_fn(|i| std::array::from_fn(|j| (0..3).map(|k| self.mds[i][k] * self.mds_inv[k][j]).sum()));
+        if product != std::array::from_fn(|i| std::array::from_fn(|j| if i == j { F::ONE } else { F::ZERO })) {
+            return Err(BenchmarkError::ParameterMismatch("the cached inverse of the Rescue-Prime MDS matrix does not invert it".to_string()));
+        }
+        let needed = 2 * self.common_params.state_size * self.rounds;
+        if self.round_constants.len() != needed {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "Rescue-Prime with {} rounds needs {} round constants, {} given",
+                self.rounds, needed, self.round_constants.len()
+            )));
+        }
+        // the inverse S-box exponent is derived rather than parsed, but a struct edited by hand may carry another one
+        let order = field_modulus::<F>() - 1u32;
+        if self.alpha_inv >= order || (&self.alpha_inv * self.alpha) % &order != BigUint::from(1u32) {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "alpha_inv {} is not the inverse of alpha = {} mod p - 1", self.alpha_inv, self.alpha
+            )));
+        }
+
+        Ok(())
+    }
+
+    // the inverse of the MDS matrix, as cached by the builder; the inverse permutation applies it
+    pub fn mds_inverse(&self) -> [[F; 3]; 3] {
+        self.mds_inv
+    }
+}
+
+// byte order of hex strings and byte arrays holding field elements
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Endianness {
+    // most significant byte first, as integers are usually written
+    #[default]
+    Big,
+    // least significant byte first, as PrimeField::to_repr() encodes BLS12-381 and Pasta scalars
+    Little
+}
+
+// helper function for the byte length of an encoded field element (32 for BLS12-381 and Pasta)
+pub(crate) fn fe_byte_len<F: PrimeField>() -> usize {
+    (field_modulus::<F>().bits() as usize).div_ceil(8)
+}
+
+// formats a field element as 0x-prefixed hex of fe_byte_len() bytes in the given order
+pub fn fe_to_hex<F: PrimeField>(value: &F, endianness: Endianness) -> String {
+    let mut bytes = value.to_repr().as_ref()[..fe_byte_len::<F>()].to_vec();
+    if endianness == Endianness::Big {
+        bytes.reverse();
+    }
+    format!("0x{}", bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+}
+
+// helper function to read a field element from bytes in the given order, rejecting non-canonical values >= p
+pub(crate) fn fe_from_bytes<F: PrimeField>(bytes: &[u8], endianness: Endianness) -> Result<F, BenchmarkError> {
+    let value = match endianness {
+        Endianness::Big => BigUint::from_bytes_be(bytes),
+        Endianness::Little => BigUint::from_bytes_le(bytes)
+    };
+    if value >= field_modulus::<F>() {
+        return Err(BenchmarkError::InvalidFieldElement(format!("{:#x} is not below the field modulus", value)));
+    }
+
+    Ok(fe_from_biguint(&value))
+}
+
+// parses a field element from hex, with or without 0x, whose bytes are in the given order, rejecting
+// non-canonical values >= p
+pub fn fe_from_hex<F: PrimeField>(s: &str, endianness: Endianness) -> Result<F, BenchmarkError> {
+    let invalid = || BenchmarkError::InvalidFieldElement(s.to_string());
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    // checked before slicing: a multi-byte character would split mid-character, and from_str_radix accepts a sign
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    // an odd number of digits only has an obvious meaning when the most significant byte comes first
+    let digits = match (digits.len() % 2, endianness) {
+        (0, _) => digits.to_string(),
+        (_, Endianness::Big) => format!("0{}", digits),
+        (_, Endianness::Little) => return Err(invalid())
+    };
+
+    let bytes = (0..digits.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid()))
+        .collect::<Result<Vec<u8>, BenchmarkError>>()?;
+    fe_from_bytes(&bytes, endianness)
+}
+
+// splits a byte array into the three state words, each fe_byte_len() bytes in the given order
+pub fn state_from_bytes<F: PrimeField>(bytes: &[u8], endianness: Endianness) -> Result<[F; 3], BenchmarkError> {
+    let len = fe_byte_len::<F>();
+    if bytes.len() != 3 * len {
+        return Err(BenchmarkError::ParameterMismatch(format!(
+            "a state is 3 words of {} bytes, {} bytes given", len, bytes.len()
+        )));
+    }
+
+    let words = bytes.chunks(len).map(|word| fe_from_bytes(word, endianness)).collect::<Result<Vec<F>, BenchmarkError>>()?;
+    Ok([words[0], words[1], words[2]])
+}
+
+// helper function to parse an input word: decimal, or 0x-prefixed hex in the given byte order
+pub(crate) fn parse_word<F: PrimeField>(s: &str, endianness: Endianness) -> Result<F, BenchmarkError> {
+    if s.starts_with("0x") {
+        fe_from_hex(s, endianness)
+    } else {
+        parse_fe(s)
+    }
+}
+
+// helper function to parse a field element from decimal or 0x-prefixed big-endian hex, rejecting values >= p
+pub(crate) fn parse_fe<F: PrimeField>(s: &str) -> Result<F, BenchmarkError> {
+    let invalid = || BenchmarkError::InvalidFieldElement(s.to_string());
+    let value = parse_integer(s).ok_or_else(invalid)?;
+    if value >= field_modulus::<F>() {
+        return Err(invalid());
+    }
+
+    F::from_str_vartime(&value.to_string()).ok_or_else(invalid)
+}
+
+// helper function to parse a non-negative integer from decimal or 0x-prefixed big-endian hex
+fn parse_integer(s: &str) -> Option<BigUint> {
+    match s.strip_prefix("0x") {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::from_str(s).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2curves::pasta::Fp;
+
+    use super::*;
+
+    // the hex and byte encodings of field elements: canonical values round-trip in both byte
+    // orders, p - 1 is accepted, and p or any larger 32-byte value is rejected rather than reduced
+    #[test]
+    fn encodings() -> Result<(), BenchmarkError> {
+        let p_minus_one = -Fr::ONE;
+        for endianness in [Endianness::Big, Endianness::Little] {
+            for value in [Fr::ZERO, Fr::ONE, Fr::from_u128(1 << 64), p_minus_one] {
+                let hex = fe_to_hex(&value, endianness);
+                assert_eq!(hex.len(), 2 + 2 * fe_byte_len::<Fr>());
+                assert_eq!(fe_from_hex::<Fr>(&hex, endianness)?, value);
+                assert_eq!(fe_from_hex::<Fr>(&hex[2..], endianness)?, value);
+            }
+            let pasta = -Fp::ONE;
+            assert_eq!(fe_from_hex::<Fp>(&fe_to_hex(&pasta, endianness), endianness)?, pasta);
+            assert!(fe_from_hex::<Fr>(&format!("0x{}", "ff".repeat(32)), endianness).is_err());
+        }
+        assert_eq!(fe_to_hex(&Fr::ONE, Endianness::Big), format!("0x{}01", "00".repeat(31)));
+        assert_eq!(fe_to_hex(&Fr::ONE, Endianness::Little), format!("0x01{}", "00".repeat(31)));
+        assert_eq!(fe_from_hex::<Fr>("0x5", Endianness::Big)?, Fr::from(5));
+        assert!(fe_from_hex::<Fr>("0x5", Endianness::Little).is_err());
+        assert!(fe_from_hex::<Fr>("0x", Endianness::Big).is_err());
+        assert!(fe_from_hex::<Fr>("0xzz", Endianness::Big).is_err());
+        assert!(fe_from_hex::<Fr>("0xé0", Endianness::Big).is_err());
+        assert!(fe_from_hex::<Fr>("0x+f+f", Endianness::Big).is_err());
+
+        // the modulus itself is the smallest rejected value
+        let modulus = field_modulus::<Fr>();
+        assert_eq!(fe_from_hex::<Fr>(&format!("{:#x}", &modulus - 1u32), Endianness::Big)?, p_minus_one);
+        assert!(matches!(
+            fe_from_hex::<Fr>(&format!("{:#x}", modulus), Endianness::Big),
+            Err(BenchmarkError::InvalidFieldElement(_))
+        ));
+
+        let state = [Fr::from(7), p_minus_one, Fr::ONE];
+        for endianness in [Endianness::Big, Endianness::Little] {
+            let mut bytes: Vec<u8> = Vec::new();
+            for word in &state {
+                let mut word = word.to_repr().as_ref().to_vec();
+                if endianness == Endianness::Big {
+                    word.reverse();
+                }
+                bytes.extend(word);
+            }
+            assert_eq!(state_from_bytes::<Fr>(&bytes, endianness)?, state);
+            assert!(matches!(state_from_bytes::<Fr>(&bytes[1..], endianness), Err(BenchmarkError::ParameterMismatch(_))));
+            bytes[32..64].fill(0xff);
+            assert!(matches!(state_from_bytes::<Fr>(&bytes, endianness), Err(BenchmarkError::InvalidFieldElement(_))));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,414 @@
+use std::marker::PhantomData;
+use ff::PrimeField;
+use std::fmt::Debug;
+use halo2_proofs::{
+    circuit::{AssignedCell, Region, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::chip::{Number, PermutationInstructions, PermutationChip, StateWord, UtilsInstructions, assign_word};
+use crate::error::BenchmarkError;
+use crate::gates::{
+    ChipOptions, CircuitParameters, ConstantColumns, FixedAssignment, GateConstants, assign_constant_schedule, check_sbox_layout, constant_schedule, create_arc_gates,
+    create_gate, create_mds_mul_gate, create_select_gate, create_is_equal_gate, allocate_columns, allocate_selector, sbox_constraints, sbox_square_columns,
+    squared_sbox_constraints
+};
+use crate::native::{NativePermutation, mds_mul_native};
+use crate::params::{ArcPosition, Poseidon, SboxKind, poseidon_params};
+use crate::synthesis::{check_region_rows, round_checkpoint};
+
+// Poseidon chip configuration
+#[derive(Clone, Debug)]
+pub struct PoseidonChipConfig<F: PrimeField> {
+    // the parameters the gates were built from: the witness path reads its MDS matrix and round constants from here,
+    // so a chip has one matrix, embedded in its MixLayer gate and applied to the witness alike
+    pub(crate) permutation_params: Poseidon<F>,
+    pub(crate) circuit_params: CircuitParameters,
+    // round constants by fixed cell, each round starts with its ARC and SubBytes row
+    pub(crate) constant_schedule: Vec<FixedAssignment<F>>,
+    pub(crate) _marker: PhantomData<F>,
+    // the below selectors are specific to Poseidon (Hades construction): ARC and SubBytes on all words or on state[0]
+    // by round, the full and the partial rounds each sharing one (a selector per round under the gate strategy)
+    pub(crate) s_rounds: Vec<Selector>,
+    pub(crate) s_mds_mul: Selector,
+    // the square of each S-box input, on the ARC and SubBytes row, under the squared S-box layout
+    pub(crate) squares: Option<[Column<Advice>; 3]>,
+    // why the parameters could not be used; the chip then has no round gates and its synthesis fails with this
+    pub(crate) error: Option<String>
+}
+
+// structure for the poseidon permutation chip
+pub struct PoseidonChip<F: PrimeField> {
+    pub(crate) config: PoseidonChipConfig<F>,
+    pub(crate) _marker: PhantomData<F>,
+}
+
+// implement the Chip trait for PoseidonChip
+impl<F: PrimeField> Chip<F> for PoseidonChip<F> {
+    type Config = PoseidonChipConfig<F>;
+    type Loaded = ();
+
+    // getter for the chip config
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    // getter for the loaded field
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// helper function for the S-box constraints of one state word, directly or with the input's square witnessed in
+// `square` on the same row (the squared layout, x^5 only)
+fn sbox_word_constraints<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    input: Expression<F>,
+    output: Expression<F>,
+    sbox: SboxKind,
+    square: Option<Column<Advice>>
+) -> Vec<Expression<F>> {
+    match square {
+        Some(square) => squared_sbox_constraints(input, meta.query_advice(square, Rotation::cur()), output),
+        None => sbox_constraints(input, output, sbox)
+    }
+}
+
+// helper function for the S-box constraints of one state word with its round constant: next = S(cur + rc) before the
+// S-box, next = S(cur) + rc after it
+fn arc_sbox_word_constraints<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    a: Expression<F>,
+    rc: Expression<F>,
+    a_next: Expression<F>,
+    sbox: SboxKind,
+    arc: ArcPosition,
+    square: Option<Column<Advice>>
+) -> Vec<Expression<F>> {
+    match arc {
+        ArcPosition::BeforeSbox => sbox_word_constraints(meta, a + rc, a_next, sbox, square),
+        ArcPosition::AfterSbox => sbox_word_constraints(meta, a, a_next - rc, sbox, square)
+    }
+}
+
+// helper functions for creating Poseidon specific gates; ARC and SubBytes share a row, next = S(cur + rc) (or
+// S(cur) + rc with the constants after the S-box), so a round takes two rows (this one and the MixLayer row) and the
+// MixLayer output is the next round's input row
+pub(crate) fn create_partial_round_gate_ps<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>,
+    advice: [Column<Advice>; 3],
+    squares: Option<[Column<Advice>; 3]>,
+    constants: GateConstants<F>,
+    s_sub_bytes_partial: Selector,
+    sbox: SboxKind,
+    arc: ArcPosition
+) {
+    create_gate(meta, "PS_partial_round_gate", |meta| {
+        let s_sub_bytes_partial = meta.query_selector(s_sub_bytes_partial);
+        let a = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+        let a_next = advice.map(|column| meta.query_advice(column, Rotation::next()));
+        let [rc0, rc1, rc2] = [0, 1, 2].map(|index| constants.query(meta, index));
+        let [a0, a1, a2] = a;
+        let [a0_next, a1_next, a2_next] = a_next;
+
+        // only state[0] goes through the S-box, the other words get their round constant and are carried over
+        let mut constraints = arc_sbox_word_constraints(meta, a0, rc0, a0_next, sbox, arc, squares.map(|squares| squares[0]));
+        constraints.extend([a1_next - (a1 + rc1), a2_next - (a2 + rc2)]);
+        constraints.into_iter().map(|constraint| s_sub_bytes_partial.clone() * constraint).collect()
+    });
+}
+
+pub(crate) fn create_full_round_gate_ps<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>,
+    advice: [Column<Advice>; 3],
+    squares: Option<[Column<Advice>; 3]>,
+    constants: GateConstants<F>,
+    s_sub_bytes_full: Selector,
+    sbox: SboxKind,
+    arc: ArcPosition
+) {
+    create_gate(meta, "PS_full_round_gate", |meta| {
+        let s_sub_bytes_full = meta.query_selector(s_sub_bytes_full);
+        (0..3)
+            .flat_map(|index| {
+                let a = meta.query_advice(advice[index], Rotation::cur());
+                let a_next = meta.query_advice(advice[index], Rotation::next());
+                let rc = constants.query(meta, index);
+                arc_sbox_word_constraints(meta, a, rc, a_next, sbox, arc, squares.map(|squares| squares[index]))
+            })
+            .map(|constraint| s_sub_bytes_full.clone() * constraint)
+            .collect()
+    });
+}
+
+// helper function to log why the witness path cannot continue; halo2's Error::Synthesis carries no message
+fn synthesis_error(error: BenchmarkError) -> Error {
+    tracing::error!("{}", error);
+    Error::Synthesis
+}
+
+// implementation of additional methods for the PoseidonChip
+impl<F: PrimeField> PoseidonChip<F> {
+    // constructor
+    pub(crate) fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        PoseidonChip { config, _marker: PhantomData}
+    }
+
+    // configure the chip including all gates, constraints, and selectors. Circuit::configure cannot return an error in
+    // halo2 0.3, so parameters that failed to load or that the active S-box layout cannot constrain leave the chip
+    // without round gates, with the error kept for synthesis to fail with; the public constructors check both first
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constants: ConstantColumns,
+        instance: Column<Instance>,
+        params: Result<Poseidon<F>, BenchmarkError>,
+        options: ChipOptions
+    ) -> <Self as Chip<F>>::Config {
+        let (params, error) = match params.and_then(|params| check_sbox_layout(params.sbox).map(|()| params)) {
+            Ok(params) => (params, None),
+            Err(error) => (Poseidon::unconfigured(), Some(error.to_string()))
+        };
+        // enable equality on the advice and instance columns and the round constant columns for global constants,
+        // unless a host circuit sharing the columns does it itself
+        options.enable(meta, advice, constants, instance);
+
+        let s_mds_mul = allocate_selector(meta);
+        let s_select = allocate_selector(meta);
+        let s_is_equal = allocate_selector(meta);
+        let squares = sbox_square_columns(meta);
+
+        // create gates and constraints
+        create_mds_mul_gate(meta, advice, s_mds_mul, &params.mds);
+        create_select_gate(meta, advice, s_select);
+        create_is_equal_gate(meta, advice, s_is_equal);
+        // the ARC and SubBytes gates, one for the full and one for the partial rounds or one per round
+        let total_rounds = params.full_rounds + params.partial_rounds;
+        let s_rounds = create_arc_gates(meta, constants, &params.round_constants, |round| params.is_full_round(round), |meta, full, constants, selector| {
+            if full {
+                create_full_round_gate_ps(meta, advice, squares, constants, selector, params.sbox, params.arc);
+            } else {
+                create_partial_round_gate_ps(meta, advice, squares, constants, selector, params.sbox, params.arc);
+            }
+        });
+
+        let circuit_params = CircuitParameters {
+            advice,
+            constants,
+            instance,
+            instance_offset: options.instance_offset,
+            s_select,
+            s_is_equal
+        };
+        
+        // every round (full or partial) takes two rows: ARC and SubBytes, MixLayer
+        let constant_schedule = constant_schedule(constants, &params.round_constants, (0..total_rounds).map(|round| 2 * round));
+
+        // return the config
+        PoseidonChipConfig {
+            permutation_params: params,
+            circuit_params,
+            constant_schedule,
+            _marker: PhantomData,
+            s_rounds,
+            s_mds_mul,
+            squares,
+            error
+        }
+    }
+}
+
+// implementation of the PermutationInstructions trait for the PoseidonChip
+impl<F: PrimeField> PermutationInstructions<F> for PoseidonChip<F> {
+    type Num = Number<F>;
+
+    fn expose_as_public(&self, mut layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error> {
+        let config = self.config();
+        layouter.constrain_instance(num.cell(), config.circuit_params.instance, config.circuit_params.instance_offset + row)
+    }
+
+    fn assert_output(&self, mut layouter: impl Layouter<F>, num: Self::Num, expected: F) -> Result<(), Error> {
+        layouter.assign_region(|| "assert_output", |mut region| region.constrain_constant(num.cell(), expected))
+    }
+
+    fn hash(&self, layouter: impl Layouter<F>, a0: Value<F>, a1: Value<F>) -> Result<Self::Num, Error> {
+        let tag = self.config().permutation_params.domain_tag.value();
+        let [digest, _, _] = self.permute_state(layouter, [StateWord::Witness(a0), StateWord::Witness(a1), StateWord::Constant(tag)])?;
+        Ok(digest)
+    }
+
+    fn permute_state(&self, mut layouter: impl Layouter<F>, initial: [StateWord<'_, F>; 3]) -> Result<[Self::Num; 3], Error> {
+        let config = self.config();
+        if let Some(error) = &config.error {
+            tracing::error!("invalid Poseidon parameters: {}", error);
+            return Err(Error::Synthesis);
+        }
+        layouter.assign_region(
+            || "Poseidon_Permutation", |mut region| {
+                // the floor planner may call this closure more than once, each call gets its own span
+                let span = tracing::debug_span!("poseidon.region", rows = tracing::field::Empty, advice_cells = tracing::field::Empty,
+                    fixed_cells = tracing::field::Empty, activated_gates = tracing::field::Empty);
+                let _region = span.enter();
+                let mut constant_idx: usize = 0; // index into round constants
+                let mut offset: usize = 0; // row index for computations on state
+                let mut advice_cell_ctr: usize = 0; 
+                let mut activated_gates_ctr: usize = 0;
+
+                // the round constants are laid out once in configure, here they are only replayed
+                let fixed_cell_ctr = assign_constant_schedule(&mut region, config.circuit_params.constants, &config.constant_schedule)?;
+
+                // initial state, in the first row (offset 0)
+                let advice = config.circuit_params.advice;
+                let mut state = [
+                    assign_word(&mut region, "state_0", advice[0], initial[0])?,
+                    assign_word(&mut region, "state_1", advice[1], initial[1])?,
+                    assign_word(&mut region, "state_2", advice[2], initial[2])?
+                ];
+
+                advice_cell_ctr += 3; // 3 used by loading the initial state
+
+                // helper function for the S-box for SubBytes
+                let sbox = |a: F| -> F { config.permutation_params.sbox.apply(a) };
+
+                // helper function for computing one poseidon round full or partial based on boolean
+                let poseidon_round = |
+                    region: &mut Region<F>,
+                    state: &mut [AssignedCell<F, F>; 3],
+                    round: usize,
+                    constant_idx: &mut usize,
+                    offset: &mut usize,
+                    full_round: bool,
+                    advice_cell_ctr: &mut usize,
+                    activated_gates_ctr: &mut usize
+                | -> Result<(), Error> {
+                    // round constants for the witness, the fixed cells the round gate reads (or its inlined constants)
+                    // hold the same values; a table too short for the round counts fails here, naming the round
+                    let [rc0, rc1, rc2] = config.permutation_params.round_constants_at(round, *constant_idx)
+                        .map_err(synthesis_error)?;
+                    let s_round = config.s_rounds[round];
+                    *constant_idx += 3; // 3 round constants used from the flat list
+
+                    // ARC and SubBytes in one row, based on parameter for full or partial round (partial round only
+                    // applies the S-box to state[0])
+                    s_round.enable(region, *offset)?;
+                    *activated_gates_ctr += 1;
+
+                    // the S-box inputs, with the round constants added before it or not yet
+                    let before_sbox = config.permutation_params.arc == ArcPosition::BeforeSbox;
+                    let [in0, in1, in2] = [(0, rc0), (1, rc1), (2, rc2)].map(|(word, rc)| {
+                        state[word].value().map(|v| if before_sbox { *v + rc } else { *v })
+                    });
+
+                    // the squared layout witnesses the square of every S-box input next to it
+                    if let Some(squares) = config.squares {
+                        let inputs = [in0, in1, in2];
+                        let words = if full_round { 3 } else { 1 };
+                        for (word, input) in inputs.iter().enumerate().take(words) {
+                            region.assign_advice(|| "square", squares[word], *offset, || input.map(|x| x.square()))?;
+                        }
+                        *advice_cell_ctr += words;
+                    }
+                    *offset += 1;
+
+                    // after the S-box convention adds the constants to its outputs; the words a partial round carries
+                    // over get theirs either way
+                    let after_arc = |x: F, rc: F| if before_sbox { x } else { x + rc };
+                    let after_sb = [
+                        in0.map(|x| after_arc(sbox(x), rc0)),
+                        if full_round { in1.map(|x| after_arc(sbox(x), rc1)) } else { state[1].value().map(|v| *v + rc1) },
+                        if full_round { in2.map(|x| after_arc(sbox(x), rc2)) } else { state[2].value().map(|v| *v + rc2) }
+                    ];
+
+                    state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || after_sb[0])?;
+                    state[1] = region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || after_sb[1])?;
+                    state[2] = region.assign_advice(|| "s2_sb", config.circuit_params.advice[2], *offset, || after_sb[2])?;
+                    *advice_cell_ctr += 3; // increment number of advice cells used
+
+                    // MixLayer, its output row is the next round's input row
+                    config.s_mds_mul.enable(region, *offset)?;
+                    *activated_gates_ctr += 1;
+                    *offset += 1;
+                    
+                    // the MixLayer gate embeds the same matrix, the one in the config
+                    let words = state.clone().map(|cell| cell.value().copied());
+                    let after_ml: [Value<F>; 3] = std::array::from_fn(|i| {
+                        words[0].zip(words[1]).zip(words[2])
+                            .map(|((s0, s1), s2)| mds_mul_native(&config.permutation_params.mds, &[s0, s1, s2])[i])
+                    });
+
+                    state[0] = region.assign_advice(|| "s0_ml", config.circuit_params.advice[0], *offset, || after_ml[0])?;
+                    state[1] = region.assign_advice(|| "s1_ml", config.circuit_params.advice[1], *offset, || after_ml[1])?;
+                    state[2] = region.assign_advice(|| "s2_ml", config.circuit_params.advice[2], *offset, || after_ml[2])?;
+                    *advice_cell_ctr += 3; // increment number of advice cells used
+
+                    Ok(())
+                };
+
+                // full rounds are split around the partial rounds
+                let total_rounds = config.permutation_params.full_rounds + config.permutation_params.partial_rounds;
+                for round in 0..total_rounds {
+                    let full_round = config.permutation_params.is_full_round(round);
+                    let _round = tracing::trace_span!("poseidon.round", round, offset, full = full_round).entered();
+                    round_checkpoint()?;
+                    poseidon_round(
+                        &mut region, 
+                        &mut state, 
+                        round,
+                        &mut constant_idx, 
+                        &mut offset, 
+                        full_round, 
+                        &mut advice_cell_ctr,
+                        &mut activated_gates_ctr
+                    )?;
+                }
+                // and a table too long for them leaves constants over
+                config.permutation_params.check_constants_consumed(constant_idx).map_err(synthesis_error)?;
+
+                // the region ends on the last MixLayer output row
+                let rows = offset + 1;
+                check_region_rows("Poseidon", rows, self.expected_rows())?;
+
+                span.record("rows", rows);
+                span.record("advice_cells", advice_cell_ctr);
+                span.record("fixed_cells", fixed_cell_ctr);
+                span.record("activated_gates", activated_gates_ctr);
+
+                Ok(state.map(Number::from))
+            }
+        )
+    }
+}
+
+impl<F: PrimeField> UtilsInstructions<F> for PoseidonChip<F> {
+    fn circuit_params(&self) -> &CircuitParameters {
+        &self.config.circuit_params
+    }
+}
+
+impl<F: PrimeField> PermutationChip<F> for PoseidonChip<F> {
+    const NAME: &'static str = "Poseidon";
+
+    fn configure_default(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        PoseidonChip::configure(meta, advice, constants, instance, poseidon_params(), ChipOptions::default())
+    }
+
+    fn check_parameters() -> Result<(), BenchmarkError> {
+        check_sbox_layout(poseidon_params::<F>()?.sbox)
+    }
+
+    fn from_config(config: Self::Config) -> Self {
+        PoseidonChip::construct(config)
+    }
+
+    fn params(&self) -> &dyn NativePermutation<F> {
+        &self.config.permutation_params
+    }
+
+    // the initial state, then two rows per round
+    fn expected_rows(&self) -> usize {
+        let params = &self.config.permutation_params;
+        1 + 2 * (params.full_rounds + params.partial_rounds)
+    }
+}
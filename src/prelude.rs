@@ -0,0 +1,45 @@
+// The supported public API in one import: `use permutation_benchmark::prelude::*`. Everything here follows semver
+// from 0.1 on; the other public items at the crate root back the command line tool and may change in any release.
+// examples/prelude.rs builds and mock-proves both permutation circuits through this module alone, so removing
+// anything they need fails `cargo build --examples`.
+
+// the permutation chips and their configurations, and the traits for embedding them in circuits
+pub use crate::chip::{Number, PermutationChip, PermutationInstructions, StateWord, UtilsInstructions};
+pub use crate::gates::CircuitParameters;
+#[cfg(feature = "poseidon")]
+pub use crate::poseidon::{PoseidonChip, PoseidonChipConfig};
+#[cfg(feature = "rescue")]
+pub use crate::rescue::{RescueChip, RescueChipConfig};
+#[cfg(feature = "sponge")]
+pub use crate::sponge::{BYTES_PER_ELEMENT, Sponge, SpongeConfig, SpongePadding, configure_sponge, hash_bytes_native, pack_bytes};
+
+// the benchmark circuits and their public instances
+#[cfg(feature = "bench-cli")]
+pub use crate::bench::{compose_instances, expected_instances};
+#[cfg(feature = "bench-cli")]
+pub use crate::target::{PermutationArg, PermutationKind};
+pub use crate::circuits::{FloorPlannerKind, OutputMode};
+#[cfg(feature = "poseidon")]
+pub use crate::circuits::PoseidonCircuit;
+#[cfg(feature = "rescue")]
+pub use crate::circuits::RescueCircuit;
+pub use crate::instances::{Digest, PublicInputs};
+
+// parameters: the builders, the instances of the active parameter source, and the native permutations
+pub use crate::native::NativePermutation;
+pub use crate::params::{ArcPosition, Poseidon, PoseidonBuilder, RescuePrime, RescuePrimeBuilder, SboxKind, poseidon_params, rescue_params};
+pub use crate::source::{ParameterSource, ParameterSourceGuard, set_parameter_source};
+
+// measurement entry points and their reports
+#[cfg(feature = "bench-cli")]
+pub use crate::analysis::{ColumnSummary, ConstraintSystemSummary, GateSummary, analyze};
+#[cfg(feature = "bench-cli")]
+pub use crate::bench::{permutation_report, run_benchmark, run_sweep};
+pub use crate::error::BenchmarkError;
+#[cfg(feature = "bench-cli")]
+pub use crate::proving::{Backend, ProofScheme};
+#[cfg(feature = "bench-cli")]
+pub use crate::report::{BenchmarkReport, Repeat, ReportOutput};
+pub use crate::synthesis::CheckMode;
+#[cfg(feature = "bench-cli")]
+pub use crate::transcript::TranscriptKind;
@@ -0,0 +1,34 @@
+use halo2curves::bls12381::Fr as Bls381Fr;
+use halo2curves::bn256::Fr;
+
+use crate::constants::{POSEIDON_BLS381_T3_NEPTUNE, POSEIDON_BN254_T3_CIRCOM};
+use crate::error::BenchmarkError;
+use crate::native::NativePermutation;
+use crate::params::Poseidon;
+
+// Parameter sets published by other implementations, fixed to their own field rather than taken from the parameter
+// source, so that digests can be compared with theirs.
+
+// circomlib's (and poseidon-rs') Poseidon for t = 3 over the BN254 scalar field: x^5, 8 full and 57 partial rounds, with
+// their constants and MDS matrix. The round function is the same as here (constants added before the S-box in every
+// round, the partial S-box on state[0]); circomlib puts the capacity word 0 in state[0] and the two inputs after it, so
+// poseidon([a, b]) is state[0] of the permutation of [0, a, b]
+pub(crate) fn bn254_circom() -> Result<Poseidon<Fr>, BenchmarkError> {
+    Poseidon::builder().preset(POSEIDON_BN254_T3_CIRCOM).build()
+}
+
+// neptune's Poseidon for arity 2 (t = 3) over BLS12-381: x^5, 8 full and 55 partial rounds (its own round numbers, with
+// the security margin taken differently from the reference script's 57), Grain round constants from its own seed and the
+// index Cauchy matrix instead of the sampled one. The round function is the same as here
+pub(crate) fn bls381_neptune() -> Result<Poseidon<Bls381Fr>, BenchmarkError> {
+    Poseidon::builder().preset(POSEIDON_BLS381_T3_NEPTUNE).build()
+}
+
+// neptune's domain tag for hashing two words as a Merkle tree node, 2^arity - 1
+pub(crate) const NEPTUNE_ARITY_2_TAG: u64 = 3;
+
+// neptune's Poseidon<Fr, U2>::hash of [a, b]: the domain tag in state[0], the two inputs after it, and the digest is
+// state[1] of the permutation, not state[0] as here and in circomlib
+pub(crate) fn neptune_hash(params: &Poseidon<Bls381Fr>, [a, b]: [Bls381Fr; 2]) -> Result<Bls381Fr, BenchmarkError> {
+    Ok(params.permute_native([Bls381Fr::from(NEPTUNE_ARITY_2_TAG), a, b])[1])
+}
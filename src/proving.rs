@@ -0,0 +1,777 @@
+use std::collections::{BTreeMap, btree_map::Entry};
+use std::fmt::Debug;
+use std::sync::Arc;
+use ff::{Field, PrimeField};
+use serde::Serialize;
+use clap::ValueEnum;
+use halo2_proofs::{
+    circuit::Value,
+    pasta::{EqAffine, Fp},
+    plonk::{
+        Circuit, ProvingKey, SingleVerifier, VerifyingKey,
+        create_proof, keygen_pk, keygen_vk, verify_proof
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, RngCore, SeedableRng};
+
+use crate::clock::Instant;
+use std::marker::PhantomData;
+use halo2_proofs::plonk::ConstraintSystem;
+
+use crate::bench::expected_instances;
+use crate::chip::PermutationChip;
+use crate::circuits::{BatchCircuit, BytesCircuit, MacCircuit, OutputMode, PoseidonCircuit, PublicMessageCircuit, RescueCircuit, StreamCircuit, TranscriptCircuit};
+use crate::error::BenchmarkError;
+use crate::memory::{low_memory, peak_heap, with_low_memory};
+use crate::native::NativePermutation;
+use crate::params::poseidon_params;
+use crate::report::{BatchReport, BytesReport, ProofReport, PublicMessageReport, Repeat, ScalingPoint, ScalingReport, StreamReport, TimingStats, TranscriptReport};
+use crate::source::{active_source, set_parameter_source};
+use crate::sponge::{BYTES_PER_ELEMENT, PackedChunks, Sponge, hash_bytes_native, public_message_column};
+use crate::srs::commitment_params;
+use crate::synthesis::{check_k, layout_rows, minimal_k};
+use crate::target::PermutationKind;
+use crate::transcript::{PoseidonRead, PoseidonWrite, TranscriptKind};
+use crate::witness::{GeneratedInputs, InputStream};
+
+// polynomial commitment / multi-open scheme. Every real proof uses IPA, the only scheme halo2_proofs 0.3 implements;
+// the KZG multi-open schemes are only analysed, for the verification costs derived from the verifying key
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProofScheme {
+    // inner-product argument over Pasta
+    #[default]
+    Ipa,
+    // KZG multi-open schemes from the PSE fork of halo2
+    Shplonk,
+    Gwc
+}
+
+impl ProofScheme {
+    // the scheme's name in the reports, as serialized
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProofScheme::Ipa => "ipa",
+            ProofScheme::Shplonk => "shplonk",
+            ProofScheme::Gwc => "gwc"
+        }
+    }
+}
+
+// proving backend of the real proofs: the commitment scheme, and with it the curve whose scalar field the circuits are
+// instantiated over
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    // inner-product argument over Pasta: no trusted setup, the circuits over the Pallas scalar field
+    #[default]
+    Ipa
+}
+
+// a proving backend: setup, keygen, prover and verifier behind one interface, so the proof measurements are written
+// once for every backend. The backend is its commitment parameters, so a measurement runs on the backend of the
+// parameters it is given
+pub(crate) trait ProvingBackend: Sized + Sync {
+    const BACKEND: Backend;
+    // the field the circuits are instantiated over, the scalar field of the backend's curve
+    type Scalar: PrimeField;
+    type ProvingKey;
+    type VerifyingKey: Clone;
+
+    // the commitment parameters for circuits of 2^k rows
+    fn setup(k: u32) -> Result<Self, BenchmarkError>;
+
+    // log2 of the rows the parameters commit to
+    fn k(&self) -> u32;
+
+    fn keygen<C: Circuit<Self::Scalar>>(&self, circuit: &C) -> Result<Self::ProvingKey, BenchmarkError>;
+
+    fn prove<C: Circuit<Self::Scalar>>(
+        &self,
+        pk: &Self::ProvingKey,
+        circuit: &C,
+        instance: &[&[Self::Scalar]],
+        rng: impl RngCore,
+        transcript: TranscriptKind
+    ) -> Result<Vec<u8>, BenchmarkError>;
+
+    // the verifying key inside a proving key
+    fn vk(pk: &Self::ProvingKey) -> &Self::VerifyingKey;
+
+    fn verify(&self, vk: &Self::VerifyingKey, proof: &[u8], instance: &[&[Self::Scalar]], transcript: TranscriptKind) -> Result<(), BenchmarkError>;
+}
+
+impl ProvingBackend for Params<EqAffine> {
+    const BACKEND: Backend = Backend::Ipa;
+    type Scalar = Fp;
+    type ProvingKey = ProvingKey<EqAffine>;
+    type VerifyingKey = VerifyingKey<EqAffine>;
+
+    fn setup(k: u32) -> Result<Self, BenchmarkError> {
+        commitment_params(k)
+    }
+
+    fn k(&self) -> u32 {
+        // the inherent Params::k
+        Params::k(self)
+    }
+
+    fn keygen<C: Circuit<Fp>>(&self, circuit: &C) -> Result<ProvingKey<EqAffine>, BenchmarkError> {
+        Ok(keygen_pk(self, keygen_vk(self, circuit)?, circuit)?)
+    }
+
+    fn prove<C: Circuit<Fp>>(
+        &self,
+        pk: &ProvingKey<EqAffine>,
+        circuit: &C,
+        instance: &[&[Fp]],
+        rng: impl RngCore,
+        transcript: TranscriptKind
+    ) -> Result<Vec<u8>, BenchmarkError> {
+        create_ipa_proof(self, pk, circuit, instance, rng, transcript)
+    }
+
+    fn vk(pk: &ProvingKey<EqAffine>) -> &VerifyingKey<EqAffine> {
+        pk.get_vk()
+    }
+
+    fn verify(&self, vk: &VerifyingKey<EqAffine>, proof: &[u8], instance: &[&[Fp]], transcript: TranscriptKind) -> Result<(), BenchmarkError> {
+        verify_ipa_proof(self, vk, proof, instance, transcript)
+    }
+}
+
+// prove the Poseidon permutation circuit on the inputs (0, 1, 2) once on `backend` at k, over the backend's scalar field
+#[cfg(test)]
+pub(crate) fn prove_on_backend(backend: Backend, k: u32, rng: impl RngCore) -> Result<ProofReport, BenchmarkError> {
+    fn prove<B: ProvingBackend>(k: u32, rng: impl RngCore) -> Result<ProofReport, BenchmarkError> {
+        let inputs = [0, 1, 2].map(B::Scalar::from);
+        let circuit = PoseidonCircuit::new(inputs.map(Value::known), OutputMode::AllState)?;
+        let instances = expected_instances(PermutationKind::Poseidon, inputs, OutputMode::AllState)?;
+        prove_and_verify("Poseidon", &B::setup(k)?, &circuit, instances.column(0), rng)
+    }
+
+    match backend {
+        Backend::Ipa => prove::<Params<EqAffine>>(k, rng)
+    }
+}
+
+// helper function to fingerprint a verifying key; halo2 0.3 has no vk serialization, so the pinned representation
+// (constraint system, domain and fixed column commitments) is hashed instead
+pub(crate) fn vk_digest<C: Circuit<Fp>>(params: &Params<EqAffine>, circuit: &C) -> Result<String, BenchmarkError> {
+    let vk = keygen_vk(params, circuit)?;
+    Ok(blake2b_simd::blake2b(format!("{:?}", vk.pinned()).as_bytes()).to_hex().to_string())
+}
+
+// vk digests of the two permutation circuits with the preset parameters (k = 10), pinned so that layout refactors
+// which must not change the circuit (e.g. precomputing the round constant schedule) are checked against them
+pub(crate) const PRESET_VK_DIGESTS: [(&str, &str); 2] = [
+    ("Poseidon", "dedda29e1ba4517391b0fa8b5ebe85b2cd054112226025c2db3556bf94511885e6b9cc9dafaa9f0bb7f6623e653416e6c8e09aa2f7c92b603ffd491a2ef88fee"),
+    ("Rescue-Prime", "a20d42bcc052ba94f210998fa83f02eef4d9f28edf5ed218f21eff2a8bb14941d417cedfccf33a2d8b753b96fd61e408e50cfb0fca1c1ecf6e29d963b80754a0")
+];
+
+// randomness for the provers' blinding factors: ChaCha20 from a seed for byte-identical proofs across runs, the OS
+// RNG otherwise
+pub(crate) enum ProvingRng {
+    Os(OsRng),
+    Seeded(Box<ChaCha20Rng>)
+}
+
+impl ProvingRng {
+    pub(crate) fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => ProvingRng::Seeded(Box::new(ChaCha20Rng::seed_from_u64(seed))),
+            None => ProvingRng::Os(OsRng)
+        }
+    }
+}
+
+// helper function for the number of threads halo2's parallel keygen, prover and verifier run on here: the current
+// rayon pool's, or one without the multicore feature
+pub(crate) fn current_threads() -> usize {
+    #[cfg(feature = "multicore")]
+    {
+        rayon::current_num_threads()
+    }
+    #[cfg(not(feature = "multicore"))]
+    {
+        1
+    }
+}
+
+// run `f` with halo2's keygen, prover and verifier on a rayon pool of `threads` threads, or on the current pool if
+// None. The pool is entered with ThreadPool::install, so it leaves the global pool alone and nests under the pool a
+// sweep runs its points on; the parameter source and low-memory mode are thread-local, so they are set again on the
+// pool's thread
+pub fn with_threads<T: Send>(threads: Option<usize>, f: impl FnOnce() -> T + Send) -> Result<T, BenchmarkError> {
+    match threads {
+        None => Ok(f()),
+        Some(0) => Err(BenchmarkError::ParameterMismatch("--threads must be at least 1".to_string())),
+        #[cfg(feature = "multicore")]
+        Some(threads) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|error| BenchmarkError::Unsupported(format!("cannot start the prover thread pool: {}", error)))?;
+            let (source, low_memory) = (active_source(), low_memory());
+            Ok(pool.install(|| {
+                let _parameters = set_parameter_source(source);
+                with_low_memory(low_memory, f)
+            }))
+        }
+        #[cfg(not(feature = "multicore"))]
+        Some(1) => Ok(f()),
+        #[cfg(not(feature = "multicore"))]
+        Some(threads) => Err(BenchmarkError::Unsupported(format!("{} prover threads need the multicore feature", threads)))
+    }
+}
+
+impl RngCore for ProvingRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            ProvingRng::Os(rng) => rng.next_u32(),
+            ProvingRng::Seeded(rng) => rng.next_u32()
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            ProvingRng::Os(rng) => rng.next_u64(),
+            ProvingRng::Seeded(rng) => rng.next_u64()
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            ProvingRng::Os(rng) => rng.fill_bytes(dest),
+            ProvingRng::Seeded(rng) => rng.fill_bytes(dest)
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        match self {
+            ProvingRng::Os(rng) => rng.try_fill_bytes(dest),
+            ProvingRng::Seeded(rng) => rng.try_fill_bytes(dest)
+        }
+    }
+}
+
+// helper function to create a real IPA proof over Pasta with the given transcript; `instance` holds the circuit's
+// instance columns in order
+pub(crate) fn create_ipa_proof<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: &C,
+    instance: &[&[Fp]],
+    rng: impl RngCore,
+    transcript: TranscriptKind
+) -> Result<Vec<u8>, BenchmarkError> {
+    match transcript {
+        TranscriptKind::Blake2b => {
+            let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+            create_proof(params, pk, std::slice::from_ref(circuit), &[instance], rng, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+        TranscriptKind::Poseidon => {
+            let mut transcript = PoseidonWrite::<_, EqAffine>::init(vec![])?;
+            create_proof(params, pk, std::slice::from_ref(circuit), &[instance], rng, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+    }
+}
+
+// helper function to verify a real IPA proof over Pasta, read with the given transcript
+pub(crate) fn verify_ipa_proof(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instance: &[&[Fp]],
+    transcript: TranscriptKind
+) -> Result<(), BenchmarkError> {
+    match transcript {
+        TranscriptKind::Blake2b => {
+            let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+            verify_proof(params, vk, SingleVerifier::new(params), &[instance], &mut transcript)?;
+        }
+        TranscriptKind::Poseidon => {
+            let mut transcript = PoseidonRead::<_, EqAffine>::init(proof)?;
+            verify_proof(params, vk, SingleVerifier::new(params), &[instance], &mut transcript)?;
+        }
+    }
+
+    Ok(())
+}
+
+// helper function to prove the Poseidon circuit with both transcripts, returning both reports
+pub(crate) fn measure_transcripts(k: u32, mut rng: impl RngCore) -> Result<Vec<ProofReport>, BenchmarkError> {
+    let params = commitment_params(k)?;
+    let inputs = [0, 1, 2].map(Fp::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let circuit = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+    let expected = poseidon_params::<Fp>()?.permute_native(inputs);
+
+    let reports = [TranscriptKind::Blake2b, TranscriptKind::Poseidon].into_iter()
+        .map(|transcript| prove_and_verify_repeated("Poseidon", &params, &circuit, &[&expected], &mut rng, Repeat::ONCE, transcript))
+        .collect::<Result<Vec<_>, BenchmarkError>>()?;
+    println!(
+        "Poseidon proof with the Blake2b transcript: {} ms, verified in {} ms; with the Poseidon transcript: {} ms, verified in {} ms",
+        reports[0].proving_ms, reports[0].verification_ms, reports[1].proving_ms, reports[1].verification_ms
+    );
+
+    Ok(reports)
+}
+
+// helper function to create a real proof on the backend of `params` and verify it, returning its size and timings
+pub(crate) fn prove_and_verify<B: ProvingBackend, C: Circuit<B::Scalar>>(
+    label: &'static str,
+    params: &B,
+    circuit: &C,
+    instance: &[B::Scalar],
+    rng: impl RngCore
+) -> Result<ProofReport, BenchmarkError> {
+    prove_and_verify_repeated(label, params, circuit, &[instance], rng, Repeat::ONCE, TranscriptKind::Blake2b)
+}
+
+// helper function to create and verify `repeat.rounds` real proofs on the backend of `params` after `repeat.warmup`
+// untimed ones, all with `transcript`, against the instance columns `instance`. Keygen runs once and the proving key is reused, while every proof draws fresh
+// blinding factors from `rng`, so no two consecutive proofs may be equal. In low-memory mode every proof is made
+// before any is verified, and the proving key is dropped in between
+pub(crate) fn prove_and_verify_repeated<B: ProvingBackend, C: Circuit<B::Scalar>>(
+    label: &'static str,
+    params: &B,
+    circuit: &C,
+    instance: &[&[B::Scalar]],
+    mut rng: impl RngCore,
+    repeat: Repeat,
+    transcript: TranscriptKind
+) -> Result<ProofReport, BenchmarkError> {
+    let k = params.k();
+    let _span = tracing::info_span!("prove", label, backend = ?B::BACKEND, k, rounds = repeat.rounds, warmup = repeat.warmup).entered();
+    check_k(label, k, circuit)?;
+    let pk = params.keygen(&circuit.without_witnesses())?;
+    let verify = |vk: &B::VerifyingKey, proof: &[u8], instance: &[&[B::Scalar]]| params.verify(vk, proof, instance, transcript);
+    let timed_verify = |vk: &B::VerifyingKey, proof: &[u8]| -> Result<f64, BenchmarkError> {
+        let start = Instant::now();
+        verify(vk, proof, instance)?;
+        println!("{} verification time: {} ms", label, start.elapsed().as_millis());
+        Ok(start.elapsed().as_secs_f64() * 1000.0)
+    };
+
+    for _ in 0..repeat.warmup {
+        verify(B::vk(&pk), &params.prove(&pk, circuit, instance, &mut rng, transcript)?, instance)?;
+    }
+
+    let low_memory = low_memory();
+    let (mut proving_ms, mut verification_ms) = (Vec::new(), Vec::new());
+    let mut proof: Vec<u8> = Vec::new();
+    // proofs made in low-memory mode, verified once the proving key is dropped
+    let mut pending = Vec::new();
+    for _ in 0..repeat.rounds {
+        let start = Instant::now();
+        let next = params.prove(&pk, circuit, instance, &mut rng, transcript)?;
+        proving_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+        println!("{} proving time: {} ms", label, start.elapsed().as_millis());
+        if next == proof {
+            return Err(BenchmarkError::VerificationFailed(format!("{} proof repeated the previous one", label)));
+        }
+        proof = next;
+
+        match low_memory {
+            true => pending.push(proof.clone()),
+            false => verification_ms.push(timed_verify(B::vk(&pk), &proof)?)
+        }
+    }
+    let vk = B::vk(&pk).clone();
+    if low_memory {
+        drop(pk);
+        for proof in &pending {
+            verification_ms.push(timed_verify(&vk, proof)?);
+        }
+    }
+
+    // the same proof must not verify against a different public output, the first word of the first column
+    let mut wrong_column = instance[0].to_vec();
+    wrong_column[0] += B::Scalar::ONE;
+    let wrong_instance: Vec<&[B::Scalar]> = std::iter::once(wrong_column.as_slice()).chain(instance[1..].iter().copied()).collect();
+    if verify(&vk, &proof, &wrong_instance).is_ok() {
+        return Err(BenchmarkError::VerificationFailed(format!("{} proof accepted a wrong instance", label)));
+    }
+
+    println!("{} proof size: {} bytes", label, proof.len());
+    let proving_stats = TimingStats::from_samples(&proving_ms).expect("at least one proof");
+    let verification_stats = TimingStats::from_samples(&verification_ms).expect("at least one proof");
+    Ok(ProofReport {
+        permutation: label,
+        backend: B::BACKEND,
+        scheme: ProofScheme::Ipa,
+        transcript,
+        proof_size: proof.len(),
+        proving_ms: proving_stats.median.round() as u128,
+        verification_ms: verification_stats.median.round() as u128,
+        threads: current_threads(),
+        proving_stats,
+        verification_stats
+    })
+}
+
+// prove a circuit on a pool of each number of threads, keygen included, with the speedup of the median proving time
+// over the first count; every proof is verified
+pub(crate) fn scaling_report<B: ProvingBackend, C: Circuit<B::Scalar> + Sync>(
+    label: &'static str,
+    params: &B,
+    circuit: &C,
+    instance: &[B::Scalar],
+    thread_counts: &[usize],
+    seed: Option<u64>
+) -> Result<ScalingReport, BenchmarkError> {
+    let mut points: Vec<ScalingPoint> = Vec::new();
+    for &threads in thread_counts {
+        let proof = with_threads(Some(threads), || prove_and_verify(label, params, circuit, instance, ProvingRng::new(seed)))??;
+        let baseline = points.first().map_or(proof.proving_stats.median, |first| first.proof.proving_stats.median);
+        println!("{} on {} threads: {:.1}x", label, threads, baseline / proof.proving_stats.median);
+        points.push(ScalingPoint { threads, speedup: baseline / proof.proving_stats.median, proof });
+    }
+
+    Ok(ScalingReport { permutation: label, k: params.k(), points })
+}
+
+// run keygen and the real prover for both permutations; only IPA over Pasta is available in halo2_proofs 0.3, so
+// the circuits are instantiated over the Pallas scalar field with the constant tables reduced into it
+pub(crate) fn measure_pasta_proofs(
+    k: u32,
+    mut rng: impl RngCore,
+    repeat: Repeat,
+    transcript: TranscriptKind
+) -> Result<Vec<ProofReport>, BenchmarkError> {
+    let params = commitment_params(k)?;
+    let inputs = [[0, 1, 2], [3, 4, 5]].map(|words| words.map(Fp::from));
+
+    let circuits_ps = inputs.map(|[s0, s1, s2]| PoseidonCircuit {
+        s0: Value::known(s0),
+        s1: Value::known(s1),
+        s2: Value::known(s2),
+        output_mode: OutputMode::AllState
+    });
+    let circuits_rs = inputs.map(|[s0, s1, s2]| RescueCircuit {
+        s0: Value::known(s0),
+        s1: Value::known(s1),
+        s2: Value::known(s2),
+        output_mode: OutputMode::AllState
+    });
+
+    // the public outputs over Pasta come from the native permutations with the same reduced parameters
+    let public_ps = expected_instances(PermutationKind::Poseidon, inputs[0], OutputMode::AllState)?;
+    let public_rs = expected_instances(PermutationKind::Rescue, inputs[0], OutputMode::AllState)?;
+    let (expected_ps, expected_rs) = (public_ps.column(0), public_rs.column(0));
+    Ok(vec![
+        prove_and_verify_repeated("Poseidon", &params, &circuits_ps[0], &[expected_ps], &mut rng, repeat, transcript)?,
+        prove_and_verify_repeated("Rescue-Prime", &params, &circuits_rs[0], &[expected_rs], &mut rng, repeat, transcript)?
+    ])
+}
+
+// helper function to prove a circuit at the smallest k that fits its layout, returning k, the rows used and the proof;
+// `params` caches the commitment parameters of the backend per k across calls, only for the k at hand in low-memory
+// mode
+pub(crate) fn prove_at_minimal_k<B: ProvingBackend, C: Circuit<B::Scalar>>(
+    label: &'static str,
+    circuit: &C,
+    instance: &[&[B::Scalar]],
+    params: &mut BTreeMap<u32, B>,
+    rng: impl RngCore
+) -> Result<(u32, usize, ProofReport), BenchmarkError> {
+    let k = minimal_k(circuit)?;
+    let rows = layout_rows(circuit)?;
+    println!("{}: {} rows, k = {}", label, rows, k);
+    if low_memory() {
+        params.retain(|cached, _| *cached == k);
+    }
+    let params = match params.entry(k) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(B::setup(k)?)
+    };
+    Ok((k, rows, prove_and_verify_repeated(label, params, circuit, instance, rng, Repeat::ONCE, TranscriptKind::Blake2b)?))
+}
+
+// prove `batch_size` permutations of the inputs (3i, 3i + 1, 3i + 2) in one IPA proof over Pasta, at the smallest k
+// that fits the batch
+pub(crate) fn batch_proving<P: PermutationChip<Fp>>(
+    batch_size: usize,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<BatchReport, BenchmarkError> {
+    // the states are generated when their permutations are assigned rather than held by the circuit
+    let generate: fn(usize) -> [Fp; 3] = |i| [3 * i, 3 * i + 1, 3 * i + 2].map(|word| Fp::from(word as u64));
+    let inputs = (0..batch_size).map(generate);
+    prove_batch_circuit(BatchCircuit::<Fp, P, _> { inputs: GeneratedInputs::new(batch_size, generate), _marker: PhantomData }, inputs, params, rng)
+}
+
+// prove the permutations of `inputs` in one IPA proof over Pasta, their digests (state[0]) public, at the smallest k
+// that fits the batch
+pub(crate) fn prove_batch<P: PermutationChip<Fp>>(
+    inputs: &[[Fp; 3]],
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<BatchReport, BenchmarkError> {
+    let circuit = BatchCircuit::<Fp, P> {
+        inputs: inputs.iter().map(|input| input.map(Value::known)).collect(),
+        _marker: PhantomData
+    };
+    prove_batch_circuit(circuit, inputs.iter().copied(), params, rng)
+}
+
+// helper function to prove a batch circuit whose inputs are `inputs`, with their digests computed natively
+fn prove_batch_circuit<P: PermutationChip<Fp>, S: InputStream<[Value<Fp>; 3]>>(
+    circuit: BatchCircuit<Fp, P, S>,
+    inputs: impl Iterator<Item = [Fp; 3]>,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<BatchReport, BenchmarkError> {
+    let batch_size = circuit.inputs.len();
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let digests = inputs.map(|input| chip.params().permute_native(input)[0]).collect::<Vec<_>>();
+
+    println!("{} batch of {}", P::NAME, batch_size);
+    let (proved, peak_heap_bytes) = peak_heap(|| prove_at_minimal_k(P::NAME, &circuit, &[&digests], params, rng));
+    let (k, rows, proof) = proved?;
+
+    Ok(BatchReport {
+        permutation: P::NAME,
+        batch_size,
+        k,
+        rows,
+        proving_ms_per_hash: proof.proving_ms as f64 / batch_size as f64,
+        proof,
+        low_memory: low_memory(),
+        peak_heap_bytes
+    })
+}
+
+// hash `elements` transcript elements (1, 2, ...) through the sponge and prove the challenge in one IPA proof over
+// Pasta, at the smallest k that fits; `keyed` proves a MAC over the elements under a private key instead
+pub(crate) fn transcript_proving<P: PermutationChip<Fp>>(
+    elements: usize,
+    keyed: bool,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<TranscriptReport, BenchmarkError> {
+    let values: Vec<Fp> = (1..=elements as u64).map(Fp::from).collect();
+    let witnesses: Vec<Value<Fp>> = values.iter().copied().map(Value::known).collect();
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+
+    let (k, rows, proof) = if keyed {
+        let key = Fp::from(0x6b6579);
+        let tag = Sponge::new_keyed(key).hash_native(chip.params(), &values);
+        let circuit = MacCircuit::<Fp, P> { key: Value::known(key), message: witnesses, _marker: PhantomData };
+        println!("{} MAC of {} elements", P::NAME, elements);
+        prove_at_minimal_k(P::NAME, &circuit, &[&[&[tag], values.as_slice()].concat()], params, rng)?
+    } else {
+        let challenge = Sponge::new().hash_native(chip.params(), &values);
+        let circuit = TranscriptCircuit::<Fp, P> { elements: witnesses, _marker: PhantomData };
+        println!("{} transcript of {} elements", P::NAME, elements);
+        prove_at_minimal_k(P::NAME, &circuit, &[&[challenge]], params, rng)?
+    };
+
+    Ok(TranscriptReport {
+        permutation: P::NAME,
+        keyed,
+        elements,
+        k,
+        rows,
+        rows_per_element: rows as f64 / elements as f64,
+        proof
+    })
+}
+
+// hash a `bytes`-byte message (0, 1, 2, ... wrapping at 256) packed into field elements and prove the digest in one
+// IPA proof over Pasta, at the smallest k that fits
+pub(crate) fn bytes_proving<P: PermutationChip<Fp>>(
+    bytes: usize,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<BytesReport, BenchmarkError> {
+    let message: Arc<[u8]> = (0..bytes).map(|i| i as u8).collect();
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let digest = hash_bytes_native(chip.params(), &message);
+    // the chunks are packed from the message as the sponge absorbs them
+    let circuit = BytesCircuit::<Fp, P, _> { length: bytes, chunks: PackedChunks::new(message), _marker: PhantomData };
+    println!("{} hash of a {}-byte message", P::NAME, bytes);
+    let (k, rows, proof) = prove_at_minimal_k(P::NAME, &circuit, &[&[digest]], params, rng)?;
+
+    Ok(BytesReport {
+        permutation: P::NAME,
+        bytes,
+        elements: 1 + bytes.div_ceil(BYTES_PER_ELEMENT),
+        k,
+        rows,
+        rows_per_byte: rows as f64 / bytes as f64,
+        proof
+    })
+}
+
+// hash a public message filling all `max_len` slots (1, 2, ...) from the message instance column through the sponge and
+// prove the digest in one IPA proof over Pasta, at the smallest k that fits; both instance columns are public
+pub(crate) fn public_message_proving<P: PermutationChip<Fp>>(
+    max_len: usize,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<PublicMessageReport, BenchmarkError> {
+    let message: Vec<Fp> = (1..=max_len as u64).map(Fp::from).collect();
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let column = public_message_column(&message, max_len)?;
+    let digest = Sponge::new().hash_native(chip.params(), &column);
+    let circuit = PublicMessageCircuit::<Fp, P> { max_len, _marker: PhantomData };
+    println!("{} public message of {} words", P::NAME, max_len);
+    let (k, rows, proof) = prove_at_minimal_k(P::NAME, &circuit, &[&[digest], &column], params, rng)?;
+
+    Ok(PublicMessageReport {
+        permutation: P::NAME,
+        max_len,
+        elements: column.len(),
+        k,
+        rows,
+        rows_per_element: rows as f64 / max_len as f64,
+        proof
+    })
+}
+
+// squeeze `outputs` words from a two-element seed through the sponge in duplex mode and prove them public in one IPA
+// proof over Pasta, at the smallest k that fits
+pub(crate) fn stream_proving<P: PermutationChip<Fp>>(
+    outputs: usize,
+    params: &mut BTreeMap<u32, Params<EqAffine>>,
+    rng: impl RngCore
+) -> Result<StreamReport, BenchmarkError> {
+    let seed = [Fp::from(0x73656564), Fp::from(1)];
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let stream = Sponge::new().squeeze_many_native(chip.params(), &seed, outputs);
+    let circuit = StreamCircuit::<Fp, P> { seed: seed.map(Value::known).to_vec(), outputs, _marker: PhantomData };
+    println!("{} stream of {} outputs", P::NAME, outputs);
+    let (k, rows, proof) = prove_at_minimal_k(P::NAME, &circuit, &[&stream], params, rng)?;
+
+    Ok(StreamReport {
+        permutation: P::NAME,
+        seed_elements: seed.len(),
+        outputs,
+        k,
+        rows,
+        rows_per_output: rows as f64 / outputs as f64,
+        proof
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::transcript::{EncodedChallenge, Transcript};
+    use halo2curves::bls12381::Fr;
+
+    use super::*;
+    use crate::circuits::DualCircuit;
+    use crate::gates::{ConstantStrategy, with_constant_strategy};
+    use crate::hash::poseidon_hash;
+    use crate::params::rescue_params;
+    use crate::source::{ParameterFile, ParameterSource};
+
+    // k of the keygen guardrails, the k of the pinned verifying keys
+    const K: u32 = 10;
+
+    // helper function for the keygen guardrails of one circuit under each round constant strategy: the vk from
+    // without_witnesses() (all Values unknown, which is what real keygen sees) must equal the vks of circuits with
+    // seeded random witnesses, a second keygen must give the same vk, and a parameter file with one round constant of
+    // each permutation edited must give a different one, so configure and the fixed columns depend on the constants
+    // and nothing else
+    fn assert_vk_guardrails<C: Circuit<Fp>>(label: &str, circuit: impl Fn([Value<Fp>; 3]) -> C) {
+        let params = commitment_params(K).unwrap();
+        let mut edited = ParameterFile::from_params(&poseidon_params::<Fr>().unwrap(), &rescue_params::<Fr>().unwrap());
+        for constants in [edited.poseidon.as_mut().map(|section| &mut section.round_constants), edited.rescue.as_mut().map(|section| &mut section.round_constants)].into_iter().flatten() {
+            constants[0] = if constants[0] == "1" { "2" } else { "1" }.to_string();
+        }
+        let file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        edited.write(file.path()).unwrap();
+
+        let mut rng = ChaCha20Rng::seed_from_u64(1111);
+        for strategy in [ConstantStrategy::Fixed, ConstantStrategy::Advice, ConstantStrategy::Gate] {
+            with_constant_strategy(strategy, || {
+                let expected = vk_digest(&params, &circuit([Value::unknown(); 3])).unwrap();
+                for _ in 0..4 {
+                    let witness = [(); 3].map(|()| Value::known(Fp::random(&mut rng)));
+                    assert_eq!(vk_digest(&params, &circuit(witness)).unwrap(), expected, "{} vk with {:?} constants depends on the witness values", label, strategy);
+                }
+                assert_eq!(vk_digest(&params, &circuit([Value::unknown(); 3])).unwrap(), expected, "{} keygen with {:?} constants is not deterministic", label, strategy);
+
+                let _source = set_parameter_source(ParameterSource::File(file.path().to_path_buf()));
+                assert_ne!(vk_digest(&params, &circuit([Value::unknown(); 3])).unwrap(), expected, "{} vk with {:?} constants ignores a round constant", label, strategy);
+            });
+        }
+    }
+
+    #[test]
+    fn poseidon_vk_guardrails() {
+        assert_vk_guardrails("Poseidon", |[s0, s1, s2]| PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState });
+    }
+
+    #[test]
+    fn rescue_vk_guardrails() {
+        assert_vk_guardrails("Rescue-Prime", |[s0, s1, s2]| RescueCircuit { s0, s1, s2, output_mode: OutputMode::AllState });
+    }
+
+    // the combined circuit allocates both chips' selectors; the order must not depend on the witness either
+    #[test]
+    fn dual_vk_guardrails() {
+        assert_vk_guardrails("Poseidon+Rescue-Prime", |[s0, s1, s2]| DualCircuit { s0, s1, s2 });
+    }
+
+    // proving is a function of the RNG only: two proofs from the same seed, key and witness are byte-identical, and a
+    // different seed gives a different proof
+    #[test]
+    fn seeded_proofs() {
+        let params = commitment_params(K).unwrap();
+        let inputs = [0, 1, 2].map(Fp::from);
+        let [s0, s1, s2] = inputs.map(Value::known);
+        let circuit = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+        let instance = poseidon_params::<Fp>().unwrap().permute_native(inputs);
+        let vk = keygen_vk(&params, &circuit.without_witnesses()).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit.without_witnesses()).unwrap();
+        let proof = |seed| create_ipa_proof(&params, &pk, &circuit, &[&instance], ChaCha20Rng::seed_from_u64(seed), TranscriptKind::Blake2b).unwrap();
+
+        let first = proof(1);
+        assert_eq!(proof(1), first, "proofs from the same seed differ");
+        assert_ne!(proof(2), first, "proofs from different seeds are identical");
+    }
+
+    // both transcripts give proofs of the same size, since they only differ in their challenges; each proof verifies
+    // with its own transcript's reader and fails cleanly, with an error rather than a panic, with the other one. The
+    // first Poseidon challenge is the Poseidon hash of the words absorbed before it
+    #[test]
+    fn transcript_readers() {
+        let params = commitment_params(K).unwrap();
+        let inputs = [0, 1, 2].map(Fp::from);
+        let [s0, s1, s2] = inputs.map(Value::known);
+        let circuit = PoseidonCircuit { s0, s1, s2, output_mode: OutputMode::AllState };
+        let expected = poseidon_params::<Fp>().unwrap().permute_native(inputs);
+        let mut rng = ChaCha20Rng::seed_from_u64(1085);
+
+        let reports = measure_transcripts(K, &mut rng).unwrap();
+        assert_eq!(reports[0].proof_size, reports[1].proof_size);
+
+        let vk = keygen_vk(&params, &circuit.without_witnesses()).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit.without_witnesses()).unwrap();
+        for (written, read) in [(TranscriptKind::Blake2b, TranscriptKind::Poseidon), (TranscriptKind::Poseidon, TranscriptKind::Blake2b)] {
+            let proof = create_ipa_proof(&params, &pk, &circuit, &[&expected], &mut rng, written).unwrap();
+            verify_ipa_proof(&params, pk.get_vk(), &proof, &[&expected], written).unwrap();
+            assert!(
+                matches!(verify_ipa_proof(&params, pk.get_vk(), &proof, &[&expected], read), Err(BenchmarkError::ProverError(_))),
+                "a proof written with the {:?} transcript verified with the {:?} one", written, read
+            );
+        }
+
+        let words = [3, 4, 5].map(Fp::from);
+        let mut transcript = PoseidonWrite::<_, EqAffine>::init(vec![]).unwrap();
+        for word in words {
+            Transcript::<EqAffine, _>::common_scalar(&mut transcript, word).unwrap();
+        }
+        let challenge = Transcript::<EqAffine, _>::squeeze_challenge(&mut transcript);
+        assert_eq!(challenge.get_scalar(), poseidon_hash(&words).unwrap());
+    }
+}
@@ -0,0 +1,1074 @@
+use ff::{Field, PrimeField};
+use std::fmt::Debug;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, BTreeSet};
+use clap::ValueEnum;
+use serde::Serialize;
+use halo2_proofs::{
+    dev::CircuitCost,
+    plonk::{Circuit, ConstraintSystem, Expression},
+};
+use group::GroupEncoding;
+use halo2curves::bls12381::{Fr, G1};
+
+use crate::analysis::{ConstraintSystemSummary, ProverDomain, VerificationCost, render_expression};
+use crate::circuits::{FloorPlannerKind, OutputMode};
+use crate::constants::{constants_hash, preset_with_hash};
+use crate::error::BenchmarkError;
+use crate::gates::{ConstantStrategy, SboxLayout, record_gates, selector_index};
+use crate::generate::{MdsOrigin, poseidon_mds_origin, rescue_mds_origin};
+use crate::params::{Anemoi, Bars, BarsRound, Endianness, PermutationParameters, Poseidon, RescuePrime, fe_to_decimal, fe_to_hex, get_common_params};
+use crate::proving::{Backend, ProofScheme};
+use crate::synthesis::{AdviceCells, CheckMode, FixedCells, RegionUsage, advice_cells, fixed_cells, pinned_count};
+use crate::target::PermutationKind;
+use crate::transcript::TranscriptKind;
+
+// helper function to format public outputs for the report, big-endian like integers are written
+pub(crate) fn hex_words<F: PrimeField>(words: &[F]) -> Vec<String> {
+    words.iter().map(|word| fe_to_hex(word, Endianness::Big)).collect()
+}
+
+// helper function to flatten the Debug output of halo2's cost structs into named counters,
+// nested structs become dotted keys (e.g. "advice.commitments"); their fields are private otherwise
+fn debug_fields(debug: &str) -> BTreeMap<String, usize> {
+    let mut fields = BTreeMap::new();
+    let mut path: Vec<&str> = Vec::new();
+    let mut pending: Option<&str> = None;
+
+    for token in debug.split([' ', ',']).filter(|t| !t.is_empty()) {
+        if let Some(name) = token.strip_suffix(':') {
+            pending = Some(name);
+        } else if token == "{" {
+            // the outermost struct has no field name
+            path.push(pending.take().unwrap_or(""));
+        } else if token == "}" {
+            path.pop();
+        } else if let (Some(name), Ok(value)) = (pending, token.parse::<usize>()) {
+            let key = path.iter().copied().filter(|p| !p.is_empty()).chain([name]).collect::<Vec<_>>().join(".");
+            fields.insert(key, value);
+            pending = None;
+        } else if !token.chars().next().is_some_and(char::is_uppercase) || token.contains('<') {
+            // anything but a struct name (e.g. PhantomData<..>) ends the pending field
+            pending = None;
+        }
+    }
+
+    fields
+}
+
+// structure for the permutation parameters recorded in the benchmark report
+#[derive(Serialize)]
+pub(crate) struct ParametersReport {
+    pub(crate) state_size: usize,
+    pub(crate) rate: usize,
+    pub(crate) capacity: usize,
+    pub(crate) rounds: BTreeMap<&'static str, usize>,
+    pub(crate) round_constants: usize,
+    pub(crate) alpha: String,
+    // capacity value used by the compression statement
+    pub(crate) domain_tag: String,
+    // the derivation that gives the MDS matrix, or custom
+    pub(crate) mds: MdsOrigin,
+    // the content hash of the round constants and MDS matrix, for the permutations with presets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) constants: Option<ConstantsReport>
+}
+
+// structure for the constants a permutation was measured with: their hash (see constants_hash) and the preset with the
+// same hash, if any, so that a published result names the exact constants behind it
+#[derive(Serialize)]
+pub(crate) struct ConstantsReport {
+    pub(crate) hash: String,
+    pub(crate) preset: Option<&'static str>
+}
+
+impl ConstantsReport {
+    pub(crate) fn of<F: PrimeField>(round_constants: &[F], mds: &[[F; 3]; 3]) -> Self {
+        let hash = constants_hash(round_constants, mds);
+        ConstantsReport { preset: preset_with_hash(&hash).map(|preset| preset.name), hash }
+    }
+}
+
+// structure for the analytic cost model section of the benchmark report (halo2_proofs::dev::cost)
+#[derive(Serialize)]
+pub(crate) struct CostReport {
+    // column, query, permutation, and row counts as computed by CircuitCost
+    pub(crate) circuit: BTreeMap<String, usize>,
+    // (commitments, evaluations) per component for a proof over one instance
+    pub(crate) proof_components: BTreeMap<String, usize>,
+    pub(crate) marginal_proof_size: usize,
+    pub(crate) estimated_proof_size: usize,
+    // bytes of the estimate that the lookup arguments contribute (their commitments and evaluations)
+    pub(crate) lookup_proof_size: usize,
+    // per-gate constraints, degree and queried cells
+    pub(crate) gates: GateInventory,
+    pub(crate) selectors: SelectorReport,
+    // data placed in fixed cells, read from one synthesis
+    pub(crate) fixed: FixedCells,
+    // advice cells of the used rows split into computed values, copies and padding, read from the same synthesis
+    pub(crate) advice: AdviceCells,
+    // filled in once real proofs are generated
+    pub(crate) measured_proof_size: Option<usize>
+}
+
+// structure for the selectors a circuit declares and the fixed columns before and after keygen compresses the simple
+// selectors into fixed columns (the verifying key commits to the latter)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct SelectorReport {
+    pub(crate) selectors: usize,
+    pub(crate) fixed_columns: usize,
+    pub(crate) compressed_fixed_columns: usize
+}
+
+impl SelectorReport {
+    // the declared counts are read from the pinned constraint system, whose fields are private in halo2_proofs 0.3;
+    // the compressed count comes from CircuitCost, which compresses the selectors like keygen does
+    fn collect<F: Field, C: Circuit<F>>(compressed_fixed_columns: usize) -> Self {
+        let mut meta = ConstraintSystem::default();
+        C::configure(&mut meta);
+        let pinned = format!("{:?}", meta.pinned());
+        SelectorReport {
+            selectors: pinned_count(&pinned, "num_selectors: "),
+            fixed_columns: pinned_count(&pinned, "num_fixed_columns: "),
+            compressed_fixed_columns
+        }
+    }
+}
+
+// structure for one gate: its constraint count, polynomial degree and the (column, rotation) pairs it queries
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct GateReport {
+    pub(crate) name: &'static str,
+    pub(crate) constraints: usize,
+    pub(crate) degree: usize,
+    // e.g. "advice[0]@1" for advice column 0 at the next row
+    pub(crate) queries: BTreeSet<String>,
+    // the constraints rendered as text, for the constraint system summary only
+    #[serde(skip)]
+    pub(crate) expressions: Vec<String>,
+    // allocation indices of the selectors the gate queries (see allocate_selector), for the selector overlap lint only
+    #[serde(skip)]
+    pub(crate) selectors: BTreeSet<usize>
+}
+
+// structure for the gates of one circuit, in creation order, with totals
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct GateInventory {
+    pub(crate) gates: Vec<GateReport>,
+    pub(crate) total_constraints: usize,
+    pub(crate) max_degree: usize,
+    // distinct (column, rotation) pairs over all gates, and how many of them are advice queries
+    pub(crate) distinct_queries: usize,
+    pub(crate) advice_queries: usize
+}
+
+// helper function to label a query as kind[column]@rotation; the query fields are private in halo2 0.3, so the column
+// index and rotation are read from the Debug output (e.g. "AdviceQuery { index: 0, column_index: 1, rotation: Rotation(0) }")
+pub(crate) fn query_label(kind: &str, debug: &str) -> String {
+    let column = debug_fields(debug).get("column_index").copied().unwrap_or_default();
+    let rotation = debug.split("Rotation(").nth(1).and_then(|rest| rest.split(')').next()).unwrap_or("?");
+    format!("{}[{}]@{}", kind, column, rotation)
+}
+
+// helper function to collect the cells an expression queries (selectors are not cells and are skipped)
+fn collect_queries<F: Field>(expression: &Expression<F>, queries: &mut BTreeSet<String>) {
+    match expression {
+        Expression::Fixed(query) => {
+            queries.insert(query_label("fixed", &format!("{:?}", query)));
+        }
+        Expression::Advice(query) => {
+            queries.insert(query_label("advice", &format!("{:?}", query)));
+        }
+        Expression::Instance(query) => {
+            queries.insert(query_label("instance", &format!("{:?}", query)));
+        }
+        Expression::Negated(a) | Expression::Scaled(a, _) => collect_queries(a, queries),
+        Expression::Sum(a, b) | Expression::Product(a, b) => {
+            collect_queries(a, queries);
+            collect_queries(b, queries);
+        }
+        Expression::Constant(_) | Expression::Selector(_) => {}
+    }
+}
+
+// helper function to collect the indices of the selectors an expression queries
+fn collect_selectors<F: Field>(expression: &Expression<F>, selectors: &mut BTreeSet<usize>) {
+    match expression {
+        Expression::Selector(selector) => {
+            selectors.extend(selector_index(selector));
+        }
+        Expression::Negated(a) | Expression::Scaled(a, _) => collect_selectors(a, selectors),
+        Expression::Sum(a, b) | Expression::Product(a, b) => {
+            collect_selectors(a, selectors);
+            collect_selectors(b, selectors);
+        }
+        Expression::Constant(_) | Expression::Fixed(_) | Expression::Advice(_) | Expression::Instance(_) => {}
+    }
+}
+
+impl GateReport {
+    pub(crate) fn new<F: PrimeField>(name: &'static str, constraints: &[Expression<F>]) -> Self {
+        let mut queries = BTreeSet::new();
+        let mut selectors = BTreeSet::new();
+        for constraint in constraints {
+            collect_queries(constraint, &mut queries);
+            collect_selectors(constraint, &mut selectors);
+        }
+
+        GateReport {
+            name,
+            constraints: constraints.len(),
+            degree: constraints.iter().map(Expression::degree).max().unwrap_or(0),
+            queries,
+            expressions: constraints.iter().map(render_expression).collect(),
+            selectors
+        }
+    }
+}
+
+impl GateInventory {
+    // configure the circuit once and read back the gates it creates
+    pub(crate) fn collect<F: Field, C: Circuit<F>>() -> Self {
+        let gates = record_gates(|| {
+            C::configure(&mut ConstraintSystem::default());
+        });
+        let distinct: BTreeSet<&String> = gates.iter().flat_map(|gate| &gate.queries).collect();
+
+        GateInventory {
+            total_constraints: gates.iter().map(|gate| gate.constraints).sum(),
+            max_degree: gates.iter().map(|gate| gate.degree).max().unwrap_or(0),
+            distinct_queries: distinct.len(),
+            advice_queries: distinct.iter().filter(|query| query.starts_with("advice")).count(),
+            gates
+        }
+    }
+
+    // the inventory as a text table
+    pub(crate) fn table(&self, label: &str) -> String {
+        let mut table = format!("{} gates:\n", label);
+        table += &format!("  {:<22} {:>11} {:>6}  queries\n", "gate", "constraints", "degree");
+        for gate in &self.gates {
+            let queries = gate.queries.iter().map(String::as_str).collect::<Vec<_>>().join(" ");
+            table += &format!("  {:<22} {:>11} {:>6}  {}\n", gate.name, gate.constraints, gate.degree, queries);
+        }
+        table += &format!(
+            "  {:<22} {:>11} {:>6}  {} distinct ({} advice)\n",
+            "total", self.total_constraints, self.max_degree, self.distinct_queries, self.advice_queries
+        );
+        table
+    }
+
+    pub(crate) fn print_table(&self, label: &str) {
+        print!("{}", self.table(label));
+    }
+}
+
+// structure for the measurements of one permutation circuit
+#[derive(Serialize)]
+pub(crate) struct CircuitReport {
+    pub(crate) permutation: &'static str,
+    // "permutation" (all three output words public) or "preimage" (only the digest public)
+    pub(crate) statement: &'static str,
+    pub(crate) output_mode: OutputMode,
+    // public outputs as 0x-prefixed big-endian hex
+    pub(crate) outputs: Vec<String>,
+    pub(crate) parameters: ParametersReport,
+    #[serde(flatten)]
+    pub(crate) timings: CircuitTimings,
+    pub(crate) cost: CostReport
+}
+
+// how often each timed phase runs: `warmup` untimed rounds first, then `rounds` timed ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Repeat {
+    pub rounds: usize,
+    pub warmup: usize
+}
+
+impl Repeat {
+    // a single timed round without warmup
+    pub const ONCE: Repeat = Repeat { rounds: 1, warmup: 0 };
+}
+
+// statistics over the timed rounds of one phase, in the unit of the phase
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub(crate) struct TimingStats {
+    pub(crate) rounds: usize,
+    pub(crate) min: f64,
+    pub(crate) median: f64,
+    pub(crate) mean: f64,
+    // sample standard deviation, 0 for a single round
+    pub(crate) stddev: f64,
+    pub(crate) max: f64
+}
+
+impl TimingStats {
+    // the statistics of the samples, None when there are none
+    pub(crate) fn from_samples(samples: &[f64]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let (&min, &max) = (sorted.first()?, sorted.last()?);
+        let n = sorted.len();
+        let median = if n % 2 == 1 { sorted[n / 2] } else { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 };
+        // the rounding of the sum must not push the mean outside the samples
+        let mean = (sorted.iter().sum::<f64>() / n as f64).clamp(min, max);
+        let variance = sorted.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / (n.max(2) - 1) as f64;
+        Some(TimingStats { rounds: n, min, median, mean, stddev: variance.sqrt(), max })
+    }
+
+    // the order the statistics must be in: min <= median, mean <= max, and a non-negative spread
+    #[cfg(test)]
+    pub(crate) fn is_consistent(&self) -> bool {
+        self.min <= self.median && self.median <= self.max && self.min <= self.mean && self.mean <= self.max && self.stddev >= 0.0
+    }
+
+    // median ± stddev, as in the timing table
+    fn table_cell(&self) -> String {
+        format!("{:.3} ± {:.3}", self.median, self.stddev)
+    }
+}
+
+// helper function for the statistics of integer samples
+pub(crate) fn integer_stats(samples: &[u128]) -> Option<TimingStats> {
+    TimingStats::from_samples(&samples.iter().map(|&sample| sample as f64).collect::<Vec<_>>())
+}
+
+// structure for the timings of one circuit, one entry per timed MockProver round, with their statistics
+#[derive(Serialize)]
+pub(crate) struct CircuitTimings {
+    // whether MockProver ran at the measured k or at the smallest k that fits
+    pub(crate) check_mode: CheckMode,
+    // MockProver::run, which synthesizes the circuit into its own cell storage
+    pub(crate) mock_prover_ms: Vec<u128>,
+    // MockProver::verify alone, i.e. checking the gates and copy constraints on the assigned cells
+    pub(crate) constraint_check_ms: Vec<u128>,
+    // synthesis alone (no MockProver bookkeeping), in microseconds
+    pub(crate) synthesis_us: Vec<u128>,
+    // synthesis into 2^k-row advice columns with the values evaluated, the first phase of create_proof
+    pub(crate) witness_gen_ms: Vec<f64>,
+    // whether witness generation took less time in total than MockProver (run and verify) at the same k; witness
+    // generation is one synthesis without MockProver's cell bookkeeping and constraint checks. Left out when a quick
+    // check ran MockProver at a smaller k
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) witness_gen_faster: Option<bool>,
+    // statistics of each phase above, by its name
+    pub(crate) stats: BTreeMap<&'static str, TimingStats>
+}
+
+impl CircuitTimings {
+    pub(crate) fn new(
+        check_mode: CheckMode,
+        mock_prover_ms: Vec<u128>,
+        constraint_check_ms: Vec<u128>,
+        synthesis_us: Vec<u128>,
+        witness_gen_ms: Vec<f64>
+    ) -> Self {
+        let stats = [
+            ("mock_prover_ms", integer_stats(&mock_prover_ms)),
+            ("constraint_check_ms", integer_stats(&constraint_check_ms)),
+            ("synthesis_us", integer_stats(&synthesis_us)),
+            ("witness_gen_ms", TimingStats::from_samples(&witness_gen_ms))
+        ];
+        CircuitTimings {
+            check_mode,
+            stats: stats.into_iter().filter_map(|(phase, stats)| stats.map(|stats| (phase, stats))).collect(),
+            mock_prover_ms,
+            constraint_check_ms,
+            synthesis_us,
+            witness_gen_ms,
+            witness_gen_faster: None
+        }
+    }
+
+    // one line of the timing table: median ± stddev over the rounds, and the real proof's when the circuit was proved
+    fn table_row(&self, label: &str, proving: Option<&TimingStats>) -> String {
+        let cell = |phase: &str| self.stats.get(phase).map_or("-".to_string(), TimingStats::table_cell);
+        format!(
+            "{:<36} {:>20} {:>20} {:>20} {:>22}\n",
+            label,
+            cell("witness_gen_ms"),
+            cell("constraint_check_ms"),
+            cell("mock_prover_ms"),
+            proving.map_or("-".to_string(), TimingStats::table_cell)
+        )
+    }
+}
+
+// structure for one real proof (keygen, proving and verification over Pasta); the times are the medians over the
+// timed rounds, each with a fresh proof, next to their statistics
+#[derive(Serialize)]
+pub(crate) struct ProofReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) backend: Backend,
+    pub(crate) scheme: ProofScheme,
+    pub(crate) transcript: TranscriptKind,
+    pub(crate) proof_size: usize,
+    pub(crate) proving_ms: u128,
+    pub(crate) verification_ms: u128,
+    // threads of the rayon pool that keygen, proving and verification ran on
+    pub(crate) threads: usize,
+    pub(crate) proving_stats: TimingStats,
+    pub(crate) verification_stats: TimingStats
+}
+
+// structure for the verify-batch report: each proof verified on its own, and all of them in one batch
+#[derive(Serialize)]
+pub(crate) struct BatchVerifyReport {
+    pub(crate) k: u32,
+    pub(crate) proofs: Vec<BatchedProofReport>,
+    // sum of the single verification times, against one batched verification of the same proofs
+    pub(crate) individual_ms: f64,
+    pub(crate) batched_ms: f64,
+    // proof files the batch rejected, found by verifying them one by one
+    pub(crate) rejected: Vec<String>
+}
+
+// structure for one proof file of a batch
+#[derive(Serialize)]
+pub(crate) struct BatchedProofReport {
+    pub(crate) path: String,
+    pub(crate) permutation: PermutationKind,
+    pub(crate) proof_size: usize,
+    pub(crate) verification_ms: f64,
+    pub(crate) valid: bool
+}
+
+// structure for one point of a thread scaling curve
+#[derive(Serialize)]
+pub(crate) struct ScalingPoint {
+    pub(crate) threads: usize,
+    // median proving time at the first thread count over the one at this count
+    pub(crate) speedup: f64,
+    pub(crate) proof: ProofReport
+}
+
+// structure for the same proof at several thread counts
+#[derive(Serialize)]
+pub(crate) struct ScalingReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) k: u32,
+    pub(crate) points: Vec<ScalingPoint>
+}
+
+// structure for a batch of independent permutations proved in one proof, at the smallest k that fits
+#[derive(Serialize)]
+pub(crate) struct BatchReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) batch_size: usize,
+    pub(crate) k: u32,
+    pub(crate) rows: usize,
+    pub(crate) proof: ProofReport,
+    pub(crate) proving_ms_per_hash: f64,
+    // whether it was proved in low-memory mode, and the most heap live while it was (commitment parameters, keygen,
+    // proving and verification), if the allocator counts it
+    pub(crate) low_memory: bool,
+    pub(crate) peak_heap_bytes: Option<usize>
+}
+
+// structure for a transcript hashed through the sponge and proved in one proof, at the smallest k that fits
+#[derive(Serialize)]
+pub(crate) struct TranscriptReport {
+    pub(crate) permutation: &'static str,
+    // MAC under a private key (message and tag public) rather than an unkeyed transcript challenge
+    pub(crate) keyed: bool,
+    pub(crate) elements: usize,
+    pub(crate) k: u32,
+    pub(crate) rows: usize,
+    pub(crate) rows_per_element: f64,
+    pub(crate) proof: ProofReport
+}
+
+// structure for a byte message packed into field elements, hashed through the sponge and proved in one proof, at the
+// smallest k that fits
+#[derive(Serialize)]
+pub(crate) struct BytesReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) bytes: usize,
+    // packed elements, including the length prefix
+    pub(crate) elements: usize,
+    pub(crate) k: u32,
+    pub(crate) rows: usize,
+    pub(crate) rows_per_byte: f64,
+    pub(crate) proof: ProofReport
+}
+
+// structure for a message exposed in a second instance column, absorbed from there through the sponge and proved in
+// one proof with its digest, at the smallest k that fits
+#[derive(Serialize)]
+pub(crate) struct PublicMessageReport {
+    pub(crate) permutation: &'static str,
+    // message slots, all filled
+    pub(crate) max_len: usize,
+    // absorbed elements, including the length prefix
+    pub(crate) elements: usize,
+    pub(crate) k: u32,
+    pub(crate) rows: usize,
+    // rows amortized over the message words
+    pub(crate) rows_per_element: f64,
+    pub(crate) proof: ProofReport
+}
+
+// structure for a stream of outputs squeezed from a seed in duplex mode and proved in one proof, at the smallest k
+// that fits
+#[derive(Serialize)]
+pub(crate) struct StreamReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) seed_elements: usize,
+    pub(crate) outputs: usize,
+    pub(crate) k: u32,
+    pub(crate) rows: usize,
+    // rows amortized over the outputs, including the absorption of the seed
+    pub(crate) rows_per_output: f64,
+    pub(crate) proof: ProofReport
+}
+
+// structure for one arity of the Merkle benchmark: the tree built natively, and one full path from a leaf to the
+// public root proved at the smallest k that fits
+#[derive(Serialize)]
+pub(crate) struct MerkleReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) arity: usize,
+    // state width of the compression, arity + 1
+    pub(crate) width: usize,
+    pub(crate) leaves: usize,
+    pub(crate) depth: usize,
+    // compressions to build the whole tree natively, and the time they took
+    pub(crate) tree_hashes: usize,
+    pub(crate) native_tree_ms: u128,
+    // rows of one compression (its permutation region and the row of the tag in the constants column), and of the whole
+    // path circuit including the sibling loads and selects
+    pub(crate) compression_rows: usize,
+    pub(crate) k: u32,
+    pub(crate) rows: usize,
+    pub(crate) proof: ProofReport
+}
+
+// structure for one permutation of the membership demo: the path of one leaf to the public root and its public
+// nullifier, proved in one circuit at the smallest k that fits
+#[derive(Serialize)]
+pub(crate) struct MembershipReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) leaves: usize,
+    pub(crate) depth: usize,
+    // one compression per level and one for the nullifier
+    pub(crate) permutations: usize,
+    pub(crate) k: u32,
+    pub(crate) rows: usize,
+    pub(crate) proof: ProofReport
+}
+
+// structure for one point of a parameter sweep: a permutation circuit proved at a given k
+#[derive(Serialize)]
+pub(crate) struct SweepReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) k: u32,
+    // smallest k that fits the circuit
+    pub(crate) minimal_k: u32,
+    pub(crate) output_mode: OutputMode,
+    pub(crate) floor_planner: FloorPlannerKind,
+    // the prover's domains and FFTs at this k, which the circuit's degree scales
+    pub(crate) prover: ProverDomain,
+    pub(crate) proof: ProofReport
+}
+
+// structure for both permutations in one circuit over shared columns
+#[derive(Serialize)]
+pub(crate) struct DualReport {
+    #[serde(flatten)]
+    pub(crate) timings: CircuitTimings,
+    pub(crate) cost: CostReport,
+    // max_rows of each permutation in its own circuit, to compare with the combined layout
+    pub(crate) separate_rows: BTreeMap<&'static str, usize>
+}
+
+// structure for the complete benchmark report, serialized to JSON at the end of a run
+#[derive(Serialize)]
+pub struct BenchmarkReport {
+    pub(crate) k: u32,
+    // where the round constants and MDS matrices came from (preset, generated, or file:<path>)
+    pub(crate) parameters: String,
+    // seed of the ChaCha20 RNG used for the real proofs, if the run was seeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) seed: Option<u64>,
+    pub(crate) circuits: Vec<CircuitReport>,
+    // both permutations in one circuit, only measured by the full benchmark
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) dual: Option<DualReport>,
+    pub(crate) proofs: Vec<ProofReport>,
+    // the Poseidon circuit proved with the Blake2b and the Poseidon transcript
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) transcripts: Vec<ProofReport>,
+    // both permutation circuits with round constants in fixed columns, in advice cells pinned to constants and inlined
+    // in a gate per ARC step
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) constant_strategies: Vec<ConstantStrategyReport>,
+    // the Poseidon circuit with the x^5 S-box constrained directly and through a witnessed square
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sbox_layouts: Vec<SboxLayoutReport>,
+    // rows and smallest k of the permutation and batch circuits under SimpleFloorPlanner and V1
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) floor_planners: Vec<FloorPlannerReport>,
+    // rows of one permutation region before and after the round constants were merged into the neighbouring gates
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) region_rows: Vec<RegionRowsReport>,
+    // every region of the measured circuits with its starting row and row count, to compare floor planners and see
+    // where a layout leaves rows unused
+    pub(crate) regions: Vec<RegionUsage>,
+    // the bar layer variant for several limb widths: table size and lookups against degree and rows
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) bar_layers: Vec<BarLayerReport>,
+    // time to load each preset's round constants and MDS matrix, parsed from the strings and from the embedded limbs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) constants_loading: Vec<ConstantsLoadReport>,
+    // verification cost of each permutation circuit under each multi-open scheme, by permutation
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) verification: BTreeMap<&'static str, Vec<VerificationCost>>,
+    // constraint system summary of each permutation circuit, by permutation
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) analysis: BTreeMap<&'static str, ConstraintSystemSummary>
+}
+
+// structure for loading the tables of one preset in its own field, in microseconds per load
+#[derive(Serialize)]
+pub(crate) struct ConstantsLoadReport {
+    pub(crate) preset: &'static str,
+    // round constants and MDS entries
+    pub(crate) words: usize,
+    pub(crate) parsed_us: f64,
+    pub(crate) embedded_us: f64
+}
+
+// structure for the rows of one permutation region
+#[derive(Serialize)]
+pub(crate) struct RegionRowsReport {
+    pub(crate) permutation: &'static str,
+    // the layout with a row of its own for every ARC step
+    pub(crate) previous_rows: usize,
+    // the chip's prediction, and the rows synthesis actually used
+    pub(crate) expected_rows: usize,
+    pub(crate) rows: usize
+}
+
+// structure for one circuit laid out by one floor planner; the permutation circuits are also proved over Pasta
+#[derive(Serialize)]
+pub(crate) struct FloorPlannerReport {
+    pub(crate) circuit: String,
+    pub(crate) floor_planner: FloorPlannerKind,
+    pub(crate) rows: usize,
+    pub(crate) minimal_k: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) proof_size: Option<usize>
+}
+
+// structure for one permutation circuit under one round constant strategy; the proof is a real IPA proof over Pasta
+#[derive(Serialize)]
+pub(crate) struct ConstantStrategyReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) strategy: ConstantStrategy,
+    pub(crate) gates: usize,
+    // after keygen compresses the selectors, i.e. the fixed commitments of the verifying key
+    pub(crate) fixed_columns: usize,
+    pub(crate) fixed: FixedCells,
+    pub(crate) advice_columns: usize,
+    pub(crate) copy_constraints: usize,
+    pub(crate) estimated_proof_size: usize,
+    pub(crate) proof_size: usize
+}
+
+// structure for one permutation circuit under one S-box layout; the proof is a real IPA proof over Pasta
+#[derive(Serialize)]
+pub(crate) struct SboxLayoutReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) layout: SboxLayout,
+    // degree of the constraint system, including the selectors and the permutation argument
+    pub(crate) degree: usize,
+    pub(crate) rows: usize,
+    pub(crate) advice_columns: usize,
+    // commitments of the vanishing argument: the degree - 1 pieces of the quotient and the random polynomial
+    pub(crate) quotient_commitments: usize,
+    pub(crate) estimated_proof_size: usize,
+    pub(crate) proof: ProofReport
+}
+
+// structure for the bar layer variant with one limb width, at the smallest k its rows and table fit
+#[derive(Serialize)]
+pub(crate) struct BarLayerReport {
+    pub(crate) permutation: &'static str,
+    pub(crate) limb_bits: usize,
+    pub(crate) limbs: usize,
+    pub(crate) limb_prime: u64,
+    pub(crate) limb_alpha: u64,
+    // limbs per word that go through the limb S-box, the others are only range checked
+    pub(crate) active_limbs: usize,
+    pub(crate) table_rows: usize,
+    pub(crate) lookup_arguments: usize,
+    // lookups of one permutation, over all limbs, words and bar rounds
+    pub(crate) lookups: usize,
+    // degree of the constraint system, including the lookup and permutation arguments
+    pub(crate) degree: usize,
+    pub(crate) rows: usize,
+    pub(crate) k: u32,
+    pub(crate) estimated_proof_size: usize,
+    pub(crate) lookup_proof_size: usize
+}
+
+// CSV columns written by `--output csv`, one row per (permutation, k, layout, backend, scheme). The layout is the
+// statement and output mode (e.g. "preimage/digest-only"), the backend and scheme are "mock" for circuits only measured
+// with MockProver, and the times are means over the MockProver rounds. A file that is appended to must start with this
+// exact header, followed by the statistics columns of csv_columns
+pub(crate) const CSV_COLUMNS: [&str; 19] = [
+    "permutation", "k", "layout", "backend", "scheme", "parameters", "seed",
+    "mock_prover_ms", "synthesis_us", "rows", "advice_columns", "fixed_columns", "instance_columns",
+    "gates", "max_gate_degree", "estimated_proof_size", "proof_size", "proving_ms", "verification_ms"
+];
+
+// timed phases with a CSV column per statistic after CSV_COLUMNS (e.g. proving_ms_median); the proof phases are empty
+// for circuits that were not proved
+pub(crate) const CSV_STAT_PHASES: [&str; 6] = ["mock_prover_ms", "constraint_check_ms", "synthesis_us", "witness_gen_ms", "proving_ms", "verification_ms"];
+pub(crate) const CSV_STATS: [&str; 5] = ["min", "median", "mean", "stddev", "max"];
+
+// the full CSV header: CSV_COLUMNS, then <phase>_<statistic> for every timed phase
+pub(crate) fn csv_columns() -> Vec<String> {
+    CSV_COLUMNS.iter().map(|column| column.to_string())
+        .chain(CSV_STAT_PHASES.iter().flat_map(|phase| CSV_STATS.iter().map(move |stat| format!("{}_{}", phase, stat))))
+        .collect()
+}
+
+// helper function to quote a CSV field that holds a separator, a quote or a line break (RFC 4180)
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// helper function to split one CSV record into its fields, undoing csv_field
+pub(crate) fn parse_csv_record(record: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(String::new()),
+            (c, _) => fields.last_mut().unwrap().push(c)
+        }
+    }
+    fields
+}
+
+// helper function for the mean of the timing rounds, empty when nothing was measured
+fn csv_mean(values: &[u128]) -> String {
+    if values.is_empty() {
+        return String::new();
+    }
+    format!("{:.3}", values.iter().sum::<u128>() as f64 / values.len() as f64)
+}
+
+// extra output written by `bench --output <format> <path>`, next to the JSON printed to stdout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReportOutput {
+    // rows appended to a CSV file, see CSV_COLUMNS
+    Csv(PathBuf),
+    // a GitHub-flavored Markdown table, overwriting the file
+    Markdown(PathBuf)
+}
+
+// Markdown table columns; all but the first are right-aligned. Advice cells are the advice rows times the advice
+// columns, times are medians over the rounds, and the proving, verification and proof size columns are empty ("-")
+// for circuits that were not proved
+pub(crate) const MARKDOWN_COLUMNS: [&str; 9] = [
+    "circuit", "rows", "gates", "max degree", "advice cells", "witness gen", "prove", "verify", "proof size"
+];
+
+// helper function to format a time given in milliseconds with the unit that keeps it readable (µs, ms or s)
+pub(crate) fn human_duration(ms: f64) -> String {
+    if ms < 1.0 {
+        format!("{:.1} µs", ms * 1000.0)
+    } else if ms < 1000.0 {
+        format!("{:.2} ms", ms)
+    } else {
+        format!("{:.2} s", ms / 1000.0)
+    }
+}
+
+impl BenchmarkReport {
+    // table of the witness generation, constraint check and MockProver times per circuit (milliseconds, median ±
+    // stddev), next to the real proof's proving time for the circuits that were proved
+    pub(crate) fn timing_table(&self) -> String {
+        let mut table = format!("{:<36} {:>20} {:>20} {:>20} {:>22}\n", "circuit", "witness_gen_ms", "constraint_check_ms", "mock_prover_ms", "proving_ms");
+        for circuit in &self.circuits {
+            let proved = circuit.statement == "permutation" && circuit.output_mode == OutputMode::AllState;
+            let proof = self.proofs.iter().find(|proof| proved && proof.permutation == circuit.permutation);
+            let label = format!("{} {}", circuit.permutation, circuit.statement);
+            table += &circuit.timings.table_row(&label, proof.map(|proof| &proof.proving_stats));
+        }
+        if let Some(dual) = &self.dual {
+            table += &dual.timings.table_row("Poseidon+Rescue-Prime dual", None);
+        }
+        table
+    }
+
+    // table of the regions of each circuit, printed with --verbose
+    pub(crate) fn region_table(&self) -> String {
+        let mut table = format!("{:<28} {:<28} {:>8} {:>8}\n", "circuit", "region", "start", "rows");
+        for region in &self.regions {
+            table += &format!("{:<28} {:<28} {:>8} {:>8}\n", region.circuit, region.name, region.start, region.rows);
+        }
+        table
+    }
+
+    // one row per measured circuit, with the real proof's metrics on the rows of the circuits that were proved
+    pub(crate) fn csv_rows(&self) -> Vec<Vec<String>> {
+        let seed = self.seed.map(|seed| seed.to_string()).unwrap_or_default();
+        let row = |permutation: &str, layout: String, timings: &CircuitTimings, cost: &CostReport, proof: Option<&ProofReport>| {
+            let circuit = |key: &str| cost.circuit.get(key).map(usize::to_string).unwrap_or_default();
+            let backend = proof.map_or("mock".to_string(), |proof| proof.backend.to_possible_value().unwrap().get_name().to_string());
+            let scheme = proof.map_or("mock".to_string(), |proof| proof.scheme.name().to_string());
+            vec![
+                permutation.to_string(),
+                self.k.to_string(),
+                layout,
+                backend,
+                scheme,
+                self.parameters.clone(),
+                seed.clone(),
+                csv_mean(&timings.mock_prover_ms),
+                csv_mean(&timings.synthesis_us),
+                circuit("max_rows"),
+                circuit("num_advice_columns"),
+                circuit("num_fixed_columns"),
+                circuit("num_instance_columns"),
+                cost.gates.gates.len().to_string(),
+                cost.gates.max_degree.to_string(),
+                cost.estimated_proof_size.to_string(),
+                proof.map(|proof| proof.proof_size.to_string()).unwrap_or_default(),
+                proof.map(|proof| proof.proving_ms.to_string()).unwrap_or_default(),
+                proof.map(|proof| proof.verification_ms.to_string()).unwrap_or_default()
+            ].into_iter()
+                .chain(CSV_STAT_PHASES.iter().flat_map(|&phase| {
+                    let stats = match phase {
+                        "proving_ms" => proof.map(|proof| proof.proving_stats),
+                        "verification_ms" => proof.map(|proof| proof.verification_stats),
+                        phase => timings.stats.get(phase).copied()
+                    };
+                    let values = stats.map(|stats| [stats.min, stats.median, stats.mean, stats.stddev, stats.max]);
+                    CSV_STATS.iter().enumerate().map(move |(i, _)| values.map(|values| format!("{:.3}", values[i])).unwrap_or_default())
+                }))
+                .collect::<Vec<String>>()
+        };
+
+        let mut rows: Vec<Vec<String>> = self.circuits.iter()
+            .map(|circuit| {
+                // the real proofs are made for the permutation statement with all state words public
+                let proved = circuit.statement == "permutation" && circuit.output_mode == OutputMode::AllState;
+                let proof = self.proofs.iter().find(|proof| proved && proof.permutation == circuit.permutation);
+                let output_mode = circuit.output_mode.to_possible_value().unwrap();
+                let layout = format!("{}/{}", circuit.statement, output_mode.get_name());
+                row(circuit.permutation, layout, &circuit.timings, &circuit.cost, proof)
+            })
+            .collect();
+        if let Some(dual) = &self.dual {
+            rows.push(row("Poseidon+Rescue-Prime", "dual/all-state".to_string(), &dual.timings, &dual.cost, None));
+        }
+        rows
+    }
+
+    // one row per measured circuit for the Markdown table, in the order and with the proofs of csv_rows
+    fn markdown_rows(&self) -> Vec<[String; 9]> {
+        let row = |label: String, timings: &CircuitTimings, cost: &CostReport, proof: Option<&ProofReport>| {
+            let circuit = |key: &str| cost.circuit.get(key).copied().unwrap_or_default();
+            let witness_gen = timings.stats.get("witness_gen_ms").map_or(0.0, |stats| stats.median);
+            let proved = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+            [
+                label,
+                circuit("max_rows").to_string(),
+                cost.gates.gates.len().to_string(),
+                cost.gates.max_degree.to_string(),
+                (circuit("max_advice_rows") * circuit("num_advice_columns")).to_string(),
+                human_duration(witness_gen),
+                proved(proof.map(|proof| human_duration(proof.proving_stats.median))),
+                proved(proof.map(|proof| human_duration(proof.verification_stats.median))),
+                proved(proof.map(|proof| format!("{} B", proof.proof_size)))
+            ]
+        };
+
+        let mut rows: Vec<[String; 9]> = self.circuits.iter()
+            .map(|circuit| {
+                let proved = circuit.statement == "permutation" && circuit.output_mode == OutputMode::AllState;
+                let proof = self.proofs.iter().find(|proof| proved && proof.permutation == circuit.permutation);
+                let output_mode = circuit.output_mode.to_possible_value().unwrap();
+                let label = format!("{} {}/{}", circuit.permutation, circuit.statement, output_mode.get_name());
+                row(label, &circuit.timings, &circuit.cost, proof)
+            })
+            .collect();
+        if let Some(dual) = &self.dual {
+            rows.push(row("Poseidon+Rescue-Prime dual/all-state".to_string(), &dual.timings, &dual.cost, None));
+        }
+        rows
+    }
+
+    // GitHub-flavored Markdown table of the measured circuits, padded so that the source is readable as well
+    pub(crate) fn markdown_table(&self) -> String {
+        let rows = self.markdown_rows();
+        let widths: [usize; 9] = std::array::from_fn(|i| {
+            rows.iter().map(|row| row[i].chars().count()).chain([MARKDOWN_COLUMNS[i].len(), 3]).max().unwrap()
+        });
+        let line = |cells: [String; 9]| {
+            let cells = cells.iter().enumerate().map(|(i, cell)| {
+                if i == 0 { format!("{:<1$}", cell, widths[i]) } else { format!("{:>1$}", cell, widths[i]) }
+            });
+            format!("| {} |\n", cells.collect::<Vec<_>>().join(" | "))
+        };
+
+        let mut table = line(MARKDOWN_COLUMNS.map(str::to_string));
+        table += &line(std::array::from_fn(|i| {
+            if i == 0 { format!(":{}", "-".repeat(widths[i] - 1)) } else { format!("{}:", "-".repeat(widths[i] - 1)) }
+        }));
+        for row in rows {
+            table += &line(row);
+        }
+        table
+    }
+
+    // write the Markdown table to `path`, replacing the file
+    pub(crate) fn write_markdown(&self, path: &Path) -> Result<(), BenchmarkError> {
+        std::fs::write(path, self.markdown_table()).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })
+    }
+
+    // write the extra output selected with `--output`
+    pub(crate) fn write_output(&self, output: &ReportOutput) -> Result<(), BenchmarkError> {
+        match output {
+            ReportOutput::Csv(path) => self.append_csv(path),
+            ReportOutput::Markdown(path) => self.write_markdown(path)
+        }
+    }
+
+    // append the CSV rows to `path`, writing the header first if the file is new or empty
+    pub(crate) fn append_csv(&self, path: &Path) -> Result<(), BenchmarkError> {
+        let io_error = |source| BenchmarkError::Io { path: path.display().to_string(), source };
+        let header = csv_columns().join(",");
+        let existing = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(io_error(error))
+        };
+        if existing.lines().next().is_some_and(|first| first != header) {
+            return Err(BenchmarkError::ParameterMismatch(format!(
+                "{} has different CSV columns, expected the header {}", path.display(), header
+            )));
+        }
+
+        let mut text = String::new();
+        if existing.is_empty() {
+            text += &header;
+            text += "\n";
+        }
+        for row in self.csv_rows() {
+            text += &row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",");
+            text += "\n";
+        }
+
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path).map_err(io_error)?;
+        file.write_all(text.as_bytes()).map_err(io_error)
+    }
+}
+
+impl PermutationParameters {
+    // helper function to build the report section shared by both permutations
+    pub(crate) fn report(
+        &self,
+        rounds: BTreeMap<&'static str, usize>,
+        round_constants: usize,
+        alpha: String,
+        domain_tag: String,
+        mds: MdsOrigin
+    ) -> ParametersReport {
+        ParametersReport {
+            state_size: self.state_size,
+            rate: self.rate,
+            capacity: self.capacity,
+            rounds,
+            round_constants,
+            alpha,
+            domain_tag,
+            mds,
+            constants: None
+        }
+    }
+}
+
+impl<F: PrimeField> Poseidon<F> {
+    pub(crate) fn report(&self) -> ParametersReport {
+        let rounds = BTreeMap::from([("full", self.full_rounds), ("partial", self.partial_rounds)]);
+        let domain_tag = fe_to_decimal(&self.domain_tag.value());
+        ParametersReport {
+            constants: Some(ConstantsReport::of(&self.round_constants, &self.mds)),
+            ..self.common_params.report(rounds, self.n, self.sbox.exponent().to_string(), domain_tag, poseidon_mds_origin(self))
+        }
+    }
+}
+
+impl<F: PrimeField> RescuePrime<F> {
+    pub(crate) fn report(&self) -> ParametersReport {
+        let rounds = BTreeMap::from([("rounds", self.rounds), ("secure_rounds", self.secure_rounds)]);
+        let round_constants = 2 * self.rounds * self.common_params.state_size;
+        let domain_tag = fe_to_decimal(&self.domain_tag.value());
+        ParametersReport {
+            constants: Some(ConstantsReport::of(&self.round_constants, &self.mds)),
+            ..self.common_params.report(rounds, round_constants, self.alpha.to_string(), domain_tag, rescue_mds_origin(self))
+        }
+    }
+}
+
+impl<F: PrimeField, const L: usize> Anemoi<F, L> {
+    // the 2l words with one as capacity; no compression is built on Anemoi here, so the domain tag is 0
+    pub(crate) fn report(&self) -> ParametersReport {
+        let common_params = PermutationParameters { state_size: 2 * L, rate: 2 * L - 1, capacity: 1 };
+        let rounds = BTreeMap::from([("rounds", self.rounds)]);
+        common_params.report(rounds, 2 * L * self.rounds, self.alpha.to_string(), "0".to_string(), MdsOrigin::Anemoi)
+    }
+}
+
+impl<F: PrimeField> Bars<F> {
+    // t = 3 with one word as capacity; no compression is built on the bar layer variant, so the domain tag is 0
+    pub(crate) fn report(&self) -> ParametersReport {
+        let count = |kind: BarsRound| self.rounds.iter().filter(|round| **round == kind).count();
+        let rounds = BTreeMap::from([("algebraic", count(BarsRound::Algebraic)), ("bar", count(BarsRound::Bar))]);
+        get_common_params().report(rounds, 3 * self.rounds.len(), self.alpha.to_string(), "0".to_string(), MdsOrigin::Grain)
+    }
+}
+
+impl CostReport {
+    // run halo2's cost estimator over a BLS12-381 circuit
+    pub(crate) fn measure<C: Circuit<Fr> + Debug>(k: u32, circuit: &C) -> Self {
+        let cost = CircuitCost::<G1, C>::measure(k, circuit);
+        // the estimator treats each proof as covering a single circuit instance
+        let proof_size = cost.proof_size(1);
+        let proof_components = debug_fields(&format!("{:?}", proof_size));
+
+        let counts = debug_fields(&format!("{:?}", cost));
+        let selectors = SelectorReport::collect::<Fr, C>(counts["num_fixed_columns"]);
+        let point_size = <G1 as GroupEncoding>::Repr::default().as_ref().len();
+        let scalar_size = <Fr as PrimeField>::Repr::default().as_ref().len();
+        let lookup_proof_size = proof_components["lookups.commitments"] * point_size + proof_components["lookups.evaluations"] * scalar_size;
+        CostReport {
+            circuit: counts,
+            proof_components,
+            marginal_proof_size: cost.marginal_proof_size().into(),
+            estimated_proof_size: proof_size.into(),
+            lookup_proof_size,
+            gates: GateInventory::collect::<Fr, C>(),
+            selectors,
+            fixed: fixed_cells(circuit).expect("a circuit that MockProver synthesized lays out"),
+            advice: advice_cells(circuit).expect("a circuit that MockProver synthesized lays out"),
+            measured_proof_size: None
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use std::marker::PhantomData;
+use ff::PrimeField;
+use std::fmt::Debug;
+use halo2_proofs::{
+    circuit::{AssignedCell, Region, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::chip::{Number, PermutationInstructions, PermutationChip, StateWord, UtilsInstructions, assign_word};
+use crate::gates::{
+    ChipOptions, CircuitParameters, ConstantColumns, FixedAssignment, assign_constant_schedule, check_sbox_layout, constant_schedule, create_arc_gates,
+    create_gate, create_mds_arc_gate, create_select_gate, create_is_equal_gate, allocate_columns, allocate_selector, pow_expression,
+    sbox_square_columns, squared_sbox_constraints
+};
+use crate::native::{NativePermutation, mds_mul_native};
+use crate::error::BenchmarkError;
+use crate::params::{RescuePrime, SboxKind, rescue_params};
+use crate::synthesis::{check_region_rows, round_checkpoint};
+
+// Rescue-Prime chip configuration
+#[derive(Clone, Debug)]
+pub struct RescueChipConfig<F: PrimeField> {
+    // the parameters the gates were built from: the witness path reads its MDS matrix and round constants from here,
+    // so a chip has one matrix, embedded in its MixLayer gate and applied to the witness alike
+    pub(crate) permutation_params: RescuePrime<F>,
+    pub(crate) circuit_params: CircuitParameters,
+    // round constants by fixed cell, on the two MixLayer rows of each round
+    pub(crate) constant_schedule: Vec<FixedAssignment<F>>,
+    pub(crate) _marker: PhantomData<F>,
+    // the selectors below are specific to Rescue-Prime
+    pub(crate) s_sub_bytes: Selector,
+    pub(crate) s_sub_bytes_inv: Selector,
+    // MixLayer and round constant injection in one row, by ARC step (all the same one unless the gate strategy gives
+    // every step a gate of its own)
+    pub(crate) s_mds_arc: Vec<Selector>,
+    // under the squared S-box layout, the square of each SubBytes input and of each inverse SubBytes output, on the
+    // gate's row
+    pub(crate) squares: Option<[Column<Advice>; 3]>,
+    // why the parameters could not be used; the chip then has no round gates and its synthesis fails with this
+    pub(crate) error: Option<String>
+}
+
+// structure for the poseidon permutation chip
+pub struct RescueChip<F: PrimeField> {
+    pub(crate) config: RescueChipConfig<F>,
+    pub(crate) _marker: PhantomData<F>,
+}
+
+// implement the Chip trait for RescueChip
+impl<F: PrimeField> Chip<F> for RescueChip<F> {
+    type Config = RescueChipConfig<F>;
+    type Loaded = ();
+
+    // getter for the chip config
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    // getter for the loaded field
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// helper function for the constraint of one state word's x^5 S-box, from input to output: directly, or with the
+// input's square witnessed in `square` on the gate's row (the squared layout)
+fn sbox_word_constraints_rs<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    input: Expression<F>,
+    output: Expression<F>,
+    alpha: u64,
+    square: Option<Column<Advice>>
+) -> Vec<Expression<F>> {
+    match square {
+        Some(square) => squared_sbox_constraints(input, meta.query_advice(square, Rotation::cur()), output),
+        None => vec![output - pow_expression(input, alpha)]
+    }
+}
+
+// helper functions for creating Rescue-Prime specific gates
+// alpha = 5 for the benchmark instance
+// alpha_inv = 20974350070050476191779096203274386335076221000211055129041463479975432473805 = inverse(5, p-1) over BLS12-381
+pub(crate) fn create_sbox_gate_rs<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>, 
+    advice: [Column<Advice>; 3],
+    squares: Option<[Column<Advice>; 3]>,
+    s_sub_bytes: Selector,
+    alpha: u64
+) {
+    create_gate(meta, "RS_sbox_gate", |meta| {
+        let s_sub_bytes = meta.query_selector(s_sub_bytes);
+        (0..3)
+            .flat_map(|word| {
+                let a = meta.query_advice(advice[word], Rotation::cur());
+                let a_next = meta.query_advice(advice[word], Rotation::next());
+                sbox_word_constraints_rs(meta, a, a_next, alpha, squares.map(|squares| squares[word]))
+            })
+            .map(|constraint| s_sub_bytes.clone() * constraint)
+            .collect()
+    });
+}
+
+pub(crate) fn create_sbox_inv_gate_rs<F: PrimeField>(
+    meta: &mut ConstraintSystem<F>,
+    advice: [Column<Advice>; 3],
+    squares: Option<[Column<Advice>; 3]>,
+    s_sub_bytes_inv: Selector,
+    alpha: u64
+) {
+    create_gate(meta, "RS_sbox_inv_gate", |meta| {
+        let s_sub_bytes_inv = meta.query_selector(s_sub_bytes_inv);
+        // constrain a_next^alpha = a_current instead of a_next = a_current^alpha_inv, with the square of a_next
+        // witnessed under the squared layout
+        (0..3)
+            .flat_map(|word| {
+                let a = meta.query_advice(advice[word], Rotation::cur());
+                let a_next = meta.query_advice(advice[word], Rotation::next());
+                sbox_word_constraints_rs(meta, a_next, a, alpha, squares.map(|squares| squares[word]))
+            })
+            .map(|constraint| s_sub_bytes_inv.clone() * constraint)
+            .collect()
+    });
+}
+
+// implementation of additional methods for the RescueChip
+impl<F: PrimeField> RescueChip<F> {
+    // constructor
+    pub(crate) fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        RescueChip { config, _marker: PhantomData}
+    }
+
+    // configure the chip including all gates, constraints, and selectors; parameters that cannot be used leave the chip
+    // without round gates and with the error, as for PoseidonChip::configure
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constants: ConstantColumns,
+        instance: Column<Instance>,
+        params: Result<RescuePrime<F>, BenchmarkError>,
+        options: ChipOptions
+    ) -> <Self as Chip<F>>::Config {
+        let (params, error) = match params.and_then(|params| check_sbox_layout(SboxKind::Power(params.alpha)).map(|()| params)) {
+            Ok(params) => (params, None),
+            Err(error) => (RescuePrime::unconfigured(), Some(error.to_string()))
+        };
+        // enable equality on the advice and instance columns and the round constant columns for global constants,
+        // unless a host circuit sharing the columns does it itself
+        options.enable(meta, advice, constants, instance);
+
+        // create gates and constraints
+        let s_mds_arc = create_arc_gates(meta, constants, &params.round_constants, |_| (), |meta, (), constants, selector| {
+            create_mds_arc_gate(meta, advice, constants, selector, &params.mds);
+        });
+        let s_select = allocate_selector(meta);
+        let s_is_equal = allocate_selector(meta);
+        let s_sub_bytes = allocate_selector(meta);
+        let s_sub_bytes_inv = allocate_selector(meta);
+        let squares = sbox_square_columns(meta);
+
+        create_select_gate(meta, advice, s_select);
+        create_is_equal_gate(meta, advice, s_is_equal);
+        create_sbox_gate_rs(meta, advice, squares, s_sub_bytes, params.alpha);
+        create_sbox_inv_gate_rs(meta, advice, squares, s_sub_bytes_inv, params.alpha);
+
+        let circuit_params = CircuitParameters {
+            advice,
+            constants,
+            instance,
+            instance_offset: options.instance_offset,
+            s_select,
+            s_is_equal
+        };
+        
+        // each round takes four rows: SubBytes, MDS and ARC, inverse SubBytes, MDS and ARC
+        let arc_rows = (0..params.rounds).flat_map(|round| [4 * round + 1, 4 * round + 3]);
+        let constant_schedule = constant_schedule(constants, &params.round_constants, arc_rows);
+
+        // return the config
+        RescueChipConfig {
+            permutation_params: params,
+            circuit_params,
+            constant_schedule,
+            _marker: PhantomData,
+            s_sub_bytes,
+            s_sub_bytes_inv,
+            s_mds_arc,
+            squares,
+            error
+        }
+    }
+}
+
+// implementation of the PermutationInstructions trait for the RescueChip
+impl<F: PrimeField> PermutationInstructions<F> for RescueChip<F> {
+    type Num = Number<F>;
+
+    fn expose_as_public(&self, mut layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error> {
+        let config = self.config();
+        layouter.constrain_instance(num.cell(), config.circuit_params.instance, config.circuit_params.instance_offset + row)
+    }
+
+    fn assert_output(&self, mut layouter: impl Layouter<F>, num: Self::Num, expected: F) -> Result<(), Error> {
+        layouter.assign_region(|| "assert_output", |mut region| region.constrain_constant(num.cell(), expected))
+    }
+
+    fn hash(&self, layouter: impl Layouter<F>, a0: Value<F>, a1: Value<F>) -> Result<Self::Num, Error> {
+        let tag = self.config().permutation_params.domain_tag.value();
+        let [digest, _, _] = self.permute_state(layouter, [StateWord::Witness(a0), StateWord::Witness(a1), StateWord::Constant(tag)])?;
+        Ok(digest)
+    }
+
+    fn permute_state(&self, mut layouter: impl Layouter<F>, initial: [StateWord<'_, F>; 3]) -> Result<[Self::Num; 3], Error> {
+        let config = self.config();
+        if let Some(error) = &config.error {
+            tracing::error!("invalid Rescue-Prime parameters: {}", error);
+            return Err(Error::Synthesis);
+        }
+        layouter.assign_region(
+            || "Rescue-Prime_Permutation", |mut region| {
+                // the floor planner may call this closure more than once, each call gets its own span
+                let span = tracing::debug_span!("rescue.region", rows = tracing::field::Empty, advice_cells = tracing::field::Empty,
+                    fixed_cells = tracing::field::Empty, activated_gates = tracing::field::Empty);
+                let _region = span.enter();
+                let mut offset: usize = 0; // row index for computations on state
+                let mut advice_cell_ctr: usize = 0; 
+                let mut activated_gates_ctr: usize = 0;
+
+                // the round constants are laid out once in configure, here they are only replayed
+                let fixed_cell_ctr = assign_constant_schedule(&mut region, config.circuit_params.constants, &config.constant_schedule)?;
+
+                // initial state, in the first row (offset 0)
+                let advice = config.circuit_params.advice;
+                let mut state = [
+                    assign_word(&mut region, "state_0", advice[0], initial[0])?,
+                    assign_word(&mut region, "state_1", advice[1], initial[1])?,
+                    assign_word(&mut region, "state_2", advice[2], initial[2])?
+                ];
+
+                advice_cell_ctr += 3;
+
+                // helper function for the S-box power x^alpha for SubBytes
+                let alpha = config.permutation_params.alpha;
+                let sbox = |a: F| -> F { a.pow_vartime([alpha]) };
+
+                // helper function for MDS multiplication followed by the injection of round constants idx..idx + 3,
+                // both in one gate
+                let mds_arc = |
+                    state: &mut [AssignedCell<F, F>; 3], 
+                    region: &mut Region<F>, 
+                    offset: &mut usize, 
+                    idx: usize,
+                    advice_cell_ctr: &mut usize,
+                    activated_gates_ctr: &mut usize
+                | -> Result<(), Error> {
+                    // round constants for the witness, the fixed cells the gate reads (or its inlined constants) hold
+                    // the same values
+                    let rc = &config.permutation_params.round_constants[idx..idx + 3];
+
+                    config.s_mds_arc[idx / 3].enable(region, *offset)?;
+                    *activated_gates_ctr += 1;
+                    *offset += 1;
+
+                    let words = state.clone().map(|cell| cell.value().copied());
+                    let after_arc: [Value<F>; 3] = std::array::from_fn(|i| {
+                        words[0].zip(words[1]).zip(words[2])
+                            .map(|((s0, s1), s2)| mds_mul_native(&config.permutation_params.mds, &[s0, s1, s2])[i] + rc[i])
+                    });
+
+                    state[0] = region.assign_advice(|| "s0_ml", config.circuit_params.advice[0], *offset, || after_arc[0])?;
+                    state[1] = region.assign_advice(|| "s1_ml", config.circuit_params.advice[1], *offset, || after_arc[1])?;
+                    state[2] = region.assign_advice(|| "s2_ml", config.circuit_params.advice[2], *offset, || after_arc[2])?;
+                    *advice_cell_ctr += 3; // increment number of advice cells used
+
+                    Ok(())
+                };
+
+                // helper function for computing one rescue round
+                let rescue_round = |
+                    region: &mut Region<F>,
+                    state: &mut [AssignedCell<F, F>; 3],
+                    round: usize,
+                    offset: &mut usize,
+                    advice_cell_ctr: &mut usize,
+                    activated_gates_ctr: &mut usize
+                | -> Result<(), Error> {
+                    config.s_sub_bytes.enable(region, *offset)?;
+                    *activated_gates_ctr += 1;
+                    // the squared layout witnesses the square of every S-box input next to it
+                    if let Some(squares) = config.squares {
+                        for (word, column) in squares.into_iter().enumerate() {
+                            region.assign_advice(|| "square", column, *offset, || state[word].value().map(|v| v.square()))?;
+                        }
+                        *advice_cell_ctr += 3;
+                    }
+                    *offset += 1;
+
+                    let after_sb = [
+                        state[0].value().map(|v| sbox(*v)),
+                        state[1].value().map(|v| sbox(*v)),
+                        state[2].value().map(|v| sbox(*v))
+                    ];
+
+                    state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || after_sb[0])?;
+                    state[1] = region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || after_sb[1])?;
+                    state[2] = region.assign_advice(|| "s2_sb", config.circuit_params.advice[2], *offset, || after_sb[2])?;
+                    *advice_cell_ctr += 3; // increment number of advice cells used
+
+                    // MDS multiplication and round constants helper function
+                    let state_size: usize = config.permutation_params.common_params.state_size;
+                    mds_arc(state, region, offset, 2*round*state_size, advice_cell_ctr, activated_gates_ctr)?;
+
+                    // inverse SubBytes
+                    config.s_sub_bytes_inv.enable(region, *offset)?;
+                    *activated_gates_ctr += 1;
+                    
+                    let alpha_inv_vec: Vec<u64> = config.permutation_params.alpha_inv.to_u64_digits();
+
+                    let after_sb_inv = [
+                        state[0].value().map(|v| v.pow_vartime(&alpha_inv_vec)),
+                        state[1].value().map(|v| v.pow_vartime(&alpha_inv_vec)),
+                        state[2].value().map(|v| v.pow_vartime(&alpha_inv_vec))
+                    ];
+
+                    // the squared layout witnesses the square of every inverse S-box output, on the gate's row
+                    if let Some(squares) = config.squares {
+                        for (word, column) in squares.into_iter().enumerate() {
+                            region.assign_advice(|| "square", column, *offset, || after_sb_inv[word].map(|v| v.square()))?;
+                        }
+                        *advice_cell_ctr += 3;
+                    }
+                    *offset += 1;
+
+                    state[0] = region.assign_advice(|| "s0_sb", config.circuit_params.advice[0], *offset, || after_sb_inv[0])?;
+                    state[1] = region.assign_advice(|| "s1_sb", config.circuit_params.advice[1], *offset, || after_sb_inv[1])?;
+                    state[2] = region.assign_advice(|| "s2_sb", config.circuit_params.advice[2], *offset, || after_sb_inv[2])?;
+                    *advice_cell_ctr += 3; // increment number of advice cells used
+
+                    // second mds multiplication and round constants
+                    mds_arc(state, region, offset, 2*round*state_size+state_size, advice_cell_ctr, activated_gates_ctr)?;
+
+                    Ok(())
+                };
+
+                // perform the Rescue-Prime rounds
+                for i in 0..config.permutation_params.rounds {
+                    let _round = tracing::trace_span!("rescue.round", round = i, offset).entered();
+                    round_checkpoint()?;
+                    rescue_round(
+                        &mut region, 
+                        &mut state, 
+                        i, 
+                        &mut offset, 
+                        &mut advice_cell_ctr, 
+                        &mut activated_gates_ctr
+                    )?;
+                }
+
+                // the region ends on the last MixLayer output row
+                let rows = offset + 1;
+                check_region_rows("Rescue-Prime", rows, self.expected_rows())?;
+
+                span.record("rows", rows);
+                span.record("advice_cells", advice_cell_ctr);
+                span.record("fixed_cells", fixed_cell_ctr);
+                span.record("activated_gates", activated_gates_ctr);
+
+                Ok(state.map(Number::from))
+            }
+        )
+    }
+}
+
+impl<F: PrimeField> UtilsInstructions<F> for RescueChip<F> {
+    fn circuit_params(&self) -> &CircuitParameters {
+        &self.config.circuit_params
+    }
+}
+
+impl<F: PrimeField> PermutationChip<F> for RescueChip<F> {
+    const NAME: &'static str = "Rescue-Prime";
+
+    fn configure_default(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let (advice, constants, instance) = allocate_columns(meta);
+        RescueChip::configure(meta, advice, constants, instance, rescue_params(), ChipOptions::default())
+    }
+
+    fn check_parameters() -> Result<(), BenchmarkError> {
+        check_sbox_layout(SboxKind::Power(rescue_params::<F>()?.alpha))
+    }
+
+    fn from_config(config: Self::Config) -> Self {
+        RescueChip::construct(config)
+    }
+
+    fn params(&self) -> &dyn NativePermutation<F> {
+        &self.config.permutation_params
+    }
+
+    // the initial state, then four rows per round
+    fn expected_rows(&self) -> usize {
+        1 + 4 * self.config.permutation_params.rounds
+    }
+}
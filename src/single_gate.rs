@@ -0,0 +1,214 @@
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::{MockProver, VerifyFailure},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
+};
+use halo2curves::bls12381::Fr;
+
+use crate::error::BenchmarkError;
+use crate::gates::{
+    ConstantColumns, GateConstants, allocate_selector, create_add_gate, create_is_equal_gate, create_mds_arc_gate, create_mds_mul_gate, create_mul_gate, create_select_gate
+};
+use crate::params::{ArcPosition, SboxKind};
+use crate::poseidon::{create_full_round_gate_ps, create_partial_round_gate_ps};
+use crate::rescue::{create_sbox_gate_rs, create_sbox_inv_gate_rs};
+use crate::synthesis::minimal_k;
+
+// Single-gate circuits: every gate helper on its own, over two rows with handcrafted witnesses, so that a slip in one
+// gate (a wrong rotation, a constant read from the wrong column) cannot hide behind the rest of a permutation. All
+// gates are configured on the same columns, each with its own selector, and a case enables only the selector of its
+// gate. The MDS gates use the small matrix below instead of a generated one, so the expected words can be checked by
+// hand. There is no ARC gate on its own: the round constants are added inside the Poseidon S-box gates and the Rescue
+// MixLayer gate, which the cases cover with constants from fixed columns and inlined. tests/gates.rs runs the cases.
+
+// the MDS matrix of the single-gate cases
+const MDS: [[u64; 3]; 3] = [[2, 1, 1], [1, 2, 1], [1, 1, 3]];
+
+// the gates under test, one selector each; the variants with squares use the squared S-box layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SingleGate {
+    PoseidonFull,
+    PoseidonFullInverse,
+    PoseidonFullSquared,
+    PoseidonFullInline,
+    PoseidonFullAfterSbox,
+    PoseidonPartial,
+    PoseidonPartialAfterSbox,
+    RescueSbox,
+    RescueSboxSquared,
+    RescueSboxInv,
+    RescueSboxInvSquared,
+    Mds,
+    MdsArc,
+    Add,
+    Mul,
+    Select,
+    IsEqual
+}
+
+impl SingleGate {
+    pub const ALL: [SingleGate; 17] = [
+        SingleGate::PoseidonFull, SingleGate::PoseidonFullInverse, SingleGate::PoseidonFullSquared, SingleGate::PoseidonFullInline,
+        SingleGate::PoseidonFullAfterSbox, SingleGate::PoseidonPartial, SingleGate::PoseidonPartialAfterSbox, SingleGate::RescueSbox, SingleGate::RescueSboxSquared, SingleGate::RescueSboxInv,
+        SingleGate::RescueSboxInvSquared, SingleGate::Mds, SingleGate::MdsArc, SingleGate::Add, SingleGate::Mul, SingleGate::Select,
+        SingleGate::IsEqual
+    ];
+
+    // the name the gate is created under
+    pub fn gate_name(&self) -> &'static str {
+        match self {
+            SingleGate::PoseidonFull | SingleGate::PoseidonFullInverse | SingleGate::PoseidonFullSquared | SingleGate::PoseidonFullInline
+                | SingleGate::PoseidonFullAfterSbox => "PS_full_round_gate",
+            SingleGate::PoseidonPartial | SingleGate::PoseidonPartialAfterSbox => "PS_partial_round_gate",
+            SingleGate::RescueSbox | SingleGate::RescueSboxSquared => "RS_sbox_gate",
+            SingleGate::RescueSboxInv | SingleGate::RescueSboxInvSquared => "RS_sbox_inv_gate",
+            SingleGate::Mds => "ML_gate",
+            SingleGate::MdsArc => "ML_ARC_gate",
+            SingleGate::Add => "Add_gate",
+            SingleGate::Mul => "Mul_gate",
+            SingleGate::Select => "Select_gate",
+            SingleGate::IsEqual => "Is_equal_gate"
+        }
+    }
+
+    // the cells a gate constrains as its result, which the negative cases change one at a time: the next row for the
+    // two-row gates, advice[2] of the current row for the one-row gates
+    pub fn outputs(&self) -> Vec<(usize, usize)> {
+        match self {
+            SingleGate::Add | SingleGate::Mul => vec![(0, 2)],
+            SingleGate::Select | SingleGate::IsEqual => vec![(1, 0)],
+            _ => (0..3).map(|word| (1, word)).collect()
+        }
+    }
+}
+
+// the witness of one case: the state rows, the witnessed squares and the round constants, all on the first row
+// except the second state row
+#[derive(Clone, Copy, Debug)]
+pub struct GateWitness<F> {
+    pub rows: [[F; 3]; 2],
+    pub squares: [F; 3],
+    pub constants: [F; 3]
+}
+
+// circuit with every single-gate configuration, enabling the gate of `gate` on the first row of one region
+#[derive(Clone, Debug)]
+struct SingleGateCircuit<F: PrimeField> {
+    gate: SingleGate,
+    witness: Value<GateWitness<F>>
+}
+
+#[derive(Clone, Debug)]
+struct SingleGateConfig {
+    advice: [Column<Advice>; 3],
+    squares: [Column<Advice>; 3],
+    constants: [Column<Fixed>; 3],
+    // one selector per gate, in the order of SingleGate::ALL
+    selectors: Vec<Selector>
+}
+
+impl<F: PrimeField> Circuit<F> for SingleGateCircuit<F> {
+    type Config = SingleGateConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        SingleGateCircuit { gate: self.gate, witness: Value::unknown() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let squares = [(); 3].map(|_| meta.advice_column());
+        let constants = [(); 3].map(|_| meta.fixed_column());
+        let columns = GateConstants::Columns(ConstantColumns::Fixed(constants));
+        let mds = MDS.map(|row| row.map(F::from));
+        let inline = GateConstants::Inline([1, 2, 3].map(F::from));
+
+        let selectors = SingleGate::ALL.iter().map(|gate| {
+            let selector = allocate_selector(meta);
+            match gate {
+                SingleGate::PoseidonFull => create_full_round_gate_ps(meta, advice, None, columns, selector, SboxKind::Power(5), ArcPosition::BeforeSbox),
+                SingleGate::PoseidonFullInverse => create_full_round_gate_ps(meta, advice, None, columns, selector, SboxKind::Inverse, ArcPosition::BeforeSbox),
+                SingleGate::PoseidonFullSquared => create_full_round_gate_ps(meta, advice, Some(squares), columns, selector, SboxKind::Power(5), ArcPosition::BeforeSbox),
+                SingleGate::PoseidonFullInline => create_full_round_gate_ps(meta, advice, None, inline, selector, SboxKind::Power(5), ArcPosition::BeforeSbox),
+                SingleGate::PoseidonFullAfterSbox => create_full_round_gate_ps(meta, advice, None, columns, selector, SboxKind::Power(5), ArcPosition::AfterSbox),
+                SingleGate::PoseidonPartial => create_partial_round_gate_ps(meta, advice, None, columns, selector, SboxKind::Power(5), ArcPosition::BeforeSbox),
+                SingleGate::PoseidonPartialAfterSbox => create_partial_round_gate_ps(meta, advice, None, columns, selector, SboxKind::Power(5), ArcPosition::AfterSbox),
+                SingleGate::RescueSbox => create_sbox_gate_rs(meta, advice, None, selector, 5),
+                SingleGate::RescueSboxSquared => create_sbox_gate_rs(meta, advice, Some(squares), selector, 5),
+                SingleGate::RescueSboxInv => create_sbox_inv_gate_rs(meta, advice, None, selector, 5),
+                SingleGate::RescueSboxInvSquared => create_sbox_inv_gate_rs(meta, advice, Some(squares), selector, 5),
+                SingleGate::Mds => create_mds_mul_gate(meta, advice, selector, &mds),
+                SingleGate::MdsArc => create_mds_arc_gate(meta, advice, columns, selector, &mds),
+                SingleGate::Add => create_add_gate(meta, advice, selector),
+                SingleGate::Mul => create_mul_gate(meta, advice, selector),
+                SingleGate::Select => create_select_gate(meta, advice, selector),
+                SingleGate::IsEqual => create_is_equal_gate(meta, advice, selector)
+            }
+            selector
+        }).collect();
+
+        SingleGateConfig { advice, squares, constants, selectors }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let index = SingleGate::ALL.iter().position(|gate| *gate == self.gate).expect("every gate has a selector");
+        layouter.assign_region(
+            || self.gate.gate_name(), |mut region| {
+                config.selectors[index].enable(&mut region, 0)?;
+                for word in 0..3 {
+                    for row in 0..2 {
+                        region.assign_advice(|| "state", config.advice[word], row, || self.witness.map(|witness| witness.rows[row][word]))?;
+                    }
+                    region.assign_advice(|| "square", config.squares[word], 0, || self.witness.map(|witness| witness.squares[word]))?;
+                    region.assign_fixed(|| "constant", config.constants[word], 0, || self.witness.map(|witness| witness.constants[word]))?;
+                }
+                Ok(())
+            }
+        )
+    }
+}
+
+// the cases run by tests/gates.rs
+impl SingleGate {
+    // the handcrafted witness of the gate over small values: the round constants are (1, 2, 3), the state (1, 2, 3)
+    // unless the gate needs otherwise, and the second row what the gate must compute from the first
+    pub fn witness(&self) -> GateWitness<Fr> {
+        let fr = |words: [u64; 3]| words.map(Fr::from);
+        let half = Fr::from(2).invert().unwrap();
+        let (rows, squares) = match self {
+            // (1, 2, 3) + (1, 2, 3) = (2, 4, 6), to the fifth power
+            SingleGate::PoseidonFull | SingleGate::PoseidonFullInline => ([fr([1, 2, 3]), fr([32, 1024, 7776])], fr([0; 3])),
+            SingleGate::PoseidonFullSquared => ([fr([1, 2, 3]), fr([32, 1024, 7776])], fr([4, 16, 36])),
+            // (1, 2, -3) + (1, 2, 3) = (2, 4, 0), inverted with 0 mapped to 0
+            SingleGate::PoseidonFullInverse => ([[Fr::ONE, Fr::from(2), -Fr::from(3)], [half, half.square(), Fr::ZERO]], fr([0; 3])),
+            // with the constants after the S-box: (1, 2, 3) to the fifth power, plus (1, 2, 3)
+            SingleGate::PoseidonFullAfterSbox => ([fr([1, 2, 3]), fr([2, 34, 246])], fr([0; 3])),
+            // only state[0] goes through the S-box, the others just get their constant
+            SingleGate::PoseidonPartial => ([fr([1, 2, 3]), fr([32, 4, 6])], fr([0; 3])),
+            SingleGate::PoseidonPartialAfterSbox => ([fr([1, 2, 3]), fr([2, 4, 6])], fr([0; 3])),
+            SingleGate::RescueSbox => ([fr([1, 2, 3]), fr([1, 32, 243])], fr([0; 3])),
+            SingleGate::RescueSboxSquared => ([fr([1, 2, 3]), fr([1, 32, 243])], fr([1, 4, 9])),
+            // the inverse S-box is constrained as next^5 = cur, with the squares of the next row
+            SingleGate::RescueSboxInv => ([fr([1, 32, 243]), fr([1, 2, 3])], fr([0; 3])),
+            SingleGate::RescueSboxInvSquared => ([fr([1, 32, 243]), fr([1, 2, 3])], fr([1, 4, 9])),
+            // MDS * (1, 2, 3) = (7, 8, 12), plus (1, 2, 3) for the gate with ARC
+            SingleGate::Mds => ([fr([1, 2, 3]), fr([7, 8, 12])], fr([0; 3])),
+            SingleGate::MdsArc => ([fr([1, 2, 3]), fr([8, 10, 15])], fr([0; 3])),
+            SingleGate::Add => ([fr([2, 3, 5]), fr([0; 3])], fr([0; 3])),
+            SingleGate::Mul => ([fr([2, 3, 6]), fr([0; 3])], fr([0; 3])),
+            // bit 1 selects a = 7 over b = 9
+            SingleGate::Select => ([fr([1, 7, 9]), fr([7, 0, 0])], fr([0; 3])),
+            // a = 4 and b = 6 differ: inv = (a - b)^-1 and out = 0
+            SingleGate::IsEqual => ([[Fr::from(4), Fr::from(6), -half], fr([0; 3])], fr([0; 3]))
+        };
+
+        GateWitness { rows, squares, constants: fr([1, 2, 3]) }
+    }
+
+    // run a witness for the gate through MockProver at the smallest k that fits it
+    pub fn verify(&self, witness: GateWitness<Fr>) -> Result<Result<(), Vec<VerifyFailure>>, BenchmarkError> {
+        let circuit = SingleGateCircuit { gate: *self, witness: Value::known(witness) };
+        Ok(MockProver::run(minimal_k(&circuit)?, &circuit, vec![])?.verify())
+    }
+}
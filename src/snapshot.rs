@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde_json::Value;
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{Circuit, keygen_vk},
+    poly::commitment::Params,
+};
+use halo2curves::bls12381::Fr;
+
+use crate::bench::permutation_report;
+use crate::circuits::{OutputMode, PoseidonCircuit, RescueCircuit};
+use crate::error::BenchmarkError;
+use crate::gates::{ConstantStrategy, SboxLayout, with_constant_strategy, with_sbox_layout};
+use crate::generate::MdsOrigin;
+use crate::proving::{Backend, ProofScheme};
+use crate::report::{BenchmarkReport, CircuitReport, CircuitTimings, CostReport, DualReport, GateInventory, GateReport, ParametersReport,
+    ProofReport, SelectorReport, TimingStats};
+use crate::source::{ParameterSource, set_parameter_source};
+use crate::synthesis::{AdviceCells, CheckMode, FixedCells};
+use crate::target::PermutationKind;
+use crate::transcript::TranscriptKind;
+
+// report fields that change from run to run; they are replaced by a placeholder before comparing
+const VOLATILE_FIELDS: [&str; 13] = [
+    "mock_prover_ms", "constraint_check_ms", "synthesis_us", "witness_gen_ms", "witness_gen_faster", "proving_ms", "verification_ms", "proving_ms_per_hash",
+    "min", "median", "mean", "stddev", "max"
+];
+
+// helper function to replace the volatile fields of a JSON report, at any depth, by "<volatile>"
+fn normalize(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                // the statistics of a volatile phase keep their shape, only their values are volatile
+                if VOLATILE_FIELDS.contains(&key.as_str()) && !field.is_object() {
+                    *field = Value::String("<volatile>".to_string());
+                } else {
+                    normalize(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize),
+        _ => {}
+    }
+}
+
+// helper function for a synthetic report, so that the Markdown table is pinned independently of the measured times:
+// a proved circuit, a circuit only measured with MockProver, and the dual circuit, with times in all three units
+pub(crate) fn synthetic_report() -> BenchmarkReport {
+    let cost = |rows: usize, advice_columns: usize, gates: &[(&'static str, usize)]| CostReport {
+        circuit: BTreeMap::from([
+            ("max_rows".to_string(), rows),
+            ("max_advice_rows".to_string(), rows),
+            ("num_advice_columns".to_string(), advice_columns)
+        ]),
+        proof_components: BTreeMap::new(),
+        marginal_proof_size: 0,
+        estimated_proof_size: 0,
+        lookup_proof_size: 0,
+        gates: GateInventory {
+            gates: gates.iter().map(|&(name, degree)| GateReport { name, constraints: 1, degree, queries: BTreeSet::new(), expressions: Vec::new(), selectors: BTreeSet::new() }).collect(),
+            total_constraints: gates.len(),
+            max_degree: gates.iter().map(|&(_, degree)| degree).max().unwrap_or_default(),
+            distinct_queries: 0,
+            advice_queries: 0
+        },
+        selectors: SelectorReport { selectors: 0, fixed_columns: 0, compressed_fixed_columns: 0 },
+        fixed: FixedCells { fixed_cells_assigned: 0, distinct_constants: 0, selector_cells: 0 },
+        advice: AdviceCells { computed: 0, copied: 0, padding: 0 },
+        measured_proof_size: None
+    };
+    let timings = |witness_gen_ms: Vec<f64>| CircuitTimings::new(CheckMode::Full, vec![1], vec![1], vec![1], witness_gen_ms);
+    let circuit = |permutation, statement, output_mode, witness_gen_ms, cost| CircuitReport {
+        permutation,
+        statement,
+        output_mode,
+        outputs: Vec::new(),
+        parameters: ParametersReport {
+            state_size: 3,
+            rate: 2,
+            capacity: 1,
+            rounds: BTreeMap::new(),
+            round_constants: 0,
+            alpha: "5".to_string(),
+            domain_tag: "0".to_string(),
+            mds: MdsOrigin::Grain,
+            constants: None
+        },
+        timings: timings(witness_gen_ms),
+        cost
+    };
+
+    BenchmarkReport {
+        k: 10,
+        parameters: "preset".to_string(),
+        seed: None,
+        circuits: vec![
+            circuit("Poseidon", "permutation", OutputMode::AllState, vec![0.25, 0.5], cost(131, 3, &[("ML_gate", 2), ("PS_full_round_gate", 6)])),
+            circuit("Rescue-Prime", "preimage", OutputMode::DigestOnly, vec![12.5], cost(57, 3, &[("ML_ARC_gate", 2)]))
+        ],
+        dual: Some(DualReport {
+            timings: timings(vec![1500.0]),
+            cost: cost(188, 3, &[("ML_gate", 2), ("PS_full_round_gate", 6), ("ML_ARC_gate", 2)]),
+            separate_rows: BTreeMap::new()
+        }),
+        proofs: vec![ProofReport {
+            permutation: "Poseidon",
+            backend: Backend::default(),
+            scheme: ProofScheme::default(),
+            transcript: TranscriptKind::default(),
+            proof_size: 2608,
+            proving_ms: 1234,
+            verification_ms: 8,
+            threads: 1,
+            proving_stats: TimingStats::from_samples(&[1234.0]).unwrap(),
+            verification_stats: TimingStats::from_samples(&[8.0]).unwrap()
+        }],
+        transcripts: Vec::new(),
+        constant_strategies: Vec::new(),
+        sbox_layouts: Vec::new(),
+        floor_planners: Vec::new(),
+        region_rows: Vec::new(),
+        regions: Vec::new(),
+        bar_layers: Vec::new(),
+        constants_loading: Vec::new(),
+        verification: BTreeMap::new(),
+        analysis: BTreeMap::new()
+    }
+}
+
+// k of the pinned verifying keys, the k of the report snapshots
+const VK_K: u32 = 10;
+
+// helper function to render the pinned verifying key of a circuit over Pasta, as the real proofs generate it: the
+// constraint system, the fixed and permutation commitments and the transcript representative. Any change to the gates,
+// the selectors or the fixed cells shows up in it. Params::new is deterministic, so the commitments are too
+fn pinned_vk<C: Circuit<Fp>>(circuit: &C) -> Result<String, BenchmarkError> {
+    let vk = keygen_vk(&Params::<EqAffine>::new(VK_K), circuit)?;
+    Ok(format!("{:#?}\n", vk.pinned()))
+}
+
+// helper function to render the pinned verifying keys of both permutation circuits under the benchmark layout, and
+// under each other S-box layout and constant strategy in files of their own
+fn pinned_vks() -> Result<Vec<(&'static str, String)>, BenchmarkError> {
+    let both = |[poseidon, rescue]: [&'static str; 2]| -> Result<Vec<(&'static str, String)>, BenchmarkError> {
+        Ok(vec![(poseidon, pinned_vk(&PoseidonCircuit::<Fp>::default())?), (rescue, pinned_vk(&RescueCircuit::<Fp>::default())?)])
+    };
+
+    let mut vks = both(["poseidon_vk.txt", "rescue_vk.txt"])?;
+    vks.extend(with_sbox_layout(SboxLayout::Squared, || both(["poseidon_vk_squared.txt", "rescue_vk_squared.txt"]))?);
+    vks.extend(with_constant_strategy(ConstantStrategy::Advice, || both(["poseidon_vk_advice_constants.txt", "rescue_vk_advice_constants.txt"]))?);
+    vks.extend(with_constant_strategy(ConstantStrategy::Gate, || both(["poseidon_vk_gate_constants.txt", "rescue_vk_gate_constants.txt"]))?);
+
+    Ok(vks)
+}
+
+// render the snapshots for the fixed configuration, by file name: preset parameters, k = 10, one MockProver round per
+// permutation, plus the gate inventory tables, the Markdown table of a synthetic report and the pinned verifying keys.
+// tests/snapshots.rs compares them against the golden files in snapshots/
+pub fn render_snapshots() -> Result<Vec<(&'static str, String)>, BenchmarkError> {
+    let _parameters = set_parameter_source(ParameterSource::Preset);
+    let mut snapshots = Vec::new();
+    for (file, permutation) in [("poseidon_report.json", PermutationKind::Poseidon), ("rescue_report.json", PermutationKind::Rescue)] {
+        let mut report = serde_json::to_value(permutation_report(permutation, 10, 1, CheckMode::Full)?).unwrap();
+        normalize(&mut report);
+        snapshots.push((file, serde_json::to_string_pretty(&report).unwrap() + "\n"));
+    }
+
+    let gates = GateInventory::collect::<Fr, PoseidonCircuit<Fr>>().table("Poseidon")
+        + &GateInventory::collect::<Fr, RescueCircuit<Fr>>().table("Rescue-Prime");
+    snapshots.push(("gates.txt", gates));
+    snapshots.push(("report_table.md", synthetic_report().markdown_table()));
+    snapshots.extend(pinned_vks()?);
+
+    Ok(snapshots)
+}
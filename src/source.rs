@@ -0,0 +1,216 @@
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::POSEIDON_BLS381_T3;
+use crate::error::BenchmarkError;
+use crate::generate::{poseidon_grain, rescue_constants, rescue_mds, rescue_round_count};
+use crate::params::{ArcPosition, Poseidon, RescuePrime, SECURITY_LEVEL, SboxKind, fe_to_decimal, get_common_params, parse_mds, table_constants};
+
+// where the round constants and MDS matrices of both permutations come from
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ParameterSource {
+    // the poseidon-bls381-t3 and rescue-bls381-t3 presets in constants.rs
+    #[default]
+    Preset,
+    // generated for the field like the reference implementations (Grain LFSR for Poseidon, SHAKE256 for Rescue-Prime)
+    Generated,
+    // JSON or TOML parameter file; a permutation without a section keeps the preset parameters
+    File(PathBuf)
+}
+
+impl FromStr for ParameterSource {
+    type Err = Infallible;
+
+    // "preset", "generated", or the path of a parameter file
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "preset" => ParameterSource::Preset,
+            "generated" => ParameterSource::Generated,
+            path => ParameterSource::File(PathBuf::from(path))
+        })
+    }
+}
+
+impl fmt::Display for ParameterSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterSource::Preset => write!(f, "preset"),
+            ParameterSource::Generated => write!(f, "generated"),
+            ParameterSource::File(path) => write!(f, "file:{}", path.display())
+        }
+    }
+}
+
+impl ParameterSource {
+    // Poseidon parameters from this source; the preset instance is poseidon-bls381-t3 (R_F = 8, R_P = 57, x^5)
+    pub(crate) fn poseidon<F: PrimeField>(&self) -> Result<Poseidon<F>, BenchmarkError> {
+        let preset = Poseidon::builder().preset(POSEIDON_BLS381_T3);
+        match self {
+            ParameterSource::Preset => preset.build(),
+            ParameterSource::Generated => {
+                let (round_constants, mds) = poseidon_grain(8, 57, SboxKind::Power(5));
+                preset.mds(mds).round_constants(round_constants).build()
+            }
+            ParameterSource::File(path) => match ParameterFile::read(path)?.poseidon {
+                Some(section) => Poseidon::builder()
+                    .full_rounds(section.full_rounds)
+                    .partial_rounds(section.partial_rounds)
+                    .sbox(SboxKind::from_exponent(section.alpha)?)
+                    .arc(section.arc)
+                    .mds(parse_mds("poseidon.mds", &section.mds)?)
+                    .round_constants(table_constants("poseidon.round_constants", &section.round_constants)?)
+                    .build(),
+                None => preset.build()
+            }
+        }
+    }
+
+    // Rescue-Prime parameters from this source; the preset instance is x^5, capacity 1, 128-bit security, with the
+    // round count from the security formula (14)
+    pub(crate) fn rescue<F: PrimeField>(&self) -> Result<RescuePrime<F>, BenchmarkError> {
+        let common_params = get_common_params();
+        let rounds = rescue_round_count(common_params.state_size, common_params.capacity, SECURITY_LEVEL, 5);
+        let preset = RescuePrime::builder().rounds(rounds).alpha(5);
+        match self {
+            ParameterSource::Preset => preset.build(),
+            ParameterSource::Generated => {
+                let round_constants = rescue_constants::<F, 3>(rounds, common_params.capacity, SECURITY_LEVEL as usize);
+                preset.mds(rescue_mds()?).round_constants(round_constants).build()
+            }
+            ParameterSource::File(path) => match ParameterFile::read(path)?.rescue {
+                Some(section) if section.unchecked_rounds => RescuePrime::builder()
+                    .unchecked_rounds(section.rounds)
+                    .alpha(section.alpha)
+                    .mds(parse_mds("rescue.mds", &section.mds)?)
+                    .round_constants(table_constants("rescue.round_constants", &section.round_constants)?)
+                    .build(),
+                Some(section) => RescuePrime::builder()
+                    .rounds(section.rounds)
+                    .alpha(section.alpha)
+                    .mds(parse_mds("rescue.mds", &section.mds)?)
+                    .round_constants(table_constants("rescue.round_constants", &section.round_constants)?)
+                    .build(),
+                None => preset.build()
+            }
+        }
+    }
+}
+
+thread_local! {
+    // Circuit::configure takes no arguments in halo2 0.3, so the chips read their parameters from the source set here
+    static PARAMETER_SOURCE: RefCell<ParameterSource> = const { RefCell::new(ParameterSource::Preset) };
+}
+
+// the parameter source active on this thread
+pub(crate) fn active_source() -> ParameterSource {
+    PARAMETER_SOURCE.with(|source| source.borrow().clone())
+}
+
+// restores the previous parameter source when dropped
+pub struct ParameterSourceGuard {
+    previous: Option<ParameterSource>
+}
+
+impl Drop for ParameterSourceGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            PARAMETER_SOURCE.with(|source| source.replace(previous));
+        }
+    }
+}
+
+// make `source` the parameter source of every circuit configured on this thread until the guard is dropped
+pub fn set_parameter_source(source: ParameterSource) -> ParameterSourceGuard {
+    ParameterSourceGuard { previous: Some(PARAMETER_SOURCE.with(|active| active.replace(source))) }
+}
+
+// parameter file layout; constants are decimal or 0x-prefixed hex strings, the MDS matrices are given row by row
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ParameterFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) poseidon: Option<PoseidonSection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rescue: Option<RescueSection>
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PoseidonSection {
+    pub(crate) full_rounds: usize,
+    pub(crate) partial_rounds: usize,
+    // S-box exponent, -1 for the inverse S-box x^-1
+    pub(crate) alpha: i64,
+    // "after-sbox" to add the round constants after the S-box; before it if omitted
+    #[serde(default, skip_serializing_if = "ArcPosition::is_before_sbox")]
+    pub(crate) arc: ArcPosition,
+    pub(crate) mds: [[String; 3]; 3],
+    pub(crate) round_constants: Vec<String>
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RescueSection {
+    pub(crate) rounds: usize,
+    // accept a round count below the security formula's, for benchmark-only settings
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) unchecked_rounds: bool,
+    pub(crate) alpha: u64,
+    pub(crate) mds: [[String; 3]; 3],
+    pub(crate) round_constants: Vec<String>
+}
+
+// helper function to check whether a path names a TOML file (anything else is read and written as JSON)
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "toml")
+}
+
+impl ParameterFile {
+    // write out both parameter sets in the file format
+    pub(crate) fn from_params<F: PrimeField>(poseidon: &Poseidon<F>, rescue: &RescuePrime<F>) -> Self {
+        let mds = |mds: &[[F; 3]; 3]| mds.map(|row| row.map(|entry| fe_to_decimal(&entry)));
+        let constants = |constants: &[F]| constants.iter().map(fe_to_decimal).collect();
+        ParameterFile {
+            poseidon: Some(PoseidonSection {
+                full_rounds: poseidon.full_rounds,
+                partial_rounds: poseidon.partial_rounds,
+                alpha: poseidon.sbox.exponent(),
+                arc: poseidon.arc,
+                mds: mds(&poseidon.mds),
+                round_constants: constants(&poseidon.round_constants)
+            }),
+            rescue: Some(RescueSection {
+                rounds: rescue.rounds,
+                unchecked_rounds: rescue.rounds < rescue.secure_rounds,
+                alpha: rescue.alpha,
+                mds: mds(&rescue.mds),
+                round_constants: constants(&rescue.round_constants)
+            })
+        }
+    }
+
+    pub(crate) fn read(path: &Path) -> Result<Self, BenchmarkError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+        let parsed = if is_toml(path) {
+            toml::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        };
+        parsed.map_err(|reason| BenchmarkError::InvalidParameterFile { path: path.display().to_string(), reason })
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<(), BenchmarkError> {
+        let text = if is_toml(path) {
+            toml::to_string_pretty(self).map_err(|e| e.to_string())
+        } else {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+        }.map_err(|reason| BenchmarkError::InvalidParameterFile { path: path.display().to_string(), reason })?;
+        std::fs::write(path, text).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })
+    }
+}
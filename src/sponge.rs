@@ -0,0 +1,423 @@
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+use ff::PrimeField;
+use num_bigint::BigUint;
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Instance, Selector},
+};
+
+use crate::chip::{Number, PermutationChip, StateWord, assign_word};
+use crate::error::BenchmarkError;
+use crate::gates::{allocate_selector, create_add_gate};
+use crate::native::NativePermutation;
+use crate::params::fe_from_biguint;
+use crate::witness::InputStream;
+
+// Sponge over either permutation chip with rate 2 and capacity 1, as used to hash a Fiat-Shamir transcript. The
+// capacity starts at the chip's domain tag plus the tag of the padding rule (Sponge::PADDING, 10*), the input is
+// padded to a multiple of the rate, each block is added into the rate words before a permutation, and the output is
+// state[0] after the last permutation. A keyed sponge (MAC / PRF) adds a secret key into the capacity before any
+// input is absorbed, so the capacity starts at tag + padding tag + key and the key never meets the rate words
+// directly. In duplex / stream mode the sponge squeezes many
+// outputs from one absorbed seed: both rate words of each state are output, and the state is permuted again whenever
+// more outputs are needed, so squeeze_many(1) is the hash.
+
+// sponge with an optional key: K is F natively and Value<F> in a circuit, where the key is a private witness
+#[derive(Clone, Copy, Debug)]
+pub struct Sponge<K> {
+    key: Option<K>
+}
+
+impl<K> Sponge<K> {
+    // unkeyed sponge, e.g. for transcript hashing
+    pub fn new() -> Self {
+        Sponge { key: None }
+    }
+
+    // keyed sponge, e.g. for MAC_key(message)
+    pub fn new_keyed(key: K) -> Self {
+        Sponge { key: Some(key) }
+    }
+}
+
+impl<K> Sponge<K> {
+    // the padding rule of every sponge here, native and in circuits
+    pub const PADDING: SpongePadding = SpongePadding::OneZeros;
+}
+
+impl<K> Default for Sponge<K> {
+    fn default() -> Self {
+        Sponge::new()
+    }
+}
+
+impl<F: PrimeField> Sponge<F> {
+    // helper function to absorb the padded inputs two at a time, returning the state after the last permutation; the
+    // inputs are pulled one block at a time
+    fn absorb_native<N: NativePermutation<F> + ?Sized>(&self, permutation: &N, inputs: impl IntoIterator<Item = F>) -> [F; 3] {
+        let mut state = [F::ZERO, F::ZERO, Self::PADDING.capacity(permutation.domain_tag()) + self.key.unwrap_or(F::ZERO)];
+        for block in Self::PADDING.blocks(inputs, F::ONE, F::ZERO) {
+            state[0] += block[0];
+            state[1] += block[1];
+            state = permutation.permute_native(state);
+        }
+
+        state
+    }
+
+    // native counterpart of Sponge::hash: absorb the padded inputs two at a time, squeeze state[0]
+    pub fn hash_native<N: NativePermutation<F> + ?Sized>(&self, permutation: &N, inputs: &[F]) -> F {
+        self.hash_native_stream(permutation, inputs.iter().copied())
+    }
+
+    // Sponge::hash_native over inputs produced on demand, e.g. packed from a message as they are absorbed
+    pub fn hash_native_stream<N: NativePermutation<F> + ?Sized>(&self, permutation: &N, inputs: impl IntoIterator<Item = F>) -> F {
+        self.absorb_native(permutation, inputs)[0]
+    }
+
+    // native counterpart of Sponge::squeeze_many: absorb the inputs, then squeeze `outputs` words two per permutation
+    pub fn squeeze_many_native<N: NativePermutation<F> + ?Sized>(
+        &self,
+        permutation: &N,
+        inputs: &[F],
+        outputs: usize
+    ) -> Vec<F> {
+        let mut state = self.absorb_native(permutation, inputs.iter().copied());
+        let mut stream = Vec::with_capacity(outputs);
+        loop {
+            stream.extend(state[..2].iter().take(outputs - stream.len()));
+            if stream.len() == outputs {
+                return stream;
+            }
+            state = permutation.permute_native(state);
+        }
+    }
+}
+
+impl<F: PrimeField> Sponge<Value<F>> {
+    // helper function to absorb the inputs, returning the state after the last permutation; the permutations after
+    // the first are chained through copy constraints (permute_assigned). The inputs are pulled one block at a time, so
+    // each block is assigned as it is produced and never held with the others
+    fn absorb<'a, P: PermutationChip<F>>(
+        &self,
+        chip: &P,
+        config: &SpongeConfig<P::Config>,
+        mut layouter: impl Layouter<F>,
+        inputs: impl IntoIterator<Item = StateWord<'a, F>>
+    ) -> Result<[Number<F>; 3], Error> {
+        let mut blocks = Self::PADDING.blocks(inputs, StateWord::Constant(F::ONE), StateWord::Constant(F::ZERO));
+
+        // the capacity starts at the tags, or at tags + key from one add row with the key witnessed and the tags pinned
+        let tag = StateWord::Constant(Self::PADDING.capacity(chip.params().domain_tag()));
+        let keyed = match self.key {
+            Some(key) => Some(add_words(layouter.namespace(|| "key"), config, StateWord::Witness(key), tag)?),
+            None => None
+        };
+        let capacity = keyed.as_ref().map_or(tag, StateWord::Copy);
+
+        // the initial state is (0, 0, capacity), so the first block is the rate of the first permutation's input
+        let first = blocks.next().expect("the padding makes at least one block");
+        let mut state = chip.permute_state(layouter.namespace(|| "block_0"), [first[0], first[1], capacity])?;
+        for (index, block) in blocks.enumerate() {
+            let s0 = add_words(layouter.namespace(|| format!("absorb_{}_0", index + 1)), config, StateWord::Copy(&state[0]), block[0])?;
+            let s1 = add_words(layouter.namespace(|| format!("absorb_{}_1", index + 1)), config, StateWord::Copy(&state[1]), block[1])?;
+            state = chip.permute_assigned(layouter.namespace(|| format!("block_{}", index + 1)), [&s0, &s1, &state[2]])?;
+        }
+
+        Ok(state)
+    }
+
+    // hash the inputs through the sponge and return state[0]
+    pub fn hash<P: PermutationChip<F>>(
+        &self,
+        chip: &P,
+        config: &SpongeConfig<P::Config>,
+        layouter: impl Layouter<F>,
+        inputs: &[StateWord<'_, F>]
+    ) -> Result<Number<F>, Error> {
+        self.hash_stream(chip, config, layouter, inputs.iter().copied())
+    }
+
+    // Sponge::hash over inputs produced on demand during synthesis, e.g. witnesses computed from a message as they are
+    // absorbed; the digest and the layout are those of Sponge::hash over the same words
+    pub fn hash_stream<'a, P: PermutationChip<F>>(
+        &self,
+        chip: &P,
+        config: &SpongeConfig<P::Config>,
+        layouter: impl Layouter<F>,
+        inputs: impl IntoIterator<Item = StateWord<'a, F>>
+    ) -> Result<Number<F>, Error> {
+        let [output, _, _] = self.absorb(chip, config, layouter, inputs)?;
+        Ok(output)
+    }
+
+    // absorb the inputs and squeeze `outputs` words, both rate words of each state; the squeezing permutations take
+    // the whole previous state through copy constraints
+    pub fn squeeze_many<P: PermutationChip<F>>(
+        &self,
+        chip: &P,
+        config: &SpongeConfig<P::Config>,
+        mut layouter: impl Layouter<F>,
+        inputs: &[StateWord<'_, F>],
+        outputs: usize
+    ) -> Result<Vec<Number<F>>, Error> {
+        let mut state = self.absorb(chip, config, layouter.namespace(|| "absorb"), inputs.iter().copied())?;
+        let mut stream = Vec::with_capacity(outputs);
+        for squeeze in 1.. {
+            stream.extend(state[..2].iter().take(outputs - stream.len()).cloned());
+            if stream.len() == outputs {
+                break;
+            }
+            state = chip.permute_assigned(layouter.namespace(|| format!("squeeze_{}", squeeze)), [&state[0], &state[1], &state[2]])?;
+        }
+
+        Ok(stream)
+    }
+}
+
+// the chip configuration plus the add gate used for absorbing, on the chip's advice columns
+#[derive(Clone, Debug)]
+pub struct SpongeConfig<C> {
+    pub chip: C,
+    advice: [Column<Advice>; 3],
+    s_add: Selector
+}
+
+// bytes packed into one field element: 31 bytes are 248 bits, below every modulus used here (BLS12-381, BN254 and
+// Pasta are all 254 or 255 bits), so a packed element is canonical whatever the bytes are
+pub const BYTES_PER_ELEMENT: usize = 31;
+
+// canonical packing of a byte message into field elements: the byte length first, then the bytes in chunks of 31,
+// each read little-endian (the last chunk may be shorter); the length prefix keeps messages that differ by trailing
+// zero bytes apart, and the empty message packs to the single element 0
+pub fn pack_bytes<F: PrimeField>(message: &[u8]) -> Vec<F> {
+    packed_elements(message).collect()
+}
+
+// pack_bytes one element at a time, as the sponge absorbs them
+pub(crate) fn packed_elements<F: PrimeField>(message: &[u8]) -> impl Iterator<Item = F> + '_ {
+    std::iter::once(F::from(message.len() as u64)).chain(message.chunks(BYTES_PER_ELEMENT).map(pack_chunk))
+}
+
+// helper function to pack one chunk of at most 31 bytes, read little-endian
+pub(crate) fn pack_chunk<F: PrimeField>(chunk: &[u8]) -> F {
+    fe_from_biguint(&BigUint::from_bytes_le(chunk))
+}
+
+// the packed chunks of a byte message (pack_bytes without the length prefix) as the inputs of a circuit: chunk i is
+// packed when the sponge pulls it, so the shared message bytes are the only copy of the input held during synthesis
+pub(crate) struct PackedChunks {
+    chunks: usize,
+    message: Value<Arc<[u8]>>
+}
+
+impl PackedChunks {
+    pub(crate) fn new(message: Arc<[u8]>) -> Self {
+        PackedChunks { chunks: message.len().div_ceil(BYTES_PER_ELEMENT), message: Value::known(message) }
+    }
+}
+
+impl Debug for PackedChunks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PackedChunks").field("chunks", &self.chunks).finish()
+    }
+}
+
+impl<F: PrimeField> InputStream<Value<F>> for PackedChunks {
+    fn len(&self) -> usize {
+        self.chunks
+    }
+
+    fn get(&self, index: usize) -> Value<F> {
+        self.message.as_ref().map(|message| pack_chunk(&message[index * BYTES_PER_ELEMENT..message.len().min((index + 1) * BYTES_PER_ELEMENT)]))
+    }
+
+    fn without_witnesses(&self) -> Self {
+        PackedChunks { chunks: self.chunks, message: Value::unknown() }
+    }
+}
+
+// hash a byte message: pack it and absorb the packed elements through the unkeyed sponge, packing each element when
+// its block is absorbed
+pub fn hash_bytes_native<F: PrimeField, N: NativePermutation<F> + ?Sized>(permutation: &N, message: &[u8]) -> F {
+    Sponge::new().hash_native_stream(permutation, packed_elements(message))
+}
+
+// padding rule of the sponge, for inputs whose length is not a multiple of the rate. The rule is encoded in the
+// capacity next to the permutation's domain tag, so that inputs padded under different rules never start from the
+// same state, and a sponge never starts where a bare compression of the same words does (without it, the sponge of
+// one element x would be the compression of (x, 1))
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpongePadding {
+    // 10*: a one, then zeros up to a multiple of the rate (2). The padding is never empty, which makes it injective:
+    // the empty input is the block (1, 0), a single element x is (x, 1), and an input at a rate boundary gains a
+    // whole block (1, 0)
+    OneZeros
+}
+
+impl SpongePadding {
+    // the padding appended to `len` inputs
+    pub fn pad<F: PrimeField>(&self, len: usize) -> Vec<F> {
+        match self {
+            SpongePadding::OneZeros => {
+                let padded = (len + 1).div_ceil(2) * 2;
+                std::iter::once(F::ONE).chain(std::iter::repeat(F::ZERO)).take(padded - len).collect()
+            }
+        }
+    }
+
+    // the padded inputs two at a time, pulled from `inputs` as the blocks are taken: the padding of pad() follows the
+    // last input, with `one` and `zero` standing for the field's 1 and 0 (F natively, constant words in circuits)
+    pub(crate) fn blocks<T: Copy>(&self, inputs: impl IntoIterator<Item = T>, one: T, zero: T) -> impl Iterator<Item = [T; 2]> {
+        let mut inputs = inputs.into_iter().fuse();
+        let mut padded = false;
+        let rule = *self;
+        std::iter::from_fn(move || match rule {
+            SpongePadding::OneZeros if padded => None,
+            SpongePadding::OneZeros => match (inputs.next(), inputs.next()) {
+                (Some(a), Some(b)) => Some([a, b]),
+                (Some(a), None) => {
+                    padded = true;
+                    Some([a, one])
+                }
+                (None, _) => {
+                    padded = true;
+                    Some([one, zero])
+                }
+            }
+        })
+    }
+
+    // the rule's tag, 2^96 times its position plus one: above the 2^64 + (o - 1) of the fixed-length domain tags, so
+    // adding it keeps those apart
+    pub fn tag<F: PrimeField>(&self) -> F {
+        let position = match self {
+            SpongePadding::OneZeros => 1
+        };
+        F::from(1 << 32).pow_vartime([3]) * F::from(position)
+    }
+
+    // the initial capacity of an unkeyed sponge over a permutation with `domain_tag`
+    pub fn capacity<F: PrimeField>(&self, domain_tag: F) -> F {
+        domain_tag + self.tag::<F>()
+    }
+}
+
+// configure the chip with its default parameters and the add gate; only circuits using the sponge pay for the gate
+pub fn configure_sponge<F: PrimeField, P: PermutationChip<F>>(meta: &mut ConstraintSystem<F>) -> SpongeConfig<P::Config> {
+    let chip = P::configure_default(meta);
+    let advice = P::from_config(chip.clone()).circuit_params().advice;
+    let s_add = allocate_selector(meta);
+    create_add_gate(meta, advice, s_add);
+
+    SpongeConfig { chip, advice, s_add }
+}
+
+// the sponge configuration plus a second instance column holding a public message, which the sponge absorbs straight
+// from there; the chip's own instance column comes first and holds the digest
+#[derive(Clone, Debug)]
+pub struct PublicMessageConfig<C> {
+    pub sponge: SpongeConfig<C>,
+    pub message: Column<Instance>
+}
+
+// configure the sponge and the message instance column, with equality so that its cells can be copied into the sponge
+pub fn configure_public_message<F: PrimeField, P: PermutationChip<F>>(meta: &mut ConstraintSystem<F>) -> PublicMessageConfig<P::Config> {
+    let sponge = configure_sponge::<F, P>(meta);
+    let message = meta.instance_column();
+    meta.enable_equality(message);
+
+    PublicMessageConfig { sponge, message }
+}
+
+// the message column of a public message circuit with room for `max_len` words: the message length, the words, then
+// zeros in the unused slots up to `max_len` words. The sponge absorbs the whole column, so one circuit (and one
+// verifying key) serves every message of up to `max_len` words, and the length prefix keeps messages that differ by
+// trailing zeros apart
+pub fn public_message_column<F: PrimeField>(message: &[F], max_len: usize) -> Result<Vec<F>, BenchmarkError> {
+    if message.len() > max_len {
+        return Err(BenchmarkError::ParameterMismatch(format!("a message of {} words does not fit {} public message slots", message.len(), max_len)));
+    }
+
+    Ok(std::iter::once(F::from(message.len() as u64))
+        .chain(message.iter().copied())
+        .chain(std::iter::repeat(F::ZERO))
+        .take(max_len + 1)
+        .collect())
+}
+
+// helper function to add two words, e.g. an input word into a state word: one row [a, b, a + b]
+fn add_words<F: PrimeField, C>(
+    mut layouter: impl Layouter<F>,
+    config: &SpongeConfig<C>,
+    a: StateWord<'_, F>,
+    b: StateWord<'_, F>
+) -> Result<Number<F>, Error> {
+    layouter.assign_region(
+        || "add", |mut region| {
+            config.s_add.enable(&mut region, 0)?;
+            let a = assign_word(&mut region, "a", config.advice[0], a)?;
+            let b = assign_word(&mut region, "b", config.advice[1], b)?;
+            let sum = a.value().copied().zip(b.value().copied()).map(|(a, b)| a + b);
+
+            Ok(Number::from(region.assign_advice(|| "sum", config.advice[2], 0, || sum)?))
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use ff::Field;
+    use halo2_proofs::dev::MockProver;
+    use halo2curves::bls12381::Fr;
+
+    use super::*;
+    use crate::circuits::TranscriptCircuit;
+    #[cfg(feature = "poseidon")]
+    use crate::poseidon::PoseidonChip;
+    #[cfg(feature = "rescue")]
+    use crate::rescue::RescueChip;
+    use crate::synthesis::minimal_k;
+
+    // helper function to check the sponge padding for each length class: empty, a single element, the rate boundary and
+    // one past it. The circuit's digest matches the native sponge and rejects a wrong one, a trailing zero changes the
+    // digest, and the one-element sponge differs from the compression of (x, 1), which the padding tag keeps apart
+    fn check_sponge_padding<P: PermutationChip<Fr>>() -> Result<(), BenchmarkError> {
+        let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+        let padding = Sponge::<Fr>::PADDING;
+        for len in 0..=4usize {
+            let pad = padding.pad::<Fr>(len);
+            assert_eq!((len + pad.len()) % 2, 0);
+            assert_eq!(pad.len(), if len.is_multiple_of(2) { 2 } else { 1 });
+            assert_eq!(pad[0], Fr::ONE);
+
+            let values: Vec<Fr> = (1..=len as u64).map(Fr::from).collect();
+            let digest = Sponge::new().hash_native(chip.params(), &values);
+            let extended = [values.as_slice(), &[Fr::ZERO]].concat();
+            assert_ne!(Sponge::new().hash_native(chip.params(), &extended), digest, "{} sponge of {} elements", P::NAME, len);
+
+            let circuit = TranscriptCircuit::<Fr, P> { elements: values.iter().copied().map(Value::known).collect(), _marker: PhantomData };
+            let k = minimal_k(&circuit)?;
+            assert_eq!(MockProver::run(k, &circuit, vec![vec![digest]])?.verify(), Ok(()));
+            assert!(MockProver::run(k, &circuit, vec![vec![digest + Fr::ONE]])?.verify().is_err());
+        }
+
+        let x = Fr::from(5);
+        assert_ne!(Sponge::new().hash_native(chip.params(), &[x]), chip.params().hash_native([x, Fr::ONE]));
+        assert_eq!(padding.capacity(chip.params().domain_tag()), chip.params().domain_tag() + padding.tag::<Fr>());
+
+        Ok(())
+    }
+
+    // the 10* padding for each length class, natively and in the circuit
+    #[test]
+    fn sponge_padding() -> Result<(), BenchmarkError> {
+        #[cfg(feature = "poseidon")]
+        check_sponge_padding::<PoseidonChip<Fr>>()?;
+        #[cfg(feature = "rescue")]
+        check_sponge_padding::<RescueChip<Fr>>()?;
+        Ok(())
+    }
+}
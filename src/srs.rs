@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use ff::{Field, PrimeField};
+use group::{Curve, GroupEncoding};
+use halo2_proofs::{
+    arithmetic::best_fft,
+    pasta::{Eq, EqAffine, Fp},
+    poly::commitment::Params,
+};
+use sha2::{Digest, Sha256};
+
+use crate::clock::Instant;
+use crate::error::BenchmarkError;
+
+// SRS files for the real proofs. IPA over Pasta has no trusted setup: Params::new(k) derives the generators by hashing
+// to the curve, so a file saves the generation time (seconds at k = 16) and pins the exact parameters a run used. A
+// file is a header (magic, curve name, SHA-256 of the rest) followed by halo2's Params::write encoding. The generators
+// for a smaller k are a prefix of those for a larger one, so one file serves every k up to its own; the Lagrange basis
+// of the smaller domain is recomputed with an inverse FFT, as Params::new computes it.
+
+const MAGIC: &[u8; 8] = b"HALO2SRS";
+
+// the curve the parameters commit over: Vesta, whose scalar field is the Pallas base field the circuits run over
+const CURVE: &str = "vesta";
+
+// bytes of a compressed point
+const POINT_BYTES: usize = 32;
+
+// commitment parameters read from an SRS file, with the header checked
+pub(crate) struct SrsFile {
+    path: String,
+    k: u32,
+    // halo2's encoding: k, then the 2^k generators, the 2^k Lagrange basis points, w and u
+    payload: Vec<u8>
+}
+
+impl SrsFile {
+    // encode parameters with the header
+    fn encode(params: &Params<EqAffine>) -> Vec<u8> {
+        let mut payload = Vec::new();
+        params.write(&mut payload).expect("writing to a Vec does not fail");
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(CURVE.len() as u8);
+        bytes.extend_from_slice(CURVE.as_bytes());
+        bytes.extend_from_slice(&Sha256::digest(&payload));
+        bytes.extend(payload);
+        bytes
+    }
+
+    // check the header, the checksum and the payload size of an SRS file's bytes
+    fn parse(path: &str, bytes: &[u8]) -> Result<Self, BenchmarkError> {
+        let invalid = |reason: String| BenchmarkError::InvalidSrsFile { path: path.to_string(), reason };
+        let rest = bytes.strip_prefix(MAGIC.as_slice()).ok_or_else(|| invalid("not an SRS file (no HALO2SRS header)".to_string()))?;
+        let (&name_len, rest) = rest.split_first().ok_or_else(|| invalid("truncated header".to_string()))?;
+        if rest.len() < name_len as usize + 32 {
+            return Err(invalid("truncated header".to_string()));
+        }
+        let (name, rest) = rest.split_at(name_len as usize);
+        if name != CURVE.as_bytes() {
+            return Err(invalid(format!("parameters for the {} curve, the proofs commit over {}", String::from_utf8_lossy(name), CURVE)));
+        }
+        let (checksum, payload) = rest.split_at(32);
+        let actual = Sha256::digest(payload);
+        if checksum != actual.as_slice() {
+            return Err(invalid(format!(
+                "SHA-256 checksum mismatch: the header has {}, the contents hash to {}; the file is corrupt or truncated",
+                hex(checksum), hex(&actual)
+            )));
+        }
+
+        let k = payload.get(..4).map(|k| u32::from_le_bytes(k.try_into().unwrap())).ok_or_else(|| invalid("truncated parameters".to_string()))?;
+        if k >= 32 || payload.len() != 4 + (2usize << k) * POINT_BYTES + 2 * POINT_BYTES {
+            return Err(invalid(format!("{} bytes of parameters do not encode k = {}", payload.len(), k)));
+        }
+
+        Ok(SrsFile { path: path.to_string(), k, payload: payload.to_vec() })
+    }
+
+    pub(crate) fn read(path: &Path) -> Result<Self, BenchmarkError> {
+        let bytes = std::fs::read(path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+        SrsFile::parse(&path.display().to_string(), &bytes)
+    }
+
+    // the parameters for k, downsized from the file's own k if it is larger
+    pub(crate) fn params(&self, k: u32) -> Result<Params<EqAffine>, BenchmarkError> {
+        if k > self.k {
+            return Err(BenchmarkError::ParameterMismatch(format!("the SRS file {} has k = {}, the circuit needs k = {}", self.path, self.k, k)));
+        }
+        let payload = match k == self.k {
+            true => self.payload.clone(),
+            false => self.downsized_payload(k)?
+        };
+        Params::read(&mut &payload[..]).map_err(|error| BenchmarkError::InvalidSrsFile { path: self.path.clone(), reason: error.to_string() })
+    }
+
+    // helper function for the encoding of the parameters for a smaller k: the first 2^k generators, their Lagrange
+    // basis over the domain of size 2^k, and the same w and u
+    fn downsized_payload(&self, k: u32) -> Result<Vec<u8>, BenchmarkError> {
+        let n = 1usize << k;
+        let generators = &self.payload[4..4 + n * POINT_BYTES];
+        let g = generators.chunks(POINT_BYTES)
+            .map(|bytes| {
+                let mut repr = <EqAffine as GroupEncoding>::Repr::default();
+                repr.as_mut().copy_from_slice(bytes);
+                Option::from(EqAffine::from_bytes(&repr))
+                    .ok_or_else(|| BenchmarkError::InvalidSrsFile { path: self.path.clone(), reason: "a generator is not a curve point".to_string() })
+            })
+            .collect::<Result<Vec<EqAffine>, _>>()?;
+
+        let mut omega_inv = Fp::ROOT_OF_UNITY_INV;
+        for _ in k..Fp::S {
+            omega_inv = omega_inv.square();
+        }
+        let mut lagrange: Vec<Eq> = g.iter().map(Eq::from).collect();
+        best_fft(&mut lagrange, omega_inv, k);
+        let n_inv = Fp::TWO_INV.pow_vartime([k as u64]);
+        for point in &mut lagrange {
+            *point *= n_inv;
+        }
+        let mut lagrange_affine = vec![EqAffine::default(); n];
+        Eq::batch_normalize(&lagrange, &mut lagrange_affine);
+
+        let mut payload = k.to_le_bytes().to_vec();
+        payload.extend_from_slice(generators);
+        for point in &lagrange_affine {
+            payload.extend_from_slice(point.to_bytes().as_ref());
+        }
+        payload.extend_from_slice(&self.payload[self.payload.len() - 2 * POINT_BYTES..]);
+        Ok(payload)
+    }
+}
+
+// helper function for lowercase hex
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// the SRS file set with --srs, read on first use, and the parameters derived from it by k
+struct SrsCache {
+    path: PathBuf,
+    file: Option<Arc<SrsFile>>,
+    params: BTreeMap<u32, Params<EqAffine>>
+}
+
+// process-wide rather than thread-local like the parameter source: the sweep's workers and the --threads pool all
+// prove against the same file, which is read and checked once
+static SRS: Mutex<Option<SrsCache>> = Mutex::new(None);
+
+// read the commitment parameters of every real proof from this SRS file instead of generating them
+pub fn use_srs_file(path: PathBuf) {
+    *SRS.lock().unwrap() = Some(SrsCache { path, file: None, params: BTreeMap::new() });
+}
+
+// the commitment parameters for k: from the SRS file if one is set, generated otherwise. The lock is not held while
+// parameters are generated or downsized: both run on rayon, and a sweep worker waiting on them can pick up another
+// point's job, which would then block on the lock its own thread holds
+pub(crate) fn commitment_params(k: u32) -> Result<Params<EqAffine>, BenchmarkError> {
+    let file = {
+        let mut srs = SRS.lock().unwrap();
+        let Some(cache) = srs.as_mut() else {
+            drop(srs);
+            return Ok(Params::new(k));
+        };
+        if let Some(params) = cache.params.get(&k) {
+            return Ok(params.clone());
+        }
+        if cache.file.is_none() {
+            cache.file = Some(Arc::new(SrsFile::read(&cache.path)?));
+        }
+        Arc::clone(cache.file.as_ref().unwrap())
+    };
+    let params = file.params(k)?;
+    if let Some(cache) = SRS.lock().unwrap().as_mut() {
+        cache.params.insert(k, params.clone());
+    }
+    Ok(params)
+}
+
+// write the commitment parameters for k as an SRS file
+pub fn generate_srs(k: u32, out: &Path) -> Result<(), BenchmarkError> {
+    if k >= 32 {
+        return Err(BenchmarkError::ParameterMismatch(format!("k = {} is too large for the parameters", k)));
+    }
+    let start = Instant::now();
+    let bytes = SrsFile::encode(&Params::<EqAffine>::new(k));
+    std::fs::write(out, &bytes).map_err(|source| BenchmarkError::Io { path: out.display().to_string(), source })?;
+    println!("SRS for k = {} written to {}: {} bytes in {} ms", k, out.display(), bytes.len(), start.elapsed().as_millis());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SRS files: parameters written to a file read back to the same bytes, the parameters for a
+    // smaller k derived from them equal the generated ones, a larger k is refused, and a changed byte, a different curve
+    // name or a truncated file is rejected with its reason
+    #[test]
+    fn srs_files() -> Result<(), BenchmarkError> {
+        let encode = |params: &Params<EqAffine>| {
+            let mut bytes = Vec::new();
+            params.write(&mut bytes).expect("writing to a Vec does not fail");
+            bytes
+        };
+        let params = Params::<EqAffine>::new(8);
+        let path = std::env::temp_dir().join("permutation_benchmark_srs.bin");
+        std::fs::write(&path, SrsFile::encode(&params)).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+        let file = SrsFile::read(&path);
+        let _ = std::fs::remove_file(&path);
+        let file = file?;
+        assert_eq!(encode(&file.params(8)?), encode(&params));
+        for k in [4, 7] {
+            assert_eq!(encode(&file.params(k)?), encode(&Params::<EqAffine>::new(k)), "SRS downsized to k = {}", k);
+        }
+        assert!(matches!(file.params(9), Err(BenchmarkError::ParameterMismatch(_))));
+
+        let bytes = SrsFile::encode(&Params::<EqAffine>::new(4));
+        let reason = |bytes: &[u8]| match SrsFile::parse("srs.bin", bytes) {
+            Err(BenchmarkError::InvalidSrsFile { reason, .. }) => reason,
+            other => panic!("a damaged SRS file is accepted: {:?}", other.map(|file| file.k))
+        };
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(reason(&corrupted).contains("checksum mismatch"));
+        let other_curve = [&bytes[..9], b"palla", &bytes[14..]].concat();
+        assert!(reason(&other_curve).contains("the palla curve"));
+        assert!(reason(&bytes[..bytes.len() - 100]).contains("checksum mismatch"));
+        assert!(reason(&bytes[..20]).contains("truncated header"));
+        assert!(reason(b"not parameters").contains("no HALO2SRS header"));
+
+        Ok(())
+    }
+}
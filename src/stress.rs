@@ -0,0 +1,138 @@
+use std::marker::PhantomData;
+use ff::Field;
+use halo2_proofs::{
+    circuit::Value,
+    dev::MockProver,
+    plonk::{Circuit, ConstraintSystem},
+};
+use halo2curves::bls12381::Fr;
+
+use crate::chip::PermutationChip;
+use crate::circuits::{BatchCircuit, ChainCircuit, OutputMode, PoseidonCircuit, RescueCircuit};
+use crate::error::BenchmarkError;
+use crate::failures::verification_failed;
+use crate::poseidon::PoseidonChip;
+use crate::rescue::RescueChip;
+use crate::synthesis::{check_k, layout_rows};
+
+// Stress run (`bench --stress`): circuits at the edges of the 2^k rows, where assumptions about offsets and halo2's
+// reserved rows show up. A single permutation at k = 16 leaves tens of thousands of empty rows before the blinding
+// rows; a batch of permutations is sized to the k that leaves the fewest usable rows free; a chain of Poseidon and
+// Rescue-Prime permutations fills the usable rows of k = 16 exactly, and one more permutation has to be rejected. Each
+// circuit is checked with MockProver at its k, against correct and tampered public inputs. MockProver walks all 2^16
+// rows of the large cases, so the run is opt-in rather than part of the benchmark.
+
+// k of the single permutations and of the chain
+const STRESS_K: u32 = 16;
+
+// ks the batch circuits are sized for
+const BATCH_KS: std::ops::RangeInclusive<u32> = 10..=STRESS_K;
+
+// helper function for the rows a circuit can use at k: halo2 reserves the last blinding_factors + 1 rows of every
+// column, as fitting_k accounts for
+fn usable_rows<C: Circuit<Fr>>(k: u32) -> usize {
+    let mut meta = ConstraintSystem::default();
+    C::configure(&mut meta);
+    (1 << k) - (meta.blinding_factors() + 1)
+}
+
+// helper function for one stress case: the layout has to use the predicted rows and fit the usable ones, and
+// MockProver has to accept the public inputs and reject them with the first one changed. A failure names the variant
+// with the predicted and the laid out row counts
+fn stress_case<C: Circuit<Fr>>(variant: &str, k: u32, circuit: &C, instance: Vec<Fr>, predicted: usize) -> Result<(), BenchmarkError> {
+    let rows = layout_rows(circuit)?;
+    let usable = usable_rows::<C>(k);
+    let context = format!("{} at k = {}: predicted {} rows, laid out {} of {} usable", variant, k, predicted, rows, usable);
+    if rows != predicted || rows > usable {
+        return Err(BenchmarkError::VerificationFailed(context));
+    }
+
+    let verified = MockProver::run(k, circuit, vec![instance.clone()])
+        .map_err(|e| BenchmarkError::VerificationFailed(format!("{}: {}", context, e)))?
+        .verify();
+    if let Err(failures) = verified {
+        return Err(verification_failed(context, &failures));
+    }
+    let mut tampered = instance;
+    tampered[0] += Fr::ONE;
+    if MockProver::run(k, circuit, vec![tampered])?.verify().is_ok() {
+        return Err(BenchmarkError::VerificationFailed(format!("{}: a changed public input verifies", context)));
+    }
+
+    println!("stress: {} at k = {}, {} of {} usable rows, verified", variant, k, rows, usable);
+    Ok(())
+}
+
+// helper function for a stress case that must not fit: check_k rejects it before synthesis, and MockProver on its own
+// runs out of rows
+fn overflow_case<C: Circuit<Fr>>(variant: &str, k: u32, circuit: &C) -> Result<(), BenchmarkError> {
+    let rows = layout_rows(circuit)?;
+    let context = format!("{} at k = {}: laid out {} rows, {} usable", variant, k, rows, usable_rows::<C>(k));
+    match (check_k(variant, k, circuit), MockProver::run(k, circuit, vec![Vec::new()])) {
+        (Err(BenchmarkError::KTooSmall { .. }), Err(_)) => {
+            println!("stress: {} rejected at k = {}", variant, k);
+            Ok(())
+        }
+        (checked, _) => Err(BenchmarkError::VerificationFailed(format!("{} should not fit, check_k gave {:?}", context, checked)))
+    }
+}
+
+// helper function for the batch case: as many permutations as fit at the k in BATCH_KS that leaves the fewest usable
+// rows free, then one more, which has to be rejected
+fn stress_batch<P: PermutationChip<Fr>>() -> Result<(), BenchmarkError> {
+    let chip = P::from_config(P::configure_default(&mut ConstraintSystem::default()));
+    let rows = chip.expected_rows();
+    let (k, size) = BATCH_KS
+        .map(|k| (k, usable_rows::<BatchCircuit<Fr, P>>(k) / rows))
+        .min_by_key(|&(k, size)| usable_rows::<BatchCircuit<Fr, P>>(k) - size * rows)
+        .expect("a k to size the batch for");
+
+    let inputs: Vec<[Fr; 3]> = (0..size as u64).map(|i| [i, i + 1, i + 2].map(Fr::from)).collect();
+    let digests = inputs.iter().map(|&input| chip.params().permute_native(input)[0]).collect::<Vec<Fr>>();
+    let batch = |inputs: &[[Fr; 3]]| BatchCircuit::<Fr, P> { inputs: inputs.iter().map(|input| input.map(Value::known)).collect(), _marker: PhantomData };
+    stress_case(&format!("{} batch of {}", P::NAME, size), k, &batch(&inputs), digests, size * rows)?;
+
+    let mut overfull = inputs;
+    overfull.push([Fr::ZERO; 3]);
+    overflow_case(&format!("{} batch of {}", P::NAME, size + 1), k, &batch(&overfull))
+}
+
+// run the stress cases, stopping at the first failure
+pub fn run_stress() -> Result<(), BenchmarkError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let mut meta = ConstraintSystem::default();
+    let poseidon = PoseidonChip::from_config(PoseidonChip::configure_default(&mut meta));
+    let rescue = RescueChip::from_config(RescueChip::configure_default(&mut meta));
+    let (rows_ps, rows_rs) = (poseidon.expected_rows(), rescue.expected_rows());
+
+    // one permutation with all of its outputs public, far below the reserved rows
+    let circuit_ps = PoseidonCircuit::new(inputs.map(Value::known), OutputMode::AllState)?;
+    stress_case("Poseidon permutation", STRESS_K, &circuit_ps, poseidon.params().permute_native(inputs).to_vec(), rows_ps)?;
+    let circuit_rs = RescueCircuit::new(inputs.map(Value::known), OutputMode::AllState)?;
+    stress_case("Rescue-Prime permutation", STRESS_K, &circuit_rs, rescue.params().permute_native(inputs).to_vec(), rows_rs)?;
+
+    stress_batch::<PoseidonChip<Fr>>()?;
+    stress_batch::<RescueChip<Fr>>()?;
+
+    // a chain whose regions add up to the usable rows: the most Poseidon permutations that leave a multiple of the
+    // Rescue-Prime rows; the region heights are coprime, so some split exists once the column is long enough
+    let usable = usable_rows::<ChainCircuit<Fr>>(STRESS_K);
+    let count_ps = (0..=usable / rows_ps).rev()
+        .find(|count| (usable - count * rows_ps).is_multiple_of(rows_rs))
+        .ok_or_else(|| BenchmarkError::ParameterMismatch(format!("no chain of {}- and {}-row regions fills {} rows", rows_ps, rows_rs, usable)))?;
+    let count_rs = (usable - count_ps * rows_ps) / rows_rs;
+    let mut state = inputs;
+    for step in 0..count_ps + count_rs {
+        state = match step < count_ps {
+            true => poseidon.params().permute_native(state),
+            false => rescue.params().permute_native(state)
+        };
+    }
+    let chain = |rescue: usize| ChainCircuit { inputs: inputs.map(Value::known), poseidon: count_ps, rescue };
+    let variant = format!("chain of {} Poseidon and {} Rescue-Prime permutations", count_ps, count_rs);
+    stress_case(&variant, STRESS_K, &chain(count_rs), vec![state[0]], usable)?;
+    overflow_case(&format!("chain of {} Poseidon and {} Rescue-Prime permutations", count_ps, count_rs + 1), STRESS_K, &chain(count_rs + 1))?;
+
+    println!("stress: all cases passed");
+    Ok(())
+}
@@ -0,0 +1,155 @@
+#[cfg(feature = "multicore")]
+use std::sync::Mutex;
+#[cfg(feature = "multicore")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use halo2_proofs::{
+    circuit::{SimpleFloorPlanner, Value, floor_planner::V1},
+    pasta::EqAffine,
+    plonk::Circuit,
+    poly::commitment::Params,
+};
+
+use crate::analysis::prover_domain;
+use crate::bench::expected_instances;
+use crate::circuits::{FloorPlannerKind, OutputMode, Planned, PoseidonCircuit, RescueCircuit};
+use crate::error::BenchmarkError;
+use crate::memory::{low_memory, low_memory_threads, with_low_memory};
+use crate::proving::{Backend, ProvingBackend, ProvingRng, prove_and_verify};
+use crate::report::ProofReport;
+use crate::report::SweepReport;
+use crate::source::{ParameterSource, active_source, set_parameter_source};
+use crate::synthesis::{check_k, minimal_k};
+use crate::target::PermutationKind;
+
+// Parameter sweeps over (backend, permutation, k, layout, floor planner), each point with its own keygen and real proof
+// on its backend.
+
+// one point of a sweep
+#[derive(Clone, Copy, Debug)]
+struct SweepPoint {
+    backend: Backend,
+    permutation: PermutationKind,
+    k: u32,
+    output_mode: OutputMode,
+    floor_planner: FloorPlannerKind
+}
+
+// helper function to prove one sweep point on the inputs (0, 1, 2), over the scalar field of the point's backend; the
+// parameter source and low-memory mode are thread-local, so the caller's are set again on the worker thread
+fn prove_point(point: SweepPoint, source: &ParameterSource, low_memory: bool, seed: Option<u64>) -> Result<SweepReport, BenchmarkError> {
+    let _parameters = set_parameter_source(source.clone());
+    with_low_memory(low_memory, || match point.backend {
+        Backend::Ipa => prove_point_on::<Params<EqAffine>>(point, seed)
+    })
+}
+
+// helper function to prove one sweep point on the backend B
+fn prove_point_on<B: ProvingBackend>(point: SweepPoint, seed: Option<u64>) -> Result<SweepReport, BenchmarkError> {
+    let inputs = [0, 1, 2].map(B::Scalar::from);
+    let [s0, s1, s2] = inputs.map(Value::known);
+    let output_mode = point.output_mode;
+    let instances = expected_instances(point.permutation, inputs, output_mode)?;
+    let instance = instances.column(0);
+    let mut rng = ProvingRng::new(seed);
+
+    let (label, minimal_k, prover, proof) = match point.permutation {
+        PermutationKind::Poseidon => {
+            let circuit = PoseidonCircuit { s0, s1, s2, output_mode };
+            let (needed, proof) = prove_planned::<B, _>("Poseidon", point, circuit, instance, &mut rng)?;
+            ("Poseidon", needed, prover_domain::<B::Scalar, PoseidonCircuit<B::Scalar>>(point.k), proof)
+        }
+        PermutationKind::Rescue => {
+            let circuit = RescueCircuit { s0, s1, s2, output_mode };
+            let (needed, proof) = prove_planned::<B, _>("Rescue-Prime", point, circuit, instance, &mut rng)?;
+            ("Rescue-Prime", needed, prover_domain::<B::Scalar, RescueCircuit<B::Scalar>>(point.k), proof)
+        }
+    };
+
+    Ok(SweepReport { permutation: label, k: point.k, minimal_k, output_mode, floor_planner: point.floor_planner, prover, proof })
+}
+
+// helper function to prove a point's circuit under the point's floor planner, returning the smallest k that fits the
+// circuit under that planner along with the proof
+fn prove_planned<B: ProvingBackend, C: Circuit<B::Scalar>>(
+    label: &'static str,
+    point: SweepPoint,
+    circuit: C,
+    instance: &[B::Scalar],
+    rng: &mut ProvingRng
+) -> Result<(u32, ProofReport), BenchmarkError> {
+    fn prove<B: ProvingBackend, C: Circuit<B::Scalar>>(label: &'static str, k: u32, circuit: &C, instance: &[B::Scalar], rng: &mut ProvingRng) -> Result<(u32, ProofReport), BenchmarkError> {
+        let needed = minimal_k(circuit)?;
+        check_k(label, k, circuit)?;
+        Ok((needed, prove_and_verify(label, &B::setup(k)?, circuit, instance, rng)?))
+    }
+
+    match point.floor_planner {
+        FloorPlannerKind::Simple => prove::<B, _>(label, point.k, &Planned::<_, SimpleFloorPlanner>::new(circuit), instance, rng),
+        FloorPlannerKind::V1 => prove::<B, _>(label, point.k, &Planned::<_, V1>::new(circuit), instance, rng)
+    }
+}
+
+// prove every (backend, permutation, k, output mode, floor planner) combination with `max_parallel` worker threads,
+// which take the next point when they finish one, so at most that many proofs (and proving keys) are in memory at
+// once. halo2's parallel prover runs on one shared rayon pool of `threads` threads (rayon's default when None) rather
+// than one pool per worker, so the workers do not oversubscribe the machine. The results are in sweep order (backend,
+// then permutation, then k, then output mode, then floor planner) whatever order the points finish in. In low-memory
+// mode there is one worker, so one proving key at a time, and the pool defaults to LOW_MEMORY_THREADS threads. Without
+// the multicore feature the points run one after the other
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sweep(
+    backends: &[Backend],
+    permutations: &[PermutationKind],
+    ks: &[u32],
+    output_modes: &[OutputMode],
+    floor_planners: &[FloorPlannerKind],
+    max_parallel: usize,
+    threads: Option<usize>,
+    seed: Option<u64>
+) -> Result<Vec<SweepReport>, BenchmarkError> {
+    if max_parallel == 0 || threads == Some(0) {
+        return Err(BenchmarkError::ParameterMismatch("--max-parallel and --prover-threads must be at least 1".to_string()));
+    }
+    let points: Vec<SweepPoint> = backends.iter()
+        .flat_map(|&backend| permutations.iter().map(move |&permutation| (backend, permutation)))
+        .flat_map(|(backend, permutation)| ks.iter().flat_map(move |&k| output_modes.iter().flat_map(move |&output_mode| {
+            floor_planners.iter().map(move |&floor_planner| SweepPoint { backend, permutation, k, output_mode, floor_planner })
+        })))
+        .collect();
+    let source = active_source();
+    let low_memory = low_memory();
+    let (max_parallel, threads) = match low_memory {
+        true => (1, low_memory_threads(threads)),
+        false => (max_parallel, threads)
+    };
+    // each point gets its own seed, so a seeded sweep is reproducible independently of the thread schedule
+    let run = |index: usize| prove_point(points[index], &source, low_memory, seed.map(|seed| seed.wrapping_add(index as u64)));
+
+    #[cfg(feature = "multicore")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.unwrap_or(0))
+            .build()
+            .map_err(|error| BenchmarkError::Unsupported(format!("cannot start the prover thread pool: {}", error)))?;
+        let next = AtomicUsize::new(0);
+        let results: Vec<Mutex<Option<Result<SweepReport, BenchmarkError>>>> = points.iter().map(|_| Mutex::new(None)).collect();
+        std::thread::scope(|scope| {
+            for _ in 0..max_parallel.min(points.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= points.len() {
+                        break;
+                    }
+                    let result = pool.install(|| run(index));
+                    *results[index].lock().unwrap() = Some(result);
+                });
+            }
+        });
+        results.into_iter().map(|result| result.into_inner().unwrap().expect("every point is proved")).collect()
+    }
+    #[cfg(not(feature = "multicore"))]
+    {
+        let _ = (max_parallel, threads);
+        (0..points.len()).map(run).collect()
+    }
+}
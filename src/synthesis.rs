@@ -0,0 +1,630 @@
+use std::cell::RefCell;
+#[cfg(feature = "bench-cli")]
+use std::collections::BTreeMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Duration;
+use ff::{Field, PrimeField};
+use serde::Serialize;
+use clap::ValueEnum;
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed, FloorPlanner, Instance, Selector},
+};
+
+use crate::clock::Instant;
+use crate::error::BenchmarkError;
+use crate::gates::track_constants;
+
+thread_local! {
+    // the round hook of the prover job synthesizing on this thread (see with_round_hook)
+    static ROUND_HOOK: RefCell<Option<Box<dyn Fn() -> bool>>> = const { RefCell::new(None) };
+}
+
+// helper function for the chips to call before each round they lay out: reports the round to the hook installed on
+// this thread, if any, and fails synthesis when the hook asks to stop (a cancelled prover job)
+pub(crate) fn round_checkpoint() -> Result<(), Error> {
+    match ROUND_HOOK.with(|hook| hook.borrow().as_ref().is_none_or(|hook| hook())) {
+        true => Ok(()),
+        false => Err(Error::Synthesis)
+    }
+}
+
+// helper function for the chips to compare the rows a permutation region used with the rows they predict, which k and
+// the batch capacities are sized from; a mismatch fails synthesis instead of laying out a circuit that does not fit
+pub(crate) fn check_region_rows(label: &str, rows: usize, expected: usize) -> Result<(), Error> {
+    if rows != expected {
+        tracing::error!("{} region rows ({}) differ from the layout prediction ({})", label, rows, expected);
+        return Err(Error::Synthesis);
+    }
+    Ok(())
+}
+
+// helper function to run `run` with `hook` called at every round checkpoint on this thread; synthesis stops at the
+// first round the hook returns false for
+pub(crate) fn with_round_hook<T>(hook: impl Fn() -> bool + 'static, run: impl FnOnce() -> T) -> T {
+    let previous = ROUND_HOOK.with(|active| active.replace(Some(Box::new(hook))));
+    let result = run();
+    ROUND_HOOK.with(|active| active.replace(previous));
+    result
+}
+
+// assignment backend that discards the cells and only tracks the rows used and the copy constraints, so synthesis can
+// be timed without MockProver's bookkeeping and a circuit's height can be read without picking k first
+#[derive(Default)]
+struct LayoutAssignment {
+    rows: usize,
+    copies: usize
+}
+
+impl LayoutAssignment {
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for LayoutAssignment {
+    fn enter_region<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A: FnOnce() -> AR, AR: Into<String>>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error> {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, _: Column<Advice>, row: usize, to: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        // the witness is still computed
+        self.touch(row);
+        to();
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, _: Column<Fixed>, row: usize, to: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        self.touch(row);
+        to();
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        self.copies += 1;
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<F>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// assignment backend that keeps the advice values column by column, as the prover's witness collection does before
+// committing to them; fixed cells and copy constraints belong to keygen and are skipped. The column count is private
+// in halo2 0.3, so each 2^k-row column is allocated when it is first assigned. Streamed, each value is evaluated as it
+// is assigned and nothing is kept
+struct WitnessAssignment<F: Field> {
+    n: usize,
+    advice: HashMap<Column<Advice>, Vec<Assigned<F>>>,
+    streamed: bool
+}
+
+impl<F: Field> Assignment<F> for WitnessAssignment<F> {
+    fn enter_region<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A: FnOnce() -> AR, AR: Into<String>>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, column: Column<Advice>, row: usize, to: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        let n = self.n;
+        if self.streamed {
+            if row >= n {
+                return Err(Error::BoundsFailure);
+            }
+            to().map(|value| std::hint::black_box(value.into().evaluate()));
+            return Ok(());
+        }
+        let cell = self.advice.entry(column).or_insert_with(|| vec![Assigned::Zero; n]).get_mut(row).ok_or(Error::BoundsFailure)?;
+        to().map(|value| *cell = value.into());
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, _: Column<Fixed>, _: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<F>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// assignment backend that keeps the fixed cells and the enabled selector cells, by position, as keygen would place
+// them; advice cells are skipped, and the witness is not computed
+struct FixedAssignment<F: PrimeField> {
+    fixed: HashMap<(Column<Fixed>, usize), F>,
+    selectors: HashSet<(Selector, usize)>
+}
+
+impl<F: PrimeField> Assignment<F> for FixedAssignment<F> {
+    fn enter_region<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A: FnOnce() -> AR, AR: Into<String>>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error> {
+        self.selectors.insert((*selector, row));
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, _: Column<Advice>, _: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, column: Column<Fixed>, row: usize, to: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        to().map(|value| self.fixed.insert((column, row), value.into().evaluate()));
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<F>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// assignment backend that records the rows each region occupies, like LayoutAssignment but with the region names
+// evaluated; cells outside regions (the floor planner's constants) are not attributed to any region
+struct RegionAssignment {
+    circuit: &'static str,
+    regions: Vec<RegionUsage>,
+    // name of the open region, and the first and last rows it touched so far
+    current: Option<(String, Option<(usize, usize)>)>
+}
+
+impl RegionAssignment {
+    fn touch(&mut self, row: usize) {
+        if let Some((_, touched)) = self.current.as_mut() {
+            *touched = Some(touched.map_or((row, row), |(first, last)| (first.min(row), last.max(row))));
+        }
+    }
+}
+
+impl<F: Field> Assignment<F> for RegionAssignment {
+    fn enter_region<NR: Into<String>, N: FnOnce() -> NR>(&mut self, name: N) {
+        self.current = Some((name().into(), None));
+    }
+
+    // regions that assign no cell (e.g. one that only pins a cell to a constant) occupy no rows and are left out
+    fn exit_region(&mut self) {
+        if let Some((name, Some((first, last)))) = self.current.take() {
+            self.regions.push(RegionUsage { circuit: self.circuit, name, start: first, rows: last - first + 1 });
+        }
+    }
+
+    fn enable_selector<A: FnOnce() -> AR, AR: Into<String>>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error> {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, _: Column<Advice>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, _: Column<Fixed>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<F>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// a cell of any column, by column and row
+type AnyCell = (Column<Any>, usize);
+
+// assignment backend that numbers the advice cells in the order they are assigned and keeps the copy constraints, so
+// each cell can be told apart as a computed value or a copy of one assigned earlier; it also tracks the rows used,
+// like LayoutAssignment
+#[derive(Default)]
+struct AdviceAssignment {
+    rows: usize,
+    advice: HashMap<(Column<Advice>, usize), usize>,
+    copies: Vec<(AnyCell, AnyCell)>
+}
+
+impl AdviceAssignment {
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for AdviceAssignment {
+    fn enter_region<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A: FnOnce() -> AR, AR: Into<String>>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error> {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, column: Column<Advice>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        self.touch(row);
+        // a cell assigned twice keeps its first position in the order
+        let order = self.advice.len();
+        self.advice.entry((column, row)).or_insert(order);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, _: Column<Fixed>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, left: Column<Any>, left_row: usize, right: Column<Any>, right_row: usize) -> Result<(), Error> {
+        self.copies.push(((left, left_row), (right, right_row)));
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<F>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR: Into<String>, N: FnOnce() -> NR>(&mut self, _: N) {}
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// structure for the rows one region of a circuit occupies, by the name it was assigned with (e.g.
+// "Poseidon_Permutation"); regions are listed in the order the floor planner assigned them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct RegionUsage {
+    pub(crate) circuit: &'static str,
+    pub(crate) name: String,
+    pub(crate) start: usize,
+    pub(crate) rows: usize
+}
+
+// helper function to configure a circuit and list the rows of each of its regions, as its floor planner lays them out
+pub(crate) fn region_usage<F: Field, C: Circuit<F>>(label: &'static str, circuit: &C) -> Result<Vec<RegionUsage>, BenchmarkError> {
+    let (_, config, constants) = configure::<F, C>();
+    let mut assignment = RegionAssignment { circuit: label, regions: Vec::new(), current: None };
+    C::FloorPlanner::synthesize(&mut assignment, circuit, config, constants)?;
+    Ok(assignment.regions)
+}
+
+// structure for the data a circuit places in fixed cells, which the fixed column commitments are computed from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct FixedCells {
+    // fixed cells assigned by the circuit: round constants and the constants column
+    pub(crate) fixed_cells_assigned: usize,
+    // distinct values among them
+    pub(crate) distinct_constants: usize,
+    // enabled selector cells, which keygen compresses into further fixed columns
+    pub(crate) selector_cells: usize
+}
+
+// helper function to configure a circuit, synthesize it against the fixed-cell backend and count what it places in
+// fixed cells
+pub(crate) fn fixed_cells<F: PrimeField, C: Circuit<F>>(circuit: &C) -> Result<FixedCells, BenchmarkError> {
+    let (_, config, constants) = configure::<F, C>();
+    let mut assignment = FixedAssignment { fixed: HashMap::new(), selectors: HashSet::new() };
+    C::FloorPlanner::synthesize(&mut assignment, circuit, config, constants)?;
+
+    let distinct: HashSet<Vec<u8>> = assignment.fixed.values().map(|value| value.to_repr().as_ref().to_vec()).collect();
+    Ok(FixedCells {
+        fixed_cells_assigned: assignment.fixed.len(),
+        distinct_constants: distinct.len(),
+        selector_cells: assignment.selectors.len()
+    })
+}
+
+// helper function to synthesize a circuit against the fixed-cell backend with a configuration and constants columns
+// already made by configuring it, and list the selectors enabled on each row. MockProver keeps its selector matrix
+// private in halo2 0.3, and fills it from the same enable_selector calls
+#[cfg(feature = "bench-cli")]
+pub(crate) fn selector_rows<F: PrimeField, C: Circuit<F>>(config: C::Config, constants: Vec<Column<Fixed>>, circuit: &C) -> Result<BTreeMap<usize, Vec<Selector>>, BenchmarkError> {
+    let mut assignment = FixedAssignment { fixed: HashMap::new(), selectors: HashSet::new() };
+    C::FloorPlanner::synthesize(&mut assignment, circuit, config, constants)?;
+
+    let mut rows: BTreeMap<usize, Vec<Selector>> = BTreeMap::new();
+    for (selector, row) in &assignment.selectors {
+        rows.entry(*row).or_default().push(*selector);
+    }
+    Ok(rows)
+}
+
+// structure for the advice cells within the rows a circuit's layout uses, by what they hold
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct AdviceCells {
+    // cells holding a value the circuit computed (or a private input), constrained by the gates
+    pub(crate) computed: usize,
+    // cells that repeat a value assigned earlier, tied to it by a copy constraint, or a constant from a fixed cell
+    pub(crate) copied: usize,
+    // cells of the used rows left unassigned, which the prover fills with zeros
+    pub(crate) padding: usize
+}
+
+// helper function to configure a circuit, synthesize it against the advice-tracking backend and classify its advice
+// cells. An advice cell is a copy when a copy constraint ties it to a fixed cell, or to an advice cell assigned before
+// it; a copy from an instance cell exposes the value rather than repeating it, so it leaves the cell computed. The
+// advice column count is private in halo2 0.3 and read from the pinned constraint system
+pub(crate) fn advice_cells<F: Field, C: Circuit<F>>(circuit: &C) -> Result<AdviceCells, BenchmarkError> {
+    let (meta, config, constants) = configure::<F, C>();
+    let columns = pinned_count(&format!("{:?}", meta.pinned()), "num_advice_columns: ");
+    let mut assignment = AdviceAssignment::default();
+    C::FloorPlanner::synthesize(&mut assignment, circuit, config, constants)?;
+
+    let advice = |(column, row): AnyCell| Column::<Advice>::try_from(column).ok().map(|column| (column, row));
+    let mut copied = HashSet::new();
+    for &(left, right) in &assignment.copies {
+        let later = match (advice(left), advice(right)) {
+            (Some(left), Some(right)) => Some(if assignment.advice.get(&left) > assignment.advice.get(&right) { left } else { right }),
+            (Some(cell), None) | (None, Some(cell)) if *left.0.column_type() == Any::Fixed || *right.0.column_type() == Any::Fixed => Some(cell),
+            _ => None
+        };
+        copied.extend(later.filter(|cell| assignment.advice.contains_key(cell)));
+    }
+
+    let assigned = assignment.advice.len();
+    Ok(AdviceCells {
+        computed: assigned - copied.len(),
+        copied: copied.len(),
+        padding: (assignment.rows * columns).saturating_sub(assigned)
+    })
+}
+
+// helper function to read a column count (e.g. "num_selectors: ") from the Debug output of a pinned constraint system
+pub(crate) fn pinned_count(pinned: &str, field: &str) -> usize {
+    let start = pinned.find(field).map(|index| index + field.len()).expect("the pinned constraint system lists its column counts");
+    pinned[start..].chars().take_while(char::is_ascii_digit).collect::<String>().parse().expect("a column count")
+}
+
+// helper function to configure a circuit on a fresh constraint system, with the columns it enables for global
+// constants, which the floor planner places constants in
+fn configure<F: Field, C: Circuit<F>>() -> (ConstraintSystem<F>, C::Config, Vec<Column<Fixed>>) {
+    let mut meta = ConstraintSystem::default();
+    let (config, constants) = track_constants(|| C::configure(&mut meta));
+    (meta, config, constants)
+}
+
+// helper function to re-create fixed columns from their indices in a scratch constraint system, which allocates fixed
+// columns in index order
+fn fixed_columns_at<F: Field>(indices: &[usize]) -> Vec<Column<Fixed>> {
+    let mut scratch = ConstraintSystem::<F>::default();
+    let columns: Vec<Column<Fixed>> = (0..=indices.iter().copied().max().unwrap_or(0)).map(|_| scratch.fixed_column()).collect();
+    indices.iter().map(|&index| columns[index]).collect()
+}
+
+// helper function for the rows of a circuit's lookup tables: the table columns are the fixed columns the lookup
+// arguments' table expressions query, read from the pinned constraint system's Debug output, and their rows are the
+// rows the circuit assigns in them, before halo2 fills the rest of each column with the table's default row. Tables
+// do not depend on the witness, so a circuit without witnesses gives the same count
+pub(crate) fn lookup_table_rows<F: PrimeField, C: Circuit<F>>(circuit: &C) -> Result<usize, BenchmarkError> {
+    let (meta, config, constants) = configure::<F, C>();
+    let pinned = format!("{:?}", meta.pinned());
+    let lookups = pinned.split("lookups: [").nth(1).and_then(|rest| rest.split("constants: [").next()).unwrap_or_default();
+    let indices: BTreeSet<usize> = lookups.split("table_expressions: [").skip(1)
+        .flat_map(|table| table.split("input_expressions").next().unwrap_or_default().split("Fixed {").skip(1))
+        .filter_map(|query| query.split("column_index: ").nth(1)?.split([',', ' ']).next()?.parse().ok())
+        .collect();
+    if indices.is_empty() {
+        return Ok(0);
+    }
+
+    let tables: HashSet<Column<Fixed>> = fixed_columns_at::<F>(&indices.into_iter().collect::<Vec<_>>()).into_iter().collect();
+    let mut assignment = FixedAssignment { fixed: HashMap::new(), selectors: HashSet::new() };
+    C::FloorPlanner::synthesize(&mut assignment, circuit, config, constants)?;
+    Ok(assignment.fixed.keys().filter(|(column, _)| tables.contains(column)).map(|&(_, row)| row + 1).max().unwrap_or(0))
+}
+
+// helper function to synthesize a circuit against the row-tracking backend, as MockProver would (same constants)
+fn synthesize_layout<F: Field, C: Circuit<F>>(
+    circuit: &C,
+    config: C::Config,
+    constants: Vec<Column<Fixed>>
+) -> Result<LayoutAssignment, BenchmarkError> {
+    let mut layout = LayoutAssignment::default();
+    C::FloorPlanner::synthesize(&mut layout, circuit, config, constants)?;
+    Ok(layout)
+}
+
+// helper function to time one synthesis of a circuit in microseconds; configure (which lays out the round constant
+// schedule) runs once beforehand, as it does once per key
+pub(crate) fn time_synthesis<F: Field, C: Circuit<F>>(circuit: &C) -> Result<u128, BenchmarkError> {
+    let (_, config, constants) = configure::<F, C>();
+    let _span = tracing::debug_span!("synthesis").entered();
+    let start = Instant::now();
+    synthesize_layout(circuit, config, constants)?;
+    Ok(start.elapsed().as_micros())
+}
+
+// helper function to time witness generation for a circuit at size 2^k: synthesis into 2^k-row advice columns, then
+// every cell evaluated out of its fraction form, or `streamed` in one pass that evaluates each cell as it is assigned
+// without keeping the columns. configure runs beforehand, as for time_synthesis
+pub(crate) fn time_witness_generation<F: Field, C: Circuit<F>>(k: u32, circuit: &C, streamed: bool) -> Result<Duration, BenchmarkError> {
+    let (_, config, constants) = configure::<F, C>();
+    let _span = tracing::debug_span!("witness_generation").entered();
+    let start = Instant::now();
+    let mut witness = WitnessAssignment { n: 1 << k, advice: HashMap::new(), streamed };
+    C::FloorPlanner::synthesize(&mut witness, circuit, config, constants)?;
+    let _values: Vec<Vec<F>> = witness.advice.into_values()
+        .map(|column| column.into_iter().map(Assigned::evaluate).collect())
+        .collect();
+    Ok(start.elapsed())
+}
+
+// helper function to configure a circuit and synthesize it against the row-tracking backend
+fn layout<F: Field, C: Circuit<F>>(circuit: &C) -> Result<LayoutAssignment, BenchmarkError> {
+    let (_, config, constants) = configure::<F, C>();
+    synthesize_layout(circuit, config, constants)
+}
+
+// helper function for the number of rows a circuit's layout uses, independent of k
+pub(crate) fn layout_rows<F: Field, C: Circuit<F>>(circuit: &C) -> Result<usize, BenchmarkError> {
+    Ok(layout(circuit)?.rows)
+}
+
+// helper function for the number of copy constraints in a circuit's layout, including the copies from the constants
+// column that the floor planner adds for assign_advice_from_constant
+pub(crate) fn copy_constraints<F: Field, C: Circuit<F>>(circuit: &C) -> Result<usize, BenchmarkError> {
+    Ok(layout(circuit)?.copies)
+}
+
+// helper function for the smallest k whose 2^k rows fit `rows` rows of layout: halo2 reserves the last
+// blinding_factors + 1 rows of every column (blinding and l_last), and needs at least minimum_rows in total
+pub(crate) fn fitting_k<F: Field>(meta: &ConstraintSystem<F>, rows: usize) -> u32 {
+    let needed = (rows + meta.blinding_factors() + 1).max(meta.minimum_rows());
+    needed.next_power_of_two().trailing_zeros()
+}
+
+// helper function for the smallest k whose 2^k rows fit the circuit's layout plus halo2's reserved (blinding) rows
+pub(crate) fn minimal_k<F: Field, C: Circuit<F>>(circuit: &C) -> Result<u32, BenchmarkError> {
+    let mut meta = ConstraintSystem::default();
+    C::configure(&mut meta);
+    Ok(fitting_k(&meta, layout_rows(circuit)?))
+}
+
+// helper function to reject a k below the smallest that fits the circuit's layout, before MockProver or keygen would
+// run out of rows in the middle of synthesis
+pub(crate) fn check_k<F: Field, C: Circuit<F>>(label: &str, k: u32, circuit: &C) -> Result<(), BenchmarkError> {
+    let mut meta = ConstraintSystem::default();
+    C::configure(&mut meta);
+    let rows = layout_rows(circuit)?;
+    let minimum = fitting_k(&meta, rows);
+    if k < minimum {
+        return Err(BenchmarkError::KTooSmall { circuit: label.to_string(), k, minimum, rows });
+    }
+
+    Ok(())
+}
+
+// how MockProver checks a measured circuit. halo2_proofs 0.3 has neither verify_par nor a verify restricted to some
+// rows, and its verify() evaluates every gate on all 2^k rows, so the quick check runs MockProver at the smallest k
+// that fits instead: the rows it walks are the used region plus halo2's reserved rows. A quick measurement still
+// ends with one full check at the measured k
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckMode {
+    // MockProver at the measured k
+    #[default]
+    Full,
+    // MockProver at the smallest k that fits the circuit
+    Quick
+}
+
+impl CheckMode {
+    // k at which MockProver runs for a circuit measured at k
+    pub(crate) fn mock_k<F: Field, C: Circuit<F>>(&self, k: u32, circuit: &C) -> Result<u32, BenchmarkError> {
+        match self {
+            CheckMode::Full => Ok(k),
+            CheckMode::Quick => Ok(minimal_k(circuit)?.min(k))
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+use std::str::FromStr;
+use clap::ValueEnum;
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::Value,
+    pasta::{EqAffine, Fp},
+    plonk::{Circuit, keygen_pk, keygen_vk},
+    poly::commitment::Params,
+};
+use halo2curves::bls12381::Fr;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{ConstraintSystemSummary, analyze};
+use crate::bench::{expected_instances, measure_circuit};
+use crate::chip::PermutationChip;
+use crate::circuits::{OutputMode, PoseidonCircuit, RescueCircuit};
+use crate::error::BenchmarkError;
+use crate::instances::PublicInputs;
+use crate::lint::{SelectorOverlap, selector_overlaps};
+use crate::params::{poseidon_params, rescue_params};
+use crate::poseidon::PoseidonChip;
+use crate::proving::create_ipa_proof;
+use crate::rescue::RescueChip;
+use crate::report::{CircuitReport, ParametersReport, Repeat, hex_words};
+use crate::synthesis::{CheckMode, RegionUsage, region_usage};
+#[cfg(test)]
+use {
+    halo2_proofs::dev::MockProver,
+    crate::failures::verification_failed,
+    crate::proving::verify_ipa_proof,
+};
+use crate::transcript::TranscriptKind;
+
+// Every permutation the tool benchmarks, as a value. `PermutationKind::target` turns one into a `BenchTarget`, which
+// mock-proves, measures, proves and verifies its permutation circuit behind one interface, so the command line, the
+// sweep and the snapshots take a kind and stay out of the circuit types. Only Poseidon and Rescue-Prime have a chip in
+// this crate; a permutation with a chip of its own becomes a variant here, an arm in `target`, and a `TargetCircuit`
+// impl for its circuit, and check_permutation_kinds covers it from then on.
+
+// permutations selectable from the command line; in JSON they are the kebab-case variant names
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum PermutationKind {
+    Poseidon,
+    Rescue
+}
+
+// the name PermutationKind had before it carried its targets, which the prelude keeps exporting
+pub type PermutationArg = PermutationKind;
+
+impl PermutationKind {
+    // every registered permutation, in declaration order, for the modes that run all of them
+    pub fn all() -> impl Iterator<Item = PermutationKind> + Clone {
+        PermutationKind::value_variants().iter().copied()
+    }
+
+    // the permutation's name in reports and output ("Poseidon", "Rescue-Prime")
+    pub fn name(self) -> &'static str {
+        self.target().name()
+    }
+
+    // the permutation circuit of this kind, over BLS12-381 for MockProver and over Pasta for real proofs
+    pub(crate) fn target(self) -> Box<dyn BenchTarget> {
+        match self {
+            PermutationKind::Poseidon => Box::new(CircuitTarget::<PoseidonCircuit<Fr>, PoseidonCircuit<Fp>>::new(self)),
+            PermutationKind::Rescue => Box::new(CircuitTarget::<RescueCircuit<Fr>, RescueCircuit<Fp>>::new(self))
+        }
+    }
+}
+
+// the command line spelling, case-insensitively, as clap parses it
+impl FromStr for PermutationKind {
+    type Err = BenchmarkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <PermutationKind as ValueEnum>::from_str(s, true).map_err(|_| {
+            let known: Vec<String> = PermutationKind::all().filter_map(|kind| kind.to_possible_value()).map(|value| value.get_name().to_string()).collect();
+            BenchmarkError::ParameterMismatch(format!("unknown permutation {:?}; expected one of {}", s, known.join(", ")))
+        })
+    }
+}
+
+impl fmt::Display for PermutationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// one permutation circuit: MockProver and measurements over BLS12-381, real proofs with IPA over Pasta (Blake2b
+// transcript), the circuit's input words private and the words `output_mode` selects public
+pub(crate) trait BenchTarget {
+    #[cfg(test)]
+    fn kind(&self) -> PermutationKind;
+
+    fn name(&self) -> &'static str;
+
+    // the public instances of the circuit on `inputs`, from the native permutation
+    fn expected_instances(&self, inputs: [Fr; 3], output_mode: OutputMode) -> Result<PublicInputs<Fr>, BenchmarkError>;
+
+    // run MockProver at k against `instances`, with the failures summarized if the circuit does not verify
+    #[cfg(test)]
+    fn mock(&self, k: u32, inputs: [Fr; 3], output_mode: OutputMode, instances: &PublicInputs<Fr>) -> Result<(), BenchmarkError>;
+
+    // time MockProver, synthesis and witness generation at k and run the cost model, as run_benchmark reports a circuit
+    fn measure(&self, k: u32, inputs: [Fr; 3], output_mode: OutputMode, repeat: Repeat, check: CheckMode) -> Result<CircuitReport, BenchmarkError>;
+
+    // the rows of each region of the circuit
+    fn regions(&self, output_mode: OutputMode) -> Result<Vec<RegionUsage>, BenchmarkError>;
+
+    // the rows of the circuit with more than one selector enabled, checked against the overlaps its chip declares
+    fn selector_overlaps(&self, output_mode: OutputMode) -> Result<Vec<SelectorOverlap>, BenchmarkError>;
+
+    // the circuit's constraint system, with the prover's domain at k
+    fn analysis(&self, k: u32) -> ConstraintSystemSummary;
+
+    // prove the circuit on `inputs` under `params`, returning the proof and the public instances it was proved against
+    fn prove(&self, params: &Params<EqAffine>, inputs: [Fp; 3], output_mode: OutputMode, rng: &mut dyn RngCore) -> Result<(Vec<u8>, PublicInputs<Fp>), BenchmarkError>;
+
+    // verify a proof from `prove` against `instances`, with a verifying key generated under `params`
+    #[cfg(test)]
+    fn verify(&self, params: &Params<EqAffine>, output_mode: OutputMode, proof: &[u8], instances: &PublicInputs<Fp>) -> Result<(), BenchmarkError>;
+}
+
+// a permutation circuit over any field, built from its three private inputs
+pub(crate) trait TargetCircuit<F: PrimeField>: Circuit<F> + Debug + Default {
+    const NAME: &'static str;
+
+    // the selector combinations the circuit's chip declares (see PermutationChip::SELECTOR_OVERLAPS)
+    const SELECTOR_OVERLAPS: &'static [&'static [&'static str]];
+
+    fn with_inputs(inputs: [Value<F>; 3], output_mode: OutputMode) -> Result<Self, BenchmarkError>;
+
+    // the parameters of the active source the circuit is built with
+    fn parameters() -> Result<ParametersReport, BenchmarkError>;
+}
+
+impl<F: PrimeField> TargetCircuit<F> for PoseidonCircuit<F> {
+    const NAME: &'static str = "Poseidon";
+    const SELECTOR_OVERLAPS: &'static [&'static [&'static str]] = PoseidonChip::<F>::SELECTOR_OVERLAPS;
+
+    fn with_inputs(inputs: [Value<F>; 3], output_mode: OutputMode) -> Result<Self, BenchmarkError> {
+        PoseidonCircuit::new(inputs, output_mode)
+    }
+
+    fn parameters() -> Result<ParametersReport, BenchmarkError> {
+        Ok(poseidon_params::<F>()?.report())
+    }
+}
+
+impl<F: PrimeField> TargetCircuit<F> for RescueCircuit<F> {
+    const NAME: &'static str = "Rescue-Prime";
+    const SELECTOR_OVERLAPS: &'static [&'static [&'static str]] = RescueChip::<F>::SELECTOR_OVERLAPS;
+
+    fn with_inputs(inputs: [Value<F>; 3], output_mode: OutputMode) -> Result<Self, BenchmarkError> {
+        RescueCircuit::new(inputs, output_mode)
+    }
+
+    fn parameters() -> Result<ParametersReport, BenchmarkError> {
+        Ok(rescue_params::<F>()?.report())
+    }
+}
+
+// the target of one permutation: C is its circuit over BLS12-381, D the same circuit over Pasta
+struct CircuitTarget<C, D> {
+    kind: PermutationKind,
+    _marker: PhantomData<fn() -> (C, D)>
+}
+
+impl<C, D> CircuitTarget<C, D> {
+    fn new(kind: PermutationKind) -> Self {
+        CircuitTarget { kind, _marker: PhantomData }
+    }
+}
+
+impl<C: TargetCircuit<Fr>, D: TargetCircuit<Fp>> BenchTarget for CircuitTarget<C, D> {
+    #[cfg(test)]
+    fn kind(&self) -> PermutationKind {
+        self.kind
+    }
+
+    fn name(&self) -> &'static str {
+        C::NAME
+    }
+
+    fn expected_instances(&self, inputs: [Fr; 3], output_mode: OutputMode) -> Result<PublicInputs<Fr>, BenchmarkError> {
+        expected_instances(self.kind, inputs, output_mode)
+    }
+
+    #[cfg(test)]
+    fn mock(&self, k: u32, inputs: [Fr; 3], output_mode: OutputMode, instances: &PublicInputs<Fr>) -> Result<(), BenchmarkError> {
+        let circuit = C::with_inputs(inputs.map(Value::known), output_mode)?;
+        MockProver::run(k, &circuit, instances.to_instances())?.verify().map_err(|failures| verification_failed(C::NAME, &failures))
+    }
+
+    fn measure(&self, k: u32, inputs: [Fr; 3], output_mode: OutputMode, repeat: Repeat, check: CheckMode) -> Result<CircuitReport, BenchmarkError> {
+        let instances = self.expected_instances(inputs, output_mode)?;
+        let circuit = C::with_inputs(inputs.map(Value::known), output_mode)?;
+        let (timings, cost) = measure_circuit(C::NAME, k, &circuit, &instances, repeat, check)?;
+
+        Ok(CircuitReport {
+            permutation: C::NAME,
+            statement: "permutation",
+            output_mode,
+            outputs: hex_words(instances.column(0)),
+            parameters: C::parameters()?,
+            timings,
+            cost
+        })
+    }
+
+    fn regions(&self, output_mode: OutputMode) -> Result<Vec<RegionUsage>, BenchmarkError> {
+        region_usage(C::NAME, &C::with_inputs([Value::unknown(); 3], output_mode)?)
+    }
+
+    fn selector_overlaps(&self, output_mode: OutputMode) -> Result<Vec<SelectorOverlap>, BenchmarkError> {
+        selector_overlaps(&C::with_inputs([Value::unknown(); 3], output_mode)?, C::SELECTOR_OVERLAPS)
+    }
+
+    fn analysis(&self, k: u32) -> ConstraintSystemSummary {
+        analyze::<C>().at_k(k)
+    }
+
+    fn prove(&self, params: &Params<EqAffine>, inputs: [Fp; 3], output_mode: OutputMode, rng: &mut dyn RngCore) -> Result<(Vec<u8>, PublicInputs<Fp>), BenchmarkError> {
+        let instances = expected_instances(self.kind, inputs, output_mode)?;
+        let circuit = D::with_inputs(inputs.map(Value::known), output_mode)?;
+        let pk = keygen_pk(params, keygen_vk(params, &circuit.without_witnesses())?, &circuit.without_witnesses())?;
+        let proof = create_ipa_proof(params, &pk, &circuit, &[instances.column(0)], rng, TranscriptKind::Blake2b)?;
+
+        Ok((proof, instances))
+    }
+
+    #[cfg(test)]
+    fn verify(&self, params: &Params<EqAffine>, output_mode: OutputMode, proof: &[u8], instances: &PublicInputs<Fp>) -> Result<(), BenchmarkError> {
+        let vk = keygen_vk(params, &D::with_inputs([Value::unknown(); 3], output_mode)?)?;
+        verify_ipa_proof(params, &vk, proof, &[instances.column(0)], TranscriptKind::Blake2b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proving::ProvingRng;
+    use crate::srs::commitment_params;
+
+    // every registered permutation through its target: the kind parses back from its command
+    // line and JSON names, its circuit verifies under MockProver at k against the native outputs in each output mode and
+    // rejects a changed output word, and a real proof at k verifies against its instances and not against changed ones
+    #[test]
+    fn permutation_kinds() -> Result<(), BenchmarkError> {
+        let (k, seed) = (10, Some(0));
+        let inputs = [0, 1, 2].map(Fr::from);
+        let params = commitment_params(k)?;
+        let mut rng = ProvingRng::new(seed);
+        for kind in PermutationKind::all() {
+            let target = kind.target();
+            assert_eq!(target.kind(), kind);
+            let spelling = kind.to_possible_value().unwrap();
+            assert_eq!(spelling.get_name().parse::<PermutationKind>()?, kind);
+            assert_eq!(spelling.get_name().to_uppercase().parse::<PermutationKind>()?, kind);
+            assert_eq!(serde_json::to_string(&kind).unwrap(), format!("\"{}\"", spelling.get_name()));
+
+            for output_mode in [OutputMode::AllState, OutputMode::DigestOnly] {
+                let instances = target.expected_instances(inputs, output_mode)?;
+                target.mock(k, inputs, output_mode, &instances)?;
+                let mut wrong = instances.to_instances();
+                wrong[0][0] += Fr::from(1);
+                if target.mock(k, inputs, output_mode, &PublicInputs::from_instances(wrong)).is_ok() {
+                    return Err(BenchmarkError::VerificationFailed(format!("{} accepted a changed output word", target.name())));
+                }
+            }
+
+            let (proof, instances) = target.prove(&params, [0, 1, 2].map(Fp::from), OutputMode::DigestOnly, &mut rng)?;
+            target.verify(&params, OutputMode::DigestOnly, &proof, &instances)?;
+            let mut wrong = instances.to_instances();
+            wrong[0][0] += Fp::from(1);
+            assert!(target.verify(&params, OutputMode::DigestOnly, &proof, &PublicInputs::from_instances(wrong)).is_err());
+        }
+        assert!(matches!("sha256".parse::<PermutationKind>(), Err(BenchmarkError::ParameterMismatch(_))));
+        println!("permutation kinds: {} registered, each mock-proves and proves through its target and rejects a changed output",
+            PermutationKind::all().count());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use clap::ValueEnum;
+use ff::PrimeField;
+use num_bigint::BigUint;
+use serde::Serialize;
+use halo2_proofs::{
+    arithmetic::{Coordinates, CurveAffine},
+    transcript::{EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite},
+};
+
+use crate::error::BenchmarkError;
+use crate::hash::Hasher;
+use crate::params::fe_from_biguint;
+use crate::sponge::{BYTES_PER_ELEMENT, Sponge};
+
+// Fiat-Shamir transcript over the native Poseidon sponge, the algebraic counterpart of halo2's Blake2b transcript that
+// recursion-friendly systems use. The proof has the layout of a Blake2b one (compressed points and canonical
+// scalars); only the challenges differ. A duplex sponge over the proof's scalar field absorbs what Blake2b hashes:
+// each scalar as one word, and each point as its affine coordinates, whose base field does not fit the scalar field,
+// split into 31-byte words. A challenge pads the words absorbed since the previous one with 10*, absorbs them two per
+// permutation from the sponge's initial capacity (domain tag plus padding tag), and is state[0], so the first challenge is poseidon_hash of the
+// words before it. Points at infinity are rejected, as Blake2b does.
+
+// Fiat-Shamir transcript of the real proofs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TranscriptKind {
+    // halo2's Blake2b transcript with Challenge255
+    #[default]
+    Blake2b,
+    // the Poseidon duplex sponge over the scalar field
+    Poseidon
+}
+
+// challenge squeezed from the Poseidon transcript: a scalar already, where Challenge255 reduces 64 hash bytes
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonChallenge<C: CurveAffine> {
+    scalar: C::Scalar
+}
+
+impl<C: CurveAffine> EncodedChallenge<C> for PoseidonChallenge<C> {
+    type Input = C::Scalar;
+
+    fn new(challenge_input: &C::Scalar) -> Self {
+        PoseidonChallenge { scalar: *challenge_input }
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        self.scalar
+    }
+}
+
+// helper structure for the duplex sponge shared by the writer and the reader
+struct PoseidonDuplex<F: PrimeField> {
+    hasher: Hasher<F>,
+    state: [F; 3],
+    // words absorbed since the last challenge
+    pending: Vec<F>
+}
+
+impl<F: PrimeField> PoseidonDuplex<F> {
+    fn new() -> Result<Self, BenchmarkError> {
+        let hasher = Hasher::poseidon()?;
+        let state = [F::ZERO, F::ZERO, Sponge::<F>::PADDING.capacity(hasher.domain_tag())];
+        Ok(PoseidonDuplex { hasher, state, pending: Vec::new() })
+    }
+
+    fn squeeze(&mut self) -> F {
+        let words: Vec<F> = self.pending.drain(..).collect();
+        let padding = Sponge::<F>::PADDING.pad::<F>(words.len());
+        for block in [words, padding].concat().chunks(2) {
+            self.state[0] += block[0];
+            self.state[1] += block[1];
+            self.state = self.hasher.permute(self.state);
+        }
+
+        self.state[0]
+    }
+
+    fn absorb_scalar(&mut self, scalar: F) {
+        self.pending.push(scalar);
+    }
+
+    // each coordinate as little-endian words of 31 bytes, canonical in the scalar field whatever the bytes are
+    fn absorb_point<C: CurveAffine<ScalarExt = F>>(&mut self, point: C) -> io::Result<()> {
+        let coordinates: Coordinates<C> = Option::from(point.coordinates())
+            .ok_or_else(|| io::Error::other("cannot write points at infinity to the transcript"))?;
+        for coordinate in [coordinates.x(), coordinates.y()] {
+            let repr = coordinate.to_repr();
+            self.pending.extend(repr.as_ref().chunks(BYTES_PER_ELEMENT).map(|chunk| fe_from_biguint::<F>(&BigUint::from_bytes_le(chunk))));
+        }
+
+        Ok(())
+    }
+}
+
+// Poseidon transcript writer, the counterpart of Blake2bWrite
+pub struct PoseidonWrite<W: Write, C: CurveAffine> {
+    duplex: PoseidonDuplex<C::Scalar>,
+    writer: W,
+    _marker: PhantomData<C>
+}
+
+impl<W: Write, C: CurveAffine> PoseidonWrite<W, C> {
+    pub fn init(writer: W) -> Result<Self, BenchmarkError> {
+        Ok(PoseidonWrite { duplex: PoseidonDuplex::new()?, writer, _marker: PhantomData })
+    }
+
+    // the proof written so far
+    pub fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, C: CurveAffine> Transcript<C, PoseidonChallenge<C>> for PoseidonWrite<W, C> {
+    fn squeeze_challenge(&mut self) -> PoseidonChallenge<C> {
+        PoseidonChallenge::new(&self.duplex.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.duplex.absorb_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.duplex.absorb_scalar(scalar);
+        Ok(())
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWrite<C, PoseidonChallenge<C>> for PoseidonWrite<W, C> {
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        self.writer.write_all(point.to_bytes().as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.writer.write_all(scalar.to_repr().as_ref())
+    }
+}
+
+// Poseidon transcript reader, the counterpart of Blake2bRead
+pub struct PoseidonRead<R: Read, C: CurveAffine> {
+    duplex: PoseidonDuplex<C::Scalar>,
+    reader: R,
+    _marker: PhantomData<C>
+}
+
+impl<R: Read, C: CurveAffine> PoseidonRead<R, C> {
+    pub fn init(reader: R) -> Result<Self, BenchmarkError> {
+        Ok(PoseidonRead { duplex: PoseidonDuplex::new()?, reader, _marker: PhantomData })
+    }
+}
+
+impl<R: Read, C: CurveAffine> Transcript<C, PoseidonChallenge<C>> for PoseidonRead<R, C> {
+    fn squeeze_challenge(&mut self) -> PoseidonChallenge<C> {
+        PoseidonChallenge::new(&self.duplex.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.duplex.absorb_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.duplex.absorb_scalar(scalar);
+        Ok(())
+    }
+}
+
+impl<R: Read, C: CurveAffine> TranscriptRead<C, PoseidonChallenge<C>> for PoseidonRead<R, C> {
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| io::Error::other("invalid point encoding in proof"))?;
+        self.common_point(point)?;
+
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut repr = <C::Scalar as PrimeField>::Repr::default();
+        self.reader.read_exact(repr.as_mut())?;
+        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(repr)).ok_or_else(|| io::Error::other("invalid field element encoding in proof"))?;
+        self.common_scalar(scalar)?;
+
+        Ok(scalar)
+    }
+}
@@ -0,0 +1,386 @@
+use std::path::Path;
+use ff::Field;
+use halo2_proofs::{circuit::Value, dev::MockProver};
+use halo2curves::bls12381::Fr;
+use halo2curves::bn256::Fr as Bn256Fr;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::circuits::{CircomPoseidonCircuit, OutputMode, PoseidonCircuit, RescueCircuit};
+use crate::error::BenchmarkError;
+use crate::failures::verification_failed;
+use crate::generate::poseidon_grain;
+use crate::native::{NativePermutation, TraceStep};
+use crate::params::{ArcPosition, Endianness, Poseidon, fe_from_hex, field_modulus, poseidon_params, rescue_params};
+use crate::presets::{NEPTUNE_ARITY_2_TAG, bls381_neptune, bn254_circom, neptune_hash};
+use crate::report::hex_words;
+use crate::source::{ParameterSource, set_parameter_source};
+use crate::synthesis::minimal_k;
+
+// Test vectors for cross-checking other implementations (Sage, circom, arkworks): input states with their Poseidon and
+// Rescue-Prime output states under the preset parameters over the BLS12-381 scalar field.
+
+// number of seeded random states after the fixed ones, and the ChaCha20 seed they are drawn with
+const RANDOM_VECTORS: usize = 8;
+const RANDOM_SEED: u64 = 0;
+
+// vector file layout; field elements are 0x-prefixed big-endian hex
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct VectorFile {
+    pub(crate) field: String,
+    pub(crate) modulus: String,
+    pub(crate) parameters: String,
+    pub(crate) vectors: Vec<TestVector>
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TestVector {
+    pub(crate) label: String,
+    pub(crate) input: Vec<String>,
+    pub(crate) poseidon: Vec<String>,
+    pub(crate) rescue: Vec<String>
+}
+
+// the committed vector file, next to the sources so that changes to it show up in review; relative to the crate root,
+// where `cargo run` is started from
+const VECTORS_PATH: &str = "tests/data/vectors.json";
+
+// helper function for the input states: edge values (0, 1, p - 1, mixed, all equal), the benchmark inputs, then
+// seeded random states
+fn inputs() -> Vec<(String, [Fr; 3])> {
+    let minus_one = -Fr::ONE;
+    let mut inputs: Vec<(String, [Fr; 3])> = [
+        ("zero", [Fr::ZERO; 3]),
+        ("one", [Fr::ONE; 3]),
+        ("minus-one", [minus_one; 3]),
+        ("edges", [Fr::ZERO, Fr::ONE, minus_one]),
+        ("all-equal", [Fr::from(42); 3]),
+        ("benchmark", [0, 1, 2].map(Fr::from))
+    ].into_iter().map(|(label, input)| (label.to_string(), input)).collect();
+
+    let mut rng = ChaCha20Rng::seed_from_u64(RANDOM_SEED);
+    for index in 0..RANDOM_VECTORS {
+        inputs.push((format!("random-{}", index), [(); 3].map(|_| Fr::random(&mut rng))));
+    }
+    inputs
+}
+
+// helper function to compute the vectors with the native permutations; the parameter source is pinned to the presets
+fn generate() -> Result<VectorFile, BenchmarkError> {
+    let _parameters = set_parameter_source(ParameterSource::Preset);
+    let poseidon = poseidon_params::<Fr>()?;
+    let rescue = rescue_params::<Fr>()?;
+    let vectors = inputs().into_iter()
+        .map(|(label, input)| Ok(TestVector {
+            label,
+            input: hex_words(&input),
+            poseidon: hex_words(&poseidon.permute_native(input)),
+            rescue: hex_words(&rescue.permute_native(input))
+        }))
+        .collect::<Result<_, BenchmarkError>>()?;
+
+    Ok(VectorFile {
+        field: "bls12-381 scalar".to_string(),
+        modulus: format!("0x{:x}", field_modulus::<Fr>()),
+        parameters: ParameterSource::Preset.to_string(),
+        vectors
+    })
+}
+
+// helper function to render a vector file exactly as it is committed
+fn render(file: &VectorFile) -> String {
+    serde_json::to_string_pretty(file).unwrap() + "\n"
+}
+
+// write the test vectors to `out`, or over the committed file when no path is given
+pub fn write_vectors(out: Option<&Path>) -> Result<(), BenchmarkError> {
+    let path = out.unwrap_or(Path::new(VECTORS_PATH));
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|source| BenchmarkError::Io { path: dir.display().to_string(), source })?;
+    }
+    std::fs::write(path, render(&generate()?)).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+    println!("test vectors written to {}", path.display());
+    Ok(())
+}
+
+// helper function to parse a state of three hex words from the vector file
+fn parse_state(path: &Path, label: &str, words: &[String]) -> Result<[Fr; 3], BenchmarkError> {
+    let state = words.iter().map(|word| fe_from_hex::<Fr>(word, Endianness::Big)).collect::<Result<Vec<_>, _>>()?;
+    state.try_into().map_err(|state: Vec<Fr>| BenchmarkError::InvalidVectorFile {
+        path: path.display().to_string(),
+        reason: format!("{}: expected 3 words, found {}", label, state.len())
+    })
+}
+
+// check a vector file: regenerating it must give byte-identical content, and every entry must match the native
+// permutations and verify in both permutation circuits under MockProver
+pub fn check_vectors(path: &Path) -> Result<(), BenchmarkError> {
+    let committed = std::fs::read_to_string(path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+    if committed != render(&generate()?) {
+        return Err(BenchmarkError::VerificationFailed(format!(
+            "{} differs from the generated vectors; if the change is intended, review it and run `cargo run -- vectors`",
+            path.display()
+        )));
+    }
+    let file: VectorFile = serde_json::from_str(&committed)
+        .map_err(|e| BenchmarkError::InvalidVectorFile { path: path.display().to_string(), reason: e.to_string() })?;
+
+    let _parameters = set_parameter_source(ParameterSource::Preset);
+    let poseidon = poseidon_params::<Fr>()?;
+    let rescue = rescue_params::<Fr>()?;
+    let output_mode = OutputMode::AllState;
+    let k_ps = minimal_k(&PoseidonCircuit::<Fr> { output_mode, ..Default::default() })?;
+    let k_rs = minimal_k(&RescueCircuit::<Fr> { output_mode, ..Default::default() })?;
+    for vector in &file.vectors {
+        let input = parse_state(path, &vector.label, &vector.input)?;
+        let expected_ps = parse_state(path, &vector.label, &vector.poseidon)?;
+        let expected_rs = parse_state(path, &vector.label, &vector.rescue)?;
+        let mismatch = |permutation: &str| BenchmarkError::VerificationFailed(format!("test vector {}: {} output differs", vector.label, permutation));
+        if poseidon.permute_native(input) != expected_ps {
+            return Err(mismatch("native Poseidon"));
+        }
+        if rescue.permute_native(input) != expected_rs {
+            return Err(mismatch("native Rescue-Prime"));
+        }
+
+        let [s0, s1, s2] = input.map(Value::known);
+        MockProver::run(k_ps, &PoseidonCircuit { s0, s1, s2, output_mode }, vec![expected_ps.to_vec()])?
+            .verify()
+            .map_err(|failures| verification_failed(format_args!("test vector {}: Poseidon circuit output differs", vector.label), &failures))?;
+        MockProver::run(k_rs, &RescueCircuit { s0, s1, s2, output_mode }, vec![expected_rs.to_vec()])?
+            .verify()
+            .map_err(|failures| verification_failed(format_args!("test vector {}: Rescue-Prime circuit output differs", vector.label), &failures))?;
+    }
+    println!("{} test vectors match the native permutations and both circuits", file.vectors.len());
+
+    Ok(())
+}
+
+// round-by-round trace fixture for auditing the permutations against an independent implementation: for the
+// benchmark input (0, 1, 2), every intermediate state of Poseidon and of Rescue-Prime under the preset parameters over
+// the BLS12-381 scalar field, as 0x-prefixed big-endian hex like the vectors
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TraceFile {
+    pub(crate) field: String,
+    pub(crate) modulus: String,
+    pub(crate) parameters: String,
+    pub(crate) input: Vec<String>,
+    pub(crate) poseidon: Vec<TraceEntry>,
+    pub(crate) rescue: Vec<TraceEntry>
+}
+
+// one step of a trace: the round (from 0), the step applied in it and the state after it
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TraceEntry {
+    pub(crate) round: usize,
+    pub(crate) step: String,
+    pub(crate) state: Vec<String>
+}
+
+// the committed trace fixture, next to the test vectors
+const TRACE_PATH: &str = "tests/data/trace.json";
+
+// helper function to compute the trace fixture with the native traces; the parameter source is pinned to the presets
+fn generate_trace() -> Result<TraceFile, BenchmarkError> {
+    let _parameters = set_parameter_source(ParameterSource::Preset);
+    let input = [0, 1, 2].map(Fr::from);
+    let entries = |steps: Vec<TraceStep<Fr>>| -> Vec<TraceEntry> {
+        steps.into_iter().map(|step| TraceEntry { round: step.round, step: step.step.to_string(), state: hex_words(&step.state) }).collect()
+    };
+
+    Ok(TraceFile {
+        field: "bls12-381 scalar".to_string(),
+        modulus: format!("0x{:x}", field_modulus::<Fr>()),
+        parameters: ParameterSource::Preset.to_string(),
+        input: hex_words(&input),
+        poseidon: entries(poseidon_params::<Fr>()?.trace_native(input)),
+        rescue: entries(rescue_params::<Fr>()?.trace_native(input))
+    })
+}
+
+// write the trace fixture to `out`, or over the committed file when no path is given
+pub fn write_fixtures(out: Option<&Path>) -> Result<(), BenchmarkError> {
+    let path = out.unwrap_or(Path::new(TRACE_PATH));
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|source| BenchmarkError::Io { path: dir.display().to_string(), source })?;
+    }
+    let trace = serde_json::to_string_pretty(&generate_trace()?).unwrap() + "\n";
+    std::fs::write(path, trace).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+    println!("trace fixture written to {}", path.display());
+    Ok(())
+}
+
+// check a trace fixture element by element against a regenerated trace, naming the first divergence, and check that
+// each trace ends at the native permutation's output
+pub fn check_trace_fixture(path: &Path) -> Result<(), BenchmarkError> {
+    let committed = std::fs::read_to_string(path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+    let fixture: TraceFile = serde_json::from_str(&committed)
+        .map_err(|e| BenchmarkError::InvalidVectorFile { path: path.display().to_string(), reason: e.to_string() })?;
+    let generated = generate_trace()?;
+    let diverged = |what: String| BenchmarkError::VerificationFailed(format!(
+        "{} diverges from the native trace at {}; if the change is intended, review it and run `cargo run -- gen-fixtures`",
+        path.display(), what
+    ));
+    for (name, fixture, generated) in [("field", &fixture.field, &generated.field), ("modulus", &fixture.modulus, &generated.modulus), ("parameters", &fixture.parameters, &generated.parameters)] {
+        if fixture != generated {
+            return Err(diverged(format!("{}: {} in the fixture, {} regenerated", name, fixture, generated)));
+        }
+    }
+    if fixture.input != generated.input {
+        return Err(diverged(format!("the input: {:?} in the fixture, {:?} regenerated", fixture.input, generated.input)));
+    }
+
+    for (permutation, fixture, generated) in [("Poseidon", &fixture.poseidon, &generated.poseidon), ("Rescue-Prime", &fixture.rescue, &generated.rescue)] {
+        for (index, (expected, actual)) in fixture.iter().zip(generated).enumerate() {
+            if (expected.round, &expected.step) != (actual.round, &actual.step) {
+                return Err(diverged(format!(
+                    "{} step {}: round {} {} in the fixture, round {} {} regenerated",
+                    permutation, index, expected.round, expected.step, actual.round, actual.step
+                )));
+            }
+            let mut words = expected.state.iter().zip(&actual.state).enumerate();
+            if let Some((word, (expected_word, actual_word))) = words.find(|(_, (expected, actual))| expected != actual) {
+                return Err(diverged(format!(
+                    "{} round {} {}, word {}: {} in the fixture, {} regenerated",
+                    permutation, actual.round, actual.step, word, expected_word, actual_word
+                )));
+            }
+            if expected.state.len() != actual.state.len() {
+                return Err(diverged(format!("{} round {} {}: {} words in the fixture", permutation, actual.round, actual.step, expected.state.len())));
+            }
+        }
+        if fixture.len() != generated.len() {
+            return Err(diverged(format!("{} step {}: {} steps in the fixture, {} regenerated", permutation, fixture.len().min(generated.len()), fixture.len(), generated.len())));
+        }
+    }
+
+    let _parameters = set_parameter_source(ParameterSource::Preset);
+    let input = [0, 1, 2].map(Fr::from);
+    let outputs = [
+        (poseidon_params::<Fr>()?.permute_native(input), &generated.poseidon),
+        (rescue_params::<Fr>()?.permute_native(input), &generated.rescue)
+    ];
+    for (output, trace) in outputs {
+        if trace.last().map(|entry| &entry.state) != Some(&hex_words(&output)) {
+            return Err(BenchmarkError::VerificationFailed("a native trace does not end at the permutation output".to_string()));
+        }
+    }
+    println!("trace fixture matches the native traces: {} Poseidon and {} Rescue-Prime steps", fixture.poseidon.len(), fixture.rescue.len());
+
+    Ok(())
+}
+
+// layout of a file of two-word digests from another implementation (circomlib, neptune); field elements are
+// 0x-prefixed big-endian hex
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DigestVectorFile {
+    source: String,
+    field: String,
+    modulus: String,
+    vectors: Vec<DigestVector>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DigestVector {
+    label: String,
+    inputs: [String; 2],
+    digest: String
+}
+
+// check the bn254_circom preset against a file of circomlib's published digests: the tables must be the Grain LFSR
+// output for its round numbers, and every vector must match the native permutation of [0, a, b] and verify under
+// MockProver, while a wrong digest must be rejected. The vectors must also match the preset's constants-after-S-box
+// form, and not the preset constants moved after the S-box
+pub fn check_circom_vectors(path: &Path) -> Result<(), BenchmarkError> {
+    let committed = std::fs::read_to_string(path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+    let invalid = |reason: String| BenchmarkError::InvalidVectorFile { path: path.display().to_string(), reason };
+    let file: DigestVectorFile = serde_json::from_str(&committed).map_err(|e| invalid(e.to_string()))?;
+    if file.field != "bn254 scalar" || file.modulus != format!("0x{:x}", field_modulus::<Bn256Fr>()) {
+        return Err(invalid(format!("expected the bn254 scalar field, found {} ({})", file.field, file.modulus)));
+    }
+
+    let params = bn254_circom()?;
+    let (round_constants, mds) = poseidon_grain::<Bn256Fr, 3>(params.full_rounds, params.partial_rounds, params.sbox);
+    if params.round_constants != round_constants || params.mds != mds {
+        return Err(BenchmarkError::VerificationFailed("the circomlib tables differ from the Grain LFSR output".to_string()));
+    }
+
+    // circomlib's optimized template adds the first round's constants to the input and each later round's after the
+    // previous S-box, which is the constants-after-S-box form of the same tables
+    let (first, folded) = params.after_sbox_form()?;
+    let swapped = Poseidon { arc: ArcPosition::AfterSbox, ..params.clone() };
+
+    let k = minimal_k(&CircomPoseidonCircuit::default())?;
+    for vector in &file.vectors {
+        let [a, b] = vector.inputs.each_ref().map(|word| fe_from_hex::<Bn256Fr>(word, Endianness::Big));
+        let (a, b) = (a?, b?);
+        let digest = fe_from_hex::<Bn256Fr>(&vector.digest, Endianness::Big)?;
+        let mismatch = |what: &str| BenchmarkError::VerificationFailed(format!("circomlib vector {}: {}", vector.label, what));
+        if params.permute_native([Bn256Fr::ZERO, a, b])[0] != digest {
+            return Err(mismatch("native Poseidon digest differs"));
+        }
+        if folded.permute_native([first[0], a + first[1], b + first[2]])[0] != digest {
+            return Err(mismatch("digest with the constants after the S-box differs"));
+        }
+        if swapped.permute_native([Bn256Fr::ZERO, a, b])[0] == digest {
+            return Err(mismatch("the preset constants after the S-box give the same digest"));
+        }
+
+        let circuit = CircomPoseidonCircuit { inputs: [Value::known(a), Value::known(b)] };
+        MockProver::run(k, &circuit, vec![vec![digest]])?
+            .verify()
+            .map_err(|failures| verification_failed(format_args!("circomlib vector {}: Poseidon circuit does not verify", vector.label), &failures))?;
+        if MockProver::run(k, &circuit, vec![vec![digest + Bn256Fr::ONE]])?.verify().is_ok() {
+            return Err(mismatch("Poseidon circuit accepts a wrong digest"));
+        }
+    }
+    println!(
+        "{} vectors from {} match the BN254 Poseidon preset natively and in the circuit, and its constants-after-S-box form natively",
+        file.vectors.len(), file.source
+    );
+
+    Ok(())
+}
+
+// check the poseidon-bls381-t3-neptune preset against a file of neptune's digests. neptune adds the first round's
+// constants to the preimage and each later round's after the previous S-box, so every vector must match the preset's
+// constants-after-S-box form on (3, a, b) plus the first round's constants, and the preset itself through
+// neptune_hash, but not the preset constants moved after the S-box
+pub fn check_neptune_vectors(path: &Path) -> Result<(), BenchmarkError> {
+    let committed = std::fs::read_to_string(path).map_err(|source| BenchmarkError::Io { path: path.display().to_string(), source })?;
+    let invalid = |reason: String| BenchmarkError::InvalidVectorFile { path: path.display().to_string(), reason };
+    let file: DigestVectorFile = serde_json::from_str(&committed).map_err(|e| invalid(e.to_string()))?;
+    if file.field != "bls12-381 scalar" || file.modulus != format!("0x{:x}", field_modulus::<Fr>()) {
+        return Err(invalid(format!("expected the bls12-381 scalar field, found {} ({})", file.field, file.modulus)));
+    }
+
+    let params = bls381_neptune()?;
+    let (first, folded) = params.after_sbox_form()?;
+    let swapped = Poseidon { arc: ArcPosition::AfterSbox, ..params.clone() };
+    let tag = Fr::from(NEPTUNE_ARITY_2_TAG);
+    for vector in &file.vectors {
+        let [a, b] = vector.inputs.each_ref().map(|word| fe_from_hex::<Fr>(word, Endianness::Big));
+        let (a, b) = (a?, b?);
+        let digest = fe_from_hex::<Fr>(&vector.digest, Endianness::Big)?;
+        let mismatch = |what: &str| BenchmarkError::VerificationFailed(format!("neptune vector {}: {}", vector.label, what));
+        if folded.permute_native([tag + first[0], a + first[1], b + first[2]])[1] != digest {
+            return Err(mismatch("digest with the constants after the S-box differs"));
+        }
+        if neptune_hash(&params, [a, b])? != digest {
+            return Err(mismatch("native Poseidon digest differs"));
+        }
+        if swapped.permute_native([tag, a, b])[1] == digest {
+            return Err(mismatch("the preset constants after the S-box give the same digest"));
+        }
+    }
+    println!("{} vectors from {} match the neptune preset's constants-after-S-box form and the preset", file.vectors.len(), file.source);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use halo2_proofs::{circuit::Value, dev::MockProver, plonk::Circuit};
+use halo2curves::bls12381::Fr;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::circuits::OutputMode;
+#[cfg(feature = "poseidon")]
+use crate::circuits::PoseidonCircuit;
+#[cfg(feature = "rescue")]
+use crate::circuits::RescueCircuit;
+use crate::clock::Instant;
+use crate::instances::PublicInputs;
+use crate::native::NativePermutation;
+#[cfg(feature = "poseidon")]
+use crate::params::poseidon_params;
+#[cfg(feature = "rescue")]
+use crate::params::rescue_params;
+use crate::synthesis::{layout_rows, time_synthesis};
+
+// in-browser entry points: one MockProver round over a single permutation circuit on the inputs (0, 1, 2), with all
+// state words public, returned as JSON. They only need the chip features, not the proving dependencies of bench-cli
+
+// what one in-browser round measures
+#[derive(Serialize)]
+struct MockProverReport {
+    permutation: &'static str,
+    k: u32,
+    rows: usize,
+    synthesis_us: u128,
+    mock_prover_ms: u128
+}
+
+#[cfg(feature = "poseidon")]
+#[wasm_bindgen]
+pub fn bench_poseidon(k: u32) -> Result<JsValue, JsError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let circuit = PoseidonCircuit::new(inputs.map(Value::known), OutputMode::AllState)?;
+    bench("Poseidon", k, circuit, poseidon_params::<Fr>()?.permute_native(inputs))
+}
+
+#[cfg(feature = "rescue")]
+#[wasm_bindgen]
+pub fn bench_rescue(k: u32) -> Result<JsValue, JsError> {
+    let inputs = [0, 1, 2].map(Fr::from);
+    let circuit = RescueCircuit::new(inputs.map(Value::known), OutputMode::AllState)?;
+    bench("Rescue-Prime", k, circuit, rescue_params::<Fr>()?.permute_native(inputs))
+}
+
+// helper function to lay out, synthesize and mock-prove a permutation circuit whose outputs are `state`
+fn bench<C: Circuit<Fr>>(permutation: &'static str, k: u32, circuit: C, state: [Fr; 3]) -> Result<JsValue, JsError> {
+    let rows = layout_rows(&circuit)?;
+    let synthesis_us = time_synthesis(&circuit)?;
+    let start = Instant::now();
+    MockProver::run(k, &circuit, PublicInputs::from_output(OutputMode::AllState, &state).into())?.verify()
+        .map_err(|failures| JsError::new(&format!("the {} circuit fails {} constraints at k = {}", permutation, failures.len(), k)))?;
+    let mock_prover_ms = start.elapsed().as_millis();
+
+    let report = MockProverReport { permutation, k, rows, synthesis_us, mock_prover_ms };
+    Ok(JsValue::from_str(&serde_json::to_string(&report)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    // helper function to read a report back from the JSON an entry point returns
+    fn report(json: JsValue) -> serde_json::Value {
+        serde_json::from_str(&json.as_string().expect("the report is a JSON string")).unwrap()
+    }
+
+    // the Poseidon entry point mock-proves its 131-row region at k = 8 and refuses a k it does not fit in
+    #[cfg(feature = "poseidon")]
+    #[wasm_bindgen_test]
+    fn poseidon() {
+        let report = report(bench_poseidon(8).unwrap());
+        assert_eq!((report["permutation"].as_str(), report["k"].as_u64(), report["rows"].as_u64()), (Some("Poseidon"), Some(8), Some(131)));
+        assert!(bench_poseidon(4).is_err());
+    }
+
+    // the Rescue-Prime entry point mock-proves its 57-row region at k = 7 and refuses a k it does not fit in
+    #[cfg(feature = "rescue")]
+    #[wasm_bindgen_test]
+    fn rescue() {
+        let report = report(bench_rescue(7).unwrap());
+        assert_eq!((report["permutation"].as_str(), report["k"].as_u64(), report["rows"].as_u64()), (Some("Rescue-Prime"), Some(7), Some(57)));
+        assert!(bench_rescue(4).is_err());
+    }
+}
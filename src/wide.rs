@@ -0,0 +1,315 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::chip::{Number, StateWord, UtilsInstructions, assign_word};
+use crate::error::BenchmarkError;
+use crate::gates::{CircuitParameters, ConstantColumns, allocate_selector, create_gate, create_is_equal_gate, create_select_gate, enable_constant, matrix_mul_exprs, pow_expression};
+use crate::params::{WidePoseidon, WideRescue, wide_poseidon_params, wide_rescue_params};
+use crate::synthesis::check_region_rows;
+
+// Permutations of width T on a chip of their own, for compressions of T - 1 words such as the 4-ary Merkle tree over
+// t = 5. The benchmark chips are fixed to t = 3; this one keeps their row layout (two rows per Poseidon round, four per
+// Rescue-Prime round) on T advice columns, so the two arities differ in the width only. Like the Anemoi chip it computes
+// the trace natively and assigns it row by row, with the round constants in T fixed columns on the rows that add them.
+
+// a width-T permutation the wide chip can lay out: its gates, the gate on each row and the native trace
+pub(crate) trait WidePermutation<F: PrimeField, const T: usize>: Clone + Debug + Sized {
+    // name used in logs and the benchmark report
+    const NAME: &'static str;
+
+    // the instance for 128-bit security
+    fn load() -> Result<Self, BenchmarkError>;
+
+    // create the gates over the chip's columns, returning their selectors
+    fn configure_gates(&self, meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; T], constants: [Column<Fixed>; T]) -> Vec<Selector>;
+
+    // the gate of every row of a region but the last, with the round constants that row holds
+    fn row_gates(&self, selectors: &[Selector]) -> Vec<(Selector, Option<[F; T]>)>;
+
+    // the state on every row of a region, starting with the input
+    fn trace_native(&self, input: [F; T]) -> Vec<[F; T]>;
+
+    // capacity value used by the compression function
+    fn domain_tag(&self) -> F;
+
+    fn permute_native(&self, input: [F; T]) -> [F; T] {
+        *self.trace_native(input).last().expect("the trace starts with the input")
+    }
+
+    // compression of T - 1 words: the domain tag fills the capacity, the digest is state[0]
+    fn compress_native(&self, inputs: &[F]) -> F {
+        assert_eq!(inputs.len(), T - 1, "a width-{} compression takes {} words", T, T - 1);
+        self.permute_native(std::array::from_fn(|i| inputs.get(i).copied().unwrap_or(self.domain_tag())))[0]
+    }
+}
+
+impl<F: PrimeField, const T: usize> WidePermutation<F, T> for WidePoseidon<F, T> {
+    const NAME: &'static str = "Poseidon";
+
+    fn load() -> Result<Self, BenchmarkError> {
+        wide_poseidon_params()
+    }
+
+    // ARC and S-box on all words (full rounds) or on word 0 (partial rounds), each into the next row, and the
+    // MixLayer from one row to the next
+    fn configure_gates(&self, meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; T], constants: [Column<Fixed>; T]) -> Vec<Selector> {
+        let [s_full, s_partial, s_mds] = [(); 3].map(|()| allocate_selector(meta));
+        for (name, selector, words) in [("WP_full_round_gate", s_full, T), ("WP_partial_round_gate", s_partial, 1)] {
+            create_gate(meta, name, |meta| {
+                let s_round = meta.query_selector(selector);
+                (0..T).map(|i| {
+                    let input = meta.query_advice(advice[i], Rotation::cur()) + meta.query_fixed(constants[i]);
+                    let output = meta.query_advice(advice[i], Rotation::next());
+                    let image = if i < words { pow_expression(input, 5) } else { input };
+                    s_round.clone() * (output - image)
+                }).collect()
+            });
+        }
+        let mds = self.mds;
+        create_gate(meta, "WP_mds_gate", |meta| {
+            let s_mds = meta.query_selector(s_mds);
+            let state: [Expression<F>; T] = std::array::from_fn(|i| meta.query_advice(advice[i], Rotation::cur()));
+            let mixed = matrix_mul_exprs(&mds, &state);
+            (0..T).map(|i| s_mds.clone() * (meta.query_advice(advice[i], Rotation::next()) - mixed[i].clone())).collect()
+        });
+
+        vec![s_full, s_partial, s_mds]
+    }
+
+    fn row_gates(&self, selectors: &[Selector]) -> Vec<(Selector, Option<[F; T]>)> {
+        let &[s_full, s_partial, s_mds] = selectors else { unreachable!("three Poseidon selectors") };
+        self.round_constants.iter().enumerate()
+            .flat_map(|(round, constants)| {
+                let s_round = if self.is_full_round(round) { s_full } else { s_partial };
+                [(s_round, Some(*constants)), (s_mds, None)]
+            })
+            .collect()
+    }
+
+    fn trace_native(&self, input: [F; T]) -> Vec<[F; T]> {
+        WidePoseidon::trace_native(self, input)
+    }
+
+    fn domain_tag(&self) -> F {
+        self.domain_tag.value()
+    }
+}
+
+impl<F: PrimeField, const T: usize> WidePermutation<F, T> for WideRescue<F, T> {
+    const NAME: &'static str = "Rescue-Prime";
+
+    fn load() -> Result<Self, BenchmarkError> {
+        wide_rescue_params()
+    }
+
+    // SubBytes into the next row, the inverse S-box as next^alpha = cur, and MixLayer with the round constants
+    fn configure_gates(&self, meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; T], constants: [Column<Fixed>; T]) -> Vec<Selector> {
+        let [s_sub_bytes, s_sub_bytes_inv, s_mds_arc] = [(); 3].map(|()| allocate_selector(meta));
+        let alpha = self.alpha;
+        create_gate(meta, "WR_sbox_gate", |meta| {
+            let s_sub_bytes = meta.query_selector(s_sub_bytes);
+            (0..T).map(|i| {
+                let a = meta.query_advice(advice[i], Rotation::cur());
+                s_sub_bytes.clone() * (meta.query_advice(advice[i], Rotation::next()) - pow_expression(a, alpha))
+            }).collect()
+        });
+        create_gate(meta, "WR_sbox_inv_gate", |meta| {
+            let s_sub_bytes_inv = meta.query_selector(s_sub_bytes_inv);
+            (0..T).map(|i| {
+                let a_next = meta.query_advice(advice[i], Rotation::next());
+                s_sub_bytes_inv.clone() * (meta.query_advice(advice[i], Rotation::cur()) - pow_expression(a_next, alpha))
+            }).collect()
+        });
+        let mds = self.mds;
+        create_gate(meta, "WR_mds_arc_gate", |meta| {
+            let s_mds_arc = meta.query_selector(s_mds_arc);
+            let state: [Expression<F>; T] = std::array::from_fn(|i| meta.query_advice(advice[i], Rotation::cur()));
+            let mixed = matrix_mul_exprs(&mds, &state);
+            (0..T).map(|i| {
+                let output = meta.query_advice(advice[i], Rotation::next());
+                s_mds_arc.clone() * (output - (mixed[i].clone() + meta.query_fixed(constants[i])))
+            }).collect()
+        });
+
+        vec![s_sub_bytes, s_sub_bytes_inv, s_mds_arc]
+    }
+
+    fn row_gates(&self, selectors: &[Selector]) -> Vec<(Selector, Option<[F; T]>)> {
+        let &[s_sub_bytes, s_sub_bytes_inv, s_mds_arc] = selectors else { unreachable!("three Rescue-Prime selectors") };
+        self.round_constants.chunks(2)
+            .flat_map(|constants| [(s_sub_bytes, None), (s_mds_arc, Some(constants[0])), (s_sub_bytes_inv, None), (s_mds_arc, Some(constants[1]))])
+            .collect()
+    }
+
+    fn trace_native(&self, input: [F; T]) -> Vec<[F; T]> {
+        WideRescue::trace_native(self, input)
+    }
+
+    fn domain_tag(&self) -> F {
+        self.domain_tag.value()
+    }
+}
+
+// wide chip configuration: the state in T advice columns, the round constants in T fixed columns, the first of which
+// also holds the domain tag, and the select gates of the t = 3 chips on the first three advice columns
+#[derive(Clone, Debug)]
+pub(crate) struct WideChipConfig<F: PrimeField, const T: usize, P> {
+    pub(crate) permutation_params: P,
+    pub(crate) advice: [Column<Advice>; T],
+    pub(crate) constants: [Column<Fixed>; T],
+    pub(crate) circuit_params: CircuitParameters,
+    // the permutation's gates, in the order configure_gates returns them
+    pub(crate) selectors: Vec<Selector>,
+    pub(crate) _marker: PhantomData<F>
+}
+
+// structure for the wide permutation chip
+pub(crate) struct WideChip<F: PrimeField, const T: usize, P> {
+    pub(crate) config: WideChipConfig<F, T, P>
+}
+
+// implement the Chip trait for WideChip
+impl<F: PrimeField, const T: usize, P: WidePermutation<F, T>> Chip<F> for WideChip<F, T, P> {
+    type Config = WideChipConfig<F, T, P>;
+    type Loaded = ();
+
+    // getter for the chip config
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    // getter for the loaded field
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// implementation of additional methods for the WideChip
+impl<F: PrimeField, const T: usize, P: WidePermutation<F, T>> WideChip<F, T, P> {
+    // constructor
+    pub(crate) fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        WideChip { config }
+    }
+
+    // configure the chip with its own columns, including all gates, constraints, and selectors
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, params: P) -> <Self as Chip<F>>::Config {
+        let advice: [Column<Advice>; T] = std::array::from_fn(|_| meta.advice_column());
+        let constants: [Column<Fixed>; T] = std::array::from_fn(|_| meta.fixed_column());
+        let instance = meta.instance_column();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+        enable_constant(meta, constants[0]);
+
+        let selectors = params.configure_gates(meta, advice, constants);
+        let shared = [advice[0], advice[1], advice[2]];
+        let s_select = allocate_selector(meta);
+        let s_is_equal = allocate_selector(meta);
+        create_select_gate(meta, shared, s_select);
+        create_is_equal_gate(meta, shared, s_is_equal);
+
+        WideChipConfig {
+            permutation_params: params,
+            advice,
+            constants,
+            circuit_params: CircuitParameters {
+                advice: shared,
+                constants: ConstantColumns::Fixed([constants[0], constants[1], constants[2]]),
+                instance,
+                instance_offset: 0,
+                s_select,
+                s_is_equal
+            },
+            selectors,
+            _marker: PhantomData
+        }
+    }
+
+    // configure the chip with the parameters for 128-bit security
+    pub(crate) fn configure_default(meta: &mut ConstraintSystem<F>) -> <Self as Chip<F>>::Config {
+        // configure cannot return an error, so unsupported parameters are fatal here
+        let params = P::load().unwrap_or_else(|e| panic!("invalid width-{} {} parameters: {}", T, P::NAME, e));
+        WideChip::configure(meta, params)
+    }
+
+    // rows of one permutation region: the input and one row per step
+    pub(crate) fn expected_rows(&self) -> usize {
+        self.config.permutation_params.row_gates(&self.config.selectors).len() + 1
+    }
+
+    // rows of one compression: a permutation region, then the tag in the constants column, which the round constants
+    // share as on the t = 3 chips
+    pub(crate) fn compression_rows(&self) -> usize {
+        self.expected_rows() + 1
+    }
+
+    // copy a value into the instance column
+    pub(crate) fn expose_as_public(&self, mut layouter: impl Layouter<F>, num: Number<F>, row: usize) -> Result<(), Error> {
+        layouter.constrain_instance(num.cell(), self.config.circuit_params.instance, row)
+    }
+
+    // permutation of an initial state whose words are witnessed, constant or copied from earlier cells
+    pub(crate) fn permute_state(&self, mut layouter: impl Layouter<F>, initial: [StateWord<'_, F>; T]) -> Result<[Number<F>; T], Error> {
+        let config = self.config();
+        let params = &config.permutation_params;
+        layouter.assign_region(
+            || format!("{}_t{}_Permutation", P::NAME, T), |mut region| {
+                let span = tracing::debug_span!("wide.region", permutation = P::NAME, t = T, rows = tracing::field::Empty);
+                let _region = span.enter();
+
+                let mut state: Vec<Number<F>> = Vec::with_capacity(T);
+                for (i, word) in initial.iter().enumerate() {
+                    state.push(Number::from(assign_word(&mut region, "state", config.advice[i], *word)?));
+                }
+                let input: Value<Vec<F>> = state.iter().map(|number| number.value().copied()).collect();
+                let trace = input.map(|input| params.trace_native(std::array::from_fn(|i| input[i])));
+
+                let row_gates = params.row_gates(&config.selectors);
+                for (row, (selector, constants)) in row_gates.iter().enumerate() {
+                    selector.enable(&mut region, row)?;
+                    if let Some(constants) = constants {
+                        for (column, constant) in config.constants.iter().zip(constants) {
+                            region.assign_fixed(|| "round_constant", *column, row, || Value::known(*constant))?;
+                        }
+                    }
+                    state.clear();
+                    for (i, column) in config.advice.iter().enumerate() {
+                        let word = trace.as_ref().map(|trace| trace[row + 1][i]);
+                        state.push(Number::from(region.assign_advice(|| "state", *column, row + 1, || word)?));
+                    }
+                }
+
+                let rows = row_gates.len() + 1;
+                check_region_rows("wide", rows, self.expected_rows())?;
+                span.record("rows", rows);
+
+                let mut words = state.drain(..);
+                Ok(std::array::from_fn(|_| words.next().expect("the region ends on a full state")))
+            }
+        )
+    }
+}
+
+impl<F: PrimeField, P: WidePermutation<F, 5>> WideChip<F, 5, P> {
+    // 4-to-1 compression of assigned cells: the four words are copied in, the domain tag fills the capacity, the digest
+    // is state[0]
+    pub(crate) fn compress4(&self, layouter: impl Layouter<F>, inputs: [Number<F>; 4]) -> Result<Number<F>, Error> {
+        let tag = self.config.permutation_params.domain_tag();
+        let [a, b, c, d] = &inputs;
+        let [digest, ..] = self.permute_state(layouter, [StateWord::Copy(a), StateWord::Copy(b), StateWord::Copy(c), StateWord::Copy(d), StateWord::Constant(tag)])?;
+        Ok(digest)
+    }
+}
+
+impl<F: PrimeField, const T: usize, P: WidePermutation<F, T>> UtilsInstructions<F> for WideChip<F, T, P> {
+    fn circuit_params(&self) -> &CircuitParameters {
+        &self.config.circuit_params
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt::{self, Debug};
+use ff::Field;
+use halo2_proofs::circuit::Value;
+
+// Private inputs a circuit pulls one at a time while it synthesizes, instead of holding all of them as witnesses before
+// synthesis starts. The number of inputs shapes the circuit and survives without_witnesses(); each value is produced
+// when the region assigning it is laid out, and dropped with that region's closure. A Vec of values is the buffered
+// source, with every witness built up front; the other sources compute an input from its index.
+
+// source of the private inputs of a circuit, by index
+pub(crate) trait InputStream<T>: Debug {
+    // the number of inputs
+    fn len(&self) -> usize;
+
+    // input `index`, with unknown values without witnesses
+    fn get(&self, index: usize) -> T;
+
+    // the same number of inputs with unknown values
+    fn without_witnesses(&self) -> Self where Self: Sized;
+
+    // the inputs in order, each produced when it is taken
+    fn stream(&self) -> impl Iterator<Item = T> + '_ where Self: Sized {
+        (0..self.len()).map(|index| self.get(index))
+    }
+}
+
+impl<F: Field> InputStream<Value<F>> for Vec<Value<F>> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, index: usize) -> Value<F> {
+        self[index]
+    }
+
+    fn without_witnesses(&self) -> Self {
+        vec![Value::unknown(); self.len()]
+    }
+}
+
+impl<F: Field> InputStream<[Value<F>; 3]> for Vec<[Value<F>; 3]> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, index: usize) -> [Value<F>; 3] {
+        self[index]
+    }
+
+    fn without_witnesses(&self) -> Self {
+        vec![[Value::unknown(); 3]; self.len()]
+    }
+}
+
+// inputs computed from their index when they are pulled, e.g. the states (3i, 3i + 1, 3i + 2) of a batch; without
+// witnesses only the count is left
+#[derive(Clone, Copy)]
+pub(crate) struct GeneratedInputs<T> {
+    len: usize,
+    generate: Option<fn(usize) -> T>
+}
+
+impl<T> GeneratedInputs<T> {
+    pub(crate) fn new(len: usize, generate: fn(usize) -> T) -> Self {
+        GeneratedInputs { len, generate: Some(generate) }
+    }
+}
+
+impl<T> Debug for GeneratedInputs<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneratedInputs").field("len", &self.len).field("known", &self.generate.is_some()).finish()
+    }
+}
+
+impl<F: Field> InputStream<[Value<F>; 3]> for GeneratedInputs<[F; 3]> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> [Value<F>; 3] {
+        match self.generate {
+            Some(generate) => generate(index).map(Value::known),
+            None => [Value::unknown(); 3]
+        }
+    }
+
+    fn without_witnesses(&self) -> Self {
+        GeneratedInputs { len: self.len, generate: None }
+    }
+}
@@ -0,0 +1,142 @@
+// Every gate helper on its own, in the single-gate circuit: the handcrafted witness verifies, and changing any output
+// cell, or moving the output to the wrong row, fails only on the gate under test. A few further cases cover the
+// branches a changed output does not reach.
+
+use ff::Field;
+use halo2_proofs::dev::VerifyFailure;
+use halo2curves::bls12381::Fr;
+use permutation_benchmark::{GateWitness, SingleGate};
+
+// helper function to assert that MockProver rejects `witness` in the gate under test and in no other gate
+fn assert_fails_only_in(gate: SingleGate, witness: GateWitness<Fr>) {
+    let name = format!("('{}')", gate.gate_name());
+    match gate.verify(witness).unwrap() {
+        Err(failures) => assert!(
+            failures.iter().all(|failure| matches!(failure, VerifyFailure::ConstraintNotSatisfied { constraint, .. } if constraint.to_string().contains(&name))),
+            "{:?}: expected failures in {} only, got {:?}", gate, gate.gate_name(), failures
+        ),
+        Ok(()) => panic!("{:?}: {:?} verifies", gate, witness)
+    }
+}
+
+// helper function for the rows every gate is checked on: the handcrafted witness verifies, each output cell changed
+// on its own fails, and so does the output on the wrong row (the current row for the two-row gates, or left where the
+// next row expects it for the one-row gates)
+fn assert_gate(gate: SingleGate) {
+    let valid = gate.witness();
+    assert_eq!(gate.verify(valid).unwrap(), Ok(()), "{:?}: the handcrafted witness fails", gate);
+    for (row, word) in gate.outputs() {
+        let mut wrong = valid;
+        wrong.rows[row][word] += Fr::ONE;
+        assert_fails_only_in(gate, wrong);
+    }
+
+    let mut shifted = valid;
+    shifted.rows = if gate.outputs()[0].0 == 1 { [valid.rows[1], valid.rows[0]] } else { [valid.rows[0].map(|word| word + Fr::ONE), valid.rows[0]] };
+    assert_fails_only_in(gate, shifted);
+}
+
+#[test]
+fn poseidon_full_round() {
+    assert_gate(SingleGate::PoseidonFull);
+}
+
+#[test]
+fn poseidon_full_round_inverse_sbox() {
+    assert_gate(SingleGate::PoseidonFullInverse);
+
+    // a non-zero input must not map to 0 under the inverse S-box, which only the zero input may
+    let mut wrong = SingleGate::PoseidonFullInverse.witness();
+    wrong.rows[1][0] = Fr::ZERO;
+    assert_fails_only_in(SingleGate::PoseidonFullInverse, wrong);
+}
+
+#[test]
+fn poseidon_full_round_squared() {
+    assert_gate(SingleGate::PoseidonFullSquared);
+}
+
+#[test]
+fn poseidon_full_round_inline_constants() {
+    assert_gate(SingleGate::PoseidonFullInline);
+}
+
+#[test]
+fn poseidon_full_round_constants_after_sbox() {
+    assert_gate(SingleGate::PoseidonFullAfterSbox);
+}
+
+#[test]
+fn poseidon_partial_round() {
+    assert_gate(SingleGate::PoseidonPartial);
+}
+
+#[test]
+fn poseidon_partial_round_constants_after_sbox() {
+    assert_gate(SingleGate::PoseidonPartialAfterSbox);
+}
+
+#[test]
+fn rescue_sbox() {
+    assert_gate(SingleGate::RescueSbox);
+}
+
+#[test]
+fn rescue_sbox_squared() {
+    assert_gate(SingleGate::RescueSboxSquared);
+}
+
+#[test]
+fn rescue_sbox_inverse() {
+    assert_gate(SingleGate::RescueSboxInv);
+}
+
+#[test]
+fn rescue_sbox_inverse_squared() {
+    assert_gate(SingleGate::RescueSboxInvSquared);
+}
+
+#[test]
+fn mds() {
+    assert_gate(SingleGate::Mds);
+}
+
+#[test]
+fn mds_arc() {
+    assert_gate(SingleGate::MdsArc);
+}
+
+#[test]
+fn add() {
+    assert_gate(SingleGate::Add);
+}
+
+#[test]
+fn mul() {
+    assert_gate(SingleGate::Mul);
+}
+
+#[test]
+fn select() {
+    assert_gate(SingleGate::Select);
+
+    // a bit of 2 would select 2a - b = 5
+    let mut wrong = SingleGate::Select.witness();
+    wrong.rows = [[Fr::from(2), Fr::from(7), Fr::from(9)], [Fr::from(5), Fr::ZERO, Fr::ZERO]];
+    assert_fails_only_in(SingleGate::Select, wrong);
+}
+
+#[test]
+fn is_equal() {
+    assert_gate(SingleGate::IsEqual);
+
+    // equal words give out = 1 for any inv, and unequal words cannot claim out = 1
+    let mut equal = SingleGate::IsEqual.witness();
+    equal.rows = [[Fr::from(4), Fr::from(4), Fr::from(9)], [Fr::ONE, Fr::ZERO, Fr::ZERO]];
+    assert_eq!(SingleGate::IsEqual.verify(equal).unwrap(), Ok(()), "is_equal on equal words fails");
+    let mut wrong = SingleGate::IsEqual.witness();
+    wrong.rows[1][0] = Fr::ONE;
+    wrong.rows[0][2] = Fr::ZERO;
+    assert_fails_only_in(SingleGate::IsEqual, wrong);
+}
+
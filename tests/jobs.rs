@@ -0,0 +1,67 @@
+// Prover jobs at k = 10, in a test binary of their own so PeakAllocator is the global allocator and no other test
+// allocates while the heap a cancelled job leaves behind is measured.
+
+use std::sync::mpsc;
+
+use halo2_proofs::{circuit::Value, pasta::Fp};
+use permutation_benchmark::{
+    BenchmarkError, JobPhase, JobProgress, NativePermutation, OutputMode, PeakAllocator, PoseidonCircuit, ProofRequest, ProverJob, PublicInputs, RescueCircuit,
+    live_heap, poseidon_params, rescue_params
+};
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+// heap a cancelled job may leave live for the standard library's own bookkeeping of the thread and channel; its keys
+// and traces take megabytes
+const CANCEL_SLACK: usize = 64 << 10;
+
+// a Poseidon job runs through every phase in order to a proof that verifies, and a Rescue-Prime one cancelled halfway
+// through its keygen rounds stops at the next round with BenchmarkError::Cancelled and leaves no more heap live than
+// before it started, give or take CANCEL_SLACK
+#[test]
+fn prover_jobs() {
+    let (k, seed) = (10, Some(0));
+    let inputs = [0, 1, 2].map(Fp::from);
+    let output_mode = OutputMode::DigestOnly;
+
+    let (sender, phases) = mpsc::channel();
+    let request = ProofRequest {
+        label: "Poseidon digest".to_string(),
+        circuit: PoseidonCircuit::new(inputs.map(Value::known), output_mode).unwrap(),
+        public_inputs: PublicInputs::from_output(output_mode, &poseidon_params().unwrap().permute_native(inputs)),
+        k,
+        seed
+    };
+    let job = ProverJob::spawn_with(request, move |progress| {
+        let _ = sender.send(progress.phase);
+    });
+    let artifact = job.join().unwrap();
+    let mut phases: Vec<JobPhase> = phases.into_iter().collect();
+    phases.dedup();
+    assert_eq!(phases, [JobPhase::Layout, JobPhase::Keygen, JobPhase::Proving, JobPhase::Verifying, JobPhase::Done]);
+    assert!(!artifact.proof.is_empty());
+
+    // the worker blocks at every checkpoint until the progress is taken, so the job is cancelled at a known round
+    let before = live_heap().expect("PeakAllocator is the global allocator");
+    let (sender, progress) = mpsc::sync_channel(0);
+    let request = ProofRequest {
+        label: "Rescue-Prime digest".to_string(),
+        circuit: RescueCircuit::new(inputs.map(Value::known), output_mode).unwrap(),
+        public_inputs: PublicInputs::from_output(output_mode, &rescue_params().unwrap().permute_native(inputs)),
+        k,
+        seed
+    };
+    let job = ProverJob::spawn_with(request, move |progress| {
+        let _ = sender.send(progress);
+    });
+    let halfway = progress.iter()
+        .find(|progress: &JobProgress| progress.phase == JobPhase::Keygen && progress.percent >= Some(50))
+        .expect("the keygen rounds are reported");
+    job.cancel();
+    drop(progress);
+    let result = job.join();
+    assert!(matches!(result, Err(BenchmarkError::Cancelled(_))), "a job cancelled at {} returned {:?} instead of Cancelled", halfway, result.map(|artifact| artifact.label));
+    let after = live_heap().unwrap();
+    assert!(after <= before + CANCEL_SLACK, "a cancelled job left {} more heap bytes live", after.saturating_sub(before));
+}
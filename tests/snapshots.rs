@@ -0,0 +1,32 @@
+// The report format and the pinned verifying keys against the golden files in snapshots/, committed with the source
+// so that format and circuit changes show up in review. After reviewing an intended change, rewrite the files with
+// `BLESS=1 cargo test --test snapshots`.
+
+use std::path::PathBuf;
+
+use permutation_benchmark::render_snapshots;
+
+#[test]
+fn snapshots_match() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+    let bless = std::env::var_os("BLESS").is_some();
+    let mut changed = Vec::new();
+    for (file, rendered) in render_snapshots().unwrap() {
+        let path = dir.join(file);
+        if bless {
+            std::fs::write(&path, rendered).unwrap();
+            continue;
+        }
+
+        let golden = std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+        if golden != rendered {
+            changed.push(file);
+        }
+    }
+
+    assert!(
+        changed.is_empty(),
+        "the report format or the circuits differ from the snapshots {:?}; if the change is intended, review it and run `BLESS=1 cargo test --test snapshots`",
+        changed
+    );
+}
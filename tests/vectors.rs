@@ -0,0 +1,27 @@
+// Every file in tests/data against the crate: the test vectors and the trace fixture regenerate byte for byte and
+// verify in the circuits, the circomlib digests match the BN254 preset, and neptune's match the constants-after-S-box
+// form of the neptune preset. A data file without a check here fails, so a new fixture cannot be committed unchecked.
+
+use std::path::{Path, PathBuf};
+
+use permutation_benchmark::{BenchmarkError, check_circom_vectors, check_neptune_vectors, check_trace_fixture, check_vectors};
+
+#[test]
+fn data_files() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data");
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    files.sort();
+    assert!(!files.is_empty(), "no data files in {}", dir.display());
+
+    for path in files {
+        let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let check: fn(&Path) -> Result<(), BenchmarkError> = match file_name.as_str() {
+            "vectors.json" => check_vectors,
+            "trace.json" => check_trace_fixture,
+            "circomlib_poseidon.json" => check_circom_vectors,
+            "neptune_poseidon.json" => check_neptune_vectors,
+            _ => panic!("tests/data/{} has no check in tests/vectors.rs", file_name)
+        };
+        check(&path).unwrap_or_else(|error| panic!("tests/data/{}: {}", file_name, error));
+    }
+}
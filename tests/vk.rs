@@ -0,0 +1,34 @@
+// The verifying keys of both permutation circuits over Pasta at k = 10, as the real proofs generate them: two keygens
+// of the same circuit agree, and the pinned key is the one committed in snapshots/, so a layout refactor that should
+// leave the circuit unchanged fails here if the key changes.
+
+use std::path::PathBuf;
+
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{Circuit, keygen_vk},
+    poly::commitment::Params,
+};
+use permutation_benchmark::{PoseidonCircuit, RescueCircuit};
+
+// helper function to run keygen twice on the witness-free circuit and compare the pinned keys with each other and with
+// the snapshot
+fn assert_vk_stable<C: Circuit<Fp> + Default>(label: &str, snapshot: &str) {
+    let params = Params::<EqAffine>::new(10);
+    let [first, second] = [(); 2].map(|()| format!("{:#?}\n", keygen_vk(&params, &C::default()).unwrap().pinned()));
+    assert!(first == second, "two keygens of the {} circuit give different verifying keys", label);
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("snapshots").join(snapshot);
+    let golden = std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    assert!(first == golden, "the {} verifying key differs from {}", label, path.display());
+}
+
+#[test]
+fn poseidon_vk_is_stable() {
+    assert_vk_stable::<PoseidonCircuit<Fp>>("Poseidon", "poseidon_vk.txt");
+}
+
+#[test]
+fn rescue_vk_is_stable() {
+    assert_vk_stable::<RescueCircuit<Fp>>("Rescue-Prime", "rescue_vk.txt");
+}